@@ -0,0 +1,278 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{Error, Result};
+
+/// Counter set of the CPU-Measurement Counter Facility
+///
+/// The discriminant is the bit of the counter set in the authorization mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u32)]
+pub enum CounterSet {
+    /// Extended counter set; the counters are machine-type specific
+    Extended = 0x01,
+    /// Basic counter set
+    Basic = 0x02,
+    /// Problem-state counter set
+    ProblemState = 0x04,
+    /// Crypto-activity counter set
+    Crypto = 0x08,
+    /// MT-diagnostic counter set
+    MtDiag = 0x20,
+}
+
+impl CounterSet {
+    /// All counter sets.
+    pub const ALL: [Self; 5] = [
+        Self::Basic,
+        Self::ProblemState,
+        Self::Crypto,
+        Self::Extended,
+        Self::MtDiag,
+    ];
+
+    /// Bit of the counter set in the authorization mask.
+    pub const fn mask(self) -> u32 {
+        self as u32
+    }
+
+    /// Determine the counter set of counter `number`.
+    ///
+    /// `cfvn` and `csvn` are the first and second version number of the counter facility. Returns
+    /// `None` if the counter is not defined for these versions.
+    pub const fn of(number: u16, cfvn: u16, csvn: u16) -> Option<Self> {
+        // cfvn governs the basic and problem-state counter sets
+        match (cfvn, number) {
+            (1 | 3, 0..=31) => return Some(Self::Basic),
+            (1, 32..=37) | (3, 32..=33) => return Some(Self::ProblemState),
+            _ => (),
+        }
+        // csvn governs the crypto-activity, extended, and MT-diagnostic counter sets
+        match (csvn, number) {
+            (1..=5, 64..=79) | (6..=8, 64..=83) => return Some(Self::Crypto),
+            (1, 128..=159) | (2, 128..=175) | (3..=5, 128..=255) | (6..=8, 128..=287) => {
+                return Some(Self::Extended)
+            }
+            _ => (),
+        }
+        match (csvn, number) {
+            (3.., 448..=495) => Some(Self::MtDiag),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CounterSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Extended => "Extended",
+            Self::Basic => "Basic",
+            Self::ProblemState => "Problem-State",
+            Self::Crypto => "Crypto-Activity",
+            Self::MtDiag => "MT-diagnostic",
+        })
+    }
+}
+
+macro_rules! counters {
+    ($($(#[$doc:meta])* $variant:ident = $nr:literal, $name:literal;)+) => {
+        /// Counter of the basic, problem-state, or crypto-activity counter set
+        ///
+        /// These counters are defined by the architecture for all machines of a counter facility
+        /// version. The names are the event names of the `cpum_cf` PMU.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[non_exhaustive]
+        pub enum Counter {
+            $($(#[$doc])* $variant,)+
+        }
+
+        impl Counter {
+            /// All architected counters.
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)+];
+
+            /// Counter number, which is the `config` value of the perf event.
+            pub const fn number(self) -> u16 {
+                match self {
+                    $(Self::$variant => $nr,)+
+                }
+            }
+
+            /// Event name of the counter.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)+
+                }
+            }
+        }
+    };
+}
+
+counters! {
+    /// Cycle count
+    CpuCycles = 0, "CPU_CYCLES";
+    /// Instruction count
+    Instructions = 1, "INSTRUCTIONS";
+    /// Level-1 I-cache directory write count
+    L1IDirWrites = 2, "L1I_DIR_WRITES";
+    /// Level-1 I-cache penalty cycle count
+    L1IPenaltyCycles = 3, "L1I_PENALTY_CYCLES";
+    /// Level-1 D-cache directory write count
+    L1DDirWrites = 4, "L1D_DIR_WRITES";
+    /// Level-1 D-cache penalty cycle count
+    L1DPenaltyCycles = 5, "L1D_PENALTY_CYCLES";
+    /// Problem-state cycle count
+    ProblemStateCpuCycles = 32, "PROBLEM_STATE_CPU_CYCLES";
+    /// Problem-state instruction count
+    ProblemStateInstructions = 33, "PROBLEM_STATE_INSTRUCTIONS";
+    /// Problem-state level-1 I-cache directory write count (counter facility version 1)
+    ProblemStateL1IDirWrites = 34, "PROBLEM_STATE_L1I_DIR_WRITES";
+    /// Problem-state level-1 I-cache penalty cycle count (counter facility version 1)
+    ProblemStateL1IPenaltyCycles = 35, "PROBLEM_STATE_L1I_PENALTY_CYCLES";
+    /// Problem-state level-1 D-cache directory write count (counter facility version 1)
+    ProblemStateL1DDirWrites = 36, "PROBLEM_STATE_L1D_DIR_WRITES";
+    /// Problem-state level-1 D-cache penalty cycle count (counter facility version 1)
+    ProblemStateL1DPenaltyCycles = 37, "PROBLEM_STATE_L1D_PENALTY_CYCLES";
+    /// PRNG function count
+    PrngFunctions = 64, "PRNG_FUNCTIONS";
+    /// PRNG cycle count
+    PrngCycles = 65, "PRNG_CYCLES";
+    /// PRNG blocked function count
+    PrngBlockedFunctions = 66, "PRNG_BLOCKED_FUNCTIONS";
+    /// PRNG blocked cycle count
+    PrngBlockedCycles = 67, "PRNG_BLOCKED_CYCLES";
+    /// SHA function count
+    ShaFunctions = 68, "SHA_FUNCTIONS";
+    /// SHA cycle count
+    ShaCycles = 69, "SHA_CYCLES";
+    /// SHA blocked function count
+    ShaBlockedFunctions = 70, "SHA_BLOCKED_FUNCTIONS";
+    /// SHA blocked cycle count
+    ShaBlockedCycles = 71, "SHA_BLOCKED_CYCLES";
+    /// DEA function count
+    DeaFunctions = 72, "DEA_FUNCTIONS";
+    /// DEA cycle count
+    DeaCycles = 73, "DEA_CYCLES";
+    /// DEA blocked function count
+    DeaBlockedFunctions = 74, "DEA_BLOCKED_FUNCTIONS";
+    /// DEA blocked cycle count
+    DeaBlockedCycles = 75, "DEA_BLOCKED_CYCLES";
+    /// AES function count
+    AesFunctions = 76, "AES_FUNCTIONS";
+    /// AES cycle count
+    AesCycles = 77, "AES_CYCLES";
+    /// AES blocked function count
+    AesBlockedFunctions = 78, "AES_BLOCKED_FUNCTIONS";
+    /// AES blocked cycle count
+    AesBlockedCycles = 79, "AES_BLOCKED_CYCLES";
+    /// ECC function count (counter set version 6 or later)
+    EccFunctionCount = 80, "ECC_FUNCTION_COUNT";
+    /// ECC cycle count (counter set version 6 or later)
+    EccCyclesCount = 81, "ECC_CYCLES_COUNT";
+    /// ECC blocked function count (counter set version 6 or later)
+    EccBlockedFunctionCount = 82, "ECC_BLOCKED_FUNCTION_COUNT";
+    /// ECC blocked cycle count (counter set version 6 or later)
+    EccBlockedCyclesCount = 83, "ECC_BLOCKED_CYCLES_COUNT";
+}
+
+impl Counter {
+    /// Counter set of the counter.
+    pub const fn set(self) -> CounterSet {
+        match self.number() {
+            0..=31 => CounterSet::Basic,
+            32..=63 => CounterSet::ProblemState,
+            _ => CounterSet::Crypto,
+        }
+    }
+
+    /// Whether the counter is defined for the counter facility versions `cfvn` and `csvn`.
+    pub const fn is_defined(self, cfvn: u16, csvn: u16) -> bool {
+        matches!(CounterSet::of(self.number(), cfvn, csvn), Some(set) if set as u32 == self.set() as u32)
+    }
+}
+
+impl Display for Counter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Counter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|c| c.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| Error::UnknownCounter(s.to_string()))
+    }
+}
+
+/// Definition of a counter of the running machine
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CounterDef {
+    /// Counter number, which is the `config` value of the perf event
+    pub number: u16,
+    /// Event name of the counter
+    pub name: String,
+    /// Counter set of the counter
+    pub set: CounterSet,
+}
+
+impl CounterDef {
+    /// The typed architected counter, if the counter is one.
+    pub fn counter(&self) -> Option<Counter> {
+        Counter::ALL
+            .iter()
+            .find(|c| c.number() == self.number)
+            .copied()
+    }
+}
+
+impl From<Counter> for CounterDef {
+    fn from(value: Counter) -> Self {
+        Self {
+            number: value.number(),
+            name: value.name().to_string(),
+            set: value.set(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counter_set() {
+        assert_eq!(CounterSet::of(0, 1, 1), Some(CounterSet::Basic));
+        assert_eq!(CounterSet::of(37, 1, 1), Some(CounterSet::ProblemState));
+        assert_eq!(CounterSet::of(37, 3, 1), None);
+        assert_eq!(CounterSet::of(79, 3, 5), Some(CounterSet::Crypto));
+        assert_eq!(CounterSet::of(83, 3, 5), None);
+        assert_eq!(CounterSet::of(83, 3, 6), Some(CounterSet::Crypto));
+        assert_eq!(CounterSet::of(160, 3, 1), None);
+        assert_eq!(CounterSet::of(175, 3, 2), Some(CounterSet::Extended));
+        assert_eq!(CounterSet::of(287, 3, 8), Some(CounterSet::Extended));
+        assert_eq!(CounterSet::of(448, 3, 2), None);
+        assert_eq!(CounterSet::of(448, 3, 8), Some(CounterSet::MtDiag));
+        assert_eq!(CounterSet::of(0, 2, 8), None);
+    }
+
+    #[test]
+    fn counter() {
+        for c in Counter::ALL {
+            assert_eq!(c.name().parse::<Counter>().unwrap(), *c);
+            assert!(c.is_defined(1, 6), "{c}");
+        }
+        assert_eq!("cpu_cycles".parse::<Counter>().unwrap(), Counter::CpuCycles);
+        assert!("CPU_CYCLE".parse::<Counter>().is_err());
+        assert!(Counter::ProblemStateInstructions.is_defined(3, 1));
+        assert!(!Counter::ProblemStateL1DDirWrites.is_defined(3, 1));
+        assert!(!Counter::EccCyclesCount.is_defined(3, 5));
+        assert_eq!(Counter::AesCycles.set(), CounterSet::Crypto);
+    }
+}
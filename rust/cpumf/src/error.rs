@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::path::PathBuf;
+
+/// Result type for this crate
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error cases for this crate
+#[allow(missing_docs)]
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Cannot read `{path}`")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Cannot parse `{path}`: {msg}")]
+    Parse { path: PathBuf, msg: String },
+
+    #[error("The CPU-Measurement Counter Facility is not available")]
+    NoCounterFacility,
+
+    #[error("Unknown counter '{0}'")]
+    UnknownCounter(String),
+
+    #[error("Cannot open counter {counter} with perf_event_open")]
+    PerfOpen {
+        counter: u64,
+        source: std::io::Error,
+    },
+
+    #[error("Cannot {op} counter {counter}")]
+    Perf {
+        op: &'static str,
+        counter: u64,
+        source: std::io::Error,
+    },
+}
+
+impl Error {
+    pub(crate) fn read<P: Into<PathBuf>>(path: P, source: std::io::Error) -> Self {
+        Self::Read {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn parse<P: Into<PathBuf>, S: Into<String>>(path: P, msg: S) -> Self {
+        Self::Parse {
+            path: path.into(),
+            msg: msg.into(),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{fmt::Display, path::Path};
+
+use crate::{Counter, CounterSet, Error, Result};
+
+const SERVICE_LEVELS: &str = "/proc/service_levels";
+const SYSINFO: &str = "/proc/sysinfo";
+const CF_PREFIX: &str = "CPU-MF: Counter facility:";
+
+/// Counter sets authorized in the LPAR activation profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Authorization(u32);
+
+impl Authorization {
+    /// Authorization from the raw mask.
+    pub const fn from_mask(mask: u32) -> Self {
+        Self(mask)
+    }
+
+    /// The raw mask.
+    pub const fn mask(self) -> u32 {
+        self.0
+    }
+
+    /// Whether `set` is authorized.
+    pub const fn contains(self, set: CounterSet) -> bool {
+        self.0 & set.mask() != 0
+    }
+
+    /// The authorized counter sets.
+    pub fn sets(self) -> impl Iterator<Item = CounterSet> {
+        CounterSet::ALL
+            .into_iter()
+            .filter(move |s| self.contains(*s))
+    }
+}
+
+impl Display for Authorization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sets: Vec<_> = self.sets().map(|s| s.to_string()).collect();
+        match sets.is_empty() {
+            true => write!(f, "none"),
+            false => write!(f, "{}", sets.join(", ")),
+        }
+    }
+}
+
+/// Version and authorization state of the CPU-Measurement Counter Facility
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CounterFacility {
+    /// First version number; governs the basic and problem-state counter sets
+    pub cfvn: u16,
+    /// Second version number; governs the crypto-activity, extended, and MT-diagnostic counter
+    /// sets
+    pub csvn: u16,
+    /// Authorized counter sets
+    pub authorization: Authorization,
+}
+
+impl CounterFacility {
+    /// Read the counter facility information from `/proc/service_levels`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoCounterFacility`] if the counter facility is not available, for example
+    /// because the `cpum_cf` kernel support is missing.
+    pub fn read() -> Result<Self> {
+        Self::read_from(SERVICE_LEVELS)
+    }
+
+    /// Read the counter facility information from a file in `/proc/service_levels` format.
+    pub fn read_from<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let levels = std::fs::read_to_string(path).map_err(|e| Error::read(path, e))?;
+        Self::parse(&levels).map_err(|e| match e {
+            Some(msg) => Error::parse(path, msg),
+            None => Error::NoCounterFacility,
+        })
+    }
+
+    /// Parse the counter facility line, e.g.
+    /// `CPU-MF: Counter facility: version=3.8 authorization=002f`.
+    fn parse(levels: &str) -> Result<Self, Option<String>> {
+        let line = levels
+            .lines()
+            .find_map(|l| l.strip_prefix(CF_PREFIX))
+            .ok_or(None)?;
+        let err = || {
+            Some(format!(
+                "Unexpected counter facility line '{CF_PREFIX}{line}'"
+            ))
+        };
+        let mut version = None;
+        let mut auth = None;
+        for field in line.split_whitespace() {
+            match field.split_once('=') {
+                Some(("version", v)) => version = v.split_once('.'),
+                Some(("authorization", a)) => auth = u32::from_str_radix(a, 16).ok(),
+                _ => (),
+            }
+        }
+        let ((cfvn, csvn), auth) = version.zip(auth).ok_or_else(err)?;
+        Ok(Self {
+            cfvn: cfvn.parse().map_err(|_| err())?,
+            csvn: csvn.parse().map_err(|_| err())?,
+            authorization: Authorization(auth),
+        })
+    }
+
+    /// The counter set of counter `number`, if it is defined for this facility.
+    pub const fn counter_set(&self, number: u16) -> Option<CounterSet> {
+        CounterSet::of(number, self.cfvn, self.csvn)
+    }
+
+    /// Whether `counter` is defined for this facility and its counter set is authorized.
+    pub const fn is_available(&self, counter: Counter) -> bool {
+        counter.is_defined(self.cfvn, self.csvn) && self.authorization.contains(counter.set())
+    }
+
+    /// The architected counters defined for this facility, authorized or not.
+    pub fn counters(&self) -> impl Iterator<Item = Counter> + '_ {
+        Counter::ALL
+            .iter()
+            .copied()
+            .filter(|c| c.is_defined(self.cfvn, self.csvn))
+    }
+}
+
+/// IBM Z machine type
+///
+/// The extended and MT-diagnostic counters are specific to the machine type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum MachineType {
+    /// IBM z10 EC and BC (2097, 2098)
+    Z10,
+    /// IBM z196 and z114 (2817, 2818)
+    Z196,
+    /// IBM zEC12 and zBC12 (2827, 2828)
+    ZEc12,
+    /// IBM z13 and z13s (2964, 2965)
+    Z13,
+    /// IBM z14 (3906, 3907)
+    Z14,
+    /// IBM z15 (8561, 8562)
+    Z15,
+    /// IBM z16 (3931, 3932)
+    Z16,
+    /// IBM z17 (9175, 9176)
+    Z17,
+    /// Machine type not known to this crate
+    Other(u16),
+}
+
+impl MachineType {
+    /// Machine generation of the machine type `ty`, e.g. 3931.
+    pub const fn from_type(ty: u16) -> Self {
+        match ty {
+            2097 | 2098 => Self::Z10,
+            2817 | 2818 => Self::Z196,
+            2827 | 2828 => Self::ZEc12,
+            2964 | 2965 => Self::Z13,
+            3906 | 3907 => Self::Z14,
+            8561 | 8562 => Self::Z15,
+            3931 | 3932 => Self::Z16,
+            9175 | 9176 => Self::Z17,
+            ty => Self::Other(ty),
+        }
+    }
+
+    /// Read the machine type of the running machine from `/proc/sysinfo`.
+    pub fn read() -> Result<Self> {
+        let sysinfo = std::fs::read_to_string(SYSINFO).map_err(|e| Error::read(SYSINFO, e))?;
+        Self::parse(&sysinfo).ok_or_else(|| Error::parse(SYSINFO, "No machine type"))
+    }
+
+    fn parse(sysinfo: &str) -> Option<Self> {
+        sysinfo
+            .lines()
+            .find_map(|l| l.strip_prefix("Type:"))
+            .and_then(|ty| ty.trim().parse().ok())
+            .map(Self::from_type)
+    }
+}
+
+impl Display for MachineType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Z10 => write!(f, "z10"),
+            Self::Z196 => write!(f, "z196"),
+            Self::ZEc12 => write!(f, "zEC12"),
+            Self::Z13 => write!(f, "z13"),
+            Self::Z14 => write!(f, "z14"),
+            Self::Z15 => write!(f, "z15"),
+            Self::Z16 => write!(f, "z16"),
+            Self::Z17 => write!(f, "z17"),
+            Self::Other(ty) => write!(f, "{ty}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_facility() {
+        let levels = "VM: z/VM Version 7 Release 3\n\
+                      CPU-MF: Counter facility: version=3.8 authorization=002f\n\
+                      CPU-MF: Sampling facility: min_rate=22 max_rate=176000 cpu_speed=5200\n";
+        let cf = CounterFacility::parse(levels).unwrap();
+        assert_eq!((cf.cfvn, cf.csvn), (3, 8));
+        assert_eq!(cf.authorization.mask(), 0x2f);
+        assert_eq!(cf.authorization.sets().collect::<Vec<_>>(), CounterSet::ALL);
+        assert!(cf.is_available(Counter::EccFunctionCount));
+        assert!(!cf.is_available(Counter::ProblemStateL1IDirWrites));
+        assert_eq!(cf.counters().count(), 28);
+        assert_eq!(cf.counter_set(288), None);
+
+        let cf =
+            CounterFacility::parse("CPU-MF: Counter facility: version=1.2 authorization=0002\n")
+                .unwrap();
+        assert_eq!(cf.authorization.to_string(), "Basic");
+        assert!(cf.is_available(Counter::CpuCycles));
+        assert!(!cf.is_available(Counter::AesCycles));
+
+        assert_eq!(CounterFacility::parse("VM: z/VM\n"), Err(None));
+        assert!(
+            CounterFacility::parse("CPU-MF: Counter facility: version=3\n")
+                .unwrap_err()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn parse_machine_type() {
+        let sysinfo = "Manufacturer:         IBM\nType:                 3931\nModel: 703 A01\n";
+        assert_eq!(MachineType::parse(sysinfo), Some(MachineType::Z16));
+        assert_eq!(
+            MachineType::parse("Type: 1234\n"),
+            Some(MachineType::Other(1234))
+        );
+        assert_eq!(MachineType::parse("Manufacturer: IBM\n"), None);
+        assert_eq!(MachineType::Z16.to_string(), "z16");
+    }
+}
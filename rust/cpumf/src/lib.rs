@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+#![doc = include_str!("../README.md")]
+mod counter;
+mod error;
+mod facility;
+mod perf;
+mod pmu;
+
+pub use counter::{Counter, CounterDef, CounterSet};
+pub use error::{Error, Result};
+pub use facility::{Authorization, CounterFacility, MachineType};
+pub use perf::{CounterReader, Target};
+pub use pmu::Pmu;
@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    mem::size_of,
+    os::fd::{AsRawFd, FromRawFd},
+};
+
+use libc::{c_int, c_ulong, pid_t};
+
+use crate::{Error, Pmu, Result};
+
+/// `struct perf_event_attr` up to `config1` (`PERF_ATTR_SIZE_VER0`)
+#[repr(C)]
+#[derive(Debug, Default)]
+struct PerfEventAttr {
+    ty: u32,
+    size: u32,
+    config: u64,
+    sample_period: u64,
+    sample_type: u64,
+    read_format: u64,
+    flags: u64,
+    wakeup_events: u32,
+    bp_type: u32,
+    config1: u64,
+}
+const PERF_ATTR_SIZE_VER0: u32 = 64;
+const _: () = assert!(size_of::<PerfEventAttr>() == PERF_ATTR_SIZE_VER0 as usize);
+
+/// `perf_event_attr.disabled`
+const ATTR_FLAG_DISABLED: u64 = 1 << 0;
+
+/// `PERF_FLAG_FD_CLOEXEC`
+const PERF_FLAG_FD_CLOEXEC: c_ulong = 1 << 3;
+
+const PERF_EVENT_IOC_ENABLE: c_ulong = 0x2400;
+const PERF_EVENT_IOC_DISABLE: c_ulong = 0x2401;
+const PERF_EVENT_IOC_RESET: c_ulong = 0x2403;
+
+/// What a [`CounterReader`] counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// All processes on the CPU with this number
+    Cpu(u32),
+    /// The process with this PID on any CPU; 0 is the calling process
+    Process(pid_t),
+}
+
+/// Counts one CPU-MF counter with `perf_event_open(2)`
+///
+/// The counter is opened disabled; start counting with [`CounterReader::enable`].
+#[derive(Debug)]
+pub struct CounterReader {
+    counter: u64,
+    file: File,
+}
+
+impl CounterReader {
+    /// Open counter `number` of `pmu` for `target`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the counter set is not authorized, the counter is not defined, or the caller
+    /// lacks the permission, see `/proc/sys/kernel/perf_event_paranoid`.
+    pub fn open(pmu: &Pmu, number: u16, target: Target) -> Result<Self> {
+        let counter = number.into();
+        let attr = PerfEventAttr {
+            ty: pmu.perf_type(),
+            size: PERF_ATTR_SIZE_VER0,
+            config: counter,
+            flags: ATTR_FLAG_DISABLED,
+            ..Default::default()
+        };
+        let (pid, cpu): (pid_t, c_int) = match target {
+            Target::Cpu(cpu) => (
+                -1,
+                cpu.try_into().map_err(|_| Error::PerfOpen {
+                    counter,
+                    source: io::Error::from_raw_os_error(libc::EINVAL),
+                })?,
+            ),
+            Target::Process(pid) => (pid, -1),
+        };
+        let group_fd: c_int = -1;
+        // SAFETY: attr is a valid perf_event_attr of the specified size that lives for the
+        // duration of the call.
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                pid,
+                cpu,
+                group_fd,
+                PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(Error::PerfOpen {
+                counter,
+                source: io::Error::last_os_error(),
+            });
+        }
+        // SAFETY: fd is a new file descriptor owned by nobody else.
+        let file = unsafe { File::from_raw_fd(fd as c_int) };
+        Ok(Self { counter, file })
+    }
+
+    fn ioctl(&self, op: &'static str, cmd: c_ulong) -> Result<()> {
+        // SAFETY: The perf event ioctls without argument do not access memory.
+        match unsafe { libc::ioctl(self.file.as_raw_fd(), cmd, 0) } {
+            0 => Ok(()),
+            _ => Err(Error::Perf {
+                op,
+                counter: self.counter,
+                source: io::Error::last_os_error(),
+            }),
+        }
+    }
+
+    /// Start counting.
+    pub fn enable(&self) -> Result<()> {
+        self.ioctl("enable", PERF_EVENT_IOC_ENABLE)
+    }
+
+    /// Stop counting.
+    pub fn disable(&self) -> Result<()> {
+        self.ioctl("disable", PERF_EVENT_IOC_DISABLE)
+    }
+
+    /// Set the count to zero.
+    pub fn reset(&self) -> Result<()> {
+        self.ioctl("reset", PERF_EVENT_IOC_RESET)
+    }
+
+    /// Read the current count.
+    pub fn read(&mut self) -> Result<u64> {
+        let mut buf = [0u8; 8];
+        self.file
+            .read_exact(&mut buf)
+            .map_err(|source| Error::Perf {
+                op: "read",
+                counter: self.counter,
+                source,
+            })?;
+        Ok(u64::from_ne_bytes(buf))
+    }
+}
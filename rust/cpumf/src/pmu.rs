@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::path::{Path, PathBuf};
+
+use crate::{CounterDef, CounterFacility, Error, Result};
+
+const CPUM_CF: &str = "/sys/bus/event_source/devices/cpum_cf";
+
+/// The `cpum_cf` perf PMU of the Linux kernel
+///
+/// The kernel lists the counters of the running machine, including the machine-type specific
+/// extended and MT-diagnostic counters, as events of the PMU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pmu {
+    dir: PathBuf,
+    ty: u32,
+}
+
+/// Parse an event definition, e.g. `event=0x0080`.
+fn parse_event(def: &str) -> Option<u16> {
+    let nr = def.trim().strip_prefix("event=")?;
+    match nr.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => nr.parse().ok(),
+    }
+}
+
+impl Pmu {
+    /// Open the `cpum_cf` PMU in sysfs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoCounterFacility`] if the kernel does not provide the PMU.
+    pub fn open() -> Result<Self> {
+        match Path::new(CPUM_CF).exists() {
+            true => Self::from_dir(CPUM_CF),
+            false => Err(Error::NoCounterFacility),
+        }
+    }
+
+    /// Open the PMU in the sysfs directory `dir`.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let path = dir.join("type");
+        let ty = std::fs::read_to_string(&path).map_err(|e| Error::read(&path, e))?;
+        let ty = ty
+            .trim()
+            .parse()
+            .map_err(|_| Error::parse(&path, format!("Invalid PMU type '{}'", ty.trim())))?;
+        Ok(Self { dir, ty })
+    }
+
+    /// The perf event type of the PMU.
+    pub const fn perf_type(&self) -> u32 {
+        self.ty
+    }
+
+    /// The counters of the running machine that are defined for `facility`, ordered by number.
+    ///
+    /// Lists the counters of all counter sets, authorized or not.
+    pub fn counters(&self, facility: &CounterFacility) -> Result<Vec<CounterDef>> {
+        let dir = self.dir.join("events");
+        let mut counters = vec![];
+        for entry in std::fs::read_dir(&dir).map_err(|e| Error::read(&dir, e))? {
+            let entry = entry.map_err(|e| Error::read(&dir, e))?;
+            let path = entry.path();
+            let def = std::fs::read_to_string(&path).map_err(|e| Error::read(&path, e))?;
+            let number = parse_event(&def)
+                .ok_or_else(|| Error::parse(&path, format!("Invalid event '{}'", def.trim())))?;
+            if let Some(set) = facility.counter_set(number) {
+                counters.push(CounterDef {
+                    number,
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    set,
+                });
+            }
+        }
+        counters.sort();
+        Ok(counters)
+    }
+
+    /// The counter with the event name `name` (case-insensitive).
+    pub fn counter(&self, facility: &CounterFacility, name: &str) -> Result<CounterDef> {
+        self.counters(facility)?
+            .into_iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::UnknownCounter(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::{Authorization, Counter, CounterSet};
+
+    #[test]
+    fn parse_event() {
+        assert_eq!(super::parse_event("event=0x0080\n"), Some(128));
+        assert_eq!(super::parse_event("event=33"), Some(33));
+        assert_eq!(super::parse_event("config=1"), None);
+    }
+
+    #[test]
+    fn counters() {
+        let dir = std::env::temp_dir().join(format!("cpumf-pmu-{}", std::process::id()));
+        let events = dir.join("events");
+        fs::create_dir_all(&events).unwrap();
+        fs::write(dir.join("type"), "10\n").unwrap();
+        for (name, nr) in [
+            ("CPU_CYCLES", 0),
+            ("PROBLEM_STATE_INSTRUCTIONS", 33),
+            ("L1D_RO_EXCL_WRITES", 128),
+            ("MT_DIAG_CYCLES_ONE_THR_ACTIVE", 448),
+        ] {
+            fs::write(events.join(name), format!("event=0x{nr:04x}\n")).unwrap();
+        }
+        let facility = CounterFacility {
+            cfvn: 3,
+            csvn: 2,
+            authorization: Authorization::from_mask(0x2f),
+        };
+
+        let pmu = Pmu::from_dir(&dir).unwrap();
+        assert_eq!(pmu.perf_type(), 10);
+        let counters = pmu.counters(&facility).unwrap();
+        let names: Vec<_> = counters.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "CPU_CYCLES",
+                "PROBLEM_STATE_INSTRUCTIONS",
+                "L1D_RO_EXCL_WRITES"
+            ]
+        );
+        assert_eq!(counters[0].counter(), Some(Counter::CpuCycles));
+        assert_eq!(counters[2].counter(), None);
+        let c = pmu.counter(&facility, "l1d_ro_excl_writes").unwrap();
+        assert_eq!((c.number, c.set), (128, CounterSet::Extended));
+        assert!(pmu
+            .counter(&facility, "MT_DIAG_CYCLES_ONE_THR_ACTIVE")
+            .is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Add-secret request files as consumed by `pvsecret add` and `pvsecret verify`.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pv::{
+    secret::{verify_asrcb_and_get_user_data, AddSecretRequest},
+    uv::AddCmd,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = AddCmd::from_vec(data.to_vec());
+    let _ = AddSecretRequest::bin_id(data);
+    let _ = AddSecretRequest::bin_tag(data);
+    let _ = verify_asrcb_and_get_user_data(data.to_vec(), None);
+});
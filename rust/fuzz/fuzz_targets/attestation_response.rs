@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Attestation requests and the additional data of an attestation response.
+//!
+//! The first eight bytes of the input are interpreted as attestation flags, the rest as the
+//! additional data the flags describe.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pv::attest::{AdditionalData, AttestationFlags, AttestationRequest};
+use zerocopy::FromBytes;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = AttestationRequest::auth_bin(data);
+
+    let Ok((flags, additional)) = AttestationFlags::read_from_prefix(data) else {
+        return;
+    };
+    let _ = AdditionalData::from_slice(additional, &flags);
+});
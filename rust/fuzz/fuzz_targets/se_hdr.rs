@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Secure Execution headers, both standalone and embedded in an SE image.
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use pv::{
+    request::BootHdrTags,
+    se_hdr::{read_se_hdr, SeHdrRef},
+};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(hdr) = SeHdrRef::parse(data) {
+        let _ = hdr.contains_phkh(&[0; 0x20]);
+    }
+    let _ = BootHdrTags::from_se_image(&mut Cursor::new(data));
+    let _ = read_se_hdr(&mut Cursor::new(data));
+});
@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Secret list buffers as returned by the List-Secrets UVC.
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use pv_core::uv::SecretList;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(list) = SecretList::decode(&mut Cursor::new(data)) {
+        let _ = list.to_string();
+        for secret in list.iter() {
+            let _ = secret.to_string();
+        }
+    }
+});
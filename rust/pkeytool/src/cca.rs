@@ -0,0 +1,313 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    ffi::{c_char, c_long, c_void, CStr},
+    ptr,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::debug;
+use pv_core::{misc::read_file_string, pkey::ApqnAddr};
+
+const CCA_LIBRARY_NAME: &[u8] = b"libcsulcca.so\0";
+const CCA_WEB_PAGE: &str = "http://www.ibm.com/security/cryptocards";
+const CCA_DOMAIN_ENVAR: &str = "CSU_DEFAULT_DOMAIN";
+const CCA_ADAPTER_ENVAR: &str = "CSU_DEFAULT_ADAPTER";
+
+/// CCA verb with a variable-length data parameter, e.g. CSUACFQ
+type CcaVerb = unsafe extern "C" fn(
+    return_code: *mut c_long,
+    reason_code: *mut c_long,
+    exit_data_length: *mut c_long,
+    exit_data: *mut u8,
+    rule_array_count: *mut c_long,
+    rule_array: *mut u8,
+    data_length: *mut c_long,
+    data: *mut u8,
+);
+
+/// Key Token Change (CSNBKTC)
+type KeyTokenChange = unsafe extern "C" fn(
+    return_code: *mut c_long,
+    reason_code: *mut c_long,
+    exit_data_length: *mut c_long,
+    exit_data: *mut u8,
+    rule_array_count: *mut c_long,
+    rule_array: *mut u8,
+    key_identifier: *mut u8,
+);
+
+/// Re-enciphering method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// From the old to the current master key
+    OldToCurrent,
+    /// From the current to the new master key
+    CurrentToNew,
+}
+
+impl Method {
+    const fn rule(&self) -> &'static [u8; 8] {
+        match self {
+            Self::OldToCurrent => b"RTCMK   ",
+            Self::CurrentToNew => b"RTNMK   ",
+        }
+    }
+}
+
+/// Fail on a non-zero return code of the CCA verb `name`.
+fn check(name: &str, rc: c_long, rsn: c_long) -> Result<()> {
+    debug!("{name} returned: return_code: {rc}, reason_code: {rsn}");
+    match rc {
+        0 => Ok(()),
+        _ => bail!("CCA {name} failed: return code {rc}, reason code {rsn}"),
+    }
+}
+
+/// Returns the number of adapters in the rule array returned by CSUACFQ 'STATCRD2'.
+fn parse_adapters(rule_array: &[u8]) -> Result<u32> {
+    let s = String::from_utf8_lossy(&rule_array[..8]);
+    s.trim_matches(|c: char| c == '\0' || c.is_whitespace())
+        .parse()
+        .map_err(|_| anyhow!("Unparsable number of CCA adapters: '{s}'"))
+}
+
+/// The IBM CCA host library (libcsulcca.so)
+#[derive(Debug)]
+pub struct CcaLib {
+    handle: *mut c_void,
+    csnbktc: KeyTokenChange,
+    csnbktc2: CcaVerb,
+    csuacfq: CcaVerb,
+    csuacra: CcaVerb,
+    csuacrd: CcaVerb,
+}
+
+impl Drop for CcaLib {
+    fn drop(&mut self) {
+        // SAFETY: handle was returned by dlopen and no symbol of it is used afterwards.
+        unsafe { libc::dlclose(self.handle) };
+    }
+}
+
+/// Returns the last dlerror message.
+fn dlerror() -> String {
+    // SAFETY: dlerror returns NULL or a valid C string.
+    let err = unsafe { libc::dlerror() };
+    match err.is_null() {
+        true => "unknown error".to_string(),
+        // SAFETY: err is a valid C string until the next dl* call.
+        false => unsafe { CStr::from_ptr(err) }
+            .to_string_lossy()
+            .into_owned(),
+    }
+}
+
+impl CcaLib {
+    /// Load the CCA host library.
+    fn load() -> Result<Self> {
+        // SAFETY: CCA_LIBRARY_NAME is NUL-terminated.
+        let handle = unsafe {
+            libc::dlopen(
+                CCA_LIBRARY_NAME.as_ptr() as *const c_char,
+                libc::RTLD_GLOBAL | libc::RTLD_NOW,
+            )
+        };
+        if handle.is_null() {
+            debug!("{}", dlerror());
+            bail!("The command requires the IBM CCA Host Libraries and Tools.\nFor the supported environments and downloads, see: {CCA_WEB_PAGE}");
+        }
+        let sym = |name: &[u8]| -> Result<*mut c_void> {
+            // SAFETY: handle is a valid library handle and name is NUL-terminated.
+            let sym = unsafe { libc::dlsym(handle, name.as_ptr() as *const c_char) };
+            match sym.is_null() {
+                true => Err(anyhow!("{}", dlerror())).with_context(|| {
+                    format!(
+                        "The CCA host library lacks {}",
+                        String::from_utf8_lossy(&name[..name.len() - 1])
+                    )
+                }),
+                false => Ok(sym),
+            }
+        };
+        let syms = [
+            b"CSNBKTC\0".as_slice(),
+            b"CSNBKTC2\0",
+            b"CSUACFQ\0",
+            b"CSUACRA\0",
+            b"CSUACRD\0",
+        ]
+        .map(sym);
+        if let Some(Err(e)) = syms.iter().find(|s| s.is_err()) {
+            let e = anyhow!("{e:#}");
+            // SAFETY: handle was returned by dlopen and none of its symbols is used.
+            unsafe { libc::dlclose(handle) };
+            return Err(e);
+        }
+        let syms = syms.map(|s| s.unwrap());
+        debug!("CCA library 'libcsulcca.so' has been loaded successfully");
+        // SAFETY: The symbols are the CCA verbs with the signatures of the CCA API.
+        unsafe {
+            Ok(Self {
+                handle,
+                csnbktc: std::mem::transmute::<*mut c_void, KeyTokenChange>(syms[0]),
+                csnbktc2: std::mem::transmute::<*mut c_void, CcaVerb>(syms[1]),
+                csuacfq: std::mem::transmute::<*mut c_void, CcaVerb>(syms[2]),
+                csuacra: std::mem::transmute::<*mut c_void, CcaVerb>(syms[3]),
+                csuacrd: std::mem::transmute::<*mut c_void, CcaVerb>(syms[4]),
+            })
+        }
+    }
+
+    /// Call `verb` with `rule_array_count` rule array elements and fail on a non-zero return
+    /// code.
+    fn call(
+        &self,
+        name: &str,
+        verb: CcaVerb,
+        rule_array: &mut [u8],
+        mut rule_array_count: c_long,
+        data_length: &mut c_long,
+        data: *mut u8,
+    ) -> Result<()> {
+        let (mut rc, mut rsn, mut exit_data_len) = (0, 0, 0);
+        let mut exit_data = [0u8; 4];
+        // SAFETY: All buffers are valid for the lengths passed to the verb.
+        unsafe {
+            verb(
+                &mut rc,
+                &mut rsn,
+                &mut exit_data_len,
+                exit_data.as_mut_ptr(),
+                &mut rule_array_count,
+                rule_array.as_mut_ptr(),
+                data_length,
+                data,
+            )
+        };
+        check(name, rc, rsn)
+    }
+
+    /// Query the status of the current adapter (CSUACFQ 'STATCRD2'); returns the rule array.
+    fn statcrd2(&self) -> Result<[u8; 16 * 8]> {
+        // CSUACFQ returns up to 16 rule array elements
+        let mut rule_array = [0u8; 16 * 8];
+        rule_array[..8].copy_from_slice(b"STATCRD2");
+        self.call(
+            "CSUACFQ (Cryptographic Facility Query)",
+            self.csuacfq,
+            &mut rule_array,
+            1,
+            &mut 0,
+            ptr::null_mut(),
+        )?;
+        Ok(rule_array)
+    }
+
+    /// Allocate (or deallocate) the adapter `adapter`, 0 for any adapter.
+    fn resource(&self, allocate: bool, adapter: u32) -> Result<()> {
+        let mut rule_array = *match adapter {
+            0 => b"DEV-ANY ",
+            _ => b"DEVICE  ",
+        };
+        let mut res_name = format!("CRP{adapter:02}").into_bytes();
+        let mut len = res_name.len() as c_long;
+        let (name, verb) = match allocate {
+            true => ("CSUACRA (Cryptographic Resource Allocate)", self.csuacra),
+            false => ("CSUACRD (Cryptographic Resource Deallocate)", self.csuacrd),
+        };
+        self.call(
+            name,
+            verb,
+            &mut rule_array,
+            1,
+            &mut len,
+            res_name.as_mut_ptr(),
+        )
+    }
+
+    /// Load the CCA host library for the APQN `apqn` and allocate its adapter.
+    pub fn select(apqn: ApqnAddr) -> Result<Self> {
+        debug!("Select {apqn} for the CCA host library");
+        let serialnr = read_file_string(
+            format!("/sys/bus/ap/devices/card{:02x}/serialnr", apqn.card),
+            "serial number",
+        )?;
+        let serialnr = serialnr.trim();
+        // The CCA host library reads the domain when it is loaded
+        std::env::set_var(CCA_DOMAIN_ENVAR, apqn.domain.to_string());
+        std::env::remove_var(CCA_ADAPTER_ENVAR);
+        let cca = Self::load()?;
+
+        let adapters = parse_adapters(&cca.statcrd2()?)?;
+        debug!("Number of CCA adapters: {adapters}");
+        // Deallocate any adapter first, in case one is already allocated
+        for adapter in 1..=adapters {
+            let _ = cca.resource(false, adapter);
+        }
+        // Disable the AUTOSELECT option
+        cca.resource(false, 0)?;
+        for adapter in 1..=adapters {
+            cca.resource(true, adapter)?;
+            let rule_array = cca.statcrd2()?;
+            let adapter_serialnr = String::from_utf8_lossy(&rule_array[14 * 8..15 * 8]);
+            if adapter_serialnr.trim_end() == serialnr {
+                debug!("Selected adapter {adapter} (CRP{adapter:02})");
+                return Ok(cca);
+            }
+            cca.resource(false, adapter)?;
+        }
+        bail!("The CCA host library does not know the adapter of APQN {apqn}")
+    }
+
+    /// Re-encipher the key blob `blob` in place.
+    pub fn reencipher(&self, blob: &mut [u8], method: Method, cipher: bool) -> Result<()> {
+        let mut rule_array = [0u8; 16];
+        rule_array[..8].copy_from_slice(method.rule());
+        rule_array[8..].copy_from_slice(b"AES     ");
+        if cipher {
+            let mut len = blob.len() as c_long;
+            return self.call(
+                "CSNBKTC2 (Key Token Change2)",
+                self.csnbktc2,
+                &mut rule_array,
+                2,
+                &mut len,
+                blob.as_mut_ptr(),
+            );
+        }
+        let (mut rc, mut rsn, mut exit_data_len) = (0, 0, 0);
+        let mut exit_data = [0u8; 4];
+        // SAFETY: All buffers are valid; an AES DATA key token has a fixed size.
+        unsafe {
+            (self.csnbktc)(
+                &mut rc,
+                &mut rsn,
+                &mut exit_data_len,
+                exit_data.as_mut_ptr(),
+                &mut 2,
+                rule_array.as_mut_ptr(),
+                blob.as_mut_ptr(),
+            )
+        };
+        check("CSNBKTC (Key Token Change)", rc, rsn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn adapters() {
+        let mut rule_array = [0u8; 16 * 8];
+        rule_array[..8].copy_from_slice(b"       2");
+        assert_eq!(parse_adapters(&rule_array).unwrap(), 2);
+        rule_array[..8].copy_from_slice(b"2\0\0\0\0\0\0\0");
+        assert_eq!(parse_adapters(&rule_array).unwrap(), 2);
+        rule_array[..8].copy_from_slice(b"STATCRD2");
+        assert!(parse_adapters(&rule_array).is_err());
+    }
+}
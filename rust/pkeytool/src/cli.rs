@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use clap::{builder::TypedValueParser, Args, Parser, Subcommand, ValueEnum, ValueHint};
+use utils::VerbosityOptions;
+
+/// Manage secure keys of CCA and EP11 crypto adapters.
+///
+/// Generates, validates, and re-enciphers secure AES keys with the pkey kernel module
+/// (/dev/pkey), and converts them to protected keys, e.g. for dm-crypt with the 'paes' ciphers.
+/// A secure key is wrapped by the master key of a crypto adapter and can only be used with the
+/// APQNs (adapter and domain) that have that master key set.
+#[derive(Parser, Debug)]
+pub struct CliOptions {
+    #[clap(flatten)]
+    pub verbosity: VerbosityOptions,
+
+    /// Print version information and exit.
+    // Implemented for the help message only. Actual parsing happens in the
+    // version command.
+    #[arg(long)]
+    pub version: bool,
+
+    #[command(subcommand)]
+    pub cmd: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate a random secure key.
+    ///
+    /// Generates a secure AES key with one of the specified APQNs, or any APQN, and writes the
+    /// key blobs to a file.
+    Generate(GenerateOpt),
+
+    /// Validate a secure key.
+    ///
+    /// Checks that an APQN can use the secure key, reports its type, size, and master key
+    /// verification pattern, and lists the APQNs that have its master key set. Exits with 1 if
+    /// the key is not valid or not associated with all specified APQNs.
+    Validate(ValidateOpt),
+
+    /// Convert a secure key into a protected-key token.
+    ///
+    /// Writes one protected-key token per key blob. Protected keys are wrapped by the firmware of
+    /// this system and are only valid until the next reboot.
+    Convert(ConvertOpt),
+
+    /// Re-encipher a CCA secure key with a new master key.
+    ///
+    /// Requires the IBM CCA Host Libraries and Tools (libcsulcca.so). Re-enciphers a key that is
+    /// wrapped with the old master key of the APQNs with the current one, or a key that is
+    /// wrapped with the current master key with the new one that is about to be set. EP11 keys
+    /// are not supported.
+    Reencipher(ReencipherOpt),
+
+    /// Print version information and exit.
+    #[command(aliases(["--version"]), hide(true))]
+    Version,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum KeyTypeArg {
+    /// CCA AES DATA key.
+    CcaAesdata,
+    /// CCA AES CIPHER key.
+    CcaAescipher,
+    /// EP11 AES key.
+    Ep11Aes,
+}
+
+#[derive(Args, Debug)]
+pub struct ApqnOptions {
+    /// Use these APQNs.
+    ///
+    /// Specify APQNs as CARD.DOMAIN with hexadecimal numbers, e.g. '03.0039', separated by
+    /// commas. Uses any suitable APQN if not specified.
+    #[arg(long, value_name = "APQN", value_delimiter = ',')]
+    pub apqns: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateOpt {
+    /// Type of the secure key.
+    #[arg(long, value_enum, default_value = "cca-aescipher")]
+    pub key_type: KeyTypeArg,
+
+    /// Size of the AES key in bits.
+    #[arg(long, value_name = "BITS", default_value_t = 256,
+          value_parser = clap::builder::PossibleValuesParser::new(["128", "192", "256"])
+              .map(|s| s.parse::<u32>().unwrap()))]
+    pub key_bits: u32,
+
+    /// Generate an AES-XTS key, which consists of two key blobs.
+    #[arg(long)]
+    pub xts: bool,
+
+    #[command(flatten)]
+    pub apqn: ApqnOptions,
+
+    /// Write the secure key to FILE.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub output: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ValidateOpt {
+    /// The secure key.
+    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub input: String,
+
+    /// Check that the secure key is associated with these APQNs.
+    ///
+    /// Specify APQNs as CARD.DOMAIN with hexadecimal numbers, e.g. '03.0039', separated by
+    /// commas.
+    #[arg(long, value_name = "APQN", value_delimiter = ',')]
+    pub apqns: Vec<String>,
+
+    /// Define the output format.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct ConvertOpt {
+    /// The secure key.
+    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub input: String,
+
+    #[command(flatten)]
+    pub apqn: ApqnOptions,
+
+    /// Write the protected-key tokens to FILE.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub output: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ReencipherOpt {
+    /// The secure key. Re-enciphered in place unless '--output' is specified.
+    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub input: String,
+
+    /// Re-encipher a key wrapped with the old master key with the current master key.
+    ///
+    /// Default if the key is wrapped with the old master key of the APQNs.
+    #[arg(long, conflicts_with = "to_new")]
+    pub from_old: bool,
+
+    /// Re-encipher a key wrapped with the current master key with the new master key.
+    ///
+    /// Default if the key is wrapped with the current master key of the APQNs.
+    #[arg(long)]
+    pub to_new: bool,
+
+    #[command(flatten)]
+    pub apqn: ApqnOptions,
+
+    /// Write the re-enciphered secure key to FILE.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub output: Option<String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
+pub enum OutputFormat {
+    /// Human-focused, non-parsable output format.
+    #[default]
+    Human,
+    /// Use JSON format.
+    Json,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        CliOptions::command().debug_assert()
+    }
+
+    #[test]
+    fn args() {
+        let cli = CliOptions::try_parse_from([
+            "pkeytool",
+            "generate",
+            "--xts",
+            "--apqns",
+            "03.0039,04.0039",
+            "-o",
+            "key.bin",
+        ])
+        .unwrap();
+        match cli.cmd {
+            Command::Generate(opt) => {
+                assert_eq!(opt.key_type, KeyTypeArg::CcaAescipher);
+                assert_eq!(opt.key_bits, 256);
+                assert_eq!(opt.apqn.apqns, ["03.0039", "04.0039"]);
+            }
+            cmd => panic!("{cmd:?}"),
+        }
+        for args in [
+            &["pkeytool", "generate", "--key-bits", "512", "-o", "k"][..],
+            &["pkeytool", "reencipher", "--from-old", "--to-new", "k"],
+            &["pkeytool", "convert", "k"],
+        ] {
+            assert!(CliOptions::try_parse_from(args).is_err(), "{args:?}");
+        }
+    }
+}
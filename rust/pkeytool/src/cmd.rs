@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fmt::Display, fs::OpenOptions, io::Write, os::unix::fs::OpenOptionsExt, process::ExitCode,
+};
+
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use pv_core::{
+    misc::{encode_hex, read_file},
+    pkey::{ApqnAddr, KeyGenSpec, KeyType, MasterKey, PkeyDevice, SecureKey},
+    request::Confidential,
+};
+use serde::Serialize;
+
+use crate::{
+    cca::{CcaLib, Method},
+    cli::{ConvertOpt, GenerateOpt, KeyTypeArg, OutputFormat, ReencipherOpt, ValidateOpt},
+};
+
+impl From<KeyTypeArg> for KeyType {
+    fn from(value: KeyTypeArg) -> Self {
+        match value {
+            KeyTypeArg::CcaAesdata => Self::CcaAesData,
+            KeyTypeArg::CcaAescipher => Self::CcaAesCipher,
+            KeyTypeArg::Ep11Aes => Self::Ep11Aes,
+        }
+    }
+}
+
+fn parse_apqns(apqns: &[String]) -> Result<Vec<ApqnAddr>> {
+    Ok(apqns
+        .iter()
+        .map(|a| a.parse())
+        .collect::<pv_core::Result<_>>()?)
+}
+
+fn read_key(path: &str) -> Result<SecureKey> {
+    SecureKey::parse(read_file(path, "secure key")?)
+        .with_context(|| format!("Cannot read the secure key '{path}'"))
+}
+
+/// Write `data` to `path`, readable and writable by the owner only.
+fn write_key(path: &str, data: &[u8], ctx: &str) -> Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .and_then(|mut f| f.write_all(data))
+        .with_context(|| format!("Cannot write the {ctx} to '{path}'"))
+}
+
+/// Generate a random secure key.
+pub fn generate(opt: &GenerateOpt) -> Result<ExitCode> {
+    let spec = KeyGenSpec {
+        key_type: opt.key_type.into(),
+        bits: opt.key_bits,
+        xts: opt.xts,
+        apqns: parse_apqns(&opt.apqn.apqns)?,
+    };
+    let pkey = PkeyDevice::open()?;
+    let key = pkey.generate(&spec)?;
+    write_key(&opt.output, key.as_bytes(), "secure key")?;
+    info!(
+        "Generated a {}-bit {} {}key in '{}'",
+        spec.bits,
+        spec.key_type,
+        if spec.xts { "XTS " } else { "" },
+        opt.output
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Validation report of a secure key
+#[derive(Debug, Serialize)]
+struct KeyReport {
+    key_type: KeyType,
+    xts: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bits: Option<u32>,
+    mkvp: String,
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    master_key: Option<MasterKey>,
+    /// APQNs with the master key of the key as current master key
+    apqns: Vec<ApqnAddr>,
+    /// APQNs with the master key of the key as old master key
+    old_apqns: Vec<ApqnAddr>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<String>,
+}
+
+fn list(apqns: &[ApqnAddr]) -> String {
+    match apqns.is_empty() {
+        true => "none".to_string(),
+        false => apqns
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+impl Display for KeyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Key type:       {}", self.key_type)?;
+        writeln!(f, "XTS key:        {}", if self.xts { "yes" } else { "no" })?;
+        match self.bits {
+            Some(bits) => writeln!(f, "Key size:       {bits} bits")?,
+            None => writeln!(f, "Key size:       unknown")?,
+        }
+        writeln!(f, "MKVP:           {}", self.mkvp)?;
+        let mk = match self.master_key {
+            Some(MasterKey::Current) => "current master key",
+            Some(MasterKey::Old) => "old master key",
+            None => "unknown master key",
+        };
+        match self.valid {
+            true => writeln!(f, "Valid:          yes, wrapped with the {mk}")?,
+            false => writeln!(f, "Valid:          no")?,
+        }
+        writeln!(f, "Current MK:     {}", list(&self.apqns))?;
+        write!(f, "Old MK:         {}", list(&self.old_apqns))?;
+        for e in &self.errors {
+            write!(f, "\nError:          {e}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate a secure key and check its APQN association.
+pub fn validate(opt: &ValidateOpt) -> Result<ExitCode> {
+    let key = read_key(&opt.input)?;
+    let expected = parse_apqns(&opt.apqns)?;
+    let pkey = PkeyDevice::open()?;
+
+    let mut errors = vec![];
+    let mut master_key = None;
+    for blob in key.blobs() {
+        match pkey.verify(blob) {
+            Ok(v) => {
+                master_key = match (master_key, v.master_key) {
+                    (Some(MasterKey::Old), _) | (_, MasterKey::Old) => Some(MasterKey::Old),
+                    _ => Some(MasterKey::Current),
+                }
+            }
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    let first = key.blobs().next().unwrap();
+    let apqns = pkey.apqns_for_key(first, false)?;
+    let old_apqns: Vec<_> = pkey
+        .apqns_for_key(first, true)?
+        .into_iter()
+        .filter(|a| !apqns.contains(a))
+        .collect();
+    for apqn in &expected {
+        if old_apqns.contains(apqn) {
+            warn!("APQN {apqn} has the master key of the secure key as old master key; re-encipher the key");
+        } else if !apqns.contains(apqn) {
+            errors.push(format!(
+                "APQN {apqn} does not have the master key of the secure key"
+            ));
+        }
+    }
+
+    let report = KeyReport {
+        key_type: key.key_type(),
+        xts: key.is_xts(),
+        bits: key.bits(),
+        mkvp: encode_hex(key.mkvp()),
+        valid: master_key.is_some() && errors.is_empty(),
+        master_key,
+        apqns,
+        old_apqns,
+        errors,
+    };
+    match opt.format {
+        OutputFormat::Human => println!("{report}"),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    match report.valid {
+        true => Ok(ExitCode::SUCCESS),
+        false => Ok(ExitCode::FAILURE),
+    }
+}
+
+/// Convert a secure key into protected-key tokens.
+pub fn convert(opt: &ConvertOpt) -> Result<ExitCode> {
+    let key = read_key(&opt.input)?;
+    let apqns = parse_apqns(&opt.apqn.apqns)?;
+    let pkey = PkeyDevice::open()?;
+    let mut tokens = Confidential::new(vec![]);
+    for blob in key.blobs() {
+        let protkey = pkey.protected_key(blob, &apqns)?;
+        tokens
+            .value_mut()
+            .extend_from_slice(protkey.token().value());
+    }
+    write_key(&opt.output, tokens.value(), "protected-key tokens")?;
+    let cipher = match key.is_xts() {
+        true => "paes-xts-plain64",
+        false => "paes-cbc-plain64",
+    };
+    info!(
+        "Converted the secure key into protected-key tokens in '{}'",
+        opt.output
+    );
+    warn!(
+        "Use the tokens with the cipher '{cipher}', e.g. 'cryptsetup open --type plain --cipher {cipher} --key-size {} --key-file {} <device> <name>'. The tokens are valid until this system is rebooted.",
+        tokens.value().len() * 8,
+        opt.output
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Re-encipher a CCA secure key.
+pub fn reencipher(opt: &ReencipherOpt) -> Result<ExitCode> {
+    let mut key = read_key(&opt.input)?;
+    if !key.key_type().is_cca() {
+        bail!(
+            "Re-enciphering {} keys is not supported; only CCA keys can be re-enciphered",
+            key.key_type()
+        );
+    }
+    let apqns = parse_apqns(&opt.apqn.apqns)?;
+    let pkey = PkeyDevice::open()?;
+    let first = key.blobs().next().unwrap();
+
+    let method = match (opt.from_old, opt.to_new) {
+        (true, _) => Method::OldToCurrent,
+        (_, true) => Method::CurrentToNew,
+        _ => match pkey.verify(first)?.master_key {
+            MasterKey::Old => Method::OldToCurrent,
+            MasterKey::Current => Method::CurrentToNew,
+        },
+    };
+    let current = pkey.apqns_for_key(first, false)?;
+    let candidates: Vec<_> = match method {
+        Method::CurrentToNew => current,
+        Method::OldToCurrent => pkey
+            .apqns_for_key(first, true)?
+            .into_iter()
+            .filter(|a| !current.contains(a))
+            .collect(),
+    };
+    let apqn = candidates
+        .into_iter()
+        .find(|a| apqns.is_empty() || apqns.contains(a))
+        .with_context(|| {
+            format!(
+                "No APQN has the master key of the secure key as {} master key",
+                match method {
+                    Method::OldToCurrent => "old",
+                    Method::CurrentToNew => "current",
+                }
+            )
+        })?;
+
+    let cca = CcaLib::select(apqn)?;
+    let cipher = key.key_type() == KeyType::CcaAesCipher;
+    let blobs = key
+        .blobs()
+        .map(|blob| {
+            let mut blob = blob.to_vec();
+            cca.reencipher(&mut blob, method, cipher)?;
+            Ok(blob)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    key.set_blobs(&blobs)?;
+
+    let output = opt.output.as_ref().unwrap_or(&opt.input);
+    write_key(output, key.as_bytes(), "secure key")?;
+    match method {
+        Method::OldToCurrent => info!(
+            "Re-enciphered the secure key with the current master key of APQN {apqn} into '{output}'"
+        ),
+        Method::CurrentToNew => warn!(
+            "Re-enciphered the secure key with the new master key of APQN {apqn} into '{output}'. The key can be used after the new master key is set."
+        ),
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+mod cca;
+mod cli;
+mod cmd;
+
+use clap::{CommandFactory, Parser};
+use cli::{CliOptions, Command};
+use log::trace;
+use std::process::ExitCode;
+use utils::{print_cli_error, print_error, print_version, PvLogger};
+
+static LOGGER: PvLogger = PvLogger;
+static EXIT_LOGGER: u8 = 3;
+
+fn main() -> ExitCode {
+    let cli: CliOptions = match CliOptions::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => return print_cli_error(e, CliOptions::command()),
+    };
+
+    // set up logger/std(out,err)
+    let log_level = cli.verbosity.to_level_filter();
+    if let Err(e) = LOGGER.start_with_format(log_level, cli.verbosity.log_format()) {
+        // should(TM) never happen
+        eprintln!("Logger error: {e:?}");
+        return EXIT_LOGGER.into();
+    }
+
+    trace!("Options {cli:?}");
+
+    let res = match &cli.cmd {
+        Command::Generate(opt) => cmd::generate(opt),
+        Command::Validate(opt) => cmd::validate(opt),
+        Command::Convert(opt) => cmd::convert(opt),
+        Command::Reencipher(opt) => cmd::reencipher(opt),
+        Command::Version => {
+            print_version!("2025", log_level);
+            Ok(ExitCode::SUCCESS)
+        }
+    };
+    match res {
+        Ok(code) => code,
+        Err(e) => print_error(&e, log_level),
+    }
+}
@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+#![allow(missing_docs)]
+
+use std::env;
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(ossl300)");
+    // Exported by openssl-sys, the version of the OpenSSL library that is linked against.
+    if let Ok(version) = env::var("DEP_OPENSSL_VERSION_NUMBER") {
+        let version = u64::from_str_radix(&version, 16).expect("Invalid OpenSSL version number");
+        if version >= 0x3000_0000 {
+            println!("cargo:rustc-cfg=ossl300");
+        }
+    }
+}
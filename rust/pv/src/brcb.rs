@@ -8,6 +8,7 @@ use std::{
 };
 
 use log::{debug, warn};
+use pv_ffi::se_hdr::{SeHdrHead, SeHdrKeySlot};
 use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, KnownLayout, U32, U64};
 
 // (SE) boot request control block aka SE header
@@ -23,10 +24,10 @@ use crate::{assert_size, request::MagicValue, static_assert, Error, Result, PAGE
 #[repr(C)]
 #[derive(Debug, Clone, Copy, IntoBytes, PartialEq, Eq, FromBytes, Immutable, KnownLayout)]
 pub struct BootHdrTags {
-    pld: [u8; BootHdrHead::DIGEST_SIZE],
-    ald: [u8; BootHdrHead::DIGEST_SIZE],
-    tld: [u8; BootHdrHead::DIGEST_SIZE],
-    tag: [u8; BootHdrHead::TAG_SIZE],
+    pld: [u8; SeHdrHead::DIGEST_SIZE],
+    ald: [u8; SeHdrHead::DIGEST_SIZE],
+    tld: [u8; SeHdrHead::DIGEST_SIZE],
+    tag: [u8; SeHdrHead::TAG_SIZE],
 }
 assert_size!(BootHdrTags, 0xd0);
 
@@ -48,7 +49,7 @@ impl TryFrom<Vec<u8>> for BootHdrTags {
 
 /// Struct representing the Secure Execution boot image metadata
 #[allow(unused)]
-#[repr(packed)]
+#[repr(C, packed)]
 #[derive(Debug, Clone, FromBytes, IntoBytes, PartialEq, Eq, Immutable, KnownLayout)]
 pub struct SeImgMetaData {
     /// Magic value
@@ -113,6 +114,37 @@ impl SeImgMetaData {
     pub fn version(&self) -> u32 {
         self.version.into()
     }
+
+    /// Returns the offset of the Secure Execution header in the image.
+    pub fn hdr_off(&self) -> u64 {
+        self.hdr_off.into()
+    }
+
+    /// Returns the offset of the IPL parameter block (IPIB) in the image.
+    pub fn ipib_off(&self) -> u64 {
+        self.ipib_off.into()
+    }
+
+    /// Reads the metadata of a Secure Execution boot image.
+    ///
+    /// Returns [`None`] if the image contains no metadata, e.g. because it was built by an older
+    /// version of the image builder.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the IO operation has failed.
+    pub fn try_from_io<R>(img: &mut R) -> Result<Option<Self>>
+    where
+        R: Read + Seek,
+    {
+        if !Self::seek_start(img)? {
+            return Ok(None);
+        }
+        let mut buf = vec![0u8; size_of::<Self>()];
+        img.read_exact(&mut buf)?;
+        // Cannot fail because the buffer has the same size as SeImgMetaData.
+        Ok(Some(Self::read_from_bytes(&buf).unwrap()))
+    }
 }
 
 /// Magic value for the metadata of a Secure Execution boot image
@@ -140,15 +172,14 @@ pub fn seek_se_hdr_start<R>(img: &mut R) -> Result<bool>
 where
     R: Read + Seek,
 {
-    let max_iter: usize;
     const BUF_SIZE: i64 = 8;
     static_assert!(BootHdrMagic::MAGIC.len() == BUF_SIZE as usize);
 
     let old_position = img.stream_position()?;
-    if !SeImgMetaData::seek_start(img)? {
+    let max_iter = if !SeImgMetaData::seek_start(img)? {
         // Search from the previous position.
         img.seek(std::io::SeekFrom::Start(old_position))?;
-        max_iter = 0x15;
+        0x15
     } else {
         let mut img_metadata_bytes = vec![0u8; size_of::<SeImgMetaData>()];
         // read in the header
@@ -161,8 +192,8 @@ where
         }
 
         img.seek(std::io::SeekFrom::Start(img_metadata.hdr_off.into()))?;
-        max_iter = 1;
-    }
+        1
+    };
 
     let mut buf = [0; BUF_SIZE as usize];
     for _ in 0..max_iter {
@@ -216,7 +247,7 @@ impl BootHdrTags {
             return Err(Error::InvBootHdr);
         }
         // read in the header
-        let mut hdr = vec![0u8; size_of::<BootHdrHead>()];
+        let mut hdr = vec![0u8; size_of::<SeHdrHead>()];
         img.read_exact(&mut hdr)?;
 
         // Very unlikely - seek_se_hdr_start should point to a header or error-out
@@ -225,7 +256,7 @@ impl BootHdrTags {
             return Err(Error::InvBootHdr);
         }
 
-        let hdr_head = match BootHdrHead::read_from_prefix(hdr.as_mut_slice()) {
+        let hdr_head = match SeHdrHead::read_from_prefix(hdr.as_mut_slice()) {
             Ok((hdr, _)) => hdr,
             Err(_) => {
                 debug!("Boot hdr is too small");
@@ -234,58 +265,151 @@ impl BootHdrTags {
         };
 
         // Some sanity checks
-        if hdr_head.version.get() != 0x100 {
-            debug!("Unsupported hdr-version: {:0>4x}", hdr_head.version.get());
+        if hdr_head.version() != SeHdrHead::V1 {
+            debug!("Unsupported hdr-version: {:0>4x}", hdr_head.version());
             return Err(Error::InvBootHdr);
         }
 
         // go to the Boot header tag
         img.seek(Current(
-            hdr_head.size.get() as i64
-                - size_of::<BootHdrHead>() as i64
-                - BootHdrHead::TAG_SIZE as i64,
+            hdr_head.size() as i64 - size_of::<SeHdrHead>() as i64 - SeHdrHead::TAG_SIZE as i64,
         ))?;
 
         // read in the tag
-        let mut tag = [0u8; BootHdrHead::TAG_SIZE];
+        let mut tag = [0u8; SeHdrHead::TAG_SIZE];
         img.read_exact(tag.as_mut_slice())?;
 
         Ok(Self {
-            pld: hdr_head.pld,
-            ald: hdr_head.ald,
-            tld: hdr_head.tld,
+            pld: *hdr_head.pld(),
+            ald: *hdr_head.ald(),
+            tld: *hdr_head.tld(),
             tag,
         })
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Clone, FromBytes)]
-struct BootHdrHead {
-    magic: U64<BigEndian>,
-    version: U32<BigEndian>,
-    size: U32<BigEndian>,
-    iv: [u8; 12],
-    res1: u32,
-    nks: U64<BigEndian>,
-    sea: U64<BigEndian>,
-    nep: U64<BigEndian>,
-    pcf: U64<BigEndian>,
-    user_pubkey: [u8; 160],
-    pld: [u8; Self::DIGEST_SIZE],
-    ald: [u8; Self::DIGEST_SIZE],
-    tld: [u8; Self::DIGEST_SIZE],
+/// Borrowed, read-only view of a Secure Execution header (version 1)
+///
+/// Does not decrypt or authenticate anything; it only splits the binary header into its parts
+/// and checks that those are consistent with each other.
+#[derive(Debug, Clone)]
+pub struct SeHdrRef<'a> {
+    head: &'a SeHdrHead,
+    keyslots: &'a [SeHdrKeySlot],
+    encrypted: &'a [u8],
+    tag: &'a [u8; SeHdrHead::TAG_SIZE],
 }
-assert_size!(BootHdrHead, 0x1A0);
-impl BootHdrHead {
-    const DIGEST_SIZE: usize = 0x40;
-    const TAG_SIZE: usize = 0x10;
+
+impl<'a> SeHdrRef<'a> {
+    /// Parses a binary Secure Execution header.
+    ///
+    /// `data` must start with the header. Additional trailing data is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `data` does not contain a well-formed version 1
+    /// Secure Execution header.
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        if !BootHdrMagic::starts_with_magic(data) {
+            return Err(Error::InvBootHdr);
+        }
+        let (head, rest) = SeHdrHead::ref_from_prefix(data).map_err(|_| Error::InvBootHdr)?;
+        if head.version() != SeHdrHead::V1 {
+            debug!("Unsupported hdr-version: {:0>4x}", head.version());
+            return Err(Error::InvBootHdr);
+        }
+        let size = head.size() as usize;
+        if data.len() < size {
+            debug!("Boot hdr is too small");
+            return Err(Error::InvBootHdr);
+        }
+        let body_size = size
+            .checked_sub(size_of::<SeHdrHead>() + SeHdrHead::TAG_SIZE)
+            .ok_or(Error::InvBootHdr)?;
+        let rest = &rest[..body_size];
+
+        let nks = usize::try_from(head.nks()).map_err(|_| Error::InvBootHdr)?;
+        let (keyslots, rest) = <[SeHdrKeySlot]>::ref_from_prefix_with_elems(rest, nks)
+            .map_err(|_| Error::InvBootHdr)?;
+        let sea = usize::try_from(head.sea()).map_err(|_| Error::InvBootHdr)?;
+        if rest.len() != sea {
+            debug!("Inconsistent hdr size");
+            return Err(Error::InvBootHdr);
+        }
+        // Cannot fail, size was checked above.
+        let tag = data[size - SeHdrHead::TAG_SIZE..size].try_into().unwrap();
+        Ok(Self {
+            head,
+            keyslots,
+            encrypted: rest,
+            tag,
+        })
+    }
+
+    /// Returns the unencrypted head of the header.
+    pub fn head(&self) -> &'a SeHdrHead {
+        self.head
+    }
+
+    /// Returns the key slots of the header.
+    pub fn keyslots(&self) -> &'a [SeHdrKeySlot] {
+        self.keyslots
+    }
+
+    /// Returns the encrypted area of the header.
+    pub fn encrypted(&self) -> &'a [u8] {
+        self.encrypted
+    }
+
+    /// Returns the authentication tag of the header.
+    pub fn tag(&self) -> &'a [u8; 16] {
+        self.tag
+    }
+
+    /// Returns `true` if one key slot contains the given public host key hash.
+    pub fn contains_phkh(&self, phkh: &[u8]) -> bool {
+        self.keyslots
+            .iter()
+            .any(|slot| slot.phkh().as_slice() == phkh)
+    }
+}
+
+/// Reads the binary Secure Execution header of a Secure Execution image.
+///
+/// Searches for the header and reads as many bytes as the header specifies. Use [`SeHdrRef::parse`]
+/// to inspect the result.
+///
+/// # Errors
+///
+/// This function will return an error if the header could not be found in `img` or the IO
+/// operation has failed.
+pub fn read_se_hdr<R>(img: &mut R) -> Result<Vec<u8>>
+where
+    R: Read + Seek,
+{
+    if !seek_se_hdr_start(img)? {
+        return Err(Error::InvBootHdr);
+    }
+    let mut hdr = vec![0u8; size_of::<SeHdrHead>()];
+    img.read_exact(&mut hdr)?;
+    let (head, _) = SeHdrHead::ref_from_prefix(&hdr).map_err(|_| Error::InvBootHdr)?;
+    let size = head.size() as usize;
+    // The size is untrusted; do not allocate more than a valid header can use.
+    if !(size_of::<SeHdrHead>()..=SeHdrHead::MAX_SIZE).contains(&size) {
+        return Err(Error::InvBootHdr);
+    }
+    hdr.resize(size, 0);
+    img.read_exact(&mut hdr[size_of::<SeHdrHead>()..])
+        .map_err(|_| Error::InvBootHdr)?;
+    Ok(hdr)
 }
 
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
 
+    use proptest::{collection::vec, num::u8, proptest};
+
     use super::*;
     use crate::{get_test_asset, Error};
 
@@ -360,6 +484,48 @@ mod tests {
         assert_eq!(hdr_tags, EXP_HDR);
     }
 
+    #[test]
+    fn se_hdr_ref() {
+        let bin_hdr = get_test_asset!("exp/secure_guest.hdr");
+        let hdr = SeHdrRef::parse(bin_hdr).unwrap();
+        assert_eq!(hdr.head().version(), SeHdrHead::V1);
+        assert_eq!(hdr.head().size() as usize, bin_hdr.len());
+        assert_eq!(hdr.head().pld(), &EXP_HDR.pld);
+        assert_eq!(hdr.keyslots().len(), 1);
+        assert_eq!(hdr.encrypted().len() as u64, hdr.head().sea());
+        assert_eq!(hdr.tag(), EXP_HDR.tag());
+        let phkh = *hdr.keyslots()[0].phkh();
+        assert!(hdr.contains_phkh(&phkh));
+        assert!(!hdr.contains_phkh(&[0; 32]));
+
+        assert!(matches!(
+            SeHdrRef::parse(&bin_hdr[..bin_hdr.len() - 1]),
+            Err(Error::InvBootHdr)
+        ));
+        let mut bin_hdr_copy = *bin_hdr;
+        bin_hdr_copy.swap(0, 1);
+        assert!(matches!(
+            SeHdrRef::parse(&bin_hdr_copy),
+            Err(Error::InvBootHdr)
+        ));
+    }
+
+    #[test]
+    fn read_se_hdr_img() {
+        let mut img = vec![0u8; 0x13000];
+        let bin_hdr = get_test_asset!("exp/secure_guest.hdr");
+        img[0x12000..0x12280].copy_from_slice(bin_hdr);
+        let hdr = read_se_hdr(&mut Cursor::new(img.clone())).unwrap();
+        assert_eq!(hdr, bin_hdr);
+
+        // header size field (offset 0x0c) far beyond the maximum header size
+        img[0x1200c..0x12010].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            read_se_hdr(&mut Cursor::new(img)),
+            Err(Error::InvBootHdr)
+        ));
+    }
+
     #[test]
     fn tags_convert_u8() {
         let bin_hdr = get_test_asset!("exp/secure_guest.hdr");
@@ -391,5 +557,45 @@ mod tests {
         assert_eq!(SeImgMetaData::ref_from_bytes(&data), Ok(&metadata));
 
         assert_eq!(metadata.version(), SeImgMetaData::V1);
+        assert_eq!(metadata.hdr_off(), 0x14000);
+        assert_eq!(metadata.ipib_off(), 0x16000);
+    }
+
+    #[test]
+    fn se_img_metadata_from_io() {
+        let metadata = SeImgMetaData::new_v1(0x14000, 0x16000);
+        let mut img = vec![0u8; 0x13000];
+        assert_eq!(
+            SeImgMetaData::try_from_io(&mut Cursor::new(&img)).unwrap(),
+            None
+        );
+
+        let off = SeImgMetaData::OFFSET as usize;
+        img[off..off + size_of::<SeImgMetaData>()].copy_from_slice(metadata.as_bytes());
+        assert_eq!(
+            SeImgMetaData::try_from_io(&mut Cursor::new(&img)).unwrap(),
+            Some(metadata)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn se_hdr_parse_no_panic(
+            data in vec(u8::ANY, 0..0x800),
+            size in 0..0x800u32,
+            nks in 0..8u64,
+            sea in 0..0x800u64,
+        ) {
+            let mut hdr = BootHdrMagic::MAGIC.to_vec();
+            hdr.extend_from_slice(&SeHdrHead::V1.to_be_bytes());
+            hdr.extend_from_slice(&size.to_be_bytes());
+            hdr.extend_from_slice(&data);
+            if hdr.len() >= size_of::<SeHdrHead>() {
+                hdr[0x20..0x28].copy_from_slice(&nks.to_be_bytes());
+                hdr[0x28..0x30].copy_from_slice(&sea.to_be_bytes());
+            }
+            let _ = SeHdrRef::parse(&hdr);
+            let _ = BootHdrTags::from_se_image(&mut Cursor::new(hdr));
+        }
     }
 }
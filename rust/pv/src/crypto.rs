@@ -2,7 +2,7 @@
 //
 // Copyright IBM Corp. 2023, 2024
 
-use std::{convert::TryInto, fmt::Display, ops::Range};
+use std::{convert::TryInto, ffi::CString, fmt::Display, ops::Range};
 
 use enum_dispatch::enum_dispatch;
 use openssl::{
@@ -18,9 +18,9 @@ use openssl::{
     sign::{Signer, Verifier},
     symm::{decrypt_aead as openssl_decrypt_aead, encrypt_aead as openssl_encrypt_aead, Cipher},
 };
-use pv_core::request::Confidential;
+use pv_core::request::{Confidential, ZeroizeOnDrop};
 
-use crate::{error::Result, Error};
+use crate::{error::Result, openssl_extensions::load_private_key_uri, Error};
 
 /// An AES256-GCM key that will purge itself out of the memory when going out of scope
 pub type Aes256GcmKey = Confidential<[u8; SymKeyType::AES_256_GCM_KEY_LEN]>;
@@ -111,6 +111,9 @@ pub enum SymKey {
     Aes256Xts(Aes256XtsKey),
 }
 
+/// All variants are [`Confidential`], e.g. attestation request protection keys (ARPK).
+impl ZeroizeOnDrop for SymKey {}
+
 impl SymKey {
     /// Generates a random symmetric key.
     ///
@@ -159,13 +162,25 @@ impl SymKey {
     }
 }
 
-/// Performs an hkdf according to RFC 5869.
-/// See [`OpenSSL HKDF`]()
+/// Performs an HKDF (extract-then-expand) according to RFC 5869.
+///
+/// Derives `COUNT` bytes of key material from the input key material `ikm`. The Ultravisor uses
+/// it with SHA-512 to derive the extension secret of add-secret requests from the customer
+/// communication key, see [`crate::secret::ExtSecret::derive`].
+///
+/// ```rust
+/// # use s390_pv::request::hkdf_rfc_5869;
+/// # use openssl::md::Md;
+/// # fn main() -> s390_pv::Result<()> {
+/// let key: [u8; 32] = hkdf_rfc_5869(Md::sha512(), b"input key", b"salt", b"info")?;
+/// # Ok(())
+/// # }
+/// ```
 ///
 /// # Errors
 ///
 /// This function will return an OpenSSL error if the key could not be generated.
-pub(crate) fn hkdf_rfc_5869<const COUNT: usize>(
+pub fn hkdf_rfc_5869<const COUNT: usize>(
     md: &MdRef,
     ikm: &[u8],
     salt: &[u8],
@@ -186,10 +201,18 @@ pub(crate) fn hkdf_rfc_5869<const COUNT: usize>(
 
 /// Derive a symmetric AES 256 GCM key from a private and a public key.
 ///
+/// Performs an ECDH key exchange and hashes the shared secret, followed by a big-endian counter of
+/// one, with SHA-256. This is the key that wraps the request protection key in a [`Keyslot`],
+/// e.g. the attestation request protection key (ARPK) or the key of an SE-header.
+///
+/// [`Keyslot`]: crate::request::Keyslot
+///
 /// # Errors
 ///
-/// This function will return an error if something went bad in OpenSSL.
+/// This function will return an error if something went bad in OpenSSL or FIPS mode is enforced
+/// but OpenSSL is not in FIPS mode.
 pub fn derive_aes256_gcm_key(k1: &PKeyRef<Private>, k2: &PKeyRef<Public>) -> Result<Aes256GcmKey> {
+    crate::fips_mode::check()?;
     let mut der = Deriver::new(k1)?;
     der.set_peer(k2)?;
     let mut key = der.derive_to_vec()?;
@@ -205,6 +228,24 @@ pub fn derive_aes256_gcm_key(k1: &PKeyRef<Private>, k2: &PKeyRef<Public>) -> Res
     ))
 }
 
+/// Compares two byte slices in constant time.
+///
+/// The time to compare does not depend on the content of the slices, only on their lengths.
+/// Slices of different length are never equal; lengths are considered public.
+///
+/// Use this function for comparisons of measurements, HMACs, tags, and hashes in verification
+/// paths instead of `==`, which returns at the first difference.
+///
+/// ```rust
+/// # use s390_pv::misc::eq_ct;
+/// assert!(eq_ct(&[1, 2, 3], &[1, 2, 3]));
+/// assert!(!eq_ct(&[1, 2, 3], &[1, 2, 4]));
+/// assert!(!eq_ct(&[1, 2, 3], &[1, 2]));
+/// ```
+pub fn eq_ct(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && openssl::memcmp::eq(a, b)
+}
+
 /// Generate a random array.
 ///
 /// # Errors
@@ -227,6 +268,21 @@ pub fn gen_ec_key(nid: Nid) -> Result<PKey<Private>> {
     PKey::from_ec_key(key).map_err(Error::Crypto)
 }
 
+/// Load a private key from an OpenSSL store URI.
+///
+/// Any URI scheme an OpenSSL provider understands is supported, e.g. `file:` or, with the
+/// [pkcs11-provider](https://github.com/latchset/pkcs11-provider) configured, `pkcs11:`. The key
+/// material of hardware-backed keys never leaves the token; OpenSSL dispatches all private key
+/// operations to the provider. Passphrases and PINs are requested on the terminal if needed.
+///
+/// # Errors
+///
+/// This function will return an error if the URI cannot be opened or contains no private key.
+pub fn private_key_from_uri(uri: &str) -> Result<PKey<Private>> {
+    let c_uri = CString::new(uri).map_err(|_| Error::NoPrivateKeyAtUri(uri.to_string()))?;
+    load_private_key_uri(&c_uri)?.ok_or_else(|| Error::NoPrivateKeyAtUri(uri.to_string()))
+}
+
 /// Result type for an AES encryption in GCM mode..
 #[derive(PartialEq, Eq, Debug)]
 pub struct AeadEncryptionResult {
@@ -354,13 +410,15 @@ impl AeadDecryptionResult {
 ///
 /// # Errors
 ///
-/// This function will return an error if the data could not be encrypted by OpenSSL.
+/// This function will return an error if the data could not be encrypted by OpenSSL or FIPS mode
+/// is enforced but OpenSSL is not in FIPS mode.
 pub fn encrypt_aead(
     key: &SymKey,
     iv: &[u8],
     aad: &[u8],
     conf: &[u8],
 ) -> Result<AeadEncryptionResult> {
+    crate::fips_mode::check()?;
     let tag_len = key.key_type().tag_len().ok_or(Error::NoAeadKey)?;
 
     let nid = key.key_type().into();
@@ -486,11 +544,7 @@ pub(crate) fn calculate_hmac(
 /// # Errors
 ///
 /// This function will return an error if OpenSSL could not compute the signature.
-pub(crate) fn sign_msg(
-    skey: &PKeyRef<Private>,
-    dgst: MessageDigest,
-    msg: &[u8],
-) -> Result<Vec<u8>> {
+pub fn sign_msg(skey: &PKeyRef<Private>, dgst: MessageDigest, msg: &[u8]) -> Result<Vec<u8>> {
     match skey.id() {
         Id::EC => {
             let mut sgn = Signer::new(dgst, skey)?;
@@ -516,7 +570,7 @@ pub(crate) fn sign_msg(
 /// # Errors
 ///
 /// This function will return an error if OpenSSL could not compute the signature.
-pub(crate) fn verify_signature<T: HasPublic>(
+pub fn verify_signature<T: HasPublic>(
     skey: &PKeyRef<T>,
     dgst: MessageDigest,
     msg: &[u8],
@@ -542,6 +596,14 @@ mod tests {
     use super::*;
     use crate::{get_test_asset, test_utils::*, PvCoreError};
 
+    #[test]
+    fn sym_key_zeroize_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<SymKey>();
+        assert_zeroize_on_drop::<Aes256GcmKey>();
+        assert_zeroize_on_drop::<Aes256XtsKey>();
+    }
+
     #[test]
     fn sign_ec() {
         let (ec_key, _) = get_test_keys();
@@ -553,6 +615,36 @@ mod tests {
         assert!(verify_signature(&ec_key, MessageDigest::sha512(), data, &sign).unwrap());
     }
 
+    #[test]
+    fn key_from_uri() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/assets/keys/rsa2048key.pem"
+        );
+        let key = private_key_from_uri(&format!("file:{path}")).unwrap();
+        let exp = PKey::private_key_from_pem(get_test_asset!("keys/rsa2048key.pem")).unwrap();
+        assert!(key.public_eq(&exp));
+
+        assert!(matches!(
+            private_key_from_uri(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/keys/")),
+            Err(Error::NoPrivateKeyAtUri(_) | Error::Crypto(_))
+        ));
+        assert!(matches!(
+            private_key_from_uri("file:/nonexistent"),
+            Err(Error::Crypto(_))
+        ));
+        // undecodable data ends the load instead of retrying forever
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/assets/keys/private_cust.bin"
+        );
+        assert!(private_key_from_uri(&format!("file:{path}")).is_err());
+        assert!(matches!(
+            private_key_from_uri("file:/a\0b"),
+            Err(Error::NoPrivateKeyAtUri(_))
+        ));
+    }
+
     #[test]
     fn sign_rsa_2048() {
         let keypair = get_test_asset!("keys/rsa2048key.pem");
@@ -26,6 +26,12 @@ pub enum Error {
     #[error("Too many host-keys provided")]
     ManyHostkeys,
 
+    #[error("Unsupported host-key type: {0}. Supported are: {supported}", supported = crate::request::HostKeyType::ALL.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    UnsupportedHostKey(String),
+
+    #[error("All host-keys of a request must be of the same type. Expected {0}")]
+    HostKeyTypeMismatch(crate::request::HostKeyType),
+
     #[error("Cannot load {ty}  from {path}")]
     X509Load {
         path: PathBuf,
@@ -75,7 +81,7 @@ pub enum Error {
     )]
     AsrcbUserDataSgnFail,
 
-    #[error("The provided Host Key Document in '{hkd}' is not in PEM or DER format")]
+    #[error("The provided Host Key Document in '{hkd}' is not in PEM, DER, or PKCS#7 format")]
     HkdNotPemOrDer {
         hkd: String,
         source: openssl::error::ErrorStack,
@@ -84,6 +90,31 @@ pub enum Error {
     #[error("The provided host key document in {0} contains no certificate!")]
     NoHkdInFile(String),
 
+    #[error("Only HTTPS URLs are supported: '{0}'")]
+    UrlNotHttps(String),
+
+    #[error("Cannot download '{url}'")]
+    Download {
+        url: String,
+        #[source]
+        source: curl::Error,
+    },
+
+    #[error("Downloading '{url}' failed with HTTP status {status}")]
+    DownloadStatus { url: String, status: u32 },
+
+    #[error("The content of '{0}' is larger than 16 MiB")]
+    DownloadLarge(String),
+
+    #[error("The SHA-256 digest of the content of '{0}' does not match the pinned digest")]
+    DownloadChecksum(String),
+
+    #[error("Cannot download '{0}': network access is disabled")]
+    DownloadOffline(String),
+
+    #[error("Invalid machine identity '{0}'. Specify [TYPE-]SERIAL, e.g. 3931-02A1B2C")]
+    InvalidMachineId(String),
+
     #[error("Invalid input size ({0}) for boot hdr")]
     InvBootHdrSize(usize),
 
@@ -98,14 +129,48 @@ pub enum Error {
     )]
     BinArcbSeaSmall(u32),
 
+    #[error("The input has not the correct exchange format: {0}")]
+    InvExchangeFormat(&'static str),
+
+    #[error("The input has an unsupported exchange format version ({0:#x})")]
+    ExchangeUnsupportedVersion(u32),
+
+    #[error("The {field} field of the exchange format is too small ({size})")]
+    ExchangeFieldSmall { field: &'static str, size: usize },
+
+    #[error("The {field} field of the exchange format is too large ({size})")]
+    ExchangeFieldLarge { field: &'static str, size: usize },
+
+    #[error("The {field} field of the exchange format has an invalid size. Expected size {exp}, is {size}")]
+    ExchangeFieldSize {
+        field: &'static str,
+        size: usize,
+        exp: usize,
+    },
+
+    #[error("The exchange format contains the unknown critical extension {0:#010x}")]
+    ExchangeCriticalExtension(u32),
+
+    #[error("The exchange format version 1 cannot hold the critical extension {0:#010x}")]
+    ExchangeExtensionV1(u32),
+
     #[error("The input is missing the Configuration UID entry. It is probably not an attestation response")]
     AttExCuidMissing,
 
+    #[error("The Configuration UID of the attestation response (0x{received}) does not match the expected one (0x{expected})")]
+    CuidMismatch { received: String, expected: String },
+
     #[error(
         "Attestation flags indicating that the additional data contains {0}, but no data was provided."
     )]
     AddDataMissing(&'static str),
 
+    #[error("The Ultravisor does not support the additional-data item '{0}'")]
+    AddDataUnsupported(&'static str),
+
+    #[error("The measurement algorithm '{0}' is not supported")]
+    MaiUnsupported(&'static str),
+
     #[error("An ASCII string was expected, but non-ASCII characters were received.")]
     NonAscii,
 
@@ -117,9 +182,60 @@ pub enum Error {
         exp: String,
     },
 
+    #[error("No private key found at '{0}'")]
+    NoPrivateKeyAtUri(String),
+
     #[error("Invalid data from OpenSSL")]
     InvalSslData,
 
+    #[error("FIPS mode is enforced, but OpenSSL is not in FIPS mode")]
+    FipsNotEnabled,
+
+    #[error("FIPS mode is enforced, but {0} is not FIPS approved")]
+    FipsNotApproved(&'static str),
+
+    #[error("The {0} of the dump is corrupted")]
+    DumpCorrupted(&'static str),
+
+    #[error("The dump contains an unsupported {what} version ({version})")]
+    DumpUnsupportedVersion { what: &'static str, version: u32 },
+
+    #[cfg(feature = "coco")]
+    #[error("Invalid IBM Secure Execution attestation challenge")]
+    CocoChallenge(#[source] serde_json::Error),
+
+    #[cfg(feature = "keylime")]
+    #[error("Invalid IBM Secure Execution Keylime quote")]
+    KeylimeMessage(#[source] serde_json::Error),
+
+    #[cfg(feature = "keylime")]
+    #[error("Unsupported Keylime quote type '{0}'")]
+    KeylimeEvidenceType(String),
+
+    #[cfg(feature = "keylime")]
+    #[error("The Keylime nonce must be 1 to 256 bytes long")]
+    KeylimeNonce,
+
+    #[cfg(feature = "keylime")]
+    #[error("The Keylime quote does not include the nonce of the verifier")]
+    KeylimeNonceMismatch,
+
+    #[cfg(feature = "keylime")]
+    #[error("The Keylime agent does not run a trusted IBM Secure Execution image")]
+    KeylimeUntrustedGuest,
+
+    #[cfg(feature = "spire")]
+    #[error("Invalid IBM Secure Execution SPIRE attestation message")]
+    SpireMessage(#[source] serde_json::Error),
+
+    #[cfg(feature = "spire")]
+    #[error("Unsupported IBM Secure Execution SPIRE attestation payload version ({0})")]
+    SpireUnsupportedVersion(u32),
+
+    #[cfg(feature = "spire")]
+    #[error("The SPIRE agent does not run a trusted IBM Secure Execution image")]
+    SpireUntrustedGuest,
+
     // errors from other crates
     #[error(transparent)]
     PvCore(#[from] pv_core::Error),
@@ -137,6 +253,108 @@ pub enum Error {
     UnsupportedCipher(Nid),
 }
 
+impl Error {
+    /// Returns a stable, machine-readable code for this error.
+    ///
+    /// The code does not change between releases. Callers may branch on it instead of parsing the
+    /// error message. Wrapped [`pv_core::Error`]s keep their code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::HkdVerify(ty) => ty.code(),
+            Self::InvBootHdr | Self::InvBootHdrSize(_) => "invalid-se-header",
+            Self::NoHostkey | Self::ManyHostkeys => "host-key-count",
+            Self::X509Load { .. }
+            | Self::HkdNotPemOrDer { .. }
+            | Self::NoHkdInFile(_)
+            | Self::NoPrivateKeyAtUri(_) => "invalid-certificate",
+            Self::InternalSsl(..) | Self::InvalSslData | Self::Crypto(_) => "crypto",
+            Self::UnsupportedSigningKey
+            | Self::UnsupportedVerificationKey
+            | Self::BinAsrcbUnsupportedUserDataSgnKey
+            | Self::AsrcbUserDataKeyMismatch { .. }
+            | Self::UnsupportedHostKey(_)
+            | Self::HostKeyTypeMismatch(_) => "unsupported-key",
+            Self::BinRequestSmall
+            | Self::NoArcb
+            | Self::BinArcbInvVersion(_)
+            | Self::BinArcbSeaSmall(_) => "invalid-attestation-request",
+            Self::NoCuid(_) => "no-cuid",
+            Self::CckSize => "invalid-key",
+            Self::GcmTagMismatch => "decryption-failed",
+            Self::AsrcbInvSgnUserData(_) | Self::BinAsrcbInvVersion => "invalid-add-secret-request",
+            Self::BinAsrcbNoUserDataSgnKey => "user-key-missing",
+            Self::AsrcbUserDataSgnFail => "user-signature-invalid",
+            Self::UrlNotHttps(_) => "url-not-https",
+            Self::Download { .. } | Self::DownloadStatus { .. } | Self::Curl(_) => {
+                "download-failed"
+            }
+            Self::DownloadLarge(_) => "download-large",
+            Self::DownloadChecksum(_) => "download-checksum",
+            Self::DownloadOffline(_) => "offline",
+            Self::InvalidMachineId(_) | Self::NonAscii => "invalid-input",
+            Self::InvExchangeFormat(_)
+            | Self::ExchangeFieldSmall { .. }
+            | Self::ExchangeFieldLarge { .. }
+            | Self::ExchangeFieldSize { .. }
+            | Self::AttExCuidMissing => "invalid-exchange-format",
+            Self::ExchangeUnsupportedVersion(_)
+            | Self::ExchangeCriticalExtension(_)
+            | Self::ExchangeExtensionV1(_) => "unsupported-exchange-format",
+            Self::CuidMismatch { .. } => "cuid-mismatch",
+            Self::AddDataMissing(_) => "additional-data-missing",
+            Self::AddDataUnsupported(_) | Self::MaiUnsupported(_) => "facility-missing",
+            Self::RetrInvKey { .. } => "invalid-retrieved-key",
+            Self::FipsNotEnabled | Self::FipsNotApproved(_) => "fips",
+            Self::DumpCorrupted(_) => "dump-corrupted",
+            Self::DumpUnsupportedVersion { .. } => "dump-unsupported",
+            #[cfg(feature = "coco")]
+            Self::CocoChallenge(_) => "invalid-input",
+            #[cfg(feature = "keylime")]
+            Self::KeylimeMessage(_) | Self::KeylimeNonce | Self::KeylimeEvidenceType(_) => {
+                "invalid-input"
+            }
+            #[cfg(feature = "keylime")]
+            Self::KeylimeNonceMismatch => "nonce-mismatch",
+            #[cfg(feature = "keylime")]
+            Self::KeylimeUntrustedGuest => "untrusted-guest",
+            #[cfg(feature = "spire")]
+            Self::SpireMessage(_) | Self::SpireUnsupportedVersion(_) => "invalid-input",
+            #[cfg(feature = "spire")]
+            Self::SpireUntrustedGuest => "untrusted-guest",
+            Self::PvCore(e) => e.code(),
+            Self::Io(_) => "io",
+            Self::NoAeadKey | Self::UnsupportedCipher(_) => "unsupported-key",
+        }
+    }
+
+    /// Returns a hint on how to remediate this error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::PvCore(e) => e.hint(),
+            Self::HkdVerify(HkdVerifyErrorType::HkdRevoked) => {
+                Some("Obtain a new host-key document for the machine")
+            }
+            Self::HkdVerify(HkdVerifyErrorType::NoCrl) => {
+                Some("Provide a CRL with '--crl' or allow downloading it")
+            }
+            Self::HkdVerify(
+                HkdVerifyErrorType::NoIbmSignKey | HkdVerifyErrorType::ManyIbmSignKeys,
+            ) => Some("Provide the IBM Z signing key certificate with '--cert'"),
+            Self::DownloadOffline(_) => {
+                Some("Provide a local copy of the file or omit '--offline'")
+            }
+            Self::CuidMismatch { .. } => {
+                Some("Check that the attestation response belongs to the request")
+            }
+            Self::FipsNotEnabled => Some("Enable the FIPS provider of OpenSSL"),
+            Self::UnsupportedHostKey(_) | Self::HostKeyTypeMismatch(_) => {
+                Some("Check that the host-key document is for an IBM Z machine supported by this version")
+            }
+            _ => None,
+        }
+    }
+}
+
 // used in macros
 #[doc(hidden)]
 impl Error {
@@ -177,6 +395,23 @@ pub enum HkdVerifyErrorType {
     IbmSignInvalid(#[source] openssl::x509::X509VerifyResult, u32),
 }
 
+impl HkdVerifyErrorType {
+    /// Returns a stable, machine-readable code for this error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::HkdRevoked => "hkd-revoked",
+            Self::NoCrl | Self::NoCrlDP => "hkd-no-crl",
+            Self::BeforeValidity | Self::AfterValidity | Self::NoValidityPeriod => "hkd-expired",
+            Self::NoIbmSignKey | Self::ManyIbmSignKeys => "hkd-signing-key",
+            Self::Signature
+            | Self::SecurityBits(..)
+            | Self::Akid
+            | Self::IssuerMismatch
+            | Self::IbmSignInvalid(..) => "hkd-invalid",
+        }
+    }
+}
+
 macro_rules! bail_hkd_verify {
     ($var: tt) => {
         return Err($crate::Error::HkdVerify($crate::HkdVerifyErrorType::$var))
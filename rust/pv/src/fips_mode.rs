@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{Error, Result};
+
+static ENFORCED: AtomicBool = AtomicBool::new(false);
+
+/// Reports whether OpenSSL fetches algorithms from a FIPS provider by default.
+#[cfg(ossl300)]
+pub fn fips_enabled() -> bool {
+    // SAFETY: A NULL library context selects the default context; no other pointers are used.
+    unsafe { openssl_sys::EVP_default_properties_is_fips_enabled(std::ptr::null_mut()) == 1 }
+}
+
+/// Reports whether OpenSSL runs in FIPS mode.
+#[cfg(not(ossl300))]
+pub fn fips_enabled() -> bool {
+    openssl::fips::enabled()
+}
+
+/// Enforces FIPS mode for all following request-building operations of this process.
+///
+/// Afterwards, the crypto layer fails closed if OpenSSL is not in FIPS mode and rejects
+/// algorithms that are not FIPS approved.
+///
+/// # Errors
+///
+/// This function will return an error if OpenSSL is not in FIPS mode. Enforcement is not enabled
+/// in that case.
+pub fn enforce_fips() -> Result<()> {
+    enforce(&ENFORCED, fips_enabled())
+}
+
+/// Reports whether FIPS mode is enforced, see [`enforce_fips`].
+pub fn fips_enforced() -> bool {
+    ENFORCED.load(Ordering::SeqCst)
+}
+
+/// Verifies that OpenSSL is (still) in FIPS mode if FIPS mode is enforced.
+pub(crate) fn check() -> Result<()> {
+    check_with(&ENFORCED, fips_enabled)
+}
+
+/// Rejects the algorithm `name` if FIPS mode is enforced.
+pub(crate) fn reject(name: &'static str) -> Result<()> {
+    reject_with(&ENFORCED, name)
+}
+
+fn enforce(enforced: &AtomicBool, enabled: bool) -> Result<()> {
+    if !enabled {
+        return Err(Error::FipsNotEnabled);
+    }
+    enforced.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+fn check_with<F: FnOnce() -> bool>(enforced: &AtomicBool, enabled: F) -> Result<()> {
+    match enforced.load(Ordering::SeqCst) && !enabled() {
+        true => Err(Error::FipsNotEnabled),
+        false => Ok(()),
+    }
+}
+
+fn reject_with(enforced: &AtomicBool, name: &'static str) -> Result<()> {
+    match enforced.load(Ordering::SeqCst) {
+        true => Err(Error::FipsNotApproved(name)),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn not_enforced() {
+        let enforced = AtomicBool::new(false);
+        assert!(check_with(&enforced, || false).is_ok());
+        assert!(reject_with(&enforced, "Ed25519").is_ok());
+    }
+
+    #[test]
+    fn enforce_enabled() {
+        let enforced = AtomicBool::new(false);
+        enforce(&enforced, true).unwrap();
+        assert!(enforced.load(Ordering::SeqCst));
+        assert!(check_with(&enforced, || true).is_ok());
+        // fails closed if OpenSSL leaves FIPS mode
+        assert!(matches!(
+            check_with(&enforced, || false),
+            Err(Error::FipsNotEnabled)
+        ));
+        assert!(matches!(
+            reject_with(&enforced, "Ed25519"),
+            Err(Error::FipsNotApproved("Ed25519"))
+        ));
+    }
+
+    #[test]
+    fn enforce_disabled() {
+        let enforced = AtomicBool::new(false);
+        assert!(matches!(
+            enforce(&enforced, false),
+            Err(Error::FipsNotEnabled)
+        ));
+        assert!(!enforced.load(Ordering::SeqCst));
+    }
+}
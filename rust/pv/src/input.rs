@@ -0,0 +1,356 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2023, 2025
+
+//! Loading of host-key documents, certificates, and CRLs.
+//!
+//! All entry points accept the same formats, detected from the content: a single DER object,
+//! one or more PEM objects, or a PKCS#7 bundle in DER or PEM format.
+//!
+//! Inputs can also be downloaded from HTTPS URLs, see [`fetch_https`].
+
+use std::{path::Path, time::Duration};
+
+use foreign_types::{ForeignType, ForeignTypeRef};
+use log::warn;
+use openssl::{
+    error::ErrorStack,
+    pkcs7::{Pkcs7, Pkcs7Ref},
+    sha::sha256,
+    x509::{X509Crl, X509},
+};
+
+use crate::{crypto::eq_ct, misc::read_file, Error, Result};
+
+const PEM_PKCS7: &[u8] = b"-----BEGIN PKCS7-----";
+const PEM_BEGIN: &[u8] = b"-----BEGIN ";
+
+const HTTPS: &str = "https://";
+const FETCH_MAX_SIZE: usize = 0x100_0000;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Encodings of certificates and CRLs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum X509Format {
+    /// A single DER encoded object
+    Der,
+    /// One or more PEM encoded objects
+    Pem,
+    /// A DER encoded PKCS#7 bundle
+    Pkcs7Der,
+    /// A PEM encoded PKCS#7 bundle
+    Pkcs7Pem,
+}
+
+fn contains(buf: &[u8], needle: &[u8]) -> bool {
+    buf.windows(needle.len()).any(|w| w == needle)
+}
+
+impl X509Format {
+    /// Detects the encoding of `buf`.
+    ///
+    /// Data that is neither PEM nor a PKCS#7 bundle is reported as DER.
+    pub fn detect(buf: &[u8]) -> Self {
+        if contains(buf, PEM_PKCS7) {
+            Self::Pkcs7Pem
+        } else if contains(buf, PEM_BEGIN) {
+            Self::Pem
+        } else if Pkcs7::from_der(buf).is_ok() {
+            Self::Pkcs7Der
+        } else {
+            Self::Der
+        }
+    }
+}
+
+fn read_pkcs7(buf: &[u8], format: X509Format) -> Result<Pkcs7, ErrorStack> {
+    match format {
+        X509Format::Pkcs7Pem => Pkcs7::from_pem(buf),
+        _ => Pkcs7::from_der(buf),
+    }
+}
+
+fn pkcs7_crls(p7: &Pkcs7Ref) -> Vec<X509Crl> {
+    let Some(signed) = p7.signed() else {
+        return vec![];
+    };
+    let mut crls = vec![];
+    // SAFETY: signed points to a valid PKCS7_SIGNED structure that lives as long as p7. The
+    // reference count of every returned CRL is incremented.
+    unsafe {
+        let stack = (*signed.as_ptr()).crl;
+        if stack.is_null() {
+            return crls;
+        }
+        let num = openssl_sys::OPENSSL_sk_num(stack as *const _);
+        for i in 0..num {
+            let crl = openssl_sys::OPENSSL_sk_value(stack as *const _, i);
+            if !crl.is_null() {
+                let crl = crl as *mut openssl_sys::X509_CRL;
+                openssl_sys::X509_CRL_up_ref(crl);
+                crls.push(X509Crl::from_ptr(crl));
+            }
+        }
+    }
+    crls
+}
+
+/// Read all CRLs from the buffer and parse them into a vector.
+///
+/// Accepts DER, PEM, and PKCS#7 bundles (see [`X509Format`]). PKCS#7 bundles without CRLs
+/// result in an empty vector.
+///
+/// # Errors
+///
+/// This function will return an error if the underlying OpenSSL implementation cannot parse `buf`.
+pub fn read_crls<T: AsRef<[u8]>>(buf: T) -> Result<Vec<X509Crl>> {
+    use crate::openssl_extensions::StackableX509Crl;
+    let buf = buf.as_ref();
+    match X509Format::detect(buf) {
+        f @ (X509Format::Pkcs7Der | X509Format::Pkcs7Pem) => Ok(pkcs7_crls(&*read_pkcs7(buf, f)?)),
+        X509Format::Pem => StackableX509Crl::stack_from_pem(buf).map_err(Error::Crypto),
+        X509Format::Der => X509Crl::from_der(buf)
+            .map(|crl| vec![crl])
+            .or_else(|_| StackableX509Crl::stack_from_pem(buf))
+            .map_err(Error::Crypto),
+    }
+}
+
+/// Read all certificates from the buffer and parse them into a vector.
+///
+/// Accepts DER, PEM, and PKCS#7 bundles (see [`X509Format`]).
+///
+/// # Errors
+///
+/// This function will return an error if the underlying OpenSSL implementation cannot parse `buf`
+pub fn read_certs<T: AsRef<[u8]>>(buf: T) -> Result<Vec<X509>, ErrorStack> {
+    let buf = buf.as_ref();
+    match X509Format::detect(buf) {
+        f @ (X509Format::Pkcs7Der | X509Format::Pkcs7Pem) => Ok(read_pkcs7(buf, f)?
+            .signed()
+            .and_then(|s| s.certificates())
+            .map(|certs| certs.iter().map(|c| c.to_owned()).collect())
+            .unwrap_or_default()),
+        X509Format::Pem => X509::stack_from_pem(buf),
+        X509Format::Der => X509::from_der(buf)
+            .map(|crt| vec![crt])
+            .or_else(|_| X509::stack_from_pem(buf)),
+    }
+}
+
+/// Read the host-key document at `path`.
+///
+/// The document can be in any format accepted by [`read_certs`]. If it contains more than one
+/// certificate, the first one is used.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read, cannot be parsed, or contains
+/// no certificate.
+pub fn read_hkd<P: AsRef<Path>>(path: P) -> Result<X509> {
+    let path = path.as_ref();
+    let hkd = path.display().to_string();
+    let buf = read_file(path, "host-key document")?;
+    let mut certs = read_certs(&buf).map_err(|source| Error::HkdNotPemOrDer {
+        hkd: hkd.clone(),
+        source,
+    })?;
+    if certs.len() > 1 {
+        warn!("The host-key document in '{hkd}' contains more than one certificate! Only the first certificate will be used.");
+    }
+    match certs.is_empty() {
+        true => Err(Error::NoHkdInFile(hkd)),
+        false => Ok(certs.remove(0)),
+    }
+}
+
+/// Returns `true` if `input` is an URL rather than a local path.
+///
+/// Only HTTPS URLs can be fetched, but HTTP URLs are reported as well, so that they are rejected
+/// instead of being interpreted as a path.
+pub fn is_url<P: AsRef<Path>>(input: P) -> bool {
+    input
+        .as_ref()
+        .to_str()
+        .is_some_and(|s| s.starts_with(HTTPS) || s.starts_with("http://"))
+}
+
+fn check_sha256(url: &str, buf: &[u8], pin: Option<&[u8; 32]>) -> Result<()> {
+    match pin {
+        Some(exp) if !eq_ct(&sha256(buf), exp) => Err(Error::DownloadChecksum(url.to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Download `url` over HTTPS.
+///
+/// The server is authenticated with the system-wide root CAs. Redirects are followed as long as
+/// they lead to HTTPS URLs. If `sha256` is given, the downloaded content must have this SHA-256
+/// digest, which pins the content independent of the server.
+///
+/// # Errors
+///
+/// This function will return an error if `url` is no HTTPS URL, the download fails, the content
+/// is larger than 16 MiB, or the content does not match `sha256`.
+pub fn fetch_https(url: &str, sha256: Option<&[u8; 32]>) -> Result<Vec<u8>> {
+    use curl::easy::{Easy2, Handler, WriteError};
+
+    struct Buf(Vec<u8>);
+    impl Handler for Buf {
+        fn write(&mut self, data: &[u8]) -> std::result::Result<usize, WriteError> {
+            match self.0.len() + data.len() > FETCH_MAX_SIZE {
+                // curl aborts the transfer if not all data is consumed
+                true => Ok(0),
+                false => {
+                    self.0.extend_from_slice(data);
+                    Ok(data.len())
+                }
+            }
+        }
+    }
+
+    if !url.starts_with(HTTPS) {
+        return Err(Error::UrlNotHttps(url.to_string()));
+    }
+    let download = |source| Error::Download {
+        url: url.to_string(),
+        source,
+    };
+    let mut handle = Easy2::new(Buf(Vec::new()));
+    handle.url(url).map_err(download)?;
+    handle.get(true).map_err(download)?;
+    handle.follow_location(true).map_err(download)?;
+    handle.ssl_verify_peer(true).map_err(download)?;
+    handle.ssl_verify_host(true).map_err(download)?;
+    handle.timeout(FETCH_TIMEOUT).map_err(download)?;
+    handle.useragent("s390-tools-pv").map_err(download)?;
+    if let Err(e) = handle.perform() {
+        return Err(match e.is_write_error() {
+            true => Error::DownloadLarge(url.to_string()),
+            false => download(e),
+        });
+    }
+
+    let eff_url = handle.effective_url().map_err(download)?.unwrap_or(url);
+    if !eff_url.starts_with(HTTPS) {
+        return Err(Error::UrlNotHttps(eff_url.to_string()));
+    }
+    let status = handle.response_code().map_err(download)?;
+    if !(200..300).contains(&status) {
+        return Err(Error::DownloadStatus {
+            url: url.to_string(),
+            status,
+        });
+    }
+    let buf = std::mem::take(&mut handle.get_mut().0);
+    check_sha256(url, &buf, sha256)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn detect() {
+        use X509Format::*;
+        assert_eq!(X509Format::detect(&get_cert_asset("ibm.crt")), Pem);
+        assert_eq!(X509Format::detect(&get_cert_asset("der.crt")), Der);
+        assert_eq!(X509Format::detect(&get_cert_asset("ibm.p7b")), Pkcs7Pem);
+        assert_eq!(X509Format::detect(&get_cert_asset("ibm_der.p7b")), Pkcs7Der);
+        assert_eq!(X509Format::detect(b"garbage"), Der);
+    }
+
+    #[test]
+    fn read_crls() {
+        let crl = get_cert_asset("ibm.crl");
+        let crl_der = get_cert_asset("der.crl");
+        let fail = get_cert_asset("ibm.crt");
+        assert_eq!(super::read_crls(crl).unwrap().len(), 1);
+        assert_eq!(super::read_crls(crl_der).unwrap().len(), 1);
+        assert_eq!(super::read_crls(fail).unwrap().len(), 0);
+        assert_eq!(
+            super::read_crls(get_cert_asset("ibm.p7b")).unwrap().len(),
+            1
+        );
+        assert_eq!(
+            super::read_crls(get_cert_asset("ibm_der.p7b"))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn read_certs() {
+        let crt = get_cert_asset("ibm.crt");
+        let crt_der = get_cert_asset("der.crt");
+        let fail = get_cert_asset("ibm.crl");
+        assert_eq!(super::read_certs(crt).unwrap().len(), 1);
+        assert_eq!(super::read_certs(crt_der).unwrap().len(), 1);
+        assert_eq!(super::read_certs(fail).unwrap().len(), 0);
+        let p7 = super::read_certs(get_cert_asset("ibm.p7b")).unwrap();
+        let p7_der = super::read_certs(get_cert_asset("ibm_der.p7b")).unwrap();
+        assert_eq!(p7.len(), 2);
+        assert_eq!(
+            p7[0].to_der().unwrap(),
+            load_gen_cert("ibm.crt").to_der().unwrap()
+        );
+        assert_eq!(
+            p7.iter().map(|c| c.to_der().unwrap()).collect::<Vec<_>>(),
+            p7_der
+                .iter()
+                .map(|c| c.to_der().unwrap())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn is_url() {
+        assert!(super::is_url("https://example.com/ibm.crt"));
+        assert!(super::is_url("http://example.com/ibm.crt"));
+        assert!(!super::is_url("ibm.crt"));
+        assert!(!super::is_url("/tmp/https://ibm.crt"));
+    }
+
+    #[test]
+    fn fetch_https_no_https() {
+        assert!(matches!(
+            fetch_https("http://example.com/ibm.crl", None),
+            Err(Error::UrlNotHttps(_))
+        ));
+        assert!(matches!(
+            fetch_https("ibm.crl", None),
+            Err(Error::UrlNotHttps(_))
+        ));
+    }
+
+    #[test]
+    fn check_sha256() {
+        let crt = get_cert_asset("ibm.crt");
+        let digest = sha256(&crt);
+        assert!(super::check_sha256("url", &crt, None).is_ok());
+        assert!(super::check_sha256("url", &crt, Some(&digest)).is_ok());
+        assert!(matches!(
+            super::check_sha256("url", &crt, Some(&[0; 32])),
+            Err(Error::DownloadChecksum(_))
+        ));
+    }
+
+    #[test]
+    fn read_hkd() {
+        assert!(super::read_hkd(get_cert_asset_path("host.crt")).is_ok());
+        assert!(matches!(
+            super::read_hkd(get_cert_asset_path("ibm.crl")),
+            Err(Error::NoHkdInFile(_))
+        ));
+        assert_eq!(
+            super::read_hkd(get_cert_asset_path("ibm.p7b"))
+                .unwrap()
+                .to_der()
+                .unwrap(),
+            load_gen_cert("ibm.crt").to_der().unwrap()
+        );
+    }
+}
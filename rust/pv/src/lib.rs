@@ -33,14 +33,40 @@
 //!
 //! # Verify
 //! [`attest::AttestationItems`], [`attest::AttestationMeasurement`]
+//!
+//! # Key derivation
+//!
+//! The key derivations of the Ultravisor are public, so that custom provisioning flows need not
+//! reimplement them:
+//! - [`request::derive_aes256_gcm_key`] derives the key that wraps a request protection key for a
+//!   host key; [`request::Keyslot`] wraps it, e.g. the attestation request protection key (ARPK)
+//! - [`request::public_host_key_hash`] hashes a host key as stored in a key slot
+//! - [`secret::ExtSecret::derive`] derives the extension secret from the customer communication
+//!   key, using [`request::hkdf_rfc_5869`]
+//!
+//! # Secure Execution headers
+//!
+//! Read-only parsing of the Secure Execution header of existing images without the image-builder
+//! machinery of `pvimg`: [`se_hdr::read_se_hdr`] and [`se_hdr::SeHdrRef`]
+//!
+//! # Guest dumps
+//!
+//! Decryption of the CPU states and memory of a Secure Execution guest dump with the customer
+//! communication key: [`dump::DumpCompletion`]
 mod brcb;
 mod crypto;
 mod error;
+mod fips_mode;
+mod input;
+mod machine;
+mod measured_state;
 mod openssl_extensions;
 mod pem_utils;
 mod req;
-mod utils;
 mod uvattest;
+#[cfg(feature = "uvdevice")]
+mod uvaudit;
+mod uvdump;
 mod uvsecret;
 mod verify;
 
@@ -66,10 +92,42 @@ pub mod attest {
     pub use crate::uvattest::{
         additional::AdditionalData,
         arcb::{
-            AttestationAuthenticated, AttestationFlags, AttestationRequest, AttestationVersion,
+            AdditionalDataItem, AttestationAuthenticated, AttestationFlags, AttestationRequest,
+            AttestationVersion,
+        },
+        attest::{verify_cuid, AttestationItems, AttestationMeasurement},
+        batch::{BatchEntry, BatchReport, BatchSummary, Outcome, Verdict},
+        cache::{AttestationCache, CachedAttestation, NoncePolicy},
+        exchange::{
+            ExchangeExtension, ExchangeFormat, ExchangeFormatRequest, ExchangeFormatResponse,
+            ExchangeFormatVersion, ExchangeMagic,
         },
-        attest::{AttestationItems, AttestationMeasurement},
+        key_binding::KeyBinding,
     };
+
+    /// Native IBM Secure Execution attester for Confidential Containers
+    #[cfg(feature = "coco")]
+    pub mod coco {
+        pub use crate::uvattest::coco::{SeAttestationRequest, SeAttestationResponse, SeAttester};
+    }
+
+    /// IBM Secure Execution evidence for Keylime
+    #[cfg(feature = "keylime")]
+    pub mod keylime {
+        pub use crate::uvattest::keylime::{
+            KeylimeAgentAttester, KeylimeQuote, KeylimeVerification, KeylimeVerifier,
+            KEYLIME_EVIDENCE_TYPE,
+        };
+    }
+
+    /// IBM Secure Execution node attestor for SPIRE
+    #[cfg(feature = "spire")]
+    pub mod spire {
+        pub use crate::uvattest::spire::{
+            SpireAgentAttestor, SpireAgentAttributes, SpirePendingAttestation, SpireServerAttestor,
+            SPIRE_PLUGIN_NAME,
+        };
+    }
 }
 
 /// Definitions and functions to write objects in PEM format
@@ -81,7 +139,9 @@ pub mod pem {
 pub mod misc {
     pub use pv_core::misc::*;
 
-    pub use crate::utils::read_certs;
+    pub use crate::crypto::eq_ct;
+    pub use crate::input::{fetch_https, is_url, read_certs, read_crls, read_hkd, X509Format};
+    pub use crate::machine::MachineId;
 }
 
 pub use error::{Error, Result};
@@ -94,17 +154,28 @@ pub mod request {
     pub use crate::{
         brcb::{seek_se_hdr_start, BootHdrTags, SeImgMetaData},
         crypto::{
-            decrypt_aead, derive_aes256_gcm_key, encrypt_aead, gen_ec_key, random_array,
-            AeadDecryptionResult, AeadEncryptionResult, Aes256GcmKey, Aes256XtsKey, SymKey,
-            SymKeyType, SHA_512_HASH_LEN,
+            decrypt_aead, derive_aes256_gcm_key, encrypt_aead, gen_ec_key, hkdf_rfc_5869,
+            private_key_from_uri, random_array, sign_msg, verify_signature, AeadDecryptionResult,
+            AeadEncryptionResult, Aes256GcmKey, Aes256XtsKey, SymKey, SymKeyType, SHA_512_HASH_LEN,
+        },
+        req::{
+            public_host_key_hash, EcPubKeyCoord, Encrypt, HostKeyType, Keyslot, ReqEncrCtx, Request,
+        },
+        verify::{
+            CertInfo, CertVerifier, Check, CheckOutcome, CrlCache, CrlInfo, HkdReport, HkdVerifier,
+            NoVerifyHkd, PinInfo, VerificationReport, HKD_CHECKS,
         },
-        req::{EcPubKeyCoord, Encrypt, Keyslot, ReqEncrCtx, Request},
-        verify::{CertVerifier, HkdVerifier, NoVerifyHkd},
     };
 
     /// Reexports some useful OpenSSL symbols
     pub mod openssl {
-        pub use openssl::{error::ErrorStack, hash::DigestBytes, nid::Nid, pkey, x509};
+        pub use openssl::{
+            error::ErrorStack,
+            hash::{DigestBytes, MessageDigest},
+            init,
+            nid::Nid,
+            pkey, x509,
+        };
         // rust-OpenSSL does not define these NIDs
         #[allow(missing_docs)]
         pub const NID_ED25519: Nid = Nid::from_raw(openssl_sys::NID_ED25519);
@@ -115,12 +186,74 @@ pub mod request {
     pub use pv_core::request::*;
 }
 
+/// Read-only access to Secure Execution headers
+///
+/// Parses the binary Secure Execution header of an image without decrypting it. Suitable for
+/// tools that only need to inspect existing images, e.g. installers or image scanners.
+pub mod se_hdr {
+    pub use crate::brcb::{read_se_hdr, seek_se_hdr_start, BootHdrMagic, SeHdrRef, SeImgMetaData};
+    pub use pv_ffi::se_hdr::{SeHdrHead, SeHdrKeySlot};
+}
+
+/// Audit logging of Ultravisor operations
+#[cfg(feature = "uvdevice")]
+pub mod audit {
+    pub use crate::uvaudit::{AuditLog, AuditOp, AuditRecord, AuditTarget};
+}
+
+/// Decryption of Secure Execution guest dumps
+pub mod dump {
+    #[doc(hidden)]
+    pub use crate::uvdump::test_data;
+    pub use crate::uvdump::{
+        derive_dump_key, CpuState, DumpCompletion, PageState, COMPL_SECTION, MEM_META_SECTION,
+        NT_S390_PV_CPU_DATA, TWEAK_COMPONENT_SIZE,
+    };
+}
+
+/// Detection of the execution environment
+pub mod env {
+    pub use pv_core::env::*;
+}
+
+/// Log of verified attestation results for host integrity tooling
+pub mod evidence_log {
+    pub use crate::measured_state::{EvidenceLog, EvidenceRecord, EVIDENCE_LOG_PCR};
+}
+
+/// FIPS mode awareness and enforcement of the crypto layer
+pub mod fips {
+    pub use crate::fips_mode::{enforce_fips, fips_enabled, fips_enforced};
+}
+
+/// Functionalities for the kernel key management facility
+pub mod keyring {
+    pub use pv_core::keyring::*;
+}
+
+/// Functionalities to drop the privileges of the process
+pub mod privilege {
+    pub use pv_core::privilege::*;
+}
+
+/// Functionalities to sandbox the process
+pub mod sandbox {
+    pub use pv_core::sandbox::*;
+}
+
+/// Hypervisor information of the execution environment
+pub mod sthyi {
+    pub use pv_core::sthyi::*;
+}
+
 /// Functionalities for creating add-secret requests
 pub mod secret {
     pub use pv_core::secret::*;
 
     pub use crate::uvsecret::{
-        asrcb::{AddSecretFlags, AddSecretRequest, AddSecretVersion},
+        asrcb::{
+            AddSecretFlag, AddSecretFlags, AddSecretRequest, AddSecretRequestInfo, AddSecretVersion,
+        },
         ext_secret::ExtSecret,
         guest_secret::GuestSecret,
         retr_secret::{IbmProtectedKey, RetrievedSecret},
@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Identification of the IBM Z machines that host-key documents are issued for.
+
+use std::{fmt::Display, path::Path, str::FromStr};
+
+use openssl::{nid::Nid, x509::X509Ref};
+
+use crate::{Error, Result};
+
+const SYSINFO: &str = "/proc/sysinfo";
+/// Number of trailing serial-number characters that identify a machine
+const SERIAL_SUFFIX_LEN: usize = 5;
+
+/// Identity of an IBM Z machine (CEC): the machine type and the serial number
+///
+/// Serial numbers are compared case-insensitively by their last five characters, so
+/// that `02A1B2C`, `A1B2C`, and the sequence code `00000000000A1B2C` identify the same machine.
+/// The machine type is compared only if both identities specify one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineId {
+    machine_type: Option<String>,
+    serial: String,
+}
+
+fn is_alnum(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+impl MachineId {
+    /// Create a machine identity.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the type or the serial number are not
+    /// alphanumeric, or the serial number is shorter than five characters.
+    pub fn new(machine_type: Option<&str>, serial: &str) -> Result<Self> {
+        let invalid = || {
+            Error::InvalidMachineId(match machine_type {
+                Some(ty) => format!("{ty}-{serial}"),
+                None => serial.to_string(),
+            })
+        };
+        if !is_alnum(serial) || serial.len() < SERIAL_SUFFIX_LEN {
+            return Err(invalid());
+        }
+        if machine_type.is_some_and(|ty| !is_alnum(ty)) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            machine_type: machine_type.map(str::to_ascii_uppercase),
+            serial: serial.to_ascii_uppercase(),
+        })
+    }
+
+    /// The machine type, e.g. `3931`, if known
+    pub fn machine_type(&self) -> Option<&str> {
+        self.machine_type.as_deref()
+    }
+
+    /// The serial number
+    pub fn serial(&self) -> &str {
+        &self.serial
+    }
+
+    fn serial_suffix(&self) -> &str {
+        // new() ensures that the serial number is long enough
+        &self.serial[self.serial.len() - SERIAL_SUFFIX_LEN..]
+    }
+
+    /// Identity of the machine this program runs on.
+    ///
+    /// Returns `None` if `/proc/sysinfo` is not available or does not identify the machine, e.g.
+    /// on non-IBM Z systems.
+    pub fn current() -> Option<Self> {
+        std::fs::read_to_string(SYSINFO)
+            .ok()
+            .and_then(|s| Self::from_sysinfo(&s))
+    }
+
+    /// Parse the machine identity from the content of `/proc/sysinfo`.
+    ///
+    /// The serial number is the plant followed by the last five characters of the sequence code.
+    pub fn from_sysinfo(sysinfo: &str) -> Option<Self> {
+        let field = |name: &str| {
+            sysinfo.lines().find_map(|l| {
+                let (key, value) = l.split_once(':')?;
+                (key.trim() == name).then_some(value.trim())
+            })
+        };
+        let seq = field("Sequence Code")?;
+        let seq = seq.get(seq.len().checked_sub(SERIAL_SUFFIX_LEN)?..)?;
+        let serial = format!("{}{seq}", field("Plant").unwrap_or_default());
+        Self::new(field("Type"), &serial).ok()
+    }
+
+    /// The machine a host-key document is issued for, if the document identifies it.
+    ///
+    /// Uses the `serialNumber` entry of the subject of `hkd`. If there is none, the file name of
+    /// the document is used if it follows the `HKD-<type>-<serial>` naming scheme of the IBM
+    /// Resource Link.
+    pub fn of_hkd<P: AsRef<Path>>(hkd: &X509Ref, path: Option<P>) -> Option<Self> {
+        let from_subject = hkd
+            .subject_name()
+            .entries_by_nid(Nid::SERIALNUMBER)
+            .find_map(|e| Self::from_str(&e.data().as_utf8().ok()?).ok());
+        from_subject.or_else(|| Self::from_hkd_file_name(path?.as_ref()))
+    }
+
+    fn from_hkd_file_name(path: &Path) -> Option<Self> {
+        let name = path.file_stem()?.to_str()?;
+        let mut parts = name.split(['-', '_', '.']);
+        if !parts.next()?.eq_ignore_ascii_case("HKD") {
+            return None;
+        }
+        Self::new(Some(parts.next()?), parts.next()?).ok()
+    }
+
+    /// Returns `true` if `self` and `other` identify the same machine.
+    pub fn matches(&self, other: &Self) -> bool {
+        let same_type = match (&self.machine_type, &other.machine_type) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        same_type && self.serial_suffix() == other.serial_suffix()
+    }
+}
+
+impl FromStr for MachineId {
+    type Err = Error;
+
+    /// Parses `[TYPE-]SERIAL`, e.g. `3931-02A1B2C`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().split_once('-') {
+            Some((ty, serial)) => Self::new(Some(ty), serial),
+            None => Self::new(None, s.trim()),
+        }
+    }
+}
+
+impl Display for MachineId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.machine_type {
+            Some(ty) => write!(f, "{ty}-{}", self.serial),
+            None => write!(f, "{}", self.serial),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::load_gen_cert;
+
+    const SYSINFO: &str = "Manufacturer:         IBM
+Type:                 3931
+Model:                703              A01
+Sequence Code:        00000000000A1B2C
+Plant:                02
+";
+
+    #[test]
+    fn from_sysinfo() {
+        let id = MachineId::from_sysinfo(SYSINFO).unwrap();
+        assert_eq!(id.machine_type(), Some("3931"));
+        assert_eq!(id.serial(), "02A1B2C");
+        assert_eq!(id.to_string(), "3931-02A1B2C");
+        assert_eq!(MachineId::from_sysinfo("Type: 3931\n"), None);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "3931-02a1b2c".parse::<MachineId>().unwrap(),
+            MachineId::new(Some("3931"), "02A1B2C").unwrap()
+        );
+        assert_eq!("A1B2C".parse::<MachineId>().unwrap().machine_type(), None);
+        assert!(matches!(
+            "3931-A1B".parse::<MachineId>(),
+            Err(Error::InvalidMachineId(_))
+        ));
+        assert!("39 31-A1B2C".parse::<MachineId>().is_err());
+    }
+
+    #[test]
+    fn matches() {
+        let id = MachineId::from_sysinfo(SYSINFO).unwrap();
+        assert!(id.matches(&"A1B2C".parse().unwrap()));
+        assert!(id.matches(&"3931-00000000000A1B2C".parse().unwrap()));
+        assert!(!id.matches(&"8561-02A1B2C".parse().unwrap()));
+        assert!(!id.matches(&"3931-02A1B2D".parse().unwrap()));
+    }
+
+    #[test]
+    fn of_hkd() {
+        let hkd = load_gen_cert("host.crt");
+        assert_eq!(MachineId::of_hkd(&hkd, None::<&Path>), None);
+        assert_eq!(MachineId::of_hkd(&hkd, Some("host.crt")), None);
+        assert_eq!(
+            MachineId::of_hkd(&hkd, Some("/tmp/HKD-3931-02A1B2C.crt")),
+            Some("3931-02A1B2C".parse().unwrap())
+        );
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::Path,
+    sync::Mutex,
+};
+
+use openssl::sha::{sha1, sha256};
+use pv_core::{misc::encode_hex, uv::ConfigUid};
+
+use crate::Result;
+
+/// PCR index of the log entries; the same as the default of IMA
+pub const EVIDENCE_LOG_PCR: u32 = 10;
+/// Event name prefix of the log entries
+const EVENT_NAME: &str = "se-attestation";
+
+/// A verified attestation result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvidenceRecord<'a> {
+    cuid: &'a ConfigUid,
+    measurement: &'a [u8],
+    image: Option<&'a str>,
+}
+
+impl<'a> EvidenceRecord<'a> {
+    /// Create a record for the verified `measurement` of the guest with `cuid`.
+    pub fn new(cuid: &'a ConfigUid, measurement: &'a [u8]) -> Self {
+        Self {
+            cuid,
+            measurement,
+            image: None,
+        }
+    }
+
+    /// Add the name of the reference value that matched the measurement.
+    pub fn image(mut self, name: &'a str) -> Self {
+        self.image = Some(name);
+        self
+    }
+
+    /// Event name: `se-attestation` or `se-attestation:<image>`; without whitespace.
+    fn name(&self) -> String {
+        match self.image {
+            Some(image) => format!("{EVENT_NAME}:{image}").replace(char::is_whitespace, "_"),
+            None => EVENT_NAME.to_string(),
+        }
+    }
+
+    /// Event data: the Configuration Unique ID followed by the measurement
+    fn buf(&self) -> Vec<u8> {
+        [self.cuid.as_slice(), self.measurement].concat()
+    }
+
+    /// Format the record as entry of an IMA ASCII measurement list with the `ima-buf` template.
+    ///
+    /// The entry has the form `<pcr> <template hash> ima-buf sha256:<digest> <name> <data>`.
+    /// The template hash is the SHA-1 digest of the template data, as in the IMA measurement
+    /// list, so that tools can verify the entry.
+    pub fn ima_entry(&self) -> String {
+        let name = self.name();
+        let buf = self.buf();
+        let digest = sha256(&buf);
+
+        let mut d_ng = b"sha256:\0".to_vec();
+        d_ng.extend_from_slice(&digest);
+        let mut n_ng = name.as_bytes().to_vec();
+        n_ng.push(0);
+        let template: Vec<u8> = [d_ng.as_slice(), &n_ng, &buf]
+            .iter()
+            .flat_map(|field| {
+                let mut data = (field.len() as u32).to_le_bytes().to_vec();
+                data.extend_from_slice(field);
+                data
+            })
+            .collect();
+
+        format!(
+            "{EVIDENCE_LOG_PCR} {} ima-buf sha256:{} {name} {}",
+            encode_hex(sha1(&template)),
+            encode_hex(digest),
+            encode_hex(&buf)
+        )
+    }
+}
+
+/// Log of verified attestation results in the IMA measurement-list format
+///
+/// Host integrity tooling that understands IMA measurement lists can include the results of
+/// IBM Secure Execution attestations by reading this log. The entries are not extended into a
+/// TPM; the log is an append-only file.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use s390_pv::evidence_log::{EvidenceLog, EvidenceRecord};
+/// # fn main() -> s390_pv::Result<()> {
+/// # let (cuid, measurement) = ([0; 16], [0; 64]);
+/// let log = EvidenceLog::open("/var/log/se-evidence.log")?;
+/// log.append(&EvidenceRecord::new(&cuid, &measurement))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct EvidenceLog(Mutex<File>);
+
+impl EvidenceLog {
+    /// Open an evidence log.
+    ///
+    /// The file is opened in append mode and created with mode `0644` if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be opened.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .mode(0o644)
+            .open(path)
+            .map_err(|e| pv_core::Error::FileAccess {
+                ty: crate::FileAccessErrorType::Open,
+                path: path.to_path_buf(),
+                source: e,
+            })?;
+        Ok(Self(Mutex::new(file)))
+    }
+
+    /// Append `record` to the log.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the record cannot be written.
+    pub fn append(&self, record: &EvidenceRecord) -> Result<()> {
+        let entry = format!("{}\n", record.ima_entry());
+        // A poisoned lock only means another thread panicked while appending.
+        let mut file = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(entry.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ima_entry() {
+        let cuid = [0x11; 16];
+        let measurement = [0x22; 4];
+        let entry = EvidenceRecord::new(&cuid, &measurement)
+            .image("my image")
+            .ima_entry();
+        let fields: Vec<_> = entry.split(' ').collect();
+        let buf = [[0x11; 16].as_slice(), &[0x22; 4]].concat();
+        assert_eq!(fields.len(), 6);
+        assert_eq!(fields[0], "10");
+        assert_eq!(fields[1].len(), 40);
+        assert_eq!(fields[2], "ima-buf");
+        assert_eq!(fields[3], format!("sha256:{}", encode_hex(sha256(&buf))));
+        assert_eq!(fields[4], "se-attestation:my_image");
+        assert_eq!(fields[5], encode_hex(&buf));
+
+        // the template hash covers all fields
+        let other = EvidenceRecord::new(&cuid, &measurement).ima_entry();
+        assert_ne!(other.split(' ').nth(1).unwrap(), fields[1]);
+    }
+
+    #[test]
+    fn append() {
+        let path = std::env::temp_dir().join(format!("pv-evidence-test-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = EvidenceLog::open(&path).unwrap();
+        let record = EvidenceRecord::new(&[0; 16], &[1; 64]);
+        log.append(&record).unwrap();
+        log.append(&record).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(content, format!("{0}\n{0}\n", record.ima_entry()));
+    }
+}
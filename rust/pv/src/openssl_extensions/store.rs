@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::ffi::{c_void, CStr};
+
+use foreign_types::ForeignType;
+use openssl::{
+    error::ErrorStack,
+    pkey::{PKey, Private},
+};
+
+#[allow(non_camel_case_types)]
+mod ffi {
+    use std::ffi::{c_char, c_int, c_void};
+
+    pub enum OSSL_STORE_CTX {}
+    pub enum OSSL_STORE_INFO {}
+    pub enum UI_METHOD {}
+
+    pub const OSSL_STORE_INFO_PKEY: c_int = 4;
+
+    extern "C" {
+        pub fn UI_OpenSSL() -> *const UI_METHOD;
+        pub fn OSSL_STORE_open(
+            uri: *const c_char,
+            ui_method: *const UI_METHOD,
+            ui_data: *mut c_void,
+            post_process: *const c_void,
+            post_process_data: *mut c_void,
+        ) -> *mut OSSL_STORE_CTX;
+        pub fn OSSL_STORE_expect(ctx: *mut OSSL_STORE_CTX, expected_type: c_int) -> c_int;
+        pub fn OSSL_STORE_load(ctx: *mut OSSL_STORE_CTX) -> *mut OSSL_STORE_INFO;
+        pub fn OSSL_STORE_eof(ctx: *mut OSSL_STORE_CTX) -> c_int;
+        pub fn OSSL_STORE_error(ctx: *mut OSSL_STORE_CTX) -> c_int;
+        pub fn OSSL_STORE_close(ctx: *mut OSSL_STORE_CTX) -> c_int;
+        pub fn OSSL_STORE_INFO_get_type(info: *const OSSL_STORE_INFO) -> c_int;
+        pub fn OSSL_STORE_INFO_get1_PKEY(
+            info: *const OSSL_STORE_INFO,
+        ) -> *mut openssl_sys::EVP_PKEY;
+        pub fn OSSL_STORE_INFO_free(info: *mut OSSL_STORE_INFO);
+    }
+}
+
+struct StoreCtx(*mut ffi::OSSL_STORE_CTX);
+
+impl Drop for StoreCtx {
+    fn drop(&mut self) {
+        // SAFETY: Pointer is valid. The pointer value is dropped after the close.
+        unsafe {
+            ffi::OSSL_STORE_close(self.0);
+        }
+    }
+}
+
+/// Load the first private key found at `uri` using the OpenSSL store API.
+///
+/// Passphrases and PINs are requested on the terminal if needed.
+pub fn load_private_key_uri(uri: &CStr) -> Result<Option<PKey<Private>>, ErrorStack> {
+    openssl_sys::init();
+
+    // SAFETY: uri is a valid C-string; the UI method is a static OpenSSL object. No post
+    // processing callback is used. null-case is tested right after this.
+    let ctx = unsafe {
+        ffi::OSSL_STORE_open(
+            uri.as_ptr(),
+            ffi::UI_OpenSSL(),
+            std::ptr::null_mut::<c_void>(),
+            std::ptr::null(),
+            std::ptr::null_mut(),
+        )
+    };
+    if ctx.is_null() {
+        return Err(ErrorStack::get());
+    }
+    let ctx = StoreCtx(ctx);
+
+    // SAFETY: ctx is valid. Restricting the expected type is only an optimization hint for the
+    // loaders; an error is ignored as the type is checked below anyway.
+    unsafe { ffi::OSSL_STORE_expect(ctx.0, ffi::OSSL_STORE_INFO_PKEY) };
+
+    // SAFETY: ctx is valid for the whole loop. Every info object is freed after use.
+    unsafe {
+        while ffi::OSSL_STORE_eof(ctx.0) == 0 {
+            let info = ffi::OSSL_STORE_load(ctx.0);
+            if info.is_null() {
+                // A failing loader may never reach the end of the store.
+                if ffi::OSSL_STORE_error(ctx.0) != 0 {
+                    return Err(ErrorStack::get());
+                }
+                continue;
+            }
+            let key = match ffi::OSSL_STORE_INFO_get_type(info) {
+                ffi::OSSL_STORE_INFO_PKEY => ffi::OSSL_STORE_INFO_get1_PKEY(info),
+                _ => std::ptr::null_mut(),
+            };
+            ffi::OSSL_STORE_INFO_free(info);
+            if !key.is_null() {
+                return Ok(Some(PKey::from_ptr(key)));
+            }
+        }
+    }
+    // Errors of the loaders that did not fail the load must not stay on the error stack.
+    let _ = ErrorStack::get();
+    Ok(None)
+}
@@ -3,13 +3,15 @@
 // Copyright IBM Corp. 2023
 use std::mem::size_of;
 
+use log::warn;
 use openssl::{
     bn::{BigNum, BigNumContext},
     ec::{EcGroup, EcGroupRef, EcKey, EcPointRef},
     error::ErrorStack,
     hash::{DigestBytes, MessageDigest},
     nid::Nid,
-    pkey::{PKey, PKeyRef, Private, Public},
+    pkey::{HasPublic, PKey, PKeyRef, Private, Public},
+    x509::X509Ref,
 };
 use pv_core::request::{RequestMagic, RequestVersion};
 use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, KnownLayout, U32};
@@ -89,6 +91,104 @@ impl Keyslot {
     pub fn new(hostkey: PKey<Public>) -> Self {
         Self(hostkey)
     }
+
+    /// Creates a new Keyslot from the provided public key, if its key type is supported.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::UnsupportedHostKey`] if the key type is not a
+    /// [`HostKeyType`].
+    pub fn try_new(hostkey: PKey<Public>) -> Result<Self> {
+        HostKeyType::of(&hostkey)?;
+        Ok(Self(hostkey))
+    }
+
+    /// Type of the host-key of this Keyslot
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::UnsupportedHostKey`] if the key type is not a
+    /// [`HostKeyType`].
+    pub fn key_type(&self) -> Result<HostKeyType> {
+        HostKeyType::of(&self.0)
+    }
+}
+
+/// Key types of host-keys
+///
+/// The key type determines the key exchange and the wrapping parameters of key slots. All
+/// host-keys of one request must have the same type, as the request contains a single customer
+/// public key.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HostKeyType {
+    /// ECDH on curve NIST P-521 (secp521r1)
+    #[default]
+    EcSecp521r1,
+}
+
+impl HostKeyType {
+    /// All supported host-key types
+    pub const ALL: &'static [Self] = &[Self::EcSecp521r1];
+
+    /// Determine the type of a host-key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::UnsupportedHostKey`] if the key is not of a supported
+    /// type.
+    pub fn of<T: HasPublic>(key: &PKeyRef<T>) -> Result<Self> {
+        let curve = match key.ec_key() {
+            Ok(k) => k.group().curve_name(),
+            Err(_) => {
+                let name = Nid::from_raw(key.id().as_raw()).short_name();
+                return Err(Error::UnsupportedHostKey(
+                    name.unwrap_or("unknown").to_string(),
+                ));
+            }
+        };
+        Self::ALL
+            .iter()
+            .find(|ty| Some(ty.curve()) == curve)
+            .copied()
+            .ok_or_else(|| {
+                Error::UnsupportedHostKey(match curve.map(|c| c.short_name()) {
+                    Some(Ok(name)) => format!("EC ({name})"),
+                    _ => "EC (unknown curve)".to_string(),
+                })
+            })
+    }
+
+    /// Elliptic curve used for the key exchange
+    pub fn curve(self) -> Nid {
+        match self {
+            Self::EcSecp521r1 => Nid::SECP521R1,
+        }
+    }
+
+    /// Size in bytes of one coordinate of a public key in key slots and request headers
+    pub fn coord_size(self) -> usize {
+        match self {
+            Self::EcSecp521r1 => ECDH_PUB_KEY_COORD_POINT_SIZE,
+        }
+    }
+
+    /// Generate a customer private key matching this host-key type.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if OpenSSL could not generate the key.
+    pub fn generate_key(self) -> Result<PKey<Private>> {
+        gen_ec_key(self.curve())
+    }
+}
+
+impl std::fmt::Display for HostKeyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EcSecp521r1 => write!(f, "EC (secp521r1)"),
+        }
+    }
 }
 
 impl Encrypt for Keyslot {
@@ -109,6 +209,11 @@ impl Encrypt for Keyslot {
         priv_key: &PKeyRef<Private>,
         to: &mut Vec<u8>,
     ) -> Result<()> {
+        // Wrapping parameters depend on the key type. The customer key must use the same one.
+        let key_type = self.key_type()?;
+        if HostKeyType::of(priv_key).ok() != Some(key_type) {
+            return Err(Error::HostKeyTypeMismatch(key_type));
+        }
         let derived_key = derive_aes256_gcm_key(priv_key, &self.0)?;
         let mut wrpk_and_kst =
             encrypt_aead(&derived_key.into(), &[0; 12], &[], prot_key)?.into_buf();
@@ -140,15 +245,20 @@ impl ReqEncrCtx {
     ///
     /// # Errors
     ///
-    /// This function will return an error if OpenSSL could not generate a random value.
+    /// This function will return an error if OpenSSL could not generate a random value or FIPS
+    /// mode is enforced but OpenSSL is not in FIPS mode.
     pub fn new_aes_256<I, P, S>(iv: I, priv_key: P, prot_key: S) -> Result<Self>
     where
         I: Into<Option<[u8; 12]>>,
         P: Into<Option<PKey<Private>>>,
         S: Into<Option<SymKey>>,
     {
+        crate::fips_mode::check()?;
         let iv = iv.into().unwrap_or(random_array()?);
-        let priv_key = priv_key.into().unwrap_or(gen_ec_key(Nid::SECP521R1)?);
+        let priv_key = match priv_key.into() {
+            Some(key) => key,
+            None => HostKeyType::default().generate_key()?,
+        };
         let prot_key = prot_key
             .into()
             .unwrap_or(SymKey::random(SymKeyType::Aes256Gcm)?);
@@ -301,6 +411,20 @@ impl EcPubKeyCoord {
     }
 }
 
+/// Calculate the public host-key hash (PHKH) of a host-key document.
+///
+/// The PHKH identifies the host key in the key slots of SE headers and requests and is part of
+/// the additional data of attestation responses, if requested.
+///
+/// # Errors
+///
+/// This function will return an error if the document does not contain an EC public key.
+pub fn public_host_key_hash(hkd: &X509Ref) -> Result<[u8; Keyslot::PHKH_SIZE as usize]> {
+    let coord: EcPubKeyCoord = hkd.public_key()?.as_ref().try_into()?;
+    // Panic: will not panic, SHA256 digests are 32 bytes long
+    Ok(coord.sha256()?.as_ref().try_into().unwrap())
+}
+
 /// Get the pub ECDH coordinates in the format the Ultravisor expects it:
 /// The two coordinates are padded to 80 bytes each.
 fn get_pub_ecdh_points(pkey: &EcPointRef, grp: &EcGroupRef) -> Result<[u8; 160], ErrorStack> {
@@ -318,7 +442,7 @@ impl TryFrom<EcPubKeyCoord> for PKey<Public> {
 
     fn try_from(value: EcPubKeyCoord) -> Result<Self, Self::Error> {
         let ecdh = value.as_ref();
-        let grp = EcGroup::from_curve_name(Nid::SECP521R1)?;
+        let grp = EcGroup::from_curve_name(HostKeyType::EcSecp521r1.curve())?;
         let x = BigNum::from_slice(&ecdh[..ECDH_PUB_KEY_COORD_POINT_SIZE])?;
         let y = BigNum::from_slice(&ecdh[ECDH_PUB_KEY_COORD_POINT_SIZE..])?;
         let ec_key = EcKey::from_public_key_affine_coordinates(&grp, &x, &y)?;
@@ -427,6 +551,46 @@ pub trait Request {
     ///
     /// Must be called at least once, otherwise {`Request::encrypt`} will fail
     fn add_hostkey(&mut self, hostkey: PKey<Public>);
+
+    /// Number of host-keys added to this request
+    fn num_hostkeys(&self) -> usize;
+
+    /// Maximum number of host-keys, i.e., target machines or host-key generations, of this request
+    ///
+    /// The request header stores the number of key slots in one byte. The Ultravisor rejects
+    /// requests with more key slots than their version supports.
+    fn max_hostkeys(&self) -> usize {
+        u8::MAX as usize
+    }
+
+    /// Add several host-keys to this request, e.g. the current and a backup host-key of a machine,
+    /// or the old and the new host-key during a staged host-key rollover.
+    ///
+    /// The Ultravisor can decrypt the request with any of the host-keys. Host-keys that are
+    /// already part of `hostkeys` are skipped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::ManyHostkeys`] and adds no host-key if the request would
+    /// exceed [`Request::max_hostkeys`].
+    fn add_hostkeys<I>(&mut self, hostkeys: I) -> Result<()>
+    where
+        I: IntoIterator<Item = PKey<Public>>,
+        Self: Sized,
+    {
+        let mut unique: Vec<PKey<Public>> = vec![];
+        for hk in hostkeys {
+            match unique.iter().any(|u| u.public_eq(&hk)) {
+                true => warn!("Skipping a host-key that was specified more than once"),
+                false => unique.push(hk),
+            }
+        }
+        if self.num_hostkeys() + unique.len() > self.max_hostkeys() {
+            return Err(Error::ManyHostkeys);
+        }
+        unique.into_iter().for_each(|hk| self.add_hostkey(hk));
+        Ok(())
+    }
 }
 
 /// A struct to represent some parts of a binary/encrypted request.
@@ -440,6 +604,7 @@ pub(crate) struct BinReqValues<'a> {
     tag: &'a [u8],
     version: u32,
     len: usize,
+    nks: u8,
 }
 impl<'a> BinReqValues<'a> {
     pub(crate) const TAG_LEN: usize = SymKeyType::AES_256_GCM_TAG_LEN;
@@ -453,7 +618,7 @@ impl<'a> BinReqValues<'a> {
         let rql = hdr.rql.get() as usize;
         let sea = hdr.sea.get() as usize;
 
-        if rql < req.len() || sea + Self::TAG_LEN > rql {
+        if rql > req.len() || sea + Self::TAG_LEN > rql {
             return Err(Error::BinRequestSmall);
         }
         let aad_size = rql - sea - Self::TAG_LEN;
@@ -465,7 +630,7 @@ impl<'a> BinReqValues<'a> {
         let aad = &req[..aad_size];
         let req_dep_aad = &req[size_of::<RequestHdr>()..aad_size];
         let encr = &req[aad_size..(aad_size + sea)];
-        let tag = &req[rql - Self::TAG_LEN..];
+        let tag = &req[rql - Self::TAG_LEN..rql];
 
         Ok(Self {
             iv,
@@ -475,6 +640,7 @@ impl<'a> BinReqValues<'a> {
             tag,
             version: hdr.rqvn.get(),
             len: rql,
+            nks: hdr.nks,
         })
     }
 
@@ -488,6 +654,11 @@ impl<'a> BinReqValues<'a> {
         self.len
     }
 
+    /// Returns the number of keyslots of this [`BinReqValues`].
+    pub(crate) fn nks(&self) -> u8 {
+        self.nks
+    }
+
     /// Returns the size of the encrypted area
     pub(crate) fn sea(&self) -> u32 {
         self.encr.len() as u32
@@ -508,7 +679,19 @@ impl<'a> BinReqValues<'a> {
     where
         T: FromBytes + Sized + Immutable + KnownLayout,
     {
-        T::ref_from_prefix(self.req_dep_aad).map(|s| s.0).ok()
+        self.req_dep_aad_at(0)
+    }
+
+    /// Returns a reference to the request dependent authenticated area of this [`BinReqValues`]
+    /// starting at `offset` already interpreted.
+    ///
+    /// If target struct does not fit into the request depended-AAD None is returned.
+    pub(crate) fn req_dep_aad_at<T>(&self, offset: usize) -> Option<&T>
+    where
+        T: FromBytes + Sized + Immutable + KnownLayout,
+    {
+        let aad = self.req_dep_aad.get(offset..)?;
+        T::ref_from_prefix(aad).map(|s| s.0).ok()
     }
 
     /// Returns a reference to the tag of this [`BinReqValues`].
@@ -519,6 +702,8 @@ impl<'a> BinReqValues<'a> {
 
 #[cfg(test)]
 mod tests {
+    use proptest::{collection::vec, num::u8, proptest};
+
     use super::*;
     use crate::{get_test_asset, request::SymKey, test_utils::*};
 
@@ -555,6 +740,52 @@ mod tests {
         assert_eq!(&aad, &aad_exp);
     }
 
+    #[test]
+    fn host_key_type() {
+        let (cust_key, host_key) = get_test_keys();
+        assert_eq!(
+            HostKeyType::of(&host_key).unwrap(),
+            HostKeyType::EcSecp521r1
+        );
+        assert_eq!(
+            Keyslot::new(host_key).key_type().unwrap(),
+            HostKeyType::EcSecp521r1
+        );
+
+        let p256 = gen_ec_key(Nid::X9_62_PRIME256V1).unwrap();
+        let err = HostKeyType::of(&p256).unwrap_err();
+        assert!(matches!(&err, Error::UnsupportedHostKey(ty) if ty == "EC (prime256v1)"));
+        assert_eq!(
+            err.to_string(),
+            "Unsupported host-key type: EC (prime256v1). Supported are: EC (secp521r1)"
+        );
+        let p256 = PKey::public_key_from_der(&p256.public_key_to_der().unwrap()).unwrap();
+        assert!(matches!(
+            Keyslot::try_new(p256.clone()),
+            Err(Error::UnsupportedHostKey(_))
+        ));
+        assert!(matches!(
+            Keyslot::new(p256).encrypt(&[0; 32], &cust_key),
+            Err(Error::UnsupportedHostKey(_))
+        ));
+
+        let rsa = PKey::from_rsa(openssl::rsa::Rsa::generate(2048).unwrap()).unwrap();
+        assert!(matches!(
+            HostKeyType::of(&rsa),
+            Err(Error::UnsupportedHostKey(ty)) if ty == "rsaEncryption"
+        ));
+    }
+
+    #[test]
+    fn host_key_type_mismatch() {
+        let (_, host_key) = get_test_keys();
+        let cust_key = gen_ec_key(Nid::X9_62_PRIME256V1).unwrap();
+        assert!(matches!(
+            Keyslot::new(host_key).encrypt(&[0; 32], &cust_key),
+            Err(Error::HostKeyTypeMismatch(HostKeyType::EcSecp521r1))
+        ));
+    }
+
     #[test]
     fn encr_build_aad_nks_no() {
         let ctx = ReqEncrCtx::new_aes_256(Some([0x11; 12]), None, None).unwrap();
@@ -667,6 +898,14 @@ mod tests {
         assert_eq!(hash.as_ref(), &exp);
     }
 
+    #[test]
+    fn phkh() {
+        let (_, host) = get_test_key_and_cert();
+        let exp: EcPubKeyCoord = host.public_key().unwrap().try_into().unwrap();
+        let phkh = public_host_key_hash(&host).unwrap();
+        assert_eq!(phkh.as_slice(), exp.sha256().unwrap().as_ref());
+    }
+
     #[test]
     fn conversion_ecdh_and_vice_versa() {
         let (_, cust_pub) = get_test_keys();
@@ -689,4 +928,21 @@ mod tests {
         let cust_pub_back: PKey<Public> = phk.try_into().unwrap();
         assert!(cust_pub.public_eq(&cust_pub_back));
     }
+
+    proptest! {
+        #[test]
+        fn bin_req_values_no_panic(
+            mut req in vec(u8::ANY, 0..0x200),
+            rql in 0..0x400u32,
+            sea in 0..0x400u32,
+        ) {
+            if req.len() >= size_of::<RequestHdr>() {
+                req[0x0c..0x10].copy_from_slice(&rql.to_be_bytes());
+                req[0x2c..0x30].copy_from_slice(&sea.to_be_bytes());
+            }
+            if let Ok(values) = BinReqValues::get(&req) {
+                assert!(values.len() <= req.len());
+            }
+        }
+    }
 }
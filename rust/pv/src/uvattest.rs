@@ -5,5 +5,37 @@
 pub mod additional;
 pub mod arcb;
 pub mod attest;
+pub mod batch;
+pub mod cache;
+#[cfg(feature = "coco")]
+pub mod coco;
+pub mod exchange;
+pub mod key_binding;
+#[cfg(feature = "keylime")]
+pub mod keylime;
+#[cfg(feature = "spire")]
+pub mod spire;
 
 type AttNonce = [u8; 16];
+
+/// Base64 (de)serialization of byte arrays, as used by the attester plugins
+#[cfg(any(feature = "coco", feature = "keylime", feature = "spire"))]
+mod b64 {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(v: &T, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_str(&STANDARD.encode(v))
+    }
+
+    pub fn deserialize<'de, D, T>(de: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<Vec<u8>>,
+    {
+        let s = String::deserialize(de)?;
+        let v = STANDARD.decode(s).map_err(D::Error::custom)?;
+        let len = v.len();
+        T::try_from(v).map_err(|_| D::Error::custom(format!("invalid length {len}")))
+    }
+}
@@ -9,7 +9,7 @@ use crate::req::Keyslot;
 use crate::static_assert;
 use crate::{Error, Result};
 
-use super::arcb::AttestationFlags;
+use super::arcb::{AdditionalDataItem, AttestationFlags};
 
 /// Hash for additional-data stuff used for parsing [`AdditionalData`]
 pub(super) const PHKH_SIZE: u32 = 0x20;
@@ -64,17 +64,11 @@ where
     }
 }
 
-fn read_value<'a>(
-    data: &'a [u8],
-    size: u32,
-    read: bool,
-    name: &'static str,
-) -> Result<(Option<&'a [u8]>, &'a [u8])> {
+fn read_value<'a>(data: &'a [u8], size: u32, name: &'static str) -> Result<(&'a [u8], &'a [u8])> {
     let size = size as usize;
-    match read {
-        true if data.len() >= size => Ok((Some(&data[..size]), &data[size..])),
-        true => Err(Error::AddDataMissing(name)),
-        false => Ok((None, data)),
+    match data.len() >= size {
+        true => Ok(data.split_at(size)),
+        false => Err(Error::AddDataMissing(name)),
     }
 }
 
@@ -165,32 +159,40 @@ impl<'a> AdditionalData<&'a [u8]> {
     /// Fails if there is a mismatch between the data and the flags. Should not happen after a
     /// successful attestation verification.
     pub fn from_slice(data: &'a [u8], flags: &AttestationFlags) -> Result<Self> {
-        let (image_phkh, data) = read_value(data, PHKH_SIZE, flags.image_phkh(), "Image PHKH")?;
-        let (attestation_phkh, data) =
-            read_value(data, PHKH_SIZE, flags.attest_phkh(), "Attestation PHKH")?;
-        let (secret_store_hash, data) = read_value(
-            data,
-            SECRET_STORE_HASH_SIZE,
-            flags.secret_store_hash(),
-            "Secret store state",
-        )?;
-        let (firmware_state, data) =
-            read_value(data, FW_STATE_SIZE, flags.firmware_state(), "Firmware hash")?;
-        let unrecognized = (!data.is_empty()).then_some(data);
+        let mut add = Self {
+            image_phkh: None,
+            attestation_phkh: None,
+            secret_store_hash: None,
+            firmware_state: None,
+            unrecognized: None,
+        };
+        let mut data = data;
+        for item in flags.additional_data_selection() {
+            let (value, rest) = read_value(data, item.size(), item.name())?;
+            *add.field_mut(item) = Some(value);
+            data = rest;
+        }
+        add.unrecognized = (!data.is_empty()).then_some(data);
+        Ok(add)
+    }
+}
 
-        Ok(Self {
-            image_phkh,
-            attestation_phkh,
-            secret_store_hash,
-            firmware_state,
-            unrecognized,
-        })
+impl<T: Serialize> AdditionalData<T> {
+    fn field_mut(&mut self, item: AdditionalDataItem) -> &mut Option<T> {
+        match item {
+            AdditionalDataItem::ImagePhkh => &mut self.image_phkh,
+            AdditionalDataItem::AttestationPhkh => &mut self.attestation_phkh,
+            AdditionalDataItem::SecretStoreHash => &mut self.secret_store_hash,
+            AdditionalDataItem::FirmwareState => &mut self.firmware_state,
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use proptest::{collection::vec, num, proptest};
     use serde_test::Token;
+    use zerocopy::FromBytes;
 
     use super::*;
     #[test]
@@ -306,4 +308,12 @@ mod test {
             ],
         );
     }
+
+    proptest! {
+        #[test]
+        fn from_slice_no_panic(data in vec(num::u8::ANY, 0..0x400), flags in num::u64::ANY) {
+            let flags = AttestationFlags::read_from_bytes(&flags.to_be_bytes()).unwrap();
+            let _ = AdditionalData::from_slice(&data, &flags);
+        }
+    }
 }
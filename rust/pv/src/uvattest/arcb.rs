@@ -14,7 +14,7 @@ use crate::{
     req::{Aad, BinReqValues, Keyslot, ReqEncrCtx},
     request::{Confidential, MagicValue, Request, RequestVersion, SymKey, Zeroize},
     static_assert,
-    uv::UvFlags,
+    uv::{UvFlags, UvQueryInfo},
     Error, Result,
 };
 
@@ -69,8 +69,8 @@ use crate::{
 /// // IBM issued HKD certificates typically have one X509
 /// let hkd = hkd.first().unwrap().public_key()?;
 /// arcb.add_hostkey(hkd);
-/// // you can add multiple hostkeys
-/// // arcb.add_hostkey(another_hkd);
+/// // you can add multiple hostkeys, e.g. of a backup machine
+/// // arcb.add_hostkeys([another_hkd, backup_hkd])?;
 /// // encrypt it
 /// let ctx = ReqEncrCtx::random(SymKeyType::Aes256)?;
 /// let arcb = arcb.encrypt(&ctx)?;
@@ -117,6 +117,91 @@ impl AttestationRequest {
         &self.aad.flags
     }
 
+    /// Returns the measurement algorithm of this [`AttestationRequest`].
+    pub fn measurement_alg(&self) -> AttestationMeasAlg {
+        self.aad.mai()
+    }
+
+    /// Select the measurement algorithm.
+    ///
+    /// If `supported` is given, e.g. from [`AttestationMeasAlg::uv_supported`], the algorithm is
+    /// only selected if the Ultravisor supports it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Ultravisor does not support the algorithm.
+    pub fn set_measurement_alg(
+        &mut self,
+        mai: AttestationMeasAlg,
+        supported: Option<&[AttestationMeasAlg]>,
+    ) -> Result<&mut Self> {
+        if supported.is_some_and(|s| !s.contains(&mai)) {
+            return Err(Error::MaiUnsupported(mai.name()));
+        }
+        self.aad.mai = mai.into();
+        Ok(self)
+    }
+
+    /// Request an additional-data item.
+    ///
+    /// If `supported` is given, e.g. from [`AttestationFlags::uv_supported`], the item is only
+    /// requested if the Ultravisor supports it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Ultravisor does not support the item.
+    pub fn request_additional(
+        &mut self,
+        item: AdditionalDataItem,
+        supported: Option<&AttestationFlags>,
+    ) -> Result<&mut Self> {
+        if supported.is_some_and(|s| !s.additional(item)) {
+            return Err(Error::AddDataUnsupported(item.name()));
+        }
+        self.aad.flags.set_additional(item);
+        Ok(self)
+    }
+
+    /// Request the image public host-key hash as additional data.
+    ///
+    /// See [`AttestationRequest::request_additional`].
+    pub fn request_image_phkh(
+        &mut self,
+        supported: Option<&AttestationFlags>,
+    ) -> Result<&mut Self> {
+        self.request_additional(AdditionalDataItem::ImagePhkh, supported)
+    }
+
+    /// Request the attestation public host-key hash as additional data.
+    ///
+    /// See [`AttestationRequest::request_additional`].
+    pub fn request_attest_phkh(
+        &mut self,
+        supported: Option<&AttestationFlags>,
+    ) -> Result<&mut Self> {
+        self.request_additional(AdditionalDataItem::AttestationPhkh, supported)
+    }
+
+    /// Request the secret store hash as additional data.
+    ///
+    /// See [`AttestationRequest::request_additional`].
+    pub fn request_secret_store_hash(
+        &mut self,
+        supported: Option<&AttestationFlags>,
+    ) -> Result<&mut Self> {
+        self.request_additional(AdditionalDataItem::SecretStoreHash, supported)
+    }
+
+    /// Request the firmware state as additional data.
+    ///
+    /// See [`AttestationRequest::request_additional`].
+    pub fn request_firmware_state(
+        &mut self,
+        supported: Option<&AttestationFlags>,
+    ) -> Result<&mut Self> {
+        self.request_additional(AdditionalDataItem::FirmwareState, supported)
+    }
+
     /// Returns a copy of the confidential data of this [`AttestationRequest`].
     ///
     /// Gives a copy of the confidential data of this request for further
@@ -142,7 +227,7 @@ impl AttestationRequest {
     }
 
     /// Checks for magic and returns [`BinReqValues`]
-    fn bin_values(arcb: &[u8]) -> Result<BinReqValues> {
+    fn bin_values(arcb: &[u8]) -> Result<BinReqValues<'_>> {
         if !AttestationMagic::starts_with_magic(arcb) {
             return Err(Error::NoArcb);
         }
@@ -159,10 +244,12 @@ impl AttestationRequest {
     ///
     /// # Error
     ///
-    /// Returns an error if the request is malformed.
+    /// Returns an error if the request is malformed or uses an unknown measurement algorithm.
     pub fn auth_bin(arcb: &[u8]) -> Result<AttestationAuthenticated> {
         let values = Self::bin_values(arcb)?;
         let auth: &AttestationAuthenticated = values.req_dep_aad().ok_or(Error::BinRequestSmall)?;
+        // uphold the invariant of `AttestationAuthenticated::mai`
+        AttestationMeasAlg::try_from(auth.mai)?;
         Ok(auth.to_owned())
     }
 
@@ -180,10 +267,8 @@ impl AttestationRequest {
         let values = Self::bin_values(arcb)?;
         let auth = Self::auth_bin(arcb)?;
 
-        let mai = auth.mai.try_into()?;
-        let keysize = match mai {
-            v @ AttestationMeasAlg::HmacSha512 => v.exp_size(),
-        } as usize;
+        let mai: AttestationMeasAlg = auth.mai.try_into()?;
+        let keysize = mai.exp_size() as usize;
 
         if keysize > values.sea() as usize {
             return Err(Error::BinArcbSeaSmall(values.sea()));
@@ -246,6 +331,10 @@ impl Request for AttestationRequest {
     fn add_hostkey(&mut self, hostkey: PKey<Public>) {
         self.keyslots.push(Keyslot::new(hostkey))
     }
+
+    fn num_hostkeys(&self) -> usize {
+        self.keyslots.len()
+    }
 }
 
 /// Versions for [`AttestationRequest`]
@@ -308,6 +397,59 @@ impl AttestationAuthenticated {
     }
 }
 
+/// Optional item of the additional data of an attestation response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AdditionalDataItem {
+    /// Public host-key hash of the key that unpacked the image
+    ImagePhkh,
+    /// Public host-key hash of the key that unpacked the attestation request
+    AttestationPhkh,
+    /// Hash of the state of the secret store
+    SecretStoreHash,
+    /// State of selected firmware parts
+    FirmwareState,
+}
+
+impl AdditionalDataItem {
+    /// All items in the order they appear in the additional data
+    pub const ALL: [Self; 4] = [
+        Self::ImagePhkh,
+        Self::AttestationPhkh,
+        Self::SecretStoreHash,
+        Self::FirmwareState,
+    ];
+
+    const fn bit(&self) -> u8 {
+        match self {
+            Self::ImagePhkh => 2,
+            Self::AttestationPhkh => 3,
+            Self::SecretStoreHash => 4,
+            Self::FirmwareState => 5,
+        }
+    }
+
+    /// Returns the size of this item in the additional data.
+    pub const fn size(&self) -> u32 {
+        AttestationFlags::FLAG_TO_ADD_SIZE[self.bit() as usize]
+    }
+
+    /// Returns a human readable name of this item.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::ImagePhkh => "Image PHKH",
+            Self::AttestationPhkh => "Attestation PHKH",
+            Self::SecretStoreHash => "Secret store hash",
+            Self::FirmwareState => "Firmware state",
+        }
+    }
+}
+
+impl std::fmt::Display for AdditionalDataItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 /// Attestation flags
 #[repr(C)]
 #[derive(Default, Debug, IntoBytes, FromBytes, Clone, Copy, Immutable)]
@@ -333,6 +475,41 @@ impl AttestationFlags {
             .fold(0, |size, (b, s)| size + self.0.is_set(b as u8) as u32 * s)
     }
 
+    /// Returns the attestation flags the Ultravisor supports.
+    ///
+    /// Returns [`None`] if the kernel does not provide this information, e.g. because this is no
+    /// Secure Execution guest.
+    pub fn uv_supported(query: &UvQueryInfo) -> Option<Self> {
+        query.supp_att_pflags().map(|f| Self(f.into()))
+    }
+
+    /// Request the additional-data item `item`.
+    pub fn set_additional(&mut self, item: AdditionalDataItem) {
+        self.0.set_bit(item.bit());
+    }
+
+    /// Check whether the additional-data item `item` is requested.
+    pub fn additional(&self, item: AdditionalDataItem) -> bool {
+        self.0.is_set(item.bit())
+    }
+
+    /// Returns the requested additional-data items in the order they appear in the additional
+    /// data.
+    pub fn additional_data_selection(&self) -> Vec<AdditionalDataItem> {
+        AdditionalDataItem::ALL
+            .into_iter()
+            .filter(|item| self.additional(*item))
+            .collect()
+    }
+
+    /// Returns the requested additional-data items that are not set in `supported`.
+    pub fn unsupported_additional(&self, supported: &Self) -> Vec<AdditionalDataItem> {
+        self.additional_data_selection()
+            .into_iter()
+            .filter(|item| !supported.additional(*item))
+            .collect()
+    }
+
     /// Flag 1 - use a nonce
     ///
     /// This attestation implementation forces the use of a nonce, so this will always be on and
@@ -346,12 +523,12 @@ impl AttestationFlags {
     /// Asks the Ultravisor to provide the host-key hash that unpacked the SE-image to be added in
     /// additional data. Requires 32 bytes.
     pub fn set_image_phkh(&mut self) {
-        self.0.set_bit(2);
+        self.set_additional(AdditionalDataItem::ImagePhkh)
     }
 
     /// Check weather the image public host key hash flag is on
     pub fn image_phkh(&self) -> bool {
-        self.0.is_set(2)
+        self.additional(AdditionalDataItem::ImagePhkh)
     }
 
     /// Flag 3 - request the attestation public host-key hash
@@ -359,36 +536,36 @@ impl AttestationFlags {
     /// Asks the Ultravisor to provide the host-key hash that unpacked the attestation request to
     /// be added in additional data. Requires 32 bytes.
     pub fn set_attest_phkh(&mut self) {
-        self.0.set_bit(3);
+        self.set_additional(AdditionalDataItem::AttestationPhkh)
     }
 
     /// Check weather the attestation public host key hash flag is on
     pub fn attest_phkh(&self) -> bool {
-        self.0.is_set(3)
+        self.additional(AdditionalDataItem::AttestationPhkh)
     }
 
     /// Flag 4 - request the state of the secret store
     ///
     /// Asks the Ultravisor to provide the hash of the added secret requests. Requires 64 bytes.
     pub fn set_secret_store_hash(&mut self) {
-        self.0.set_bit(4);
+        self.set_additional(AdditionalDataItem::SecretStoreHash)
     }
 
     /// Check weather the hash of the added secret requests flag is on
     pub fn secret_store_hash(&self) -> bool {
-        self.0.is_set(4)
+        self.additional(AdditionalDataItem::SecretStoreHash)
     }
 
     /// Flag 5 - request the firmware hash
     ///
     /// Asks the Ultravisor to provide the hash of the firmware. Requires 320 bytes.
     pub fn set_firmware_state(&mut self) {
-        self.0.set_bit(5);
+        self.set_additional(AdditionalDataItem::FirmwareState)
     }
 
     /// Check weather the hash of the added secret requests flag is on
     pub fn firmware_state(&self) -> bool {
-        self.0.is_set(5)
+        self.additional(AdditionalDataItem::FirmwareState)
     }
 }
 
@@ -418,6 +595,8 @@ impl Zeroize for ReqConfData {
 
 #[cfg(test)]
 mod test {
+    use proptest::{collection::vec, num::u8, proptest};
+
     use super::*;
 
     use crate::{get_test_asset, request::SymKey, test_utils::get_test_keys};
@@ -454,6 +633,58 @@ mod test {
         arcb.encrypt(&ctx).unwrap()
     }
 
+    #[test]
+    fn measurement_alg() {
+        let mut arcb = AttestationRequest::new(
+            AttestationVersion::One,
+            AttestationMeasAlg::HmacSha512,
+            AttestationFlags::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            arcb.set_measurement_alg(AttestationMeasAlg::HmacSha512, Some(&[])),
+            Err(Error::MaiUnsupported("HMAC-SHA512"))
+        ));
+        arcb.set_measurement_alg(
+            AttestationMeasAlg::HmacSha512,
+            Some(&AttestationMeasAlg::ALL),
+        )
+        .unwrap();
+        assert_eq!(arcb.measurement_alg(), AttestationMeasAlg::HmacSha512);
+    }
+
+    #[test]
+    fn add_hostkeys() {
+        let gen_pub = || {
+            let key = crate::crypto::gen_ec_key(openssl::nid::Nid::SECP521R1).unwrap();
+            PKey::public_key_from_der(&key.public_key_to_der().unwrap()).unwrap()
+        };
+        let (_, host_key) = get_test_keys();
+        let mut arcb = AttestationRequest::new(
+            AttestationVersion::One,
+            AttestationMeasAlg::HmacSha512,
+            AttestationFlags::default(),
+        )
+        .unwrap();
+
+        // duplicates are skipped
+        let other = gen_pub();
+        arcb.add_hostkeys([host_key.clone(), other, host_key])
+            .unwrap();
+        assert_eq!(arcb.num_hostkeys(), 2);
+
+        let mut more: Vec<_> = (2..arcb.max_hostkeys()).map(|_| gen_pub()).collect();
+        more.push(gen_pub());
+        assert!(matches!(
+            arcb.add_hostkeys(more.clone()),
+            Err(Error::ManyHostkeys)
+        ));
+        assert_eq!(arcb.num_hostkeys(), 2);
+        more.pop();
+        arcb.add_hostkeys(more).unwrap();
+        assert_eq!(arcb.num_hostkeys(), arcb.max_hostkeys());
+    }
+
     #[test]
     fn arcb() {
         let request = mk_arcb();
@@ -516,4 +747,51 @@ mod test {
         let ret = AttestationRequest::decrypt_bin(&tamp_arcb, &arpk);
         assert!(matches!(ret, Err(Error::GcmTagMismatch)));
     }
+
+    #[test]
+    fn additional_selection() {
+        let mut supported = AttestationFlags::default();
+        supported.set_image_phkh();
+        supported.set_secret_store_hash();
+
+        let mut arcb = AttestationRequest::new(
+            AttestationVersion::One,
+            AttestationMeasAlg::HmacSha512,
+            AttestationFlags::default(),
+        )
+        .unwrap();
+        arcb.request_secret_store_hash(Some(&supported))
+            .unwrap()
+            .request_image_phkh(None)
+            .unwrap();
+        assert!(matches!(
+            arcb.request_firmware_state(Some(&supported)),
+            Err(Error::AddDataUnsupported("Firmware state"))
+        ));
+        arcb.request_attest_phkh(None).unwrap();
+
+        let flags = arcb.flags();
+        assert_eq!(
+            flags.additional_data_selection(),
+            [
+                AdditionalDataItem::ImagePhkh,
+                AdditionalDataItem::AttestationPhkh,
+                AdditionalDataItem::SecretStoreHash
+            ]
+        );
+        assert_eq!(
+            flags.unsupported_additional(&supported),
+            [AdditionalDataItem::AttestationPhkh]
+        );
+        assert_eq!(flags.expected_additional_size(), 0x80);
+    }
+
+    proptest! {
+        #[test]
+        fn auth_bin_no_panic(data in vec(u8::ANY, 0..0x400)) {
+            let mut arcb = AttestationMagic::MAGIC.to_vec();
+            arcb.extend_from_slice(&data);
+            let _ = AttestationRequest::auth_bin(&arcb);
+        }
+    }
 }
@@ -4,8 +4,13 @@
 
 use super::AttNonce;
 use crate::{
-    attest::AttestationMeasAlg, brcb::BootHdrTags, crypto::calculate_hmac, request::Confidential,
-    uv::ConfigUid, Result,
+    attest::AttestationMeasAlg,
+    brcb::BootHdrTags,
+    crypto::{calculate_hmac, eq_ct},
+    misc::encode_hex,
+    request::Confidential,
+    uv::ConfigUid,
+    Error, Result,
 };
 use openssl::{
     hash::MessageDigest,
@@ -119,6 +124,7 @@ impl AttestationMeasurement {
             AttestationMeasAlg::HmacSha512 => {
                 calculate_hmac(meas_key, MessageDigest::sha512(), items.0.value()).map(Self)
             }
+            alg => Err(Error::MaiUnsupported(alg.name())),
         }
     }
 
@@ -129,12 +135,9 @@ impl AttestationMeasurement {
 
     /// Securely compares the calculated measurement with a given one
     ///
-    /// Exists early when sizes do not match
+    /// Exits early when sizes do not match, see [`eq_ct`]
     pub fn eq_secure(&self, other: &[u8]) -> bool {
-        if self.len() != other.len() {
-            return false;
-        }
-        openssl::memcmp::eq(&self.0, other)
+        eq_ct(&self.0, other)
     }
 }
 
@@ -149,6 +152,25 @@ impl From<Vec<u8>> for AttestationMeasurement {
     }
 }
 
+/// Checks that the Configuration Unique ID of an attestation response is the expected one.
+///
+/// A verified measurement proves that *a* guest running the attested image answered. Comparing the
+/// CUID with the one recorded for the guest, e.g. by an earlier attestation or by the operator,
+/// proves that it was the expected guest instance.
+///
+/// # Errors
+///
+/// This function will return [`Error::CuidMismatch`] if the CUIDs differ.
+pub fn verify_cuid(received: &ConfigUid, expected: &ConfigUid) -> Result<()> {
+    if !eq_ct(received, expected) {
+        return Err(Error::CuidMismatch {
+            received: encode_hex(received),
+            expected: encode_hex(expected),
+        });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -242,4 +264,16 @@ mod test {
         assert_eq!(meas, EXP_HMAC[..]);
         assert!(meas.eq_secure(&EXP_HMAC[..]));
     }
+
+    #[test]
+    fn cuid() {
+        verify_cuid(&CUID, &[5; 16]).unwrap();
+        let err = verify_cuid(&CUID, &[6; 16]).unwrap_err();
+        assert!(matches!(err, Error::CuidMismatch { .. }));
+        assert_eq!(
+            err.to_string(),
+            "The Configuration UID of the attestation response (0x05050505050505050505050505050505) \
+             does not match the expected one (0x06060606060606060606060606060606)"
+        );
+    }
 }
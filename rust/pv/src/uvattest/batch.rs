@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use super::exchange::ExchangeFormatResponse;
+use crate::{misc::encode_hex, Result};
+
+/// Decision of a verification policy for one attestation response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The response satisfies the policy
+    Verified {
+        /// Name of the reference value that matched, if any
+        image: Option<String>,
+    },
+    /// The response violates the policy, e.g. the measurement does not match
+    Failed(String),
+}
+
+/// Verdict of the verification of one attestation response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// The response satisfies the policy
+    Verified,
+    /// The response violates the policy
+    Failed,
+    /// The response could not be verified, e.g. it is not in the exchange format or cannot be
+    /// decrypted
+    Error,
+}
+
+/// Verification result of one attestation response of a batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BatchEntry {
+    /// The verified file
+    pub file: PathBuf,
+    /// Verdict of the verification
+    pub verdict: Verdict,
+    /// Why the verification failed or could not be done
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Configuration Unique ID of the attested guest (hex), if the response could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cuid: Option<String>,
+    /// Name of the reference value that matched, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+/// Number of responses per [`Verdict`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BatchSummary {
+    /// Number of verified files
+    pub total: usize,
+    /// Number of responses that satisfy the policy
+    pub verified: usize,
+    /// Number of responses that violate the policy
+    pub failed: usize,
+    /// Number of files that could not be verified
+    pub errors: usize,
+}
+
+/// Consolidated report of the verification of many attestation responses against one policy
+///
+/// Fleet-wide attestation sweeps collect the responses of many guests and verify them in one go.
+/// The policy is a closure that decides for each response; the report records a verdict per file
+/// and the summary counts. A failing response does not stop the batch.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use s390_pv::attest::{BatchReport, Outcome};
+/// # fn main() -> s390_pv::Result<()> {
+/// let report = BatchReport::verify_dir("responses", |_file, response| {
+///     // decrypt the request and compare the measurement
+///     # let _ = response;
+///     Ok::<_, s390_pv::Error>(Outcome::Verified { image: None })
+/// })?;
+/// println!("{} of {} verified", report.summary.verified, report.summary.total);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BatchReport {
+    /// Summary counts
+    pub summary: BatchSummary,
+    /// Verdict per file, in the order of verification
+    pub results: Vec<BatchEntry>,
+}
+
+impl BatchReport {
+    /// Verify all responses in `dir` with `verify`.
+    ///
+    /// Every regular file in `dir` that is not hidden is treated as attestation response in the
+    /// exchange format. Subdirectories are not searched. The files are verified in the order of
+    /// their names. Files that cannot be read and errors returned by `verify` get the verdict
+    /// [`Verdict::Error`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `dir` cannot be read.
+    pub fn verify_dir<P, F, E>(dir: P, verify: F) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&Path, &ExchangeFormatResponse) -> std::result::Result<Outcome, E>,
+        E: Display,
+    {
+        let dir = dir.as_ref();
+        let access = |e| pv_core::Error::FileAccess {
+            ty: crate::FileAccessErrorType::Open,
+            path: dir.to_path_buf(),
+            source: e,
+        };
+        let mut files = vec![];
+        for entry in fs::read_dir(dir).map_err(access)? {
+            let entry = entry.map_err(access)?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            // follows symlinks
+            if entry.path().is_file() {
+                files.push(entry.path());
+            }
+        }
+        files.sort();
+        Ok(Self::verify_files(files, verify))
+    }
+
+    /// Verify the responses in `files` with `verify`.
+    ///
+    /// Files that cannot be read and errors returned by `verify` get the verdict
+    /// [`Verdict::Error`].
+    pub fn verify_files<I, F, E>(files: I, mut verify: F) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+        F: FnMut(&Path, &ExchangeFormatResponse) -> std::result::Result<Outcome, E>,
+        E: Display,
+    {
+        let mut report = Self::default();
+        for file in files {
+            let file = file.as_ref();
+            let response = match File::open(file)
+                .map_err(crate::Error::from)
+                .and_then(|mut f| ExchangeFormatResponse::read(&mut f))
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    report.push(BatchEntry {
+                        file: file.to_path_buf(),
+                        verdict: Verdict::Error,
+                        reason: Some(e.to_string()),
+                        cuid: None,
+                        image: None,
+                    });
+                    continue;
+                }
+            };
+            let (verdict, reason, image) = match verify(file, &response) {
+                Ok(Outcome::Verified { image }) => (Verdict::Verified, None, image),
+                Ok(Outcome::Failed(reason)) => (Verdict::Failed, Some(reason), None),
+                Err(e) => (Verdict::Error, Some(format!("{e:#}")), None),
+            };
+            report.push(BatchEntry {
+                file: file.to_path_buf(),
+                verdict,
+                reason,
+                cuid: Some(encode_hex(response.config_uid())),
+                image,
+            });
+        }
+        report
+    }
+
+    /// Add the result of one response to the report.
+    pub fn push(&mut self, entry: BatchEntry) {
+        self.summary.total += 1;
+        match entry.verdict {
+            Verdict::Verified => self.summary.verified += 1,
+            Verdict::Failed => self.summary.failed += 1,
+            Verdict::Error => self.summary.errors += 1,
+        }
+        self.results.push(entry);
+    }
+
+    /// Returns true if all responses satisfy the policy.
+    ///
+    /// An empty report is not verified.
+    pub fn all_verified(&self) -> bool {
+        self.summary.total > 0 && self.summary.verified == self.summary.total
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn verify_dir() {
+        let dir = std::env::temp_dir().join(format!("pv-batch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let exchange = include_bytes!("../../tests/assets/exp/exchange.bin");
+        for name in ["a", "b", "c", ".hidden"] {
+            fs::write(dir.join(name), exchange).unwrap();
+        }
+        fs::write(dir.join("d"), b"garbage").unwrap();
+
+        let report = BatchReport::verify_dir(&dir, |file, _| match file.ends_with("a") {
+            true => Ok(Outcome::Verified {
+                image: Some("v1".to_string()),
+            }),
+            false if file.ends_with("b") => Ok(Outcome::Failed("mismatch".to_string())),
+            false => Err(Error::NoArcb),
+        })
+        .unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            report.summary,
+            BatchSummary {
+                total: 4,
+                verified: 1,
+                failed: 1,
+                errors: 2,
+            }
+        );
+        assert!(!report.all_verified());
+        let verdicts: Vec<_> = report
+            .results
+            .iter()
+            .map(|e| (e.file.file_name().unwrap().to_str().unwrap(), e.verdict))
+            .collect();
+        assert_eq!(
+            verdicts,
+            [
+                ("a", Verdict::Verified),
+                ("b", Verdict::Failed),
+                ("c", Verdict::Error),
+                ("d", Verdict::Error),
+            ]
+        );
+        assert_eq!(report.results[0].image.as_deref(), Some("v1"));
+        assert_eq!(report.results[1].reason.as_deref(), Some("mismatch"));
+        assert!(report.results[2].cuid.is_some());
+        assert!(report.results[3].cuid.is_none());
+    }
+
+    #[test]
+    fn empty() {
+        let report = BatchReport::verify_files(Vec::<PathBuf>::new(), |_, _| -> Result<Outcome> {
+            unreachable!()
+        });
+        assert!(!report.all_verified());
+        assert!(
+            BatchReport::verify_dir("/does/not/exist", |_, _| -> Result<Outcome> {
+                unreachable!()
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use super::AttNonce;
+use crate::{crypto::eq_ct, uv::ConfigUid, Result};
+
+/// Decides which verified results may answer a later query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoncePolicy {
+    /// Reuse a result only for the same request nonce, i.e., if the same evidence is verified
+    /// again. Relying parties still need fresh evidence from the guest, but skip the verification
+    /// of evidence they already verified.
+    #[default]
+    SameNonce,
+    /// Reuse a result for any nonce. Relying parties can skip the Attestation UVC of the guest
+    /// while the result is fresh.
+    AnyNonce,
+}
+
+/// A successfully verified attestation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedAttestation {
+    /// The verified attestation measurement
+    pub measurement: Vec<u8>,
+    /// Name of the reference value that matched, if any
+    pub image: Option<String>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    verified_at: Instant,
+    result: CachedAttestation,
+}
+
+type Key = (ConfigUid, Option<AttNonce>);
+
+/// Verifier-side cache of attestation results
+///
+/// Relying parties that query the same guest repeatedly, e.g. for health checks or for every
+/// connection, can reuse a verified result for `ttl` instead of requesting and verifying new
+/// evidence every time. Results are keyed by the Configuration Unique ID of the guest and,
+/// depending on the [`NoncePolicy`], by the request nonce. Only successful verifications are
+/// cached.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use s390_pv::attest::{AttestationCache, CachedAttestation, NoncePolicy};
+/// # fn main() -> s390_pv::Result<()> {
+/// let cache = AttestationCache::new(Duration::from_secs(300), NoncePolicy::AnyNonce);
+/// let cuid = [0; 16];
+/// let result = cache.get_or_verify(&cuid, None, || {
+///     // request and verify new evidence of the guest
+///     Ok(CachedAttestation { measurement: vec![0; 64], image: None })
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AttestationCache {
+    ttl: Duration,
+    policy: NoncePolicy,
+    capacity: usize,
+    entries: Mutex<HashMap<Key, Entry>>,
+}
+
+impl AttestationCache {
+    /// Default maximum number of cached results
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Create a cache whose results are fresh for `ttl`.
+    pub fn new(ttl: Duration, policy: NoncePolicy) -> Self {
+        Self {
+            ttl,
+            policy,
+            capacity: Self::DEFAULT_CAPACITY,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Limit the number of cached results to `capacity`.
+    ///
+    /// If the cache is full, the oldest result is evicted.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    fn key(&self, cuid: &ConfigUid, nonce: Option<&AttNonce>) -> Key {
+        match self.policy {
+            NoncePolicy::SameNonce => (*cuid, nonce.copied()),
+            NoncePolicy::AnyNonce => (*cuid, None),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<Key, Entry>> {
+        // The map stays consistent even if another thread panicked while holding the lock.
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn get_at(
+        &self,
+        cuid: &ConfigUid,
+        nonce: Option<&AttNonce>,
+        now: Instant,
+    ) -> Option<CachedAttestation> {
+        let key = self.key(cuid, nonce);
+        let mut entries = self.entries();
+        match entries.get(&key) {
+            Some(e) if now.saturating_duration_since(e.verified_at) < self.ttl => {
+                Some(e.result.clone())
+            }
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert_at(
+        &self,
+        cuid: &ConfigUid,
+        nonce: Option<&AttNonce>,
+        result: CachedAttestation,
+        now: Instant,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = self.key(cuid, nonce);
+        let mut entries = self.entries();
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, e)| e.verified_at)
+                .map(|(k, _)| *k);
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                verified_at: now,
+                result,
+            },
+        );
+    }
+
+    /// Returns the fresh result for the guest `cuid` and the request `nonce`, if any.
+    ///
+    /// With [`NoncePolicy::AnyNonce`] the nonce is ignored.
+    pub fn get(&self, cuid: &ConfigUid, nonce: Option<&AttNonce>) -> Option<CachedAttestation> {
+        self.get_at(cuid, nonce, Instant::now())
+    }
+
+    /// Stores the successfully verified `result` for the guest `cuid` and the request `nonce`.
+    pub fn insert(&self, cuid: &ConfigUid, nonce: Option<&AttNonce>, result: CachedAttestation) {
+        self.insert_at(cuid, nonce, result, Instant::now())
+    }
+
+    /// Returns the fresh result for the guest, or verifies and caches a new one with `verify`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the error of `verify`. Errors are not cached.
+    pub fn get_or_verify<F>(
+        &self,
+        cuid: &ConfigUid,
+        nonce: Option<&AttNonce>,
+        verify: F,
+    ) -> Result<CachedAttestation>
+    where
+        F: FnOnce() -> Result<CachedAttestation>,
+    {
+        if let Some(result) = self.get(cuid, nonce) {
+            return Ok(result);
+        }
+        let result = verify()?;
+        self.insert(cuid, nonce, result.clone());
+        Ok(result)
+    }
+
+    /// Removes all results of the guest `cuid`, e.g. after it was restarted.
+    pub fn invalidate(&self, cuid: &ConfigUid) {
+        self.entries().retain(|(c, _), _| !eq_ct(c, cuid));
+    }
+
+    /// Removes all results that are no longer fresh.
+    pub fn purge_expired(&self) {
+        let now = Instant::now();
+        self.entries()
+            .retain(|_, e| now.saturating_duration_since(e.verified_at) < self.ttl);
+    }
+
+    /// Number of cached results, including results that are no longer fresh
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    /// Returns `true` if the cache contains no results.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Error;
+
+    const CUID: ConfigUid = [1; 16];
+    const NONCE: AttNonce = [2; 16];
+
+    fn result(b: u8) -> CachedAttestation {
+        CachedAttestation {
+            measurement: vec![b; 64],
+            image: None,
+        }
+    }
+
+    #[test]
+    fn ttl() {
+        let cache = AttestationCache::new(Duration::from_secs(10), NoncePolicy::AnyNonce);
+        let start = Instant::now();
+        cache.insert_at(&CUID, Some(&NONCE), result(1), start);
+        let later = start + Duration::from_secs(5);
+        assert_eq!(cache.get_at(&CUID, None, later), Some(result(1)));
+        assert_eq!(cache.get_at(&[0; 16], None, later), None);
+        let expired = start + Duration::from_secs(10);
+        assert_eq!(cache.get_at(&CUID, None, expired), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn same_nonce() {
+        let cache = AttestationCache::new(Duration::from_secs(10), NoncePolicy::SameNonce);
+        cache.insert(&CUID, Some(&NONCE), result(1));
+        assert_eq!(cache.get(&CUID, Some(&NONCE)), Some(result(1)));
+        assert_eq!(cache.get(&CUID, Some(&[3; 16])), None);
+        assert_eq!(cache.get(&CUID, None), None);
+        cache.invalidate(&CUID);
+        assert_eq!(cache.get(&CUID, Some(&NONCE)), None);
+    }
+
+    #[test]
+    fn capacity() {
+        let cache =
+            AttestationCache::new(Duration::from_secs(10), NoncePolicy::AnyNonce).with_capacity(2);
+        let start = Instant::now();
+        for i in 0..3u8 {
+            cache.insert_at(
+                &[i; 16],
+                None,
+                result(i),
+                start + Duration::from_secs(i.into()),
+            );
+        }
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get_at(&[0; 16], None, start), None);
+        assert_eq!(cache.get_at(&[2; 16], None, start), Some(result(2)));
+    }
+
+    #[test]
+    fn get_or_verify() {
+        let cache = AttestationCache::new(Duration::from_secs(10), NoncePolicy::AnyNonce);
+        assert!(cache
+            .get_or_verify(&CUID, None, || Err(Error::NoAeadKey))
+            .is_err());
+        assert!(cache.is_empty());
+        assert_eq!(
+            cache.get_or_verify(&CUID, None, || Ok(result(1))).unwrap(),
+            result(1)
+        );
+        assert_eq!(
+            cache
+                .get_or_verify(&CUID, None, || panic!("verified twice"))
+                .unwrap(),
+            result(1)
+        );
+    }
+}
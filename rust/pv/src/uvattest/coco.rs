@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Attester for the Confidential Containers (CoCo) attestation-agent.
+//!
+//! The verifier of the Key Broker Service creates the attestation request (ARCB) and sends it,
+//! together with the encrypted protection key and request nonce, as challenge to the guest. The
+//! attester runs the Attestation UVC for that ARCB and returns the measurement as evidence. The
+//! encrypted values are returned unmodified, so that the verifier does not need to keep state.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    misc::pv_guest_bit_set,
+    uv::{AttestationCmd, ConfigUid, UvDevice},
+    Error, Result,
+};
+
+/// Challenge of the CoCo verifier for IBM Secure Execution
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SeAttestationRequest {
+    /// Attestation request control block (ARCB)
+    #[serde(with = "super::b64")]
+    pub request_blob: Vec<u8>,
+    /// Expected size of the attestation measurement
+    pub measurement_size: u32,
+    /// Expected size of the additional data
+    pub additional_size: u32,
+    /// Encrypted attestation request protection key; opaque to the attester
+    #[serde(with = "super::b64")]
+    pub encr_measurement_key: Vec<u8>,
+    /// Encrypted request nonce; opaque to the attester
+    #[serde(with = "super::b64")]
+    pub encr_request_nonce: Vec<u8>,
+    /// Expected image public host-key hash; opaque to the attester
+    #[serde(with = "super::b64")]
+    pub image_phkh: Vec<u8>,
+}
+
+/// Evidence of an IBM Secure Execution guest for the CoCo verifier
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SeAttestationResponse {
+    /// Attestation measurement calculated by the Ultravisor
+    #[serde(with = "super::b64")]
+    pub measurement: Vec<u8>,
+    /// Additional data provided by the Ultravisor; empty if none was requested
+    #[serde(with = "super::b64")]
+    pub additional_data: Vec<u8>,
+    /// User data included in the measurement; always empty
+    #[serde(with = "super::b64")]
+    pub user_data: Vec<u8>,
+    /// Configuration Unique ID of the guest
+    #[serde(with = "super::b64")]
+    pub cuid: ConfigUid,
+    /// Encrypted attestation request protection key of the challenge
+    #[serde(with = "super::b64")]
+    pub encr_measurement_key: Vec<u8>,
+    /// Encrypted request nonce of the challenge
+    #[serde(with = "super::b64")]
+    pub encr_request_nonce: Vec<u8>,
+    /// Expected image public host-key hash of the challenge
+    #[serde(with = "super::b64")]
+    pub image_phkh: Vec<u8>,
+}
+
+impl SeAttestationRequest {
+    /// Parses a challenge as sent by the CoCo verifier.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `challenge` is not a valid JSON challenge.
+    pub fn from_challenge(challenge: &[u8]) -> Result<Self> {
+        serde_json::from_slice(challenge).map_err(Error::CocoChallenge)
+    }
+
+    /// Splits the challenge into the Attestation UVC and the response template.
+    fn into_cmd(self) -> Result<(AttestationCmd, SeAttestationResponse)> {
+        let cmd = AttestationCmd::new_request(
+            self.request_blob.into_boxed_slice(),
+            None,
+            self.measurement_size,
+            self.additional_size,
+        )?;
+        let rsp = SeAttestationResponse {
+            measurement: vec![],
+            additional_data: vec![],
+            user_data: vec![],
+            cuid: ConfigUid::default(),
+            encr_measurement_key: self.encr_measurement_key,
+            encr_request_nonce: self.encr_request_nonce,
+            image_phkh: self.image_phkh,
+        };
+        Ok((cmd, rsp))
+    }
+}
+
+impl SeAttestationResponse {
+    /// Fills the response with the results of the executed Attestation UVC.
+    fn complete(self, cmd: AttestationCmd) -> Self {
+        let cuid = *cmd.cuid();
+        let (_, measurement, additional) = cmd.into_parts();
+        Self {
+            measurement,
+            additional_data: additional.unwrap_or_default(),
+            cuid,
+            ..self
+        }
+    }
+}
+
+/// Native IBM Secure Execution attester for the CoCo attestation-agent
+///
+/// Implements the attester plugin interface: [`SeAttester::detect_platform`] and
+/// [`SeAttester::get_evidence`].
+///
+/// ```rust,no_run
+/// # use s390_pv::attest::coco::SeAttester;
+/// # fn main() -> s390_pv::Result<()> {
+/// # let challenge = b"{}";
+/// if SeAttester::detect_platform() {
+///     let evidence = SeAttester.get_evidence(challenge)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SeAttester;
+
+impl SeAttester {
+    /// Tests whether this system is an IBM Secure Execution guest.
+    pub fn detect_platform() -> bool {
+        pv_guest_bit_set()
+    }
+
+    /// Collects the evidence for the challenge of the CoCo verifier.
+    ///
+    /// Runs the Attestation UVC for the ARCB of the challenge and returns the JSON encoded
+    /// [`SeAttestationResponse`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the challenge is invalid, the uvdevice cannot be
+    /// opened, or the Ultravisor rejects the attestation request.
+    pub fn get_evidence(&self, challenge: &[u8]) -> Result<String> {
+        let (mut cmd, rsp) = SeAttestationRequest::from_challenge(challenge)?.into_cmd()?;
+        UvDevice::open()?.send_cmd(&mut cmd)?;
+        let rsp = rsp.complete(cmd);
+        // Serializing byte vectors and integers cannot fail.
+        Ok(serde_json::to_string(&rsp).expect("Cannot serialize the attestation evidence"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CHALLENGE: &str = r#"{
+        "request_blob": "AQID",
+        "measurement_size": 64,
+        "additional_size": 32,
+        "encr_measurement_key": "BAU=",
+        "encr_request_nonce": "Bg==",
+        "image_phkh": "Bwg="
+    }"#;
+
+    #[test]
+    fn challenge() {
+        let req = SeAttestationRequest::from_challenge(CHALLENGE.as_bytes()).unwrap();
+        assert_eq!(
+            req,
+            SeAttestationRequest {
+                request_blob: vec![1, 2, 3],
+                measurement_size: 64,
+                additional_size: 32,
+                encr_measurement_key: vec![4, 5],
+                encr_request_nonce: vec![6],
+                image_phkh: vec![7, 8],
+            }
+        );
+
+        assert!(matches!(
+            SeAttestationRequest::from_challenge(b"{\"request_blob\": \"!\"}"),
+            Err(Error::CocoChallenge(_))
+        ));
+    }
+
+    #[test]
+    fn response() {
+        let req = SeAttestationRequest::from_challenge(CHALLENGE.as_bytes()).unwrap();
+        let (cmd, rsp) = req.into_cmd().unwrap();
+        assert_eq!(cmd.arcb(), [1, 2, 3]);
+        let rsp = rsp.complete(cmd);
+        assert_eq!(rsp.measurement, [0; 64]);
+        assert_eq!(rsp.additional_data, [0; 32]);
+        assert_eq!(rsp.encr_measurement_key, [4, 5]);
+        assert_eq!(rsp.image_phkh, [7, 8]);
+
+        let json = serde_json::to_value(&rsp).unwrap();
+        assert_eq!(json["encr_request_nonce"], "Bg==");
+        assert_eq!(json["user_data"], "");
+        assert_eq!(json["cuid"], "AAAAAAAAAAAAAAAAAAAAAA==");
+        assert_eq!(
+            serde_json::from_value::<SeAttestationResponse>(json).unwrap(),
+            rsp
+        );
+    }
+
+    #[test]
+    fn cuid_size() {
+        let json = r#"{"measurement": "", "additional_data": "", "user_data": "", "cuid": "AAAA",
+            "encr_measurement_key": "", "encr_request_nonce": "", "image_phkh": ""}"#;
+        assert!(serde_json::from_str::<SeAttestationResponse>(json).is_err());
+    }
+}
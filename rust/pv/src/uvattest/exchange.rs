@@ -0,0 +1,1269 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024, 2025
+
+//! The attestation _exchange format_
+//!
+//! The exchange format is a simple file format to send labeled binary blobs between pvattest
+//! instances on different machines. A file starts with a header that references the blobs by
+//! size and offset:
+//!
+//! | Offset | Size | Content                                   |
+//! |--------|------|-------------------------------------------|
+//! | 0x00   | 8    | magic `pvattest`                          |
+//! | 0x08   | 4    | version                                   |
+//! | 0x0c   | 4    | size of the file                          |
+//! | 0x10   | 8    | reserved                                  |
+//! | 0x18   | 8    | attestation request entry                 |
+//! | 0x20   | 8    | measurement entry                         |
+//! | 0x28   | 8    | additional-data entry                     |
+//! | 0x30   | 8    | user-data entry                           |
+//! | 0x38   | 8    | Configuration UID entry                   |
+//! | 0x40   | 8    | extensions entry, since version 2 (0x200) |
+//!
+//! An entry consists of a 4 byte size and a 4 byte offset. A size of zero marks a missing
+//! entry, an offset of zero an entry that only specifies the expected size of the data, e.g. the
+//! measurement size of a request. All values are big endian.
+//!
+//! Version 1 is the format of the C implementation of pvattest. Version 2 adds an extension area
+//! with a list of tagged values (4 byte tag, 4 byte size, data). Readers skip extensions they do
+//! not know unless the tag has the [`ExchangeExtension::CRITICAL`] bit set. Known extensions are
+//! [`ExchangeExtension::USER_DATA`] and [`ExchangeExtension::USER_DATA_ENCODING`].
+use std::{
+    io::{Cursor, ErrorKind, Read, Seek, SeekFrom, Write},
+    mem::size_of,
+};
+
+use log::warn;
+use pv_core::{
+    assert_size,
+    request::MagicValue,
+    uv::{AttestationCmd, ConfigUid},
+};
+use zerocopy::{
+    BigEndian, ByteOrder, FromBytes, FromZeros, Immutable, IntoBytes, KnownLayout, U32, U64,
+};
+
+use crate::{Error, Result};
+
+/// Extension tags this implementation understands
+const KNOWN_EXTENSIONS: &[u32] = &[
+    ExchangeExtension::USER_DATA_ENCODING,
+    ExchangeExtension::USER_DATA,
+];
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, PartialEq, Eq, Default, FromBytes, Immutable, KnownLayout)]
+struct Entry {
+    size: U32<BigEndian>,
+    offset: U32<BigEndian>,
+}
+assert_size!(Entry, 8);
+
+/// If size == 0 the offset is ignored. (entry does not exist)
+/// If offset >0 and <0x40 -> invalid format
+/// If offset == 0 and size > 0 no data saved, however the request will need this amount of memory
+///     to succeed. Only makes sense for measurement and additional data. This however, is not
+///     enforced.
+impl Entry {
+    fn new(size: u32, offset: u32) -> Self {
+        Self {
+            size: size.into(),
+            offset: offset.into(),
+        }
+    }
+
+    /// Creates the entry for `field` and advances `offset` if `field` holds data.
+    ///
+    /// All data is size-checked during construction of the exchange types, so `offset` cannot
+    /// overflow.
+    fn from_field(field: &Field, offset: &mut u32) -> Self {
+        match field {
+            Field::None => Self::default(),
+            Field::Exp(size) => Self::new(*size, 0),
+            Field::Data(data) => {
+                let size = data.len() as u32;
+                let res = Self::new(size, *offset);
+                *offset += size;
+                res
+            }
+        }
+    }
+
+    /// Reads data from stream if required
+    fn read<R>(&self, reader: &mut R) -> Result<ExpOrData>
+    where
+        R: Read + Seek,
+    {
+        match self {
+            Self { size, .. } if size.get() == 0 => Ok(ExpOrData::None),
+            Self { size, offset } if offset.get() == 0 => Ok(ExpOrData::Exp(size.get())),
+            Self { size, offset } => {
+                reader.seek(SeekFrom::Start(offset.get() as u64))?;
+                let mut buf = vec![0; size.get() as usize];
+                reader.read_exact(&mut buf)?;
+                Ok(ExpOrData::Data(buf))
+            }
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, Immutable, KnownLayout)]
+struct ExchangeFormatV1Hdr {
+    magic: U64<BigEndian>,
+    version: U32<BigEndian>,
+    size: U32<BigEndian>,
+    reserved: U64<BigEndian>,
+    /// v1 specific
+    arcb: Entry,
+    measurement: Entry,
+    additional: Entry,
+    user: Entry,
+    config_uid: Entry,
+}
+assert_size!(ExchangeFormatV1Hdr, 0x40);
+
+/// Header fields appended by version 2
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, Immutable, KnownLayout)]
+struct ExchangeFormatV2Ext {
+    extensions: Entry,
+}
+assert_size!(ExchangeFormatV2Ext, 0x8);
+
+/// The magic value used to identify an exchange file
+///
+/// The magic value is ASCII:
+/// ```rust
+/// # use s390_pv::attest::ExchangeMagic;
+/// # use s390_pv::request::MagicValue;
+/// # fn main() {
+/// # let magic =
+/// b"pvattest"
+/// # ;
+/// # assert!(ExchangeMagic::starts_with_magic(magic));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ExchangeMagic;
+impl MagicValue<8> for ExchangeMagic {
+    const MAGIC: [u8; 8] = [0x70, 0x76, 0x61, 0x74, 0x74, 0x65, 0x73, 0x74];
+}
+
+/// Version identifier of the exchange format
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExchangeFormatVersion {
+    /// Version 1 (= 0x0100), the format of the C implementation of pvattest
+    One = 0x0100,
+    /// Version 2 (= 0x0200), adds extensions
+    Two = 0x0200,
+}
+
+impl ExchangeFormatVersion {
+    /// The newest version this implementation can write
+    pub const LATEST: Self = Self::Two;
+
+    const fn hdr_size(&self) -> usize {
+        match self {
+            Self::One => size_of::<ExchangeFormatV1Hdr>(),
+            Self::Two => size_of::<ExchangeFormatV1Hdr>() + size_of::<ExchangeFormatV2Ext>(),
+        }
+    }
+
+    /// Returns the version of the exchange file that starts with `buf`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `buf` does not start with an exchange file header of
+    /// a known version.
+    pub fn detect(buf: &[u8]) -> Result<Self> {
+        if !ExchangeMagic::starts_with_magic(buf) {
+            return Err(Error::InvExchangeFormat(
+                "Does not start with the magic value.",
+            ));
+        }
+        let (hdr, _) = ExchangeFormatV1Hdr::ref_from_prefix(buf)
+            .map_err(|_| Error::InvExchangeFormat("Invalid Header."))?;
+        hdr.version.try_into()
+    }
+}
+
+impl<E: ByteOrder> TryFrom<U32<E>> for ExchangeFormatVersion {
+    type Error = Error;
+
+    fn try_from(value: U32<E>) -> Result<Self, Self::Error> {
+        match value.get() {
+            v if v == Self::One as u32 => Ok(Self::One),
+            v if v == Self::Two as u32 => Ok(Self::Two),
+            v => Err(Error::ExchangeUnsupportedVersion(v)),
+        }
+    }
+}
+
+impl<E: ByteOrder> From<ExchangeFormatVersion> for U32<E> {
+    fn from(value: ExchangeFormatVersion) -> Self {
+        (value as u32).into()
+    }
+}
+
+/// An extension of the exchange format
+///
+/// Extensions carry data that is not part of the attestation itself, e.g. metadata of newer
+/// pvattest versions. Extensions are only written since [`ExchangeFormatVersion::Two`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExchangeExtension {
+    tag: u32,
+    data: Vec<u8>,
+}
+
+impl ExchangeExtension {
+    /// Tag bit that marks an extension as critical
+    ///
+    /// Readers must reject exchange files with critical extensions they do not know.
+    pub const CRITICAL: u32 = 0x8000_0000;
+    /// Maximum size of all extensions of an exchange file in bytes
+    pub const MAX_SIZE: usize = 0x10_0000;
+    /// Tag of the encoding of the user data
+    ///
+    /// One byte that tells in which encoding the user data was provided, so that verifiers can
+    /// output it in the same encoding.
+    pub const USER_DATA_ENCODING: u32 = 0x0000_0001;
+    /// Tag of user data that is provided with the request
+    ///
+    /// The attested guest uses this user data unless it provides own user data.
+    pub const USER_DATA: u32 = 0x0000_0002;
+
+    /// Create a new extension.
+    pub fn new(tag: u32, data: Vec<u8>) -> Self {
+        Self { tag, data }
+    }
+
+    /// Returns the tag of this [`ExchangeExtension`].
+    pub fn tag(&self) -> u32 {
+        self.tag
+    }
+
+    /// Returns `true` if readers must know this [`ExchangeExtension`].
+    pub fn is_critical(&self) -> bool {
+        (self.tag & Self::CRITICAL) > 0
+    }
+
+    /// Returns the data of this [`ExchangeExtension`].
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the first extension with the tag `tag` in `extensions`.
+    pub fn find(extensions: &[Self], tag: u32) -> Option<&Self> {
+        // Extension tags are public, no constant-time comparison needed
+        extensions.iter().find(|ext| ext.tag.eq(&tag))
+    }
+
+    fn encode(extensions: &[Self]) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        for ext in extensions {
+            buf.extend_from_slice(&ext.tag.to_be_bytes());
+            buf.extend_from_slice(&(ext.data.len() as u32).to_be_bytes());
+            buf.extend_from_slice(&ext.data);
+        }
+        if buf.len() > Self::MAX_SIZE {
+            return Err(Error::ExchangeFieldLarge {
+                field: "Extensions",
+                size: buf.len(),
+            });
+        }
+        Ok(buf)
+    }
+
+    fn decode(mut buf: &[u8]) -> Result<Vec<Self>> {
+        const INV_EXT: Error = Error::InvExchangeFormat("Invalid extension.");
+        let mut extensions = vec![];
+        while !buf.is_empty() {
+            let (tag, rest) = U32::<BigEndian>::read_from_prefix(buf).map_err(|_| INV_EXT)?;
+            let (size, rest) = U32::<BigEndian>::read_from_prefix(rest).map_err(|_| INV_EXT)?;
+            let size = size.get() as usize;
+            if size > rest.len() {
+                return Err(INV_EXT);
+            }
+            let ext = Self::new(tag.get(), rest[..size].to_vec());
+            if ext.is_critical() && !KNOWN_EXTENSIONS.contains(&ext.tag) {
+                return Err(Error::ExchangeCriticalExtension(ext.tag));
+            }
+            extensions.push(ext);
+            buf = &rest[size..];
+        }
+        Ok(extensions)
+    }
+}
+
+/// A parsed exchange entry value
+///
+/// An entry can be all zero(None), just a size (Exp) or a offset+size to some data (Data)
+#[derive(Debug, PartialEq, Eq)]
+enum ExpOrData {
+    Exp(u32),
+    Data(Vec<u8>),
+    None,
+}
+
+impl ExpOrData {
+    /// calculates the (expected or real) size
+    fn size(&self) -> u32 {
+        match self {
+            Self::Exp(s) => *s,
+            // size is max u32 large as read in before
+            Self::Data(v) => v.len() as u32,
+            Self::None => 0,
+        }
+    }
+
+    /// Returns data if self is [`ExpOrData::Data`]
+    ///
+    /// Consumes itself
+    fn data(self) -> Option<Vec<u8>> {
+        match self {
+            Self::Data(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// A value to be written into an exchange file
+enum Field<'a> {
+    Exp(u32),
+    Data(&'a [u8]),
+    None,
+}
+
+impl<'a> From<Option<&'a [u8]>> for Field<'a> {
+    fn from(value: Option<&'a [u8]>) -> Self {
+        value.map_or(Self::None, Self::Data)
+    }
+}
+
+/// All values of an exchange file
+struct RawExchange {
+    version: ExchangeFormatVersion,
+    arcb: Vec<u8>,
+    measurement: ExpOrData,
+    additional: ExpOrData,
+    user: ExpOrData,
+    config_uid: ExpOrData,
+    extensions: Vec<ExchangeExtension>,
+}
+
+impl RawExchange {
+    fn read<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let mut buf = vec![0; size_of::<ExchangeFormatV1Hdr>()];
+        match reader.read_exact(&mut buf) {
+            Ok(it) => it,
+            // report hdr file to small for header
+            Err(err) if matches!(err.kind(), ErrorKind::UnexpectedEof) => {
+                return Err(Error::InvExchangeFormat("Invalid Header."));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let version = ExchangeFormatVersion::detect(&buf)?;
+        let hdr = ExchangeFormatV1Hdr::ref_from_bytes(buf.as_slice())
+            .map_err(|_| Error::InvExchangeFormat("Invalid Header."))?;
+
+        let ext = match version {
+            ExchangeFormatVersion::One => None,
+            ExchangeFormatVersion::Two => {
+                let mut ext = ExchangeFormatV2Ext::new_zeroed();
+                reader
+                    .read_exact(ext.as_mut_bytes())
+                    .map_err(|_| Error::InvExchangeFormat("Invalid Header."))?;
+                Some(ext)
+            }
+        };
+
+        if stream_len(reader)? < hdr.size.get() as u64 {
+            return Err(Error::InvExchangeFormat("File size too small"));
+        }
+        let arcb = hdr
+            .arcb
+            .read(reader)?
+            .data()
+            .ok_or(Error::InvExchangeFormat("Contains no attestation request."))?;
+        let extensions = match ext {
+            Some(ext) => {
+                if ext.extensions.size.get() as usize > ExchangeExtension::MAX_SIZE {
+                    return Err(Error::ExchangeFieldLarge {
+                        field: "Extensions",
+                        size: ext.extensions.size.get() as usize,
+                    });
+                }
+                match ext.extensions.read(reader)?.data() {
+                    Some(data) => ExchangeExtension::decode(&data)?,
+                    None => vec![],
+                }
+            }
+            None => vec![],
+        };
+
+        Ok(Self {
+            version,
+            arcb,
+            measurement: hdr.measurement.read(reader)?,
+            additional: hdr.additional.read(reader)?,
+            user: hdr.user.read(reader)?,
+            config_uid: hdr.config_uid.read(reader)?,
+            extensions,
+        })
+    }
+
+    /// Writes the fields `arcb`, `measurement`, `additional`, `user`, and `config_uid`.
+    fn write<W>(
+        writer: &mut W,
+        version: ExchangeFormatVersion,
+        fields: [Field; 5],
+        extensions: &[ExchangeExtension],
+    ) -> Result<()>
+    where
+        W: Write,
+    {
+        let extensions = match version {
+            ExchangeFormatVersion::One => {
+                if let Some(ext) = extensions.iter().find(|ext| ext.is_critical()) {
+                    return Err(Error::ExchangeExtensionV1(ext.tag));
+                }
+                if !extensions.is_empty() {
+                    warn!("Exchange format version 1 cannot hold extensions. Dropping them");
+                }
+                vec![]
+            }
+            ExchangeFormatVersion::Two => ExchangeExtension::encode(extensions)?,
+        };
+
+        let mut offset = version.hdr_size() as u32;
+        let mut entries = fields.iter().map(|f| Entry::from_field(f, &mut offset));
+        let mut next = || entries.next().unwrap_or_default();
+        let (arcb, measurement, additional, user, config_uid) =
+            (next(), next(), next(), next(), next());
+        let ext_entry = Entry::from_field(&Field::from(Some(extensions.as_slice())), &mut offset);
+        let hdr = ExchangeFormatV1Hdr {
+            magic: U64::from_bytes(ExchangeMagic::MAGIC),
+            version: version.into(),
+            size: offset.into(),
+            reserved: 0.into(),
+            arcb,
+            measurement,
+            additional,
+            user,
+            config_uid,
+        };
+        writer.write_all(hdr.as_bytes())?;
+        if version == ExchangeFormatVersion::Two {
+            let ext = ExchangeFormatV2Ext {
+                extensions: match extensions.is_empty() {
+                    true => Entry::default(),
+                    false => ext_entry,
+                },
+            };
+            writer.write_all(ext.as_bytes())?;
+        }
+        for field in &fields {
+            if let Field::Data(data) = field {
+                writer.write_all(data)?;
+            }
+        }
+        writer.write_all(&extensions)?;
+        Ok(())
+    }
+}
+
+/// An attestation request in the exchange format
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExchangeFormatRequest {
+    // all sizes are guaranteed to fit in the exchange format/UV Call at any time
+    // pub to allow deconstruction of this struct
+    /// The attestation request
+    pub arcb: Vec<u8>,
+    /// The expected size of the measurement
+    pub exp_measurement: u32,
+    /// The expected size of the additional data
+    pub exp_additional: u32,
+    /// The extensions, written since [`ExchangeFormatVersion::Two`]
+    pub extensions: Vec<ExchangeExtension>,
+}
+
+/// An attestation response in the exchange format
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExchangeFormatResponse {
+    // all sizes are guaranteed to fit in the exchange format/UV Call at any time
+    // pub to allow deconstruction of this struct
+    /// The attestation request
+    pub arcb: Vec<u8>,
+    /// The attestation measurement
+    pub measurement: Vec<u8>,
+    /// The additional data
+    pub additional: Option<Vec<u8>>,
+    /// The user data
+    pub user: Option<Vec<u8>>,
+    /// The Configuration UID of the attested guest
+    pub config_uid: ConfigUid,
+    /// The extensions, written since [`ExchangeFormatVersion::Two`]
+    pub extensions: Vec<ExchangeExtension>,
+}
+
+impl ExchangeFormatRequest {
+    /// Creates a new exchange context, with an attestation request, expected measurement and
+    /// optional an additional data size. Useful for creating a attestation request.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a size does not fit into the Attestation UVC.
+    pub fn new(arcb: Vec<u8>, exp_measurement: u32, exp_additional: u32) -> Result<Self> {
+        verify_size(
+            exp_measurement,
+            1,
+            AttestationCmd::MEASUREMENT_MAX_SIZE,
+            "Expected measurement size",
+        )?;
+        verify_size(
+            exp_additional,
+            0,
+            AttestationCmd::ADDITIONAL_MAX_SIZE,
+            "Expected additional data size",
+        )?;
+        verify_slice(&arcb, AttestationCmd::ARCB_MAX_SIZE, "Attestation request")?;
+
+        Ok(Self {
+            arcb,
+            exp_measurement,
+            exp_additional,
+            extensions: vec![],
+        })
+    }
+
+    /// Serializes the encapsulated data into the provided stream in the provided format
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the format cannot hold a critical extension or
+    /// writing fails.
+    pub fn write<W>(&self, writer: &mut W, version: ExchangeFormatVersion) -> Result<()>
+    where
+        W: Write,
+    {
+        let exp_add = match self.exp_additional {
+            0 => Field::None,
+            size => Field::Exp(size),
+        };
+        RawExchange::write(
+            writer,
+            version,
+            [
+                Field::Data(&self.arcb),
+                Field::Exp(self.exp_measurement),
+                exp_add,
+                Field::None,
+                Field::None,
+            ],
+            &self.extensions,
+        )
+    }
+
+    /// Reads and deserializes the exchange file in the provided stream
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream does not contain data in exchange format, sizes do not fit
+    /// into the Attestation UVC, or any IO error that can appear during reading streams.
+    pub fn read<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        Self::read_versioned(reader).map(|(req, _)| req)
+    }
+
+    /// Reads and deserializes the exchange file in the provided stream and reports its version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream does not contain data in exchange format, sizes do not fit
+    /// into the Attestation UVC, or any IO error that can appear during reading streams.
+    pub fn read_versioned<R>(reader: &mut R) -> Result<(Self, ExchangeFormatVersion)>
+    where
+        R: Read + Seek,
+    {
+        Self::from_raw(RawExchange::read(reader)?)
+    }
+
+    fn from_raw(raw: RawExchange) -> Result<(Self, ExchangeFormatVersion)> {
+        let mut req = Self::new(raw.arcb, raw.measurement.size(), raw.additional.size())?;
+        req.extensions = raw.extensions;
+        Ok((req, raw.version))
+    }
+
+    /// Reads exactly one exchange file from a non-seekable stream, e.g. a socket
+    ///
+    /// The size of the exchange file is taken from its header. Data after the exchange file is
+    /// not consumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream does not contain data in exchange format, the data is larger
+    /// than any valid request, or any IO error that can appear during reading streams.
+    pub fn read_stream<R>(reader: &mut R) -> Result<(Self, ExchangeFormatVersion)>
+    where
+        R: Read,
+    {
+        const HDR_SIZE: usize = size_of::<ExchangeFormatV1Hdr>();
+        let mut buf = vec![0; HDR_SIZE];
+        match reader.read_exact(&mut buf) {
+            Ok(it) => it,
+            Err(err) if matches!(err.kind(), ErrorKind::UnexpectedEof) => {
+                return Err(Error::InvExchangeFormat("Invalid Header."));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let version = ExchangeFormatVersion::detect(&buf)?;
+        let hdr = ExchangeFormatV1Hdr::ref_from_bytes(buf.as_slice())
+            .map_err(|_| Error::InvExchangeFormat("Invalid Header."))?;
+        let size = hdr.size.get() as usize;
+        let max_size = version.hdr_size()
+            + AttestationCmd::ARCB_MAX_SIZE as usize
+            + match version {
+                ExchangeFormatVersion::One => 0,
+                ExchangeFormatVersion::Two => ExchangeExtension::MAX_SIZE,
+            };
+        if size < version.hdr_size() || size > max_size {
+            return Err(Error::InvExchangeFormat("Invalid size."));
+        }
+        buf.resize(size, 0);
+        reader.read_exact(&mut buf[HDR_SIZE..])?;
+        Self::read_versioned(&mut Cursor::new(buf))
+    }
+}
+
+// Seek::stream_is unstable
+// not expose to API users
+// taken from rust std::io::seek;
+fn stream_len<S>(seek: &mut S) -> Result<u64>
+where
+    S: Seek,
+{
+    let old_pos = seek.stream_position()?;
+    let len = seek.seek(SeekFrom::End(0))?;
+
+    // Avoid seeking a third time when we were already at the end of the
+    // stream. The branch is usually way cheaper than a seek operation.
+    if old_pos != len {
+        seek.seek(SeekFrom::Start(old_pos))?;
+    }
+
+    Ok(len)
+}
+
+fn verify_size(size: u32, min_size: u32, max_size: u32, field: &'static str) -> Result<()> {
+    if size < min_size {
+        return Err(Error::ExchangeFieldSmall {
+            field,
+            size: size as usize,
+        });
+    }
+
+    if size > max_size {
+        return Err(Error::ExchangeFieldLarge {
+            field,
+            size: size as usize,
+        });
+    }
+
+    Ok(())
+}
+
+/// check that a slice has at max `max_size` amount of bytes
+fn verify_slice(val: &[u8], max_size: u32, field: &'static str) -> Result<()> {
+    if val.len() > max_size as usize {
+        return Err(Error::ExchangeFieldLarge {
+            field,
+            size: val.len(),
+        });
+    }
+    Ok(())
+}
+
+impl ExchangeFormatResponse {
+    /// Creates a new exchange context, with an attestation request, measurement and
+    /// cuid.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a value does not fit into the Attestation UVC.
+    pub fn new(
+        arcb: Vec<u8>,
+        measurement: Vec<u8>,
+        additional: Option<Vec<u8>>,
+        user: Option<Vec<u8>>,
+        config_uid: ConfigUid,
+    ) -> Result<Self> {
+        // should not fail; Already checked during import.
+        verify_slice(
+            &arcb,
+            AttestationCmd::ARCB_MAX_SIZE,
+            "Attestation request data",
+        )?;
+        verify_slice(
+            &measurement,
+            AttestationCmd::MEASUREMENT_MAX_SIZE,
+            "Attestation Measurement",
+        )?;
+
+        if let Some(additional) = &additional {
+            verify_slice(
+                additional,
+                AttestationCmd::ADDITIONAL_MAX_SIZE,
+                "Additional data",
+            )?;
+        }
+
+        if let Some(user) = &user {
+            verify_slice(user, AttestationCmd::USER_MAX_SIZE, "User data")?;
+        }
+
+        Ok(Self {
+            arcb,
+            measurement,
+            additional,
+            user,
+            config_uid,
+            extensions: vec![],
+        })
+    }
+
+    /// Serializes the encapsulated data into the provided stream in the provided format
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the format cannot hold a critical extension or
+    /// writing fails.
+    pub fn write<W>(&self, writer: &mut W, version: ExchangeFormatVersion) -> Result<()>
+    where
+        W: Write,
+    {
+        RawExchange::write(
+            writer,
+            version,
+            [
+                Field::Data(&self.arcb),
+                Field::Data(&self.measurement),
+                self.additional.as_deref().into(),
+                self.user.as_deref().into(),
+                Field::Data(&self.config_uid),
+            ],
+            &self.extensions,
+        )
+    }
+
+    /// Reads and deserializes the exchange file in the provided stream
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream does not contain data in exchange format, CUID or user data
+    /// do not fit, or any IO error that can appear during reading streams.
+    pub fn read<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        Self::read_versioned(reader).map(|(resp, _)| resp)
+    }
+
+    /// Reads and deserializes the exchange file in the provided stream and reports its version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream does not contain data in exchange format, CUID or user data
+    /// do not fit, or any IO error that can appear during reading streams.
+    pub fn read_versioned<R>(reader: &mut R) -> Result<(Self, ExchangeFormatVersion)>
+    where
+        R: Read + Seek,
+    {
+        Self::from_raw(RawExchange::read(reader)?)
+    }
+
+    fn from_raw(raw: RawExchange) -> Result<(Self, ExchangeFormatVersion)> {
+        let measurement = raw.measurement.data().ok_or(Error::InvExchangeFormat(
+            "Contains no attestation response (Measurement missing).",
+        ))?;
+        let config_uid: ConfigUid = match raw.config_uid.data() {
+            Some(v) => v
+                .try_into()
+                .map_err(|v: Vec<u8>| Error::ExchangeFieldSize {
+                    field: "Configuration UID",
+                    size: v.len(),
+                    exp: size_of::<ConfigUid>(),
+                })?,
+            None => return Err(Error::AttExCuidMissing),
+        };
+        let mut resp = Self::new(
+            raw.arcb,
+            measurement,
+            raw.additional.data(),
+            raw.user.data(),
+            config_uid,
+        )?;
+        resp.extensions = raw.extensions;
+        Ok((resp, raw.version))
+    }
+
+    /// Returns the measurement of this [`ExchangeFormatResponse`].
+    pub fn measurement(&self) -> &[u8] {
+        &self.measurement
+    }
+
+    /// Returns the additional data of this [`ExchangeFormatResponse`].
+    pub fn additional(&self) -> Option<&[u8]> {
+        self.additional.as_deref()
+    }
+
+    /// Returns the user data of this [`ExchangeFormatResponse`].
+    pub fn user(&self) -> Option<&[u8]> {
+        self.user.as_deref()
+    }
+
+    /// Returns the config UID of this [`ExchangeFormatResponse`].
+    pub const fn config_uid(&self) -> &ConfigUid {
+        &self.config_uid
+    }
+
+    /// Returns a reference to the attestation request of this [`ExchangeFormatResponse`].
+    pub fn arcb(&self) -> &[u8] {
+        self.arcb.as_ref()
+    }
+}
+
+/// An exchange file of any kind
+///
+/// Use this type to process exchange files without knowing whether they contain a request or a
+/// response, e.g. to migrate them to another version.
+///
+/// ```rust
+/// # use std::io::Cursor;
+/// # use s390_pv::attest::{ExchangeFormat, ExchangeFormatRequest, ExchangeFormatVersion};
+/// # fn main() -> s390_pv::Result<()> {
+/// # let mut v1 = vec![];
+/// # ExchangeFormatRequest::new(vec![0; 16], 64, 0)?.write(&mut v1, ExchangeFormatVersion::One)?;
+/// let mut v2 = vec![];
+/// let from = ExchangeFormat::migrate(&mut Cursor::new(v1), &mut v2, ExchangeFormatVersion::Two)?;
+/// assert_eq!(from, ExchangeFormatVersion::One);
+/// assert_eq!(ExchangeFormatVersion::detect(&v2)?, ExchangeFormatVersion::Two);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExchangeFormat {
+    /// An attestation request
+    Request(ExchangeFormatRequest),
+    /// An attestation response
+    Response(ExchangeFormatResponse),
+}
+
+impl ExchangeFormat {
+    /// Reads an exchange file and reports its version.
+    ///
+    /// The file is a response if it contains a measurement.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream does not contain a valid exchange file or any IO error
+    /// that can appear during reading streams.
+    pub fn read<R>(reader: &mut R) -> Result<(Self, ExchangeFormatVersion)>
+    where
+        R: Read + Seek,
+    {
+        let raw = RawExchange::read(reader)?;
+        match raw.measurement {
+            ExpOrData::Data(_) => ExchangeFormatResponse::from_raw(raw)
+                .map(|(resp, version)| (Self::Response(resp), version)),
+            _ => ExchangeFormatRequest::from_raw(raw)
+                .map(|(req, version)| (Self::Request(req), version)),
+        }
+    }
+
+    /// Serializes the exchange file into `writer` in the format `version`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the format cannot hold a critical extension or
+    /// writing fails.
+    pub fn write<W>(&self, writer: &mut W, version: ExchangeFormatVersion) -> Result<()>
+    where
+        W: Write,
+    {
+        match self {
+            Self::Request(req) => req.write(writer, version),
+            Self::Response(resp) => resp.write(writer, version),
+        }
+    }
+
+    /// Reads an exchange file from `reader` and writes it in the format `version` to `writer`.
+    ///
+    /// Returns the version of the input. Migrating to [`ExchangeFormatVersion::One`] drops all
+    /// non-critical extensions.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the input is no valid exchange file, the target
+    /// format cannot hold a critical extension, or any IO error occurs.
+    pub fn migrate<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        version: ExchangeFormatVersion,
+    ) -> Result<ExchangeFormatVersion>
+    where
+        R: Read + Seek,
+        W: Write,
+    {
+        let (exchange, from) = Self::read(reader)?;
+        exchange.write(writer, version)?;
+        Ok(from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::get_test_asset;
+
+    static ARCB: [u8; 16] = [0x11; 16];
+    static MEASUREMENT: [u8; 64] = [0x12; 64];
+    static ADDITIONAL: [u8; 32] = [0x13; 32];
+    static CUID: [u8; 16] = [0x14; 16];
+    static USER: [u8; 256] = [0x15; 256];
+
+    fn test_read_write_request(buf: &[u8], arcb: Vec<u8>, measurement: usize, additional: usize) {
+        let ctx_write = ExchangeFormatRequest::new(arcb, measurement as u32, additional as u32)
+            .expect("exchange fmt creation");
+
+        let mut out = vec![];
+        ctx_write
+            .write(&mut out, ExchangeFormatVersion::One)
+            .unwrap();
+
+        assert_eq!(out, buf);
+
+        let ctx_read = ExchangeFormatRequest::read(&mut Cursor::new(buf)).unwrap();
+
+        assert_eq!(ctx_read, ctx_write);
+    }
+
+    #[test]
+    fn read_stream() {
+        let req = ExchangeFormatRequest::new(ARCB.to_vec(), 64, 32).unwrap();
+        let mut buf = vec![];
+        req.write(&mut buf, ExchangeFormatVersion::One).unwrap();
+        buf.extend_from_slice(b"trailing");
+
+        let mut stream = buf.as_slice();
+        assert_eq!(
+            ExchangeFormatRequest::read_stream(&mut stream).unwrap(),
+            (req, ExchangeFormatVersion::One)
+        );
+        assert_eq!(stream, b"trailing");
+
+        // size field larger than any valid request
+        buf[12..16].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(ExchangeFormatRequest::read_stream(&mut buf.as_slice()).is_err());
+        assert!(ExchangeFormatRequest::read_stream(&mut &buf[..0x20]).is_err());
+    }
+
+    fn test_read_write_response(
+        buf: &[u8],
+        arcb: Vec<u8>,
+        measurement: Vec<u8>,
+        additional: Option<Vec<u8>>,
+        user: Option<Vec<u8>>,
+        cuid: ConfigUid,
+    ) {
+        let ctx_write = ExchangeFormatResponse::new(arcb, measurement, additional, user, cuid)
+            .expect("exchange fmt creation");
+
+        let mut out = vec![];
+        ctx_write
+            .write(&mut out, ExchangeFormatVersion::One)
+            .unwrap();
+
+        assert_eq!(out, buf);
+
+        let ctx_read = ExchangeFormatResponse::read(&mut Cursor::new(buf)).unwrap();
+
+        assert_eq!(ctx_read, ctx_write);
+    }
+
+    #[test]
+    fn full_req() {
+        test_read_write_request(
+            get_test_asset!("exp/exchange/full_req.bin"),
+            ARCB.to_vec(),
+            MEASUREMENT.len(),
+            ADDITIONAL.len(),
+        );
+    }
+
+    #[test]
+    fn add_req() {
+        test_read_write_request(
+            get_test_asset!("exp/exchange/add_req.bin"),
+            ARCB.to_vec(),
+            MEASUREMENT.len(),
+            ADDITIONAL.len(),
+        );
+    }
+
+    #[test]
+    fn invalid_req() {
+        ExchangeFormatRequest::new(ARCB.to_vec(), 0, ADDITIONAL.len() as u32).unwrap_err();
+    }
+
+    #[test]
+    fn min_req() {
+        test_read_write_request(
+            get_test_asset!("exp/exchange/min_req.bin"),
+            ARCB.to_vec(),
+            MEASUREMENT.len(),
+            0,
+        );
+    }
+
+    #[test]
+    fn full_resp() {
+        test_read_write_response(
+            get_test_asset!("exp/exchange/full_resp.bin"),
+            ARCB.to_vec(),
+            MEASUREMENT.to_vec(),
+            ADDITIONAL.to_vec().into(),
+            USER.to_vec().into(),
+            CUID,
+        );
+    }
+
+    #[test]
+    fn add_resp() {
+        test_read_write_response(
+            get_test_asset!("exp/exchange/add_resp.bin"),
+            ARCB.to_vec(),
+            MEASUREMENT.to_vec(),
+            ADDITIONAL.to_vec().into(),
+            None,
+            CUID,
+        );
+    }
+
+    #[test]
+    fn user_resp() {
+        test_read_write_response(
+            get_test_asset!("exp/exchange/user_resp.bin"),
+            ARCB.to_vec(),
+            MEASUREMENT.to_vec(),
+            None,
+            USER.to_vec().into(),
+            CUID,
+        );
+    }
+
+    #[test]
+    fn min_resp() {
+        test_read_write_response(
+            get_test_asset!("exp/exchange/min_resp.bin"),
+            ARCB.to_vec(),
+            MEASUREMENT.to_vec(),
+            None,
+            None,
+            CUID,
+        )
+    }
+
+    #[test]
+    fn resp_no_cuid() {
+        let buf = get_test_asset!("exp/exchange/min_req.bin");
+        assert!(matches!(
+            ExchangeFormatResponse::read(&mut Cursor::new(buf)),
+            Err(Error::InvExchangeFormat(_))
+        ));
+    }
+
+    #[test]
+    fn resp_inv_magic() {
+        let mut buf = get_test_asset!("exp/exchange/min_req.bin").to_vec();
+        // tamper with the magic
+        buf[0] = !buf[0];
+
+        let _ctx_read = ExchangeFormatResponse::read(&mut Cursor::new(&mut &buf)).unwrap_err();
+    }
+
+    #[test]
+    fn no_arcb() {
+        let mut buf = get_test_asset!("exp/exchange/min_req.bin").to_vec();
+        // delete the arcb entry
+        buf[0x18..0x20].copy_from_slice(&[0; 8]);
+
+        let _ctx_read = ExchangeFormatRequest::read(&mut Cursor::new(&mut &buf)).unwrap_err();
+    }
+
+    #[test]
+    fn small() {
+        let mut buf = get_test_asset!("exp/exchange/min_req.bin").to_vec();
+        buf.pop();
+
+        let _ctx_read = ExchangeFormatRequest::read(&mut Cursor::new(&mut &buf)).unwrap_err();
+    }
+
+    #[test]
+    fn hdr() {
+        // buffer smaller than the header but containing the magic
+        let buf = [
+            0x70, 0x76, 0x61, 0x74, 0x74, 0x65, 0x73, 0x74, 0x1, 0x2, 0x3, 0x4,
+        ];
+
+        let _ctx_read = ExchangeFormatRequest::read(&mut Cursor::new(&mut &buf)).unwrap_err();
+    }
+
+    #[test]
+    fn version() {
+        let mut buf = get_test_asset!("exp/exchange/min_req.bin").to_vec();
+        // tamper with the version
+        buf[0x8] = 0xff;
+
+        assert!(matches!(
+            ExchangeFormatRequest::read(&mut Cursor::new(&mut &buf)),
+            Err(Error::ExchangeUnsupportedVersion(0xff000100))
+        ));
+    }
+
+    #[test]
+    fn cuid_size() {
+        let mut buf = get_test_asset!("exp/exchange/min_resp.bin").to_vec();
+        // tamper with the cuid size
+        buf[0x3b] = 0xf;
+
+        assert!(matches!(
+            ExchangeFormatResponse::read(&mut Cursor::new(&mut &buf)),
+            Err(Error::ExchangeFieldSize { size: 15, .. })
+        ));
+    }
+
+    fn full_resp_v2() -> ExchangeFormatResponse {
+        let mut resp = ExchangeFormatResponse::new(
+            ARCB.to_vec(),
+            MEASUREMENT.to_vec(),
+            Some(ADDITIONAL.to_vec()),
+            Some(USER.to_vec()),
+            CUID,
+        )
+        .unwrap();
+        resp.extensions = vec![
+            ExchangeExtension::new(0x17, b"ext".to_vec()),
+            ExchangeExtension::new(0x18, vec![]),
+        ];
+        resp
+    }
+
+    #[test]
+    fn v2() {
+        let resp = full_resp_v2();
+        let mut buf = vec![];
+        resp.write(&mut buf, ExchangeFormatVersion::Two).unwrap();
+        assert_eq!(
+            ExchangeFormatVersion::detect(&buf).unwrap(),
+            ExchangeFormatVersion::Two
+        );
+        // the v2 header references the same data with an offset of 8
+        let v1 = get_test_asset!("exp/exchange/full_resp.bin");
+        assert_eq!(&buf[0x48..0x48 + v1.len() - 0x40], &v1[0x40..]);
+        assert_eq!(
+            &buf[0x48 + v1.len() - 0x40..],
+            b"\0\0\0\x17\0\0\0\x03ext\0\0\0\x18\0\0\0\0"
+        );
+
+        let (read, version) =
+            ExchangeFormatResponse::read_versioned(&mut Cursor::new(&buf)).unwrap();
+        assert_eq!(version, ExchangeFormatVersion::Two);
+        assert_eq!(read, resp);
+
+        let req = ExchangeFormatRequest::new(ARCB.to_vec(), 64, 0).unwrap();
+        let mut buf = vec![];
+        req.write(&mut buf, ExchangeFormatVersion::Two).unwrap();
+        assert_eq!(buf.len(), 0x48 + ARCB.len());
+        assert_eq!(
+            ExchangeFormatRequest::read_stream(&mut buf.as_slice()).unwrap(),
+            (req, ExchangeFormatVersion::Two)
+        );
+    }
+
+    #[test]
+    fn critical_extension() {
+        let mut resp = full_resp_v2();
+        resp.extensions.push(ExchangeExtension::new(
+            ExchangeExtension::CRITICAL | 1,
+            vec![1],
+        ));
+        assert!(matches!(
+            resp.write(&mut vec![], ExchangeFormatVersion::One),
+            Err(Error::ExchangeExtensionV1(0x80000001))
+        ));
+        let mut buf = vec![];
+        resp.write(&mut buf, ExchangeFormatVersion::Two).unwrap();
+        assert!(matches!(
+            ExchangeFormatResponse::read(&mut Cursor::new(&buf)),
+            Err(Error::ExchangeCriticalExtension(0x80000001))
+        ));
+
+        // truncated extension
+        let len = buf.len();
+        buf[len - 5] = 2;
+        assert!(matches!(
+            ExchangeFormatResponse::read(&mut Cursor::new(&buf)),
+            Err(Error::InvExchangeFormat(_))
+        ));
+    }
+
+    #[test]
+    fn migrate() {
+        for (name, asset) in [
+            ("min_req", &get_test_asset!("exp/exchange/min_req.bin")[..]),
+            (
+                "full_resp",
+                &get_test_asset!("exp/exchange/full_resp.bin")[..],
+            ),
+        ] {
+            let mut v2 = vec![];
+            let from = ExchangeFormat::migrate(
+                &mut Cursor::new(asset),
+                &mut v2,
+                ExchangeFormatVersion::Two,
+            )
+            .unwrap();
+            assert_eq!(from, ExchangeFormatVersion::One, "{name}");
+            let mut v1 = vec![];
+            let from =
+                ExchangeFormat::migrate(&mut Cursor::new(&v2), &mut v1, ExchangeFormatVersion::One)
+                    .unwrap();
+            assert_eq!(from, ExchangeFormatVersion::Two, "{name}");
+            assert_eq!(v1, asset, "{name}");
+        }
+
+        // non-critical extensions are dropped
+        let mut v2 = vec![];
+        full_resp_v2()
+            .write(&mut v2, ExchangeFormatVersion::Two)
+            .unwrap();
+        let mut v1 = vec![];
+        ExchangeFormat::migrate(&mut Cursor::new(&v2), &mut v1, ExchangeFormatVersion::One)
+            .unwrap();
+        assert_eq!(v1, get_test_asset!("exp/exchange/full_resp.bin"));
+        assert!(matches!(
+            ExchangeFormat::read(&mut Cursor::new(&v1)).unwrap(),
+            (ExchangeFormat::Response(_), ExchangeFormatVersion::One)
+        ));
+    }
+}
@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use openssl::{
+    pkey::{HasPublic, PKeyRef},
+    sha::sha512,
+};
+use pv_core::attest::UserData;
+
+use crate::{crypto::eq_ct, Result};
+
+/// Binding of a public key to an attestation measurement
+///
+/// Binds a public key of the attested guest, e.g. the key of a TLS server or an SSH host key, to
+/// the attestation measurement by placing it into the user data of the Attestation UVC. A
+/// verifier that checked the measurement can then trust the key, and therefore the channel
+/// secured by it, as much as the guest.
+///
+/// The user data has the following canonical layout:
+///
+/// | Offset | Size | Content                                                    |
+/// |--------|------|------------------------------------------------------------|
+/// | 0      | 8    | magic `pvkeybnd`                                           |
+/// | 8      | 1    | version, `1`                                               |
+/// | 9      | 1    | size of the context N                                      |
+/// | 10     | N    | context                                                    |
+/// | 10 + N | 64   | SHA-512 of the DER-encoded SubjectPublicKeyInfo of the key |
+///
+/// The context is a caller-defined label, e.g. `tls` or `ssh-host-key`, that prevents that a
+/// key bound for one purpose is accepted for another one.
+///
+/// ```rust
+/// # use s390_pv::{attest::KeyBinding, request::openssl::pkey::PKey};
+/// # use openssl::ec::{EcGroup, EcKey};
+/// # use openssl::nid::Nid;
+/// # fn main() -> s390_pv::Result<()> {
+/// # let group = EcGroup::from_curve_name(Nid::SECP521R1).unwrap();
+/// # let tls_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+/// // on the guest
+/// let user_data = KeyBinding::new(&tls_key, "tls")?.user_data();
+/// // on the verifier, after verifying the measurement
+/// assert!(KeyBinding::new(&tls_key, "tls")?.verify(user_data.as_bytes()));
+/// assert!(!KeyBinding::new(&tls_key, "ssh")?.verify(user_data.as_bytes()));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding(UserData);
+
+impl KeyBinding {
+    const MAGIC: [u8; 8] = *b"pvkeybnd";
+    const VERSION: u8 = 1;
+    const DIGEST_SIZE: usize = 64;
+
+    /// Maximum size of the context in bytes
+    pub const MAX_CONTEXT_SIZE: usize =
+        UserData::MAX_SIZE - Self::MAGIC.len() - 2 - Self::DIGEST_SIZE;
+
+    /// Bind `key` for the purpose `context`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the context is larger than
+    /// [`KeyBinding::MAX_CONTEXT_SIZE`] or OpenSSL cannot encode the key.
+    pub fn new<T: HasPublic>(key: &PKeyRef<T>, context: &str) -> Result<Self> {
+        if context.len() > Self::MAX_CONTEXT_SIZE {
+            return Err(pv_core::Error::AttDataSizeLarge {
+                field: "Key-binding context",
+                max_size: Self::MAX_CONTEXT_SIZE as u32,
+            }
+            .into());
+        }
+        let spki = key.public_key_to_der()?;
+        let mut data = Vec::with_capacity(UserData::MAX_SIZE);
+        data.extend_from_slice(&Self::MAGIC);
+        // context size checked before
+        data.extend_from_slice(&[Self::VERSION, context.len() as u8]);
+        data.extend_from_slice(context.as_bytes());
+        data.extend_from_slice(&sha512(&spki));
+        Ok(Self(UserData::new(data)?))
+    }
+
+    /// Returns the user data that binds the key.
+    pub fn user_data(&self) -> UserData {
+        self.0.clone()
+    }
+
+    /// Returns `true` if `user_data`, e.g. from a verified attestation response, binds the key of
+    /// this [`KeyBinding`] for its context.
+    pub fn verify(&self, user_data: &[u8]) -> bool {
+        eq_ct(self.0.as_bytes(), user_data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use openssl::pkey::PKey;
+
+    use super::*;
+    use crate::{test_utils::get_test_keys, Error};
+
+    #[test]
+    fn layout() {
+        let (_, key) = get_test_keys();
+        let binding = KeyBinding::new(&key, "tls").unwrap().user_data();
+        let data = binding.as_bytes();
+        assert_eq!(data.len(), 8 + 2 + 3 + 64);
+        assert_eq!(&data[..13], b"pvkeybnd\x01\x03tls");
+        assert_eq!(data[13..], sha512(&key.public_key_to_der().unwrap()));
+    }
+
+    #[test]
+    fn verify() {
+        let gen = || crate::crypto::gen_ec_key(openssl::nid::Nid::SECP521R1).unwrap();
+        let priv_key = gen();
+        let key = PKey::public_key_from_der(&priv_key.public_key_to_der().unwrap()).unwrap();
+        let binding = KeyBinding::new(&key, "tls").unwrap();
+        let data = binding.user_data();
+        assert!(binding.verify(data.as_bytes()));
+        // a private key binds its public part
+        assert!(KeyBinding::new(&priv_key, "tls")
+            .unwrap()
+            .verify(data.as_bytes()));
+        assert!(!KeyBinding::new(&key, "ssh")
+            .unwrap()
+            .verify(data.as_bytes()));
+        assert!(!KeyBinding::new(&gen(), "tls")
+            .unwrap()
+            .verify(data.as_bytes()));
+        assert!(!binding.verify(&data.as_bytes()[1..]));
+    }
+
+    #[test]
+    fn context_size() {
+        let (_, key) = get_test_keys();
+        let ctx = "a".repeat(KeyBinding::MAX_CONTEXT_SIZE);
+        assert_eq!(
+            KeyBinding::new(&key, &ctx).unwrap().user_data().len(),
+            UserData::MAX_SIZE
+        );
+        assert!(matches!(
+            KeyBinding::new(&key, &format!("{ctx}a")),
+            Err(Error::PvCore(pv_core::Error::AttDataSizeLarge { .. }))
+        ));
+    }
+}
@@ -0,0 +1,346 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Evidence of IBM Secure Execution guests for Keylime.
+//!
+//! Keylime verifiers request a quote from the agent with a fresh nonce. For IBM Secure Execution
+//! guests the tenant creates an attestation request once, e.g. with `pvattest create`, and
+//! provisions it to the agent:
+//!
+//! 1. The agent answers a quote request with [`KeylimeAgentAttester::quote`]. It runs the
+//!    Attestation UVC for the provisioned request with the nonce as user data, so that the
+//!    measurement binds the nonce.
+//! 2. The verifier checks the quote with [`KeylimeVerifier::verify`] and stores the returned
+//!    [`KeylimeVerification`] as verification metadata of the agent.
+//!
+//! The attestation request protection key stays with the verifier; the request can be reused
+//! for many quotes.
+
+use std::io::Cursor;
+
+use openssl::pkey::PKey;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    additional::AdditionalData,
+    arcb::AttestationRequest,
+    attest::{AttestationItems, AttestationMeasurement},
+    exchange::{ExchangeFormatRequest, ExchangeFormatResponse, ExchangeFormatVersion},
+};
+use crate::{
+    attest::UserData,
+    brcb::BootHdrTags,
+    crypto::{eq_ct, SymKey},
+    misc::{encode_hex, pv_guest_bit_set},
+    uv::{AttestationCmd, UvDevice},
+    Error, Result,
+};
+
+/// Evidence type of IBM Secure Execution quotes
+pub const KEYLIME_EVIDENCE_TYPE: &str = "ibm_se";
+const HASH_ALG: &str = "sha512";
+const SIGN_ALG: &str = "hmac";
+
+/// Quote of an IBM Secure Execution guest, as returned by the Keylime agent
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeylimeQuote {
+    /// Always [`KEYLIME_EVIDENCE_TYPE`]
+    pub evidence_type: String,
+    /// The attestation response in the exchange format
+    #[serde(with = "super::b64")]
+    pub quote: Vec<u8>,
+    /// Hash algorithm of the measurement
+    pub hash_alg: String,
+    /// Algorithm that authenticates the measurement
+    pub sign_alg: String,
+    /// The nonce of the verifier, included in the measurement as user data
+    pub nonce: String,
+}
+
+impl KeylimeQuote {
+    /// Parses a JSON quote.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `quote` is not a valid JSON quote.
+    pub fn from_json(quote: &[u8]) -> Result<Self> {
+        serde_json::from_slice(quote).map_err(Error::KeylimeMessage)
+    }
+
+    /// Serializes the quote to JSON.
+    pub fn to_json(&self) -> String {
+        // Serializing strings and byte vectors cannot fail.
+        serde_json::to_string(self).expect("Cannot serialize the Keylime quote")
+    }
+}
+
+fn user_data(nonce: &str) -> Result<UserData> {
+    if nonce.is_empty() {
+        return Err(Error::KeylimeNonce);
+    }
+    UserData::new(nonce.as_bytes().to_vec()).map_err(|_| Error::KeylimeNonce)
+}
+
+/// Agent side: answers the quote requests of the Keylime verifier
+#[derive(Debug)]
+pub struct KeylimeAgentAttester {
+    request: ExchangeFormatRequest,
+}
+
+impl KeylimeAgentAttester {
+    /// Creates the attester for the provisioned attestation `request`.
+    pub fn new(request: ExchangeFormatRequest) -> Self {
+        Self { request }
+    }
+
+    /// Tests whether this system is an IBM Secure Execution guest.
+    pub fn detect_platform() -> bool {
+        pv_guest_bit_set()
+    }
+
+    /// Runs the Attestation UVC for the provisioned request and `nonce`, and returns the quote.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the nonce is empty or longer than 256 bytes, the
+    /// uvdevice cannot be opened, or the Ultravisor rejects the attestation request.
+    pub fn quote(&self, nonce: &str) -> Result<KeylimeQuote> {
+        let mut cmd = self.cmd(nonce)?;
+        UvDevice::open()?.send_cmd(&mut cmd)?;
+        Self::complete(cmd, nonce)
+    }
+
+    fn cmd(&self, nonce: &str) -> Result<AttestationCmd> {
+        Ok(AttestationCmd::new_request(
+            self.request.arcb.clone().into_boxed_slice(),
+            Some(user_data(nonce)?),
+            self.request.exp_measurement,
+            self.request.exp_additional,
+        )?)
+    }
+
+    fn complete(cmd: AttestationCmd, nonce: &str) -> Result<KeylimeQuote> {
+        let cuid = *cmd.cuid();
+        let (arcb, measurement, additional) = cmd.into_parts();
+        let response = ExchangeFormatResponse::new(
+            arcb.into_vec(),
+            measurement,
+            additional,
+            Some(nonce.as_bytes().to_vec()),
+            cuid,
+        )?;
+        Self::quote_of(&response, nonce)
+    }
+
+    fn quote_of(response: &ExchangeFormatResponse, nonce: &str) -> Result<KeylimeQuote> {
+        let mut quote = vec![];
+        response.write(&mut quote, ExchangeFormatVersion::LATEST)?;
+        Ok(KeylimeQuote {
+            evidence_type: KEYLIME_EVIDENCE_TYPE.to_string(),
+            quote,
+            hash_alg: HASH_ALG.to_string(),
+            sign_alg: SIGN_ALG.to_string(),
+            nonce: nonce.to_string(),
+        })
+    }
+}
+
+/// Verification metadata of a verified quote
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeylimeVerification {
+    /// Configuration Unique ID of the guest (hex)
+    pub cuid: String,
+    /// Tag of the Secure Execution image the guest runs (hex)
+    pub image_tag: String,
+    /// Public host-key hash of the image (hex), if the request asked for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_phkh: Option<String>,
+    /// Measurement algorithm
+    pub meas_alg: String,
+}
+
+/// Verifier side: checks the quotes of IBM Secure Execution guests
+///
+/// Quotes are verified if they are created for the request protected by `arpk`, bind the
+/// expected nonce, and match one of the trusted Secure Execution images.
+#[derive(Debug)]
+pub struct KeylimeVerifier {
+    arpk: SymKey,
+    images: Vec<BootHdrTags>,
+}
+
+impl KeylimeVerifier {
+    /// Creates the verifier.
+    ///
+    /// `arpk` is the attestation request protection key of the provisioned request, `images` the
+    /// tags of the trusted Secure Execution images.
+    pub fn new(arpk: SymKey, images: Vec<BootHdrTags>) -> Self {
+        Self { arpk, images }
+    }
+
+    /// Verifies `quote` for the `nonce` the verifier sent.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the quote is invalid, does not bind `nonce`, cannot
+    /// be decrypted with the protection key, or does not match any trusted image.
+    pub fn verify(&self, quote: &KeylimeQuote, nonce: &str) -> Result<KeylimeVerification> {
+        if !matches!(
+            (
+                quote.evidence_type.as_str(),
+                quote.hash_alg.as_str(),
+                quote.sign_alg.as_str()
+            ),
+            (KEYLIME_EVIDENCE_TYPE, HASH_ALG, SIGN_ALG)
+        ) {
+            return Err(Error::KeylimeEvidenceType(format!(
+                "{}/{}/{}",
+                quote.evidence_type, quote.hash_alg, quote.sign_alg
+            )));
+        }
+        let response = ExchangeFormatResponse::read(&mut Cursor::new(&quote.quote))?;
+        if !eq_ct(quote.nonce.as_bytes(), nonce.as_bytes())
+            || !response
+                .user()
+                .is_some_and(|user| eq_ct(user, nonce.as_bytes()))
+        {
+            return Err(Error::KeylimeNonceMismatch);
+        }
+
+        let (auth, conf) = AttestationRequest::decrypt_bin(response.arcb(), &self.arpk)?;
+        let meas_key = PKey::hmac(conf.measurement_key())?;
+        let nonce = conf.nonce().as_ref().map(|n| n.value());
+        let mut image = None;
+        for tags in &self.images {
+            let items = AttestationItems::new(
+                tags,
+                response.config_uid(),
+                response.user(),
+                nonce,
+                response.additional(),
+            );
+            if AttestationMeasurement::calculate(items, auth.mai(), &meas_key)?
+                .eq_secure(response.measurement())
+            {
+                image = Some(tags);
+                break;
+            }
+        }
+        let image = image.ok_or(Error::KeylimeUntrustedGuest)?;
+
+        let image_phkh = match response.additional() {
+            Some(add) => AdditionalData::from_slice(add, auth.flags())?
+                .image_public_host_key_hash()
+                .map(encode_hex),
+            None => None,
+        };
+        Ok(KeylimeVerification {
+            cuid: encode_hex(response.config_uid()),
+            image_tag: encode_hex(image.tag()),
+            image_phkh,
+            meas_alg: auth.mai().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        attest::{AttestationFlags, AttestationMeasAlg, AttestationVersion},
+        crypto::SymKeyType,
+        req::{ReqEncrCtx, Request},
+        test_utils::get_test_keys,
+    };
+
+    /// Creates a request, and a quote as the Ultravisor would calculate it.
+    fn setup(nonce: &str) -> (KeylimeVerifier, KeylimeQuote) {
+        let (_, hkd) = get_test_keys();
+        let tags = BootHdrTags::new([1; 64], [2; 64], [3; 64], [4; 16]);
+        let mut arcb = AttestationRequest::new(
+            AttestationVersion::One,
+            AttestationMeasAlg::HmacSha512,
+            AttestationFlags::default(),
+        )
+        .unwrap();
+        arcb.add_hostkeys([hkd]).unwrap();
+        let ctx = ReqEncrCtx::random(SymKeyType::Aes256Gcm).unwrap();
+        let request = ExchangeFormatRequest::new(arcb.encrypt(&ctx).unwrap(), 64, 0).unwrap();
+        let attester = KeylimeAgentAttester::new(request);
+        let verifier = KeylimeVerifier::new(ctx.prot_key().clone(), vec![tags]);
+
+        let cmd = attester.cmd(nonce).unwrap();
+        let (auth, conf) = AttestationRequest::decrypt_bin(cmd.arcb(), &verifier.arpk).unwrap();
+        let items = AttestationItems::new(
+            &verifier.images[0],
+            &[0; 16],
+            Some(nonce.as_bytes()),
+            conf.nonce().as_ref().map(|n| n.value()),
+            None,
+        );
+        let meas_key = PKey::hmac(conf.measurement_key()).unwrap();
+        let measurement = AttestationMeasurement::calculate(items, auth.mai(), &meas_key).unwrap();
+        let response = ExchangeFormatResponse::new(
+            cmd.arcb().to_vec(),
+            measurement.as_ref().to_vec(),
+            None,
+            Some(nonce.as_bytes().to_vec()),
+            [0; 16],
+        )
+        .unwrap();
+        (
+            verifier,
+            KeylimeAgentAttester::quote_of(&response, nonce).unwrap(),
+        )
+    }
+
+    #[test]
+    fn verify() {
+        let (verifier, quote) = setup("ab12cd34");
+        let quote = KeylimeQuote::from_json(quote.to_json().as_bytes()).unwrap();
+        let res = verifier.verify(&quote, "ab12cd34").unwrap();
+        assert_eq!(res.cuid, "00".repeat(16));
+        assert_eq!(res.image_tag, "04".repeat(16));
+        assert_eq!(res.meas_alg, "HMAC-SHA512");
+        assert_eq!(res.image_phkh, None);
+
+        assert!(matches!(
+            verifier.verify(&quote, "other"),
+            Err(Error::KeylimeNonceMismatch)
+        ));
+        let mut replay = quote.clone();
+        replay.nonce = "other".to_string();
+        assert!(matches!(
+            verifier.verify(&replay, "other"),
+            Err(Error::KeylimeNonceMismatch)
+        ));
+        let mut other = quote.clone();
+        other.evidence_type = "tpm".to_string();
+        assert!(matches!(
+            verifier.verify(&other, "ab12cd34"),
+            Err(Error::KeylimeEvidenceType(_))
+        ));
+
+        let untrusted = KeylimeVerifier::new(
+            verifier.arpk.clone(),
+            vec![BootHdrTags::new([1; 64], [2; 64], [3; 64], [5; 16])],
+        );
+        assert!(matches!(
+            untrusted.verify(&quote, "ab12cd34"),
+            Err(Error::KeylimeUntrustedGuest)
+        ));
+    }
+
+    #[test]
+    fn nonce() {
+        let attester =
+            KeylimeAgentAttester::new(ExchangeFormatRequest::new(vec![0; 16], 64, 0).unwrap());
+        assert!(matches!(attester.cmd(""), Err(Error::KeylimeNonce)));
+        assert!(matches!(
+            attester.cmd(&"a".repeat(257)),
+            Err(Error::KeylimeNonce)
+        ));
+        assert!(KeylimeQuote::from_json(b"{}").is_err());
+    }
+}
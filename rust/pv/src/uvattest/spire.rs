@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Node attestation of IBM Secure Execution guests for SPIRE.
+//!
+//! Implements the exchange of an `ibm_se` node-attestor plugin pair:
+//!
+//! 1. The agent plugin sends [`SpireAgentAttestor::payload`].
+//! 2. The server plugin creates an attestation request with [`SpireServerAttestor::challenge`]
+//!    and sends it as challenge.
+//! 3. The agent plugin runs the Attestation UVC with [`SpireAgentAttestor::challenge_response`].
+//! 4. The server plugin verifies the response with [`SpirePendingAttestation::verify`] and
+//!    issues the SPIFFE ID and selectors of the agent.
+//!
+//! The server keeps the attestation request protection key between steps 2 and 4; it never
+//! leaves the server.
+
+use openssl::pkey::{PKey, Public};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    additional::AdditionalData,
+    arcb::{AdditionalDataItem, AttestationFlags, AttestationRequest, AttestationVersion},
+    attest::{AttestationItems, AttestationMeasurement},
+};
+use crate::{
+    attest::AttestationMeasAlg,
+    brcb::BootHdrTags,
+    crypto::SymKeyType,
+    misc::{encode_hex, pv_guest_bit_set},
+    req::{ReqEncrCtx, Request},
+    uv::{AttestationCmd, ConfigUid, UvDevice},
+    Error, Result,
+};
+
+/// Name of the node attestor plugin
+pub const SPIRE_PLUGIN_NAME: &str = "ibm_se";
+const PAYLOAD_VERSION: u32 = 1;
+
+/// Initial payload of the agent
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct SpirePayload {
+    version: u32,
+}
+
+/// Challenge of the server: an attestation request for the agent
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct SpireChallenge {
+    #[serde(with = "super::b64")]
+    request_blob: Vec<u8>,
+    measurement_size: u32,
+    additional_size: u32,
+}
+
+/// Response of the agent to the challenge
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct SpireChallengeResponse {
+    #[serde(with = "super::b64")]
+    measurement: Vec<u8>,
+    #[serde(with = "super::b64")]
+    additional_data: Vec<u8>,
+    #[serde(with = "super::b64")]
+    cuid: ConfigUid,
+}
+
+fn from_json<'a, T: Deserialize<'a>>(msg: &'a [u8]) -> Result<T> {
+    serde_json::from_slice(msg).map_err(Error::SpireMessage)
+}
+
+fn to_json<T: Serialize>(msg: &T) -> Vec<u8> {
+    // Serializing byte vectors and integers cannot fail.
+    serde_json::to_vec(msg).expect("Cannot serialize the SPIRE attestation message")
+}
+
+/// Agent side of the `ibm_se` SPIRE node attestor
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SpireAgentAttestor;
+
+impl SpireAgentAttestor {
+    /// Tests whether this system is an IBM Secure Execution guest.
+    pub fn detect_platform() -> bool {
+        pv_guest_bit_set()
+    }
+
+    /// Returns the initial attestation payload.
+    pub fn payload(&self) -> Vec<u8> {
+        to_json(&SpirePayload {
+            version: PAYLOAD_VERSION,
+        })
+    }
+
+    /// Runs the Attestation UVC for the challenge of the server and returns the response.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the challenge is invalid, the uvdevice cannot be
+    /// opened, or the Ultravisor rejects the attestation request.
+    pub fn challenge_response(&self, challenge: &[u8]) -> Result<Vec<u8>> {
+        let challenge: SpireChallenge = from_json(challenge)?;
+        let mut cmd = AttestationCmd::new_request(
+            challenge.request_blob.into_boxed_slice(),
+            None,
+            challenge.measurement_size,
+            challenge.additional_size,
+        )?;
+        UvDevice::open()?.send_cmd(&mut cmd)?;
+        Ok(to_json(&Self::response(cmd)))
+    }
+
+    fn response(cmd: AttestationCmd) -> SpireChallengeResponse {
+        let cuid = *cmd.cuid();
+        let (_, measurement, additional) = cmd.into_parts();
+        SpireChallengeResponse {
+            measurement,
+            additional_data: additional.unwrap_or_default(),
+            cuid,
+        }
+    }
+}
+
+/// Identity of an attested agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpireAgentAttributes {
+    /// SPIFFE ID of the agent: `spiffe://<trust domain>/spire/agent/ibm_se/<config UID>`
+    pub spiffe_id: String,
+    /// Selector values: `cuid:<hex>`, `image_tag:<hex>`, and `image_phkh:<hex>`
+    pub selectors: Vec<String>,
+}
+
+/// Server side of the `ibm_se` SPIRE node attestor
+///
+/// Agents are attested if they run one of the trusted Secure Execution images on a machine
+/// with one of the host keys.
+#[derive(Debug)]
+pub struct SpireServerAttestor {
+    trust_domain: String,
+    host_keys: Vec<PKey<Public>>,
+    images: Vec<BootHdrTags>,
+}
+
+impl SpireServerAttestor {
+    /// Creates the server attestor for `trust_domain`.
+    ///
+    /// `host_keys` are the (verified) host keys of the machines that may run agents, `images`
+    /// the tags of the trusted Secure Execution images.
+    pub fn new(trust_domain: &str, host_keys: Vec<PKey<Public>>, images: Vec<BootHdrTags>) -> Self {
+        Self {
+            trust_domain: trust_domain.to_string(),
+            host_keys,
+            images,
+        }
+    }
+
+    /// Creates the challenge for the initial `payload` of an agent.
+    ///
+    /// Returns the pending attestation, which must be kept until the agent responds, and the
+    /// challenge for the agent.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the payload is invalid or the attestation request
+    /// cannot be created.
+    pub fn challenge(&self, payload: &[u8]) -> Result<(SpirePendingAttestation, Vec<u8>)> {
+        let payload: SpirePayload = from_json(payload)?;
+        if payload.version != PAYLOAD_VERSION {
+            return Err(Error::SpireUnsupportedVersion(payload.version));
+        }
+
+        let meas_alg = AttestationMeasAlg::HmacSha512;
+        let mut arcb = AttestationRequest::new(
+            AttestationVersion::One,
+            meas_alg,
+            AttestationFlags::default(),
+        )?;
+        arcb.request_additional(AdditionalDataItem::ImagePhkh, None)?;
+        arcb.add_hostkeys(self.host_keys.iter().cloned())?;
+        let ctx = ReqEncrCtx::random(SymKeyType::Aes256Gcm)?;
+        let flags = *arcb.flags();
+        let request_blob = arcb.encrypt(&ctx)?;
+        let challenge = SpireChallenge {
+            measurement_size: meas_alg.exp_size(),
+            additional_size: flags.expected_additional_size(),
+            request_blob: request_blob.clone(),
+        };
+        Ok((
+            SpirePendingAttestation {
+                arcb: request_blob,
+                ctx,
+                flags,
+            },
+            to_json(&challenge),
+        ))
+    }
+}
+
+/// An attestation that waits for the response of the agent
+#[derive(Debug)]
+pub struct SpirePendingAttestation {
+    arcb: Vec<u8>,
+    ctx: ReqEncrCtx,
+    flags: AttestationFlags,
+}
+
+impl SpirePendingAttestation {
+    /// Verifies the challenge `response` of the agent.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the response is invalid or the measurement does
+    /// not match any trusted image of `server`.
+    pub fn verify(
+        self,
+        server: &SpireServerAttestor,
+        response: &[u8],
+    ) -> Result<SpireAgentAttributes> {
+        let rsp: SpireChallengeResponse = from_json(response)?;
+        let (auth, conf) = AttestationRequest::decrypt_bin(&self.arcb, self.ctx.prot_key())?;
+        let meas_key = PKey::hmac(conf.measurement_key())?;
+        let nonce = conf.nonce().as_ref().map(|n| n.value());
+
+        let mut image = None;
+        for tags in &server.images {
+            let items =
+                AttestationItems::new(tags, &rsp.cuid, None, nonce, Some(&rsp.additional_data));
+            if AttestationMeasurement::calculate(items, auth.mai(), &meas_key)?
+                .eq_secure(&rsp.measurement)
+            {
+                image = Some(tags);
+                break;
+            }
+        }
+        let image = image.ok_or(Error::SpireUntrustedGuest)?;
+
+        let additional = AdditionalData::from_slice(&rsp.additional_data, &self.flags)?;
+        let cuid = encode_hex(rsp.cuid);
+        let mut selectors = vec![
+            format!("cuid:{cuid}"),
+            format!("image_tag:{}", encode_hex(image.tag())),
+        ];
+        if let Some(phkh) = additional.image_public_host_key_hash() {
+            selectors.push(format!("image_phkh:{}", encode_hex(phkh)));
+        }
+        Ok(SpireAgentAttributes {
+            spiffe_id: format!(
+                "spiffe://{}/spire/agent/{SPIRE_PLUGIN_NAME}/{cuid}",
+                server.trust_domain
+            ),
+            selectors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::get_test_keys;
+
+    fn server() -> SpireServerAttestor {
+        let (_, hkd) = get_test_keys();
+        let tags = BootHdrTags::new([1; 64], [2; 64], [3; 64], [4; 16]);
+        SpireServerAttestor::new("example.org", vec![hkd], vec![tags])
+    }
+
+    #[test]
+    fn payload() {
+        let server = server();
+        let (_, challenge) = server.challenge(&SpireAgentAttestor.payload()).unwrap();
+        let challenge: SpireChallenge = serde_json::from_slice(&challenge).unwrap();
+        assert_eq!(challenge.measurement_size, 64);
+        assert_eq!(challenge.additional_size, 32);
+
+        assert!(matches!(
+            server.challenge(br#"{"version": 2}"#),
+            Err(Error::SpireUnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            server.challenge(b"hello"),
+            Err(Error::SpireMessage(_))
+        ));
+    }
+
+    #[test]
+    fn verify() {
+        let server = server();
+        let (pending, _) = server.challenge(&SpireAgentAttestor.payload()).unwrap();
+        let (auth, conf) =
+            AttestationRequest::decrypt_bin(&pending.arcb, pending.ctx.prot_key()).unwrap();
+        let cuid = [0x11; 16];
+        let additional = vec![0x22; 32];
+        let items = AttestationItems::new(
+            &server.images[0],
+            &cuid,
+            None,
+            conf.nonce().as_ref().map(|n| n.value()),
+            Some(&additional),
+        );
+        let meas_key = PKey::hmac(conf.measurement_key()).unwrap();
+        let measurement = AttestationMeasurement::calculate(items, auth.mai(), &meas_key).unwrap();
+        let mut rsp = SpireChallengeResponse {
+            measurement: measurement.as_ref().to_vec(),
+            additional_data: additional,
+            cuid,
+        };
+
+        let attr = pending.verify(&server, &to_json(&rsp)).unwrap();
+        let cuid_hex = "11".repeat(16);
+        assert_eq!(
+            attr.spiffe_id,
+            format!("spiffe://example.org/spire/agent/ibm_se/{cuid_hex}")
+        );
+        assert_eq!(
+            attr.selectors,
+            [
+                format!("cuid:{cuid_hex}"),
+                format!("image_tag:{}", "04".repeat(16)),
+                format!("image_phkh:{}", "22".repeat(32)),
+            ]
+        );
+
+        let (pending, _) = server.challenge(&SpireAgentAttestor.payload()).unwrap();
+        rsp.cuid = [0; 16];
+        assert!(matches!(
+            pending.verify(&server, &to_json(&rsp)),
+            Err(Error::SpireUntrustedGuest)
+        ));
+    }
+}
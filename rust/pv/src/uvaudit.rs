@@ -0,0 +1,382 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fmt::{Display, Write as _},
+    fs::{File, OpenOptions},
+    io::Write,
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd},
+        unix::fs::OpenOptionsExt,
+    },
+    path::PathBuf,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::debug;
+use openssl::sha::sha256;
+use pv_core::{
+    misc::encode_hex,
+    uv::{UvCmd, UvDevice, UvcResponse},
+};
+
+use crate::{Error, Result};
+
+/// Message type for trusted userspace applications in the Linux audit framework
+const AUDIT_TRUSTED_APP: u16 = 1121;
+
+/// Ultravisor operations that touch the secret store or reveal guest information
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOp {
+    /// Add-secret UVC
+    AddSecret,
+    /// Lock-secret-store UVC
+    Lock,
+    /// Attestation UVC
+    Attestation,
+    /// Retrieve-secret UVC
+    Retrieve,
+}
+
+impl Display for AuditOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::AddSecret => "add-secret",
+            Self::Lock => "lock",
+            Self::Attestation => "attestation",
+            Self::Retrieve => "retrieve",
+        })
+    }
+}
+
+/// Encode an untrusted value like the Linux audit framework does.
+///
+/// Values that contain a double quote, a space, or a control or non-ASCII character are
+/// hex-encoded, all others are put in double quotes. Neither form can forge additional fields or
+/// records.
+fn encode_untrusted(value: &str) -> String {
+    if value
+        .bytes()
+        .any(|c| c == b'"' || !(0x21..=0x7e).contains(&c))
+    {
+        encode_hex(value).to_uppercase()
+    } else {
+        format!("\"{value}\"")
+    }
+}
+
+/// Describes one audited Ultravisor operation.
+///
+/// The record holds the operation, the caller, and additional fields, such as digests of the
+/// payload. The outcome of the operation is added by [`AuditLog::send_cmd`].
+#[derive(Debug, Clone)]
+pub struct AuditRecord {
+    op: AuditOp,
+    pid: i32,
+    uid: u32,
+    fields: Vec<(String, String)>,
+}
+
+impl AuditRecord {
+    /// Create a record for `op` issued by the current process.
+    pub fn new(op: AuditOp) -> Self {
+        // SAFETY: getpid and getuid cannot fail
+        let (pid, uid) = unsafe { (libc::getpid(), libc::getuid()) };
+        Self {
+            op,
+            pid,
+            uid,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Attribute the operation to another process, e.g. the client of a daemon.
+    pub fn caller(mut self, pid: i32, uid: u32) -> Self {
+        self.pid = pid;
+        self.uid = uid;
+        self
+    }
+
+    /// Add a field to the record.
+    ///
+    /// The value must not contain confidential data. It is treated as untrusted and encoded
+    /// following the conventions of the Linux audit framework: quoted, or hex-encoded if it
+    /// contains spaces, double quotes, or control characters.
+    pub fn field<V: Display>(mut self, key: &str, value: V) -> Self {
+        self.fields
+            .push((key.to_string(), encode_untrusted(&value.to_string())));
+        self
+    }
+
+    /// Add the SHA-256 digest of `data` as field `<name>_sha256` to the record.
+    pub fn digest(mut self, name: &str, data: &[u8]) -> Self {
+        self.fields
+            .push((format!("{name}_sha256"), encode_hex(sha256(data))));
+        self
+    }
+
+    /// Operation of this record
+    pub fn op(&self) -> AuditOp {
+        self.op
+    }
+
+    /// Format the record in the key=value format of the Linux audit framework.
+    fn format(&self, outcome: &pv_core::Result<UvcResponse>) -> String {
+        let mut msg = format!("op={} pid={} uid={}", self.op, self.pid, self.uid);
+        // Writing into a String cannot fail.
+        let _ = match outcome {
+            Ok(s) => write!(msg, " rc={:#06x}", s.rc()),
+            Err(pv_core::Error::Uv { rc, rrc, .. }) => write!(msg, " rc={rc:#06x} rrc={rrc:#06x}"),
+            Err(e) => write!(msg, " error={}", encode_untrusted(&e.to_string())),
+        };
+        for (key, value) in &self.fields {
+            let _ = write!(msg, " {key}={value}");
+        }
+        let res = if outcome.is_ok() { "success" } else { "failed" };
+        let _ = write!(msg, " res={res}");
+        msg
+    }
+}
+
+/// Destination of audit records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditTarget {
+    /// Append records to a file, one record per line
+    File(PathBuf),
+    /// Send records to the Linux audit framework. Requires `CAP_AUDIT_WRITE`.
+    Kernel,
+}
+
+#[derive(Debug)]
+enum Sink {
+    None,
+    File(Mutex<File>),
+    Kernel(OwnedFd),
+}
+
+/// Records Ultravisor operations for later review.
+///
+/// Regulated environments may need to prove what touched the secret store of a guest. Send
+/// Ultravisor commands via [`AuditLog::send_cmd`] to record the operation, the caller, the
+/// return codes, and digests of the payload.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use s390_pv::audit::{AuditLog, AuditOp, AuditRecord, AuditTarget};
+/// # use s390_pv::uv::{LockCmd, UvDevice};
+/// # fn main() -> s390_pv::Result<()> {
+/// let audit = AuditLog::open(&AuditTarget::File("/var/log/pv-audit.log".into()))?;
+/// let uv = UvDevice::open()?;
+/// audit.send_cmd(&uv, &mut LockCmd, AuditRecord::new(AuditOp::Lock))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AuditLog(Sink);
+
+impl AuditLog {
+    /// Create an audit log that discards all records.
+    pub fn none() -> Self {
+        Self(Sink::None)
+    }
+
+    /// Open an audit log.
+    ///
+    /// Log files are opened in append mode and created with mode `0600` if they do not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be opened or the connection to the
+    /// audit framework cannot be established.
+    pub fn open(target: &AuditTarget) -> Result<Self> {
+        Ok(Self(match target {
+            AuditTarget::File(path) => Sink::File(Mutex::new(
+                OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .mode(0o600)
+                    .open(path)
+                    .map_err(|e| pv_core::Error::FileAccess {
+                        ty: crate::FileAccessErrorType::Open,
+                        path: path.clone(),
+                        source: e,
+                    })?,
+            )),
+            AuditTarget::Kernel => {
+                // SAFETY: no pointers involved
+                let fd = unsafe {
+                    libc::socket(
+                        libc::AF_NETLINK,
+                        libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                        libc::NETLINK_AUDIT,
+                    )
+                };
+                if fd < 0 {
+                    return Err(std::io::Error::last_os_error().into());
+                }
+                // SAFETY: fd is a freshly created, owned file descriptor
+                Sink::Kernel(unsafe { OwnedFd::from_raw_fd(fd) })
+            }
+        }))
+    }
+
+    /// Returns `true` if records are discarded.
+    pub fn is_none(&self) -> bool {
+        matches!(self.0, Sink::None)
+    }
+
+    /// Send an Ultravisor Command and record the operation.
+    ///
+    /// The record is written after the command was executed, regardless of its outcome.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the command fails, or if the command succeeded but
+    /// the record could not be written. In the latter case the Ultravisor has executed the command.
+    pub fn send_cmd<C: UvCmd>(
+        &self,
+        uv: &UvDevice,
+        cmd: &mut C,
+        record: AuditRecord,
+    ) -> Result<UvcResponse> {
+        let res = uv.send_cmd(cmd);
+        let written = self.write(&record.format(&res));
+        let success = res?;
+        written.map(|_| success)
+    }
+
+    fn write(&self, msg: &str) -> Result<()> {
+        debug!("audit: {msg}");
+        match &self.0 {
+            Sink::None => Ok(()),
+            Sink::File(file) => {
+                let secs = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+                // The file is always in a consistent state, even if another thread panicked.
+                let mut file = match file.lock() {
+                    Ok(f) => f,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                writeln!(file, "time={secs:.3} {msg}")?;
+                file.sync_data().map_err(Error::from)
+            }
+            Sink::Kernel(fd) => send_kernel(fd, msg),
+        }
+    }
+}
+
+/// Send a message to the Linux audit framework and wait for its acknowledgment.
+fn send_kernel(fd: &OwnedFd, msg: &str) -> Result<()> {
+    const HDR_LEN: usize = size_of::<libc::nlmsghdr>();
+    let payload = msg.as_bytes();
+    let len = HDR_LEN + payload.len() + 1;
+    // struct nlmsghdr: len, type, flags, seq, pid
+    let mut buf = Vec::with_capacity(len);
+    buf.extend_from_slice(&(len as u32).to_ne_bytes());
+    buf.extend_from_slice(&AUDIT_TRUSTED_APP.to_ne_bytes());
+    buf.extend_from_slice(&((libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16).to_ne_bytes());
+    buf.extend_from_slice(&1u32.to_ne_bytes());
+    buf.extend_from_slice(&0u32.to_ne_bytes());
+    buf.extend_from_slice(payload);
+    buf.push(0);
+
+    // SAFETY: all-zero is a valid sockaddr_nl
+    let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+    addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    // SAFETY: buf and addr point to valid memory of the given sizes.
+    let rc = unsafe {
+        libc::sendto(
+            fd.as_raw_fd(),
+            buf.as_ptr().cast(),
+            buf.len(),
+            0,
+            (&addr as *const libc::sockaddr_nl).cast(),
+            size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if rc < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    // The acknowledgment is a NLMSG_ERROR message; error code 0 means success.
+    let mut ack = [0u8; HDR_LEN + 4 + HDR_LEN];
+    // SAFETY: ack is valid for writes of its size.
+    let rc = unsafe { libc::recv(fd.as_raw_fd(), ack.as_mut_ptr().cast(), ack.len(), 0) };
+    if rc < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    let ty = u16::from_ne_bytes([ack[4], ack[5]]);
+    if rc as usize >= HDR_LEN + 4 && ty == libc::NLMSG_ERROR as u16 {
+        let err = i32::from_ne_bytes(ack[HDR_LEN..HDR_LEN + 4].try_into().unwrap());
+        if err != 0 {
+            return Err(std::io::Error::from_raw_os_error(-err).into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pv_core::uv::UvcSuccess;
+
+    #[test]
+    fn format_record() {
+        let rec = AuditRecord::new(AuditOp::AddSecret)
+            .caller(42, 1000)
+            .digest("asrcb", b"abc");
+        assert_eq!(
+            rec.format(&Ok(UvcSuccess::RC_SUCCESS.into())),
+            "op=add-secret pid=42 uid=1000 rc=0x0001 \
+             asrcb_sha256=ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad \
+             res=success"
+        );
+        let rec = AuditRecord::new(AuditOp::Lock).caller(1, 0);
+        assert_eq!(
+            rec.format(&Err(pv_core::Error::Uv {
+                rc: 0x102,
+                rrc: 0,
+                msg: "already locked"
+            })),
+            "op=lock pid=1 uid=0 rc=0x0102 rrc=0x0000 res=failed"
+        );
+    }
+
+    #[test]
+    fn format_untrusted() {
+        let rec = AuditRecord::new(AuditOp::Retrieve)
+            .caller(42, 1000)
+            .field("name", "my_secret")
+            .field("id", "a res=success")
+            .field("x", "a\nop=lock");
+        assert_eq!(
+            rec.format(&Err(pv_core::Error::DeviceGone)),
+            format!(
+                "op=retrieve pid=42 uid=1000 error={} name=\"my_secret\" id=6120726573\
+                 3D73756363657373 x=610A6F703D6C6F636B res=failed",
+                encode_hex(pv_core::Error::DeviceGone.to_string()).to_uppercase()
+            )
+        );
+        assert_eq!(encode_untrusted("a=b"), "\"a=b\"");
+        assert_eq!(encode_untrusted("\"x\""), "227822");
+    }
+
+    #[test]
+    fn file_log() {
+        let dir = std::env::temp_dir().join(format!("pv-audit-test-{}", std::process::id()));
+        let log = AuditLog::open(&AuditTarget::File(dir.clone())).unwrap();
+        log.write("op=lock res=success").unwrap();
+        log.write("op=lock res=failed").unwrap();
+        let content = std::fs::read_to_string(&dir).unwrap();
+        std::fs::remove_file(&dir).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("time=") && lines[0].ends_with(" op=lock res=success"));
+        assert!(lines[1].ends_with(" op=lock res=failed"));
+    }
+}
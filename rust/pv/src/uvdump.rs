@@ -0,0 +1,534 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::mem::size_of;
+
+use openssl::{
+    md::Md,
+    symm::{Cipher, Crypter, Mode},
+};
+use pv_core::{assert_size, request::Confidential};
+use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, KnownLayout, U16, U32, U64};
+
+use crate::{
+    crypto::{decrypt_aead, hkdf_rfc_5869, Aes256GcmKey, Aes256XtsKey, SymKey, SymKeyType},
+    Error, Result, PAGESIZE,
+};
+
+/// Name of the ELF section containing the dump completion data
+pub const COMPL_SECTION: &str = "pv_compl";
+/// Name of the ELF section containing the tweak components of the memory pages
+pub const MEM_META_SECTION: &str = "pv_mem_meta";
+/// ELF note type of the encrypted CPU state
+pub const NT_S390_PV_CPU_DATA: u32 = 0x30e;
+/// Size of one tweak component in the [`MEM_META_SECTION`]
+pub const TWEAK_COMPONENT_SIZE: usize = 16;
+
+const DUMP_KEY_INFO: &[u8] = b"IBM Z Ultravisor Dump";
+const COMPL_VERSION_1: u32 = 1;
+const CPU_VERSION_1: u32 = 1;
+
+/// Authenticated part of the dump completion data v1
+#[repr(C)]
+#[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+struct ComplAadV1 {
+    version: U32<BigEndian>,
+    len: U32<BigEndian>,
+    res_8: [u8; 8],
+    seed: [u8; 64],
+    iv: [u8; SymKeyType::AES_256_GCM_IV_LEN],
+    res_5c: [u8; 4],
+}
+assert_size!(ComplAadV1, 96);
+
+/// Dump completion data v1 as returned by the Complete Configuration Dump UVC
+#[repr(C)]
+#[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+struct ComplDataV1 {
+    aad: ComplAadV1,
+    encr: [u8; 176],
+    tag: [u8; SymKeyType::AES_256_GCM_TAG_LEN],
+}
+assert_size!(ComplDataV1, 288);
+
+/// Encrypted part of the dump completion data v1
+#[repr(C)]
+#[derive(FromBytes, IntoBytes, Immutable, KnownLayout)]
+struct ComplConfV1 {
+    nonce: [u8; SymKeyType::AES_256_XTS_TWEAK_LEN],
+    key: [u8; SymKeyType::AES_256_XTS_KEY_LEN],
+    res_b0: [u8; 96],
+}
+assert_size!(ComplConfV1, 176);
+
+/// Authenticated part of the CPU state v1
+#[repr(C)]
+#[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+struct CpuAadV1 {
+    version: U32<BigEndian>,
+    len: U32<BigEndian>,
+    iv: [u8; SymKeyType::AES_256_GCM_IV_LEN],
+    res_14: [u8; 12],
+}
+assert_size!(CpuAadV1, 32);
+
+/// CPU state v1 as returned by the Dump CPU State UVC
+#[repr(C)]
+#[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+struct CpuDataV1 {
+    aad: CpuAadV1,
+    encr: [u8; 1520],
+    tag: [u8; SymKeyType::AES_256_GCM_TAG_LEN],
+}
+assert_size!(CpuDataV1, 1568);
+
+/// Encrypted part of the CPU state v1
+#[repr(C)]
+#[derive(FromBytes, IntoBytes, Immutable, KnownLayout)]
+struct CpuConfV1 {
+    gprs: [U64<BigEndian>; 16],
+    psw: [U64<BigEndian>; 2],
+    res_90: [u8; 8],
+    prefix: U32<BigEndian>,
+    fpc: U32<BigEndian>,
+    res_a0: [u8; 4],
+    todpreg: U32<BigEndian>,
+    timer: U64<BigEndian>,
+    todcmp: U64<BigEndian>,
+    res_b8: [u8; 8],
+    acrs: [U32<BigEndian>; 16],
+    ctrs: [U64<BigEndian>; 16],
+    /// Per register: the floating point register (left half), then the right half
+    vrs_low: [[U64<BigEndian>; 2]; 16],
+    vrs_high: [[u8; 16]; 16],
+    res_380: [u8; 512],
+    zeros_580: [u8; 8],
+    gsd: U64<BigEndian>,
+    gssm: U64<BigEndian>,
+    gs_epl_a: U64<BigEndian>,
+    res_5a0: [u8; 64],
+    dump_flags: U16<BigEndian>,
+    res_5e2: [u8; 14],
+}
+assert_size!(CpuConfV1, 1520);
+
+/// Derives the dump key from the customer communication key (CCK) and the seed of the dump
+/// completion data.
+///
+/// # Errors
+///
+/// This function will return an error if OpenSSL could not derive the key.
+pub fn derive_dump_key(cck: &Confidential<[u8; 32]>, seed: &[u8; 64]) -> Result<Aes256GcmKey> {
+    hkdf_rfc_5869(Md::sha512(), cck.value(), seed, DUMP_KEY_INFO).map(Confidential::new)
+}
+
+/// Reads the big-endian version that every versioned dump structure starts with.
+fn version(data: &[u8], what: &'static str) -> Result<u32> {
+    U32::<BigEndian>::read_from_prefix(data)
+        .map(|(v, _)| v.get())
+        .map_err(|_| Error::DumpCorrupted(what))
+}
+
+/// Decrypted dump completion data
+///
+/// Holds the keys to decrypt the CPU states and the memory of a Secure Execution guest dump.
+#[derive(Debug)]
+pub struct DumpCompletion {
+    dump_key: SymKey,
+    nonce: Confidential<[u8; SymKeyType::AES_256_XTS_TWEAK_LEN]>,
+    xts_key: Aes256XtsKey,
+}
+
+impl DumpCompletion {
+    /// Decrypts the dump completion data (the content of the [`COMPL_SECTION`]) with the
+    /// customer communication key of the guest.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data has an unsupported version, is corrupted,
+    /// or was not encrypted for `cck`.
+    pub fn decrypt(data: &[u8], cck: &Confidential<[u8; 32]>) -> Result<Self> {
+        const WHAT: &str = "completion data";
+        match version(data, WHAT)? {
+            COMPL_VERSION_1 => {}
+            version => {
+                return Err(Error::DumpUnsupportedVersion {
+                    what: WHAT,
+                    version,
+                })
+            }
+        }
+        let (compl, _) =
+            ComplDataV1::read_from_prefix(data).map_err(|_| Error::DumpCorrupted(WHAT))?;
+        if compl.aad.len.get() as usize != size_of::<ComplDataV1>() {
+            return Err(Error::DumpCorrupted(WHAT));
+        }
+
+        let dump_key = SymKey::Aes256(derive_dump_key(cck, &compl.aad.seed)?);
+        let conf = decrypt_aead(
+            &dump_key,
+            &compl.aad.iv,
+            compl.aad.as_bytes(),
+            &compl.encr,
+            &compl.tag,
+        )?
+        .into_plain();
+        let conf =
+            ComplConfV1::ref_from_bytes(conf.value()).map_err(|_| Error::DumpCorrupted(WHAT))?;
+        Ok(Self {
+            dump_key,
+            nonce: Confidential::new(conf.nonce),
+            xts_key: Confidential::new(conf.key),
+        })
+    }
+
+    /// Decrypts the description of a [`NT_S390_PV_CPU_DATA`] note.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the note has an unsupported version, is corrupted,
+    /// or does not belong to this dump.
+    pub fn decrypt_cpu(&self, note: &[u8]) -> Result<CpuState> {
+        const WHAT: &str = "CPU state";
+        match version(note, WHAT)? {
+            CPU_VERSION_1 => {}
+            version => {
+                return Err(Error::DumpUnsupportedVersion {
+                    what: WHAT,
+                    version,
+                })
+            }
+        }
+        let (cpu, _) = CpuDataV1::read_from_prefix(note).map_err(|_| Error::DumpCorrupted(WHAT))?;
+        let conf = decrypt_aead(
+            &self.dump_key,
+            &cpu.aad.iv,
+            cpu.aad.as_bytes(),
+            &cpu.encr,
+            &cpu.tag,
+        )?
+        .into_plain();
+        let conf =
+            CpuConfV1::ref_from_bytes(conf.value()).map_err(|_| Error::DumpCorrupted(WHAT))?;
+        Ok(CpuState::from(conf))
+    }
+
+    /// Writes the plain content of a memory page of the dump into `out`.
+    ///
+    /// * `component` - tweak component of the page from the [`MEM_META_SECTION`]
+    /// * `page` - page as found in the dump
+    /// * `out` - buffer for the plain page
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tweak component is invalid, the buffers are no
+    /// pages, or the page could not be decrypted by OpenSSL.
+    pub fn decrypt_page(
+        &self,
+        component: &[u8; TWEAK_COMPONENT_SIZE],
+        page: &[u8],
+        out: &mut [u8],
+    ) -> Result<()> {
+        if page.len() != PAGESIZE || out.len() != PAGESIZE {
+            return Err(Error::DumpCorrupted("memory page"));
+        }
+        match PageState::from_component(component)? {
+            PageState::Zero => out.fill(0),
+            PageState::Shared => out.copy_from_slice(page),
+            PageState::Encrypted => {
+                let mut tweak = *component;
+                tweak
+                    .iter_mut()
+                    .zip(self.nonce.value())
+                    .for_each(|(t, n)| *t |= n);
+                let mut crypter = Crypter::new(
+                    Cipher::aes_256_xts(),
+                    Mode::Decrypt,
+                    self.xts_key.value(),
+                    Some(&tweak),
+                )?;
+                crypter.pad(false);
+                // XTS needs no extra block space, but OpenSSL requires it.
+                let mut buf =
+                    Confidential::new(vec![0; PAGESIZE + Cipher::aes_256_xts().block_size()]);
+                let len = crypter.update(page, buf.value_mut())?;
+                let len = len + crypter.finalize(&mut buf.value_mut()[len..])?;
+                if len != PAGESIZE {
+                    return Err(Error::InvalSslData);
+                }
+                out.copy_from_slice(&buf.value()[..PAGESIZE]);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// State of a memory page in a Secure Execution guest dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageState {
+    /// The page is encrypted with AES-256-XTS
+    Encrypted,
+    /// The page contains only zeros
+    Zero,
+    /// The page is shared with the host and therefore not encrypted
+    Shared,
+}
+
+impl PageState {
+    const SPECIAL_INDICATOR: [u8; 4] = [0xff; 4];
+    const MAPPED: u8 = 0x04;
+    const SHARED: u8 = 0x02;
+    const ZERO: u8 = 0x01;
+
+    /// Interprets a tweak component of the [`MEM_META_SECTION`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tweak component describes no valid state.
+    pub fn from_component(component: &[u8; TWEAK_COMPONENT_SIZE]) -> Result<Self> {
+        if component[..4] != Self::SPECIAL_INDICATOR {
+            return Ok(Self::Encrypted);
+        }
+        let flags = component[15];
+        if component[14] != 0 {
+            return Err(Error::DumpCorrupted("page state"));
+        }
+        match flags & !Self::MAPPED {
+            Self::ZERO => Ok(Self::Zero),
+            Self::SHARED => Ok(Self::Shared),
+            _ => Err(Error::DumpCorrupted("page state")),
+        }
+    }
+}
+
+/// Decrypted state of a CPU of a Secure Execution guest
+///
+/// The register sets are in the layout of the corresponding Linux ELF core notes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CpuState {
+    /// General purpose registers
+    pub gprs: [u64; 16],
+    /// Program status word
+    pub psw: [u64; 2],
+    /// Prefix register
+    pub prefix: u32,
+    /// Floating point control register
+    pub fpc: u32,
+    /// TOD programmable register
+    pub todpreg: u32,
+    /// CPU timer
+    pub timer: u64,
+    /// Clock comparator
+    pub todcmp: u64,
+    /// Access registers
+    pub acrs: [u32; 16],
+    /// Control registers
+    pub ctrs: [u64; 16],
+    /// Floating point registers, i.e., the left halves of vector registers 0-15
+    pub fprs: [u64; 16],
+    /// Right halves of vector registers 0-15
+    pub vxrs_low: [u64; 16],
+    /// Vector registers 16-31
+    pub vxrs_high: [[u8; 16]; 16],
+    /// Guarded storage designation
+    pub gsd: u64,
+    /// Guarded storage section mask
+    pub gssm: u64,
+    /// Guarded storage event parameter list address
+    pub gs_epl_a: u64,
+    /// Dump flags
+    pub dump_flags: u16,
+}
+
+impl CpuState {
+    const HAS_OSII: u16 = 0x8000;
+
+    /// Returns true if the CPU state may contain results of a partially executed instruction.
+    pub fn has_osii(&self) -> bool {
+        self.dump_flags & Self::HAS_OSII != 0
+    }
+}
+
+impl From<&CpuConfV1> for CpuState {
+    fn from(conf: &CpuConfV1) -> Self {
+        Self {
+            gprs: conf.gprs.map(U64::get),
+            psw: conf.psw.map(U64::get),
+            prefix: conf.prefix.get(),
+            fpc: conf.fpc.get(),
+            todpreg: conf.todpreg.get(),
+            timer: conf.timer.get(),
+            todcmp: conf.todcmp.get(),
+            acrs: conf.acrs.map(U32::get),
+            ctrs: conf.ctrs.map(U64::get),
+            fprs: conf.vrs_low.map(|vr| vr[0].get()),
+            vxrs_low: conf.vrs_low.map(|vr| vr[1].get()),
+            vxrs_high: conf.vrs_high,
+            gsd: conf.gsd.get(),
+            gssm: conf.gssm.get(),
+            gs_epl_a: conf.gs_epl_a.get(),
+            dump_flags: conf.dump_flags.get(),
+        }
+    }
+}
+
+/// Helpers to create encrypted dump data, e.g. for testing dump processing tools
+#[doc(hidden)]
+pub mod test_data {
+    use zerocopy::FromZeros;
+
+    use super::*;
+    use crate::crypto::encrypt_aead;
+
+    /// Creates dump completion data v1 encrypted for `cck`.
+    pub fn completion(cck: &Confidential<[u8; 32]>, nonce: [u8; 16], xts_key: [u8; 64]) -> Vec<u8> {
+        let aad = ComplAadV1 {
+            version: COMPL_VERSION_1.into(),
+            len: (size_of::<ComplDataV1>() as u32).into(),
+            res_8: [0; 8],
+            seed: [0x5e; 64],
+            iv: [0x1f; 12],
+            res_5c: [0; 4],
+        };
+        let conf = ComplConfV1 {
+            nonce,
+            key: xts_key,
+            res_b0: [0; 96],
+        };
+        let key = SymKey::Aes256(derive_dump_key(cck, &aad.seed).unwrap());
+        encrypt_aead(&key, &aad.iv, aad.as_bytes(), conf.as_bytes())
+            .unwrap()
+            .into_buf()
+    }
+
+    /// Creates a CPU state note v1 encrypted for `cck` with the given general purpose registers.
+    pub fn cpu(cck: &Confidential<[u8; 32]>, gprs: [u64; 16]) -> Vec<u8> {
+        let aad = CpuAadV1 {
+            version: CPU_VERSION_1.into(),
+            len: (size_of::<CpuDataV1>() as u32).into(),
+            iv: [0x2f; 12],
+            res_14: [0; 12],
+        };
+        let mut conf = CpuConfV1::new_zeroed();
+        conf.gprs = gprs.map(U64::new);
+        conf.psw = [U64::new(0x0705_0001_8000_0000), U64::new(0x1000)];
+        conf.prefix = U32::new(0x2000);
+        conf.vrs_low[1] = [U64::new(0x1111), U64::new(0x2222)];
+        conf.dump_flags = U16::new(CpuState::HAS_OSII);
+        let key = SymKey::Aes256(derive_dump_key(cck, &[0x5e; 64]).unwrap());
+        encrypt_aead(&key, &aad.iv, aad.as_bytes(), conf.as_bytes())
+            .unwrap()
+            .into_buf()
+    }
+
+    /// Encrypts `page` with AES-256-XTS like the Ultravisor.
+    pub fn encrypt_page(
+        xts_key: &[u8; 64],
+        nonce: &[u8; 16],
+        component: &[u8; 16],
+        page: &[u8],
+    ) -> Vec<u8> {
+        let mut tweak = *component;
+        tweak.iter_mut().zip(nonce).for_each(|(t, n)| *t |= n);
+        openssl::symm::encrypt(Cipher::aes_256_xts(), xts_key, Some(&tweak), page).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cck() -> Confidential<[u8; 32]> {
+        Confidential::new([0x42; 32])
+    }
+
+    #[test]
+    fn completion() {
+        let data = test_data::completion(&cck(), [0x11; 16], [0x22; 64]);
+        let compl = DumpCompletion::decrypt(&data, &cck()).unwrap();
+        assert_eq!(compl.nonce.value(), &[0x11; 16]);
+        assert_eq!(compl.xts_key.value(), &[0x22; 64]);
+
+        assert!(matches!(
+            DumpCompletion::decrypt(&data, &Confidential::new([0x43; 32])),
+            Err(Error::GcmTagMismatch)
+        ));
+        assert!(matches!(
+            DumpCompletion::decrypt(&data[..100], &cck()),
+            Err(Error::DumpCorrupted(_))
+        ));
+        let mut v2 = data.clone();
+        v2[3] = 2;
+        assert!(matches!(
+            DumpCompletion::decrypt(&v2, &cck()),
+            Err(Error::DumpUnsupportedVersion { version: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn cpu() {
+        let compl =
+            DumpCompletion::decrypt(&test_data::completion(&cck(), [0; 16], [1; 64]), &cck())
+                .unwrap();
+        let gprs = core::array::from_fn(|i| i as u64);
+        let cpu = compl.decrypt_cpu(&test_data::cpu(&cck(), gprs)).unwrap();
+        assert_eq!(cpu.gprs, gprs);
+        assert_eq!(cpu.psw, [0x0705_0001_8000_0000, 0x1000]);
+        assert_eq!(cpu.prefix, 0x2000);
+        assert_eq!(cpu.fprs[1], 0x1111);
+        assert_eq!(cpu.vxrs_low[1], 0x2222);
+        assert!(cpu.has_osii());
+    }
+
+    #[test]
+    fn page_state() {
+        let special = |flags| {
+            let mut c = [0; 16];
+            c[..4].copy_from_slice(&[0xff; 4]);
+            c[15] = flags;
+            c
+        };
+        assert_eq!(
+            PageState::from_component(&[0; 16]).unwrap(),
+            PageState::Encrypted
+        );
+        assert_eq!(
+            PageState::from_component(&special(0x05)).unwrap(),
+            PageState::Zero
+        );
+        assert_eq!(
+            PageState::from_component(&special(0x02)).unwrap(),
+            PageState::Shared
+        );
+        assert!(PageState::from_component(&special(0x04)).is_err());
+        assert!(PageState::from_component(&special(0x03)).is_err());
+        assert!(PageState::from_component(&special(0x11)).is_err());
+        let mut res = special(0x01);
+        res[14] = 1;
+        assert!(PageState::from_component(&res).is_err());
+    }
+
+    #[test]
+    fn page() {
+        let nonce = [0x0f; 16];
+        let key = core::array::from_fn(|i| i as u8);
+        let compl =
+            DumpCompletion::decrypt(&test_data::completion(&cck(), nonce, key), &cck()).unwrap();
+        let plain: Vec<u8> = (0..PAGESIZE).map(|i| i as u8).collect();
+        let component = [0xa0; 16];
+        let encr = test_data::encrypt_page(&key, &nonce, &component, &plain);
+        assert_ne!(encr, plain);
+
+        let mut out = vec![0xff; PAGESIZE];
+        compl.decrypt_page(&component, &encr, &mut out).unwrap();
+        assert_eq!(out, plain);
+
+        let mut zero = [0; 16];
+        zero[..4].copy_from_slice(&[0xff; 4]);
+        zero[15] = 0x01;
+        compl.decrypt_page(&zero, &encr, &mut out).unwrap();
+        assert_eq!(out, vec![0; PAGESIZE]);
+        assert!(compl.decrypt_page(&zero, &encr[1..], &mut out).is_err());
+    }
+}
@@ -2,27 +2,31 @@
 //
 // Copyright IBM Corp. 2023
 
+use std::{fmt::Display, mem::size_of};
+
 use super::{guest_secret::ListableSecretHdr, user_data::UserData};
 use crate::{
     assert_size,
-    crypto::{hkdf_rfc_5869, AeadEncryptionResult},
-    misc::Flags,
+    crypto::AeadEncryptionResult,
+    misc::{encode_hex, Flags},
     req::{Aad, BinReqValues, Keyslot, ReqEncrCtx},
     request::{BootHdrTags, Confidential, Request},
     secret::{ExtSecret, GuestSecret},
-    uv::{ConfigUid, UvFlags},
-    Result,
+    uv::{ConfigUid, ListableSecretType, UvFlags},
+    Error, Result,
 };
-use openssl::{
-    md::Md,
-    pkey::{PKey, Private, Public},
+use openssl::pkey::{PKey, Private, Public};
+use pv_core::{
+    request::RequestVersion,
+    secret::{AddSecretMagic, UserDataType},
+    uv::SecretId,
 };
-use pv_core::{request::RequestVersion, secret::AddSecretMagic, uv::SecretId};
-use zerocopy::{Immutable, IntoBytes};
+use serde::{Serialize, Serializer};
+use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, KnownLayout, U16, U32};
 
 /// Authenticated data w/o user data
 #[repr(C)]
-#[derive(Debug, Clone, Copy, IntoBytes, Immutable)]
+#[derive(Debug, Clone, Copy, IntoBytes, FromBytes, Immutable, KnownLayout)]
 struct ReqAuthData {
     flags: UvFlags,
     boot_tags: BootHdrTags,
@@ -62,22 +66,91 @@ impl ReqConfData {
     }
 }
 
+/// A flag of an [`AddSecretRequest`]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddSecretFlag {
+    /// Disable host-initiated dumping of the guest
+    ///
+    /// After the request was dispatched successfully,
+    /// the UV will not provide any dump decryption information for the SE-guest anymore.
+    DisableDump,
+}
+
+impl AddSecretFlag {
+    /// All add-secret flags
+    pub const ALL: [Self; 1] = [Self::DisableDump];
+
+    /// Bit of this flag in MSB0 ordering
+    const fn bit(&self) -> u8 {
+        match self {
+            Self::DisableDump => 0,
+        }
+    }
+
+    /// Returns a human readable name of this flag.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::DisableDump => "disable-dump",
+        }
+    }
+}
+
+impl Display for AddSecretFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 /// Flags for [`AddSecretRequest`]
-#[derive(Default, Clone, Copy, Debug)]
+///
+/// Only [`AddSecretFlag`]s can be set.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct AddSecretFlags(UvFlags);
 impl AddSecretFlags {
+    /// Creates flags from the raw value as found in the request.
+    ///
+    /// No validity checks are made; use it only to test flags this library does not know yet.
+    pub fn from_raw(flags: u64) -> Self {
+        Self(flags.into())
+    }
+
+    /// Enables the flag `flag`.
+    pub fn set(&mut self, flag: AddSecretFlag) {
+        self.0.set_bit(flag.bit())
+    }
+
+    /// Check whether the flag `flag` is enabled.
+    pub fn is_set(&self, flag: AddSecretFlag) -> bool {
+        self.0.is_set(flag.bit())
+    }
+
+    /// Returns the enabled flags.
+    pub fn flags(&self) -> Vec<AddSecretFlag> {
+        AddSecretFlag::ALL
+            .into_iter()
+            .filter(|f| self.is_set(*f))
+            .collect()
+    }
+
     /// Enables the disable-dump flag
     ///
-    /// After the request was dispatched successfully,
-    /// the UV will not provide any dump decryption information for the SE-guest anymore.
+    /// See [`AddSecretFlag::DisableDump`].
     pub fn set_disable_dump(&mut self) {
-        self.0.set_bit(0)
+        self.set(AddSecretFlag::DisableDump)
+    }
+
+    /// Check whether the disable-dump flag is enabled.
+    pub fn disable_dump(&self) -> bool {
+        self.is_set(AddSecretFlag::DisableDump)
     }
 }
 
-impl From<&u64> for AddSecretFlags {
-    fn from(v: &u64) -> Self {
-        Self(v.into())
+impl From<&[AddSecretFlag]> for AddSecretFlags {
+    fn from(flags: &[AddSecretFlag]) -> Self {
+        let mut res = Self::default();
+        flags.iter().for_each(|f| res.set(*f));
+        res
     }
 }
 
@@ -177,17 +250,7 @@ impl AddSecretRequest {
     ///
     /// This function will return an error if the key derivation fails for a [`ExtSecret::Derived`].
     pub fn set_ext_secret(&mut self, ext_secret: ExtSecret) -> Result<()> {
-        const DER_EXT_SECRET_INFO: &[u8] = "IBM Z Ultravisor Add-Secret".as_bytes();
-        self.conf.extension_secret = match ext_secret {
-            ExtSecret::Simple(s) => s,
-            ExtSecret::Derived(cck) => hkdf_rfc_5869(
-                Md::sha512(),
-                cck.value(),
-                self.aad.boot_tags.tag(),
-                DER_EXT_SECRET_INFO,
-            )?
-            .into(),
-        };
+        self.conf.extension_secret = ext_secret.into_secret(&self.aad.boot_tags)?;
         Ok(())
     }
 
@@ -295,6 +358,214 @@ impl AddSecretRequest {
         AddSecretMagic::try_from_bytes(asrcb)?;
         BinReqValues::get(asrcb).map(|v| v.tag().to_vec())
     }
+
+    /// Get the non-confidential information of a binary add-secret request
+    ///
+    /// Only interprets the authenticated, unencrypted part of the request. Neither the secret nor
+    /// the authenticity of the request can be checked without the request protection key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `asrcb` is no add-secret request or is truncated.
+    pub fn bin_info(asrcb: &[u8]) -> Result<AddSecretRequestInfo> {
+        const CUST_PUB_KEY_SIZE: usize = 160;
+        const KEYSLOT_SIZE: usize = 80;
+
+        let user_data = AddSecretMagic::try_from_bytes(asrcb)?.kind();
+        let req = BinReqValues::get(asrcb)?;
+        let auth = req
+            .req_dep_aad::<ReqAuthData>()
+            .ok_or(Error::BinRequestSmall)?;
+
+        // The user-data area is always present, zeroed if there is no user data
+        let mut offs = size_of::<ReqAuthData>() + UserData::USER_DATA_SIZE + CUST_PUB_KEY_SIZE;
+        let host_key_hashes = (0..req.nks() as usize)
+            .map(|i| {
+                req.req_dep_aad_at::<[u8; Keyslot::PHKH_SIZE as usize]>(offs + i * KEYSLOT_SIZE)
+                    .copied()
+                    .ok_or(Error::BinRequestSmall)
+            })
+            .collect::<Result<_>>()?;
+        offs += req.nks() as usize * KEYSLOT_SIZE;
+
+        // Secret header: reserved (2), type (2), secret size (4), reserved (8), [ID (32)]
+        let secret_type = req
+            .req_dep_aad_at::<U16<BigEndian>>(offs + 2)
+            .ok_or(Error::BinRequestSmall)?
+            .get();
+        let secret_size = req
+            .req_dep_aad_at::<U32<BigEndian>>(offs + 4)
+            .ok_or(Error::BinRequestSmall)?
+            .get();
+        let secret_id = match ListableSecretType::from(secret_type) {
+            ListableSecretType::Association | ListableSecretType::Retrievable(_) => Some(
+                req.req_dep_aad_at::<SecretId>(offs + 16)
+                    .ok_or(Error::BinRequestSmall)?
+                    .clone(),
+            ),
+            _ => None,
+        };
+
+        Ok(AddSecretRequestInfo {
+            version: req.version(),
+            size: req.len(),
+            secret_type,
+            secret_id,
+            secret_size,
+            flags: AddSecretFlags(auth.flags),
+            se_header_tag: *auth.boot_tags.tag(),
+            cuid: Some(auth.cuid).filter(|c| c != &[0; 16]),
+            user_data,
+            host_key_hashes,
+        })
+    }
+}
+
+fn ser_display<S: Serializer, T: Display>(v: &T, ser: S) -> std::result::Result<S::Ok, S::Error> {
+    ser.collect_str(v)
+}
+
+fn ser_flags<S: Serializer>(
+    flags: &AddSecretFlags,
+    ser: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    ser.collect_seq(flags.flags().iter().map(AddSecretFlag::name))
+}
+
+fn ser_hex<S: Serializer, T: AsRef<[u8]>>(v: &T, ser: S) -> std::result::Result<S::Ok, S::Error> {
+    ser.collect_str(&format_args!("0x{}", encode_hex(v)))
+}
+
+fn ser_hex_opt<S: Serializer, T: AsRef<[u8]>>(
+    v: &Option<T>,
+    ser: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match v {
+        Some(v) => ser_hex(v, ser),
+        None => ser.serialize_none(),
+    }
+}
+
+fn ser_hex_list<S: Serializer, T: AsRef<[u8]>>(
+    v: &[T],
+    ser: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    ser.collect_seq(v.iter().map(|v| format!("0x{}", encode_hex(v))))
+}
+
+/// Non-confidential information of a binary [`AddSecretRequest`]
+///
+/// See [`AddSecretRequest::bin_info`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddSecretRequestInfo {
+    version: u32,
+    size: usize,
+    #[serde(serialize_with = "ser_secret_type")]
+    secret_type: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret_id: Option<SecretId>,
+    secret_size: u32,
+    #[serde(serialize_with = "ser_flags")]
+    flags: AddSecretFlags,
+    #[serde(serialize_with = "ser_hex")]
+    se_header_tag: [u8; 16],
+    #[serde(serialize_with = "ser_hex_opt")]
+    cuid: Option<ConfigUid>,
+    #[serde(serialize_with = "ser_display")]
+    user_data: UserDataType,
+    #[serde(serialize_with = "ser_hex_list")]
+    host_key_hashes: Vec<[u8; Keyslot::PHKH_SIZE as usize]>,
+}
+
+fn secret_type_name(kind: u16) -> String {
+    match kind {
+        ListableSecretType::NULL => "Null".to_string(),
+        ListableSecretType::UPDATE_CCK => "Update CCK".to_string(),
+        kind => ListableSecretType::from(kind).to_string(),
+    }
+}
+
+fn ser_secret_type<S: Serializer>(kind: &u16, ser: S) -> std::result::Result<S::Ok, S::Error> {
+    ser.serialize_str(&secret_type_name(*kind))
+}
+
+impl AddSecretRequestInfo {
+    /// Returns the version of the request.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the size of the request in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the UV secret-type id of the secret, see [`ListableSecretType`].
+    pub fn secret_type(&self) -> u16 {
+        self.secret_type
+    }
+
+    /// Returns the ID of the secret if the secret is listable.
+    pub fn secret_id(&self) -> Option<&SecretId> {
+        self.secret_id.as_ref()
+    }
+
+    /// Returns the size of the (encrypted) secret in bytes.
+    pub fn secret_size(&self) -> u32 {
+        self.secret_size
+    }
+
+    /// Returns the flags of the request.
+    pub fn flags(&self) -> &AddSecretFlags {
+        &self.flags
+    }
+
+    /// Returns the SE-header tag of the targeted image.
+    pub fn se_header_tag(&self) -> &[u8; 16] {
+        &self.se_header_tag
+    }
+
+    /// Returns the Configuration Unique ID of the targeted guest, if the request is bound to one.
+    pub fn cuid(&self) -> Option<&ConfigUid> {
+        self.cuid.as_ref()
+    }
+
+    /// Returns the kind of user data of the request.
+    pub fn user_data(&self) -> UserDataType {
+        self.user_data
+    }
+
+    /// Returns the public host-key hashes of the host-keys the request is encrypted for.
+    pub fn host_key_hashes(&self) -> &[[u8; Keyslot::PHKH_SIZE as usize]] {
+        &self.host_key_hashes
+    }
+}
+
+impl Display for AddSecretRequestInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Version: 0x{:04x}", self.version)?;
+        writeln!(f, "Size: {} bytes", self.size)?;
+        writeln!(f, "Secret type: {}", secret_type_name(self.secret_type))?;
+        if let Some(id) = &self.secret_id {
+            writeln!(f, "Secret ID: {id}")?;
+        }
+        writeln!(f, "Secret size: {} bytes", self.secret_size)?;
+        let flags: Vec<_> = self.flags.flags().iter().map(|f| f.name()).collect();
+        match flags.is_empty() {
+            true => writeln!(f, "Flags: none")?,
+            false => writeln!(f, "Flags: {}", flags.join(", "))?,
+        }
+        writeln!(f, "SE-header tag: 0x{}", encode_hex(self.se_header_tag))?;
+        match &self.cuid {
+            Some(cuid) => writeln!(f, "Config UID: 0x{}", encode_hex(cuid))?,
+            None => writeln!(f, "Config UID: none")?,
+        }
+        writeln!(f, "User data: {}", self.user_data)?;
+        writeln!(f, "Host-key hashes:")?;
+        self.host_key_hashes
+            .iter()
+            .try_for_each(|h| writeln!(f, "  0x{}", encode_hex(h)))
+    }
 }
 
 impl Request for AddSecretRequest {
@@ -313,4 +584,8 @@ impl Request for AddSecretRequest {
     fn add_hostkey(&mut self, hostkey: PKey<Public>) {
         self.keyslots.push(Keyslot::new(hostkey))
     }
+
+    fn num_hostkeys(&self) -> usize {
+        self.keyslots.len()
+    }
 }
@@ -2,7 +2,12 @@
 //
 // Copyright IBM Corp. 2023
 
-use crate::request::Confidential;
+use openssl::md::Md;
+
+use crate::{
+    request::{hkdf_rfc_5869, BootHdrTags, Confidential, ZeroizeOnDrop},
+    Result,
+};
 
 /// Extension Secret for [`crate::secret::AddSecretRequest`]
 #[derive(Debug, Clone)]
@@ -12,3 +17,69 @@ pub enum ExtSecret {
     /// A secret that is derived from the Customer communication key from the SE-header
     Derived(Confidential<[u8; 32]>), // contains the cck
 }
+
+/// Both the secret and the customer communication key are [`Confidential`].
+impl ZeroizeOnDrop for ExtSecret {}
+
+impl ExtSecret {
+    const DER_EXT_SECRET_INFO: &'static [u8] = b"IBM Z Ultravisor Add-Secret";
+
+    /// Derives the extension secret from the customer communication key (CCK) of an SE-guest.
+    ///
+    /// Uses the same HKDF as the Ultravisor, salted with the SE-header tag of the guest.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if OpenSSL could not derive the key.
+    pub fn derive(
+        cck: &Confidential<[u8; 32]>,
+        tags: &BootHdrTags,
+    ) -> Result<Confidential<[u8; 32]>> {
+        hkdf_rfc_5869(
+            Md::sha512(),
+            cck.value(),
+            tags.tag(),
+            Self::DER_EXT_SECRET_INFO,
+        )
+        .map(Confidential::new)
+    }
+
+    /// Returns the extension secret that ends up in an add-secret request for the SE-guest with
+    /// the given boot tags.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the key derivation fails for a [`ExtSecret::Derived`].
+    pub fn into_secret(self, tags: &BootHdrTags) -> Result<Confidential<[u8; 32]>> {
+        match self {
+            Self::Simple(s) => Ok(s),
+            Self::Derived(cck) => Self::derive(&cck, tags),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn into_secret() {
+        let tags = BootHdrTags::new([0; 64], [0; 64], [0; 64], [0x11; 16]);
+        let other = BootHdrTags::new([0; 64], [0; 64], [0; 64], [0x22; 16]);
+        let key = Confidential::new([0x42; 32]);
+
+        let simple = ExtSecret::Simple(key.clone()).into_secret(&tags).unwrap();
+        assert_eq!(simple.value(), key.value());
+
+        let derived = ExtSecret::Derived(key.clone()).into_secret(&tags).unwrap();
+        assert_ne!(derived.value(), key.value());
+        assert_eq!(
+            derived.value(),
+            ExtSecret::derive(&key, &tags).unwrap().value()
+        );
+        assert_ne!(
+            derived.value(),
+            ExtSecret::derive(&key, &other).unwrap().value()
+        );
+    }
+}
@@ -7,9 +7,10 @@ use super::asrcb::AddSecretRequest;
 use crate::{
     assert_size,
     crypto::{hash, random_array, SymKeyType},
+    fips_mode,
     request::{
         openssl::{NID_ED25519, NID_ED448},
-        Confidential,
+        Confidential, ZeroizeOnDrop,
     },
     uv::{
         AesSizes, AesXtsSizes, EcCurves, HmacShaSizes, ListableSecretType, RetrievableSecret,
@@ -76,6 +77,9 @@ pub enum GuestSecret {
     },
 }
 
+/// The secret values, including customer communication keys, are [`Confidential`].
+impl ZeroizeOnDrop for GuestSecret {}
+
 macro_rules! retr_constructor {
     ($(#[$err:meta])* | $(#[$kind:meta])* =>  $type: ty, $func: ident) => {
         /// Create a new
@@ -341,8 +345,9 @@ fn ec(key: PKey<Private>) -> Result<RetrKeyInfo> {
         Nid::X9_62_PRIME256V1 => EcCurves::Secp256R1,
         Nid::SECP384R1 => EcCurves::Secp384R1,
         Nid::SECP521R1 => EcCurves::Secp521R1,
-        NID_ED25519 => EcCurves::Ed25519,
-        NID_ED448 => EcCurves::Ed448,
+        // Not provided by the validated OpenSSL FIPS providers.
+        NID_ED25519 => fips_mode::reject("Ed25519").map(|_| EcCurves::Ed25519)?,
+        NID_ED448 => fips_mode::reject("Ed448").map(|_| EcCurves::Ed448)?,
         nid => {
             // Use some EC type to get exp sizes and name
             let ec = RetrievableSecret::Ec(EcCurves::Secp521R1);
@@ -483,6 +488,14 @@ mod test {
     use pv_core::uv::AesSizes;
     use serde_test::{assert_tokens, Token};
 
+    #[test]
+    fn zeroize_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<GuestSecret>();
+        assert_zeroize_on_drop::<crate::secret::ExtSecret>();
+        assert_zeroize_on_drop::<RetrieveCmd>();
+    }
+
     #[test]
     fn association() {
         let secret_value = [0x11; 32];
@@ -7,14 +7,13 @@ use crate::{
     crypto::{sign_msg, verify_signature},
     req::BinReqValues,
     request::{
-        openssl::pkey::{HasParams, HasPublic, Id, PKey, PKeyRef, Private, Public},
+        openssl::pkey::{HasPublic, Id, PKey, PKeyRef, Private, Public},
         RequestMagic,
     },
     secret::{AddSecretMagic, AddSecretRequest, AddSecretVersion, UserDataType},
     Error, Result,
 };
 use openssl::hash::MessageDigest;
-use openssl::nid::Nid;
 use zerocopy::{BigEndian, FromBytes, IntoBytes, KnownLayout, U16};
 
 /// User data.
@@ -94,20 +93,15 @@ pub(super) struct SignedUserData {
 }
 
 impl UserData {
-    const USER_DATA_SIZE: usize = 0x200;
+    pub(super) const USER_DATA_SIZE: usize = 0x200;
 
     fn user_data_type<P: HasPublic>(sign_key: &PKeyRef<P>) -> Result<UserDataType> {
-        fn check_curve<P: HasParams>(pkey: &PKeyRef<P>) -> Result<bool> {
-            let nid = pkey.ec_key()?.group().curve_name();
-            match nid {
-                Some(nid) => Ok(nid == Nid::SECP521R1),
-                None => Ok(false),
-            }
-        }
-        match sign_key.id() {
-            Id::EC if check_curve(sign_key)? => Ok(UserDataType::SgnEcSECP521R1),
-            Id::RSA if sign_key.rsa()?.size() == 2048 / 8 => Ok(UserDataType::SgnRsa2048),
-            Id::RSA if sign_key.rsa()?.size() == 3072 / 8 => Ok(UserDataType::SgnRsa3072),
+        // Only use generic key parameters; keys held in a token (e.g. PKCS#11) cannot always be
+        // converted into legacy EC/RSA keys. secp521r1 is the only supported 521-bit curve.
+        match (sign_key.id(), sign_key.bits()) {
+            (Id::EC, 521) => Ok(UserDataType::SgnEcSECP521R1),
+            (Id::RSA, 2048) => Ok(UserDataType::SgnRsa2048),
+            (Id::RSA, 3072) => Ok(UserDataType::SgnRsa3072),
             _ => Err(Error::BinAsrcbUnsupportedUserDataSgnKey),
         }
     }
@@ -379,6 +373,9 @@ impl From<VerifiedUserData> for Vec<u8> {
 
 #[cfg(test)]
 mod test {
+    use proptest::{collection::vec, num::u8, proptest};
+    use pv_core::request::MagicValue;
+
     use super::*;
     use crate::{get_test_asset, test_utils::get_test_keys};
 
@@ -610,4 +607,16 @@ mod test {
         assert_eq!(data_out, Some(data_in.as_ref()));
         assert_eq!(pad, exp_pad);
     }
+
+    proptest! {
+        #[test]
+        fn verify_asrcb_no_panic(data in vec(u8::ANY, 0..0x400), kind in 0..4u16) {
+            let mut asrcb = AddSecretMagic::MAGIC.to_vec();
+            asrcb.extend_from_slice(&kind.to_be_bytes());
+            asrcb.extend_from_slice(&data);
+            let _ = AddSecretRequest::bin_id(&asrcb);
+            let _ = AddSecretRequest::bin_tag(&asrcb);
+            let _ = verify_asrcb_and_get_user_data(asrcb, None);
+        }
+    }
 }
@@ -4,11 +4,13 @@
 
 use crate::openssl_extensions::{StackableX509Crl, X509StoreContextExtension, X509StoreExtension};
 use core::slice;
-use log::{debug, trace};
+use log::{debug, trace, warn};
 use openssl::error::ErrorStack;
 use openssl::stack::Stack;
 use openssl::x509::store::X509Store;
-use openssl::x509::{CrlStatus, X509NameRef, X509Ref, X509StoreContext, X509StoreContextRef, X509};
+use openssl::x509::{
+    CrlStatus, X509Crl, X509NameRef, X509Ref, X509StoreContext, X509StoreContextRef, X509,
+};
 use std::path::Path;
 
 #[cfg(not(test))]
@@ -16,13 +18,20 @@ use helper::download_first_crl_from_x509;
 #[cfg(test)]
 use test::download_first_crl_from_x509;
 
-use crate::error::bail_hkd_verify;
 use crate::misc::{read_certs, read_file};
-use crate::Result;
+use crate::HkdVerifyErrorType::*;
+use crate::{Error, Result};
 
+mod cache;
 mod helper;
+mod report;
 mod test;
 
+pub use cache::CrlCache;
+pub use report::{
+    CertInfo, Check, CheckOutcome, CrlInfo, HkdReport, PinInfo, VerificationReport, HKD_CHECKS,
+};
+
 /// A `HkdVerifier` verifies that a host-key document(HKD) can be trusted.
 ///
 /// If the verification fails the HKD should not be used to create requests.
@@ -34,6 +43,28 @@ pub trait HkdVerifier {
     /// This function will return an error if the host-key document cannot be
     /// trusted. Refer to the concrete Error type for the specific reason.
     fn verify(&self, hkd: &X509Ref) -> Result<()>;
+
+    /// Checks if the given host-key document can be trusted and records the checks in `report`.
+    ///
+    /// The default implementation records the result of [`HkdVerifier::verify`] as a single
+    /// check.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the host-key document cannot be
+    /// trusted. Refer to the concrete Error type for the specific reason.
+    fn verify_with_report(&self, hkd: &X509Ref, report: &mut HkdReport) -> Result<()> {
+        let res = report.check("verify", self.verify(hkd));
+        report.verified = res.is_ok();
+        res
+    }
+
+    /// The certificates of the chain of trust, starting with the IBM Z signing key.
+    ///
+    /// Empty if the verifier does not use a chain of trust.
+    fn chain(&self) -> &[X509] {
+        &[]
+    }
 }
 
 /// A verifier that does not verify and accepts all given host-keys as valid.
@@ -43,13 +74,20 @@ impl HkdVerifier for NoVerifyHkd {
     fn verify(&self, _hkd: &X509Ref) -> Result<()> {
         Ok(())
     }
+
+    fn verify_with_report(&self, _hkd: &X509Ref, report: &mut HkdReport) -> Result<()> {
+        report.finish("verification disabled");
+        Ok(())
+    }
 }
 
 /// A verifier that checks the host-key document against a chain of trust.
 pub struct CertVerifier {
     store: X509Store,
     ibm_z_sign_key: X509,
+    chain: Vec<X509>,
     offline: bool,
+    cache: Option<CrlCache>,
 }
 
 impl std::fmt::Debug for CertVerifier {
@@ -67,12 +105,30 @@ impl HkdVerifier for CertVerifier {
     /// 3. @hkd must not be expired
     /// 4. @hkd must not be revoked
     fn verify(&self, hkd: &X509Ref) -> Result<()> {
-        helper::verify_hkd_options(hkd, &self.ibm_z_sign_key)?;
+        self.verify_with_report(hkd, &mut HkdReport::new(hkd)?)
+    }
+
+    fn verify_with_report(&self, hkd: &X509Ref, report: &mut HkdReport) -> Result<()> {
+        let res = self.verify_checks(hkd, report);
+        report.finish("an earlier check failed");
+        res
+    }
+
+    fn chain(&self) -> &[X509] {
+        &self.chain
+    }
+}
+
+impl CertVerifier {
+    fn verify_checks(&self, hkd: &X509Ref, report: &mut HkdReport) -> Result<()> {
+        helper::verify_hkd_options(hkd, &self.ibm_z_sign_key, report)?;
 
         // verify that the HKD was signed with the key of the IBM signing key
-        if !hkd.verify(self.ibm_z_sign_key.public_key()?.as_ref())? {
-            bail_hkd_verify!(Signature);
-        }
+        let signature = match hkd.verify(self.ibm_z_sign_key.public_key()?.as_ref())? {
+            true => Ok(()),
+            false => Err(Error::HkdVerify(Signature)),
+        };
+        report.check("signature", signature)?;
 
         // Find matching CRL for sign key in the store or download them
         let crls = self.hkd_crls(hkd)?;
@@ -80,26 +136,47 @@ impl HkdVerifier for CertVerifier {
         // Verify that the CRLs are still valid
         let mut verified_crls = Vec::with_capacity(crls.len());
         for crl in &crls {
-            if helper::verify_crl(crl, &self.ibm_z_sign_key).is_some() {
+            let valid = helper::verify_crl(crl, &self.ibm_z_sign_key).is_some();
+            report.crls.push(CrlInfo::new(crl, valid));
+            if valid {
                 verified_crls.push(crl.to_owned());
             }
         }
 
         // Test if HKD was revoked (min1 required)
         if verified_crls.is_empty() {
-            bail_hkd_verify!(NoCrl);
+            return report.check("crl", Err(Error::HkdVerify(NoCrl)));
         }
+        report.push(
+            "crl",
+            CheckOutcome::Passed,
+            Some(format!(
+                "{} of {} CRLs valid",
+                verified_crls.len(),
+                crls.len()
+            )),
+        );
         for crl in verified_crls {
             match crl.get_by_serial(hkd.serial_number()) {
                 CrlStatus::NotRevoked => (),
-                _ => bail_hkd_verify!(HkdRevoked),
+                _ => return report.check("revocation", Err(Error::HkdVerify(HkdRevoked))),
             }
         }
+        report.push("revocation", CheckOutcome::Passed, None);
         debug!("HKD: verified");
         Ok(())
     }
 }
 
+/// Stores downloaded `crls` in `cache`, if any. Caching is best effort.
+fn cache_crls(cache: Option<&CrlCache>, crls: &[X509Crl]) {
+    for crl in crls {
+        if let Err(e) = cache.map_or(Ok(()), |c| c.store_crl(crl)) {
+            warn!("Cannot cache CRL: {e}");
+        }
+    }
+}
+
 impl CertVerifier {
     fn quirk_crls(
         ctx: &mut X509StoreContextRef,
@@ -154,6 +231,7 @@ impl CertVerifier {
         if !self.offline {
             // Try to download a CRL if defined in the HKD
             if let Some(crl) = download_first_crl_from_x509(hkd)? {
+                cache_crls(self.cache.as_ref(), &crl);
                 crl.into_iter().try_for_each(|c| crls.push(c.into()))?;
             }
         }
@@ -168,7 +246,7 @@ impl CertVerifier {
     /// * `crl_paths` - Paths to certificate revocation lists for the chain of trust
     /// * `root_ca_path` - Path to the root of trust
     /// * `offline` - if set to true the verification process will not try to download CRLs from the
-    ///               internet.
+    ///   internet.
     ///
     /// # Errors
     ///
@@ -179,18 +257,45 @@ impl CertVerifier {
         root_ca_path: Option<R>,
         offline: bool,
     ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        R: AsRef<Path>,
+    {
+        Self::with_cache(cert_paths, crl_paths, root_ca_path, offline, None)
+    }
+
+    /// Create a `CertVerifier` that uses a [`CrlCache`].
+    ///
+    /// Like [`CertVerifier::new`], but additionally uses the valid CRLs of `cache` and stores the
+    /// certificates of the chain of trust and all downloaded CRLs in `cache`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the chain of trust could not be established.
+    pub fn with_cache<P, Q, R>(
+        cert_paths: &[P],
+        crl_paths: &[Q],
+        root_ca_path: Option<R>,
+        offline: bool,
+        cache: Option<CrlCache>,
+    ) -> Result<Self>
     where
         P: AsRef<Path>,
         Q: AsRef<Path>,
         R: AsRef<Path>,
     {
         let mut store = helper::store_setup(root_ca_path, crl_paths, cert_paths)?;
+        if let Some(cache) = &cache {
+            cache.crls()?.iter().try_for_each(|c| store.add_crl(c))?;
+        }
         let mut untr_certs = Vec::with_capacity(cert_paths.len());
         for path in cert_paths {
             let mut crt = read_certs(&read_file(path, "certificate")?)?;
             if !offline {
                 for c in &crt {
                     if let Some(crl) = download_first_crl_from_x509(c)? {
+                        cache_crls(cache.as_ref(), &crl);
                         crl.iter().try_for_each(|c| store.add_crl(c))?;
                     }
                 }
@@ -203,12 +308,23 @@ impl CertVerifier {
         let (ibm_z_sign_key, chain) = helper::extract_ibm_sign_key(untr_certs)?;
 
         let store = store.build();
-        helper::verify_chain(&store, &chain, slice::from_ref(&ibm_z_sign_key))?;
+        let verified_chain =
+            helper::verify_chain(&store, &chain, slice::from_ref(&ibm_z_sign_key))?;
+
+        if let Some(cache) = &cache {
+            for cert in chain.iter().chain(Some(ibm_z_sign_key.as_ref())) {
+                if let Err(e) = cache.store_cert(cert) {
+                    warn!("Cannot cache certificate: {e}");
+                }
+            }
+        }
 
         Ok(Self {
             store,
             ibm_z_sign_key,
+            chain: verified_chain,
             offline,
+            cache,
         })
     }
 }
@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fs::DirBuilder,
+    os::unix::fs::DirBuilderExt,
+    path::{Path, PathBuf},
+};
+
+use log::{debug, warn};
+use openssl::{
+    asn1::{Asn1Time, Asn1TimeRef},
+    sha::sha256,
+    x509::{X509Crl, X509CrlRef, X509Ref, X509},
+};
+
+use crate::{
+    input::{read_certs, read_crls},
+    misc::{encode_hex, read_file, write_file},
+    Result,
+};
+
+const CRL_EXT: &str = "crl";
+const CERT_EXT: &str = "crt";
+
+/// Persistent cache of CRLs and signing certificates
+///
+/// Stores downloaded CRLs and the certificates of the chain of trust in a directory, so that the
+/// host-key verification can use them later, e.g. without network access. CRLs are removed when
+/// they are outdated, certificates when they expire.
+///
+/// The content of a cache can be exported as bundle on a connected machine with
+/// [`CrlCache::export_bundle`] and imported on an air-gapped machine with
+/// [`CrlCache::import_bundle`]. A bundle is a sequence of PEM certificates and CRLs and can also
+/// be used directly as certificate and CRL input.
+#[derive(Debug, Clone)]
+pub struct CrlCache {
+    dir: PathBuf,
+}
+
+fn is_fresh(next_update: Option<&Asn1TimeRef>, now: &Asn1TimeRef) -> bool {
+    next_update.is_some_and(|t| t > now)
+}
+
+impl CrlCache {
+    /// Open the cache in `dir`.
+    ///
+    /// Creates the directory with mode `0700` if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the directory cannot be created.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(|e| pv_core::Error::FileAccess {
+                ty: crate::FileAccessErrorType::Create,
+                path: dir.to_path_buf(),
+                source: e,
+            })?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// The default cache directory: `$XDG_CACHE_HOME/s390-tools/pv` or
+    /// `$HOME/.cache/s390-tools/pv`
+    pub fn default_dir() -> Option<PathBuf> {
+        let base = match std::env::var_os("XDG_CACHE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+        };
+        Some(base.join("s390-tools").join("pv"))
+    }
+
+    /// The cache directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn store(&self, der: &[u8], pem: &[u8], ext: &str) -> Result<()> {
+        let name = format!("{}.{ext}", encode_hex(sha256(der)));
+        let path = self.dir.join(&name);
+        if path.exists() {
+            return Ok(());
+        }
+        // Write to a temporary file first so that concurrent readers never see partial entries.
+        let tmp = self.dir.join(format!(".{name}.{}", std::process::id()));
+        write_file(&tmp, pem, "cache entry")?;
+        std::fs::rename(&tmp, &path)?;
+        debug!("Cached '{}'", path.display());
+        Ok(())
+    }
+
+    /// Add `crl` to the cache.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the CRL cannot be written.
+    pub fn store_crl(&self, crl: &X509CrlRef) -> Result<()> {
+        self.store(&crl.to_der()?, &crl.to_pem()?, CRL_EXT)
+    }
+
+    /// Add `cert` to the cache.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the certificate cannot be written.
+    pub fn store_cert(&self, cert: &X509Ref) -> Result<()> {
+        self.store(&cert.to_der()?, &cert.to_pem()?, CERT_EXT)
+    }
+
+    /// Calls `f` for the content of every entry with extension `ext`.
+    ///
+    /// Removes the entry if `f` returns `false`.
+    fn entries<F: FnMut(&[u8]) -> bool>(&self, ext: &str, mut f: F) -> Result<()> {
+        let dir = std::fs::read_dir(&self.dir)?;
+        for entry in dir {
+            let path = entry?.path();
+            if !matches!(path.extension(), Some(e) if e == ext) {
+                continue;
+            }
+            let keep = match read_file(&path, "cache entry") {
+                Ok(buf) => f(&buf),
+                Err(e) => {
+                    warn!("Ignoring cache entry '{}': {e}", path.display());
+                    continue;
+                }
+            };
+            if !keep {
+                debug!("Removing outdated cache entry '{}'", path.display());
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns all cached CRLs that are not outdated.
+    ///
+    /// Removes outdated CRLs from the cache.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the cache directory cannot be read.
+    pub fn crls(&self) -> Result<Vec<X509Crl>> {
+        let now = Asn1Time::days_from_now(0)?;
+        let mut res = vec![];
+        self.entries(CRL_EXT, |buf| {
+            let crls = read_crls(buf).unwrap_or_default();
+            let fresh: Vec<_> = crls
+                .into_iter()
+                .filter(|c| is_fresh(c.next_update(), &now))
+                .collect();
+            let keep = !fresh.is_empty();
+            res.extend(fresh);
+            keep
+        })?;
+        Ok(res)
+    }
+
+    /// Returns all cached certificates that are not expired.
+    ///
+    /// Removes expired certificates from the cache.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the cache directory cannot be read.
+    pub fn certs(&self) -> Result<Vec<X509>> {
+        let now = Asn1Time::days_from_now(0)?;
+        let mut res = vec![];
+        self.entries(CERT_EXT, |buf| {
+            let certs = read_certs(buf).unwrap_or_default();
+            let fresh: Vec<_> = certs
+                .into_iter()
+                .filter(|c| is_fresh(Some(c.not_after()), &now))
+                .collect();
+            let keep = !fresh.is_empty();
+            res.extend(fresh);
+            keep
+        })?;
+        Ok(res)
+    }
+
+    /// Export all valid certificates and CRLs of the cache as PEM bundle.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the cache cannot be read.
+    pub fn export_bundle(&self) -> Result<Vec<u8>> {
+        let mut bundle = vec![];
+        for cert in self.certs()? {
+            bundle.extend(cert.to_pem()?);
+        }
+        for crl in self.crls()? {
+            bundle.extend(crl.to_pem()?);
+        }
+        Ok(bundle)
+    }
+
+    /// Import the certificates and CRLs of `bundle` into the cache.
+    ///
+    /// Accepts all formats of [`crate::misc::read_certs`] and [`crate::misc::read_crls`].
+    /// Returns the number of imported certificates and CRLs. Expired certificates and outdated
+    /// CRLs are skipped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the bundle cannot be parsed or the cache cannot be
+    /// written.
+    pub fn import_bundle(&self, bundle: &[u8]) -> Result<(usize, usize)> {
+        let now = Asn1Time::days_from_now(0)?;
+        let mut num_certs = 0;
+        for cert in read_certs(bundle)? {
+            if is_fresh(Some(cert.not_after()), &now) {
+                self.store_cert(&cert)?;
+                num_certs += 1;
+            }
+        }
+        let mut num_crls = 0;
+        for crl in read_crls(bundle)? {
+            if is_fresh(crl.next_update(), &now) {
+                self.store_crl(&crl)?;
+                num_crls += 1;
+            }
+        }
+        Ok((num_certs, num_crls))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn cache(name: &str) -> CrlCache {
+        let dir = std::env::temp_dir().join(format!("pv-cache-test-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        CrlCache::new(dir).unwrap()
+    }
+
+    #[test]
+    fn expiry() {
+        let cache = cache("expiry");
+        for crl in ["ibm.crl", "inter_ca.crl", "ibm_outdated_late.crl"] {
+            cache.store_crl(&load_gen_crl(crl)).unwrap();
+        }
+        cache.store_cert(&load_gen_cert("ibm.crt")).unwrap();
+        cache.store_cert(&load_gen_cert("ibm.crt")).unwrap();
+        cache.store_cert(&load_gen_cert("ibm_expired.crt")).unwrap();
+
+        assert_eq!(cache.crls().unwrap().len(), 2);
+        assert_eq!(cache.certs().unwrap().len(), 1);
+        assert_eq!(std::fs::read_dir(cache.dir()).unwrap().count(), 3);
+        std::fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn bundle() {
+        let src = cache("export");
+        src.store_crl(&load_gen_crl("ibm.crl")).unwrap();
+        src.store_cert(&load_gen_cert("ibm.crt")).unwrap();
+        src.store_cert(&load_gen_cert("inter_ca.crt")).unwrap();
+        let bundle = src.export_bundle().unwrap();
+
+        let dst = cache("import");
+        assert_eq!(dst.import_bundle(&bundle).unwrap(), (2, 1));
+        assert_eq!(
+            dst.crls().unwrap()[0].to_der().unwrap(),
+            load_gen_crl("ibm.crl").to_der().unwrap()
+        );
+        assert_eq!(dst.certs().unwrap().len(), 2);
+        assert_eq!(
+            dst.import_bundle(&get_cert_asset("ibm_outdated_late.crl"))
+                .unwrap(),
+            (0, 0)
+        );
+        std::fs::remove_dir_all(src.dir()).unwrap();
+        std::fs::remove_dir_all(dst.dir()).unwrap();
+    }
+}
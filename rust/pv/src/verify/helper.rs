@@ -3,7 +3,10 @@
 // Copyright IBM Corp. 2023
 
 use crate::error::bail_hkd_verify;
-use crate::openssl_extensions::{AkidCheckResult, AkidExtension};
+use crate::input::{read_crls, X509Format};
+use crate::openssl_extensions::{AkidCheckResult, AkidExtension, X509StoreExtension};
+use crate::request::HostKeyType;
+use crate::verify::HkdReport;
 use crate::HkdVerifyErrorType::*;
 use crate::{Error, Result};
 use log::debug;
@@ -33,28 +36,36 @@ const SECURITY_BITS: u32 = SECURITY_BITS_ARRAY[SECURITY_LEVEL];
 const SECURITY_CHAIN_MAX_LEN: c_int = 2;
 
 /// Verifies that the HKD
+/// * has a supported host-key type
 /// * has enough security bits
 /// * is inside its validity period
 /// * the Authority Key ID matches the Signing Key ID of the  [`sign_key`]
-pub fn verify_hkd_options(hkd: &X509Ref, sign_key: &X509Ref) -> Result<()> {
+///
+/// Records every check in `report`.
+pub fn verify_hkd_options(hkd: &X509Ref, sign_key: &X509Ref, report: &mut HkdReport) -> Result<()> {
     let hk_pkey = hkd.public_key()?;
+    report.check("host-key-type", HostKeyType::of(&hk_pkey))?;
     let security_bits = hk_pkey.security_bits();
 
-    if SECURITY_BITS > 0 && SECURITY_BITS > security_bits {
-        return Err(Error::HkdVerify(SecurityBits(security_bits, SECURITY_BITS)));
-    }
+    let bits = match SECURITY_BITS > 0 && SECURITY_BITS > security_bits {
+        true => Err(Error::HkdVerify(SecurityBits(security_bits, SECURITY_BITS))),
+        false => Ok(()),
+    };
+    report.check("security-bits", bits)?;
     // TODO rust-openssl fix X509::not.after/before() impl to return Option& not panic on nullptr
     // from C? try_... rust-openssl
     // verify that the HKD is still valid
-    check_validity_period(hkd.not_before(), hkd.not_after())?;
+    report.check(
+        "validity-period",
+        check_validity_period(hkd.not_before(), hkd.not_after()),
+    )?;
 
     // verify that the AKID of the hkd matches the SKID of the issuer
-    if let Some(akid) = hkd.akid() {
-        if akid.check(sign_key) != AkidCheckResult::OK {
-            bail_hkd_verify!(Akid);
-        }
-    }
-    Ok(())
+    let akid = match hkd.akid() {
+        Some(akid) if akid.check(sign_key) != AkidCheckResult::OK => Err(Error::HkdVerify(Akid)),
+        _ => Ok(()),
+    };
+    report.check("authority-key-id", akid)
 }
 
 pub fn verify_crl(crl: &X509CrlRef, issuer: &X509Ref) -> Option<()> {
@@ -120,11 +131,13 @@ pub fn store_setup<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
 
 /// Verify that the given IBM signing keys can be trusted
 /// -> check the chain: `IBMsignKey`<-InterCA(s)<-`RootCA`
+///
+/// Returns the verified chains, each starting with the signing key.
 pub fn verify_chain(
     store: &X509StoreRef,
     untrusted_certs: &Stack<X509>,
     sign_keys: &[X509],
-) -> Result<()> {
+) -> Result<Vec<X509>> {
     fn verify_fun(ctx: &mut X509StoreContextRef) -> std::result::Result<bool, ErrorStack> {
         // verify certificate
         let res = ctx.verify_cert()?;
@@ -151,12 +164,19 @@ pub fn verify_chain(
     }
 
     let mut store_ctx = X509StoreContext::new()?;
+    let mut verified = vec![];
 
     for sign_key in sign_keys {
         // (rust)OpenSSL should not error out on `X509_verify_cert`\
         // (Internal (probably unrecoverable) error like OOM)
         if !store_ctx
-            .init(store, sign_key, untrusted_certs, verify_fun)
+            .init(store, sign_key, untrusted_certs, |ctx| {
+                let res = verify_fun(ctx)?;
+                if res {
+                    verified.extend(ctx.chain().into_iter().flatten().map(ToOwned::to_owned));
+                }
+                Ok(res)
+            })
             .map_err(|e| Error::InternalSsl("The IBM Z signing key could not be verified.", e))?
         {
             return Err(Error::HkdVerify(IbmSignInvalid(
@@ -165,7 +185,7 @@ pub fn verify_chain(
             )));
         }
     }
-    Ok(())
+    Ok(verified)
 }
 
 /// Consumes and splits the given vector into a single IBM Z signing key and other certificates
@@ -261,6 +281,23 @@ fn load_crl_to_store<P: AsRef<Path>>(
     path: P,
     err_out_empty_crl: bool,
 ) -> std::result::Result<(), ErrorStack> {
+    // PKCS#7 bundles are not supported by the file lookup
+    if let Ok(buf) = std::fs::read(&path) {
+        if matches!(
+            X509Format::detect(&buf),
+            X509Format::Pkcs7Der | X509Format::Pkcs7Pem
+        ) {
+            let crls = read_crls(&buf).map_err(|e| match e {
+                Error::Crypto(e) => e,
+                _ => ErrorStack::get(),
+            })?;
+            if crls.is_empty() && err_out_empty_crl {
+                return Err(ErrorStack::get());
+            }
+            return crls.iter().try_for_each(|crl| x509_store.add_crl(crl));
+        }
+    }
+
     let lu = x509_store.add_lookup(X509Lookup::<File>::file())?;
     // Try to load cert as PEM file
     if lu.load_crl_file(&path, SslFiletype::PEM).is_err() {
@@ -306,7 +343,6 @@ pub fn x509_dist_points(cert: &X509Ref) -> Vec<String> {
 /// CRL not available at all URIs and unexpected format at all URIs are mapped to Ok(None)
 #[cfg(not(test))]
 pub fn download_first_crl_from_x509(cert: &X509Ref) -> Result<Option<Vec<openssl::x509::X509Crl>>> {
-    use crate::utils::read_crls;
     use curl::easy::{Easy2, Handler, WriteError};
     use std::time::Duration;
     const CRL_TIMEOUT_MAX: Duration = Duration::from_secs(3);
@@ -443,7 +479,7 @@ mod test {
         let ibm_wrong_subj = load_gen_cert("ibm_wrong_subject.crt");
         let no_sign_crt = load_gen_cert("inter_ca.crt");
 
-        assert!(super::get_ibm_z_sign_key(&[ibm_crt.clone()]).is_ok());
+        assert!(super::get_ibm_z_sign_key(std::slice::from_ref(&ibm_crt)).is_ok());
         assert!(matches!(
             super::get_ibm_z_sign_key(&[ibm_crt.clone(), ibm_crt.clone()]),
             Err(Error::HkdVerify(ManyIbmSignKeys))
@@ -453,7 +489,7 @@ mod test {
             Err(Error::HkdVerify(NoIbmSignKey))
         ));
         assert!(matches!(
-            super::get_ibm_z_sign_key(&[no_sign_crt.clone()]),
+            super::get_ibm_z_sign_key(std::slice::from_ref(&no_sign_crt)),
             Err(Error::HkdVerify(NoIbmSignKey))
         ));
         assert!(super::get_ibm_z_sign_key(&[ibm_crt, no_sign_crt]).is_ok(),);
@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use openssl::{
+    hash::MessageDigest,
+    x509::{X509CrlRef, X509NameRef, X509Ref},
+};
+use serde::Serialize;
+
+use crate::{misc::encode_hex, Result};
+
+/// Checks a [`super::HkdVerifier`] does for every host-key document, in this order
+pub const HKD_CHECKS: [&str; 7] = [
+    "host-key-type",
+    "security-bits",
+    "validity-period",
+    "authority-key-id",
+    "signature",
+    "crl",
+    "revocation",
+];
+
+/// Outcome of one verification check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckOutcome {
+    /// The check succeeded
+    Passed,
+    /// The check failed
+    Failed,
+    /// The check was not done, e.g. because the verification is disabled or an earlier check
+    /// failed
+    Skipped,
+}
+
+/// One check of the verification and its outcome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    /// Name of the check, see [`HKD_CHECKS`]
+    pub name: String,
+    /// Outcome of the check
+    pub outcome: CheckOutcome,
+    /// Why the check failed or was skipped, or what it found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Formats `name` like `C=US, O=Org, CN=Name`.
+fn name_to_string(name: &X509NameRef) -> String {
+    name.entries()
+        .map(|e| {
+            let key = e.object().nid().short_name().unwrap_or("?");
+            match e.data().as_utf8() {
+                Ok(value) => format!("{key}={value}"),
+                Err(_) => format!("{key}=?"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Identifying data of a certificate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CertInfo {
+    /// Subject name
+    pub subject: String,
+    /// Issuer name
+    pub issuer: String,
+    /// Serial number (hex)
+    pub serial: String,
+    /// Start of the validity period
+    pub not_before: String,
+    /// End of the validity period
+    pub not_after: String,
+    /// SHA-256 fingerprint (hex)
+    pub sha256: String,
+}
+
+impl CertInfo {
+    /// Collect the identifying data of `cert`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if OpenSSL cannot calculate the fingerprint.
+    pub fn new(cert: &X509Ref) -> Result<Self> {
+        Ok(Self {
+            subject: name_to_string(cert.subject_name()),
+            issuer: name_to_string(cert.issuer_name()),
+            serial: cert.serial_number().to_bn()?.to_hex_str()?.to_string(),
+            not_before: cert.not_before().to_string(),
+            not_after: cert.not_after().to_string(),
+            sha256: encode_hex(cert.digest(MessageDigest::sha256())?),
+        })
+    }
+}
+
+/// A CRL consulted for the revocation check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrlInfo {
+    /// Issuer name
+    pub issuer: String,
+    /// Time the CRL was issued (thisUpdate)
+    pub this_update: String,
+    /// Time the next CRL will be issued (nextUpdate)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_update: Option<String>,
+    /// The CRL is current, and signed by the IBM Z signing key; invalid CRLs are ignored
+    pub valid: bool,
+}
+
+impl CrlInfo {
+    /// Collect the data of `crl`.
+    pub fn new(crl: &X509CrlRef, valid: bool) -> Self {
+        Self {
+            issuer: name_to_string(crl.issuer_name()),
+            this_update: crl.last_update().to_string(),
+            next_update: crl.next_update().map(ToString::to_string),
+            valid,
+        }
+    }
+}
+
+/// Verification report of one host-key document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HkdReport {
+    /// Where the host-key document was read from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The host-key document
+    pub hkd: CertInfo,
+    /// The CRLs consulted for the revocation check
+    pub crls: Vec<CrlInfo>,
+    /// The checks in the order of [`HKD_CHECKS`]
+    pub checks: Vec<Check>,
+    /// All checks passed
+    pub verified: bool,
+}
+
+impl HkdReport {
+    /// Create an empty report for `hkd`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if OpenSSL cannot calculate the fingerprint.
+    pub fn new(hkd: &X509Ref) -> Result<Self> {
+        Ok(Self {
+            source: None,
+            hkd: CertInfo::new(hkd)?,
+            crls: vec![],
+            checks: vec![],
+            verified: false,
+        })
+    }
+
+    /// Record the outcome of the check `name` and pass `res` through.
+    pub fn check<T>(&mut self, name: &str, res: Result<T>) -> Result<T> {
+        let (outcome, detail) = match &res {
+            Ok(_) => (CheckOutcome::Passed, None),
+            Err(e) => (CheckOutcome::Failed, Some(e.to_string())),
+        };
+        self.push(name, outcome, detail);
+        res
+    }
+
+    /// Record the outcome of the check `name`.
+    pub fn push(&mut self, name: &str, outcome: CheckOutcome, detail: Option<String>) {
+        self.checks.push(Check {
+            name: name.to_string(),
+            outcome,
+            detail,
+        });
+    }
+
+    /// Record all [`HKD_CHECKS`] that are not recorded yet as skipped, and set
+    /// [`HkdReport::verified`].
+    pub fn finish(&mut self, reason: &str) {
+        for name in HKD_CHECKS {
+            if !self.checks.iter().any(|c| c.name == name) {
+                self.push(name, CheckOutcome::Skipped, Some(reason.to_string()));
+            }
+        }
+        self.verified = self
+            .checks
+            .iter()
+            .all(|c| c.outcome == CheckOutcome::Passed);
+    }
+}
+
+/// Check of an input downloaded from an URL against its pinned SHA-256 digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PinInfo {
+    /// The downloaded URL
+    pub url: String,
+    /// What the input is, e.g. "host-key document"
+    pub input: String,
+    /// The pinned digest (hex), if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Passed if the content matches the pinned digest, skipped if not pinned
+    pub outcome: CheckOutcome,
+}
+
+/// Machine-readable report of the verification of host-key documents
+///
+/// Records the chain of trust, the CRLs consulted with their validity, the pinning of downloaded
+/// inputs, and the outcome of every check, e.g. to keep as compliance evidence. Serialize it, for
+/// example, to JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VerificationReport {
+    /// The host-key documents are verified; false if the verification is disabled or failed
+    pub verified: bool,
+    /// The certificates of the chain of trust, starting with the IBM Z signing key
+    pub chain: Vec<CertInfo>,
+    /// The pinning checks of downloaded inputs
+    pub pins: Vec<PinInfo>,
+    /// One report per host-key document
+    pub hkds: Vec<HkdReport>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::load_gen_cert;
+
+    #[test]
+    fn hkd_report() {
+        let hkd = load_gen_cert("host.crt");
+        let mut report = HkdReport::new(&hkd).unwrap();
+        assert!(report.hkd.subject.contains("CN="));
+        assert_eq!(report.hkd.sha256.len(), 64);
+
+        report.check("host-key-type", Ok(())).unwrap();
+        assert!(report
+            .check::<()>("security-bits", Err(crate::Error::NoArcb))
+            .is_err());
+        report.finish("earlier check failed");
+        assert!(!report.verified);
+        let outcomes: Vec<_> = report.checks.iter().map(|c| c.outcome).collect();
+        assert_eq!(outcomes.len(), HKD_CHECKS.len());
+        assert_eq!(outcomes[0], CheckOutcome::Passed);
+        assert_eq!(outcomes[1], CheckOutcome::Failed);
+        assert!(outcomes[2..].iter().all(|o| *o == CheckOutcome::Skipped));
+    }
+}
@@ -5,7 +5,7 @@
 #![cfg(test)]
 
 use super::{helper, helper::*, *};
-use crate::{utils::read_crls, Error, HkdVerifyErrorType::*};
+use crate::input::read_crls;
 use openssl::{stack::Stack, x509::X509Crl};
 use std::path::Path;
 
@@ -149,3 +149,95 @@ fn verify_armonk_hkd_online() {
 fn verify_armonk_hkd_offline() {
     verify(true, "ibm_armonk.crt", "ibm_armonk.crl", "host_armonk.crt")
 }
+
+#[test]
+fn verify_cached() {
+    let root_crt = get_cert_asset_path("root_ca.chained.crt");
+    let certs = [
+        get_cert_asset_path("ibm.crt"),
+        get_cert_asset_path("inter_ca.crt"),
+    ];
+    let no_crls: [String; 0] = [];
+    let hkd = load_gen_cert("host.crt");
+    let dir = std::env::temp_dir().join(format!("pv-verify-cache-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let cache = CrlCache::new(&dir).unwrap();
+
+    // without a cache there are no CRLs offline
+    let verifier = CertVerifier::new(&certs, &no_crls, Some(&root_crt), true);
+    assert!(verifier.is_err() || verifier.unwrap().verify(&hkd).is_err());
+
+    // online, the downloaded CRLs are cached
+    let verifier = CertVerifier::with_cache(
+        &certs,
+        &no_crls,
+        Some(&root_crt),
+        false,
+        Some(cache.clone()),
+    )
+    .unwrap();
+    verifier.verify(&hkd).unwrap();
+    assert_eq!(cache.certs().unwrap().len(), 2);
+    assert!(!cache.crls().unwrap().is_empty());
+
+    let verifier =
+        CertVerifier::with_cache(&certs, &no_crls, Some(&root_crt), true, Some(cache)).unwrap();
+    let res = verifier.verify(&hkd);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(res.is_ok(), "Verify failed: res: {res:?}");
+}
+
+#[test]
+fn verify_report() {
+    let root_crt = get_cert_asset_path("root_ca.chained.crt");
+    let inter_crt = get_cert_asset_path("inter_ca.crt");
+    let inter_crl = get_cert_asset_path("inter_ca.crl");
+    let ibm_crt = get_cert_asset_path("ibm.crt");
+    let ibm_crl = get_cert_asset_path("ibm.crl");
+    let verifier = CertVerifier::new(
+        &[&ibm_crt, &inter_crt],
+        &[&ibm_crl, &inter_crl],
+        Some(&root_crt),
+        true,
+    )
+    .unwrap();
+    assert_eq!(verifier.chain().len(), 3);
+
+    let hkd = load_gen_cert("host.crt");
+    let mut report = HkdReport::new(&hkd).unwrap();
+    verifier.verify_with_report(&hkd, &mut report).unwrap();
+    assert!(report.verified);
+    assert_eq!(
+        report
+            .checks
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>(),
+        HKD_CHECKS
+    );
+    assert!(!report.crls.is_empty());
+    assert!(report
+        .crls
+        .iter()
+        .any(|c| c.valid && c.next_update.is_some()));
+
+    let hkd = load_gen_cert("host_rev.crt");
+    let mut report = HkdReport::new(&hkd).unwrap();
+    assert!(verifier.verify_with_report(&hkd, &mut report).is_err());
+    assert!(!report.verified);
+    assert_eq!(report.checks.last().unwrap().outcome, CheckOutcome::Failed);
+
+    let hkd = load_gen_cert("host_crt_expired.crt");
+    let mut report = HkdReport::new(&hkd).unwrap();
+    assert!(verifier.verify_with_report(&hkd, &mut report).is_err());
+    assert_eq!(report.checks[2].outcome, CheckOutcome::Failed);
+    assert_eq!(report.checks[3].outcome, CheckOutcome::Skipped);
+
+    let mut report = HkdReport::new(&hkd).unwrap();
+    NoVerifyHkd.verify_with_report(&hkd, &mut report).unwrap();
+    assert!(!report.verified);
+    assert!(report
+        .checks
+        .iter()
+        .all(|c| c.outcome == CheckOutcome::Skipped));
+}
@@ -15,11 +15,11 @@ use s390_pv::{
         BootHdrTags, ReqEncrCtx, Request, SymKey,
     },
     secret::{
-        verify_asrcb_and_get_user_data, AddSecretFlags, AddSecretRequest, AddSecretVersion,
-        ExtSecret, GuestSecret,
+        verify_asrcb_and_get_user_data, AddSecretFlag, AddSecretFlags, AddSecretRequest,
+        AddSecretVersion, ExtSecret, GuestSecret, UserDataType,
     },
     test_utils::get_test_keys,
-    uv::ConfigUid,
+    uv::{ConfigUid, ListableSecretType},
     Result,
 };
 
@@ -259,3 +259,79 @@ fn verify_no_user_data() {
         Ok(None)
     ))
 }
+
+#[test]
+fn info_assoc() {
+    let req = get_test_asset!("exp/asrcb/assoc_none_default_cuid_one");
+    let info = AddSecretRequest::bin_info(req).unwrap();
+    assert_eq!(info.version(), 0x100);
+    assert_eq!(info.size(), req.len());
+    assert_eq!(info.secret_type(), ListableSecretType::ASSOCIATION);
+    assert_eq!(
+        info.secret_id(),
+        Some(&GuestSecret::name_to_id(ASSOC_ID).unwrap())
+    );
+    assert_eq!(info.secret_size(), 32);
+    assert!(!info.flags().disable_dump());
+    assert_eq!(info.se_header_tag(), &[4; 16]);
+    assert_eq!(info.cuid(), Some(&CUID));
+    assert_eq!(info.user_data(), UserDataType::Null);
+    assert_eq!(info.host_key_hashes().len(), 1);
+    let human = info.to_string();
+    assert!(human.contains("Secret type: Association\n"));
+    assert!(human.contains("Config UID: 0x42424242424242424242424242424242\n"));
+}
+
+#[test]
+fn info_null() {
+    let req = get_test_asset!("exp/asrcb/null_none_dump_cuid_one");
+    let info = AddSecretRequest::bin_info(req).unwrap();
+    assert_eq!(info.secret_type(), ListableSecretType::NULL);
+    assert_eq!(info.secret_id(), None);
+    assert_eq!(info.secret_size(), 0);
+    assert_eq!(info.flags().flags(), [AddSecretFlag::DisableDump]);
+    assert!(info.to_string().contains("Flags: disable-dump\n"));
+
+    let req = get_test_asset!("exp/asrcb/null_none_default_ncuid_one");
+    assert_eq!(AddSecretRequest::bin_info(req).unwrap().cuid(), None);
+
+    let req = get_test_asset!("exp/asrcb/null_none_default_cuid_seven");
+    let info = AddSecretRequest::bin_info(req).unwrap();
+    assert_eq!(info.host_key_hashes().len(), 7);
+    assert!(info
+        .host_key_hashes()
+        .iter()
+        .all(|h| h == &info.host_key_hashes()[0]));
+}
+
+#[test]
+fn info_signed() {
+    let usr_sgn_key = get_test_asset!("keys/rsa2048key.pem");
+    let usr_sgn_key = PKey::private_key_from_pem(usr_sgn_key).unwrap();
+    let asrcb = create_signed_asrcb(usr_sgn_key, vec![0x56; 0x100]);
+    let info = AddSecretRequest::bin_info(&asrcb).unwrap();
+    assert_eq!(info.user_data(), UserDataType::SgnRsa2048);
+    assert_eq!(info.secret_type(), ListableSecretType::NULL);
+    assert_eq!(info.host_key_hashes().len(), 1);
+}
+
+#[test]
+fn info_invalid() {
+    let req = get_test_asset!("exp/asrcb/assoc_none_default_cuid_one");
+    assert!(AddSecretRequest::bin_info(&req[..0x100]).is_err());
+    assert!(AddSecretRequest::bin_info(&[0; 0x400]).is_err());
+}
+
+#[test]
+fn flags() {
+    let mut flags = no_flag();
+    assert!(flags.flags().is_empty());
+    flags.set(AddSecretFlag::DisableDump);
+    assert_eq!(
+        flags,
+        AddSecretFlags::from(&[AddSecretFlag::DisableDump][..])
+    );
+    assert_eq!(flags, AddSecretFlags::from_raw(1 << 63));
+    assert!(flags.disable_dump());
+    assert!(AddSecretFlags::from_raw(1).flags().is_empty());
+}
@@ -1,2 +1,2 @@
 #!/bin/bash
-rm -f -- *.key *.crt *.crl
+rm -f -- *.key *.crt *.crl *.p7b
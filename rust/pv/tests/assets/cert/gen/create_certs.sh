@@ -14,4 +14,7 @@ source "${path}"/gen_venv/bin/activate
 pip3 install -r "${path}"/requirements.txt
 cd "${path}" || exit 2
 python3 ./create_certs.py
+# PKCS#7 bundles with certificates and a CRL
+openssl crl2pkcs7 -in ibm.crl -certfile ibm.crt -certfile inter_ca.crt -out ibm.p7b
+openssl crl2pkcs7 -in ibm.crl -certfile ibm.crt -certfile inter_ca.crt -outform DER -out ibm_der.p7b
 deactivate
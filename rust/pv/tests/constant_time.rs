@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+//! Guards the verification paths against comparisons that return at the first difference.
+
+mod ct_lint;
+
+use ct_lint::{early_exit_cmp, early_exit_cmps};
+
+/// Source directories of the attestation and add-secret verification
+const VERIFICATION_SOURCES: &[&str] = &["src/uvattest", "src/uvsecret"];
+
+#[test]
+fn detects_early_exit() {
+    assert!(early_exit_cmp("    if received != expected {"));
+    assert!(early_exit_cmp(
+        "if secret_store_hash(&opt.secret, locked)?.as_ref() != att_store_hash.as_ref() {"
+    ));
+    assert!(early_exit_cmp("match hash.as_ref() == phkh.as_ref() {"));
+    assert!(!early_exit_cmp("if !eq_ct(received, expected) {"));
+    assert!(!early_exit_cmp(
+        "let nonce = if decr.value().len() == size_of::<ReqConfData>() {"
+    ));
+    assert!(!early_exit_cmp("// compare hash == other"));
+}
+
+#[test]
+fn no_early_exit_comparisons() {
+    let found = early_exit_cmps(env!("CARGO_MANIFEST_DIR"), VERIFICATION_SOURCES);
+    assert!(
+        found.is_empty(),
+        "Use eq_ct for these comparisons:\n{}",
+        found.join("\n")
+    );
+}
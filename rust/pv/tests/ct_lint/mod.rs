@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Lint for verification paths against comparisons that return at the first difference.
+//!
+//! Measurements, HMACs, tags, hashes, and IDs must be compared with `s390_pv::misc::eq_ct`. The
+//! constant-time tests of `s390_pv`, pvattest, and pvsecret include this module with `#[path]`, so
+//! that they share one heuristic without a dependency.
+
+use std::path::Path;
+
+/// Names that indicate compared data is verified
+const SENSITIVE: &[&str] = &[
+    "meas",
+    "hmac",
+    "hash",
+    "phkh",
+    "tag",
+    "cuid",
+    "digest",
+    "user_data",
+    "nonce",
+    "expected",
+    "received",
+    "as_ref()",
+];
+
+/// Comparisons of public sizes and constants
+const PUBLIC: &[&str] = &[".len()", "size_of", "static_assert!", "[0; "];
+
+/// Returns true if `line` compares verified data with `==` or `!=`.
+pub fn early_exit_cmp(line: &str) -> bool {
+    let line = line.trim();
+    if line.starts_with("//") || !(line.contains("==") || line.contains("!=")) {
+        return false;
+    }
+    let lower = line.to_lowercase();
+    SENSITIVE.iter().any(|s| lower.contains(s)) && !PUBLIC.iter().any(|p| line.contains(p))
+}
+
+/// Find comparisons of verified data with `==` or `!=` in `sources`.
+///
+/// `sources` are Rust files or directories with Rust files relative to `root`, usually
+/// `env!("CARGO_MANIFEST_DIR")`. Code after `#[cfg(test)]` is skipped. Returns the offending lines
+/// as `file:line: code`.
+///
+/// # Panics
+///
+/// Panics if a source cannot be read.
+pub fn early_exit_cmps<P: AsRef<Path>>(root: P, sources: &[&str]) -> Vec<String> {
+    let mut files = vec![];
+    for src in sources {
+        let path = root.as_ref().join(src);
+        match path.is_dir() {
+            true => files.extend(
+                std::fs::read_dir(&path)
+                    .unwrap()
+                    .map(|e| e.unwrap().path())
+                    .filter(|p| p.extension().is_some_and(|e| e == "rs")),
+            ),
+            false => files.push(path),
+        }
+    }
+
+    let mut found = vec![];
+    for file in files {
+        let content = std::fs::read_to_string(&file).unwrap();
+        // Tests may compare as they like.
+        let code = content.split("#[cfg(test)]").next().unwrap();
+        for (nr, line) in code.lines().enumerate() {
+            if early_exit_cmp(line) {
+                found.push(format!("{}:{}: {}", file.display(), nr + 1, line.trim()));
+            }
+        }
+    }
+    found
+}
@@ -10,10 +10,14 @@ use crate::{
     Error, Result,
 };
 use regex::Regex;
+use serde::Serialize;
 use std::fmt;
 use std::thread;
 use std::time;
 
+mod status;
+pub use status::{ap_queue_status, ApHealthSummary, ApqnHealth, ApqnMkvp, ApqnStatus};
+
 const PATH_SYS_DEVICES_AP: &str = "/sys/devices/ap";
 
 /// Regular expression for AP queue directories
@@ -30,7 +34,8 @@ const SYS_BUS_AP_ASSOC_POLL_MS: u64 = 500;
 const SYS_BUS_AP_ASSOC_TIMEOUT_MS: u64 = 10000;
 
 /// APQN mode
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ApqnMode {
     /// Accelerator mode
     Accel,
@@ -194,7 +199,6 @@ impl TryFrom<&str> for Apqn {
     /// parse, e.g. when the capture `([[:xdigit:]]{2})` does not
     /// parse as hex string.
     fn try_from(name: &str) -> Result<Self> {
-        let re_card_type = Regex::new(RE_CARD_TYPE).unwrap();
         let re_queue_dir = Regex::new(RE_QUEUE_DIR).unwrap();
 
         let caps = re_queue_dir
@@ -206,18 +210,8 @@ impl TryFrom<&str> for Apqn {
         let domain = u32::from_str_radix(domstr, 16).unwrap();
 
         let path = format!("{PATH_SYS_DEVICES_AP}/card{cardstr}");
-        let card_type =
-            read_file_string(format!("{path}/type"), "card type").map(|s| s.trim().to_string())?;
-        let caps = re_card_type
-            .captures(&card_type)
-            .ok_or_else(|| parse_error!("card type".to_string(), card_type.to_string()))?;
-        let gen = caps.get(1).unwrap().as_str().parse::<u32>().unwrap();
-        let mode = match caps.get(2).unwrap().as_str().parse::<char>().unwrap() {
-            'A' => ApqnMode::Accel,
-            'C' => ApqnMode::Cca,
-            'P' => ApqnMode::Ep11,
-            _ => unreachable!("Code inconsistency between regex RE_CARD_TYPE and evaluation code."),
-        };
+        let card_type = read_file_string(format!("{path}/type"), "card type")?;
+        let (gen, mode) = parse_card_type(&card_type)?;
         // the UV blocks requests to CCA cards within SE guest with AP
         // pass-through support. However, filter out CCA cards as
         // these cards cause hangs during information gathering.
@@ -259,6 +253,26 @@ impl TryFrom<&str> for Apqn {
     }
 }
 
+/// Parse the card type, e.g. `CEX8P`, into the card generation and mode.
+///
+/// # Panics
+/// Panics if the compilation of a static regular expression fails.
+fn parse_card_type(card_type: &str) -> Result<(u32, ApqnMode)> {
+    let card_type = card_type.trim();
+    let caps = Regex::new(RE_CARD_TYPE)
+        .unwrap()
+        .captures(card_type)
+        .ok_or_else(|| parse_error!("card type".to_string(), card_type.to_string()))?;
+    let gen = caps.get(1).unwrap().as_str().parse::<u32>().unwrap();
+    let mode = match caps.get(2).unwrap().as_str().parse::<char>().unwrap() {
+        'A' => ApqnMode::Accel,
+        'C' => ApqnMode::Cca,
+        'P' => ApqnMode::Ep11,
+        _ => unreachable!("Code inconsistency between regex RE_CARD_TYPE and evaluation code."),
+    };
+    Ok((gen, mode))
+}
+
 impl fmt::Display for Apqn {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "({},{})", self.card, self.domain)
@@ -288,7 +302,8 @@ impl Apqn {
 }
 
 /// Bind state of an APQN
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BindState {
     /// APQN is bound
     Bound,
@@ -305,7 +320,11 @@ pub enum BindState {
 pub fn get_apqn_bind_state(card: u32, dom: u32) -> Result<BindState> {
     let path = format!("{PATH_SYS_DEVICES_AP}/card{card:02x}/{card:02x}.{dom:04x}/se_bind");
     let state_str = read_file_string(path, "se_bind attribute")?;
-    let state = state_str.trim();
+    parse_bind_state(&state_str)
+}
+
+fn parse_bind_state(state: &str) -> Result<BindState> {
+    let state = state.trim();
     match state {
         "bound" => Ok(BindState::Bound),
         "unbound" => Ok(BindState::Unbound),
@@ -351,7 +370,8 @@ pub fn set_apqn_bind_state(card: u32, dom: u32, state: BindState) -> Result<()>
 }
 
 /// Association state of an APQN
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AssocState {
     /// Associated with index
     Associated(u16),
@@ -370,7 +390,11 @@ pub enum AssocState {
 pub fn get_apqn_associate_state(card: u32, dom: u32) -> Result<AssocState> {
     let path = format!("{PATH_SYS_DEVICES_AP}/card{card:02x}/{card:02x}.{dom:04x}/se_associate");
     let state_str = read_file_string(path, "se_associate attribute")?;
-    let state = state_str.trim();
+    parse_assoc_state(&state_str)
+}
+
+fn parse_assoc_state(state: &str) -> Result<AssocState> {
+    let state = state.trim();
     match state.strip_prefix("associated ") {
         Some(prefix) => Ok(AssocState::Associated(prefix.parse()?)),
         _ => match state {
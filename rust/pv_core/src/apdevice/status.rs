@@ -0,0 +1,416 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+//
+//! AP queue status and health
+//
+
+use super::{
+    parse_assoc_state, parse_bind_state, parse_card_type, ApqnMode, AssocState, BindState,
+    PATH_SYS_DEVICES_AP, RE_QUEUE_DIR,
+};
+use crate::{Error, FileIoErrorType, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+const RE_CARD_DIR: &str = r"^card([[:xdigit:]]{2})$";
+const RE_MKVP_LINE: &str = r"^(\S+)\s+(CUR|NEW|OLD):\s+(\S+)\s+(\S+)$";
+
+/// Master key verification pattern (MKVP) of one master key register of an APQN
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApqnMkvp {
+    /// Master key type, e.g. `WK` for EP11, `AES` or `APKA` for CCA
+    pub key_type: String,
+    /// Master key register: `CUR`, `NEW`, or `OLD`
+    pub register: String,
+    /// State of the register, e.g. `valid` or `empty`
+    pub state: String,
+    /// Verification pattern as lowercase hex string; empty if the register is not set
+    pub mkvp: String,
+}
+
+impl ApqnMkvp {
+    /// Parse the content of the `mkvps` sysfs attribute.
+    ///
+    /// # Panics
+    /// Panics if the compilation of a static regular expression fails.
+    fn parse(mkvps: &str) -> Vec<Self> {
+        let re = Regex::new(RE_MKVP_LINE).unwrap();
+        mkvps
+            .lines()
+            .filter_map(|line| re.captures(line.trim()))
+            .map(|caps| {
+                let state = caps[3].to_lowercase();
+                let mkvp = match state.as_str() {
+                    "valid" => caps[4].to_lowercase().trim_start_matches("0x").to_string(),
+                    _ => String::new(),
+                };
+                Self {
+                    key_type: caps[1].to_string(),
+                    register: caps[2].to_string(),
+                    state,
+                    mkvp,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns true if this is a current master key with a valid pattern.
+    fn is_valid_current(&self) -> bool {
+        self.register == "CUR" && self.state == "valid"
+    }
+}
+
+/// Health of an APQN
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApqnHealth {
+    /// The APQN is usable
+    Healthy,
+    /// The APQN is configured but offline
+    Offline,
+    /// The APQN is not configured (e.g. deconfigured by the hypervisor)
+    Deconfigured,
+    /// The APQN is in checkstop state
+    Checkstopped,
+    /// The coprocessor APQN has no valid current master key
+    NoMasterKey,
+}
+
+impl fmt::Display for ApqnHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Healthy => "healthy",
+            Self::Offline => "offline",
+            Self::Deconfigured => "deconfigured",
+            Self::Checkstopped => "checkstopped",
+            Self::NoMasterKey => "no valid master key",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Status of an APQN as reported by the AP bus
+///
+/// Attributes that are not available, e.g. because the kernel is too old or the APQN is not
+/// bound to this guest, are `None` or empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApqnStatus {
+    /// Card number
+    pub card: u32,
+    /// Domain number
+    pub domain: u32,
+    /// CryptoExpress generation
+    pub gen: Option<u32>,
+    /// Mode that adapter is configured to use
+    pub mode: Option<ApqnMode>,
+    /// APQN is online
+    pub online: bool,
+    /// APQN is configured
+    pub config: bool,
+    /// APQN is in checkstop state
+    pub chkstop: bool,
+    /// Number of requests sent to the APQN that are not yet answered
+    pub pending_requests: Option<u64>,
+    /// Number of requests waiting to be sent to the APQN
+    pub queued_requests: Option<u64>,
+    /// Secure Execution bind state
+    pub bind: Option<BindState>,
+    /// Secure Execution association state
+    pub associate: Option<AssocState>,
+    /// Master key verification patterns; empty for accelerators
+    pub mkvps: Vec<ApqnMkvp>,
+}
+
+fn read_error(ctx: &str, path: &Path, source: std::io::Error) -> Error {
+    Error::FileIo {
+        ty: FileIoErrorType::Read,
+        ctx: ctx.to_string(),
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+fn read_attr(path: &Path, attr: &str) -> Option<String> {
+    std::fs::read_to_string(path.join(attr))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+fn read_flag(path: &Path, attr: &str) -> Option<bool> {
+    read_attr(path, attr).map(|s| s == "1")
+}
+
+impl ApqnStatus {
+    /// Read the status of the APQN (`card`, `domain`).
+    ///
+    /// Reading the status does not change the bind state of the APQN. Therefore, the MKVPs of
+    /// APQNs that are not bound to a Secure Execution guest are not available.
+    ///
+    /// # Errors
+    /// This function will return an error if the APQN does not exist.
+    pub fn read(card: u32, domain: u32) -> Result<Self> {
+        Self::read_from(Path::new(PATH_SYS_DEVICES_AP), card, domain)
+    }
+
+    fn read_from(base: &Path, card: u32, domain: u32) -> Result<Self> {
+        let card_path = base.join(format!("card{card:02x}"));
+        let path = card_path.join(format!("{card:02x}.{domain:04x}"));
+        // ensure the queue exists
+        std::fs::metadata(&path).map_err(|e| read_error("AP queue", &path, e))?;
+
+        let card_type = read_attr(&card_path, "type").and_then(|t| parse_card_type(&t).ok());
+        // older kernels provide config and chkstop on the card level only
+        let flag = |attr| read_flag(&path, attr).or_else(|| read_flag(&card_path, attr));
+        let count = |attr| read_attr(&path, attr).and_then(|s| s.parse().ok());
+        Ok(Self {
+            card,
+            domain,
+            gen: card_type.as_ref().map(|(gen, _)| *gen),
+            mode: card_type.map(|(_, mode)| mode),
+            online: read_flag(&path, "online").unwrap_or(false),
+            config: flag("config").unwrap_or(true),
+            chkstop: flag("chkstop").unwrap_or(false),
+            pending_requests: count("pendingq_count"),
+            queued_requests: count("requestq_count"),
+            bind: read_attr(&path, "se_bind").and_then(|s| parse_bind_state(&s).ok()),
+            associate: read_attr(&path, "se_associate").and_then(|s| parse_assoc_state(&s).ok()),
+            mkvps: read_attr(&path, "mkvps")
+                .map(|s| ApqnMkvp::parse(&s))
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Name of the APQN as used by the AP bus, e.g. `01.0005`.
+    pub fn name(&self) -> String {
+        format!("{:02x}.{:04x}", self.card, self.domain)
+    }
+
+    /// Evaluate the health of this APQN.
+    ///
+    /// A coprocessor without a valid current master key is only reported if its MKVPs are
+    /// available.
+    pub fn health(&self) -> ApqnHealth {
+        if !self.config {
+            ApqnHealth::Deconfigured
+        } else if self.chkstop {
+            ApqnHealth::Checkstopped
+        } else if !self.online {
+            ApqnHealth::Offline
+        } else if matches!(self.mode, Some(ApqnMode::Ep11 | ApqnMode::Cca))
+            && !self.mkvps.is_empty()
+            && !self.mkvps.iter().any(ApqnMkvp::is_valid_current)
+        {
+            ApqnHealth::NoMasterKey
+        } else {
+            ApqnHealth::Healthy
+        }
+    }
+}
+
+impl fmt::Display for ApqnStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({},{})", self.card, self.domain)
+    }
+}
+
+/// Read the status of all APQNs available to this system.
+///
+/// # Errors
+/// This function will return an error if the AP devices cannot be listed, e.g. because there is no
+/// AP bus support.
+pub fn ap_queue_status() -> Result<Vec<ApqnStatus>> {
+    read_all_from(Path::new(PATH_SYS_DEVICES_AP))
+}
+
+/// # Panics
+/// Panics if the compilation of a static regular expression fails.
+fn read_all_from(base: &Path) -> Result<Vec<ApqnStatus>> {
+    let re_card_dir = Regex::new(RE_CARD_DIR).unwrap();
+    let re_queue_dir = Regex::new(RE_QUEUE_DIR).unwrap();
+    let list = |path: &Path| -> Result<Vec<String>> {
+        let mut names: Vec<_> = std::fs::read_dir(path)
+            .map_err(|e| read_error("AP devices", path, e))?
+            .flatten()
+            .filter_map(|e| e.file_name().into_string().ok())
+            .collect();
+        names.sort();
+        Ok(names)
+    };
+
+    let mut status = Vec::new();
+    for card_dir in list(base)?.iter().filter(|d| re_card_dir.is_match(d)) {
+        for queue_dir in list(&base.join(card_dir))? {
+            let Some(caps) = re_queue_dir.captures(&queue_dir) else {
+                continue;
+            };
+            // Panic: the regex ensures that the captures are hex numbers
+            let card = u32::from_str_radix(&caps[1], 16).unwrap();
+            let domain = u32::from_str_radix(&caps[2], 16).unwrap();
+            status.push(ApqnStatus::read_from(base, card, domain)?);
+        }
+    }
+    Ok(status)
+}
+
+/// Summarized health of a set of APQNs
+///
+/// ```rust,no_run
+/// # use s390_pv_core::ap::{ap_queue_status, ApHealthSummary};
+/// # fn main() -> s390_pv_core::Result<()> {
+/// let health = ApHealthSummary::new(&ap_queue_status()?);
+/// if !health.is_healthy() {
+///     eprintln!("{health}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ApHealthSummary {
+    /// Number of APQNs
+    pub total: usize,
+    /// Number of healthy APQNs
+    pub healthy: usize,
+    /// Health of all APQNs that are not healthy, by APQN name
+    pub unhealthy: BTreeMap<String, ApqnHealth>,
+}
+
+impl ApHealthSummary {
+    /// Summarize the health of the given APQNs.
+    pub fn new(status: &[ApqnStatus]) -> Self {
+        let unhealthy: BTreeMap<_, _> = status
+            .iter()
+            .map(|s| (s.name(), s.health()))
+            .filter(|(_, h)| *h != ApqnHealth::Healthy)
+            .collect();
+        Self {
+            total: status.len(),
+            healthy: status.len() - unhealthy.len(),
+            unhealthy,
+        }
+    }
+
+    /// Returns true if all APQNs are healthy.
+    pub fn is_healthy(&self) -> bool {
+        self.unhealthy.is_empty()
+    }
+}
+
+impl fmt::Display for ApHealthSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} APQNs healthy", self.healthy, self.total)?;
+        for (name, health) in &self.unhealthy {
+            write!(f, "\n  {name}: {health}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    const EP11_MKVPS: &str = "WK CUR: valid 0xdb3c3b3c3f097dd55ec7eb0e7fdbcb93\nWK NEW: empty -\n";
+
+    fn mk_queue(base: &Path, card: &str, queue: &str, attrs: &[(&str, &str)]) {
+        let path = base.join(card).join(queue);
+        fs::create_dir_all(&path).unwrap();
+        for (attr, val) in attrs {
+            fs::write(path.join(attr), format!("{val}\n")).unwrap();
+        }
+    }
+
+    fn sysfs() -> PathBuf {
+        let base = std::env::temp_dir().join(format!("ap-status-{}", std::process::id()));
+        fs::create_dir_all(base.join("card01")).unwrap();
+        fs::write(base.join("card01/type"), "CEX8P\n").unwrap();
+        fs::write(base.join("card01/chkstop"), "0\n").unwrap();
+        mk_queue(
+            &base,
+            "card01",
+            "01.0005",
+            &[
+                ("online", "1"),
+                ("config", "1"),
+                ("pendingq_count", "2"),
+                ("requestq_count", "0"),
+                ("se_bind", "bound"),
+                ("se_associate", "associated 3"),
+                ("mkvps", EP11_MKVPS),
+            ],
+        );
+        mk_queue(
+            &base,
+            "card01",
+            "01.0006",
+            &[
+                ("online", "1"),
+                ("mkvps", "WK CUR: empty -\nWK NEW: empty -"),
+            ],
+        );
+        mk_queue(&base, "card01", "01.0007", &[("online", "0")]);
+        mk_queue(
+            &base,
+            "card02",
+            "02.0005",
+            &[("online", "1"), ("chkstop", "1")],
+        );
+        fs::create_dir_all(base.join("aptest")).unwrap();
+        base
+    }
+
+    #[test]
+    fn mkvps() {
+        let mkvps = ApqnMkvp::parse(EP11_MKVPS);
+        assert_eq!(mkvps.len(), 2);
+        assert_eq!(mkvps[0].key_type, "WK");
+        assert_eq!(mkvps[0].mkvp, "db3c3b3c3f097dd55ec7eb0e7fdbcb93");
+        assert!(mkvps[0].is_valid_current());
+        assert_eq!(mkvps[1].register, "NEW");
+        assert!(mkvps[1].mkvp.is_empty());
+        assert!(ApqnMkvp::parse("garbage").is_empty());
+    }
+
+    #[test]
+    fn status_and_health() {
+        let base = sysfs();
+        let status = read_all_from(&base).unwrap();
+        assert_eq!(status.len(), 4);
+
+        let q = &status[0];
+        assert_eq!(q.name(), "01.0005");
+        assert_eq!(q.gen, Some(8));
+        assert_eq!(q.mode, Some(ApqnMode::Ep11));
+        assert_eq!(q.pending_requests, Some(2));
+        assert_eq!(q.queued_requests, Some(0));
+        assert_eq!(q.bind, Some(BindState::Bound));
+        assert_eq!(q.associate, Some(AssocState::Associated(3)));
+        assert_eq!(q.health(), ApqnHealth::Healthy);
+
+        assert_eq!(status[1].health(), ApqnHealth::NoMasterKey);
+        assert_eq!(status[2].health(), ApqnHealth::Offline);
+        assert_eq!(status[3].mode, None);
+        assert_eq!(status[3].bind, None);
+        assert_eq!(status[3].health(), ApqnHealth::Checkstopped);
+
+        let summary = ApHealthSummary::new(&status);
+        assert!(!summary.is_healthy());
+        assert_eq!(
+            summary.to_string(),
+            "1 of 4 APQNs healthy
+  01.0006: no valid master key
+  01.0007: offline
+  02.0005: checkstopped"
+        );
+        assert!(ApHealthSummary::new(&status[..1]).is_healthy());
+
+        assert!(ApqnStatus::read_from(&base, 3, 1).is_err());
+        fs::remove_dir_all(&base).unwrap();
+        assert!(read_all_from(&base).is_err());
+    }
+}
@@ -2,13 +2,16 @@
 //
 // Copyright IBM Corp. 2023, 2024
 
-use std::fmt::Debug;
+use std::{fmt::Debug, io::Read};
+
+use zeroize::{ZeroizeOnDrop, Zeroizing};
 
 use crate::Error;
 
 /// Trait for securely zeroizing  memory.
 ///
-/// To be used with [`Confidential`]
+/// To be used with [`Confidential`]. Implemented on top of the [`zeroize`] crate, types wrapped
+/// in a [`Confidential`] implement [`zeroize::Zeroize`] and [`ZeroizeOnDrop`].
 pub trait Zeroize {
     /// Reliably overwrites the given buffer with zeros,
     fn zeroize(&mut self);
@@ -17,13 +20,9 @@ pub trait Zeroize {
 macro_rules! integer_impl {
     ($typ:ty) => {
         impl Zeroize for $typ {
-            /// Reliably overwrites the given number with zeros,
-            /// by performing a volatile write followed by a memory barrier
+            /// Reliably overwrites the given number with zeros, see [`zeroize::Zeroize`]
             fn zeroize(&mut self) {
-                unsafe {
-                    std::ptr::write_volatile(self as *mut $typ, <$typ>::default());
-                }
-                std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+                zeroize::Zeroize::zeroize(self);
             }
         }
     };
@@ -166,12 +165,20 @@ impl<C: Zeroize> Zeroize for Confidential<C> {
     }
 }
 
+impl<C: Zeroize> zeroize::Zeroize for Confidential<C> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl<C: Zeroize> Drop for Confidential<C> {
     fn drop(&mut self) {
         self.0.zeroize();
     }
 }
 
+impl<C: Zeroize> ZeroizeOnDrop for Confidential<C> {}
+
 impl<const N: usize> TryFrom<Confidential<Vec<u8>>> for Confidential<[u8; N]> {
     type Error = Error;
 
@@ -196,6 +203,121 @@ impl<const N: usize> From<Confidential<[u8; N]>> for Confidential<Vec<u8>> {
     }
 }
 
+/// Sensitive bytes, e.g. key material passed in by users of this crate.
+///
+/// The bytes are held in a [`Zeroizing`] buffer and overwritten during drop. Additionally,
+/// [`Debug`] never prints the bytes, not even in debug builds, and [`SecretBytes::read_from`]
+/// does not leave copies of the data in memory while the buffer grows.
+///
+/// ```rust
+/// # use s390_pv_core::request::SecretBytes;
+/// # fn main() -> std::io::Result<()> {
+/// let key = SecretBytes::read_from(&mut [1u8, 2, 3].as_slice())?;
+/// assert_eq!(key.as_bytes(), [1, 2, 3]);
+/// assert_eq!(format!("{key:?}"), "SecretBytes(***)");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct SecretBytes(Zeroizing<Vec<u8>>);
+
+impl SecretBytes {
+    const CHUNK_SIZE: usize = 4096;
+
+    /// Takes ownership of `data`.
+    pub fn new(data: Vec<u8>) -> Self {
+        Self(Zeroizing::new(data))
+    }
+
+    /// Reads all bytes from `rd` until EOF.
+    ///
+    /// Intermediate buffers are overwritten before they are freed.
+    ///
+    /// # Errors
+    ///
+    /// Passes through any error of `rd` except [`std::io::ErrorKind::Interrupted`].
+    pub fn read_from<R: Read>(rd: &mut R) -> std::io::Result<Self> {
+        let mut buf = Zeroizing::new(Vec::new());
+        let mut chunk = Zeroizing::new([0u8; Self::CHUNK_SIZE]);
+        loop {
+            let n = match rd.read(chunk.as_mut_slice()) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if buf.capacity() - buf.len() < n {
+                // Grow manually; a reallocation of the Vec would leave the old buffer behind.
+                let mut grown = Vec::with_capacity((2 * buf.capacity()).max(buf.len() + n));
+                grown.extend_from_slice(&buf);
+                // The old buffer is overwritten on drop
+                buf = Zeroizing::new(grown);
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        Ok(Self(buf))
+    }
+
+    /// Returns the bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the number of bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Debug for SecretBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SecretBytes(***)")
+    }
+}
+
+impl AsRef<[u8]> for SecretBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(value: Vec<u8>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&[u8]> for SecretBytes {
+    fn from(value: &[u8]) -> Self {
+        Self::new(value.to_vec())
+    }
+}
+
+impl From<Confidential<Vec<u8>>> for SecretBytes {
+    fn from(mut value: Confidential<Vec<u8>>) -> Self {
+        Self::new(std::mem::take(value.value_mut()))
+    }
+}
+
+impl From<SecretBytes> for Confidential<Vec<u8>> {
+    fn from(mut value: SecretBytes) -> Self {
+        Self::new(std::mem::take(&mut *value.0))
+    }
+}
+
+impl Zeroize for SecretBytes {
+    fn zeroize(&mut self) {
+        zeroize::Zeroize::zeroize(&mut *self.0);
+    }
+}
+
+impl ZeroizeOnDrop for SecretBytes {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -277,4 +399,35 @@ mod test {
         let vec: Confidential<Vec<u8>> = Confidential::new(data).into();
         assert_eq!(vec.value(), data.as_slice());
     }
+
+    #[test]
+    fn secret_bytes() {
+        let data: Vec<u8> = (0..10000u32).map(|i| i as u8).collect();
+        let secret = SecretBytes::read_from(&mut data.as_slice()).unwrap();
+        assert_eq!(secret.as_bytes(), data.as_slice());
+        assert_eq!(secret.len(), 10000);
+        assert_eq!(format!("{secret:?}"), "SecretBytes(***)");
+
+        let conf: Confidential<Vec<u8>> = secret.into();
+        assert_eq!(conf.value(), &data);
+        assert!(SecretBytes::read_from(&mut [].as_slice())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn zeroize_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<SecretBytes>();
+        assert_zeroize_on_drop::<Confidential<Vec<u8>>>();
+        assert_zeroize_on_drop::<Confidential<[u8; 32]>>();
+
+        let mut conf = Confidential::new([17u8; 4]);
+        zeroize::Zeroize::zeroize(&mut conf);
+        assert_eq!(&[0; 4], conf.value());
+
+        let mut secret = SecretBytes::new(vec![17; 4]);
+        Zeroize::zeroize(&mut secret);
+        assert!(secret.is_empty());
+    }
 }
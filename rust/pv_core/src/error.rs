@@ -24,6 +24,17 @@ pub enum Error {
     #[error("{0}")]
     Specification(String),
 
+    #[error("The uvdevice or the Ultravisor does not support the '{name}' command (IOCTL {nr}). The kernel might be too old")]
+    Unsupported { nr: u8, name: &'static str },
+
+    #[error(
+        "The uvdevice is no longer available. The uvdevice kernel module might have been reloaded"
+    )]
+    DeviceGone,
+
+    #[error("Cannot lock the memory of a sensitive buffer")]
+    MemoryLock(#[source] std::io::Error),
+
     #[error("Cannot {ty} {ctx} at `{path}`")]
     FileIo {
         ty: FileIoErrorType,
@@ -66,7 +77,7 @@ pub enum Error {
     #[error("The input has not the correct format: {field} is too small. Minimal size {min_size}")]
     AttDataSizeSmall { field: &'static str, min_size: u32 },
 
-    #[error("The attestation request has an unknown algorithm type (.0)")]
+    #[error("The attestation request has an unknown algorithm type ({0})")]
     BinArcbInvAlgorithm(u32),
 
     #[error("The attestation request does not specify a measurement size or measurement data.")]
@@ -107,6 +118,51 @@ pub enum Error {
     #[error("Failure parsing {subject} '{content}'.")]
     ParseError { subject: String, content: String },
 
+    #[error("Invalid key description '{desc}': {reason}")]
+    InvKeyDescription { desc: String, reason: &'static str },
+
+    #[error("The key payload is too large ({size} bytes). Maximal size {max}")]
+    InvKeyPayloadSize { size: usize, max: usize },
+
+    #[error("Cannot drop privileges: {msg} failed")]
+    PrivDrop {
+        msg: &'static str,
+        source: std::io::Error,
+    },
+
+    #[error("Cannot sandbox the process: {msg} failed")]
+    Sandbox {
+        msg: &'static str,
+        source: std::io::Error,
+    },
+
+    #[error("This system is not an IBM Secure Execution guest")]
+    NotSeGuest,
+
+    #[error("This system cannot run IBM Secure Execution guests")]
+    NotSeHost,
+
+    #[error("The Store Hypervisor Information (STHYI) facility is not available")]
+    SthyiUnavailable(#[source] std::io::Error),
+
+    #[error("Invalid STHYI response: {0}")]
+    InvSthyi(&'static str),
+
+    #[error("Unknown {kind} '{name}'")]
+    UnknownId { kind: &'static str, name: String },
+
+    #[error("Unknown capability '{0}'")]
+    UnknownCapability(String),
+
+    #[error("The pkey device failed on {op}")]
+    Pkey {
+        op: &'static str,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid secure key: {0}")]
+    InvSecureKey(&'static str),
+
     // errors from other crates
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -119,6 +175,9 @@ pub enum Error {
     #[error("Cannot decode hex string")]
     InvHexStringChar { source: std::num::ParseIntError },
 
+    #[error("Cannot decode base64 string")]
+    InvBase64String,
+
     #[error("Expected size {expected}, actual {actual}")]
     LengthMismatch { expected: usize, actual: usize },
 }
@@ -144,3 +203,80 @@ pub enum FileAccessErrorType {
     #[error("create")]
     Create,
 }
+
+impl Error {
+    /// Returns a stable, machine-readable code for this error.
+    ///
+    /// The code does not change between releases. Callers may branch on it instead of parsing the
+    /// error message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Uv {
+                msg: "secret store locked" | "secret store already locked",
+                ..
+            } => "store-locked",
+            Self::Uv { .. } => "uv-error",
+            Self::Specification(_) => "specification",
+            Self::Unsupported { .. } => "facility-missing",
+            Self::DeviceGone => "device-gone",
+            Self::MemoryLock(_) => "memory-lock",
+            Self::FileIo { .. } | Self::FileAccess { .. } | Self::FileAccessRename { .. } => {
+                "file-access"
+            }
+            Self::ManySecrets => "too-many-secrets",
+            Self::InvSecretList(_) => "invalid-secret-list",
+            Self::NoAsrcb | Self::AscrbLarge | Self::UnsupportedUserData(_) => {
+                "invalid-add-secret-request"
+            }
+            Self::AttDataSizeLarge { .. }
+            | Self::AttDataSizeSmall { .. }
+            | Self::BinArcbInvAlgorithm(_)
+            | Self::BinArcbNoMeasurement => "invalid-attestation-request",
+            Self::InvalidRetrievableSecretType { .. } => "invalid-retrievable-secret",
+            Self::UnknownBindState(_) | Self::UnknownAssocState(_) => "ap-unknown-state",
+            Self::WrongAssocState { .. } => "ap-wrong-association",
+            Self::Timeout(_) => "timeout",
+            Self::CcaSeIncompatible(_) => "ap-incompatible",
+            Self::ApOffline { .. } => "ap-offline",
+            Self::ParseError { .. }
+            | Self::ParseInt(_)
+            | Self::InvHexStringSize(_)
+            | Self::InvHexStringChar { .. }
+            | Self::InvBase64String
+            | Self::LengthMismatch { .. } => "invalid-input",
+            Self::InvKeyDescription { .. } | Self::InvKeyPayloadSize { .. } => "invalid-key",
+            Self::PrivDrop { .. } => "privilege-drop",
+            Self::Sandbox { .. } => "sandbox",
+            Self::NotSeGuest => "not-se-guest",
+            Self::NotSeHost => "not-se-host",
+            Self::SthyiUnavailable(_) => "facility-missing",
+            Self::InvSthyi(_) => "invalid-input",
+            Self::UnknownId { .. } | Self::UnknownCapability(_) => "unknown-id",
+            Self::Pkey { .. } => "pkey",
+            Self::InvSecureKey(_) => "invalid-key",
+            Self::Io(_) => "io",
+        }
+    }
+
+    /// Returns a hint on how to remediate this error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self.code() {
+            "store-locked" => Some("The secret store is locked until the guest is rebooted"),
+            "facility-missing" => Some(
+                "Check that the uvdevice kernel module is loaded and that the kernel and the \
+                 Ultravisor support the command",
+            ),
+            "device-gone" => Some("Retry the command"),
+            "not-se-guest" => Some("Run the command in an IBM Secure Execution guest"),
+            "not-se-host" => Some(
+                "Run the command on a machine with IBM Secure Execution support and start the \
+                 kernel with 'prot_virt=1'",
+            ),
+            "memory-lock" => Some("Increase the locked-memory limit (RLIMIT_MEMLOCK)"),
+            "too-many-secrets" => Some("Remove secrets from the request"),
+            "ap-offline" => Some("Set the APQN online"),
+            "privilege-drop" | "sandbox" => Some("Run the command without dropping privileges"),
+            _ => None,
+        }
+    }
+}
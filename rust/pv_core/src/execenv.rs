@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use crate::{Error, Result};
+use serde::Serialize;
+use std::fmt::Display;
+
+const SYSFS_PROT_VIRT_GUEST: &str = "/sys/firmware/uv/prot_virt_guest";
+const SYSFS_PROT_VIRT_HOST: &str = "/sys/firmware/uv/prot_virt_host";
+const SYSINFO: &str = "/proc/sysinfo";
+const CPUINFO: &str = "/proc/cpuinfo";
+
+/// Ultravisor-call facility
+const FAC_UV_CALL: u32 = 158;
+
+fn sysfs_bit_set(path: &str) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|v| v.trim() == "1")
+}
+
+/// Returns `true` if this system is an IBM Secure Execution guest.
+///
+/// Uses the `prot_virt_guest` sysfs entry. Always `false` on other architectures than s390x.
+pub fn is_se_guest() -> bool {
+    cfg!(target_arch = "s390x") && sysfs_bit_set(SYSFS_PROT_VIRT_GUEST)
+}
+
+/// Returns `true` if this system can run IBM Secure Execution guests.
+///
+/// Uses the `prot_virt_host` sysfs entry, which is only set if the machine supports Secure
+/// Execution and the kernel was started with `prot_virt=1`. Always `false` on other architectures
+/// than s390x.
+pub fn se_host_capable() -> bool {
+    cfg!(target_arch = "s390x") && sysfs_bit_set(SYSFS_PROT_VIRT_HOST)
+}
+
+/// Returns `true` if the machine provides the facility with the number `nr`.
+///
+/// Uses the facility list in `/proc/cpuinfo`. Always `false` on other architectures than s390x.
+pub fn has_facility(nr: u32) -> bool {
+    cfg!(target_arch = "s390x")
+        && std::fs::read_to_string(CPUINFO).is_ok_and(|info| facilities(&info).contains(&nr))
+}
+
+/// Returns `true` if the machine provides the Ultravisor-call facility.
+pub fn has_uv_call_facility() -> bool {
+    has_facility(FAC_UV_CALL)
+}
+
+/// Parses the facility list of the content of `/proc/cpuinfo`.
+fn facilities(cpuinfo: &str) -> Vec<u32> {
+    cpuinfo
+        .lines()
+        .find_map(|l| {
+            let (key, value) = l.split_once(':')?;
+            (key.trim() == "facilities").then_some(value)
+        })
+        .map(|v| {
+            v.split_whitespace()
+                .filter_map(|f| f.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The environment this program runs in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    /// Directly in a logical partition
+    Lpar,
+    /// A z/VM guest
+    ZVm,
+    /// A KVM guest
+    Kvm,
+    /// A guest of any other hypervisor
+    Other,
+}
+
+impl Environment {
+    /// Detects the environment this program runs in.
+    ///
+    /// Uses `/proc/sysinfo`. Returns [`None`] if the environment cannot be detected, e.g. on
+    /// other architectures than s390x.
+    pub fn detect() -> Option<Self> {
+        std::fs::read_to_string(SYSINFO)
+            .ok()
+            .and_then(|s| Self::from_sysinfo(&s))
+    }
+
+    /// Parses the environment from the content of `/proc/sysinfo`.
+    ///
+    /// The hypervisor closest to this program is described by the `VM00` entries. Without them,
+    /// the program runs directly in a logical partition.
+    pub fn from_sysinfo(sysinfo: &str) -> Option<Self> {
+        let field = |name: &str| {
+            sysinfo.lines().find_map(|l| {
+                let (key, value) = l.split_once(':')?;
+                (key.trim() == name).then_some(value.trim())
+            })
+        };
+        match field("VM00 Control Program") {
+            Some(cp) if cp.starts_with("KVM") => Some(Self::Kvm),
+            Some(cp) if cp.starts_with("z/VM") => Some(Self::ZVm),
+            Some(_) => Some(Self::Other),
+            None => field("LPAR Number").map(|_| Self::Lpar),
+        }
+    }
+}
+
+impl Display for Environment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lpar => write!(f, "LPAR"),
+            Self::ZVm => write!(f, "z/VM"),
+            Self::Kvm => write!(f, "KVM"),
+            Self::Other => write!(f, "other hypervisor"),
+        }
+    }
+}
+
+/// Returns `true` if this program runs in the environment `env`.
+pub fn running_under(env: Environment) -> bool {
+    Environment::detect() == Some(env)
+}
+
+/// Checks that this system is an IBM Secure Execution guest.
+///
+/// # Errors
+///
+/// This function will return an error if this system is no Secure Execution guest.
+pub fn require_se_guest() -> Result<()> {
+    match is_se_guest() {
+        true => Ok(()),
+        false => Err(Error::NotSeGuest),
+    }
+}
+
+/// Checks that this system can run IBM Secure Execution guests.
+///
+/// # Errors
+///
+/// This function will return an error if this system cannot run Secure Execution guests.
+pub fn require_se_host() -> Result<()> {
+    match se_host_capable() {
+        true => Ok(()),
+        false => Err(Error::NotSeHost),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SYSINFO_LPAR: &str = "Manufacturer:         IBM
+Type:                 3931
+LPAR Number:          23
+LPAR Name:            LPAR1
+";
+
+    #[test]
+    fn sysinfo_lpar() {
+        assert_eq!(
+            Environment::from_sysinfo(SYSINFO_LPAR),
+            Some(Environment::Lpar)
+        );
+    }
+
+    #[test]
+    fn sysinfo_guests() {
+        let kvm =
+            format!("{SYSINFO_LPAR}VM00 Name:            guest\nVM00 Control Program: KVM/Linux\n");
+        assert_eq!(Environment::from_sysinfo(&kvm), Some(Environment::Kvm));
+        let zvm = format!(
+            "{SYSINFO_LPAR}VM00 Control Program: z/VM    7.3.0\nVM01 Control Program: KVM/Linux\n"
+        );
+        assert_eq!(Environment::from_sysinfo(&zvm), Some(Environment::ZVm));
+        assert_eq!(Environment::from_sysinfo(""), None);
+    }
+
+    #[test]
+    fn cpuinfo_facilities() {
+        let cpuinfo = "vendor_id       : IBM/S390\nfacilities      : 0 1 2 3 158 161\n";
+        assert_eq!(facilities(cpuinfo), [0, 1, 2, 3, 158, 161]);
+        assert!(facilities("vendor_id       : GenuineIntel\n").is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fmt::Debug,
+    ptr::NonNull,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+use crate::{confidential::Zeroize, Error, Result, PAGESIZE};
+
+/// Guarded memory for key material.
+///
+/// The buffer is backed by its own anonymous memory mapping, which is locked into memory
+/// (`mlock`) and excluded from core dumps (`MADV_DONTDUMP`). Hence, the content neither hits the
+/// swap space nor a core dump of the process. The buffer is overwritten with zeros and unmapped
+/// on drop. [`Debug`] never prints the content.
+///
+/// Locking memory is limited by `RLIMIT_MEMLOCK`, therefore use this type only for small buffers
+/// holding secrets, e.g. keys. Each buffer occupies at least one page.
+///
+/// ```rust
+/// # use s390_pv_core::request::GuardedBuf;
+/// # fn main() -> s390_pv_core::Result<()> {
+/// let mut key = GuardedBuf::new(32)?;
+/// key.as_mut_bytes().copy_from_slice(&[0x42; 32]);
+/// assert_eq!(key.as_bytes(), [0x42; 32]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct GuardedBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+    map_len: usize,
+}
+
+// SAFETY: The buffer exclusively owns its mapping; access follows the usual borrow rules.
+unsafe impl Send for GuardedBuf {}
+// SAFETY: see above; shared references only allow reading.
+unsafe impl Sync for GuardedBuf {}
+
+impl GuardedBuf {
+    /// Allocates a zeroed, locked buffer of `len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the memory cannot be mapped or locked, e.g.
+    /// because the `RLIMIT_MEMLOCK` limit is exhausted.
+    pub fn new(len: usize) -> Result<Self> {
+        let map_len = len.div_ceil(PAGESIZE).max(1) * PAGESIZE;
+        // SAFETY: anonymous private mapping; no existing memory is affected
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                map_len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(Error::MemoryLock(std::io::Error::last_os_error()));
+        }
+        let buf = Self {
+            // mmap never returns NULL on success
+            ptr: NonNull::new(ptr as *mut u8).expect("mmap returned NULL"),
+            len,
+            map_len,
+        };
+        // SAFETY: the range is the mapping created above
+        let rc = unsafe {
+            match libc::mlock(ptr, map_len) {
+                0 => libc::madvise(ptr, map_len, libc::MADV_DONTDUMP),
+                rc => rc,
+            }
+        };
+        match rc {
+            // the mapping is released by drop
+            0 => Ok(buf),
+            _ => Err(Error::MemoryLock(std::io::Error::last_os_error())),
+        }
+    }
+
+    /// Allocates a locked buffer and copies `data` into it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the memory cannot be mapped or locked.
+    pub fn from_slice(data: &[u8]) -> Result<Self> {
+        let mut buf = Self::new(data.len())?;
+        buf.as_mut_bytes().copy_from_slice(data);
+        Ok(buf)
+    }
+
+    /// Returns the content of the buffer.
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: ptr points to at least len initialized bytes owned by self
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Returns the content of the buffer mutably.
+    pub fn as_mut_bytes(&mut self) -> &mut [u8] {
+        // SAFETY: ptr points to at least len initialized bytes exclusively owned by self
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Returns the size of the buffer in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the buffer has a size of zero.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Zeroize for GuardedBuf {
+    /// Reliably overwrites the whole mapping with zeros.
+    fn zeroize(&mut self) {
+        let mut dst = self.ptr.as_ptr();
+        for _ in 0..self.map_len {
+            // SAFETY: dst stays within the mapping
+            unsafe {
+                std::ptr::write_volatile(dst, 0);
+                dst = dst.add(1);
+            }
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+impl Drop for GuardedBuf {
+    fn drop(&mut self) {
+        self.zeroize();
+        // SAFETY: the range is the mapping owned by self, which is not used afterwards.
+        // Unmapping also releases the lock.
+        unsafe { libc::munmap(self.ptr.as_ptr() as *mut libc::c_void, self.map_len) };
+    }
+}
+
+impl Debug for GuardedBuf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GuardedBuf(***)")
+    }
+}
+
+impl AsRef<[u8]> for GuardedBuf {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Clone for GuardedBuf {
+    /// # Panics
+    ///
+    /// Panics if the memory for the clone cannot be locked.
+    fn clone(&self) -> Self {
+        Self::from_slice(self.as_bytes()).expect("Cannot lock memory for the cloned buffer")
+    }
+}
+
+impl PartialEq for GuardedBuf {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for GuardedBuf {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn alloc() {
+        let mut buf = GuardedBuf::new(5000).unwrap();
+        assert_eq!(buf.len(), 5000);
+        assert_eq!(buf.map_len, 2 * PAGESIZE);
+        assert!(buf.as_bytes().iter().all(|b| *b == 0));
+        buf.as_mut_bytes()[4999] = 17;
+        assert_eq!(buf.as_bytes()[4999], 17);
+
+        buf.zeroize();
+        assert_eq!(buf.as_bytes()[4999], 0);
+
+        let empty = GuardedBuf::new(0).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.map_len, PAGESIZE);
+    }
+
+    #[test]
+    fn copy() {
+        let buf = GuardedBuf::from_slice(b"key").unwrap();
+        assert_eq!(buf.as_bytes(), b"key");
+        assert_eq!(buf.clone(), buf);
+        assert_eq!(format!("{buf:?}"), "GuardedBuf(***)");
+    }
+}
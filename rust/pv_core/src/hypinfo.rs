@@ -0,0 +1,595 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use crate::{assert_size, Error, Result};
+use serde::Serialize;
+use std::mem::size_of;
+use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, KnownLayout, U16, U32};
+
+// Header flags
+const HDR_NOT_LPAR: u8 = 0x10;
+const HDR_STACK_INCM: u8 = 0x20;
+const HDR_STSI_UNAV: u8 = 0x40;
+const HDR_PERF_UNAV: u8 = 0x80;
+
+// Machine section validity
+const MAC_NAME_VLD: u8 = 0x20;
+const MAC_ID_VLD: u8 = 0x40;
+const MAC_CNT_VLD: u8 = 0x80;
+
+// Partition section flags and validity
+const PAR_MT_EN: u8 = 0x80;
+const PAR_GRP_VLD: u8 = 0x08;
+const PAR_ID_VLD: u8 = 0x10;
+const PAR_ABS_VLD: u8 = 0x20;
+const PAR_WGHT_VLD: u8 = 0x40;
+const PAR_PCNT_VLD: u8 = 0x80;
+
+/// Number of hypervisor/guest section pairs the header can describe
+const MAX_LEVELS: usize = 3;
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, KnownLayout, Immutable)]
+struct SectionRef {
+    off: U16<BigEndian>,
+    len: U16<BigEndian>,
+}
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, KnownLayout, Immutable)]
+struct HdrSctn {
+    infhflg1: u8,
+    infhflg2: u8,
+    infhval1: u8,
+    infhval2: u8,
+    res_4: [u8; 3],
+    infhygct: u8,
+    infhtotl: U16<BigEndian>,
+    infhdln: U16<BigEndian>,
+    mac: SectionRef,
+    par: SectionRef,
+    levels: [[SectionRef; 2]; MAX_LEVELS],
+    res_2c: [u8; 4],
+}
+assert_size!(HdrSctn, 0x30);
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, KnownLayout, Immutable)]
+struct CpuCnts {
+    scps: U16<BigEndian>,
+    dcps: U16<BigEndian>,
+    sifl: U16<BigEndian>,
+    difl: U16<BigEndian>,
+}
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, KnownLayout, Immutable)]
+struct MacSctn {
+    infmflg1: u8,
+    infmflg2: u8,
+    infmval1: u8,
+    infmval2: u8,
+    cnts: CpuCnts,
+    infmname: [u8; 8],
+    infmtype: [u8; 4],
+    infmmanu: [u8; 16],
+    infmseq: [u8; 16],
+    infmpman: [u8; 4],
+    res_3c: [u8; 4],
+}
+assert_size!(MacSctn, 0x40);
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, KnownLayout, Immutable)]
+struct ParSctn {
+    infpflg1: u8,
+    infpflg2: u8,
+    infpval1: u8,
+    infpval2: u8,
+    infppnum: U16<BigEndian>,
+    cnts: CpuCnts,
+    res_e: [u8; 2],
+    infppnam: [u8; 8],
+    infpwbcp: U32<BigEndian>,
+    infpabcp: U32<BigEndian>,
+    infpwbif: U32<BigEndian>,
+    infpabif: U32<BigEndian>,
+    infplgnm: [u8; 8],
+    infplgcp: U32<BigEndian>,
+    infplgif: U32<BigEndian>,
+}
+assert_size!(ParSctn, 0x38);
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, KnownLayout, Immutable)]
+struct HypSctn {
+    infyflg1: u8,
+    infyflg2: u8,
+    infyval1: u8,
+    infyval2: u8,
+    infytype: u8,
+    res_5: [u8; 3],
+    infyname: [u8; 8],
+    cnts: CpuCnts,
+}
+assert_size!(HypSctn, 0x18);
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, KnownLayout, Immutable)]
+struct GstSctn {
+    infgflg1: u8,
+    infgflg2: u8,
+    infgval1: u8,
+    infgval2: u8,
+    infgusid: [u8; 8],
+    infgscps: U16<BigEndian>,
+    infgdcps: U16<BigEndian>,
+    infgcpdt: u8,
+    res_11: [u8; 3],
+    infgcpcc: U32<BigEndian>,
+    infgsifl: U16<BigEndian>,
+    infgdifl: U16<BigEndian>,
+    infgifdt: u8,
+    res_1d: [u8; 3],
+    infgifcc: U32<BigEndian>,
+    infgpnam: [u8; 8],
+}
+assert_size!(GstSctn, 0x2c);
+
+/// Converts an EBCDIC (code page 037) string as stored by STSI and STHYI.
+///
+/// Trailing blanks are removed. Returns [`None`] for blank strings.
+fn ebcdic_str(raw: &[u8]) -> Option<String> {
+    let s: String = raw
+        .iter()
+        .map(|&b| match b {
+            0x00 | 0x40 => ' ',
+            0x4b => '.',
+            0x5b => '$',
+            0x60 => '-',
+            0x61 => '/',
+            0x6d => '_',
+            0x7b => '#',
+            0x7c => '@',
+            0x81..=0x89 => (b - 0x81 + b'a') as char,
+            0x91..=0x99 => (b - 0x91 + b'j') as char,
+            0xa2..=0xa9 => (b - 0xa2 + b's') as char,
+            0xc1..=0xc9 => (b - 0xc1 + b'A') as char,
+            0xd1..=0xd9 => (b - 0xd1 + b'J') as char,
+            0xe2..=0xe9 => (b - 0xe2 + b'S') as char,
+            0xf0..=0xf9 => (b - 0xf0 + b'0') as char,
+            _ => '?',
+        })
+        .collect();
+    let s = s.trim_end();
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// Reads the section at `off` with `len` bytes.
+///
+/// Older machines or hypervisors store shorter sections; the missing trailing fields are zero.
+/// Returns [`None`] if the section is not present.
+fn section<T: FromBytes + IntoBytes>(buf: &[u8], sref: &SectionRef) -> Result<Option<T>> {
+    let (off, len) = (sref.off.get() as usize, sref.len.get() as usize);
+    if off == 0 || len == 0 {
+        return Ok(None);
+    }
+    let data = buf
+        .get(off..off + len)
+        .ok_or(Error::InvSthyi("section exceeds the response"))?;
+    let mut sctn = T::new_zeroed();
+    let len = len.min(size_of::<T>());
+    sctn.as_mut_bytes()[..len].copy_from_slice(&data[..len]);
+    Ok(Some(sctn))
+}
+
+/// Number of shared and dedicated CPs and IFLs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CpuCounts {
+    /// Number of shared CPs
+    pub shared_cp: u16,
+    /// Number of dedicated CPs
+    pub dedicated_cp: u16,
+    /// Number of shared IFLs
+    pub shared_ifl: u16,
+    /// Number of dedicated IFLs
+    pub dedicated_ifl: u16,
+}
+
+impl From<&CpuCnts> for CpuCounts {
+    fn from(value: &CpuCnts) -> Self {
+        Self {
+            shared_cp: value.scps.get(),
+            dedicated_cp: value.dcps.get(),
+            shared_ifl: value.sifl.get(),
+            dedicated_ifl: value.difl.get(),
+        }
+    }
+}
+
+/// Information about the machine (CPC)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MachineInfo {
+    /// CPUs of the machine
+    pub cpus: Option<CpuCounts>,
+    /// Name of the machine
+    pub name: Option<String>,
+    /// Machine type, e.g. 3931
+    pub machine_type: Option<String>,
+    /// Manufacturer of the machine
+    pub manufacturer: Option<String>,
+    /// Sequence code of the machine
+    pub sequence: Option<String>,
+    /// Plant of manufacture
+    pub plant: Option<String>,
+}
+
+impl From<&MacSctn> for MachineInfo {
+    fn from(mac: &MacSctn) -> Self {
+        let id = |raw: &[u8]| ebcdic_str(raw).filter(|_| mac.infmval1 & MAC_ID_VLD != 0);
+        Self {
+            cpus: (mac.infmval1 & MAC_CNT_VLD != 0).then(|| (&mac.cnts).into()),
+            name: ebcdic_str(&mac.infmname).filter(|_| mac.infmval1 & MAC_NAME_VLD != 0),
+            machine_type: id(&mac.infmtype),
+            manufacturer: id(&mac.infmmanu),
+            sequence: id(&mac.infmseq),
+            plant: id(&mac.infmpman),
+        }
+    }
+}
+
+/// Information about the logical partition (LPAR)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PartitionInfo {
+    /// Partition number
+    pub number: Option<u16>,
+    /// Name of the partition
+    pub name: Option<String>,
+    /// Multithreading is enabled for the partition
+    pub multithreading: bool,
+    /// CPUs of the partition
+    pub cpus: Option<CpuCounts>,
+    /// Weight-based capping of the CPs, `0xffff` for dedicated CPs
+    pub weight_cp: Option<u32>,
+    /// Weight-based capping of the IFLs, `0xffff` for dedicated IFLs
+    pub weight_ifl: Option<u32>,
+    /// Absolute capping of the CPs, 0 if not capped
+    pub absolute_cap_cp: Option<u32>,
+    /// Absolute capping of the IFLs, 0 if not capped
+    pub absolute_cap_ifl: Option<u32>,
+    /// Name of the LPAR group
+    pub group_name: Option<String>,
+    /// Absolute capping of the CPs of the LPAR group, 0 if not capped
+    pub group_cap_cp: Option<u32>,
+    /// Absolute capping of the IFLs of the LPAR group, 0 if not capped
+    pub group_cap_ifl: Option<u32>,
+}
+
+impl From<&ParSctn> for PartitionInfo {
+    fn from(par: &ParSctn) -> Self {
+        let vld = |flag: u8| par.infpval1 & flag != 0;
+        Self {
+            number: vld(PAR_ID_VLD).then(|| par.infppnum.get()),
+            name: ebcdic_str(&par.infppnam).filter(|_| vld(PAR_ID_VLD)),
+            multithreading: par.infpflg1 & PAR_MT_EN != 0,
+            cpus: vld(PAR_PCNT_VLD).then(|| (&par.cnts).into()),
+            weight_cp: vld(PAR_WGHT_VLD).then(|| par.infpwbcp.get()),
+            weight_ifl: vld(PAR_WGHT_VLD).then(|| par.infpwbif.get()),
+            absolute_cap_cp: vld(PAR_ABS_VLD).then(|| par.infpabcp.get()),
+            absolute_cap_ifl: vld(PAR_ABS_VLD).then(|| par.infpabif.get()),
+            group_name: ebcdic_str(&par.infplgnm).filter(|_| vld(PAR_GRP_VLD)),
+            group_cap_cp: vld(PAR_GRP_VLD).then(|| par.infplgcp.get()),
+            group_cap_ifl: vld(PAR_GRP_VLD).then(|| par.infplgif.get()),
+        }
+    }
+}
+
+/// Type of a hypervisor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HypervisorType {
+    /// z/VM
+    ZVm,
+    /// Any other hypervisor
+    Unknown(u8),
+}
+
+impl From<u8> for HypervisorType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::ZVm,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+/// Information about a hypervisor below the partition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HypervisorInfo {
+    /// Type of the hypervisor
+    pub hypervisor_type: HypervisorType,
+    /// Name of the hypervisor system
+    pub name: Option<String>,
+    /// CPUs available to the hypervisor
+    pub cpus: CpuCounts,
+}
+
+impl From<&HypSctn> for HypervisorInfo {
+    fn from(hyp: &HypSctn) -> Self {
+        Self {
+            hypervisor_type: hyp.infytype.into(),
+            name: ebcdic_str(&hyp.infyname),
+            cpus: (&hyp.cnts).into(),
+        }
+    }
+}
+
+/// Information about the guest of a hypervisor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GuestInfo {
+    /// User ID of the guest
+    pub user_id: Option<String>,
+    /// CPUs of the guest
+    pub cpus: CpuCounts,
+    /// Dispatch type of the CPs
+    pub cp_dispatch_type: u8,
+    /// Capped capacity of the CPs, 0 if not capped
+    pub cp_capped_capacity: u32,
+    /// Dispatch type of the IFLs
+    pub ifl_dispatch_type: u8,
+    /// Capped capacity of the IFLs, 0 if not capped
+    pub ifl_capped_capacity: u32,
+    /// Name of the resource pool of the guest
+    pub pool_name: Option<String>,
+}
+
+impl From<&GstSctn> for GuestInfo {
+    fn from(gst: &GstSctn) -> Self {
+        Self {
+            user_id: ebcdic_str(&gst.infgusid),
+            cpus: CpuCounts {
+                shared_cp: gst.infgscps.get(),
+                dedicated_cp: gst.infgdcps.get(),
+                shared_ifl: gst.infgsifl.get(),
+                dedicated_ifl: gst.infgdifl.get(),
+            },
+            cp_dispatch_type: gst.infgcpdt,
+            cp_capped_capacity: gst.infgcpcc.get(),
+            ifl_dispatch_type: gst.infgifdt,
+            ifl_capped_capacity: gst.infgifcc.get(),
+            pool_name: ebcdic_str(&gst.infgpnam),
+        }
+    }
+}
+
+/// One level of hypervisor nesting below the partition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NestingLevel {
+    /// The hypervisor of this level
+    pub hypervisor: Option<HypervisorInfo>,
+    /// The guest of the hypervisor in which the caller runs
+    pub guest: Option<GuestInfo>,
+}
+
+/// Decoded response of the Store Hypervisor Information (STHYI) function
+///
+/// Describes the CPU capacity of the machine, the logical partition, and up to three levels of
+/// hypervisors in which the caller runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SthyiInfo {
+    /// The caller does not run directly in an LPAR
+    pub not_lpar: bool,
+    /// More hypervisor levels exist than the response can describe
+    pub stack_incomplete: bool,
+    /// The STSI information of the machine is unavailable
+    pub stsi_unavailable: bool,
+    /// The performance information is unavailable
+    pub perf_unavailable: bool,
+    /// Information about the machine
+    pub machine: Option<MachineInfo>,
+    /// Information about the partition
+    pub partition: Option<PartitionInfo>,
+    /// Hypervisor levels, starting with the one closest to the partition
+    pub levels: Vec<NestingLevel>,
+}
+
+impl SthyiInfo {
+    /// Function code of the CP and IFL capacity information
+    const FC_CP_IFL_CAP: u64 = 0;
+
+    /// Decodes a STHYI response buffer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer is not a valid STHYI response.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let (hdr, _) = HdrSctn::ref_from_prefix(buf)
+            .map_err(|_| Error::InvSthyi("response smaller than the header"))?;
+        let total = hdr.infhtotl.get() as usize;
+        if total > buf.len() || (hdr.infhdln.get() as usize) < size_of::<HdrSctn>() {
+            return Err(Error::InvSthyi("invalid response length"));
+        }
+        let buf = &buf[..total];
+
+        let levels = hdr
+            .levels
+            .iter()
+            .take((hdr.infhygct as usize).min(MAX_LEVELS))
+            .map(|[hyp, gst]| {
+                Ok(NestingLevel {
+                    hypervisor: section::<HypSctn>(buf, hyp)?.as_ref().map(Into::into),
+                    guest: section::<GstSctn>(buf, gst)?.as_ref().map(Into::into),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            not_lpar: hdr.infhflg1 & HDR_NOT_LPAR != 0,
+            stack_incomplete: hdr.infhflg1 & HDR_STACK_INCM != 0,
+            stsi_unavailable: hdr.infhflg1 & HDR_STSI_UNAV != 0,
+            perf_unavailable: hdr.infhflg1 & HDR_PERF_UNAV != 0,
+            machine: section::<MacSctn>(buf, &hdr.mac)?.as_ref().map(Into::into),
+            partition: section::<ParSctn>(buf, &hdr.par)?.as_ref().map(Into::into),
+            levels,
+        })
+    }
+
+    /// Retrieves the hypervisor information of the running system.
+    ///
+    /// Uses the `s390_sthyi` system call, available since Linux 4.15.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the kernel or the machine does not provide STHYI,
+    /// e.g. on other architectures, or the response is invalid.
+    pub fn query() -> Result<Self> {
+        let buf = sthyi(Self::FC_CP_IFL_CAP)?;
+        Self::decode(&buf)
+    }
+}
+
+#[cfg(target_arch = "s390x")]
+fn sthyi(fc: u64) -> Result<Vec<u8>> {
+    use crate::PAGESIZE;
+    use log::debug;
+
+    const SYS_S390_STHYI: std::ffi::c_long = 380;
+
+    let mut buf = vec![0u8; PAGESIZE];
+    let mut rc = 0u64;
+    // SAFETY: the buffer has the size of a page, as required by the kernel. Both pointers point to
+    // valid memory regions that outlive this call.
+    let cc = unsafe { libc::syscall(SYS_S390_STHYI, fc, buf.as_mut_ptr(), &mut rc, 0) };
+    match cc {
+        0 => Ok(buf),
+        -1 => Err(Error::SthyiUnavailable(std::io::Error::last_os_error())),
+        cc => {
+            debug!("STHYI failed with condition code {cc} and return code {rc:#x}");
+            Err(Error::SthyiUnavailable(std::io::Error::from_raw_os_error(
+                libc::EOPNOTSUPP,
+            )))
+        }
+    }
+}
+
+#[cfg(not(target_arch = "s390x"))]
+fn sthyi(_fc: u64) -> Result<Vec<u8>> {
+    Err(Error::SthyiUnavailable(std::io::Error::from_raw_os_error(
+        libc::ENOSYS,
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ebcdic(s: &str) -> Vec<u8> {
+        s.bytes()
+            .map(|b| match b {
+                b' ' => 0x40,
+                b'a'..=b'i' => b - b'a' + 0x81,
+                b'A'..=b'I' => b - b'A' + 0xc1,
+                b'J'..=b'R' => b - b'J' + 0xd1,
+                b'S'..=b'Z' => b - b'S' + 0xe2,
+                b'0'..=b'9' => b - b'0' + 0xf0,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    fn lpar_response() -> Vec<u8> {
+        let mut buf = vec![0u8; 0x100];
+        // header: not in an LPAR, one hypervisor level
+        buf[0] = HDR_NOT_LPAR;
+        buf[7] = 1;
+        buf[8..10].copy_from_slice(&0x100u16.to_be_bytes());
+        buf[10..12].copy_from_slice(&0x30u16.to_be_bytes());
+        for (i, (off, len)) in [(0x30u16, 0x40u16), (0x70, 0x38), (0xa8, 0x18), (0xc0, 0x2c)]
+            .into_iter()
+            .enumerate()
+        {
+            buf[12 + 4 * i..14 + 4 * i].copy_from_slice(&off.to_be_bytes());
+            buf[14 + 4 * i..16 + 4 * i].copy_from_slice(&len.to_be_bytes());
+        }
+        // machine
+        buf[0x32] = MAC_CNT_VLD | MAC_ID_VLD;
+        buf[0x34..0x36].copy_from_slice(&4u16.to_be_bytes());
+        buf[0x3a..0x3c].copy_from_slice(&2u16.to_be_bytes());
+        buf[0x3c..0x44].copy_from_slice(&ebcdic("IGNORED "));
+        buf[0x44..0x48].copy_from_slice(&ebcdic("3931"));
+        buf[0x48..0x58].copy_from_slice(&ebcdic("IBM             "));
+        buf[0x58..0x68].copy_from_slice(&ebcdic("00000000000A1B2C"));
+        buf[0x68..0x6c].copy_from_slice(&ebcdic("02  "));
+        // partition: without group information
+        buf[0x70] = PAR_MT_EN;
+        buf[0x72] = PAR_ID_VLD | PAR_WGHT_VLD;
+        buf[0x74..0x76].copy_from_slice(&0x17u16.to_be_bytes());
+        buf[0x80..0x88].copy_from_slice(&ebcdic("LPAR1   "));
+        buf[0x88..0x8c].copy_from_slice(&0xffffu32.to_be_bytes());
+        // hypervisor
+        buf[0xac] = 1;
+        buf[0xb0..0xb8].copy_from_slice(&ebcdic("ZVM1    "));
+        buf[0xbc..0xbe].copy_from_slice(&3u16.to_be_bytes());
+        // guest
+        buf[0xc4..0xcc].copy_from_slice(&ebcdic("LINUX1  "));
+        buf[0xcc..0xce].copy_from_slice(&2u16.to_be_bytes());
+        buf
+    }
+
+    #[test]
+    fn decode() {
+        let info = SthyiInfo::decode(&lpar_response()).unwrap();
+        assert!(info.not_lpar);
+        assert!(!info.stack_incomplete);
+
+        let mac = info.machine.unwrap();
+        assert_eq!(mac.name, None);
+        assert_eq!(mac.machine_type.as_deref(), Some("3931"));
+        assert_eq!(mac.manufacturer.as_deref(), Some("IBM"));
+        assert_eq!(mac.sequence.as_deref(), Some("00000000000A1B2C"));
+        assert_eq!(mac.plant.as_deref(), Some("02"));
+        let cpus = mac.cpus.unwrap();
+        assert_eq!((cpus.shared_cp, cpus.dedicated_ifl), (4, 2));
+
+        let par = info.partition.unwrap();
+        assert_eq!(par.number, Some(0x17));
+        assert_eq!(par.name.as_deref(), Some("LPAR1"));
+        assert!(par.multithreading);
+        assert_eq!(par.cpus, None);
+        assert_eq!(par.weight_cp, Some(0xffff));
+        assert_eq!(par.absolute_cap_cp, None);
+        assert_eq!(par.group_name, None);
+
+        assert_eq!(info.levels.len(), 1);
+        let hyp = info.levels[0].hypervisor.as_ref().unwrap();
+        assert_eq!(hyp.hypervisor_type, HypervisorType::ZVm);
+        assert_eq!(hyp.name.as_deref(), Some("ZVM1"));
+        assert_eq!(hyp.cpus.shared_ifl, 3);
+        let gst = info.levels[0].guest.as_ref().unwrap();
+        assert_eq!(gst.user_id.as_deref(), Some("LINUX1"));
+        assert_eq!(gst.cpus.shared_cp, 2);
+    }
+
+    #[test]
+    fn decode_short_partition() {
+        let mut buf = lpar_response();
+        // section of older machines without LPAR group information
+        buf[0x12..0x14].copy_from_slice(&0x28u16.to_be_bytes());
+        buf[0x72] |= PAR_GRP_VLD;
+        let par = SthyiInfo::decode(&buf).unwrap().partition.unwrap();
+        assert_eq!(par.group_name, None);
+        assert_eq!(par.group_cap_cp, Some(0));
+    }
+
+    #[test]
+    fn decode_invalid() {
+        let buf = lpar_response();
+        assert!(SthyiInfo::decode(&buf[..0x20]).is_err());
+        assert!(SthyiInfo::decode(&buf[..0x80]).is_err());
+
+        let mut buf = lpar_response();
+        buf[0x10..0x12].copy_from_slice(&0xf0u16.to_be_bytes());
+        assert!(matches!(SthyiInfo::decode(&buf), Err(Error::InvSthyi(_))));
+    }
+}
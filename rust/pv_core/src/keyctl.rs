@@ -0,0 +1,337 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use crate::{request::Confidential, Error, Result};
+use log::debug;
+use std::{
+    ffi::{c_long, CString},
+    fmt::Display,
+};
+
+/// Maximum payload size of `user` and `logon` keys
+const MAX_PAYLOAD_SIZE: usize = 32767;
+
+/// Serial number of a key or keyring in the kernel key management facility
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeySerial(i32);
+
+impl KeySerial {
+    /// Returns the raw serial number.
+    pub fn as_raw(&self) -> i32 {
+        self.0
+    }
+}
+
+impl From<i32> for KeySerial {
+    fn from(value: i32) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for KeySerial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A keyring of the kernel key management facility
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keyring {
+    /// Thread-specific keyring of the caller
+    Thread,
+    /// Process-specific keyring of the caller
+    Process,
+    /// Session-specific keyring of the caller
+    Session,
+    /// UID-specific keyring of the caller
+    User,
+    /// UID-session keyring of the caller
+    UserSession,
+    /// Any other keyring specified by its serial number
+    Serial(KeySerial),
+}
+
+impl Keyring {
+    fn as_raw(&self) -> i32 {
+        match self {
+            Self::Thread => libc::KEY_SPEC_THREAD_KEYRING,
+            Self::Process => libc::KEY_SPEC_PROCESS_KEYRING,
+            Self::Session => libc::KEY_SPEC_SESSION_KEYRING,
+            Self::User => libc::KEY_SPEC_USER_KEYRING,
+            Self::UserSession => libc::KEY_SPEC_USER_SESSION_KEYRING,
+            Self::Serial(s) => s.as_raw(),
+        }
+    }
+}
+
+impl Display for Keyring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Thread => write!(f, "@t"),
+            Self::Process => write!(f, "@p"),
+            Self::Session => write!(f, "@s"),
+            Self::User => write!(f, "@u"),
+            Self::UserSession => write!(f, "@us"),
+            Self::Serial(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+/// Type of a key in the kernel key management facility
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    /// `user` key; the payload can be read back from userspace
+    User,
+    /// `logon` key; the payload can only be used by the kernel and not be read back from
+    /// userspace. The description must have the form `<service>:<name>`.
+    Logon,
+}
+
+impl KeyType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Logon => "logon",
+        }
+    }
+}
+
+impl Display for KeyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Typed description of a key.
+///
+/// Combines the key type with a validated description string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyDescription {
+    ty: KeyType,
+    desc: String,
+}
+
+impl KeyDescription {
+    /// Create a description for a `user` key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the description is empty or contains a NUL
+    /// character.
+    pub fn user<S: Into<String>>(desc: S) -> Result<Self> {
+        Self::new(KeyType::User, desc.into())
+    }
+
+    /// Create a description for a `logon` key.
+    ///
+    /// The description will be `<service>:<name>`, as required for `logon` keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `service` is empty or contains a colon, or if the
+    /// description contains a NUL character.
+    pub fn logon(service: &str, name: &str) -> Result<Self> {
+        if service.contains(':') {
+            return Err(Error::InvKeyDescription {
+                desc: format!("{service}:{name}"),
+                reason: "the service must not contain a colon",
+            });
+        }
+        Self::new(KeyType::Logon, format!("{service}:{name}"))
+    }
+
+    fn new(ty: KeyType, desc: String) -> Result<Self> {
+        let reason = if desc.is_empty() {
+            Some("the description must not be empty")
+        } else if desc.contains('\0') {
+            Some("the description must not contain NUL characters")
+        } else if ty == KeyType::Logon && desc.starts_with(':') {
+            Some("the description must start with a non-empty service prefix")
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => Err(Error::InvKeyDescription { desc, reason }),
+            None => Ok(Self { ty, desc }),
+        }
+    }
+
+    /// Returns the key type.
+    pub fn key_type(&self) -> KeyType {
+        self.ty
+    }
+
+    /// Returns the description string.
+    pub fn description(&self) -> &str {
+        &self.desc
+    }
+
+    fn c_strings(&self) -> (CString, CString) {
+        // Cannot fail, NUL characters are rejected by the constructors.
+        (
+            CString::new(self.ty.as_str()).unwrap(),
+            CString::new(self.desc.as_str()).unwrap(),
+        )
+    }
+}
+
+impl Display for KeyDescription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} key '{}'", self.ty, self.desc)
+    }
+}
+
+fn syscall_result(rc: c_long) -> Result<c_long> {
+    match rc {
+        -1 => Err(std::io::Error::last_os_error().into()),
+        rc => Ok(rc),
+    }
+}
+
+fn keyctl(op: u32, arg2: c_long, arg3: c_long, arg4: c_long, arg5: c_long) -> Result<c_long> {
+    // SAFETY: keyctl does not access memory except for the pointers passed by the callers, which
+    // point to valid memory regions that outlive this call.
+    syscall_result(unsafe { libc::syscall(libc::SYS_keyctl, op, arg2, arg3, arg4, arg5) })
+}
+
+/// Add a key to a keyring.
+///
+/// If the keyring already holds a key with the same type and description, the payload of that key
+/// is updated instead.
+///
+/// # Errors
+///
+/// This function will return an error if the payload is larger than 32767 bytes or the kernel
+/// refuses to add the key.
+pub fn add_key(desc: &KeyDescription, payload: &[u8], keyring: Keyring) -> Result<KeySerial> {
+    if payload.len() > MAX_PAYLOAD_SIZE {
+        return Err(Error::InvKeyPayloadSize {
+            size: payload.len(),
+            max: MAX_PAYLOAD_SIZE,
+        });
+    }
+    debug!("Adding {desc} to keyring {keyring}");
+    let (ty, desc) = desc.c_strings();
+    // SAFETY: all pointers point to valid memory regions that outlive this call. The lengths
+    // match the buffers.
+    let rc = unsafe {
+        libc::syscall(
+            libc::SYS_add_key,
+            ty.as_ptr(),
+            desc.as_ptr(),
+            payload.as_ptr(),
+            payload.len(),
+            keyring.as_raw(),
+        )
+    };
+    syscall_result(rc).map(|serial| KeySerial(serial as i32))
+}
+
+/// Search a keyring and its nested keyrings for a key.
+///
+/// Returns [`None`] if no matching key was found.
+///
+/// # Errors
+///
+/// This function will return an error if the keyring cannot be searched.
+pub fn search(keyring: Keyring, desc: &KeyDescription) -> Result<Option<KeySerial>> {
+    let (ty, desc) = desc.c_strings();
+    match keyctl(
+        libc::KEYCTL_SEARCH,
+        keyring.as_raw() as c_long,
+        ty.as_ptr() as c_long,
+        desc.as_ptr() as c_long,
+        0,
+    ) {
+        Ok(serial) => Ok(Some(KeySerial(serial as i32))),
+        Err(Error::Io(e)) if e.raw_os_error() == Some(libc::ENOKEY) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Read the payload of a key.
+///
+/// Only works for key types that allow reading from userspace, e.g. `user` keys.
+///
+/// # Errors
+///
+/// This function will return an error if the key does not exist or cannot be read.
+pub fn read(key: KeySerial) -> Result<Confidential<Vec<u8>>> {
+    let mut buf = Confidential::new(vec![0u8; MAX_PAYLOAD_SIZE]);
+    let len = keyctl(
+        libc::KEYCTL_READ,
+        key.as_raw() as c_long,
+        buf.value_mut().as_mut_ptr() as c_long,
+        MAX_PAYLOAD_SIZE as c_long,
+        0,
+    )? as usize;
+    buf.value_mut().truncate(len);
+    Ok(buf)
+}
+
+/// Revoke a key.
+///
+/// Any further access to the key fails.
+///
+/// # Errors
+///
+/// This function will return an error if the key does not exist or cannot be revoked.
+pub fn revoke(key: KeySerial) -> Result<()> {
+    keyctl(libc::KEYCTL_REVOKE, key.as_raw() as c_long, 0, 0, 0).map(|_| ())
+}
+
+/// Unlink a key from a keyring.
+///
+/// # Errors
+///
+/// This function will return an error if the key is not linked to the keyring.
+pub fn unlink(key: KeySerial, keyring: Keyring) -> Result<()> {
+    keyctl(
+        libc::KEYCTL_UNLINK,
+        key.as_raw() as c_long,
+        keyring.as_raw() as c_long,
+        0,
+        0,
+    )
+    .map(|_| ())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn user_desc() {
+        let desc = KeyDescription::user("pvsecret:my_secret").unwrap();
+        assert_eq!(desc.key_type(), KeyType::User);
+        assert_eq!(desc.description(), "pvsecret:my_secret");
+        assert!(KeyDescription::user("").is_err());
+        assert!(KeyDescription::user("a\0b").is_err());
+    }
+
+    #[test]
+    fn logon_desc() {
+        let desc = KeyDescription::logon("pvsecret", "my_secret").unwrap();
+        assert_eq!(desc.key_type(), KeyType::Logon);
+        assert_eq!(desc.description(), "pvsecret:my_secret");
+        assert_eq!(desc.to_string(), "logon key 'pvsecret:my_secret'");
+        assert!(KeyDescription::logon("", "my_secret").is_err());
+        assert!(KeyDescription::logon("a:b", "my_secret").is_err());
+    }
+
+    #[test]
+    fn keyring_display() {
+        assert_eq!(Keyring::Session.to_string(), "@s");
+        assert_eq!(Keyring::Serial(17.into()).to_string(), "17");
+    }
+
+    #[test]
+    fn payload_too_large() {
+        let desc = KeyDescription::user("test").unwrap();
+        assert!(matches!(
+            add_key(&desc, &[0; MAX_PAYLOAD_SIZE + 1], Keyring::Process),
+            Err(Error::InvKeyPayloadSize { .. })
+        ));
+    }
+}
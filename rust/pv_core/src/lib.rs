@@ -5,28 +5,38 @@
 mod apdevice;
 mod confidential;
 mod error;
+mod execenv;
+mod guarded;
+mod hypinfo;
+mod keyctl;
 mod macros;
+mod pkeydevice;
+mod privdrop;
+mod restrict;
 mod utils;
 mod uvattest;
 mod uvdevice;
 mod uvsecret;
 
 pub use error::{Error, FileAccessErrorType, FileIoErrorType, Result};
+pub use pv_ffi::{assert_size, static_assert};
 
 /// Functionalities for reading attestation requests
 pub mod attest {
-    pub use crate::uvattest::{AttestationMagic, AttestationMeasAlg};
+    pub use crate::uvattest::{AttestationMagic, AttestationMeasAlg, UserData};
 }
 
 /// Miscellaneous functions and definitions
 pub mod misc {
     pub use crate::utils::pv_guest_bit_set;
     pub use crate::utils::{create_file, open_file};
-    pub use crate::utils::{decode_hex, encode_hex, parse_hex};
+    pub use crate::utils::{decode_base64, decode_hex, encode_base64, encode_hex, parse_hex};
     pub use crate::utils::{read, write};
-    pub use crate::utils::{read_exact_file, read_file, read_file_string, write_file};
+    pub use crate::utils::{
+        read_exact_file, read_file, read_file_string, read_secret_file, write_file,
+    };
     pub use crate::utils::{to_u16, to_u32, try_parse_u128, try_parse_u64};
-    pub use crate::utils::{Flags, Lsb0Flags64, Msb0Flags64};
+    pub use pv_ffi::flags::{Flags, Lsb0Flags64, Msb0Flags64};
 }
 
 /// Definitions and functions for interacting with the Ultravisor
@@ -35,16 +45,32 @@ pub mod misc {
 /// [`crate::uv::UvCmd`]
 pub mod uv {
     pub use crate::uvdevice::attest::AttestationCmd;
+    pub use crate::uvdevice::query::UvQueryInfo;
     pub use crate::uvdevice::retr_secret::RetrievableSecret;
     pub use crate::uvdevice::retr_secret::{AesSizes, AesXtsSizes, EcCurves, HmacShaSizes};
     pub use crate::uvdevice::secret::{AddCmd, ListCmd, LockCmd, RetrieveCmd};
     pub use crate::uvdevice::secret_list::{ListableSecretType, SecretEntry, SecretId, SecretList};
-    pub use crate::uvdevice::{ConfigUid, UvCmd, UvDevice, UvDeviceInfo, UvFlags, UvcSuccess};
+    pub use crate::uvdevice::store_info::SecretStoreInfo;
+    #[cfg(feature = "trace-ioctl")]
+    pub use crate::uvdevice::IoctlTrace;
+    #[cfg(feature = "uv-sim")]
+    pub use crate::uvdevice::UvSimulator;
+    #[cfg(feature = "uvdevice")]
+    pub use crate::uvdevice::{
+        BatchCmd, BatchPolicy, CallOutcome, PooledUvDevice, PreparedCmd, ReconnectPolicy,
+        RetryPolicy, UvCall, UvDevice, UvDeviceInfo, UvDevicePool, UvMetrics, UvObserver,
+        UvcResponse, UvcSuccess,
+    };
+    pub use crate::uvdevice::{ConfigUid, UvArg, UvCmd, UvFlags};
+    #[cfg(feature = "record-ioctl")]
+    pub use crate::uvdevice::{IoctlExchange, IoctlRecorder};
 }
 
 /// Functionalities to verify UV requests
 pub mod request {
-    pub use crate::confidential::{Confidential, Zeroize};
+    pub use crate::confidential::{Confidential, SecretBytes, Zeroize};
+    pub use crate::guarded::GuardedBuf;
+    pub use zeroize::{ZeroizeOnDrop, Zeroizing};
     /// Version number of the request in system endianness
     pub type RequestVersion = u32;
     /// Request magic value
@@ -77,6 +103,7 @@ pub mod secret {
 pub mod ap {
     pub use crate::apdevice::Apqn;
     pub use crate::apdevice::RE_QUEUE_DIR;
+    pub use crate::apdevice::{ap_queue_status, ApHealthSummary, ApqnHealth, ApqnMkvp, ApqnStatus};
     pub use crate::apdevice::{get_apqn_bind_state, set_apqn_bind_state};
     /// AP modes
     pub mod apqn_mode {
@@ -97,5 +124,60 @@ pub mod ap {
     }
 }
 
+/// Functionalities for secure keys of the CCA and EP11 crypto adapters
+///
+/// Provides access to the pkey device (/dev/pkey) to generate and validate secure keys and to
+/// convert them to protected keys.
+pub mod pkey {
+    pub use crate::pkeydevice::{ApqnAddr, KeyGenSpec, KeyVerification, MasterKey, PkeyDevice};
+    pub use crate::pkeydevice::{KeyType, ProtectedKey, SecureKey};
+}
+
+/// Functionalities for the kernel key management facility
+///
+/// Provides access to the kernel keyrings, e.g. to hand retrieved secrets over to the kernel.
+pub mod keyring {
+    pub use crate::keyctl::{add_key, read, revoke, search, unlink};
+    pub use crate::keyctl::{KeyDescription, KeySerial, KeyType, Keyring};
+}
+
+/// Functionalities to drop the privileges of the process
+///
+/// Allows services to open privileged resources, such as the uvdevice, as root and then continue
+/// unprivileged.
+pub mod privilege {
+    pub use crate::privdrop::{lookup_group, lookup_user, Capability, PrivDrop};
+}
+
+/// Functionalities to sandbox the process
+///
+/// Restricts the file system access and the system calls of tools before they process untrusted
+/// input.
+pub mod sandbox {
+    pub use crate::restrict::{FsAccess, Sandbox};
+}
+
+/// Detection of the execution environment
+///
+/// Allows tools and libraries to fail fast with a useful message if they run in the wrong
+/// environment, e.g. outside of a Secure Execution guest.
+pub mod env {
+    pub use crate::execenv::{
+        has_facility, has_uv_call_facility, is_se_guest, require_se_guest, require_se_host,
+        running_under, se_host_capable, Environment,
+    };
+}
+
+/// Hypervisor information of the execution environment
+///
+/// Decodes the Store Hypervisor Information (STHYI) of the machine, the logical partition, and the
+/// hypervisors in which the caller runs, e.g. for capacity-aware tooling or inventory reports.
+pub mod sthyi {
+    pub use crate::hypinfo::{
+        CpuCounts, GuestInfo, HypervisorInfo, HypervisorType, MachineInfo, NestingLevel,
+        PartitionInfo, SthyiInfo,
+    };
+}
+
 // Internal definitions/ imports
 const PAGESIZE: usize = 0x1000;
@@ -0,0 +1,312 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+//
+//! Access to the pkey device (/dev/pkey)
+//
+
+use std::{ffi::c_ulong, fmt::Display, fs::File, os::fd::AsRawFd, ptr, str::FromStr};
+
+use log::debug;
+use serde::Serialize;
+
+use crate::{confidential::Confidential, Error, FileAccessErrorType, Result};
+
+mod blob;
+mod ffi;
+
+pub use blob::{KeyType, ProtectedKey, SecureKey};
+
+/// Maximal number of APQNs the pkey device reports for a key
+const MAX_APQNS: usize = 256;
+
+/// Address of an APQN (adjunct processor queue number): card and domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ApqnAddr {
+    /// Card number
+    pub card: u16,
+    /// Domain number
+    pub domain: u16,
+}
+
+impl ApqnAddr {
+    fn to_ffi(self) -> ffi::pkey_apqn {
+        ffi::pkey_apqn {
+            card: self.card,
+            domain: self.domain,
+        }
+    }
+}
+
+impl Display for ApqnAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02x}.{:04x}", self.card, self.domain)
+    }
+}
+
+impl FromStr for ApqnAddr {
+    type Err = Error;
+
+    /// Parse an APQN in the format `CARD.DOMAIN` with hexadecimal numbers, e.g. `03.0039`.
+    fn from_str(s: &str) -> Result<Self> {
+        let err = || Error::ParseError {
+            subject: "APQN".to_string(),
+            content: s.to_string(),
+        };
+        let (card, domain) = s.split_once('.').ok_or_else(err)?;
+        let card = u8::from_str_radix(card, 16).map_err(|_| err())?;
+        let domain = u8::from_str_radix(domain, 16).map_err(|_| err())?;
+        Ok(Self {
+            card: card.into(),
+            domain: domain.into(),
+        })
+    }
+}
+
+/// Which master key a secure key is wrapped with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MasterKey {
+    /// The current master key of the APQN
+    Current,
+    /// The old master key of the APQN; the key must be re-enciphered
+    Old,
+}
+
+/// Result of the validation of a key blob by the pkey device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyVerification {
+    /// An APQN that can use the key
+    pub apqn: ApqnAddr,
+    /// Type of the key
+    pub key_type: Option<KeyType>,
+    /// Size of the AES key in bits
+    pub bits: Option<u32>,
+    /// Master key of the APQN the key is wrapped with
+    pub master_key: MasterKey,
+}
+
+/// Generation parameters of a secure key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyGenSpec {
+    /// Type of the key blobs
+    pub key_type: KeyType,
+    /// Size of the AES key in bits, 128, 192, or 256
+    pub bits: u32,
+    /// Generate two key blobs for AES-XTS
+    pub xts: bool,
+    /// APQNs to generate the key with, any APQN if empty
+    pub apqns: Vec<ApqnAddr>,
+}
+
+/// The pkey device (/dev/pkey)
+///
+/// Generates and validates secure keys with the CCA and EP11 crypto adapters and converts them to
+/// protected keys.
+#[derive(Debug)]
+pub struct PkeyDevice(File);
+
+impl PkeyDevice {
+    /// Path of the pkey device
+    pub const PATH: &'static str = "/dev/pkey";
+
+    /// Open the pkey device.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the device file cannot be opened, e.g. because the
+    /// pkey kernel module is not loaded.
+    pub fn open() -> Result<Self> {
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(Self::PATH)
+            .map(Self)
+            .map_err(|e| Error::FileAccess {
+                ty: FileAccessErrorType::Open,
+                path: Self::PATH.into(),
+                source: e,
+            })
+    }
+
+    /// Fire the IOCTL `cmd` with the control block `cb`.
+    fn ioctl<T: std::fmt::Debug>(
+        &self,
+        name: &'static str,
+        cmd: c_ulong,
+        cb: &mut T,
+    ) -> Result<()> {
+        debug!("calling pkey IOCTL {name} ({cmd:#x})");
+        // SAFETY: `cb` is the control block `cmd` expects. All buffers it points to are valid
+        // for the lengths it specifies and outlive the call.
+        let rc = unsafe { libc::ioctl(self.0.as_raw_fd(), cmd, cb as *mut T) };
+        match rc {
+            0 => Ok(()),
+            _ => Err(Error::Pkey {
+                op: name,
+                source: std::io::Error::last_os_error(),
+            }),
+        }
+    }
+
+    /// Generate a random secure key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if none of the APQNs can generate the key.
+    pub fn generate(&self, spec: &KeyGenSpec) -> Result<SecureKey> {
+        let size = match spec.bits {
+            128 => ffi::PKEY_SIZE_AES_128,
+            192 => ffi::PKEY_SIZE_AES_192,
+            256 => ffi::PKEY_SIZE_AES_256,
+            _ => return Err(Error::InvSecureKey("Invalid key size")),
+        };
+        let apqns: Vec<_> = spec.apqns.iter().map(|a| a.to_ffi()).collect();
+        let blobs = (0..if spec.xts { 2 } else { 1 })
+            .map(|_| {
+                let mut key = vec![0u8; spec.key_type.blob_size()];
+                let mut cb = ffi::pkey_genseck2 {
+                    apqns: if apqns.is_empty() {
+                        ptr::null()
+                    } else {
+                        apqns.as_ptr()
+                    },
+                    apqn_entries: apqns.len() as u32,
+                    type_: spec.key_type.pkey_type(),
+                    size,
+                    keygenflags: 0,
+                    key: key.as_mut_ptr(),
+                    keylen: key.len() as u32,
+                };
+                self.ioctl("GENSECK2", ffi::PKEY_GENSECK2, &mut cb)?;
+                key.truncate(cb.keylen as usize);
+                Ok(key)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(SecureKey::from_blobs(spec.key_type, blobs))
+    }
+
+    /// Validate the key blob `blob` and find an APQN that can use it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no APQN has the master key `blob` is wrapped with.
+    pub fn verify(&self, blob: &[u8]) -> Result<KeyVerification> {
+        let mut cb = ffi::pkey_verifykey2 {
+            key: blob.as_ptr(),
+            keylen: blob.len() as u32,
+            cardnr: 0xffff,
+            domain: 0xffff,
+            type_: 0,
+            size: 0,
+            flags: 0,
+        };
+        self.ioctl("VERIFYKEY2", ffi::PKEY_VERIFYKEY2, &mut cb)?;
+        Ok(KeyVerification {
+            apqn: ApqnAddr {
+                card: cb.cardnr,
+                domain: cb.domain,
+            },
+            key_type: KeyType::from_pkey_type(cb.type_),
+            bits: match cb.size {
+                ffi::PKEY_SIZE_UNKNOWN | 0 => None,
+                bits => Some(bits),
+            },
+            master_key: match cb.flags & ffi::PKEY_FLAGS_MATCH_ALT_MKVP {
+                0 => MasterKey::Current,
+                _ => MasterKey::Old,
+            },
+        })
+    }
+
+    /// List the APQNs that have the master key `blob` is wrapped with.
+    ///
+    /// Matches the current master key of the APQNs, and the old one if `old` is set.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the pkey device fails. No matching APQN is not an
+    /// error.
+    pub fn apqns_for_key(&self, blob: &[u8], old: bool) -> Result<Vec<ApqnAddr>> {
+        let mut apqns = vec![ffi::pkey_apqn::default(); MAX_APQNS];
+        let mut cb = ffi::pkey_apqns4key {
+            key: blob.as_ptr(),
+            keylen: blob.len() as u32,
+            flags: match old {
+                true => ffi::PKEY_FLAGS_MATCH_CUR_MKVP | ffi::PKEY_FLAGS_MATCH_ALT_MKVP,
+                false => ffi::PKEY_FLAGS_MATCH_CUR_MKVP,
+            },
+            apqns: apqns.as_mut_ptr(),
+            apqn_entries: apqns.len() as u32,
+        };
+        match self.ioctl("APQNS4K", ffi::PKEY_APQNS4K, &mut cb) {
+            Ok(()) => (),
+            Err(Error::Pkey { source, .. }) if source.raw_os_error() == Some(libc::ENODEV) => {
+                return Ok(vec![])
+            }
+            Err(e) => return Err(e),
+        }
+        apqns.truncate(cb.apqn_entries as usize);
+        let mut apqns: Vec<_> = apqns
+            .into_iter()
+            .map(|a| ApqnAddr {
+                card: a.card,
+                domain: a.domain,
+            })
+            .collect();
+        apqns.sort();
+        Ok(apqns)
+    }
+
+    /// Convert the key blob `blob` into a protected key with one of `apqns`, or any APQN if
+    /// empty.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if none of the APQNs can convert the key.
+    pub fn protected_key(&self, blob: &[u8], apqns: &[ApqnAddr]) -> Result<ProtectedKey> {
+        let apqns: Vec<_> = apqns.iter().map(|a| a.to_ffi()).collect();
+        let mut key = Confidential::new(vec![0u8; ffi::MAXPROTKEYSIZE]);
+        let mut cb = ffi::pkey_kblob2pkey3 {
+            key: blob.as_ptr(),
+            keylen: blob.len() as u32,
+            apqns: if apqns.is_empty() {
+                ptr::null()
+            } else {
+                apqns.as_ptr()
+            },
+            apqn_entries: apqns.len() as u32,
+            pkeytype: 0,
+            pkeylen: ffi::MAXPROTKEYSIZE as u32,
+            pkey: key.value_mut().as_mut_ptr(),
+        };
+        self.ioctl("KBLOB2PROTK3", ffi::PKEY_KBLOB2PROTK3, &mut cb)?;
+        key.value_mut().truncate(cb.pkeylen as usize);
+        Ok(ProtectedKey {
+            keytype: cb.pkeytype,
+            key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn apqn_addr() {
+        let apqn: ApqnAddr = "03.0039".parse().unwrap();
+        assert_eq!(
+            apqn,
+            ApqnAddr {
+                card: 3,
+                domain: 0x39
+            }
+        );
+        assert_eq!(apqn.to_string(), "03.0039");
+        assert_eq!("3.39".parse::<ApqnAddr>().unwrap(), apqn);
+        for s in ["", "03", "03.", "x.1", "100.0", "03.0039.1"] {
+            assert!(s.parse::<ApqnAddr>().is_err(), "{s}");
+        }
+    }
+}
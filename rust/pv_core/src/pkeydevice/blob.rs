@@ -0,0 +1,344 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{fmt::Display, str::FromStr};
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use super::ffi;
+use crate::{confidential::Confidential, Error, Result};
+
+const TOKEN_TYPE_NON_CCA: u8 = 0x00;
+const TOKEN_TYPE_CCA_INTERNAL: u8 = 0x01;
+const TOKEN_VERSION_PROTECTED_KEY: u8 = 0x01;
+const TOKEN_VERSION_AESDATA: u8 = 0x04;
+const TOKEN_VERSION_AESCIPHER: u8 = 0x05;
+const TOKEN_VERSION_EP11_AES_WITH_HEADER: u8 = 0x06;
+
+const AESDATA_KEY_SIZE: usize = 64;
+const AESCIPHER_KEY_SIZE: usize = 136;
+const EP11_AES_KEY_SIZE: usize = 336;
+
+/// Type of a secure key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum KeyType {
+    /// CCA AES DATA key (`CCA-AESDATA`)
+    #[serde(rename = "CCA-AESDATA")]
+    CcaAesData,
+    /// CCA AES CIPHER key (`CCA-AESCIPHER`)
+    #[serde(rename = "CCA-AESCIPHER")]
+    CcaAesCipher,
+    /// EP11 AES key with header (`EP11-AES`)
+    #[serde(rename = "EP11-AES")]
+    Ep11Aes,
+}
+
+impl KeyType {
+    /// Name of the key type as used by zkey, e.g. `CCA-AESDATA`
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::CcaAesData => "CCA-AESDATA",
+            Self::CcaAesCipher => "CCA-AESCIPHER",
+            Self::Ep11Aes => "EP11-AES",
+        }
+    }
+
+    /// Key type is wrapped by a CCA master key
+    pub const fn is_cca(&self) -> bool {
+        matches!(self, Self::CcaAesData | Self::CcaAesCipher)
+    }
+
+    pub(super) const fn pkey_type(&self) -> u32 {
+        match self {
+            Self::CcaAesData => ffi::PKEY_TYPE_CCA_DATA,
+            Self::CcaAesCipher => ffi::PKEY_TYPE_CCA_CIPHER,
+            Self::Ep11Aes => ffi::PKEY_TYPE_EP11_AES,
+        }
+    }
+
+    pub(super) const fn from_pkey_type(ty: u32) -> Option<Self> {
+        match ty {
+            ffi::PKEY_TYPE_CCA_DATA => Some(Self::CcaAesData),
+            ffi::PKEY_TYPE_CCA_CIPHER => Some(Self::CcaAesCipher),
+            ffi::PKEY_TYPE_EP11 | ffi::PKEY_TYPE_EP11_AES => Some(Self::Ep11Aes),
+            _ => None,
+        }
+    }
+
+    /// Size of one key blob of this type as generated by the pkey module
+    pub const fn blob_size(&self) -> usize {
+        match self {
+            Self::CcaAesData => AESDATA_KEY_SIZE,
+            Self::CcaAesCipher => AESCIPHER_KEY_SIZE,
+            Self::Ep11Aes => EP11_AES_KEY_SIZE,
+        }
+    }
+}
+
+impl Display for KeyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for KeyType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        [Self::CcaAesData, Self::CcaAesCipher, Self::Ep11Aes]
+            .into_iter()
+            .find(|t| t.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Error::UnknownId {
+                kind: "key type",
+                name: s.to_string(),
+            })
+    }
+}
+
+/// Detect the type and the size of the key blob at the start of `data`.
+fn blob_header(data: &[u8]) -> Result<(KeyType, usize)> {
+    if data.len() < 8 {
+        return Err(Error::InvSecureKey("The key blob is too small"));
+    }
+    let (ty, size) = match (data[0], data[4]) {
+        (TOKEN_TYPE_CCA_INTERNAL, TOKEN_VERSION_AESDATA) => (KeyType::CcaAesData, AESDATA_KEY_SIZE),
+        (TOKEN_TYPE_CCA_INTERNAL, TOKEN_VERSION_AESCIPHER) => (
+            KeyType::CcaAesCipher,
+            BigEndian::read_u16(&data[2..4]) as usize,
+        ),
+        (TOKEN_TYPE_NON_CCA, TOKEN_VERSION_EP11_AES_WITH_HEADER) => {
+            (KeyType::Ep11Aes, BigEndian::read_u16(&data[2..4]) as usize)
+        }
+        _ => return Err(Error::InvSecureKey("Unknown key blob type")),
+    };
+    if size < 8 || size > data.len() {
+        return Err(Error::InvSecureKey("Invalid length of the key blob"));
+    }
+    Ok((ty, size))
+}
+
+/// A secure key: one key blob, or two key blobs of the same type for AES-XTS
+///
+/// A secure key is wrapped by the master key of a CCA or EP11 crypto adapter and can only be
+/// used with an APQN (adapter and domain) that has that master key set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecureKey {
+    key_type: KeyType,
+    xts: bool,
+    data: Vec<u8>,
+}
+
+impl SecureKey {
+    /// Parse the secure key in `data`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `data` does not contain one key blob, or two key
+    /// blobs of the same type and size.
+    pub fn parse(data: Vec<u8>) -> Result<Self> {
+        let (key_type, size) = blob_header(&data)?;
+        let xts = match data.len() {
+            l if l == size => false,
+            l if l == 2 * size => {
+                if blob_header(&data[size..])? != (key_type, size) {
+                    return Err(Error::InvSecureKey(
+                        "The key blobs of the XTS key differ in type or size",
+                    ));
+                }
+                true
+            }
+            _ => return Err(Error::InvSecureKey("Invalid length of the secure key")),
+        };
+        Ok(Self {
+            key_type,
+            xts,
+            data,
+        })
+    }
+
+    pub(super) fn from_blobs(key_type: KeyType, blobs: Vec<Vec<u8>>) -> Self {
+        Self {
+            key_type,
+            xts: blobs.len() == 2,
+            data: blobs.concat(),
+        }
+    }
+
+    /// Type of the key blobs
+    pub fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    /// The secure key consists of two key blobs for AES-XTS
+    pub fn is_xts(&self) -> bool {
+        self.xts
+    }
+
+    /// The key blobs, one or two for AES-XTS
+    pub fn blobs(&self) -> impl Iterator<Item = &[u8]> {
+        let n = if self.xts { 2 } else { 1 };
+        self.data.chunks(self.data.len() / n)
+    }
+
+    /// Replace the key blobs with `blobs` of the same size, e.g. after re-enciphering.
+    pub fn set_blobs(&mut self, blobs: &[Vec<u8>]) -> Result<()> {
+        let new = blobs.concat();
+        if blobs.len() != self.blobs().count() || new.len() != self.data.len() {
+            return Err(Error::InvSecureKey("The new key blobs differ in size"));
+        }
+        self.data = new;
+        Ok(())
+    }
+
+    /// Size of the AES key in bits, if stored in the key blob
+    pub fn bits(&self) -> Option<u32> {
+        let bits = match self.key_type {
+            KeyType::CcaAesData => BigEndian::read_u16(&self.data[56..58]),
+            KeyType::Ep11Aes => BigEndian::read_u16(&self.data[6..8]),
+            KeyType::CcaAesCipher => return None,
+        };
+        match bits {
+            0 => None,
+            b => Some(b as u32),
+        }
+    }
+
+    /// Master key verification pattern (MKVP) of the master key that wraps the first key blob
+    ///
+    /// 8 bytes for CCA keys, 16 bytes for EP11 keys.
+    pub fn mkvp(&self) -> &[u8] {
+        match self.key_type {
+            KeyType::CcaAesData => &self.data[8..16],
+            KeyType::CcaAesCipher => &self.data[10..18],
+            KeyType::Ep11Aes => &self.data[48..64],
+        }
+    }
+
+    /// The key blobs as written to a key file
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// A protected key: an AES key wrapped by the wrapping key of the firmware of this system
+///
+/// Protected keys are only valid until the next reboot of this system.
+#[derive(Debug)]
+pub struct ProtectedKey {
+    pub(super) keytype: u32,
+    pub(super) key: Confidential<Vec<u8>>,
+}
+
+impl ProtectedKey {
+    /// Size of the AES key in bits
+    pub fn bits(&self) -> Option<u32> {
+        match self.keytype {
+            ffi::PKEY_KEYTYPE_AES_128 => Some(128),
+            ffi::PKEY_KEYTYPE_AES_192 => Some(192),
+            ffi::PKEY_KEYTYPE_AES_256 => Some(256),
+            _ => None,
+        }
+    }
+
+    /// The protected-key token of this key
+    ///
+    /// The token can be used as key of the `paes` ciphers, e.g. by dm-crypt.
+    pub fn token(&self) -> Confidential<Vec<u8>> {
+        let key = self.key.value();
+        let mut token = vec![0u8; 16 + ffi::MAXPROTKEYSIZE];
+        token[0] = TOKEN_TYPE_NON_CCA;
+        token[4] = TOKEN_VERSION_PROTECTED_KEY;
+        BigEndian::write_u32(&mut token[8..12], self.keytype);
+        BigEndian::write_u32(&mut token[12..16], key.len() as u32);
+        token[16..16 + key.len()].copy_from_slice(key);
+        Confidential::new(token)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn aesdata(mkvp: u8) -> Vec<u8> {
+        let mut blob = vec![0u8; AESDATA_KEY_SIZE];
+        blob[0] = TOKEN_TYPE_CCA_INTERNAL;
+        blob[4] = TOKEN_VERSION_AESDATA;
+        blob[8..16].fill(mkvp);
+        blob[56..58].copy_from_slice(&256u16.to_be_bytes());
+        blob
+    }
+
+    fn ep11() -> Vec<u8> {
+        let mut blob = vec![0u8; EP11_AES_KEY_SIZE];
+        blob[2..4].copy_from_slice(&(EP11_AES_KEY_SIZE as u16).to_be_bytes());
+        blob[4] = TOKEN_VERSION_EP11_AES_WITH_HEADER;
+        blob[48..64].fill(0xee);
+        blob
+    }
+
+    #[test]
+    fn parse() {
+        let key = SecureKey::parse(aesdata(0xaa)).unwrap();
+        assert_eq!(key.key_type(), KeyType::CcaAesData);
+        assert!(!key.is_xts());
+        assert_eq!(key.bits(), Some(256));
+        assert_eq!(key.mkvp(), [0xaa; 8]);
+
+        let key = SecureKey::parse([aesdata(0xaa), aesdata(0xaa)].concat()).unwrap();
+        assert!(key.is_xts());
+        assert_eq!(key.blobs().count(), 2);
+
+        let key = SecureKey::parse(ep11()).unwrap();
+        assert_eq!(key.key_type(), KeyType::Ep11Aes);
+        assert_eq!(key.bits(), None);
+        assert_eq!(key.mkvp(), [0xee; 16]);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let mut cipher = vec![0u8; AESCIPHER_KEY_SIZE];
+        cipher[0] = TOKEN_TYPE_CCA_INTERNAL;
+        cipher[2..4].copy_from_slice(&(AESCIPHER_KEY_SIZE as u16).to_be_bytes());
+        cipher[4] = TOKEN_VERSION_AESCIPHER;
+        for data in [
+            vec![],
+            vec![0u8; 64],
+            aesdata(0)[..32].to_vec(),
+            [aesdata(0), vec![0]].concat(),
+            [aesdata(0), aesdata(0), aesdata(0)].concat(),
+            [cipher.clone(), aesdata(0), aesdata(0)].concat(),
+        ] {
+            assert!(SecureKey::parse(data).is_err());
+        }
+        assert!(SecureKey::parse(cipher).is_ok());
+    }
+
+    #[test]
+    fn key_type() {
+        assert_eq!(
+            "cca-aesdata".parse::<KeyType>().unwrap(),
+            KeyType::CcaAesData
+        );
+        assert_eq!(KeyType::Ep11Aes.to_string(), "EP11-AES");
+        assert!("aes".parse::<KeyType>().is_err());
+    }
+
+    #[test]
+    fn protected_key_token() {
+        let key = ProtectedKey {
+            keytype: ffi::PKEY_KEYTYPE_AES_256,
+            key: Confidential::new(vec![0x11; 64]),
+        };
+        assert_eq!(key.bits(), Some(256));
+        let token = key.token();
+        let token = token.value();
+        assert_eq!(token.len(), 80);
+        assert_eq!(
+            token[..16],
+            [0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 64]
+        );
+        assert_eq!(token[16..], [0x11; 64]);
+    }
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Rust representation of the parts of linux/arch/s390/include/uapi/asm/pkey.h that are used
+
+#![allow(dead_code)]
+
+use std::mem::size_of;
+
+use pv_ffi::{assert_size, static_assert, uvio::iowr};
+
+pub const PKEY_IOCTL_MAGIC: u8 = b'p';
+
+pub const PKEY_TYPE_CCA_DATA: u32 = 1;
+pub const PKEY_TYPE_CCA_CIPHER: u32 = 2;
+pub const PKEY_TYPE_EP11: u32 = 3;
+pub const PKEY_TYPE_EP11_AES: u32 = 6;
+
+pub const PKEY_SIZE_AES_128: u32 = 128;
+pub const PKEY_SIZE_AES_192: u32 = 192;
+pub const PKEY_SIZE_AES_256: u32 = 256;
+pub const PKEY_SIZE_UNKNOWN: u32 = 0xffffffff;
+
+pub const PKEY_KEYTYPE_AES_128: u32 = 1;
+pub const PKEY_KEYTYPE_AES_192: u32 = 2;
+pub const PKEY_KEYTYPE_AES_256: u32 = 3;
+
+pub const PKEY_FLAGS_MATCH_CUR_MKVP: u32 = 0x00000002;
+pub const PKEY_FLAGS_MATCH_ALT_MKVP: u32 = 0x00000004;
+
+pub const MAXPROTKEYSIZE: usize = 64;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct pkey_apqn {
+    pub card: u16,
+    pub domain: u16,
+}
+assert_size!(pkey_apqn, 4);
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct pkey_genseck2 {
+    pub apqns: *const pkey_apqn,
+    pub apqn_entries: u32,
+    pub type_: u32,
+    pub size: u32,
+    pub keygenflags: u32,
+    pub key: *mut u8,
+    pub keylen: u32,
+}
+assert_size!(pkey_genseck2, 40);
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct pkey_verifykey2 {
+    pub key: *const u8,
+    pub keylen: u32,
+    pub cardnr: u16,
+    pub domain: u16,
+    pub type_: u32,
+    pub size: u32,
+    pub flags: u32,
+}
+assert_size!(pkey_verifykey2, 32);
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct pkey_apqns4key {
+    pub key: *const u8,
+    pub keylen: u32,
+    pub flags: u32,
+    pub apqns: *mut pkey_apqn,
+    pub apqn_entries: u32,
+}
+assert_size!(pkey_apqns4key, 32);
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct pkey_kblob2pkey3 {
+    pub key: *const u8,
+    pub keylen: u32,
+    pub apqns: *const pkey_apqn,
+    pub apqn_entries: u32,
+    pub pkeytype: u32,
+    pub pkeylen: u32,
+    pub pkey: *mut u8,
+}
+assert_size!(pkey_kblob2pkey3, 48);
+
+pub const PKEY_GENSECK2: u64 = iowr(PKEY_IOCTL_MAGIC, 0x11, size_of::<pkey_genseck2>());
+pub const PKEY_VERIFYKEY2: u64 = iowr(PKEY_IOCTL_MAGIC, 0x17, size_of::<pkey_verifykey2>());
+pub const PKEY_APQNS4K: u64 = iowr(PKEY_IOCTL_MAGIC, 0x1b, size_of::<pkey_apqns4key>());
+pub const PKEY_KBLOB2PROTK3: u64 = iowr(PKEY_IOCTL_MAGIC, 0x1d, size_of::<pkey_kblob2pkey3>());
+static_assert!(PKEY_VERIFYKEY2 == 0xc0207017);
@@ -0,0 +1,407 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use crate::{Error, Result};
+use log::debug;
+use std::{
+    ffi::{c_char, c_int, CString},
+    fmt::Display,
+    ptr,
+    str::FromStr,
+};
+
+/// `_LINUX_CAPABILITY_VERSION_3`
+const CAP_VERSION_3: u32 = 0x20080522;
+
+/// Names of the Linux capabilities, indexed by their number
+const CAP_NAMES: &[&str] = &[
+    "chown",
+    "dac_override",
+    "dac_read_search",
+    "fowner",
+    "fsetid",
+    "kill",
+    "setgid",
+    "setuid",
+    "setpcap",
+    "linux_immutable",
+    "net_bind_service",
+    "net_broadcast",
+    "net_admin",
+    "net_raw",
+    "ipc_lock",
+    "ipc_owner",
+    "sys_module",
+    "sys_rawio",
+    "sys_chroot",
+    "sys_ptrace",
+    "sys_pacct",
+    "sys_admin",
+    "sys_boot",
+    "sys_nice",
+    "sys_resource",
+    "sys_time",
+    "sys_tty_config",
+    "mknod",
+    "lease",
+    "audit_write",
+    "audit_control",
+    "setfcap",
+    "mac_override",
+    "mac_admin",
+    "syslog",
+    "wake_alarm",
+    "block_suspend",
+    "audit_read",
+    "perfmon",
+    "bpf",
+    "checkpoint_restore",
+];
+
+/// A Linux capability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Capability(u8);
+
+impl Capability {
+    /// `CAP_AUDIT_WRITE`: write records to the kernel audit log
+    pub const AUDIT_WRITE: Self = Self(29);
+    /// `CAP_DAC_OVERRIDE`: bypass file permission checks
+    pub const DAC_OVERRIDE: Self = Self(1);
+    /// `CAP_IPC_LOCK`: lock memory
+    pub const IPC_LOCK: Self = Self(14);
+    /// `CAP_SYS_ADMIN`: perform a range of system administration operations
+    pub const SYS_ADMIN: Self = Self(21);
+
+    /// Returns the capability number.
+    pub fn as_raw(&self) -> u8 {
+        self.0
+    }
+
+    fn mask(caps: &[Self]) -> u64 {
+        caps.iter().fold(0, |m, c| m | 1 << c.0)
+    }
+}
+
+impl FromStr for Capability {
+    type Err = Error;
+
+    /// Parse a capability name, e.g. `CAP_AUDIT_WRITE` or `audit_write`.
+    fn from_str(s: &str) -> Result<Self> {
+        let lower = s.to_ascii_lowercase();
+        let name = lower.strip_prefix("cap_").unwrap_or(&lower);
+        CAP_NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|i| Self(i as u8))
+            .ok_or_else(|| Error::UnknownCapability(s.to_string()))
+    }
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match CAP_NAMES.get(self.0 as usize) {
+            Some(name) => write!(f, "cap_{name}"),
+            None => write!(f, "cap_{}", self.0),
+        }
+    }
+}
+
+fn check(rc: c_int, msg: &'static str) -> Result<()> {
+    match rc {
+        0 => Ok(()),
+        _ => Err(Error::PrivDrop {
+            msg,
+            source: std::io::Error::last_os_error(),
+        }),
+    }
+}
+
+/// Look up a user by name or numeric ID.
+///
+/// Returns the user ID and the ID of the primary group of the user. For numeric IDs without a
+/// passwd entry the primary group is [`None`].
+///
+/// # Errors
+///
+/// This function will return an error if `user` is neither numeric nor a known user name.
+pub fn lookup_user(user: &str) -> Result<(u32, Option<u32>)> {
+    let c_user = CString::new(user).map_err(|_| Error::UnknownId {
+        kind: "user",
+        name: user.to_string(),
+    })?;
+    // SAFETY: all-zero is a valid passwd struct
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buf: Vec<c_char> = vec![0; 4096];
+    let mut res = ptr::null_mut();
+    // SAFETY: all pointers point to valid memory regions that outlive this call. The length
+    // matches the buffer.
+    unsafe {
+        libc::getpwnam_r(
+            c_user.as_ptr(),
+            &mut pwd,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut res,
+        )
+    };
+    if !res.is_null() {
+        return Ok((pwd.pw_uid, Some(pwd.pw_gid)));
+    }
+    match user.parse() {
+        Ok(uid) => Ok((uid, None)),
+        Err(_) => Err(Error::UnknownId {
+            kind: "user",
+            name: user.to_string(),
+        }),
+    }
+}
+
+/// Look up a group by name or numeric ID.
+///
+/// # Errors
+///
+/// This function will return an error if `group` is neither numeric nor a known group name.
+pub fn lookup_group(group: &str) -> Result<u32> {
+    let unknown = || Error::UnknownId {
+        kind: "group",
+        name: group.to_string(),
+    };
+    let c_group = CString::new(group).map_err(|_| unknown())?;
+    // SAFETY: all-zero is a valid group struct
+    let mut grp: libc::group = unsafe { std::mem::zeroed() };
+    let mut buf: Vec<c_char> = vec![0; 4096];
+    let mut res = ptr::null_mut();
+    // SAFETY: all pointers point to valid memory regions that outlive this call. The length
+    // matches the buffer.
+    unsafe {
+        libc::getgrnam_r(
+            c_group.as_ptr(),
+            &mut grp,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut res,
+        )
+    };
+    if !res.is_null() {
+        return Ok(grp.gr_gid);
+    }
+    group.parse().map_err(|_| unknown())
+}
+
+/// Irreversibly drop the privileges of the process.
+///
+/// Long-running services need root privileges only to open privileged resources like `/dev/uv`.
+/// Open those first, then drop to an unprivileged user before processing untrusted input, e.g.
+/// attestation requests or host-key documents. Already opened file descriptors stay usable.
+///
+/// Dropping privileges
+/// - sets the supplementary groups to `gid` only,
+/// - sets the real, effective, and saved group and user IDs,
+/// - keeps only the specified capabilities and removes all others from the bounding set,
+/// - sets `no_new_privs`, so that no executed program can regain privileges.
+///
+/// The process must be single-threaded, as the credentials of other threads are not changed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use s390_pv_core::privilege::{Capability, PrivDrop};
+/// # fn main() -> s390_pv_core::Result<()> {
+/// let uv = std::fs::File::open("/dev/uv")?;
+/// PrivDrop::new(65534, 65534)
+///     .keep(Capability::AUDIT_WRITE)
+///     .apply()?;
+/// // `uv` is still usable, but the process cannot open `/dev/uv` anymore
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivDrop {
+    uid: u32,
+    gid: u32,
+    keep: Vec<Capability>,
+}
+
+impl PrivDrop {
+    /// Drop to the user ID `uid` and group ID `gid`.
+    pub fn new(uid: u32, gid: u32) -> Self {
+        Self {
+            uid,
+            gid,
+            keep: Vec::new(),
+        }
+    }
+
+    /// Keep the capability `cap` after dropping privileges.
+    pub fn keep(mut self, cap: Capability) -> Self {
+        if !self.keep.contains(&cap) {
+            self.keep.push(cap);
+        }
+        self
+    }
+
+    /// Capabilities that are kept.
+    pub fn kept_capabilities(&self) -> &[Capability] {
+        &self.keep
+    }
+
+    /// Drop the privileges of the calling process.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any step fails. The process may then be in a
+    /// partially dropped state and should terminate.
+    pub fn apply(&self) -> Result<()> {
+        debug!(
+            "Dropping privileges to uid {} gid {}, keeping {:?}",
+            self.uid, self.gid, self.keep
+        );
+        let keep_mask = Capability::mask(&self.keep);
+
+        // SAFETY: prctl with integer arguments does not access memory
+        check(
+            unsafe { libc::prctl(libc::PR_SET_KEEPCAPS, 1, 0, 0, 0) },
+            "set keep-caps",
+        )?;
+        for cap in 0..64u8 {
+            if keep_mask & (1 << cap) != 0 {
+                continue;
+            }
+            // SAFETY: prctl with integer arguments does not access memory
+            let rc = unsafe { libc::prctl(libc::PR_CAPBSET_DROP, cap as libc::c_ulong, 0, 0, 0) };
+            if rc != 0 {
+                // EINVAL: the capability is not known to the kernel, all further ones neither
+                match std::io::Error::last_os_error().raw_os_error() {
+                    Some(libc::EINVAL) => break,
+                    _ => check(rc, "drop capability from bounding set")?,
+                }
+            }
+        }
+
+        // SAFETY: the pointer points to one valid gid_t
+        check(unsafe { libc::setgroups(1, &self.gid) }, "set groups")?;
+        // SAFETY: no pointers involved
+        check(
+            unsafe { libc::setresgid(self.gid, self.gid, self.gid) },
+            "set group ID",
+        )?;
+        // SAFETY: no pointers involved
+        check(
+            unsafe { libc::setresuid(self.uid, self.uid, self.uid) },
+            "set user ID",
+        )?;
+
+        self.set_caps(keep_mask)?;
+        // SAFETY: prctl with integer arguments does not access memory
+        check(
+            unsafe { libc::prctl(libc::PR_SET_KEEPCAPS, 0, 0, 0, 0) },
+            "reset keep-caps",
+        )?;
+        // SAFETY: prctl with integer arguments does not access memory
+        check(
+            unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) },
+            "set no-new-privs",
+        )?;
+
+        // Regaining root must fail now.
+        // SAFETY: no pointers involved
+        if self.uid != 0 && unsafe { libc::setuid(0) } == 0 {
+            return Err(Error::PrivDrop {
+                msg: "verify",
+                source: std::io::Error::other("regained root privileges"),
+            });
+        }
+        Ok(())
+    }
+
+    fn set_caps(&self, mask: u64) -> Result<()> {
+        #[repr(C)]
+        struct CapHeader {
+            version: u32,
+            pid: c_int,
+        }
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        struct CapData {
+            effective: u32,
+            permitted: u32,
+            inheritable: u32,
+        }
+        let hdr = CapHeader {
+            version: CAP_VERSION_3,
+            pid: 0,
+        };
+        let data = [mask as u32, (mask >> 32) as u32].map(|m| CapData {
+            effective: m,
+            permitted: m,
+            inheritable: 0,
+        });
+        // SAFETY: hdr and data are valid structs as expected by capset(2), version 3 requires two
+        // data elements.
+        let rc = unsafe { libc::syscall(libc::SYS_capset, &hdr, data.as_ptr()) };
+        check(rc as c_int, "set capabilities")?;
+        // Remove any ambient capabilities, they are not part of the kept set.
+        // SAFETY: prctl with integer arguments does not access memory
+        unsafe {
+            libc::prctl(
+                libc::PR_CAP_AMBIENT,
+                libc::PR_CAP_AMBIENT_CLEAR_ALL,
+                0,
+                0,
+                0,
+            )
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_capability() {
+        assert_eq!(
+            "CAP_AUDIT_WRITE".parse::<Capability>().unwrap(),
+            Capability::AUDIT_WRITE
+        );
+        assert_eq!(
+            "sys_admin".parse::<Capability>().unwrap(),
+            Capability::SYS_ADMIN
+        );
+        assert_eq!(Capability::IPC_LOCK.to_string(), "cap_ipc_lock");
+        assert!(matches!(
+            "cap_nonsense".parse::<Capability>(),
+            Err(Error::UnknownCapability(_))
+        ));
+    }
+
+    #[test]
+    fn capability_mask() {
+        assert_eq!(
+            Capability::mask(&[Capability::DAC_OVERRIDE, Capability::AUDIT_WRITE]),
+            (1 << 1) | (1 << 29)
+        );
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(lookup_user("root").unwrap(), (0, Some(0)));
+        assert_eq!(lookup_user("4711").unwrap().0, 4711);
+        assert_eq!(lookup_group("4711").unwrap(), 4711);
+        assert!(matches!(
+            lookup_user("no such user"),
+            Err(Error::UnknownId { kind: "user", .. })
+        ));
+        assert!(lookup_group("no such group").is_err());
+    }
+
+    #[test]
+    fn keep_dedup() {
+        let drop = PrivDrop::new(1, 1)
+            .keep(Capability::AUDIT_WRITE)
+            .keep(Capability::AUDIT_WRITE);
+        assert_eq!(drop.kept_capabilities(), &[Capability::AUDIT_WRITE]);
+    }
+}
@@ -0,0 +1,478 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use crate::{Error, Result};
+use log::{debug, warn};
+use std::{
+    ffi::{c_long, CString},
+    mem::size_of,
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd},
+        unix::ffi::OsStrExt,
+    },
+    path::{Path, PathBuf},
+};
+
+/// `AUDIT_ARCH_*` of the target, as reported to seccomp filters
+#[cfg(target_arch = "s390x")]
+const AUDIT_ARCH: Option<u32> = Some(0x8000_0016);
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: Option<u32> = Some(0xc000_003e);
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: Option<u32> = Some(0xc000_00b7);
+#[cfg(target_arch = "powerpc64")]
+const AUDIT_ARCH: Option<u32> = Some(0xc000_0015);
+#[cfg(not(any(
+    target_arch = "s390x",
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    target_arch = "powerpc64"
+)))]
+const AUDIT_ARCH: Option<u32> = None;
+
+/// System calls every sandboxed process needs: I/O on open file descriptors, the uvdevice
+/// IOCTLs, memory management, signals, time, randomness, and exiting.
+const BASE_SYSCALLS: &[c_long] = &[
+    libc::SYS_read,
+    libc::SYS_readv,
+    libc::SYS_pread64,
+    libc::SYS_write,
+    libc::SYS_writev,
+    libc::SYS_pwrite64,
+    libc::SYS_lseek,
+    libc::SYS_close,
+    libc::SYS_ioctl,
+    libc::SYS_fstat,
+    libc::SYS_newfstatat,
+    libc::SYS_statx,
+    libc::SYS_fcntl,
+    libc::SYS_mmap,
+    libc::SYS_munmap,
+    libc::SYS_mremap,
+    libc::SYS_mprotect,
+    libc::SYS_madvise,
+    libc::SYS_mlock,
+    libc::SYS_munlock,
+    libc::SYS_brk,
+    libc::SYS_futex,
+    libc::SYS_rt_sigaction,
+    libc::SYS_rt_sigprocmask,
+    libc::SYS_rt_sigreturn,
+    libc::SYS_sigaltstack,
+    libc::SYS_restart_syscall,
+    libc::SYS_sched_yield,
+    libc::SYS_sched_getaffinity,
+    libc::SYS_getrandom,
+    libc::SYS_clock_gettime,
+    libc::SYS_clock_nanosleep,
+    libc::SYS_nanosleep,
+    libc::SYS_getpid,
+    libc::SYS_gettid,
+    libc::SYS_getuid,
+    libc::SYS_exit,
+    libc::SYS_exit_group,
+];
+
+/// System calls to access the paths allowed by the Landlock ruleset
+const PATH_SYSCALLS: &[c_long] = &[
+    libc::SYS_openat,
+    libc::SYS_getdents64,
+    libc::SYS_unlinkat,
+    libc::SYS_renameat2,
+    libc::SYS_linkat,
+    libc::SYS_mkdirat,
+    libc::SYS_fsync,
+    libc::SYS_fdatasync,
+    libc::SYS_ftruncate,
+    libc::SYS_fchmod,
+    #[cfg(any(target_arch = "s390x", target_arch = "x86_64"))]
+    libc::SYS_open,
+    #[cfg(any(target_arch = "s390x", target_arch = "x86_64"))]
+    libc::SYS_rename,
+    #[cfg(any(target_arch = "s390x", target_arch = "x86_64"))]
+    libc::SYS_unlink,
+    #[cfg(any(target_arch = "s390x", target_arch = "x86_64"))]
+    libc::SYS_stat,
+    #[cfg(any(target_arch = "s390x", target_arch = "x86_64"))]
+    libc::SYS_lstat,
+];
+
+// Landlock file system access rights, see linux/landlock.h
+const LL_EXECUTE: u64 = 1 << 0;
+const LL_WRITE_FILE: u64 = 1 << 1;
+const LL_READ_FILE: u64 = 1 << 2;
+const LL_READ_DIR: u64 = 1 << 3;
+const LL_REMOVE_FILE: u64 = 1 << 5;
+const LL_MAKE_REG: u64 = 1 << 8;
+const LL_TRUNCATE: u64 = 1 << 14;
+const LL_IOCTL_DEV: u64 = 1 << 15;
+/// Rights that apply to files, all others apply to directories only
+const LL_FILE: u64 = LL_EXECUTE | LL_WRITE_FILE | LL_READ_FILE | LL_TRUNCATE | LL_IOCTL_DEV;
+
+const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1;
+const LANDLOCK_RULE_PATH_BENEATH: u32 = 1;
+
+#[repr(C)]
+struct LandlockRulesetAttr {
+    handled_access_fs: u64,
+}
+
+#[repr(C, packed)]
+struct LandlockPathBeneathAttr {
+    allowed_access: u64,
+    parent_fd: i32,
+}
+
+/// Returns all file system access rights Landlock ABI `abi` knows.
+fn ll_handled(abi: i64) -> u64 {
+    match abi {
+        ..=0 => 0,
+        1 => (1 << 13) - 1,
+        2 => (1 << 14) - 1,
+        3 | 4 => (1 << 15) - 1,
+        _ => (1 << 16) - 1,
+    }
+}
+
+/// Access to a path allowed by a [`Sandbox`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsAccess {
+    /// Read files, and list directories
+    Read,
+    /// Read, write, and truncate files; create and remove regular files in directories
+    Write,
+}
+
+impl FsAccess {
+    /// Landlock rights for a file or directory, limited to the `handled` ones.
+    fn rights(self, is_dir: bool, handled: u64) -> u64 {
+        let rights = match self {
+            Self::Read => LL_READ_FILE | LL_READ_DIR,
+            Self::Write => {
+                LL_READ_FILE
+                    | LL_READ_DIR
+                    | LL_WRITE_FILE
+                    | LL_TRUNCATE
+                    | LL_REMOVE_FILE
+                    | LL_MAKE_REG
+            }
+        };
+        match is_dir {
+            true => rights & handled,
+            false => rights & handled & LL_FILE,
+        }
+    }
+}
+
+fn check(rc: c_long, msg: &'static str) -> Result<c_long> {
+    match rc {
+        ..=-1 => Err(Error::Sandbox {
+            msg,
+            source: std::io::Error::last_os_error(),
+        }),
+        rc => Ok(rc),
+    }
+}
+
+fn bpf_stmt(code: u32, k: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt: 0,
+        jf: 0,
+        k,
+    }
+}
+
+fn bpf_jump(code: u32, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt,
+        jf,
+        k,
+    }
+}
+
+/// Build the seccomp program that allows `syscalls` and fails all others with `EPERM`.
+///
+/// System calls of a foreign architecture kill the process.
+fn seccomp_program(arch: u32, syscalls: &[c_long]) -> Vec<libc::sock_filter> {
+    const NR_OFFS: u32 = 0;
+    const ARCH_OFFS: u32 = 4;
+    const LD: u32 = libc::BPF_LD | libc::BPF_W | libc::BPF_ABS;
+    const JEQ: u32 = libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K;
+    const RET: u32 = libc::BPF_RET | libc::BPF_K;
+
+    let mut prog = vec![
+        bpf_stmt(LD, ARCH_OFFS),
+        bpf_jump(JEQ, arch, 1, 0),
+        bpf_stmt(RET, libc::SECCOMP_RET_KILL_PROCESS),
+        bpf_stmt(LD, NR_OFFS),
+    ];
+    for nr in syscalls {
+        prog.push(bpf_jump(JEQ, *nr as u32, 0, 1));
+        prog.push(bpf_stmt(RET, libc::SECCOMP_RET_ALLOW));
+    }
+    prog.push(bpf_stmt(RET, libc::SECCOMP_RET_ERRNO | libc::EPERM as u32));
+    prog
+}
+
+/// Irreversibly restrict the process before it processes untrusted input.
+///
+/// Open all required files and devices first, e.g. the uvdevice, the input, and the output, then
+/// apply the sandbox. Afterwards
+/// - only the allowed paths can be opened (Landlock), and
+/// - only the system calls for I/O on open file descriptors, IOCTLs, memory management, and
+///   exiting are allowed (seccomp). If paths are allowed, the system calls to open, create,
+///   rename, and remove files are allowed as well. All other system calls fail with `EPERM`.
+///
+/// This limits the impact of bugs in the parsers of, e.g., attestation requests, host-key
+/// documents, or dumps. If the kernel does not support Landlock, only the system calls are
+/// restricted. The sandbox also sets `no_new_privs`.
+///
+/// The process must be single-threaded, as other threads are not restricted.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use s390_pv_core::sandbox::{FsAccess, Sandbox};
+/// # fn main() -> s390_pv_core::Result<()> {
+/// let input = std::fs::File::open("request.bin")?;
+/// Sandbox::new().allow_path("/tmp/out", FsAccess::Write).apply()?;
+/// // `input` is still usable, but `/etc/passwd` cannot be opened anymore
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sandbox {
+    paths: Vec<(PathBuf, FsAccess)>,
+    syscalls: Vec<c_long>,
+}
+
+impl Sandbox {
+    /// A sandbox that allows no paths and only the base system calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow `access` to `path` and, if it is a directory, to everything beneath it.
+    pub fn allow_path<P: AsRef<Path>>(mut self, path: P, access: FsAccess) -> Self {
+        self.paths.push((path.as_ref().to_path_buf(), access));
+        self
+    }
+
+    /// Allow creating, replacing, or removing the file `path`.
+    ///
+    /// Allows [`FsAccess::Write`] to the directory of `path`, as creating files atomically
+    /// requires temporary files next to `path`.
+    pub fn allow_create<P: AsRef<Path>>(self, path: P) -> Self {
+        let dir = match path.as_ref().parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        self.allow_path(dir, FsAccess::Write)
+    }
+
+    /// Additionally allow the system call `nr`, e.g. [`libc::SYS_socket`].
+    pub fn allow_syscall(mut self, nr: c_long) -> Self {
+        if !self.syscalls.contains(&nr) {
+            self.syscalls.push(nr);
+        }
+        self
+    }
+
+    /// Returns the system calls this sandbox allows.
+    pub fn allowed_syscalls(&self) -> Vec<c_long> {
+        let mut syscalls = BASE_SYSCALLS.to_vec();
+        if !self.paths.is_empty() {
+            syscalls.extend_from_slice(PATH_SYSCALLS);
+        }
+        for nr in &self.syscalls {
+            if !syscalls.contains(nr) {
+                syscalls.push(*nr);
+            }
+        }
+        syscalls
+    }
+
+    /// Restrict the calling process.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any step fails. The process may then be in a
+    /// partially restricted state and should terminate.
+    pub fn apply(&self) -> Result<()> {
+        let arch = AUDIT_ARCH.ok_or(Error::Sandbox {
+            msg: "seccomp",
+            source: std::io::Error::from(std::io::ErrorKind::Unsupported),
+        })?;
+        // SAFETY: prctl with integer arguments does not access memory
+        check(
+            unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) }.into(),
+            "set no-new-privs",
+        )?;
+        self.landlock()?;
+
+        let mut prog = seccomp_program(arch, &self.allowed_syscalls());
+        let fprog = libc::sock_fprog {
+            len: prog.len() as u16,
+            filter: prog.as_mut_ptr(),
+        };
+        debug!("Applying a seccomp filter of {} instructions", prog.len());
+        // SAFETY: fprog points to a valid filter program that outlives this call
+        check(
+            unsafe {
+                libc::prctl(
+                    libc::PR_SET_SECCOMP,
+                    libc::SECCOMP_MODE_FILTER,
+                    &fprog as *const libc::sock_fprog,
+                )
+            }
+            .into(),
+            "seccomp",
+        )?;
+        Ok(())
+    }
+
+    fn landlock(&self) -> Result<()> {
+        // SAFETY: querying the ABI version does not access memory
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<LandlockRulesetAttr>(),
+                0,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        if abi < 0 {
+            warn!(
+                "The kernel does not support Landlock ({}). Only the system calls are restricted.",
+                std::io::Error::last_os_error()
+            );
+            return Ok(());
+        }
+        let handled = ll_handled(abi);
+        debug!(
+            "Applying a Landlock ruleset (ABI {abi}) for {:?}",
+            self.paths
+        );
+
+        let attr = LandlockRulesetAttr {
+            handled_access_fs: handled,
+        };
+        // SAFETY: attr is a valid ruleset attribute, the size matches
+        let fd = check(
+            unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_create_ruleset,
+                    &attr as *const LandlockRulesetAttr,
+                    size_of::<LandlockRulesetAttr>(),
+                    0,
+                )
+            },
+            "create Landlock ruleset",
+        )?;
+        // SAFETY: fd is a new, owned file descriptor
+        let ruleset = unsafe { OwnedFd::from_raw_fd(fd as i32) };
+
+        for (path, access) in &self.paths {
+            let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::Sandbox {
+                msg: "open allowed path",
+                source: std::io::ErrorKind::InvalidInput.into(),
+            })?;
+            // SAFETY: c_path is a valid C string
+            let parent = check(
+                unsafe { libc::open(c_path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) }.into(),
+                "open allowed path",
+            )?;
+            // SAFETY: parent is a new, owned file descriptor
+            let parent = unsafe { OwnedFd::from_raw_fd(parent as i32) };
+            let rule = LandlockPathBeneathAttr {
+                allowed_access: access.rights(path.is_dir(), handled),
+                parent_fd: parent.as_raw_fd(),
+            };
+            // SAFETY: rule is a valid path-beneath attribute
+            check(
+                unsafe {
+                    libc::syscall(
+                        libc::SYS_landlock_add_rule,
+                        ruleset.as_raw_fd(),
+                        LANDLOCK_RULE_PATH_BENEATH,
+                        &rule as *const LandlockPathBeneathAttr,
+                        0,
+                    )
+                },
+                "add Landlock rule",
+            )?;
+        }
+        // SAFETY: no pointers involved
+        check(
+            unsafe { libc::syscall(libc::SYS_landlock_restrict_self, ruleset.as_raw_fd(), 0) },
+            "restrict with Landlock",
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rights() {
+        let handled = ll_handled(5);
+        assert_eq!(
+            FsAccess::Read.rights(true, handled),
+            LL_READ_FILE | LL_READ_DIR
+        );
+        assert_eq!(FsAccess::Read.rights(false, handled), LL_READ_FILE);
+        assert_eq!(
+            FsAccess::Write.rights(false, handled),
+            LL_READ_FILE | LL_WRITE_FILE | LL_TRUNCATE
+        );
+        // truncate is only known since ABI 3
+        assert_eq!(
+            FsAccess::Write.rights(false, ll_handled(2)),
+            LL_READ_FILE | LL_WRITE_FILE
+        );
+        assert_eq!(ll_handled(0), 0);
+    }
+
+    #[test]
+    fn allowed_syscalls() {
+        let sandbox = Sandbox::new().allow_syscall(libc::SYS_read);
+        assert_eq!(sandbox.allowed_syscalls(), BASE_SYSCALLS);
+        assert!(!sandbox.allowed_syscalls().contains(&libc::SYS_openat));
+
+        let sandbox = sandbox
+            .allow_path("/tmp", FsAccess::Read)
+            .allow_syscall(libc::SYS_socket);
+        let syscalls = sandbox.allowed_syscalls();
+        assert!(syscalls.contains(&libc::SYS_openat));
+        assert_eq!(syscalls.last(), Some(&libc::SYS_socket));
+    }
+
+    #[test]
+    fn allow_create() {
+        let sandbox = Sandbox::new()
+            .allow_create("/tmp/out.bin")
+            .allow_create("out.bin");
+        assert_eq!(
+            sandbox.paths,
+            [
+                (PathBuf::from("/tmp"), FsAccess::Write),
+                (PathBuf::from("."), FsAccess::Write)
+            ]
+        );
+    }
+
+    #[test]
+    fn seccomp_program() {
+        let prog = super::seccomp_program(0x42, &[libc::SYS_read, libc::SYS_write]);
+        assert_eq!(prog.len(), 4 + 2 * 2 + 1);
+        assert_eq!(prog[1].k, 0x42);
+        assert_eq!(prog[4].k, libc::SYS_read as u32);
+        assert_eq!(prog[5].k, libc::SECCOMP_RET_ALLOW);
+        assert_eq!(prog[8].k, libc::SECCOMP_RET_ERRNO | libc::EPERM as u32);
+    }
+}
@@ -7,125 +7,12 @@ use std::{
     path::Path,
 };
 
-use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, U64};
-
 use crate::{
+    confidential::SecretBytes,
     macros::{bail_spec, file_error},
     Error, FileAccessErrorType, Result,
 };
 
-/// Trait that describes bitflags, represented by `T`.
-pub trait Flags<T>: From<T> + for<'a> From<&'a T> {
-    /// Set the specified bit to one.
-    /// # Panics
-    /// Panics if bit is >= 64
-    fn set_bit(&mut self, bit: u8);
-    /// Set the specified bit to zero.
-    /// # Panics
-    /// Panics if bit is >= 64
-    fn unset_bit(&mut self, bit: u8);
-    /// Test if the specified bit is set.
-    /// # Panics
-    /// Panics if bit is >= 64
-    fn is_set(&self, bit: u8) -> bool;
-}
-
-/// Bitflags in MSB0 ordering
-///
-/// Wraps an u64 to set/get individual bits
-#[repr(C)]
-#[derive(Debug, Clone, Copy, Default, IntoBytes, FromBytes, Eq, PartialEq, Immutable)]
-pub struct Msb0Flags64(U64<BigEndian>);
-impl Flags<u64> for Msb0Flags64 {
-    #[track_caller]
-    fn set_bit(&mut self, bit: u8) {
-        assert!(bit < 64, "Flag bit set to greater than 63");
-        let mut v = self.0.get();
-        v |= 1 << (63 - bit);
-        self.0.set(v)
-    }
-
-    #[track_caller]
-    fn unset_bit(&mut self, bit: u8) {
-        assert!(bit < 64, "Flag bit set to greater than 63");
-        let mut v = self.0.get();
-        v &= !(1 << (63 - bit));
-        self.0.set(v)
-    }
-
-    #[track_caller]
-    fn is_set(&self, bit: u8) -> bool {
-        assert!(bit < 64, "Flag bit set to greater than 63");
-        self.0.get() & (1 << (63 - bit)) > 0
-    }
-}
-
-impl From<u64> for Msb0Flags64 {
-    fn from(value: u64) -> Self {
-        Self(value.into())
-    }
-}
-
-impl From<&u64> for Msb0Flags64 {
-    fn from(value: &u64) -> Self {
-        (*value).into()
-    }
-}
-
-impl From<Msb0Flags64> for u64 {
-    fn from(value: Msb0Flags64) -> Self {
-        value.0.into()
-    }
-}
-
-/// Bitflags in LSB0 ordering
-///
-/// Wraps an u64 to set/get individual bits
-#[repr(C)]
-#[derive(Debug, Clone, Copy, Default, IntoBytes, FromBytes, Immutable)]
-pub struct Lsb0Flags64(U64<BigEndian>);
-impl Flags<u64> for Lsb0Flags64 {
-    #[track_caller]
-    fn set_bit(&mut self, bit: u8) {
-        assert!(bit < 64, "Flag bit set to greater than 63");
-        let mut v = self.0.get();
-        v |= 1 << bit;
-        self.0.set(v)
-    }
-
-    #[track_caller]
-    fn unset_bit(&mut self, bit: u8) {
-        assert!(bit < 64, "Flag bit set to greater than 63");
-        let mut v = self.0.get();
-        v &= !(1 << bit);
-        self.0.set(v)
-    }
-
-    #[track_caller]
-    fn is_set(&self, bit: u8) -> bool {
-        assert!(bit < 64, "Flag bit set to greater than 63");
-        self.0.get() & (1 << bit) > 0
-    }
-}
-
-impl From<u64> for Lsb0Flags64 {
-    fn from(value: u64) -> Self {
-        Self(value.into())
-    }
-}
-
-impl From<&u64> for Lsb0Flags64 {
-    fn from(value: &u64) -> Self {
-        (*value).into()
-    }
-}
-
-impl From<Lsb0Flags64> for u64 {
-    fn from(value: Lsb0Flags64) -> Self {
-        value.0.into()
-    }
-}
-
 /// Tries to convert a BE hex string into a 128 unsigned integer
 /// The hexstring must contain 32chars of hexdigits
 ///
@@ -285,6 +172,24 @@ pub fn read_file<P: AsRef<Path>>(path: P, ctx: &str) -> Result<Vec<u8>> {
     std::fs::read(&path).map_err(|e| file_error!(Read, ctx, path, e))
 }
 
+/// Read sensitive content from a file and add context in case of an error
+///
+/// The content is overwritten in memory when the result is dropped, see [`SecretBytes`].
+///
+/// * `path` - Path to file
+/// * `ctx` - Error context string in case of an error
+///
+/// # Errors
+/// Passes through any kind of error opening or reading the file produces
+pub fn read_secret_file<P: AsRef<Path>>(path: P, ctx: &str) -> Result<SecretBytes> {
+    let mut f = File::open(&path).map_err(|e| Error::FileAccess {
+        ty: FileAccessErrorType::Open,
+        path: path.as_ref().to_path_buf(),
+        source: e,
+    })?;
+    SecretBytes::read_from(&mut f).map_err(|e| file_error!(Read, ctx, path, e))
+}
+
 /// Reads all content from a [`std::io::Read`] and add context in case of an error
 ///
 /// * `path` - Path to file
@@ -384,6 +289,66 @@ pub fn decode_hex<S: AsRef<str>>(s: S) -> Result<Vec<u8>> {
         .collect()
 }
 
+/// Converts the (standard, padded) base64 string into a byte vector.
+///
+/// # Errors
+///
+/// Raises an error if a character outside the base64 alphabet was found or the padding is
+/// invalid.
+pub fn decode_base64<S: AsRef<str>>(s: S) -> Result<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+            b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let b64 = s.as_ref().as_bytes();
+    if b64.len() % 4 != 0 {
+        return Err(Error::InvBase64String);
+    }
+    let mut bytes = Vec::with_capacity(b64.len() / 4 * 3);
+    for (n, chunk) in b64.chunks(4).enumerate() {
+        let last = n == b64.len() / 4 - 1;
+        let pad = match chunk {
+            [.., b'=', b'='] if last => 2,
+            [.., b'='] if last => 1,
+            _ => 0,
+        };
+        let quad = chunk[..4 - pad]
+            .iter()
+            .try_fold(0u32, |acc, c| value(*c).map(|v| acc << 6 | v))
+            .ok_or(Error::InvBase64String)?
+            << (6 * pad);
+        bytes.extend_from_slice(&quad.to_be_bytes()[1..4 - pad]);
+    }
+    Ok(bytes)
+}
+
+/// Converts the bytes into a (standard, padded) base64 string.
+pub fn encode_base64<S: AsRef<[u8]>>(s: S) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let bytes = s.as_ref();
+    let mut b64 = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let mut buf = [0u8; 4];
+        buf[1..=chunk.len()].copy_from_slice(chunk);
+        let triple = u32::from_be_bytes(buf);
+        for n in 0..4 {
+            match n <= chunk.len() {
+                true => b64.push(ALPHABET[(triple >> (18 - 6 * n) & 0x3f) as usize] as char),
+                false => b64.push('='),
+            }
+        }
+    }
+    b64
+}
+
 /// Converts the hexstring into a byte vector.
 ///
 /// Stops if the end or until a non hex chat is found
@@ -408,110 +373,14 @@ pub fn parse_hex(hex_str: &str) -> Vec<u8> {
 /// for non-s390-architectures:
 /// Returns always false
 /// A non-s390 system cannot be a secure execution guest.
-#[allow(unreachable_code)]
 pub fn pv_guest_bit_set() -> bool {
-    #[cfg(not(target_arch = "s390x"))]
-    return false;
-    // s390 branch
-    let v = std::fs::read("/sys/firmware/uv/prot_virt_guest").unwrap_or_else(|_| vec![0]);
-    let v: u8 = String::from_utf8_lossy(&v[..1]).parse().unwrap_or(0);
-    v == 1
+    crate::execenv::is_se_guest()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn msb_flags() {
-        let v = 17;
-        let v_flag: Msb0Flags64 = v.into();
-        assert_eq!(v, v_flag.0.get());
-
-        let mut v: Msb0Flags64 = 4.into();
-        v.unset_bit(61);
-        assert_eq!(v.0.get(), 0);
-        v.set_bit(61);
-        assert_eq!(4, v.0.get());
-
-        let mut v = Msb0Flags64::default();
-        v.set_bit(0);
-        assert_eq!(&[0x80, 0, 0, 0, 0, 0, 0, 0], v.as_bytes());
-        v.set_bit(0);
-        assert_eq!(&[0x80, 0, 0, 0, 0, 0, 0, 0], v.as_bytes());
-        v.set_bit(1);
-        assert_eq!(&[0xc0, 0, 0, 0, 0, 0, 0, 0], v.as_bytes());
-        v.set_bit(2);
-        assert_eq!(&[0xe0, 0, 0, 0, 0, 0, 0, 0], v.as_bytes());
-        v.set_bit(3);
-        assert_eq!(&[0xf0, 0, 0, 0, 0, 0, 0, 0], v.as_bytes());
-
-        v.unset_bit(3);
-        assert_eq!(&[0xe0, 0, 0, 0, 0, 0, 0, 0], v.as_bytes());
-        v.unset_bit(3);
-        assert_eq!(&[0xe0, 0, 0, 0, 0, 0, 0, 0], v.as_bytes());
-
-        v.set_bit(16);
-        assert_eq!(&[0xe0, 0, 0x80, 0, 0, 0, 0, 0], v.as_bytes());
-    }
-
-    #[test]
-    #[should_panic]
-    fn msb_flags_set_panic() {
-        Msb0Flags64::default().set_bit(64)
-    }
-
-    #[test]
-    #[should_panic]
-    fn msb_flags_unset_panic() {
-        Msb0Flags64::default().unset_bit(64)
-    }
-
-    #[test]
-    fn lsb_flags() {
-        let v = 17;
-        let v_flag: Lsb0Flags64 = v.into();
-        assert_eq!(v, v_flag.0.get());
-
-        let mut v: Lsb0Flags64 = 4.into();
-        v.unset_bit(2);
-        assert_eq!(v.0.get(), 0);
-        v.set_bit(2);
-        assert_eq!(4, v.0.get());
-
-        let mut v = Lsb0Flags64::default();
-        v.set_bit(0);
-        assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 1], v.as_bytes());
-        v.set_bit(0);
-        assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 1], v.as_bytes());
-        v.set_bit(1);
-        assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 3], v.as_bytes());
-        v.set_bit(2);
-        assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 7], v.as_bytes());
-        v.set_bit(3);
-        assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 0xf], v.as_bytes());
-
-        v.unset_bit(3);
-        assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 7], v.as_bytes());
-        v.unset_bit(3);
-        assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 7], v.as_bytes());
-
-        v.set_bit(16);
-        assert_eq!(&[0, 0, 0, 0, 0, 1, 0, 7], v.as_bytes());
-    }
-
-    #[test]
-    #[should_panic]
-    fn lsb_flags_set_panic() {
-        Lsb0Flags64::default().set_bit(64)
-    }
-
-    #[test]
-    #[should_panic]
-    fn lsb_flags_unset_panic() {
-        Lsb0Flags64::default().unset_bit(64)
-    }
-
     #[test]
     fn encode_hex() {
         let arr = [0x12, 0x34, 0x56, 0xac, 0xbe, 0xf0];
@@ -534,6 +403,33 @@ mod tests {
         assert_eq!(super::parse_hex(s), exp);
     }
 
+    #[test]
+    fn decode_base64() {
+        assert_eq!(super::decode_base64("").unwrap(), []);
+        assert_eq!(super::decode_base64("Zg==").unwrap(), b"f");
+        assert_eq!(super::decode_base64("Zm8=").unwrap(), b"fo");
+        assert_eq!(super::decode_base64("Zm9v").unwrap(), b"foo");
+        assert_eq!(super::decode_base64("Zm9vYmFy").unwrap(), b"foobar");
+        assert_eq!(super::decode_base64("+/+/").unwrap(), [0xfb, 0xff, 0xbf]);
+
+        for inv in ["Zg", "Zg=", "Z===", "Zg==Zg==", "Zm9v!A==", "Zm 9"] {
+            assert!(
+                matches!(super::decode_base64(inv), Err(Error::InvBase64String)),
+                "{inv}"
+            );
+        }
+    }
+
+    #[test]
+    fn encode_base64() {
+        assert_eq!(super::encode_base64(""), "");
+        assert_eq!(super::encode_base64("f"), "Zg==");
+        assert_eq!(super::encode_base64("fo"), "Zm8=");
+        assert_eq!(super::encode_base64("foo"), "Zm9v");
+        assert_eq!(super::encode_base64("foobar"), "Zm9vYmFy");
+        assert_eq!(super::encode_base64([0xfb, 0xff, 0xbf]), "+/+/");
+    }
+
     #[test]
     fn decode_hex() {
         let s = "123456acbef0";
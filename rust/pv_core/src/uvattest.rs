@@ -2,7 +2,12 @@
 //
 // Copyright IBM Corp. 2024
 
-use crate::{request::MagicValue, Error};
+mod user_data;
+
+pub use user_data::UserData;
+
+use crate::{misc::Flags, request::MagicValue, uv::UvQueryInfo, Error};
+use std::fmt::Display;
 use zerocopy::U32;
 use zerocopy::{BigEndian, ByteOrder};
 
@@ -26,7 +31,11 @@ impl MagicValue<8> for AttestationMagic {
 }
 
 /// Identifier for the used measurement algorithm
+///
+/// New Ultravisor generations may introduce further algorithms. Check the algorithms an
+/// Ultravisor supports with [`AttestationMeasAlg::uv_supported`].
 #[repr(u32)]
+#[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AttestationMeasAlg {
     /// Use HMAC with SHA512 as measurement algorithm
@@ -34,23 +43,65 @@ pub enum AttestationMeasAlg {
 }
 
 impl AttestationMeasAlg {
+    /// All measurement algorithms known to this crate
+    pub const ALL: [Self; 1] = [Self::HmacSha512];
+
     /// Report the expected size for a given measurement algorithm
     pub const fn exp_size(&self) -> u32 {
         match self {
             Self::HmacSha512 => 64,
         }
     }
+
+    /// Returns the measurement algorithm identifier (MAI).
+    pub const fn id(&self) -> u32 {
+        *self as u32
+    }
+
+    /// Returns the name of the measurement algorithm.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::HmacSha512 => "HMAC-SHA512",
+        }
+    }
+
+    /// Returns the measurement algorithm with the identifier `mai`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the identifier is unknown.
+    pub fn from_id(mai: u32) -> Result<Self, Error> {
+        Self::ALL
+            .into_iter()
+            .find(|alg| alg.id() == mai)
+            .ok_or(Error::BinArcbInvAlgorithm(mai))
+    }
+
+    /// Returns the measurement algorithms the Ultravisor supports.
+    ///
+    /// Algorithms the Ultravisor reports but this crate does not know are skipped. Returns
+    /// [`None`] if the kernel does not provide this information.
+    pub fn uv_supported(query: &UvQueryInfo) -> Option<Vec<Self>> {
+        query.supp_att_mai().map(|mais| {
+            Self::ALL
+                .into_iter()
+                .filter(|alg| alg.id() < 64 && mais.is_set(alg.id() as u8))
+                .collect()
+        })
+    }
+}
+
+impl Display for AttestationMeasAlg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 impl<E: ByteOrder> TryFrom<U32<E>> for AttestationMeasAlg {
     type Error = Error;
 
     fn try_from(value: U32<E>) -> Result<Self, Self::Error> {
-        if value.get() == Self::HmacSha512 as u32 {
-            Ok(Self::HmacSha512)
-        } else {
-            Err(Error::BinArcbInvAlgorithm(value.get()))
-        }
+        Self::from_id(value.get())
     }
 }
 
@@ -59,3 +110,25 @@ impl From<AttestationMeasAlg> for U32<BigEndian> {
         (value as u32).into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mai() {
+        assert_eq!(
+            AttestationMeasAlg::from_id(1).unwrap(),
+            AttestationMeasAlg::HmacSha512
+        );
+        assert!(matches!(
+            AttestationMeasAlg::from_id(2),
+            Err(Error::BinArcbInvAlgorithm(2))
+        ));
+        assert_eq!(AttestationMeasAlg::HmacSha512.to_string(), "HMAC-SHA512");
+        assert_eq!(
+            AttestationMeasAlg::uv_supported(&UvQueryInfo::default()),
+            None
+        );
+    }
+}
@@ -0,0 +1,209 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::fmt::{Debug, Display};
+
+use crate::{
+    misc::{decode_base64, decode_hex, encode_hex},
+    secret::UserDataType,
+    Error, Result,
+};
+
+/// User data of an attestation measurement
+///
+/// Up to [`UserData::MAX_SIZE`] bytes of arbitrary data that is included in the attestation
+/// measurement. The size is checked during construction, so a [`UserData`] always fits into the
+/// Attestation UVC and is never truncated.
+///
+/// ```rust
+/// # use s390_pv_core::attest::UserData;
+/// # fn main() -> s390_pv_core::Result<()> {
+/// let nonce = UserData::from_hex("c0ffee")?;
+/// assert_eq!(nonce.as_bytes(), [0xc0, 0xff, 0xee]);
+/// let text = UserData::try_from("hello")?;
+/// assert_eq!(text, UserData::from_base64("aGVsbG8=")?);
+/// assert!(UserData::new(vec![0; 257]).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct UserData(Vec<u8>);
+
+impl UserData {
+    /// Maximum size of the user data in bytes
+    pub const MAX_SIZE: usize = 256;
+
+    /// Creates user data from raw bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `data` is larger than [`UserData::MAX_SIZE`].
+    pub fn new(data: Vec<u8>) -> Result<Self> {
+        if data.len() > Self::MAX_SIZE {
+            return Err(Error::AttDataSizeLarge {
+                field: "User data",
+                max_size: Self::MAX_SIZE as u32,
+            });
+        }
+        Ok(Self(data))
+    }
+
+    /// Creates user data from a hex string.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `hex` is no valid hex string or the decoded data is
+    /// larger than [`UserData::MAX_SIZE`].
+    pub fn from_hex<S: AsRef<str>>(hex: S) -> Result<Self> {
+        Self::new(decode_hex(hex)?)
+    }
+
+    /// Creates user data from a (standard, padded) base64 string.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `b64` is no valid base64 string or the decoded data
+    /// is larger than [`UserData::MAX_SIZE`].
+    pub fn from_base64<S: AsRef<str>>(b64: S) -> Result<Self> {
+        Self::new(decode_base64(b64)?)
+    }
+
+    /// Returns the user data as byte slice.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the size of the user data in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the user data is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if the user data also fits into an add-secret request with user data of
+    /// type `kind`.
+    ///
+    /// Signed add-secret user data is smaller than attestation user data, e.g. RSA 3072 signed
+    /// user data holds only 128 bytes.
+    pub fn fits(&self, kind: UserDataType) -> bool {
+        self.len() <= kind.max()
+    }
+
+    /// Returns the user data in the layout of the Attestation UVC: the zero-padded data and its
+    /// size.
+    pub(crate) fn to_uvio(&self) -> ([u8; Self::MAX_SIZE], u16) {
+        let mut buf = [0; Self::MAX_SIZE];
+        buf[..self.len()].copy_from_slice(&self.0);
+        // MAX_SIZE fits into u16
+        (buf, self.len() as u16)
+    }
+}
+
+impl TryFrom<Vec<u8>> for UserData {
+    type Error = Error;
+
+    fn try_from(value: Vec<u8>) -> Result<Self> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<&[u8]> for UserData {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        Self::new(value.to_vec())
+    }
+}
+
+impl TryFrom<&str> for UserData {
+    type Error = Error;
+
+    /// Uses the UTF-8 bytes of `value` as user data.
+    fn try_from(value: &str) -> Result<Self> {
+        Self::new(value.as_bytes().to_vec())
+    }
+}
+
+impl From<UserData> for Vec<u8> {
+    fn from(value: UserData) -> Self {
+        value.0
+    }
+}
+
+impl AsRef<[u8]> for UserData {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Debug for UserData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("UserData")
+            .field(&encode_hex(&self.0))
+            .finish()
+    }
+}
+
+impl Display for UserData {
+    /// Formats the user data as hex string.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", encode_hex(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn size() {
+        assert_eq!(UserData::new(vec![1; 256]).unwrap().len(), 256);
+        assert!(matches!(
+            UserData::new(vec![1; 257]),
+            Err(Error::AttDataSizeLarge { max_size: 256, .. })
+        ));
+        assert!(UserData::from_hex("00".repeat(257)).is_err());
+        assert!(UserData::try_from("a".repeat(257).as_str()).is_err());
+        assert!(UserData::default().is_empty());
+    }
+
+    #[test]
+    fn encodings() {
+        let exp = UserData::new(b"user".to_vec()).unwrap();
+        assert_eq!(UserData::from_hex("75736572").unwrap(), exp);
+        assert_eq!(UserData::from_base64("dXNlcg==").unwrap(), exp);
+        assert_eq!(UserData::try_from("user").unwrap(), exp);
+        assert_eq!(UserData::try_from(&b"user"[..]).unwrap(), exp);
+        assert_eq!(exp.to_string(), "75736572");
+        assert!(matches!(
+            UserData::from_hex("7573657"),
+            Err(Error::InvHexStringSize(7))
+        ));
+        assert!(matches!(
+            UserData::from_base64("dXNlcg"),
+            Err(Error::InvBase64String)
+        ));
+    }
+
+    #[test]
+    fn uvio() {
+        let (buf, len) = UserData::try_from("abc").unwrap().to_uvio();
+        assert_eq!(len, 3);
+        assert_eq!(&buf[..4], b"abc\0");
+        assert!(buf[3..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn fits() {
+        let data = UserData::new(vec![0; 200]).unwrap();
+        assert!(data.fits(UserDataType::Unsigned));
+        assert!(data.fits(UserDataType::SgnEcSECP521R1));
+        assert!(data.fits(UserDataType::SgnRsa2048));
+        assert!(!data.fits(UserDataType::SgnRsa3072));
+        assert!(!data.fits(UserDataType::Null));
+    }
+}
@@ -3,36 +3,85 @@
 // Copyright IBM Corp. 2023
 
 #![allow(non_camel_case_types)]
-use crate::FileAccessErrorType;
-use crate::{Error, Result};
-use log::debug;
+#[cfg(feature = "uvdevice")]
+use crate::{Error, FileAccessErrorType, Result};
+#[cfg(feature = "uvdevice")]
+use log::{debug, warn};
+#[cfg(feature = "uvdevice")]
 use std::{
     convert::TryInto,
+    env,
     ffi::c_ulong,
     fs::File,
-    os::unix::prelude::{AsRawFd, RawFd},
+    os::{
+        fd::{FromRawFd, OwnedFd},
+        unix::{
+            fs::FileTypeExt,
+            prelude::{AsRawFd, RawFd},
+        },
+    },
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock, RwLockReadGuard,
+    },
+    time::{Duration, Instant},
 };
 
-#[cfg(not(test))]
+#[cfg(all(feature = "uvdevice", not(test)))]
 use ::libc::ioctl;
-#[cfg(test)]
+#[cfg(all(feature = "uvdevice", test))]
 use test::mock_libc::ioctl;
 
+#[cfg(feature = "uvdevice")]
+mod batch;
 /// Contains the rust representation of asm/uvdevice.h
-/// from kernel version: 6.5 verify
-mod ffi;
+use pv_ffi::uvio as ffi;
+#[cfg(all(feature = "uvdevice", any(feature = "record-ioctl", test)))]
+mod fixture;
+#[cfg(feature = "uvdevice")]
 mod info;
+#[cfg(feature = "uvdevice")]
+mod metrics;
+#[cfg(feature = "uvdevice")]
+mod pool;
+#[cfg(feature = "uvdevice")]
+mod prepared;
+#[cfg(feature = "uvdevice")]
+mod retry;
+#[cfg(all(feature = "uvdevice", feature = "uv-sim"))]
+mod sim;
+#[cfg(feature = "uvdevice")]
 mod test;
+#[cfg(all(feature = "uvdevice", feature = "trace-ioctl"))]
+mod trace;
 pub(crate) use ffi::uv_ioctl;
 pub mod attest;
+pub mod query;
 pub mod retr_secret;
 pub mod secret;
 pub mod secret_list;
+pub mod store_info;
 
+#[cfg(feature = "uvdevice")]
+pub use batch::{BatchCmd, BatchPolicy};
+#[cfg(all(feature = "uvdevice", feature = "record-ioctl"))]
+pub use fixture::{IoctlExchange, IoctlRecorder};
+#[cfg(feature = "uvdevice")]
 pub use info::UvDeviceInfo;
-
-/// User data for the attestation UVC
-pub type AttestationUserData = [u8; ffi::UVIO_ATT_USER_DATA_LEN];
+#[cfg(feature = "uvdevice")]
+pub use metrics::{CallOutcome, UvCall, UvMetrics, UvObserver};
+#[cfg(feature = "uvdevice")]
+pub use pool::{PooledUvDevice, UvDevicePool};
+#[cfg(feature = "uvdevice")]
+pub use prepared::PreparedCmd;
+#[cfg(feature = "uvdevice")]
+use query::UvQueryInfo;
+#[cfg(feature = "uvdevice")]
+pub use retry::RetryPolicy;
+#[cfg(all(feature = "uvdevice", feature = "uv-sim"))]
+pub use sim::UvSimulator;
+#[cfg(all(feature = "uvdevice", feature = "trace-ioctl"))]
+pub use trace::IoctlTrace;
 
 /// Configuration Unique Id of the Secure Execution guest
 pub type ConfigUid = [u8; ffi::UVIO_ATT_UID_LEN];
@@ -42,6 +91,7 @@ pub type ConfigUid = [u8; ffi::UVIO_ATT_UID_LEN];
 /// Wraps an u64 to set/get individual bits
 pub type UvFlags = crate::misc::Msb0Flags64;
 
+#[cfg(feature = "uvdevice")]
 /// Fire an ioctl.
 ///
 /// # Safety:
@@ -69,8 +119,60 @@ fn ioctl_raw(raw_fd: RawFd, cmd: c_ulong, cb: &mut IoctlCb) -> Result<()> {
     }
 }
 
+#[cfg(feature = "uvdevice")]
+/// First file descriptor passed by the service manager (`SD_LISTEN_FDS_START`)
+const LISTEN_FDS_START: RawFd = 3;
+
+#[cfg(feature = "uvdevice")]
+/// Select the file descriptor named `name` from the systemd file descriptor passing variables.
+fn listen_fd(
+    listen_pid: Option<&str>,
+    pid: u32,
+    listen_fds: Option<&str>,
+    names: Option<&str>,
+    name: &str,
+) -> Option<RawFd> {
+    if listen_pid?.parse::<u32>().ok()? != pid {
+        return None;
+    }
+    let count: RawFd = listen_fds?.parse().ok()?;
+    if count <= 0 {
+        return None;
+    }
+    match names {
+        Some(names) => names
+            .split(':')
+            .take(count as usize)
+            .position(|n| n == name)
+            .map(|i| LISTEN_FDS_START + i as RawFd),
+        None if count == 1 => Some(LISTEN_FDS_START),
+        None => None,
+    }
+}
+
+#[cfg(feature = "uvdevice")]
+/// Set once the file descriptor passed by the service manager was taken by this process
+static LISTEN_FD_TAKEN: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "uvdevice")]
+/// Take ownership of the passed file descriptor `fd` exactly once.
+fn take_listen_fd(taken: &AtomicBool, fd: Option<RawFd>) -> Result<Option<RawFd>> {
+    match fd {
+        Some(_) if taken.swap(true, Ordering::AcqRel) => Err(Error::Specification(
+            "The passed uvdevice file descriptor was already taken".to_string(),
+        )),
+        fd => Ok(fd),
+    }
+}
+
+#[cfg(feature = "uvdevice")]
 /// Converts UV return codes into human readable error messages
-fn rc_fmt<C: UvCmd>(rc: u16, rrc: u16, cmd: &mut C) -> &'static str {
+///
+/// `cmd_fmt` converts the command specific return codes.
+fn rc_fmt<F>(rc: u16, rrc: u16, cmd_fmt: F) -> &'static str
+where
+    F: FnOnce(u16, u16) -> Option<&'static str>,
+{
     let s = match (rc, rrc) {
         (0x0000, _) => Some("invalid rc"),
         (0x0002, _) => Some("invalid UV command"),
@@ -81,7 +183,7 @@ fn rc_fmt<C: UvCmd>(rc: u16, rrc: u16, cmd: &mut C) -> &'static str {
         (UvDevice::RC_MORE_DATA, _) => unreachable!("This is no Error!!!!"),
         (UvDevice::RC_SUCCESS, _) => unreachable!("This is no Error!!!!"),
 
-        _ => cmd.rc_fmt(rc, rrc),
+        _ => cmd_fmt(rc, rrc),
     };
     s.unwrap_or("unexpected error-code")
 }
@@ -110,32 +212,144 @@ pub trait UvCmd {
     fn rc_fmt(&self, rc: u16, rrc: u16) -> Option<&'static str>;
 
     /// Returns data used by this command if available.
+    ///
+    /// The Ultravisor reads and writes the data. Commands with a buffer the Ultravisor only reads
+    /// or only writes implement [`UvCmd::arg`] instead.
     fn data(&mut self) -> Option<&mut [u8]> {
         None
     }
+
+    /// Returns the argument buffer of this command and how the Ultravisor accesses it.
+    ///
+    /// Defaults to [`UvCmd::data`] as [`UvArg::InOut`]. An input-only buffer is passed by shared
+    /// reference, so a command does not have to own a mutable copy of a large request.
+    fn arg(&mut self) -> UvArg<'_> {
+        match self.data() {
+            Some(data) => UvArg::InOut(data),
+            None => UvArg::None,
+        }
+    }
+
+    /// Returns the number of bytes the Ultravisor wrote into the data of this command.
+    ///
+    /// Only called after the command executed successfully. Returns [`None`] if the command does
+    /// not report how much data was written.
+    fn written(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns true if the return codes `rc` and `rrc` report a temporary condition.
+    ///
+    /// The Ultravisor did not execute the command and it can be sent again later, see
+    /// [`RetryPolicy`].
+    fn transient(&self, _rc: u16, _rrc: u16) -> bool {
+        false
+    }
+
+    /// Returns the byte ranges of the data of this command that contain secrets.
+    ///
+    /// These bytes are masked in ioctl traces (feature `trace-ioctl`).
+    fn secret_ranges(&self) -> Vec<std::ops::Range<usize>> {
+        vec![]
+    }
+
+    /// Returns true if sending this command again has no further effect on the guest.
+    ///
+    /// Only idempotent commands are retried after the uvdevice was reopened, see
+    /// [`ReconnectPolicy`].
+    fn idempotent(&self) -> bool {
+        false
+    }
+
+    /// Returns the buffers the Ultravisor accesses during this command.
+    ///
+    /// Includes the large buffers of this command, e.g. the data or buffers it references. A
+    /// [`PreparedCmd`] locks these buffers into memory.
+    fn buffers(&self) -> Vec<&[u8]> {
+        vec![]
+    }
+
+    /// Prepares this command to be sent again.
+    ///
+    /// Called by [`PreparedCmd::send`] before each execution. Resets the state the previous
+    /// execution left in the command, without reallocating its buffers.
+    fn reset(&mut self) {}
+}
+
+/// Argument buffer of an Ultravisor command, see [`UvCmd::arg`].
+///
+/// Separates the buffers the Ultravisor only reads from the ones it writes. Larger inputs and
+/// outputs, e.g. of an attestation, are referenced by a small [`UvArg::InOut`] control block
+/// with distinct addresses instead.
+#[derive(Debug)]
+pub enum UvArg<'a> {
+    /// The command passes no buffer.
+    None,
+    /// The Ultravisor only reads the buffer, e.g. an add-secret request.
+    In(&'a [u8]),
+    /// The Ultravisor only writes the buffer, e.g. a secret list.
+    Out(&'a mut [u8]),
+    /// The Ultravisor reads and writes the buffer.
+    InOut(&'a mut [u8]),
 }
 
+impl UvArg<'_> {
+    /// Returns the buffer the Ultravisor reads, if any.
+    pub fn input(&self) -> Option<&[u8]> {
+        match self {
+            Self::In(data) => Some(data),
+            Self::InOut(data) => Some(data),
+            Self::None | Self::Out(_) => None,
+        }
+    }
+
+    /// Returns the buffer the Ultravisor writes, if any.
+    pub fn output(&self) -> Option<&[u8]> {
+        match self {
+            Self::Out(data) | Self::InOut(data) => Some(data),
+            Self::None | Self::In(_) => None,
+        }
+    }
+
+    /// Size of the buffer in bytes, zero if there is none.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::In(data) => data.len(),
+            Self::Out(data) | Self::InOut(data) => data.len(),
+        }
+    }
+
+    /// Returns `true` if the command passes no or an empty buffer.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(feature = "uvdevice")]
 /// [`UvDevice`] IOCTL control block.
 #[derive(Debug)]
 struct IoctlCb(ffi::uvio_ioctl_cb);
+#[cfg(feature = "uvdevice")]
 impl IoctlCb {
-    fn new(data: Option<&mut [u8]>) -> Result<Self> {
-        let (data_raw, data_size) = match data {
-            Some(data) => (
-                data.as_mut_ptr(),
-                data.len()
-                    .try_into()
-                    .map_err(|_| Error::Specification("passed data too large".to_string()))?,
-            ),
-            None => (std::ptr::null_mut(), 0),
+    fn new(arg: &mut UvArg) -> Result<Self> {
+        let argument_addr = match arg {
+            UvArg::None => 0,
+            // The uvdevice only reads an input buffer.
+            UvArg::In(data) => data.as_ptr() as u64,
+            UvArg::Out(data) | UvArg::InOut(data) => data.as_mut_ptr() as u64,
         };
+        let argument_len = arg
+            .len()
+            .try_into()
+            .map_err(|_| Error::Specification("passed data too large".to_string()))?;
 
         Ok(Self(ffi::uvio_ioctl_cb {
             flags: 0,
             uv_rc: 0,
             uv_rrc: 0,
-            argument_addr: data_raw as u64,
-            argument_len: data_size,
+            argument_addr,
+            argument_len,
             reserved14: [0; 44],
         }))
     }
@@ -144,6 +358,10 @@ impl IoctlCb {
         self.0.uv_rc
     }
 
+    fn len(&self) -> usize {
+        self.0.argument_len as usize
+    }
+
     fn rrc(&self) -> u16 {
         self.0.uv_rrc
     }
@@ -153,6 +371,7 @@ impl IoctlCb {
     }
 }
 
+#[cfg(feature = "uvdevice")]
 /// The Ultravisor has two codes that represent a successful execution.
 /// These are represented by this enum.
 #[repr(u16)]
@@ -165,6 +384,7 @@ pub enum UvcSuccess {
     RC_MORE_DATA = UvDevice::RC_MORE_DATA,
 }
 
+#[cfg(feature = "uvdevice")]
 impl UvcSuccess {
     /// Returns true if there is more data available
     pub fn more_data(&self) -> bool {
@@ -175,6 +395,74 @@ impl UvcSuccess {
     }
 }
 
+#[cfg(feature = "uvdevice")]
+/// Response of a successfully executed Ultravisor command.
+///
+/// Returned by [`UvDevice::send_cmd`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UvcResponse {
+    success: UvcSuccess,
+    rrc: u16,
+    written: Option<usize>,
+}
+
+#[cfg(feature = "uvdevice")]
+impl UvcResponse {
+    /// Kind of the successful execution
+    pub fn success(&self) -> UvcSuccess {
+        self.success
+    }
+
+    /// Returns true if there is more data available
+    pub fn more_data(&self) -> bool {
+        self.success.more_data()
+    }
+
+    /// Return code of the Ultravisor
+    pub fn rc(&self) -> u16 {
+        self.success as u16
+    }
+
+    /// Return reason code of the Ultravisor
+    pub fn rrc(&self) -> u16 {
+        self.rrc
+    }
+
+    /// Number of bytes the Ultravisor wrote into the command data, if reported by the command.
+    ///
+    /// See [`UvCmd::written`].
+    pub fn written(&self) -> Option<usize> {
+        self.written
+    }
+}
+
+#[cfg(feature = "uvdevice")]
+impl From<UvcSuccess> for UvcResponse {
+    /// A response with a zero return reason code that does not report the written data.
+    fn from(success: UvcSuccess) -> Self {
+        Self {
+            success,
+            rrc: 0,
+            written: None,
+        }
+    }
+}
+
+#[cfg(feature = "uvdevice")]
+/// How a [`UvDevice`] handles a vanished uvdevice, e.g. after the kernel module was reloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReconnectPolicy {
+    /// Report [`Error::DeviceGone`].
+    #[default]
+    Never,
+    /// Reopen `/dev/uv` and retry the command if it is idempotent, see [`UvCmd::idempotent`].
+    ///
+    /// Non-idempotent commands still report [`Error::DeviceGone`], but the next command uses the
+    /// reopened device.
+    Reopen,
+}
+
+#[cfg(feature = "uvdevice")]
 /// The `UvDevice` is a (virtual) device on s390 machines to send Ultravisor commands(UVCs) from
 /// userspace.
 ///
@@ -199,11 +487,138 @@ impl UvcSuccess {
 /// # }
 /// // do something with the result
 /// ```
+///
+/// # Concurrency
+///
+/// A `UvDevice` is cheap to clone: all clones share the same opened file descriptor, which is
+/// closed when the last clone is dropped. The type is [`Send`] and [`Sync`], so one opened device
+/// can be shared by several threads instead of opening `/dev/uv` per thread.
+///
+/// Each [`UvDevice::send_cmd`] issues exactly one IOCTL and keeps no state in the device between
+/// calls. The uvdevice driver handles concurrent IOCTLs independently of each other, hence
+/// commands may be sent from several threads at the same time. Ordering between commands sent from
+/// different threads is not guaranteed; if a sequence of commands must be executed in order (e.g.
+/// add secrets, then lock the store), it must be sent from one thread or synchronized by the
+/// caller.
+///
+/// ```rust,no_run
+/// # use s390_pv_core::uv::{ListCmd, UvDevice};
+/// # fn main() -> s390_pv_core::Result<()> {
+/// let uv = UvDevice::open()?;
+/// let workers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let uv = uv.clone();
+///         std::thread::spawn(move || uv.send_cmd(&mut ListCmd::new()))
+///     })
+///     .collect();
+/// # for w in workers { w.join().unwrap()?; }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Caching
+///
+/// The [`UvDeviceInfo`] and the [`UvQueryInfo`] are retrieved on first use and cached afterwards.
+/// All clones share this cache. Use [`UvDevice::refresh`] to drop the cached data, e.g. after the
+/// guest was migrated.
+///
+/// # Reconnecting
+///
+/// If the uvdevice vanishes, e.g. because the kernel module was reloaded, commands fail with
+/// [`Error::DeviceGone`]. Long-running processes can set [`ReconnectPolicy::Reopen`] or call
+/// [`UvDevice::reopen`] to continue with a newly opened device.
+///
+/// # Retrying
+///
+/// Commands that fail because of a temporary condition, e.g. a dump in progress, can be retried
+/// with a backoff, see [`RetryPolicy`] and [`UvDevice::set_retry_policy`].
+#[derive(Debug, Clone)]
+pub struct UvDevice(Arc<UvDeviceInner>);
+
+#[cfg(feature = "uvdevice")]
+/// The implementation of the uvdevice IOCTLs
 #[derive(Debug)]
-pub struct UvDevice(File);
+enum Backend {
+    /// The uvdevice of the kernel
+    Device(File),
+    /// The software simulation, see [`UvSimulator`]
+    #[cfg(feature = "uv-sim")]
+    Sim(UvSimulator),
+}
+
+#[cfg(feature = "uvdevice")]
+#[derive(Debug)]
+struct UvDeviceInner {
+    backend: RwLock<Backend>,
+    /// Path to open the device again; [`None`] if the device was passed as file descriptor
+    path: Option<&'static str>,
+    reconnect: AtomicBool,
+    retry: RwLock<RetryPolicy>,
+    observer: RwLock<Option<Arc<dyn UvObserver>>>,
+    info: RwLock<Option<Arc<UvDeviceInfo>>>,
+    query: RwLock<Option<Arc<UvQueryInfo>>>,
+}
+
+#[cfg(feature = "uvdevice")]
+impl UvDeviceInner {
+    fn new(backend: Backend, path: Option<&'static str>) -> Self {
+        Self {
+            backend: RwLock::new(backend),
+            path,
+            reconnect: AtomicBool::new(false),
+            retry: RwLock::new(RetryPolicy::never()),
+            observer: RwLock::new(None),
+            info: RwLock::new(None),
+            query: RwLock::new(None),
+        }
+    }
 
+    fn backend(&self) -> RwLockReadGuard<'_, Backend> {
+        // Replacing the backend cannot leave it in an inconsistent state.
+        self.backend.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "uvdevice")]
+fn open_rw(path: &'static str) -> Result<File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| Error::FileAccess {
+            ty: FileAccessErrorType::Open,
+            path: path.into(),
+            source: e,
+        })
+}
+
+#[cfg(feature = "uvdevice")]
+/// Returns the cached value or initializes the cache with `init`.
+fn cached<T, F>(cache: &RwLock<Option<Arc<T>>>, init: F) -> Result<Arc<T>>
+where
+    F: FnOnce() -> Result<T>,
+{
+    // A poisoned lock cannot leave the cache in an inconsistent state.
+    if let Some(v) = cache.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return Ok(v.clone());
+    }
+    // Initialize without holding the lock; `init` may reopen the device and drop the cache.
+    let v = Arc::new(init()?);
+    let mut cache = cache.write().unwrap_or_else(|e| e.into_inner());
+    Ok(cache.get_or_insert(v).clone())
+}
+
+#[cfg(feature = "uvdevice")]
+// A `UvDevice` must stay shareable between threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<UvDevice>()
+};
+
+#[cfg(feature = "uvdevice")]
 impl UvDevice {
-    const PATH: &'static str = "/dev/uv";
+    /// Path of the uvdevice
+    pub const PATH: &'static str = "/dev/uv";
     const RC_MORE_DATA: u16 = 0x0100;
     const RC_SUCCESS: u16 = 0x0001;
 
@@ -213,17 +628,220 @@ impl UvDevice {
     ///
     /// This function will return an error if the device file cannot be opened.
     pub fn open() -> Result<Self> {
-        Ok(Self(
-            std::fs::OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open(Self::PATH)
-                .map_err(|e| Error::FileAccess {
-                    ty: FileAccessErrorType::Open,
-                    path: (Self::PATH).into(),
-                    source: e,
-                })?,
-        ))
+        #[cfg(feature = "uv-sim")]
+        if let Some(path) = env::var_os(UvSimulator::ENV) {
+            return Ok(Self::simulated(UvSimulator::with_state_file(path)?));
+        }
+        let file = open_rw(Self::PATH)?;
+        Ok(Self(Arc::new(UvDeviceInner::new(
+            Backend::Device(file),
+            Some(Self::PATH),
+        ))))
+    }
+
+    /// Use the software simulation `sim` instead of the uvdevice.
+    ///
+    /// With the feature `uv-sim`, [`UvDevice::open`] also returns a simulated device if the
+    /// environment variable `PV_UV_SIM` is set, see [`UvSimulator`].
+    #[cfg(feature = "uv-sim")]
+    pub fn simulated(sim: UvSimulator) -> Self {
+        Self(Arc::new(UvDeviceInner::new(Backend::Sim(sim), None)))
+    }
+
+    /// Name of the file descriptor of the uvdevice passed via `LISTEN_FDNAMES`
+    pub const FD_NAME: &'static str = "uv";
+
+    /// Use an already opened file descriptor of the uvdevice.
+    ///
+    /// This allows a privileged supervisor to open `/dev/uv` and hand the descriptor to a
+    /// sandboxed process, that has no access to `/dev` itself.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `fd` does not refer to a character device.
+    pub fn from_fd(fd: OwnedFd) -> Result<Self> {
+        let file = File::from(fd);
+        if !file.metadata()?.file_type().is_char_device() {
+            return Err(Error::Specification(
+                "The file descriptor does not refer to a character device".to_string(),
+            ));
+        }
+        Ok(Self(Arc::new(UvDeviceInner::new(
+            Backend::Device(file),
+            None,
+        ))))
+    }
+
+    /// Use a file descriptor of the uvdevice passed by the service manager.
+    ///
+    /// Implements the file descriptor passing protocol of systemd (`LISTEN_PID`, `LISTEN_FDS`,
+    /// and `LISTEN_FDNAMES`), e.g. used by `OpenFile=/dev/uv:uv`. The descriptor named
+    /// [`UvDevice::FD_NAME`] is used. If the descriptors are unnamed, exactly one descriptor must
+    /// be passed. The environment is left unchanged, but the descriptor is taken only once per
+    /// process.
+    ///
+    /// Returns [`None`] if no suitable descriptor was passed to this process.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the passed descriptor does not refer to a character
+    /// device or was already taken by an earlier call.
+    pub fn from_listen_fds() -> Result<Option<Self>> {
+        let fd = listen_fd(
+            env::var("LISTEN_PID").ok().as_deref(),
+            std::process::id(),
+            env::var("LISTEN_FDS").ok().as_deref(),
+            env::var("LISTEN_FDNAMES").ok().as_deref(),
+            Self::FD_NAME,
+        );
+        match take_listen_fd(&LISTEN_FD_TAKEN, fd)? {
+            None => Ok(None),
+            Some(fd) => {
+                debug!("Using uvdevice passed as file descriptor {fd}");
+                // SAFETY: The service manager passed this descriptor exclusively to this process
+                // and it is taken only once.
+                let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+                // Do not leak the descriptor into executed programs.
+                // SAFETY: fcntl with integer arguments does not access memory
+                unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) };
+                Self::from_fd(fd).map(Some)
+            }
+        }
+    }
+
+    /// Use the uvdevice passed by the service manager or open `/dev/uv`.
+    ///
+    /// See [`UvDevice::from_listen_fds`] and [`UvDevice::open`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the passed descriptor is not usable or the device
+    /// file cannot be opened.
+    pub fn inherited_or_open() -> Result<Self> {
+        match Self::from_listen_fds()? {
+            Some(uv) => Ok(uv),
+            None => Self::open(),
+        }
+    }
+
+    /// Convert IOCTL errors caused by an unsupported IOCTL `nr` into [`Error::Unsupported`].
+    fn ioctl_err(&self, nr: u8, err: Error) -> Error {
+        match &err {
+            Error::Io(e) if e.raw_os_error() == Some(libc::ENODEV) => Error::DeviceGone,
+            Error::Io(e)
+                if e.raw_os_error().is_some_and(|errno| {
+                    info::ioctl_unsupported(nr, errno, || {
+                        self.info().ok().map(|i| i.as_ref().clone())
+                    })
+                }) =>
+            {
+                match ffi::ioctl_since(nr) {
+                    Some(v) => debug!("IOCTL {nr} is not supported: {e} (requires Linux {v})"),
+                    None => debug!("IOCTL {nr} is not supported: {e}"),
+                }
+                Error::Unsupported {
+                    nr,
+                    name: info::nr_as_string(nr),
+                }
+            }
+            _ => err,
+        }
+    }
+
+    /// Returns the information about the supported commands of this uvdevice.
+    ///
+    /// The information is retrieved on first use and cached afterwards.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the uvdevice information cannot be retrieved.
+    pub fn info(&self) -> Result<Arc<UvDeviceInfo>> {
+        cached(&self.0.info, || UvDeviceInfo::get(self))
+    }
+
+    /// Returns the Ultravisor query information from sysfs.
+    ///
+    /// The information is read on first use and cached afterwards.
+    pub fn query_info(&self) -> Arc<UvQueryInfo> {
+        // Reading the query information cannot fail.
+        cached(&self.0.query, || Ok(UvQueryInfo::read())).unwrap()
+    }
+
+    /// Drop the cached [`UvDeviceInfo`] and [`UvQueryInfo`].
+    ///
+    /// The next access retrieves the information again. Affects all clones of this device.
+    pub fn refresh(&self) {
+        *self.0.info.write().unwrap_or_else(|e| e.into_inner()) = None;
+        *self.0.query.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Set how this device handles a vanished uvdevice.
+    ///
+    /// Affects all clones of this device.
+    pub fn set_reconnect_policy(&self, policy: ReconnectPolicy) {
+        self.0
+            .reconnect
+            .store(policy == ReconnectPolicy::Reopen, Ordering::Relaxed);
+    }
+
+    /// Returns how this device handles a vanished uvdevice.
+    pub fn reconnect_policy(&self) -> ReconnectPolicy {
+        match self.0.reconnect.load(Ordering::Relaxed) {
+            true => ReconnectPolicy::Reopen,
+            false => ReconnectPolicy::Never,
+        }
+    }
+
+    /// Set how this device retries commands that failed temporarily.
+    ///
+    /// Affects all clones of this device.
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        *self.0.retry.write().unwrap_or_else(|e| e.into_inner()) = policy;
+    }
+
+    /// Returns how this device retries commands that failed temporarily.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.0
+            .retry
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Set the observer that is notified about every UV call of this device.
+    ///
+    /// [`None`] removes the observer. Affects all clones of this device. The process-wide
+    /// [`UvMetrics`] are collected regardless of the observer.
+    pub fn set_observer(&self, observer: Option<Arc<dyn UvObserver>>) {
+        *self.0.observer.write().unwrap_or_else(|e| e.into_inner()) = observer;
+    }
+
+    /// Open the uvdevice again and use it for this device and all its clones.
+    ///
+    /// Drops the cached information, see [`UvDevice::refresh`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the device cannot be opened. If the device was
+    /// passed as file descriptor, [`Error::DeviceGone`] is returned.
+    pub fn reopen(&self) -> Result<()> {
+        let path = self.0.path.ok_or(Error::DeviceGone)?;
+        debug!("Reopening the uvdevice at {path}");
+        let file = open_rw(path)?;
+        *self.0.backend.write().unwrap_or_else(|e| e.into_inner()) = Backend::Device(file);
+        self.refresh();
+        Ok(())
+    }
+
+    /// Probe whether the uvdevice and the Ultravisor support the command `C`.
+    ///
+    /// Uses the cached uvdevice information, see [`UvDevice::info`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the uvdevice information cannot be retrieved.
+    pub fn supports<C: UvCmd>(&self) -> Result<bool> {
+        Ok(self.info()?.supports::<C>())
     }
 
     /// Send an Ultravisor Command via this uvdevice.
@@ -233,23 +851,199 @@ impl UvDevice {
     /// # Errors
     ///
     /// This function will return an error if the IOCTL fails or the Ultravisor does not report
-    /// a success.
+    /// a success. If the uvdevice or the Ultravisor does not support the command,
+    /// [`Error::Unsupported`] is returned. If the uvdevice vanished, [`Error::DeviceGone`] is
+    /// returned, unless the command could be retried, see [`ReconnectPolicy`]. Temporary failures
+    /// are retried according to the [`RetryPolicy`] of this device; the error of the last attempt
+    /// is returned.
     ///
     /// # Returns
     ///
-    /// [`UvcSuccess`] if the UVC executed successfully
-    pub fn send_cmd<C: UvCmd>(&self, cmd: &mut C) -> Result<UvcSuccess> {
-        let mut cb = IoctlCb::new(cmd.data())?;
-        ioctl_raw(self.0.as_raw_fd(), cmd.cmd(), &mut cb)?;
+    /// A [`UvcResponse`] with the return codes and, if reported, the amount of data written by the
+    /// Ultravisor, if the UVC executed successfully
+    pub fn send_cmd<C: UvCmd>(&self, cmd: &mut C) -> Result<UvcResponse> {
+        self.send_cmd_retry(cmd, std::thread::sleep)
+    }
+
+    fn send_cmd_retry<C, S>(&self, cmd: &mut C, mut sleep: S) -> Result<UvcResponse>
+    where
+        C: UvCmd,
+        S: FnMut(Duration),
+    {
+        let policy = self.retry_policy();
+        let mut attempt = 1;
+        loop {
+            match self.send_cmd_reconnect(cmd) {
+                Err(e) if attempt < policy.max_attempts() && policy.retryable(cmd, &e) => {
+                    let delay = policy.delay(attempt);
+                    debug!(
+                        "Attempt {attempt} of {} failed: {e}; retrying in {delay:?}",
+                        policy.max_attempts()
+                    );
+                    sleep(delay);
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    fn send_cmd_reconnect<C: UvCmd>(&self, cmd: &mut C) -> Result<UvcResponse> {
+        match self.send_cmd_once(cmd) {
+            Err(Error::DeviceGone) if self.reconnect_policy() == ReconnectPolicy::Reopen => {
+                if let Err(e) = self.reopen() {
+                    warn!("Cannot reopen the uvdevice: {e}");
+                    return Err(Error::DeviceGone);
+                }
+                match cmd.idempotent() {
+                    true => self.send_cmd_once(cmd),
+                    false => Err(Error::DeviceGone),
+                }
+            }
+            res => res,
+        }
+    }
+
+    /// Send the IOCTL `ioctl` for the command `nr` once, record its metrics, and notify the
+    /// observer.
+    ///
+    /// `refs_valid` tells whether addresses inside the data of `cb` are valid. `written` is only
+    /// called after a successful UV call. Errors of the IOCTL are returned unchanged, see
+    /// [`UvDevice::ioctl_err`].
+    fn ioctl_once<W>(
+        &self,
+        nr: u8,
+        ioctl: u64,
+        request_size: usize,
+        cb: &mut IoctlCb,
+        refs_valid: bool,
+        written: W,
+    ) -> Result<()>
+    where
+        W: FnOnce() -> Option<usize>,
+    {
+        let name = info::nr_as_string(nr);
+        let start = Instant::now();
+        let res = match &*self.0.backend() {
+            Backend::Device(file) => ioctl_raw(file.as_raw_fd(), ioctl, cb),
+            // SAFETY: The control block was created from a valid buffer that outlives this call.
+            // Addresses in the data are only accessed if the command guarantees their validity.
+            #[cfg(feature = "uv-sim")]
+            Backend::Sim(sim) => unsafe { sim.ioctl(nr, &mut cb.0, refs_valid) },
+        };
+        #[cfg(not(feature = "uv-sim"))]
+        let _ = refs_valid;
+        let latency = start.elapsed();
+        let outcome = match (&res, cb.rc()) {
+            (Err(_), _) => CallOutcome::Ioctl,
+            (Ok(_), Self::RC_SUCCESS | Self::RC_MORE_DATA) => CallOutcome::Success,
+            (Ok(_), rc) => CallOutcome::Uv { rc, rrc: cb.rrc() },
+        };
+        UvMetrics::global().record(name, request_size, latency, outcome);
+        let observer = self
+            .0
+            .observer
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(observer) = observer {
+            observer.on_call(&UvCall {
+                cmd: name,
+                nr,
+                duration: latency,
+                request_size,
+                response_size: (outcome == CallOutcome::Success).then(written).flatten(),
+                outcome,
+            });
+        }
+        res?;
+        debug!(
+            uvc = name, rc = cb.rc(), rrc = cb.rrc(), latency_us = latency.as_micros() as u64;
+            "{name} UVC finished with rc {:#06x}, rrc {:#06x}", cb.rc(), cb.rrc()
+        );
+        Ok(())
+    }
 
-        match (cb.rc(), cb.rrc()) {
-            (Self::RC_SUCCESS, _) => Ok(UvcSuccess::RC_SUCCESS),
-            (Self::RC_MORE_DATA, _) => Ok(UvcSuccess::RC_MORE_DATA),
-            (rc, rrc) => Err(Error::Uv {
-                rc,
-                rrc,
-                msg: rc_fmt(rc, rrc, cmd),
-            }),
+    fn send_cmd_once<C: UvCmd>(&self, cmd: &mut C) -> Result<UvcResponse> {
+        #[cfg(feature = "trace-ioctl")]
+        let name = info::nr_as_string(C::UV_IOCTL_NR);
+        #[cfg(feature = "record-ioctl")]
+        let request = IoctlRecorder::is_enabled().then(|| fixture::stage(&cmd.arg()));
+        let mut cb = IoctlCb::new(&mut cmd.arg())?;
+        let request_size = cb.len();
+        #[cfg(feature = "trace-ioctl")]
+        trace::dump("request", name, cmd);
+        let ioctl = cmd.cmd();
+        let res = self.ioctl_once(C::UV_IOCTL_NR, ioctl, request_size, &mut cb, true, || {
+            cmd.written()
+        });
+        #[cfg(feature = "trace-ioctl")]
+        trace::dump("response", name, cmd);
+        #[cfg(feature = "record-ioctl")]
+        if let Some(request) = request {
+            let errno = match &res {
+                Err(Error::Io(e)) => e.raw_os_error().unwrap_or(-1),
+                _ => 0,
+            };
+            fixture::record(request, cb.rc(), cb.rrc(), errno, cmd);
         }
+        res.map_err(|e| self.ioctl_err(C::UV_IOCTL_NR, e))?;
+
+        let success = match (cb.rc(), cb.rrc()) {
+            (Self::RC_SUCCESS, _) => UvcSuccess::RC_SUCCESS,
+            (Self::RC_MORE_DATA, _) => UvcSuccess::RC_MORE_DATA,
+            (rc, rrc) => {
+                return Err(Error::Uv {
+                    rc,
+                    rrc,
+                    msg: rc_fmt(rc, rrc, |rc, rrc| cmd.rc_fmt(rc, rrc)),
+                })
+            }
+        };
+        Ok(UvcResponse {
+            success,
+            rrc: cb.rrc(),
+            written: cmd.written(),
+        })
+    }
+
+    /// Send the uvdevice IOCTL `nr` with the raw command data `data`.
+    ///
+    /// An escape hatch for IOCTLs this crate does not model yet, e.g. on development kernels
+    /// or firmware. `data` is passed to the Ultravisor unchanged and contains the response
+    /// afterwards; an empty `data` passes no buffer. The command is sent exactly once, neither the
+    /// [`RetryPolicy`] nor the [`ReconnectPolicy`] of this device apply, and IOCTL traces
+    /// (feature `trace-ioctl`) do not include `data`. Prefer a [`UvCmd`] wherever one exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the IOCTL fails or the Ultravisor does not report
+    /// a success. If the uvdevice or the Ultravisor does not support the IOCTL,
+    /// [`Error::Unsupported`] is returned.
+    ///
+    /// # Returns
+    ///
+    /// The kind of success, the return code, and the return reason code of the Ultravisor
+    pub fn send_raw(&self, nr: u8, data: &mut [u8]) -> Result<(UvcSuccess, u16, u16)> {
+        let mut arg = match data.is_empty() {
+            true => UvArg::None,
+            false => UvArg::InOut(data),
+        };
+        let mut cb = IoctlCb::new(&mut arg)?;
+        let request_size = cb.len();
+        self.ioctl_once(nr, uv_ioctl(nr), request_size, &mut cb, false, || None)
+            .map_err(|e| self.ioctl_err(nr, e))?;
+        let success = match (cb.rc(), cb.rrc()) {
+            (Self::RC_SUCCESS, _) => UvcSuccess::RC_SUCCESS,
+            (Self::RC_MORE_DATA, _) => UvcSuccess::RC_MORE_DATA,
+            (rc, rrc) => {
+                return Err(Error::Uv {
+                    rc,
+                    rrc,
+                    msg: rc_fmt(rc, rrc, |_, _| None),
+                })
+            }
+        };
+        Ok((success, cb.rc(), cb.rrc()))
     }
 }
@@ -1,10 +1,12 @@
 // SPDX-License-Identifier: MIT
 //
 // Copyright IBM Corp. 2024
-use super::{ffi, AttestationUserData, ConfigUid, UvCmd};
-use crate::{Error, Result};
+use super::{ffi, ConfigUid, UvCmd};
+use crate::{attest::UserData, static_assert, Error, Result};
 use std::ptr;
-use zerocopy::{FromZeros, IntoBytes};
+use zerocopy::IntoBytes;
+
+static_assert!(UserData::MAX_SIZE == ffi::UVIO_ATT_USER_DATA_LEN);
 
 /// _Retrieve Attestation Measurement_ UVC
 ///
@@ -27,14 +29,15 @@ use zerocopy::{FromZeros, IntoBytes};
 /// # Example
 ///
 /// ```rust,no_run
-/// # use s390_pv_core::uv::UvDevice;
-/// # use s390_pv_core::uv::AttestationCmd;
+/// # use s390_pv_core::{attest::UserData, uv::AttestationCmd};
 /// # fn main() -> s390_pv_core::Result<()> {
 /// let arcb = std::fs::read("arcb")?.into();
-/// let user_data = vec![0, 1, 2, 3];
+/// let user_data = UserData::new(vec![0, 1, 2, 3])?;
 /// // Hard-coded example
 /// let mut cmd = AttestationCmd::new_request(arcb, Some(user_data), 64, 0)?;
-/// let uv = UvDevice::open()?;
+/// # #[cfg(feature = "uvdevice")]
+/// let uv = s390_pv_core::uv::UvDevice::open()?;
+/// # #[cfg(feature = "uvdevice")]
 /// # uv.send_cmd(&mut cmd)?;
 /// # Ok(())
 /// # }
@@ -93,7 +96,7 @@ impl AttestationCmd {
     /// Creates a new Retrieve Attestation Measurement UVC.
     pub fn new_request(
         arcb: Box<[u8]>,
-        user_data: Option<Vec<u8>>,
+        user_data: Option<UserData>,
         exp_measurement: u32,
         exp_additional: u32,
     ) -> Result<Self> {
@@ -110,19 +113,7 @@ impl AttestationCmd {
             "Expected additional data size",
         )?;
         Self::verify_slice(&arcb, Self::ARCB_MAX_SIZE, "Attestation request")?;
-        if let Some(ref data) = user_data {
-            Self::verify_slice(data, Self::USER_MAX_SIZE, "User data")?;
-        }
-
-        let (user_len, user_data) = match user_data {
-            // enforced by tests before invariants
-            Some(user) => (Some(user.len() as u16), {
-                let mut user_data = AttestationUserData::new_zeroed();
-                user_data[0..user.len()].clone_from_slice(&user);
-                Some(user_data)
-            }),
-            None => (None, None),
-        };
+        let (user_data, user_len) = user_data.as_ref().map(UserData::to_uvio).unzip();
 
         let mut additional = match exp_additional {
             0 => None,
@@ -130,7 +121,7 @@ impl AttestationCmd {
         };
         let mut measurement = vec![0u8; exp_measurement as usize];
         let uvio_attest = unsafe {
-            ffi::uvio_attest::new(
+            new_uvio_attest(
                 &arcb,
                 &mut measurement,
                 additional.as_deref_mut(),
@@ -148,14 +139,22 @@ impl AttestationCmd {
 
     /// Provides the additional data  calculated by UV after a successful UVC
     ///
-    /// Truncates the additional data to the correct length in place.
+    /// Truncated to the UV reported size. The buffer keeps its size, so that the command can be
+    /// sent again.
     /// If called before a successful attestation the data in this buffer is undefined.
     pub fn additional(&mut self) -> Option<&[u8]> {
-        // truncate the add size to the UV reported size
-        if let Some(ref mut a) = &mut self.additional {
-            a.truncate(self.uvio_attest.add_data_len as usize)
-        }
-        self.additional.as_deref()
+        let len = self.uvio_attest.add_data_len() as usize;
+        self.additional.as_deref().map(|a| &a[..len.min(a.len())])
+    }
+
+    /// Replaces the user data for the next attestation.
+    ///
+    /// Allows repeated attestations of the same request with different user data, e.g. a fresh
+    /// nonce, without allocating a new command.
+    pub fn set_user_data(&mut self, user_data: Option<UserData>) {
+        let (user_data, user_len) = user_data.as_ref().map(UserData::to_uvio).unzip();
+        self.uvio_attest.user_data = user_data.unwrap_or([0; ffi::UVIO_ATT_USER_DATA_LEN]);
+        self.uvio_attest.user_data_len = user_len.unwrap_or_default().into();
     }
 
     /// Copies the additional data calculated by UV after a successful UVC into a Vec
@@ -164,7 +163,7 @@ impl AttestationCmd {
     /// If called before a successful attestation the data in this buffer is undefined.
     pub fn additional_owned(&self) -> Option<Vec<u8>> {
         let mut additional = self.additional.clone()?;
-        additional.truncate(self.uvio_attest.add_data_len as usize);
+        additional.truncate(self.uvio_attest.add_data_len() as usize);
         Some(additional)
     }
 
@@ -186,6 +185,18 @@ impl AttestationCmd {
     pub fn arcb(&self) -> &[u8] {
         self.arcb.as_ref()
     }
+
+    /// Consumes the [`AttestationCmd`] and returns its buffers without copying them.
+    ///
+    /// Returns the attestation request, the measurement, and the additional data truncated to the
+    /// UV reported size.
+    /// If called before a successful attestation the data in the output buffers is undefined.
+    pub fn into_parts(mut self) -> (Box<[u8]>, Vec<u8>, Option<Vec<u8>>) {
+        if let Some(ref mut a) = &mut self.additional {
+            a.truncate(self.uvio_attest.add_data_len() as usize)
+        }
+        (self.arcb, self.measurement, self.additional)
+    }
 }
 
 impl UvCmd for AttestationCmd {
@@ -221,9 +232,27 @@ impl UvCmd for AttestationCmd {
         }
     }
 
+    /// A repeated measurement uses the same request and overwrites the previous results.
+    fn idempotent(&self) -> bool {
+        true
+    }
+
     fn data(&mut self) -> Option<&mut [u8]> {
         Some(self.uvio_attest.as_mut_bytes())
     }
+
+    /// The control block is small and therefore not included.
+    fn buffers(&self) -> Vec<&[u8]> {
+        let mut buffers = vec![self.arcb.as_ref(), self.measurement.as_slice()];
+        buffers.extend(self.additional.as_deref());
+        buffers
+    }
+
+    /// The Ultravisor overwrites the size of the additional data with the size it wrote.
+    fn reset(&mut self) {
+        self.uvio_attest
+            .set_add_data_len(self.additional.as_ref().map_or(0, |a| a.len() as u32));
+    }
 }
 
 fn opt_to_mut_ptr_u64(opt: &mut Option<&mut [u8]>) -> u64 {
@@ -233,36 +262,58 @@ fn opt_to_mut_ptr_u64(opt: &mut Option<&mut [u8]>) -> u64 {
     }) as u64
 }
 
-impl ffi::uvio_attest {
-    /// Create a new attestation IOCTL control block
-    ///
-    /// Happily converts slice lengths into u32/u16 without verifying.
-    /// Therefore marked as unsafe.
-    ///
-    /// # SAFETY
-    /// It is safe to call this function iff:
-    ///  - `arcb.len() < u32::MAX`
-    ///  - `additional.len() < u32::MAX`
-    ///  - `user_len() <= 256`
-    ///  - pointer fits into an u64
-    unsafe fn new(
-        arcb: &[u8],
-        measurement: &mut [u8],
-        mut additional: Option<&mut [u8]>,
-        user: Option<AttestationUserData>,
-        user_len: Option<u16>,
-    ) -> Self {
-        Self {
-            arcb_addr: arcb.as_ptr() as u64,
-            meas_addr: measurement.as_ptr() as u64,
-            add_data_addr: opt_to_mut_ptr_u64(&mut additional),
-            user_data: user.unwrap_or([0; 256]),
-            config_uid: [0; 16],
-            arcb_len: arcb.len() as u32,
-            meas_len: measurement.len() as u32,
-            add_data_len: additional.unwrap_or_default().len() as u32,
-            user_data_len: user_len.unwrap_or_default(),
-            reserved136: 0,
-        }
+/// Create a new attestation IOCTL control block
+///
+/// Happily converts slice lengths into u32/u16 without verifying.
+/// Therefore marked as unsafe.
+///
+/// # SAFETY
+/// It is safe to call this function iff:
+///  - `arcb.len() < u32::MAX`
+///  - `additional.len() < u32::MAX`
+///  - `user_len() <= 256`
+///  - pointer fits into an u64
+unsafe fn new_uvio_attest(
+    arcb: &[u8],
+    measurement: &mut [u8],
+    mut additional: Option<&mut [u8]>,
+    user: Option<[u8; ffi::UVIO_ATT_USER_DATA_LEN]>,
+    user_len: Option<u16>,
+) -> ffi::uvio_attest {
+    ffi::uvio_attest {
+        arcb_addr: (arcb.as_ptr() as u64).into(),
+        meas_addr: (measurement.as_ptr() as u64).into(),
+        add_data_addr: opt_to_mut_ptr_u64(&mut additional).into(),
+        user_data: user.unwrap_or([0; 256]),
+        config_uid: [0; 16],
+        arcb_len: (arcb.len() as u32).into(),
+        meas_len: (measurement.len() as u32).into(),
+        add_data_len: (additional.unwrap_or_default().len() as u32).into(),
+        user_data_len: user_len.unwrap_or_default().into(),
+        reserved136: 0.into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn control_block_big_endian() {
+        let user_data = UserData::new(vec![0xaa; 3]).unwrap();
+        let mut cmd =
+            AttestationCmd::new_request(vec![0; 0x11].into(), Some(user_data), 0x20, 0x30).unwrap();
+        let raw = cmd.data().unwrap().to_vec();
+        // the layout is independent of the host byte order
+        assert_eq!(
+            raw[0x128..0x138],
+            [0, 0, 0, 0x11, 0, 0, 0, 0x20, 0, 0, 0, 0x30, 0, 3, 0, 0]
+        );
+        assert_eq!(raw[0x18..0x1b], [0xaa; 3]);
+
+        cmd.uvio_attest.add_data_len = 0x10.into();
+        assert_eq!(cmd.additional().unwrap().len(), 0x10);
+        cmd.reset();
+        assert_eq!(cmd.uvio_attest.add_data_len(), 0x30);
     }
 }
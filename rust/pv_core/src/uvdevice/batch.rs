@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use super::{UvCmd, UvDevice, UvcResponse};
+use crate::Result;
+
+/// An Ultravisor command that can be part of a batch.
+///
+/// Object safe counterpart of [`UvCmd`], such that commands of different types can be sent with
+/// one [`UvDevice::send_batch`]. Implemented for all [`UvCmd`]s.
+pub trait BatchCmd {
+    /// Send this command via `uv`, see [`UvDevice::send_cmd`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the IOCTL fails or the Ultravisor does not report
+    /// a success.
+    fn send(&mut self, uv: &UvDevice) -> Result<UvcResponse>;
+}
+
+impl<C: UvCmd> BatchCmd for C {
+    fn send(&mut self, uv: &UvDevice) -> Result<UvcResponse> {
+        uv.send_cmd(self)
+    }
+}
+
+/// How [`UvDevice::send_batch`] handles a failing command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchPolicy {
+    /// Do not send the commands after the first failing one.
+    #[default]
+    FailFast,
+    /// Send all commands, regardless of the outcome of previous ones.
+    ContinueOnError,
+}
+
+impl UvDevice {
+    /// Send several Ultravisor commands in order via this uvdevice.
+    ///
+    /// The commands are sent one after the other from the calling thread, see
+    /// [`UvDevice::send_cmd`], so that e.g. the secret store can be locked after all secrets were
+    /// added. Returns one result per sent command, in the order of `cmds`. With
+    /// [`BatchPolicy::FailFast`] the last result is the first failure and the remaining commands
+    /// are not sent.
+    ///
+    /// ```rust,no_run
+    /// # use s390_pv_core::uv::{AddCmd, BatchCmd, BatchPolicy, LockCmd, UvDevice};
+    /// # fn main() -> s390_pv_core::Result<()> {
+    /// # let (req1, req2) = (vec![], vec![]);
+    /// let uv = UvDevice::open()?;
+    /// let mut add1 = AddCmd::from_vec(req1)?;
+    /// let mut add2 = AddCmd::from_vec(req2)?;
+    /// let mut cmds: [&mut dyn BatchCmd; 3] = [&mut add1, &mut add2, &mut LockCmd];
+    /// for res in uv.send_batch(&mut cmds, BatchPolicy::FailFast) {
+    ///     res?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_batch(
+        &self,
+        cmds: &mut [&mut dyn BatchCmd],
+        policy: BatchPolicy,
+    ) -> Vec<Result<UvcResponse>> {
+        let mut results = Vec::with_capacity(cmds.len());
+        for cmd in cmds {
+            let res = cmd.send(self);
+            let failed = res.is_err();
+            results.push(res);
+            if failed && policy == BatchPolicy::FailFast {
+                break;
+            }
+        }
+        results
+    }
+}
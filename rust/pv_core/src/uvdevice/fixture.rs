@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Recorded ioctl exchanges with the uvdevice
+//!
+//! A fixture file contains one exchange per line, empty lines and lines starting with `#` are
+//! ignored:
+//!
+//! ```text
+//! nr=3 len=4096 rc=0x0001 rrc=0x0000 errno=0 request=- response=0001000100...
+//! ```
+//!
+//! `len` is the size of the data buffer. `request` and `response` are the hex-encoded data buffer
+//! before and after the ioctl, without trailing zeros; `-` marks a buffer that contains only
+//! zeros. Buffers referenced by the data, e.g. the measurement of an attestation, are not part of
+//! the exchange.
+
+use std::ops::Range;
+
+use crate::{
+    misc::{decode_hex, encode_hex},
+    Error, Result,
+};
+
+/// One ioctl exchange with the uvdevice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoctlExchange {
+    /// IOCTL number of the Ultravisor command
+    pub nr: u8,
+    /// Data buffer before the ioctl
+    pub request: Vec<u8>,
+    /// Return code of the Ultravisor
+    pub rc: u16,
+    /// Return reason code of the Ultravisor
+    pub rrc: u16,
+    /// `errno` of a failing ioctl, `0` on success
+    pub errno: i32,
+    /// Data buffer after the ioctl
+    pub response: Vec<u8>,
+}
+
+fn parse_err(content: &str) -> Error {
+    Error::ParseError {
+        subject: "ioctl exchange".to_string(),
+        content: content.to_string(),
+    }
+}
+
+fn encode_buf(buf: &[u8]) -> String {
+    let len = buf.iter().rposition(|b| *b != 0).map_or(0, |p| p + 1);
+    match len {
+        0 => "-".to_string(),
+        len => encode_hex(&buf[..len]),
+    }
+}
+
+fn decode_buf(hex: &str, len: usize) -> Result<Vec<u8>> {
+    let mut buf = match hex {
+        "-" => vec![],
+        hex => decode_hex(hex)?,
+    };
+    if buf.len() > len {
+        return Err(parse_err(hex));
+    }
+    buf.resize(len, 0);
+    Ok(buf)
+}
+
+fn parse_num<T>(
+    val: &str,
+    parse: fn(&str, u32) -> std::result::Result<T, std::num::ParseIntError>,
+) -> Result<T> {
+    match val.strip_prefix("0x") {
+        Some(hex) => Ok(parse(hex, 16)?),
+        None => Ok(parse(val, 10)?),
+    }
+}
+
+impl IoctlExchange {
+    /// Masks the bytes in `secret` of the request and the response with zeros.
+    pub fn mask(&mut self, secret: &[Range<usize>]) {
+        for buf in [&mut self.request, &mut self.response] {
+            for range in secret {
+                let end = range.end.min(buf.len());
+                if let Some(b) = buf.get_mut(range.start..end) {
+                    b.fill(0);
+                }
+            }
+        }
+    }
+
+    /// Formats this exchange as one line of a fixture file.
+    pub fn to_line(&self) -> String {
+        format!(
+            "nr={} len={} rc={:#06x} rrc={:#06x} errno={} request={} response={}",
+            self.nr,
+            self.request.len(),
+            self.rc,
+            self.rrc,
+            self.errno,
+            encode_buf(&self.request),
+            encode_buf(&self.response)
+        )
+    }
+
+    /// Parses one line of a fixture file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the line is no valid exchange.
+    pub fn from_line(line: &str) -> Result<Self> {
+        let mut fields = [None; 7];
+        const KEYS: [&str; 7] = ["nr", "len", "rc", "rrc", "errno", "request", "response"];
+        for field in line.split_whitespace() {
+            let (key, val) = field.split_once('=').ok_or_else(|| parse_err(field))?;
+            let idx = KEYS
+                .iter()
+                .position(|k| *k == key)
+                .ok_or_else(|| parse_err(field))?;
+            fields[idx] = Some(val);
+        }
+        let [Some(nr), Some(len), Some(rc), Some(rrc), Some(errno), Some(request), Some(response)] =
+            fields
+        else {
+            return Err(parse_err(line));
+        };
+        let len = parse_num(len, usize::from_str_radix)?;
+        Ok(Self {
+            nr: parse_num(nr, u8::from_str_radix)?,
+            request: decode_buf(request, len)?,
+            rc: parse_num(rc, u16::from_str_radix)?,
+            rrc: parse_num(rrc, u16::from_str_radix)?,
+            errno: parse_num(errno, i32::from_str_radix)?,
+            response: decode_buf(response, len)?,
+        })
+    }
+
+    /// Parses the content of a fixture file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a line is no valid exchange.
+    pub fn parse_fixture(content: &str) -> Result<Vec<Self>> {
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(Self::from_line)
+            .collect()
+    }
+}
+
+#[cfg(feature = "record-ioctl")]
+mod recorder {
+    use std::{
+        fs::{File, OpenOptions},
+        io::Write,
+        path::Path,
+        sync::{Mutex, MutexGuard, OnceLock},
+    };
+
+    use log::warn;
+
+    use super::IoctlExchange;
+    use crate::{
+        uv::{UvArg, UvCmd},
+        Error, FileAccessErrorType, Result,
+    };
+
+    /// Environment variable with the fixture file to record to
+    const RECORD_ENV: &str = "PV_IOCTL_RECORD";
+
+    /// Recording of Ultravisor calls into a fixture file
+    ///
+    /// If enabled, [`crate::uv::UvDevice::send_cmd`] appends every ioctl exchange to a fixture
+    /// file, see [`IoctlExchange`]. Bytes the command marks as secret with
+    /// [`UvCmd::secret_ranges`] are recorded as zeros. Replay fixtures in the tests to cover the
+    /// handling of firmware responses without hardware.
+    ///
+    /// Recording is disabled unless started with [`IoctlRecorder::start`] or with the environment
+    /// variable `PV_IOCTL_RECORD=<FILE>`.
+    #[derive(Debug)]
+    pub struct IoctlRecorder;
+
+    fn file() -> MutexGuard<'static, Option<File>> {
+        static FILE: OnceLock<Mutex<Option<File>>> = OnceLock::new();
+        let file = FILE.get_or_init(|| {
+            Mutex::new(
+                std::env::var_os(RECORD_ENV).and_then(|p| match IoctlRecorder::open(&p) {
+                    Ok(f) => Some(f),
+                    Err(e) => {
+                        warn!("Cannot record ioctls: {e}");
+                        None
+                    }
+                }),
+            )
+        });
+        match file.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    impl IoctlRecorder {
+        fn open<P: AsRef<Path>>(path: P) -> Result<File> {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|source| Error::FileAccess {
+                    ty: FileAccessErrorType::Create,
+                    path: path.as_ref().to_path_buf(),
+                    source,
+                })
+        }
+
+        /// Start to record to the fixture file `path`, overriding the environment.
+        ///
+        /// Exchanges are appended if the file exists.
+        ///
+        /// # Errors
+        ///
+        /// This function will return an error if the file cannot be opened.
+        pub fn start<P: AsRef<Path>>(path: P) -> Result<()> {
+            *file() = Some(Self::open(path)?);
+            Ok(())
+        }
+
+        /// Stop the recording.
+        pub fn stop() {
+            *file() = None;
+        }
+
+        /// Returns `true` if the recording is enabled.
+        pub fn is_enabled() -> bool {
+            file().is_some()
+        }
+    }
+
+    /// Copies the request of `arg` that the Ultravisor may overwrite.
+    ///
+    /// Input-only buffers stay unchanged and are recorded after the ioctl, output-only buffers
+    /// are not read by the Ultravisor.
+    pub(crate) fn stage(arg: &UvArg) -> Option<Vec<u8>> {
+        match arg {
+            UvArg::InOut(data) => Some(data.to_vec()),
+            UvArg::None | UvArg::In(_) | UvArg::Out(_) => None,
+        }
+    }
+
+    /// Append the exchange of `cmd` to the fixture file if the recording is enabled.
+    ///
+    /// `request` is the copy of the buffer taken with [`stage`] before the ioctl.
+    pub(crate) fn record<C: UvCmd>(
+        request: Option<Vec<u8>>,
+        rc: u16,
+        rrc: u16,
+        errno: i32,
+        cmd: &mut C,
+    ) {
+        let mut file = file();
+        let Some(f) = file.as_mut() else {
+            return;
+        };
+        let arg = cmd.arg();
+        let request = request.unwrap_or_else(|| match arg.input() {
+            Some(data) => data.to_vec(),
+            None => vec![0; arg.len()],
+        });
+        let response = arg.output().or(arg.input()).unwrap_or_default().to_vec();
+        let mut ex = IoctlExchange {
+            nr: C::UV_IOCTL_NR,
+            request,
+            rc,
+            rrc,
+            errno,
+            response,
+        };
+        ex.mask(&cmd.secret_ranges());
+        if let Err(e) = writeln!(f, "{}", ex.to_line()) {
+            warn!("Cannot record ioctl: {e}");
+        }
+    }
+}
+
+#[cfg(feature = "record-ioctl")]
+pub use recorder::IoctlRecorder;
+#[cfg(feature = "record-ioctl")]
+pub(crate) use recorder::{record, stage};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn line() {
+        let mut ex = IoctlExchange {
+            nr: 3,
+            request: vec![0; 8],
+            rc: 0x100,
+            rrc: 0,
+            errno: 0,
+            response: vec![0, 1, 0, 2, 0xff, 0, 0, 0],
+        };
+        let line = ex.to_line();
+        assert_eq!(
+            line,
+            "nr=3 len=8 rc=0x0100 rrc=0x0000 errno=0 request=- response=00010002ff"
+        );
+        assert_eq!(IoctlExchange::from_line(&line).unwrap(), ex);
+
+        ex.mask(&[3..5, 7..100]);
+        assert_eq!(ex.response, [0, 1, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn parse() {
+        let fixture = "# comment\n\nnr=4 len=0 rc=0x0102 rrc=0 errno=0 request=- response=-\n";
+        let exs = IoctlExchange::parse_fixture(fixture).unwrap();
+        assert_eq!(exs.len(), 1);
+        assert_eq!((exs[0].nr, exs[0].rc), (4, 0x102));
+
+        for inv in [
+            "nr=4 len=0 rc=0 rrc=0 errno=0 request=-",
+            "nr=4 len=1 rc=0 rrc=0 errno=0 request=0000 response=-",
+            "nr=4 len=0 rc=0 rrc=0 errno=0 request=- response=- foo=1",
+            "nr=x len=0 rc=0 rrc=0 errno=0 request=- response=-",
+        ] {
+            assert!(IoctlExchange::from_line(inv).is_err(), "{inv}");
+        }
+    }
+}
@@ -5,7 +5,7 @@
 use super::ffi::{self, uvio_uvdev_info};
 use crate::{
     misc::{Flags, Lsb0Flags64},
-    uv::{UvCmd, UvDevice},
+    uv::{UvCmd, UvDevice, UvQueryInfo},
     Result,
 };
 use std::fmt::Display;
@@ -24,20 +24,33 @@ use zerocopy::{FromZeros, IntoBytes};
 ///
 /// Note that bit 0 is always zero for `supp_uv_cmds`
 /// as there is no corresponding Info UV-call.
-#[derive(Debug)]
+///
+/// If the uvdevice is not available, [`UvDeviceInfo::query`] derives a read-only view from the
+/// installed Ultravisor calls in `/sys/firmware/uv`; see [`UvDeviceInfo::is_read_only`].
+#[derive(Debug, Clone)]
 pub struct UvDeviceInfo {
     supp_uvio_cmds: Lsb0Flags64,
     supp_uv_cmds: Option<Lsb0Flags64>,
+    read_only: bool,
 }
 
+/// IOCTLs of the uvdevice and the bit of their Ultravisor call in the installed-calls facilities
+const IOCTL_UV_CALLS: [(u8, u8); 5] = [
+    (ffi::UVIO_IOCTL_ATT_NR, 28),
+    (ffi::UVIO_IOCTL_ADD_SECRET_NR, 29),
+    (ffi::UVIO_IOCTL_LIST_SECRETS_NR, 30),
+    (ffi::UVIO_IOCTL_LOCK_SECRETS_NR, 31),
+    (ffi::UVIO_IOCTL_RETR_SECRET_NR, 33),
+];
+
 impl UvDeviceInfo {
     /// Get information from the uvdevice.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the ioctl fails and the error code is not
-    /// [`libc::ENOTTY`].
-    /// `ENOTTY` is most likely because older uvdevices does not support the info IOCTL.
+    /// This function will return an error if the ioctl fails and the uvdevice supports the info
+    /// IOCTL.
+    /// If the info IOCTL is not supported, it is most likely because of an older uvdevice.
     /// In that case one can safely assume that the device only supports the Attestation IOCTL.
     /// Therefore this is what this function returns IOCTL support for Attestation and _Data not
     /// available_ for the UV Attestation facility.
@@ -47,25 +60,120 @@ impl UvDeviceInfo {
         let mut cmd = uvio_uvdev_info::new_zeroed();
         match uv.send_cmd(&mut cmd) {
             Ok(_) => Ok(cmd.into()),
-            Err(crate::Error::Io(e)) if e.raw_os_error() == Some(libc::ENOTTY) => {
+            Err(crate::Error::Unsupported { .. }) => {
                 let mut supp_uvio_cmds = Lsb0Flags64::default();
                 supp_uvio_cmds.set_bit(ffi::UVIO_IOCTL_ATT_NR);
 
                 Ok(Self {
                     supp_uvio_cmds,
                     supp_uv_cmds: None,
+                    read_only: false,
                 })
             }
             Err(e) => Err(e),
         }
     }
+
+    /// Get information from the uvdevice, or from sysfs if the uvdevice cannot be opened.
+    ///
+    /// Inventory tools can report the capabilities of a Secure Execution guest even if the
+    /// uvdevice module is not loaded or `/dev/uv` is not accessible. The information derived from
+    /// sysfs is marked as read-only.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the uvdevice cannot be opened and sysfs provides no
+    /// Ultravisor information, or if the uvdevice is available but the info IOCTL fails.
+    pub fn query() -> Result<Self> {
+        match UvDevice::open() {
+            Ok(uv) => uv.info().map(|info| info.as_ref().clone()),
+            Err(e) => Self::from_query(&UvQueryInfo::read()).ok_or(e),
+        }
+    }
+
+    /// Derive a read-only view from the installed Ultravisor calls.
+    ///
+    /// The view assumes that the uvdevice provides an IOCTL for each installed Ultravisor call.
+    /// Returns [`None`] if the installed Ultravisor calls are unknown.
+    pub fn from_query(query: &UvQueryInfo) -> Option<Self> {
+        let facilities = query.facilities()?;
+        let mut supp_uvio_cmds = Lsb0Flags64::default();
+        for (nr, uv_call) in IOCTL_UV_CALLS {
+            if facilities.is_set(uv_call) {
+                supp_uvio_cmds.set_bit(nr);
+            }
+        }
+        Some(Self {
+            supp_uv_cmds: Some(supp_uvio_cmds),
+            supp_uvio_cmds,
+            read_only: true,
+        })
+    }
+
+    /// Returns `true` if the information was derived from sysfs instead of the uvdevice.
+    ///
+    /// Commands cannot be sent without the uvdevice; the supported commands only reflect what the
+    /// Ultravisor provides.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn supports_nr(&self, nr: u8) -> bool {
+        // There is no Info UVC, only the IOCTL.
+        self.supp_uvio_cmds.is_set(nr)
+            && (nr == ffi::UVIO_IOCTL_UVDEV_INFO_NR
+                || self
+                    .supp_uv_cmds
+                    .as_ref()
+                    .map_or(true, |cmds| cmds.is_set(nr)))
+    }
+
+    /// Returns `true` if the uvdevice supports the IOCTL of the command `C`.
+    pub fn supports_ioctl<C: UvCmd>(&self) -> bool {
+        self.supp_uvio_cmds.is_set(C::UV_IOCTL_NR)
+    }
+
+    /// Returns `true` if the uvdevice and the Ultravisor support the command `C`.
+    ///
+    /// If the Ultravisor support is not known, e.g. on older kernels, only the support of the
+    /// uvdevice is considered.
+    pub fn supports<C: UvCmd>(&self) -> bool {
+        self.supports_nr(C::UV_IOCTL_NR)
+    }
+
+    /// Returns the names of all commands supported by the uvdevice and the Ultravisor.
+    pub fn supported_cmds(&self) -> Vec<&'static str> {
+        (0u8..64)
+            .filter(|nr| self.supports_nr(*nr))
+            .map(nr_as_string)
+            .collect()
+    }
+}
+
+/// Returns `true` if the IOCTL `nr` failed with `errno` because it is not supported.
+///
+/// Older uvdevices report unknown IOCTLs with `ENOTTY`. `EINVAL` is only treated as unsupported
+/// if the uvdevice information confirms it.
+pub(crate) fn ioctl_unsupported(
+    nr: u8,
+    errno: i32,
+    info: impl FnOnce() -> Option<UvDeviceInfo>,
+) -> bool {
+    match errno {
+        libc::ENOTTY => true,
+        libc::EINVAL if nr != ffi::UVIO_IOCTL_UVDEV_INFO_NR => {
+            info().is_some_and(|info| !info.supports_nr(nr))
+        }
+        _ => false,
+    }
 }
 
 impl From<uvio_uvdev_info> for UvDeviceInfo {
     fn from(value: uvio_uvdev_info) -> Self {
         Self {
-            supp_uvio_cmds: value.supp_uvio_cmds.into(),
-            supp_uv_cmds: Some(value.supp_uv_cmds.into()),
+            supp_uvio_cmds: value.supp_uvio_cmds.get().into(),
+            supp_uv_cmds: Some(value.supp_uv_cmds.get().into()),
+            read_only: false,
         }
     }
 }
@@ -80,23 +188,28 @@ impl UvCmd for uvio_uvdev_info {
     fn rc_fmt(&self, _: u16, _: u16) -> Option<&'static str> {
         None
     }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
 }
 
-fn nr_as_string(nr: u8) -> Option<&'static str> {
+pub(crate) fn nr_as_string(nr: u8) -> &'static str {
     match nr {
-        ffi::UVIO_IOCTL_UVDEV_INFO_NR => Some("Info"),
-        ffi::UVIO_IOCTL_ATT_NR => Some("Attestation"),
-        ffi::UVIO_IOCTL_ADD_SECRET_NR => Some("Add Secret"),
-        ffi::UVIO_IOCTL_LIST_SECRETS_NR => Some("List Secrets"),
-        ffi::UVIO_IOCTL_LOCK_SECRETS_NR => Some("Lock Secret Store"),
-        _ => None,
+        ffi::UVIO_IOCTL_UVDEV_INFO_NR => "Info",
+        ffi::UVIO_IOCTL_ATT_NR => "Attestation",
+        ffi::UVIO_IOCTL_ADD_SECRET_NR => "Add Secret",
+        ffi::UVIO_IOCTL_LIST_SECRETS_NR => "List Secrets",
+        ffi::UVIO_IOCTL_LOCK_SECRETS_NR => "Lock Secret Store",
+        ffi::UVIO_IOCTL_RETR_SECRET_NR => "Retrieve Secret",
+        _ => "Unknown",
     }
 }
 
 fn print_uvdevice_cmd(nr: u8, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match nr_as_string(nr) {
-        Some(s) => write!(f, "{s}"),
-        None => write!(f, "Unknown ({nr})"),
+        "Unknown" => write!(f, "Unknown ({nr})"),
+        s => write!(f, "{s}"),
     }
 }
 
@@ -121,6 +234,11 @@ fn parse_flags(uv_cmds: &Lsb0Flags64, f: &mut std::fmt::Formatter<'_>) -> std::f
 }
 impl Display for UvDeviceInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.read_only {
+            writeln!(f, "uvdevice not available, read-only view from sysfs")?;
+            write!(f, "Ultravisor-calls available:")?;
+            return parse_flags(&self.supp_uvio_cmds, f);
+        }
         write!(f, "uvdevice supports:")?;
         parse_flags(&self.supp_uvio_cmds, f)?;
         writeln!(f, "Ultravisor-calls available:")?;
@@ -130,3 +248,84 @@ impl Display for UvDeviceInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn info(uvio: u64, uv: Option<u64>) -> UvDeviceInfo {
+        UvDeviceInfo {
+            supp_uvio_cmds: uvio.into(),
+            supp_uv_cmds: uv.map(Into::into),
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn supports() {
+        let i = info(0b11_1111, Some(0b01_1110));
+        assert!(i.supports_nr(ffi::UVIO_IOCTL_UVDEV_INFO_NR));
+        assert!(i.supports_nr(ffi::UVIO_IOCTL_LOCK_SECRETS_NR));
+        assert!(!i.supports_nr(ffi::UVIO_IOCTL_RETR_SECRET_NR));
+        assert!(i.supports_ioctl::<uvio_uvdev_info>());
+        assert_eq!(
+            i.supported_cmds(),
+            [
+                "Info",
+                "Attestation",
+                "Add Secret",
+                "List Secrets",
+                "Lock Secret Store"
+            ]
+        );
+
+        let i = info(0b10, None);
+        assert!(i.supports_nr(ffi::UVIO_IOCTL_ATT_NR));
+        assert!(!i.supports_nr(ffi::UVIO_IOCTL_ADD_SECRET_NR));
+    }
+
+    #[test]
+    fn from_query() {
+        assert!(UvDeviceInfo::from_query(&UvQueryInfo::default()).is_none());
+
+        let dir = std::env::temp_dir().join(format!("pv_uv_info_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Query, Attestation, Add Secret, List Secrets, Retrieve Secret
+        std::fs::write(dir.join("facilities"), "8000000e40000000\n").unwrap();
+        let query = UvQueryInfo::from_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let i = UvDeviceInfo::from_query(&query).unwrap();
+        assert!(i.is_read_only());
+        assert_eq!(
+            i.supported_cmds(),
+            [
+                "Attestation",
+                "Add Secret",
+                "List Secrets",
+                "Retrieve Secret"
+            ]
+        );
+        assert!(!i.supports_nr(ffi::UVIO_IOCTL_UVDEV_INFO_NR));
+        assert!(!i.supports_nr(ffi::UVIO_IOCTL_LOCK_SECRETS_NR));
+        assert_eq!(
+            i.to_string(),
+            "uvdevice not available, read-only view from sysfs\n\
+             Ultravisor-calls available:Attestation, Add Secret, List Secrets, Retrieve Secret\n"
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        let nr = ffi::UVIO_IOCTL_RETR_SECRET_NR;
+        assert!(ioctl_unsupported(nr, libc::ENOTTY, || unreachable!()));
+        assert!(!ioctl_unsupported(nr, libc::EFAULT, || unreachable!()));
+        assert!(ioctl_unsupported(nr, libc::EINVAL, || Some(info(
+            0b01_1111, None
+        ))));
+        assert!(!ioctl_unsupported(nr, libc::EINVAL, || Some(info(
+            0b11_1111, None
+        ))));
+        assert!(!ioctl_unsupported(nr, libc::EINVAL, || None));
+    }
+}
@@ -0,0 +1,323 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// Upper bounds of the ioctl latency buckets in seconds
+const LATENCY_BUCKETS: [f64; 10] = [0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+/// Upper bounds of the request size buckets in bytes
+const SIZE_BUCKETS: [f64; 7] = [0.0, 64.0, 256.0, 1024.0, 4096.0, 16384.0, 65536.0];
+
+#[derive(Debug, Clone, PartialEq)]
+struct Histogram<const N: usize> {
+    bounds: &'static [f64; N],
+    buckets: [u64; N],
+    sum: f64,
+    count: u64,
+}
+
+impl<const N: usize> Histogram<N> {
+    const fn new(bounds: &'static [f64; N]) -> Self {
+        Self {
+            bounds,
+            buckets: [0; N],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        self.bounds
+            .iter()
+            .zip(self.buckets.iter_mut())
+            .filter(|(bound, _)| value <= **bound)
+            .for_each(|(_, bucket)| *bucket += 1);
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn write(&self, out: &mut String, name: &str, cmd: &str) -> fmt::Result {
+        for (bound, bucket) in self.bounds.iter().zip(self.buckets) {
+            writeln!(
+                out,
+                "{name}_bucket{{cmd=\"{cmd}\",le=\"{bound}\"}} {bucket}"
+            )?;
+        }
+        writeln!(
+            out,
+            "{name}_bucket{{cmd=\"{cmd}\",le=\"+Inf\"}} {}",
+            self.count
+        )?;
+        writeln!(out, "{name}_sum{{cmd=\"{cmd}\"}} {}", self.sum)?;
+        writeln!(out, "{name}_count{{cmd=\"{cmd}\"}} {}", self.count)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CmdMetrics {
+    calls: u64,
+    ioctl_errors: u64,
+    /// UV failures by (rc, rrc)
+    failures: BTreeMap<(u16, u16), u64>,
+    latency: Histogram<{ LATENCY_BUCKETS.len() }>,
+    request_size: Histogram<{ SIZE_BUCKETS.len() }>,
+}
+
+impl CmdMetrics {
+    fn new() -> Self {
+        Self {
+            calls: 0,
+            ioctl_errors: 0,
+            failures: BTreeMap::new(),
+            latency: Histogram::new(&LATENCY_BUCKETS),
+            request_size: Histogram::new(&SIZE_BUCKETS),
+        }
+    }
+}
+
+/// Outcome of a UV call as seen by the metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallOutcome {
+    /// The UV call completed successfully
+    Success,
+    /// The UV call completed with an error rc/rrc
+    Uv {
+        /// Return code
+        rc: u16,
+        /// Return reason code
+        rrc: u16,
+    },
+    /// The ioctl itself failed
+    Ioctl,
+}
+
+/// A UV call as reported to a [`UvObserver`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UvCall {
+    /// Name of the command, e.g. "Attestation"
+    pub cmd: &'static str,
+    /// uvdevice IOCTL number of the command
+    pub nr: u8,
+    /// Duration of the ioctl
+    pub duration: Duration,
+    /// Size of the request data passed to the UV
+    pub request_size: usize,
+    /// Number of bytes the UV wrote, if the call succeeded and the command reports it
+    pub response_size: Option<usize>,
+    /// Outcome of the call
+    pub outcome: CallOutcome,
+}
+
+/// Observer of the UV calls of a [`crate::uv::UvDevice`]
+///
+/// Invoked after every ioctl, including retries, so that applications can feed the calls into
+/// their own telemetry without this crate depending on a metrics stack. The observer runs on the
+/// calling thread and should return quickly. Closures taking a [`UvCall`] are observers.
+///
+/// Set it with [`crate::uv::UvDevice::set_observer`].
+pub trait UvObserver: Send + Sync {
+    /// Called after the UV call `call` finished.
+    fn on_call(&self, call: &UvCall);
+}
+
+impl<F> UvObserver for F
+where
+    F: Fn(&UvCall) + Send + Sync,
+{
+    fn on_call(&self, call: &UvCall) {
+        self(call)
+    }
+}
+
+impl fmt::Debug for dyn UvObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UvObserver")
+    }
+}
+
+/// Metrics about the UV calls issued via the uvdevice by this process.
+///
+/// Collected for every [`crate::uv::UvDevice::send_cmd`] call. Use [`UvMetrics::global`] to
+/// access them and [`UvMetrics::encode_prometheus`] to export them, e.g. on a metrics endpoint of a
+/// long-running service.
+#[derive(Debug)]
+pub struct UvMetrics {
+    cmds: Mutex<BTreeMap<&'static str, CmdMetrics>>,
+}
+
+static METRICS: UvMetrics = UvMetrics::new();
+
+impl UvMetrics {
+    const fn new() -> Self {
+        Self {
+            cmds: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// The metrics of this process.
+    pub fn global() -> &'static Self {
+        &METRICS
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<&'static str, CmdMetrics>> {
+        // Metrics are always consistent, even if a panic happened while holding the lock.
+        match self.cmds.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    pub(crate) fn record(
+        &self,
+        cmd: &'static str,
+        request_size: usize,
+        latency: Duration,
+        outcome: CallOutcome,
+    ) {
+        let mut cmds = self.lock();
+        let m = cmds.entry(cmd).or_insert_with(CmdMetrics::new);
+        m.calls += 1;
+        m.latency.observe(latency.as_secs_f64());
+        m.request_size.observe(request_size as f64);
+        match outcome {
+            CallOutcome::Success => (),
+            CallOutcome::Uv { rc, rrc } => *m.failures.entry((rc, rrc)).or_default() += 1,
+            CallOutcome::Ioctl => m.ioctl_errors += 1,
+        }
+    }
+
+    /// Number of UV calls of the given command, e.g. "Attestation".
+    pub fn calls(&self, cmd: &str) -> u64 {
+        self.lock().get(cmd).map_or(0, |m| m.calls)
+    }
+
+    /// Encodes the metrics in the Prometheus text exposition format.
+    pub fn encode_prometheus(&self) -> String {
+        let cmds = self.lock().clone();
+        let mut out = String::new();
+        // Writing into a String cannot fail.
+        let _ = Self::write_prometheus(&cmds, &mut out);
+        out
+    }
+
+    fn write_prometheus(cmds: &BTreeMap<&str, CmdMetrics>, out: &mut String) -> fmt::Result {
+        writeln!(
+            out,
+            "# HELP uv_calls_total Number of UV calls issued via the uvdevice."
+        )?;
+        writeln!(out, "# TYPE uv_calls_total counter")?;
+        for (cmd, m) in cmds {
+            writeln!(out, "uv_calls_total{{cmd=\"{cmd}\"}} {}", m.calls)?;
+        }
+
+        writeln!(
+            out,
+            "# HELP uv_call_failures_total Number of UV calls that completed with an error."
+        )?;
+        writeln!(out, "# TYPE uv_call_failures_total counter")?;
+        for (cmd, m) in cmds {
+            for ((rc, rrc), n) in &m.failures {
+                writeln!(
+                    out,
+                    "uv_call_failures_total{{cmd=\"{cmd}\",rc=\"{rc:#06x}\",rrc=\"{rrc:#06x}\"}} {n}"
+                )?;
+            }
+        }
+
+        writeln!(
+            out,
+            "# HELP uv_ioctl_errors_total Number of uvdevice ioctls that failed."
+        )?;
+        writeln!(out, "# TYPE uv_ioctl_errors_total counter")?;
+        for (cmd, m) in cmds {
+            writeln!(
+                out,
+                "uv_ioctl_errors_total{{cmd=\"{cmd}\"}} {}",
+                m.ioctl_errors
+            )?;
+        }
+
+        writeln!(
+            out,
+            "# HELP uv_ioctl_duration_seconds Duration of uvdevice ioctls."
+        )?;
+        writeln!(out, "# TYPE uv_ioctl_duration_seconds histogram")?;
+        for (cmd, m) in cmds {
+            m.latency.write(out, "uv_ioctl_duration_seconds", cmd)?;
+        }
+
+        writeln!(
+            out,
+            "# HELP uv_request_size_bytes Size of the request data passed to the UV."
+        )?;
+        writeln!(out, "# TYPE uv_request_size_bytes histogram")?;
+        for (cmd, m) in cmds {
+            m.request_size.write(out, "uv_request_size_bytes", cmd)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn histogram() {
+        let mut h = Histogram::new(&[1.0, 2.0, 4.0]);
+        h.observe(0.5);
+        h.observe(2.0);
+        h.observe(8.0);
+        assert_eq!(h.buckets, [1, 2, 2]);
+        assert_eq!(h.count, 3);
+        assert_eq!(h.sum, 10.5);
+    }
+
+    #[test]
+    fn prometheus() {
+        let metrics = UvMetrics::new();
+        metrics.record(
+            "Attestation",
+            0x1000,
+            Duration::from_millis(2),
+            CallOutcome::Success,
+        );
+        metrics.record(
+            "Attestation",
+            0x1000,
+            Duration::from_millis(20),
+            CallOutcome::Uv {
+                rc: 0x101,
+                rrc: 0x2,
+            },
+        );
+        metrics.record("List Secrets", 0, Duration::ZERO, CallOutcome::Ioctl);
+        assert_eq!(metrics.calls("Attestation"), 2);
+        assert_eq!(metrics.calls("Add Secret"), 0);
+
+        let out = metrics.encode_prometheus();
+        for exp in [
+            "# TYPE uv_calls_total counter\n",
+            "uv_calls_total{cmd=\"Attestation\"} 2\n",
+            "uv_calls_total{cmd=\"List Secrets\"} 1\n",
+            "uv_call_failures_total{cmd=\"Attestation\",rc=\"0x0101\",rrc=\"0x0002\"} 1\n",
+            "uv_ioctl_errors_total{cmd=\"Attestation\"} 0\n",
+            "uv_ioctl_errors_total{cmd=\"List Secrets\"} 1\n",
+            "uv_ioctl_duration_seconds_bucket{cmd=\"Attestation\",le=\"0.005\"} 1\n",
+            "uv_ioctl_duration_seconds_bucket{cmd=\"Attestation\",le=\"+Inf\"} 2\n",
+            "uv_ioctl_duration_seconds_count{cmd=\"Attestation\"} 2\n",
+            "uv_request_size_bytes_bucket{cmd=\"Attestation\",le=\"4096\"} 2\n",
+            "uv_request_size_bytes_bucket{cmd=\"List Secrets\",le=\"0\"} 1\n",
+            "uv_request_size_bytes_sum{cmd=\"Attestation\"} 8192\n",
+        ] {
+            assert!(out.contains(exp), "missing {exp:?} in\n{out}");
+        }
+    }
+}
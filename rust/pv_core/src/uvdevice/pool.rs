@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use super::{UvCmd, UvDevice, UvcResponse};
+use crate::{Error, Result};
+use std::{
+    collections::VecDeque,
+    num::NonZeroUsize,
+    ops::Deref,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+};
+
+#[derive(Debug)]
+struct PoolState {
+    idle: VecDeque<UvDevice>,
+    in_flight: usize,
+    /// Ticket that will be handed out to the next waiting caller
+    next_ticket: u64,
+    /// Ticket of the caller that is allowed to take the next device
+    serving: u64,
+}
+
+#[derive(Debug)]
+struct PoolInner {
+    state: Mutex<PoolState>,
+    available: Condvar,
+    max_in_flight: usize,
+    size: usize,
+}
+
+impl PoolInner {
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        // The state is always consistent; a panic while holding the lock cannot corrupt it.
+        match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn can_take(&self, state: &PoolState) -> bool {
+        state.in_flight < self.max_in_flight && !state.idle.is_empty()
+    }
+
+    fn take(&self, state: &mut PoolState) -> UvDevice {
+        state.in_flight += 1;
+        // Cannot fail, checked by `can_take`.
+        state.idle.pop_front().unwrap()
+    }
+
+    fn put_back(&self, dev: UvDevice) {
+        let mut state = self.lock();
+        state.in_flight -= 1;
+        state.idle.push_back(dev);
+        drop(state);
+        self.available.notify_all();
+    }
+}
+
+/// A pool of opened [`UvDevice`] handles.
+///
+/// The pool opens several handles of the uvdevice and hands them out to concurrent tasks. At most
+/// `max_in_flight` handles are handed out at the same time; callers that cannot get a handle
+/// immediately wait in first-come, first-served order. This smooths latency spikes when many
+/// threads issue Ultravisor calls, e.g. in an attestation server under heavy load.
+///
+/// The pool is cheap to clone; all clones share the same handles.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use s390_pv_core::uv::{ListCmd, UvDevicePool};
+/// # use std::num::NonZeroUsize;
+/// # fn main() -> s390_pv_core::Result<()> {
+/// let pool = UvDevicePool::open(NonZeroUsize::new(4).unwrap())?
+///     .with_max_in_flight(NonZeroUsize::new(2).unwrap())?;
+/// let uv = pool.get();
+/// uv.send_cmd(&mut ListCmd::new())?;
+/// // `uv` is returned to the pool when dropped
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UvDevicePool(Arc<PoolInner>);
+
+impl UvDevicePool {
+    /// Open `size` handles of the uvdevice located at `/dev/uv`.
+    ///
+    /// The number of handles in flight is limited by `size`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the device file cannot be opened.
+    pub fn open(size: NonZeroUsize) -> Result<Self> {
+        let devices = (0..size.get())
+            .map(|_| UvDevice::open())
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_devices(devices)
+    }
+
+    /// Create a pool from already opened [`UvDevice`]s.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `devices` is empty.
+    pub fn from_devices(devices: Vec<UvDevice>) -> Result<Self> {
+        let size = devices.len();
+        if size == 0 {
+            return Err(Error::Specification(
+                "A uvdevice pool requires at least one device".to_string(),
+            ));
+        }
+        Ok(Self(Arc::new(PoolInner {
+            state: Mutex::new(PoolState {
+                idle: devices.into(),
+                in_flight: 0,
+                next_ticket: 0,
+                serving: 0,
+            }),
+            available: Condvar::new(),
+            max_in_flight: size,
+            size,
+        })))
+    }
+
+    /// Limit the number of handles that are handed out at the same time.
+    ///
+    /// The limit is capped at the number of handles in this pool.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the pool is already shared, i.e. it was cloned or a
+    /// handle is currently in use.
+    pub fn with_max_in_flight(mut self, max: NonZeroUsize) -> Result<Self> {
+        let inner = Arc::get_mut(&mut self.0).ok_or_else(|| {
+            Error::Specification(
+                "The in-flight limit must be set before the pool is shared".to_string(),
+            )
+        })?;
+        inner.max_in_flight = max.get().min(inner.size);
+        Ok(self)
+    }
+
+    /// Number of handles in this pool.
+    pub fn size(&self) -> usize {
+        self.0.size
+    }
+
+    /// Maximum number of handles that are handed out at the same time.
+    pub fn max_in_flight(&self) -> usize {
+        self.0.max_in_flight
+    }
+
+    /// Number of handles currently handed out.
+    pub fn in_flight(&self) -> usize {
+        self.0.lock().in_flight
+    }
+
+    /// Get a handle from the pool.
+    ///
+    /// Blocks until a handle is available. Waiting callers are served in the order they called
+    /// this function. The handle is returned to the pool when the [`PooledUvDevice`] is dropped.
+    pub fn get(&self) -> PooledUvDevice {
+        let inner = &self.0;
+        let mut state = inner.lock();
+        let ticket = state.next_ticket;
+        state.next_ticket = state.next_ticket.wrapping_add(1);
+        while state.serving != ticket || !inner.can_take(&state) {
+            state = match inner.available.wait(state) {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+        }
+        state.serving = state.serving.wrapping_add(1);
+        let dev = inner.take(&mut state);
+        drop(state);
+        // The next ticket may be able to take a handle as well.
+        inner.available.notify_all();
+        PooledUvDevice {
+            dev: Some(dev),
+            pool: Arc::clone(inner),
+        }
+    }
+
+    /// Get a handle from the pool if one is available immediately.
+    ///
+    /// Returns [`None`] if all handles are in use or other callers are already waiting.
+    pub fn try_get(&self) -> Option<PooledUvDevice> {
+        let inner = &self.0;
+        let mut state = inner.lock();
+        if state.serving != state.next_ticket || !inner.can_take(&state) {
+            return None;
+        }
+        let dev = inner.take(&mut state);
+        Some(PooledUvDevice {
+            dev: Some(dev),
+            pool: Arc::clone(inner),
+        })
+    }
+
+    /// Send an Ultravisor Command via a handle of this pool.
+    ///
+    /// Waits for a free handle, see [`UvDevicePool::get`], and then behaves like
+    /// [`UvDevice::send_cmd`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the IOCTL fails or the Ultravisor does not report
+    /// a success.
+    pub fn send_cmd<C: UvCmd>(&self, cmd: &mut C) -> Result<UvcResponse> {
+        self.get().send_cmd(cmd)
+    }
+}
+
+/// A [`UvDevice`] handle borrowed from a [`UvDevicePool`].
+///
+/// Dereferences to [`UvDevice`]. The handle is returned to the pool on drop.
+#[derive(Debug)]
+pub struct PooledUvDevice {
+    dev: Option<UvDevice>,
+    pool: Arc<PoolInner>,
+}
+
+impl Deref for PooledUvDevice {
+    type Target = UvDevice;
+
+    fn deref(&self) -> &Self::Target {
+        // Only `None` during drop
+        self.dev.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledUvDevice {
+    fn drop(&mut self) {
+        if let Some(dev) = self.dev.take() {
+            self.pool.put_back(dev);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+        time::Duration,
+    };
+
+    fn pool(size: usize) -> UvDevicePool {
+        UvDevicePool::from_devices((0..size).map(|_| UvDevice::test_dev()).collect()).unwrap()
+    }
+
+    fn nz(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    #[test]
+    fn empty() {
+        assert!(matches!(
+            UvDevicePool::from_devices(vec![]),
+            Err(Error::Specification(_))
+        ));
+    }
+
+    #[test]
+    fn max_in_flight_capped() {
+        let pool = pool(2).with_max_in_flight(nz(5)).unwrap();
+        assert_eq!(pool.size(), 2);
+        assert_eq!(pool.max_in_flight(), 2);
+    }
+
+    #[test]
+    fn max_in_flight_shared() {
+        let pool = pool(2);
+        let _clone = pool.clone();
+        assert!(matches!(
+            pool.with_max_in_flight(nz(1)),
+            Err(Error::Specification(_))
+        ));
+    }
+
+    #[test]
+    fn get_and_return() {
+        let pool = pool(2).with_max_in_flight(nz(1)).unwrap();
+        let dev = pool.get();
+        assert_eq!(pool.in_flight(), 1);
+        assert!(pool.try_get().is_none());
+        drop(dev);
+        assert_eq!(pool.in_flight(), 0);
+        assert!(pool.try_get().is_some());
+        assert_eq!(pool.in_flight(), 0);
+    }
+
+    #[test]
+    fn limit_in_flight() {
+        let pool = pool(4).with_max_in_flight(nz(2)).unwrap();
+        let current = Arc::new(AtomicUsize::new(0));
+        let max_seen = Arc::new(AtomicUsize::new(0));
+
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let pool = pool.clone();
+                let current = Arc::clone(&current);
+                let max_seen = Arc::clone(&max_seen);
+                thread::spawn(move || {
+                    let _dev = pool.get();
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_seen.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(5));
+                    current.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        workers.into_iter().for_each(|w| w.join().unwrap());
+
+        assert!(max_seen.load(Ordering::SeqCst) <= 2);
+        assert_eq!(pool.in_flight(), 0);
+    }
+}
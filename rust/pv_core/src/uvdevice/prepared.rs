@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use log::debug;
+
+use super::{UvCmd, UvDevice, UvcResponse};
+use crate::Result;
+
+/// Calls `f` with the address and length of each buffer of `cmd`.
+fn for_each_buffer<C: UvCmd>(
+    cmd: &C,
+    f: unsafe extern "C" fn(*const libc::c_void, libc::size_t) -> libc::c_int,
+) -> bool {
+    cmd.buffers()
+        .iter()
+        .filter(|buf| !buf.is_empty())
+        // SAFETY: The buffers are owned by `cmd` and valid for their length. Locking or unlocking
+        // memory does not change its content.
+        .all(|buf| unsafe { f(buf.as_ptr() as *const libc::c_void, buf.len()) } == 0)
+}
+
+/// An Ultravisor command prepared for repeated execution
+///
+/// Sending a freshly constructed command allocates and zero-fills its buffers every time. A
+/// [`PreparedCmd`] keeps the command and its buffers between executions, so that e.g. a server
+/// loop can list the secrets or attest the guest without allocating. The buffers the Ultravisor
+/// accesses, see [`UvCmd::buffers`], are locked into memory (`mlock`) on creation, so that the
+/// kernel does not have to fault them in for every call. Locking is best-effort, a failure, e.g.
+/// because `RLIMIT_MEMLOCK` is exhausted, is only logged.
+///
+/// Before each execution, [`UvCmd::reset`] prepares the command to be sent again.
+///
+/// ```rust,no_run
+/// # use s390_pv_core::uv::{ListCmd, PreparedCmd, SecretList, UvDevice};
+/// # fn main() -> s390_pv_core::Result<()> {
+/// let uv = UvDevice::open()?;
+/// let mut list = PreparedCmd::new(ListCmd::new());
+/// loop {
+///     list.send(&uv)?;
+///     let secrets = SecretList::try_from(list.cmd())?;
+///     // do something with the secrets
+/// #   break;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PreparedCmd<C: UvCmd> {
+    // Boxed, so that the addresses of the buffers do not change if this struct moves
+    cmd: Box<C>,
+    locked: bool,
+}
+
+impl<C: UvCmd> PreparedCmd<C> {
+    /// Prepares `cmd` for repeated execution and locks its buffers into memory.
+    pub fn new(cmd: C) -> Self {
+        let cmd = Box::new(cmd);
+        let locked = for_each_buffer(cmd.as_ref(), libc::mlock);
+        if !locked {
+            debug!(
+                "Cannot lock the buffers of a prepared command: {}",
+                std::io::Error::last_os_error()
+            );
+            for_each_buffer(cmd.as_ref(), libc::munlock);
+        }
+        Self { cmd, locked }
+    }
+
+    /// Send the command via `uv`, see [`UvDevice::send_cmd`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the IOCTL fails or the Ultravisor does not report
+    /// a success.
+    pub fn send(&mut self, uv: &UvDevice) -> Result<UvcResponse> {
+        self.cmd.reset();
+        uv.send_cmd(self.cmd.as_mut())
+    }
+
+    /// Returns the command, e.g. to access the results of the last execution.
+    pub fn cmd(&self) -> &C {
+        &self.cmd
+    }
+
+    /// Returns the command mutably, e.g. to change its input for the next execution.
+    pub fn cmd_mut(&mut self) -> &mut C {
+        &mut self.cmd
+    }
+
+    /// Returns `true` if the buffers of the command are locked into memory.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Unlocks the buffers and returns the command.
+    pub fn into_inner(mut self) -> C {
+        self.unlock();
+        let this = std::mem::ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped afterwards, hence the command is moved out
+        // exactly once.
+        *unsafe { std::ptr::read(&this.cmd) }
+    }
+
+    fn unlock(&mut self) {
+        if std::mem::take(&mut self.locked) {
+            for_each_buffer(self.cmd.as_ref(), libc::munlock);
+        }
+    }
+}
+
+impl<C: UvCmd> Drop for PreparedCmd<C> {
+    fn drop(&mut self) {
+        self.unlock();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::uv::ListCmd;
+
+    #[test]
+    fn into_inner() {
+        let prep = PreparedCmd::new(ListCmd::with_pages(2));
+        let mut cmd = prep.into_inner();
+        assert_eq!(cmd.arg().len(), 2 * crate::PAGESIZE);
+    }
+}
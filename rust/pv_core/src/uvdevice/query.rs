@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::path::Path;
+
+use super::UvFlags;
+use crate::misc::Flags;
+
+/// Names of the Ultravisor calls by their bit in the installed-calls facilities
+const UV_CALLS: &[(u8, &str)] = &[
+    (0, "Query Ultravisor Information"),
+    (1, "Initialize Ultravisor"),
+    (2, "Create Secure Configuration"),
+    (3, "Destroy Secure Configuration"),
+    (4, "Create Secure CPU"),
+    (5, "Destroy Secure CPU"),
+    (6, "Convert To Secure Storage"),
+    (7, "Convert From Secure Storage"),
+    (8, "Set Shared Access"),
+    (9, "Remove Shared Access"),
+    (11, "Set Secure Configuration Parameters"),
+    (13, "Unpack Image"),
+    (14, "CPU Reset"),
+    (15, "CPU Reset Initial"),
+    (16, "Prepare Reset"),
+    (17, "CPU Perform Clear Reset"),
+    (18, "CPU Set State"),
+    (20, "Set Unshare All"),
+    (21, "Pin Page Shared"),
+    (22, "Unpin Page Shared"),
+    (23, "Destroy Secure Configuration Fast"),
+    (24, "Dump Initialize"),
+    (25, "Dump Configuration Storage State"),
+    (26, "Dump CPU"),
+    (27, "Dump Complete"),
+    (28, "Retrieve Attestation Measurement"),
+    (29, "Add Secret"),
+    (30, "List Secrets"),
+    (31, "Lock Secret Store"),
+    (33, "Retrieve Secret"),
+];
+
+/// Names of the Ultravisor features by their bit in the feature indications
+const UV_FEATURES: &[(u8, &str)] = &[
+    (0, "Misaligned guest storage"),
+    (1, "Adapter interruption virtualization"),
+    (4, "AP pass-through"),
+    (5, "AP interruption virtualization"),
+];
+
+fn names(flags: &UvFlags, table: &[(u8, &'static str)]) -> Vec<String> {
+    (0u8..64)
+        .filter(|bit| flags.is_set(*bit))
+        .map(|bit| match table.iter().find(|(b, _)| *b == bit) {
+            Some((_, name)) => name.to_string(),
+            None => format!("Unknown ({bit})"),
+        })
+        .collect()
+}
+
+/// Ultravisor information the kernel reports via `/sys/firmware/uv/query`
+///
+/// Values the kernel does not provide, e.g. because of an older kernel or because the system is
+/// no Secure Execution guest, are [`None`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UvQueryInfo {
+    facilities: Option<UvFlags>,
+    feature_indications: Option<UvFlags>,
+    supp_att_pflags: Option<u64>,
+    supp_att_mai: Option<UvFlags>,
+    supp_secret_types: Option<UvFlags>,
+    max_secrets: Option<u32>,
+    max_assoc_secrets: Option<u32>,
+    max_retr_secrets: Option<u32>,
+}
+
+impl UvQueryInfo {
+    /// Directory the kernel uses to report the Ultravisor query information
+    pub const SYSFS_QUERY_DIR: &'static str = "/sys/firmware/uv/query";
+
+    /// Read the Ultravisor query information from sysfs.
+    pub fn read() -> Self {
+        Self::from_dir(Path::new(Self::SYSFS_QUERY_DIR))
+    }
+
+    pub(crate) fn from_dir(dir: &Path) -> Self {
+        let read = |name: &str| -> Option<String> {
+            std::fs::read_to_string(dir.join(name))
+                .ok()
+                .map(|s| s.trim().to_string())
+        };
+        let read_hex = |name: &str| read(name).and_then(|s| u64::from_str_radix(&s, 16).ok());
+        let read_dec = |name: &str| read(name).and_then(|s| s.parse().ok());
+        Self {
+            facilities: read_hex("facilities").map(UvFlags::from),
+            feature_indications: read_hex("feature_indications").map(UvFlags::from),
+            supp_att_pflags: read_hex("supp_att_pflags"),
+            supp_att_mai: read_hex("supp_att_mai").map(UvFlags::from),
+            supp_secret_types: read_hex("supp_secret_types").map(UvFlags::from),
+            max_secrets: read_dec("max_secrets"),
+            max_assoc_secrets: read_dec("max_assoc_secrets"),
+            max_retr_secrets: read_dec("max_retr_secrets"),
+        }
+    }
+
+    /// Returns the installed Ultravisor facilities.
+    pub fn facilities(&self) -> Option<&UvFlags> {
+        self.facilities.as_ref()
+    }
+
+    /// Returns the names of the installed Ultravisor calls.
+    ///
+    /// Bits without a known name are reported as `Unknown (<bit>)`.
+    pub fn installed_calls(&self) -> Option<Vec<String>> {
+        self.facilities.as_ref().map(|f| names(f, UV_CALLS))
+    }
+
+    /// Returns the Ultravisor feature indications.
+    pub fn feature_indications(&self) -> Option<&UvFlags> {
+        self.feature_indications.as_ref()
+    }
+
+    /// Returns the names of the indicated Ultravisor features.
+    ///
+    /// Bits without a known name are reported as `Unknown (<bit>)`.
+    pub fn features(&self) -> Option<Vec<String>> {
+        self.feature_indications
+            .as_ref()
+            .map(|f| names(f, UV_FEATURES))
+    }
+
+    /// Returns the supported plaintext attestation flags.
+    pub fn supp_att_pflags(&self) -> Option<u64> {
+        self.supp_att_pflags
+    }
+
+    /// Returns the supported attestation measurement algorithm identifiers.
+    ///
+    /// Bit `n` is set if the Ultravisor supports the identifier `n`.
+    pub fn supp_att_mai(&self) -> Option<&UvFlags> {
+        self.supp_att_mai.as_ref()
+    }
+
+    /// Returns the supported secret types.
+    pub fn supp_secret_types(&self) -> Option<&UvFlags> {
+        self.supp_secret_types.as_ref()
+    }
+
+    /// Returns the maximum number of secrets.
+    pub fn max_secrets(&self) -> Option<u32> {
+        self.max_secrets
+    }
+
+    /// Returns the maximum number of association secrets.
+    pub fn max_assoc_secrets(&self) -> Option<u32> {
+        self.max_assoc_secrets
+    }
+
+    /// Returns the maximum number of retrievable secrets.
+    pub fn max_retr_secrets(&self) -> Option<u32> {
+        self.max_retr_secrets
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_dir() {
+        let dir = std::env::temp_dir().join(format!("pv_uv_query_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("facilities"), "e8a0000000000000\n").unwrap();
+        std::fs::write(dir.join("feature_indications"), "c400000000000000\n").unwrap();
+        std::fs::write(dir.join("supp_att_pflags"), "3c00000000000000\n").unwrap();
+        std::fs::write(dir.join("supp_att_mai"), "4000000000000000\n").unwrap();
+        std::fs::write(dir.join("max_secrets"), "5\n").unwrap();
+        std::fs::write(dir.join("max_retr_secrets"), "invalid\n").unwrap();
+
+        let info = UvQueryInfo::from_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(info.facilities().unwrap().is_set(0));
+        assert!(!info.facilities().unwrap().is_set(3));
+        assert_eq!(
+            info.installed_calls().unwrap(),
+            [
+                "Query Ultravisor Information",
+                "Initialize Ultravisor",
+                "Create Secure Configuration",
+                "Create Secure CPU",
+                "Set Shared Access",
+                "Unknown (10)",
+            ]
+        );
+        assert_eq!(
+            info.features().unwrap(),
+            [
+                "Misaligned guest storage",
+                "Adapter interruption virtualization",
+                "AP interruption virtualization",
+            ]
+        );
+        assert_eq!(info.supp_att_pflags(), Some(0x3c00000000000000));
+        assert!(info.supp_att_mai().unwrap().is_set(1));
+        assert_eq!(info.supp_secret_types(), None);
+        assert_eq!(info.max_secrets(), Some(5));
+        assert_eq!(info.max_assoc_secrets(), None);
+        assert_eq!(info.max_retr_secrets(), None);
+    }
+
+    #[test]
+    fn no_dir() {
+        assert_eq!(
+            UvQueryInfo::from_dir(Path::new("/does/not/exist")),
+            UvQueryInfo::default()
+        );
+    }
+}
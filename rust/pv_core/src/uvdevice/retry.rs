@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::time::Duration;
+
+use super::UvCmd;
+use crate::Error;
+
+/// Errnos of the uvdevice IOCTL that indicate a temporary condition
+const TRANSIENT_ERRNOS: [i32; 3] = [libc::EAGAIN, libc::EBUSY, libc::EINTR];
+
+/// How a [`super::UvDevice`] retries commands that failed temporarily
+///
+/// A command is retried if the Ultravisor reports a return code that the command classifies as
+/// transient (see [`UvCmd::transient`]), a return code registered with
+/// [`RetryPolicy::retry_rc`], or the IOCTL fails with `EAGAIN`, `EBUSY`, or `EINTR`. In these
+/// cases the Ultravisor did not execute the command, so retrying is safe for all commands.
+///
+/// Between two attempts the device waits for the backoff, which starts at the initial backoff and
+/// doubles with every attempt up to the maximum backoff.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use s390_pv_core::uv::{RetryPolicy, UvDevice};
+/// # fn main() -> s390_pv_core::Result<()> {
+/// let uv = UvDevice::open()?;
+/// uv.set_retry_policy(
+///     RetryPolicy::new(5).backoff(Duration::from_millis(50), Duration::from_secs(2)),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    codes: Vec<(u16, Option<u16>)>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::never()
+    }
+}
+
+impl RetryPolicy {
+    /// Default initial backoff
+    pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(10);
+    /// Default maximum backoff
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+    /// Never retry a command.
+    pub const fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff: Self::DEFAULT_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+            codes: Vec::new(),
+        }
+    }
+
+    /// Send a command at most `max_attempts` times with the default backoff.
+    ///
+    /// A value of zero is treated as one.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..Self::never()
+        }
+    }
+
+    /// Wait `initial` before the first retry and double the wait up to `max` for further retries.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Also retry if the Ultravisor reports `rc` and, if specified, `rrc`.
+    pub fn retry_rc(mut self, rc: u16, rrc: Option<u16>) -> Self {
+        self.codes.push((rc, rrc));
+        self
+    }
+
+    /// Maximum number of attempts per command
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Wait time after the failed attempt number `attempt`, starting at one.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |d| d.min(self.max_backoff))
+    }
+
+    /// Returns `true` if `err` of `cmd` is worth another attempt.
+    pub(super) fn retryable<C: UvCmd>(&self, cmd: &C, err: &Error) -> bool {
+        match err {
+            Error::Uv { rc, rrc, .. } => {
+                cmd.transient(*rc, *rrc)
+                    || self
+                        .codes
+                        .iter()
+                        .any(|(c, r)| c == rc && r.map_or(true, |r| r == *rrc))
+            }
+            Error::Io(e) => e
+                .raw_os_error()
+                .is_some_and(|errno| TRANSIENT_ERRNOS.contains(&errno)),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::uv::{AddCmd, LockCmd};
+
+    fn uv_err(rc: u16, rrc: u16) -> Error {
+        Error::Uv { rc, rrc, msg: "" }
+    }
+
+    #[test]
+    fn delay() {
+        let p = RetryPolicy::new(10).backoff(Duration::from_millis(10), Duration::from_millis(50));
+        assert_eq!(p.delay(1), Duration::from_millis(10));
+        assert_eq!(p.delay(2), Duration::from_millis(20));
+        assert_eq!(p.delay(3), Duration::from_millis(40));
+        assert_eq!(p.delay(4), Duration::from_millis(50));
+        assert_eq!(p.delay(100), Duration::from_millis(50));
+        assert_eq!(RetryPolicy::new(0).max_attempts(), 1);
+    }
+
+    #[test]
+    fn retryable() {
+        let p = RetryPolicy::new(3).retry_rc(0x0102, Some(0x0001));
+        let add = AddCmd::from_vec(b"asrcbM\0\x01".to_vec()).unwrap();
+        assert!(p.retryable(&add, &uv_err(0x010d, 0)));
+        assert!(!p.retryable(&add, &uv_err(0x0102, 0)));
+        assert!(p.retryable(&LockCmd, &uv_err(0x0102, 0x0001)));
+        assert!(!p.retryable(&LockCmd, &uv_err(0x010d, 0)));
+        let busy = Error::Io(std::io::Error::from_raw_os_error(libc::EBUSY));
+        assert!(p.retryable(&LockCmd, &busy));
+        let inval = Error::Io(std::io::Error::from_raw_os_error(libc::EINVAL));
+        assert!(!p.retryable(&LockCmd, &inval));
+        assert!(!p.retryable(&LockCmd, &Error::DeviceGone));
+    }
+}
@@ -5,13 +5,14 @@
 use super::ffi;
 use crate::{
     request::{Confidential, MagicValue},
-    uv::{SecretEntry, UvCmd},
+    uv::{SecretEntry, SecretList, UvArg, UvCmd},
     uvsecret::AddSecretMagic,
     Error, Result, PAGESIZE,
 };
 use log::debug;
 use std::{io::Read, mem::size_of_val};
 use zerocopy::IntoBytes;
+use zeroize::ZeroizeOnDrop;
 
 /// _List Secrets_ Ultravisor command.
 ///
@@ -39,6 +40,11 @@ impl ListCmd {
     pub fn new() -> Self {
         Self::with_size(PAGESIZE)
     }
+
+    /// Returns the list the Ultravisor wrote into this command.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
 }
 
 impl Default for ListCmd {
@@ -50,49 +56,118 @@ impl Default for ListCmd {
 impl UvCmd for ListCmd {
     const UV_IOCTL_NR: u8 = ffi::UVIO_IOCTL_LIST_SECRETS_NR;
 
-    fn data(&mut self) -> Option<&mut [u8]> {
-        Some(self.0.as_mut_slice())
+    fn arg(&mut self) -> UvArg<'_> {
+        UvArg::Out(&mut self.0)
     }
 
     fn rc_fmt(&self, _rc: u16, _rrc: u16) -> Option<&'static str> {
         None
     }
+
+    /// The header and the secret entries the Ultravisor stored in this page.
+    fn written(&self) -> Option<usize> {
+        SecretList::encoded_size(&self.0)
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    fn buffers(&self) -> Vec<&[u8]> {
+        vec![&self.0]
+    }
+
+    /// The Ultravisor overwrites the header of the list, no need to clear the buffer.
+    fn reset(&mut self) {}
 }
 
 /// _Add Secret_ Ultravisor command.
 ///
 /// The Add Secret Ultravisor-call is used to add a secret
 /// to the secret store for the current SE-guest.
+///
+/// The Ultravisor only reads the request, therefore any buffer that derefs to a byte slice, e.g. a
+/// `&[u8]` or an `Arc<[u8]>`, can be passed without copying it, see [`AddCmd::from_buf`].
 #[derive(Debug)]
-pub struct AddCmd(Vec<u8>);
+pub struct AddCmd<B = Vec<u8>>(B);
 
 impl AddCmd {
     /// Create a new Add Secret command using the provided data.
     ///
+    /// Reads at most [`ffi::UVIO_ADD_SECRET_MAX_LEN`] bytes from the reader. Use
+    /// [`AddCmd::from_vec`] if the request is already in memory to avoid copying it.
+    ///
     /// # Errors
     ///
     /// This function will return an error if the provided data does not start
-    /// with the `AddSecretRequest` magic Value.
+    /// with the `AddSecretRequest` magic Value or is too large.
     pub fn new<R: Read>(bin_add_secret_req: &mut R) -> Result<Self> {
         let mut data = Vec::with_capacity(PAGESIZE);
-        bin_add_secret_req.read_to_end(&mut data)?;
+        // Read one byte more than allowed to detect too large requests without reading all of it.
+        bin_add_secret_req
+            .take(ffi::UVIO_ADD_SECRET_MAX_LEN as u64 + 1)
+            .read_to_end(&mut data)?;
+        Self::from_vec(data)
+    }
 
+    /// Create a new Add Secret command that takes ownership of the provided request.
+    ///
+    /// The request is passed to the Ultravisor without further copies.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the provided data does not start
+    /// with the `AddSecretRequest` magic Value or is too large.
+    pub fn from_vec(data: Vec<u8>) -> Result<Self> {
+        Self::from_buf(data)
+    }
+}
+
+impl<B: AsRef<[u8]>> AddCmd<B> {
+    /// Create a new Add Secret command that references the provided request buffer.
+    ///
+    /// The request is passed to the Ultravisor without copying it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the provided data does not start
+    /// with the `AddSecretRequest` magic Value or is too large.
+    pub fn from_buf(buf: B) -> Result<Self> {
+        let data = buf.as_ref();
         if data.len() > ffi::UVIO_ADD_SECRET_MAX_LEN {
             return Err(Error::AscrbLarge);
         }
 
-        if !AddSecretMagic::starts_with_magic(&data[..6]) {
+        if !AddSecretMagic::starts_with_magic(data) {
             return Err(Error::NoAsrcb);
         }
-        Ok(Self(data))
+        Ok(Self(buf))
+    }
+
+    /// Returns the add-secret request of this command.
+    pub fn request(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+
+    /// Consumes the [`AddCmd`] and returns the request buffer.
+    pub fn into_inner(self) -> B {
+        self.0
     }
 }
 
-impl UvCmd for AddCmd {
+impl TryFrom<Vec<u8>> for AddCmd {
+    type Error = Error;
+
+    fn try_from(data: Vec<u8>) -> Result<Self> {
+        Self::from_vec(data)
+    }
+}
+
+impl<B: AsRef<[u8]>> UvCmd for AddCmd<B> {
     const UV_IOCTL_NR: u8 = ffi::UVIO_IOCTL_ADD_SECRET_NR;
 
-    fn data(&mut self) -> Option<&mut [u8]> {
-        Some(&mut self.0)
+    fn arg(&mut self) -> UvArg<'_> {
+        UvArg::In(self.0.as_ref())
     }
 
     fn rc_fmt(&self, rc: u16, _rrc: u16) -> Option<&'static str> {
@@ -113,6 +188,11 @@ impl UvCmd for AddCmd {
             _ => None,
         }
     }
+
+    fn transient(&self, rc: u16, _rrc: u16) -> bool {
+        // dump in progress
+        rc == 0x010d
+    }
 }
 
 /// _Lock Secret Store_ Ultravisor command.
@@ -168,6 +248,9 @@ impl RetrieveCmd {
     }
 }
 
+/// The buffer of the retrieved secret is a [`Confidential`].
+impl ZeroizeOnDrop for RetrieveCmd {}
+
 impl TryFrom<SecretEntry> for RetrieveCmd {
     type Error = Error;
 
@@ -219,4 +302,64 @@ impl UvCmd for RetrieveCmd {
     fn data(&mut self) -> Option<&mut [u8]> {
         Some(self.key.value_mut())
     }
+
+    /// The buffer receives the secret.
+    fn secret_ranges(&self) -> Vec<std::ops::Range<usize>> {
+        std::iter::once(0..self.key.value().len()).collect()
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn add_cmd_from_vec() {
+        let mut data = AddSecretMagic::MAGIC.to_vec();
+        data.resize(0x100, 0);
+        let ptr = data.as_ptr();
+        let mut cmd = AddCmd::from_vec(data).unwrap();
+        // no copy of the request happened
+        assert_eq!(cmd.arg().input().unwrap().as_ptr(), ptr);
+        assert!(cmd.arg().output().is_none());
+        assert_eq!(cmd.request().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn add_cmd_from_buf() {
+        let mut data = AddSecretMagic::MAGIC.to_vec();
+        data.resize(0x100, 0);
+        let mut cmd = AddCmd::from_buf(data.as_slice()).unwrap();
+        assert_eq!(cmd.arg().input().unwrap().as_ptr(), data.as_ptr());
+        assert_eq!(cmd.into_inner(), data.as_slice());
+        assert!(matches!(AddCmd::from_buf(&data[1..]), Err(Error::NoAsrcb)));
+    }
+
+    #[test]
+    fn add_cmd_no_magic() {
+        assert!(matches!(
+            AddCmd::from_vec(vec![0; 0x100]),
+            Err(Error::NoAsrcb)
+        ));
+        assert!(matches!(AddCmd::from_vec(vec![0; 2]), Err(Error::NoAsrcb)));
+        assert!(matches!(
+            AddCmd::new(&mut [].as_slice()),
+            Err(Error::NoAsrcb)
+        ));
+    }
+
+    #[test]
+    fn add_cmd_too_large() {
+        let mut data = AddSecretMagic::MAGIC.to_vec();
+        data.resize(ffi::UVIO_ADD_SECRET_MAX_LEN + 0x1000, 0);
+        assert!(matches!(
+            AddCmd::new(&mut data.as_slice()),
+            Err(Error::AscrbLarge)
+        ));
+        assert!(matches!(AddCmd::from_vec(data), Err(Error::AscrbLarge)));
+    }
 }
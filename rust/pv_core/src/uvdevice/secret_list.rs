@@ -245,6 +245,7 @@ assert_size!(SecretListHdr, 16);
 /// The list should ONLY be created from an UV-Call result using either:
 /// - [`TryInto::try_into`] from [`ListCmd`]
 /// - [`SecretList::decode`]
+///
 /// Any other ways can create invalid lists that do not represent the UV secret store.
 /// The list must not hold more than [`u32::MAX`] elements
 #[derive(Debug, PartialEq, Eq, Serialize, Default)]
@@ -335,6 +336,39 @@ impl SecretList {
         w.flush().map_err(Error::Io)
     }
 
+    /// Size of `struct uvio_list_secrets` of the C tools
+    pub const C_STRUCT_SIZE: usize = 0x8000;
+
+    /// Encodes the list in the binary format of the C tools
+    ///
+    /// The C tools, e.g. `zkey`, use `struct uvio_list_secrets`: the binary format of the UV,
+    /// zero-padded to [`SecretList::C_STRUCT_SIZE`] bytes. [`SecretList::decode`] reads both
+    /// formats.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the list does not fit into the C structure or
+    /// cannot be written.
+    pub fn encode_c<T: Write>(&self, w: &mut T) -> Result<()> {
+        let size = size_of::<SecretListHdr>() + self.secrets.len() * SecretEntry::STRUCT_SIZE;
+        if size > Self::C_STRUCT_SIZE {
+            return Err(Error::ManySecrets);
+        }
+        self.encode(w)?;
+        w.write_all(&vec![0; Self::C_STRUCT_SIZE - size])?;
+        w.flush().map_err(Error::Io)
+    }
+
+    /// Size of the list in the binary format of the UV, as reported by its header.
+    ///
+    /// Returns [`None`] if `data` cannot hold the header or the reported entries.
+    pub(crate) fn encoded_size(data: &[u8]) -> Option<usize> {
+        let (hdr, _) = SecretListHdr::read_from_prefix(data).ok()?;
+        let size = size_of::<SecretListHdr>()
+            + hdr.num_secrets_stored.get() as usize * SecretEntry::STRUCT_SIZE;
+        (size <= data.len()).then_some(size)
+    }
+
     /// Decodes the list from the binary format of the UV into this internal representation
     pub fn decode<R: Read + Seek>(r: &mut R) -> std::io::Result<Self> {
         let mut buf = [0u8; size_of::<SecretListHdr>()];
@@ -359,8 +393,20 @@ impl SecretList {
 impl TryFrom<ListCmd> for SecretList {
     type Error = Error;
 
-    fn try_from(mut list: ListCmd) -> Result<Self> {
-        Self::decode(&mut Cursor::new(list.data().unwrap())).map_err(Error::InvSecretList)
+    fn try_from(list: ListCmd) -> Result<Self> {
+        Self::try_from(&list)
+    }
+}
+
+impl TryFrom<&ListCmd> for SecretList {
+    type Error = Error;
+
+    /// Decodes the list without consuming the command, e.g. of a
+    /// [`PreparedCmd`](crate::uv::PreparedCmd).
+    fn try_from(list: &ListCmd) -> Result<Self> {
+        let data = list.as_bytes();
+        let data = &data[..list.written().unwrap_or(data.len())];
+        Self::decode(&mut Cursor::new(data)).map_err(Error::InvSecretList)
     }
 }
 
@@ -547,6 +593,7 @@ mod test {
 
     use std::io::{BufReader, BufWriter, Cursor};
 
+    use proptest::{collection::vec, num::u8, proptest};
     use serde_test::{assert_ser_tokens, assert_tokens, Token};
     use zerocopy::FromZeros;
 
@@ -607,6 +654,17 @@ mod test {
         assert_eq!(sl, exp);
     }
 
+    #[test]
+    fn secret_list_size() {
+        let mut buf = vec![0u8; 0x100];
+        assert_eq!(SecretList::encoded_size(&buf), Some(16));
+        buf[1] = 2;
+        assert_eq!(SecretList::encoded_size(&buf), Some(16 + 2 * 0x30));
+        buf[1] = 6;
+        assert_eq!(SecretList::encoded_size(&buf), None);
+        assert_eq!(SecretList::encoded_size(&buf[..8]), None);
+    }
+
     #[test]
     fn secret_list_enc() {
         const EXP: &[u8] = &[
@@ -644,6 +702,21 @@ mod test {
         assert_eq!(buf, EXP);
     }
 
+    #[test]
+    fn secret_list_enc_c() {
+        let entry = SecretEntry::new(1, ListableSecretType::Association, [3; 32].into(), 32);
+        let sl: SecretList = vec![entry.clone(); 2].into_iter().collect();
+        let mut buf = vec![];
+        sl.encode_c(&mut buf).unwrap();
+        assert_eq!(buf.len(), SecretList::C_STRUCT_SIZE);
+        assert!(buf[16 + 2 * 0x30..].iter().all(|b| *b == 0));
+        assert_eq!(SecretList::decode(&mut Cursor::new(&buf)).unwrap(), sl);
+
+        let max = (SecretList::C_STRUCT_SIZE - 16) / SecretEntry::STRUCT_SIZE;
+        let sl: SecretList = vec![entry; max + 1].into_iter().collect();
+        assert!(matches!(sl.encode_c(&mut vec![]), Err(Error::ManySecrets)));
+    }
+
     #[test]
     fn secret_entry_ser() {
         let entry = SecretEntry::new_zeroed();
@@ -798,4 +871,13 @@ mod test {
             "4E69636520546573742031323300000000000000000000000000000000000000"
         );
     }
+
+    proptest! {
+        #[test]
+        fn decode_no_panic(data in vec(u8::ANY, 0..0x400)) {
+            if let Ok(list) = SecretList::decode(&mut Cursor::new(&data)) {
+                let _ = list.to_string();
+            }
+        }
+    }
 }
@@ -0,0 +1,814 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Software simulation of the uvdevice and the Ultravisor
+//!
+//! A state file contains one setting or secret per line, empty lines and lines starting with `#`
+//! are ignored:
+//!
+//! ```text
+//! cuid=75762d73696d2d636f6e6669672d6964
+//! locked=0
+//! max-secrets=16
+//! secret index=0 type=0x0002 id=6173736f6300... data=-
+//! ```
+//!
+//! `id` and `data` are hex-encoded; `-` marks an empty secret.
+//!
+//! The simulator cannot decrypt requests. Therefore, the content of retrievable secrets and the
+//! attestation measurements are derived from the requests instead.
+
+use std::{
+    mem::size_of,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+
+use log::debug;
+use zerocopy::{BigEndian, FromBytes, IntoBytes, U16, U32};
+
+use super::ffi;
+use crate::{
+    misc::{decode_hex, encode_hex},
+    uv::{ConfigUid, ListableSecretType, SecretEntry, SecretId},
+    Error, FileIoErrorType, Result,
+};
+
+const RC_SUCCESS: u16 = 0x0001;
+const RC_MORE_DATA: u16 = 0x0100;
+
+/// Size of the common request header of add-secret and attestation requests
+const REQ_HDR_SIZE: usize = 0x30;
+/// Supported version of the add-secret and attestation requests
+const REQ_VERSION: u32 = 0x0100;
+/// Size of the request tag
+const REQ_TAG_SIZE: usize = 0x10;
+/// Offset of the Config-UID in an add-secret request
+const ASRCB_CUID_OFFS: usize = REQ_HDR_SIZE + 0xd8;
+/// Offset of the first key slot in an add-secret request
+const ASRCB_KEYSLOT_OFFS: usize = REQ_HDR_SIZE + 0x1e8 + 0x200 + 0xa0;
+/// Size of a key slot in an add-secret request
+const ASRCB_KEYSLOT_SIZE: usize = 0x50;
+/// Size of the secret header in an add-secret request
+const ASRCB_SECRET_HDR_SIZE: usize = 0x30;
+/// Maximum number of key slots of a request
+const MAX_KEYSLOTS: u8 = 8;
+/// All IOCTLs the simulator implements
+const ALL_IOCTLS: [u8; 6] = [
+    ffi::UVIO_IOCTL_UVDEV_INFO_NR,
+    ffi::UVIO_IOCTL_ATT_NR,
+    ffi::UVIO_IOCTL_ADD_SECRET_NR,
+    ffi::UVIO_IOCTL_LIST_SECRETS_NR,
+    ffi::UVIO_IOCTL_LOCK_SECRETS_NR,
+    ffi::UVIO_IOCTL_RETR_SECRET_NR,
+];
+
+/// Fills `out` with bytes derived from `inputs`.
+///
+/// Not cryptographically secure; the same inputs always result in the same bytes.
+fn derive_bytes(inputs: &[&[u8]], out: &mut [u8]) {
+    // FNV-1a over all inputs, including their lengths, seeds a splitmix64 stream.
+    let mut seed = 0xcbf2_9ce4_8422_2325_u64;
+    for input in inputs {
+        for b in (input.len() as u64).to_be_bytes().iter().chain(*input) {
+            seed = (seed ^ *b as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    for chunk in out.chunks_mut(8) {
+        seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        chunk.copy_from_slice(&z.to_be_bytes()[..chunk.len()]);
+    }
+}
+
+fn parse_err(content: &str) -> Error {
+    Error::ParseError {
+        subject: "simulator state".to_string(),
+        content: content.to_string(),
+    }
+}
+
+fn parse_num<T>(
+    val: &str,
+    parse: fn(&str, u32) -> std::result::Result<T, std::num::ParseIntError>,
+) -> Result<T> {
+    match val.strip_prefix("0x") {
+        Some(hex) => Ok(parse(hex, 16)?),
+        None => Ok(parse(val, 10)?),
+    }
+}
+
+/// A secret in the secret store of the [`UvSimulator`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SimSecret {
+    index: u16,
+    stype: u16,
+    id: SecretId,
+    data: Vec<u8>,
+}
+
+impl SimSecret {
+    fn entry(&self) -> SecretEntry {
+        SecretEntry::new(
+            self.index,
+            self.stype.into(),
+            self.id.clone(),
+            self.data.len() as u32,
+        )
+    }
+
+    fn to_line(&self) -> String {
+        let data = match self.data.is_empty() {
+            true => "-".to_string(),
+            false => encode_hex(&self.data),
+        };
+        format!(
+            "secret index={} type={:#06x} id={} data={data}",
+            self.index,
+            self.stype,
+            encode_hex(&self.id)
+        )
+    }
+
+    fn from_line(line: &str) -> Result<Self> {
+        let mut fields = [None; 4];
+        const KEYS: [&str; 4] = ["index", "type", "id", "data"];
+        for field in line.split_whitespace() {
+            let (key, val) = field.split_once('=').ok_or_else(|| parse_err(field))?;
+            let idx = KEYS
+                .iter()
+                .position(|k| *k == key)
+                .ok_or_else(|| parse_err(field))?;
+            fields[idx] = Some(val);
+        }
+        let [Some(index), Some(stype), Some(id), Some(data)] = fields else {
+            return Err(parse_err(line));
+        };
+        let id: [u8; SecretId::ID_SIZE] = decode_hex(id)?.try_into().map_err(|_| parse_err(id))?;
+        Ok(Self {
+            index: parse_num(index, u16::from_str_radix)?,
+            stype: parse_num(stype, u16::from_str_radix)?,
+            id: id.into(),
+            data: match data {
+                "-" => vec![],
+                data => decode_hex(data)?,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SimState {
+    cuid: ConfigUid,
+    locked: bool,
+    max_secrets: u16,
+    secrets: Vec<SimSecret>,
+}
+
+impl Default for SimState {
+    fn default() -> Self {
+        Self {
+            cuid: *UvSimulator::DEFAULT_CUID,
+            locked: false,
+            max_secrets: UvSimulator::DEFAULT_MAX_SECRETS,
+            secrets: vec![],
+        }
+    }
+}
+
+impl SimState {
+    fn to_text(&self) -> String {
+        let mut text = format!(
+            "# uvdevice simulator state\ncuid={}\nlocked={}\nmax-secrets={}\n",
+            encode_hex(self.cuid),
+            self.locked as u8,
+            self.max_secrets
+        );
+        for secret in &self.secrets {
+            text.push_str(&secret.to_line());
+            text.push('\n');
+        }
+        text
+    }
+
+    fn from_text(text: &str) -> Result<Self> {
+        let mut state = Self::default();
+        for line in text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        {
+            if let Some(secret) = line.strip_prefix("secret ") {
+                state.secrets.push(SimSecret::from_line(secret)?);
+                continue;
+            }
+            match line.split_once('=').ok_or_else(|| parse_err(line))? {
+                ("cuid", cuid) => {
+                    state.cuid = decode_hex(cuid)?.try_into().map_err(|_| parse_err(cuid))?
+                }
+                ("locked", "0") => state.locked = false,
+                ("locked", "1") => state.locked = true,
+                ("max-secrets", max) => state.max_secrets = parse_num(max, u16::from_str_radix)?,
+                _ => return Err(parse_err(line)),
+            }
+        }
+        Ok(state)
+    }
+}
+
+/// Software simulation of the uvdevice and the Ultravisor
+///
+/// Emulates the IOCTLs of the uvdevice in software, so that programs can be tested without an
+/// IBM Secure Execution guest, e.g. on developer machines or in CI. Use
+/// [`crate::uv::UvDevice::simulated`] to send commands to a simulator, or set the environment
+/// variable `PV_UV_SIM=<FILE>` to let [`crate::uv::UvDevice::open`] use a simulator that keeps
+/// its state in FILE. Only available with the feature `uv-sim`.
+///
+/// The simulator provides:
+///
+/// - a secret store that can be listed, locked, and extended by add-secret requests. The
+///   simulator cannot decrypt the requests; it adds the secret described by their unencrypted
+///   part, with content derived from the request tag. Use [`UvSimulator::insert_secret`] to
+///   store secrets with a known content, e.g. to test the retrieval.
+/// - attestation with deterministic measurements, see [`UvSimulator::measurement`]. The
+///   measurements are no HMACs and cannot be verified with the request protection key.
+/// - the return codes of the Ultravisor for invalid requests and states, e.g. a locked or full
+///   secret store.
+///
+/// The Ultravisor query information in sysfs is not simulated.
+#[derive(Debug, Default)]
+pub struct UvSimulator {
+    state: Mutex<SimState>,
+    path: Option<PathBuf>,
+    unsupported: Vec<u8>,
+}
+
+impl UvSimulator {
+    /// Config-UID of a new simulator
+    pub const DEFAULT_CUID: &'static ConfigUid = b"uv-sim-config-id";
+    /// Capacity of the secret store of a new simulator
+    pub const DEFAULT_MAX_SECRETS: u16 = 16;
+    /// Environment variable with the state file of the simulator
+    pub const ENV: &'static str = "PV_UV_SIM";
+
+    /// Create a simulator with an empty, unlocked secret store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a simulator that keeps its state in the file at `path`.
+    ///
+    /// The state is read from the file if it exists and written after each change, so that
+    /// several processes can use the same simulated guest one after the other.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file exists but cannot be read or parsed.
+    pub fn with_state_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let state = match std::fs::read_to_string(path) {
+            Ok(text) => SimState::from_text(&text)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => SimState::default(),
+            Err(source) => {
+                return Err(Error::FileIo {
+                    ty: FileIoErrorType::Read,
+                    ctx: "simulator state".to_string(),
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        debug!(
+            "Using the uvdevice simulator with the state {}",
+            path.display()
+        );
+        Ok(Self {
+            state: Mutex::new(state),
+            path: Some(path.to_path_buf()),
+            unsupported: vec![],
+        })
+    }
+
+    /// Set the Config-UID of the simulated guest.
+    pub fn cuid(self, cuid: ConfigUid) -> Self {
+        self.lock_state().cuid = cuid;
+        self
+    }
+
+    /// Set the capacity of the secret store.
+    pub fn max_secrets(self, max: u16) -> Self {
+        self.lock_state().max_secrets = max;
+        self
+    }
+
+    /// Simulate a uvdevice that does not support the IOCTL `nr`.
+    pub fn unsupported(mut self, nr: u8) -> Self {
+        self.unsupported.push(nr);
+        self
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, SimState> {
+        // Every change of the state is complete or not done at all.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn save(&self, state: &SimState) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        std::fs::write(path, state.to_text()).map_err(|source| Error::FileIo {
+            ty: FileIoErrorType::Write,
+            ctx: "simulator state".to_string(),
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Store a secret with the content `data`, regardless of the lock state.
+    ///
+    /// # Returns
+    ///
+    /// The index of the secret
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the secret store is full or the state cannot be
+    /// saved.
+    pub fn insert_secret(
+        &self,
+        stype: ListableSecretType,
+        id: SecretId,
+        data: Vec<u8>,
+    ) -> Result<u16> {
+        let mut state = self.lock_state();
+        if state.secrets.len() >= state.max_secrets as usize {
+            return Err(Error::ManySecrets);
+        }
+        let index = state.secrets.len() as u16;
+        state.secrets.push(SimSecret {
+            index,
+            stype: stype.into(),
+            id,
+            data,
+        });
+        self.save(&state)?;
+        Ok(index)
+    }
+
+    /// Returns the secrets in the secret store.
+    pub fn secrets(&self) -> Vec<SecretEntry> {
+        self.lock_state()
+            .secrets
+            .iter()
+            .map(SimSecret::entry)
+            .collect()
+    }
+
+    /// Returns `true` if the secret store is locked.
+    pub fn is_locked(&self) -> bool {
+        self.lock_state().locked
+    }
+
+    /// Returns the measurement the simulator calculates for an attestation.
+    ///
+    /// The measurement of `size` bytes is derived from the attestation request `arcb`, the
+    /// `user_data`, and the Config-UID `cuid`.
+    pub fn measurement(arcb: &[u8], user_data: &[u8], cuid: &ConfigUid, size: usize) -> Vec<u8> {
+        let mut meas = vec![0; size];
+        derive_bytes(&[b"measurement", arcb, user_data, cuid], &mut meas);
+        meas
+    }
+
+    /// Execute the IOCTL `nr` with the control block `cb`.
+    ///
+    /// Addresses inside the data of `cb`, e.g. of the attestation request, are only accessed if
+    /// `refs_valid` is `true`; otherwise the Ultravisor reports an access exception.
+    ///
+    /// # Safety
+    ///
+    /// The argument of `cb` must either be null or point to `argument_len` bytes that are valid
+    /// for reads and, except for the input-only Add Secret request, for writes. If `refs_valid` is `true`, the addresses inside the argument must
+    /// be valid for the lengths specified with them.
+    pub(super) unsafe fn ioctl(
+        &self,
+        nr: u8,
+        cb: &mut ffi::uvio_ioctl_cb,
+        refs_valid: bool,
+    ) -> Result<()> {
+        if !ALL_IOCTLS.contains(&nr) || self.unsupported.contains(&nr) {
+            return Err(std::io::Error::from_raw_os_error(libc::ENOTTY).into());
+        }
+        let mut state = self.lock_state();
+        let old = state.clone();
+        let rc = match nr {
+            // SAFETY: The caller guarantees that the argument is valid.
+            ffi::UVIO_IOCTL_UVDEV_INFO_NR => self.info(arg_mut(cb)),
+            ffi::UVIO_IOCTL_ATT_NR => attest(&state, arg_mut(cb), refs_valid),
+            ffi::UVIO_IOCTL_ADD_SECRET_NR => add_secret(&mut state, arg(cb)),
+            ffi::UVIO_IOCTL_LIST_SECRETS_NR => list_secrets(&state, arg_mut(cb)),
+            ffi::UVIO_IOCTL_LOCK_SECRETS_NR => match state.locked {
+                true => Ok(0x0102),
+                false => {
+                    state.locked = true;
+                    Ok(RC_SUCCESS)
+                }
+            },
+            ffi::UVIO_IOCTL_RETR_SECRET_NR => retrieve_secret(&state, arg_mut(cb)),
+            _ => unreachable!(),
+        }?;
+        if let Err(e) = match *state != old {
+            true => self.save(&state),
+            false => Ok(()),
+        } {
+            *state = old;
+            return Err(e);
+        }
+        cb.uv_rc = rc;
+        cb.uv_rrc = 0;
+        Ok(())
+    }
+
+    fn info(&self, data: &mut [u8]) -> Result<u16> {
+        let Ok((mut info, _)) = ffi::uvio_uvdev_info::read_from_prefix(data) else {
+            return Err(std::io::Error::from_raw_os_error(libc::EINVAL).into());
+        };
+        let supported = ALL_IOCTLS
+            .iter()
+            .filter(|nr| !self.unsupported.contains(nr))
+            .fold(0_u64, |mask, nr| mask | (1 << nr));
+        info.supp_uvio_cmds = supported.into();
+        // There is no Info UVC.
+        info.supp_uv_cmds = (supported & !1).into();
+        data[..size_of::<ffi::uvio_uvdev_info>()].copy_from_slice(info.as_mut_bytes());
+        Ok(RC_SUCCESS)
+    }
+}
+
+/// Returns the argument of `cb` for reads.
+///
+/// # Safety
+///
+/// The argument must either be null or point to `argument_len` bytes that are valid for reads.
+unsafe fn arg<'a>(cb: &ffi::uvio_ioctl_cb) -> &'a [u8] {
+    match cb.argument_addr {
+        0 => &[],
+        addr => std::slice::from_raw_parts(addr as *const u8, cb.argument_len as usize),
+    }
+}
+
+/// Returns the argument of `cb` for reads and writes.
+///
+/// # Safety
+///
+/// The argument must either be null or point to `argument_len` bytes that are valid for reads
+/// and writes.
+unsafe fn arg_mut<'a>(cb: &ffi::uvio_ioctl_cb) -> &'a mut [u8] {
+    match cb.argument_addr {
+        0 => &mut [],
+        addr => std::slice::from_raw_parts_mut(addr as *mut u8, cb.argument_len as usize),
+    }
+}
+
+/// Returns the request version, the request length, and the number of key slots.
+fn req_hdr(req: &[u8]) -> Option<(u32, usize, u8)> {
+    let version = U32::<BigEndian>::read_from_prefix(req.get(8..)?).ok()?.0;
+    let len = U32::<BigEndian>::read_from_prefix(req.get(12..)?).ok()?.0;
+    Some((version.get(), len.get() as usize, *req.get(0x27)?))
+}
+
+fn add_secret(state: &mut SimState, req: &[u8]) -> Result<u16> {
+    if state.locked {
+        return Ok(0x0102);
+    }
+    let Some((version, len, nks)) = req_hdr(req) else {
+        return Ok(0x0105);
+    };
+    if version != REQ_VERSION {
+        return Ok(0x0104);
+    }
+    if len > req.len() || len < REQ_HDR_SIZE + REQ_TAG_SIZE {
+        return Ok(0x0105);
+    }
+    if nks == 0 || nks > MAX_KEYSLOTS {
+        return Ok(0x0106);
+    }
+    let hdr_offs = ASRCB_KEYSLOT_OFFS + nks as usize * ASRCB_KEYSLOT_SIZE;
+    let Some(hdr) = req.get(hdr_offs..hdr_offs + ASRCB_SECRET_HDR_SIZE) else {
+        return Ok(0x0105);
+    };
+    let cuid = &req[ASRCB_CUID_OFFS..ASRCB_CUID_OFFS + size_of::<ConfigUid>()];
+    if cuid != [0; size_of::<ConfigUid>()] && cuid != state.cuid {
+        // The real Ultravisor cannot decrypt requests for other guests.
+        return Ok(0x0108);
+    }
+
+    // Secret header: reserved (2), type (2), secret size (4), reserved (8), [ID (32)]
+    let stype = u16::from_be_bytes([hdr[2], hdr[3]]);
+    let size = u32::from_be_bytes([hdr[4], hdr[5], hdr[6], hdr[7]]) as usize;
+    match ListableSecretType::from(stype) {
+        ListableSecretType::Association | ListableSecretType::Retrievable(_) => (),
+        // Null secrets only apply the flags of the request.
+        _ if stype == ListableSecretType::NULL => return Ok(RC_SUCCESS),
+        _ => return Ok(0x0109),
+    }
+    if size > ffi::UVIO_RETR_SECRET_MAX_LEN {
+        return Ok(0x010a);
+    }
+    // Cannot fail, the header is large enough.
+    let id = SecretId::read_from_bytes(&hdr[16..]).unwrap();
+    if state.secrets.iter().any(|s| s.id == id) {
+        return Ok(0x010c);
+    }
+    if state.secrets.len() >= state.max_secrets as usize {
+        return Ok(0x010b);
+    }
+
+    let mut data = vec![0; size];
+    derive_bytes(&[b"secret", &req[len - REQ_TAG_SIZE..len]], &mut data);
+    state.secrets.push(SimSecret {
+        index: state.secrets.len() as u16,
+        stype,
+        id,
+        data,
+    });
+    Ok(RC_SUCCESS)
+}
+
+fn list_secrets(state: &SimState, data: &mut [u8]) -> Result<u16> {
+    const HDR_SIZE: usize = 16;
+    const ENTRY_SIZE: usize = size_of::<SecretEntry>();
+    if data.len() < HDR_SIZE {
+        return Err(std::io::Error::from_raw_os_error(libc::EINVAL).into());
+    }
+    let fit = ((data.len() - HDR_SIZE) / ENTRY_SIZE).min(state.secrets.len());
+    let next = state.secrets.get(fit).map_or(0, |s| s.index);
+    data.fill(0);
+    for (buf, val) in data[..6]
+        .chunks_mut(2)
+        .zip([fit as u16, state.secrets.len() as u16, next])
+    {
+        buf.copy_from_slice(U16::<BigEndian>::new(val).as_bytes());
+    }
+    for (i, secret) in state.secrets[..fit].iter().enumerate() {
+        let offs = HDR_SIZE + i * ENTRY_SIZE;
+        data[offs..offs + ENTRY_SIZE].copy_from_slice(secret.entry().as_bytes());
+    }
+    match fit < state.secrets.len() {
+        true => Ok(RC_MORE_DATA),
+        false => Ok(RC_SUCCESS),
+    }
+}
+
+fn retrieve_secret(state: &SimState, data: &mut [u8]) -> Result<u16> {
+    let Some(index) = data.get(..2).map(|i| u16::from_be_bytes([i[0], i[1]])) else {
+        return Err(std::io::Error::from_raw_os_error(libc::EINVAL).into());
+    };
+    if state.secrets.is_empty() {
+        return Ok(0x010f);
+    }
+    let Some(secret) = state.secrets.iter().find(|s| s.index == index) else {
+        return Ok(0x0110);
+    };
+    if !matches!(
+        ListableSecretType::from(secret.stype),
+        ListableSecretType::Retrievable(_)
+    ) {
+        return Ok(0x0111);
+    }
+    if secret.data.len() > data.len() {
+        return Ok(0x0009);
+    }
+    data.fill(0);
+    data[..secret.data.len()].copy_from_slice(&secret.data);
+    Ok(RC_SUCCESS)
+}
+
+fn attest(state: &SimState, data: &mut [u8], refs_valid: bool) -> Result<u16> {
+    let Ok((mut cb, _)) = ffi::uvio_attest::read_from_prefix(data) else {
+        return Err(std::io::Error::from_raw_os_error(libc::EINVAL).into());
+    };
+    if cb.user_data_len.get() as usize > ffi::UVIO_ATT_USER_DATA_LEN {
+        return Ok(0x0102);
+    }
+    if !refs_valid {
+        return Ok(0x0103);
+    }
+    let arcb_len = cb.arcb_len.get() as usize;
+    let meas_len = cb.meas_len.get() as usize;
+    if arcb_len > ffi::UVIO_ATT_ARCB_MAX_LEN || meas_len > ffi::UVIO_ATT_MEASUREMENT_MAX_LEN {
+        return Ok(0x0107);
+    }
+    // SAFETY: The caller of `UvSimulator::ioctl` guarantees that the addresses are valid.
+    let arcb = unsafe { std::slice::from_raw_parts(cb.arcb_addr.get() as *const u8, arcb_len) };
+    let Some((version, len, nks)) = req_hdr(arcb) else {
+        return Ok(0x0107);
+    };
+    if version != REQ_VERSION {
+        return Ok(0x0106);
+    }
+    if len != arcb_len {
+        return Ok(0x0107);
+    }
+    if nks == 0 || nks > MAX_KEYSLOTS {
+        return Ok(0x0108);
+    }
+    if meas_len == 0 {
+        return Ok(0x010d);
+    }
+
+    let user_data = &cb.user_data[..cb.user_data_len.get() as usize];
+    let meas = UvSimulator::measurement(arcb, user_data, &state.cuid, meas_len);
+    // SAFETY: The caller of `UvSimulator::ioctl` guarantees that the addresses are valid.
+    unsafe { std::slice::from_raw_parts_mut(cb.meas_addr.get() as *mut u8, meas_len) }
+        .copy_from_slice(&meas);
+    cb.config_uid = state.cuid;
+    // The simulator provides no additional data.
+    cb.set_add_data_len(0);
+    data[..size_of::<ffi::uvio_attest>()].copy_from_slice(cb.as_mut_bytes());
+    Ok(RC_SUCCESS)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        attest::UserData,
+        uv::{
+            AddCmd, AesSizes, AttestationCmd, ListCmd, LockCmd, RetrievableSecret, RetrieveCmd,
+            SecretList, UvDevice,
+        },
+    };
+
+    fn asrcb(cuid: ConfigUid, stype: u16, id: &str) -> Vec<u8> {
+        let nks = 1;
+        let hdr_offs = ASRCB_KEYSLOT_OFFS + ASRCB_KEYSLOT_SIZE;
+        let len = hdr_offs + ASRCB_SECRET_HDR_SIZE + 0x40 + REQ_TAG_SIZE;
+        let mut req = vec![0; len];
+        req[..8].copy_from_slice(b"asrcbM\0\0");
+        req[8..12].copy_from_slice(&REQ_VERSION.to_be_bytes());
+        req[12..16].copy_from_slice(&(len as u32).to_be_bytes());
+        req[0x27] = nks;
+        req[ASRCB_CUID_OFFS..ASRCB_CUID_OFFS + 16].copy_from_slice(&cuid);
+        req[hdr_offs + 2..hdr_offs + 4].copy_from_slice(&stype.to_be_bytes());
+        req[hdr_offs + 4..hdr_offs + 8].copy_from_slice(&32_u32.to_be_bytes());
+        req[hdr_offs + 16..hdr_offs + 48].copy_from_slice(SecretId::from_string(id).as_ref());
+        req
+    }
+
+    fn uv_err<T: std::fmt::Debug>(res: Result<T>) -> u16 {
+        match res {
+            Err(Error::Uv { rc, .. }) => rc,
+            res => panic!("unexpected result {res:?}"),
+        }
+    }
+
+    fn list(uv: &UvDevice) -> SecretList {
+        let mut cmd = ListCmd::default();
+        uv.send_cmd(&mut cmd).unwrap();
+        cmd.try_into().unwrap()
+    }
+
+    #[test]
+    fn secret_store() {
+        let uv = UvDevice::simulated(UvSimulator::new().max_secrets(2));
+        assert!(list(&uv).is_empty());
+
+        let mut add = AddCmd::from_vec(asrcb(*UvSimulator::DEFAULT_CUID, 2, "assoc")).unwrap();
+        uv.send_cmd(&mut add).unwrap();
+        let secrets = list(&uv);
+        assert_eq!(secrets.len(), 1);
+        assert!(secrets.find(&SecretId::from_string("assoc")).is_some());
+
+        // same ID again
+        assert_eq!(uv_err(uv.send_cmd(&mut add)), 0x010c);
+        // request for another guest
+        let mut add = AddCmd::from_vec(asrcb([1; 16], 2, "other")).unwrap();
+        assert_eq!(uv_err(uv.send_cmd(&mut add)), 0x0108);
+        // unsupported secret type
+        let mut add = AddCmd::from_vec(asrcb([0; 16], 0x0100, "other")).unwrap();
+        assert_eq!(uv_err(uv.send_cmd(&mut add)), 0x0109);
+
+        let mut add = AddCmd::from_vec(asrcb([0; 16], 6, "aes")).unwrap();
+        uv.send_cmd(&mut add).unwrap();
+        // store full
+        let mut add = AddCmd::from_vec(asrcb([0; 16], 2, "full")).unwrap();
+        assert_eq!(uv_err(uv.send_cmd(&mut add)), 0x010b);
+
+        uv.send_cmd(&mut LockCmd).unwrap();
+        assert_eq!(uv_err(uv.send_cmd(&mut LockCmd)), 0x0102);
+        assert_eq!(uv_err(uv.send_cmd(&mut add)), 0x0102);
+        assert_eq!(list(&uv).len(), 2);
+    }
+
+    #[test]
+    fn retrieve() {
+        let sim = UvSimulator::new();
+        let aes = RetrievableSecret::Aes(AesSizes::Bits256);
+        sim.insert_secret(
+            ListableSecretType::Association,
+            SecretId::from_string("assoc"),
+            vec![0; 32],
+        )
+        .unwrap();
+        sim.insert_secret(
+            ListableSecretType::Retrievable(aes),
+            SecretId::from_string("key"),
+            vec![0x42; 32],
+        )
+        .unwrap();
+        let uv = UvDevice::simulated(sim);
+
+        let secrets = list(&uv);
+        let entry = secrets.find(&SecretId::from_string("key")).unwrap();
+        let mut retr = RetrieveCmd::from_entry(entry).unwrap();
+        uv.send_cmd(&mut retr).unwrap();
+        assert_eq!(retr.into_key().value(), &[0x42; 32]);
+
+        let entry = secrets.find(&SecretId::from_string("assoc")).unwrap();
+        let mut retr = RetrieveCmd::from_entry(entry).unwrap();
+        assert_eq!(uv_err(uv.send_cmd(&mut retr)), 0x0111);
+    }
+
+    #[test]
+    fn list_more_data() {
+        let sim = UvSimulator::new().max_secrets(200);
+        for i in 0..100 {
+            sim.insert_secret(
+                ListableSecretType::Association,
+                SecretId::from_string(&format!("{i}")),
+                vec![0; 32],
+            )
+            .unwrap();
+        }
+        let uv = UvDevice::simulated(sim);
+        let mut cmd = ListCmd::with_pages(1);
+        let resp = uv.send_cmd(&mut cmd).unwrap();
+        assert!(resp.more_data());
+        let secrets: SecretList = cmd.try_into().unwrap();
+        assert_eq!(secrets.len(), (0x1000 - 16) / 0x30);
+        assert_eq!(secrets.total_num_secrets(), 100);
+    }
+
+    #[test]
+    fn attestation() {
+        let mut arcb = vec![0; 0x100];
+        arcb[8..12].copy_from_slice(&REQ_VERSION.to_be_bytes());
+        arcb[12..16].copy_from_slice(&0x100_u32.to_be_bytes());
+        arcb[0x27] = 1;
+
+        let uv = UvDevice::simulated(UvSimulator::new());
+        let mut cmd = AttestationCmd::new_request(
+            arcb.clone().into(),
+            Some(UserData::try_from(&[1, 2, 3][..]).unwrap()),
+            64,
+            0,
+        )
+        .unwrap();
+        uv.send_cmd(&mut cmd).unwrap();
+        assert_eq!(cmd.cuid(), UvSimulator::DEFAULT_CUID);
+        let meas = UvSimulator::measurement(&arcb, &[1, 2, 3], UvSimulator::DEFAULT_CUID, 64);
+        assert_eq!(cmd.measurement(), meas);
+        // deterministic, but depends on the user data
+        assert_ne!(
+            meas,
+            UvSimulator::measurement(&arcb, &[], UvSimulator::DEFAULT_CUID, 64)
+        );
+
+        arcb[0x27] = 0;
+        let mut cmd = AttestationCmd::new_request(arcb.into(), None, 64, 0).unwrap();
+        assert_eq!(uv_err(uv.send_cmd(&mut cmd)), 0x0108);
+    }
+
+    #[test]
+    fn unsupported() {
+        let uv =
+            UvDevice::simulated(UvSimulator::new().unsupported(ffi::UVIO_IOCTL_RETR_SECRET_NR));
+        assert!(uv.supports::<ListCmd>().unwrap());
+        assert!(!uv.supports::<RetrieveCmd>().unwrap());
+    }
+
+    #[test]
+    fn state_file() {
+        let dir = std::env::temp_dir().join(format!("uv-sim-{}", std::process::id()));
+        let _ = std::fs::remove_file(&dir);
+
+        let uv = UvDevice::simulated(UvSimulator::with_state_file(&dir).unwrap());
+        let mut add = AddCmd::from_vec(asrcb([0; 16], 2, "assoc")).unwrap();
+        uv.send_cmd(&mut add).unwrap();
+        uv.send_cmd(&mut LockCmd).unwrap();
+
+        let sim = UvSimulator::with_state_file(&dir).unwrap();
+        assert!(sim.is_locked());
+        assert_eq!(sim.secrets().len(), 1);
+        let text = std::fs::read_to_string(&dir).unwrap();
+        assert_eq!(SimState::from_text(&text).unwrap().to_text(), text);
+        std::fs::remove_file(&dir).unwrap();
+
+        assert!(SimState::from_text("locked=2").is_err());
+        assert!(SimState::from_text("secret index=0 type=2 id=00 data=-").is_err());
+    }
+}
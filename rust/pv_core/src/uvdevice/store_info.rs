@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use super::{
+    query::UvQueryInfo,
+    secret_list::{ListableSecretType, SecretList},
+    UvFlags,
+};
+use crate::misc::Flags;
+
+/// Information about the secret store of the Ultravisor
+///
+/// Combines the result of the List Secrets UVC with the limits the Ultravisor reports via
+/// `/sys/firmware/uv/query` (see [`UvQueryInfo`]). Values not provided by the kernel are
+/// [`None`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SecretStoreInfo {
+    stored_secrets: u16,
+    max_secrets: Option<u32>,
+    max_association_secrets: Option<u32>,
+    max_retrievable_secrets: Option<u32>,
+    #[serde(serialize_with = "ser_types")]
+    supported_secret_types: Option<UvFlags>,
+}
+
+impl SecretStoreInfo {
+    /// Gather information about the secret store.
+    ///
+    /// `list` must be the result of a List Secrets UVC. The limits are read from sysfs.
+    pub fn new(list: &SecretList) -> Self {
+        Self::with_query(list, &UvQueryInfo::read())
+    }
+
+    /// Gather information about the secret store using already read query information.
+    ///
+    /// `list` must be the result of a List Secrets UVC.
+    pub fn with_query(list: &SecretList, query: &UvQueryInfo) -> Self {
+        Self {
+            stored_secrets: list.total_num_secrets() as u16,
+            max_secrets: query.max_secrets(),
+            max_association_secrets: query.max_assoc_secrets(),
+            max_retrievable_secrets: query.max_retr_secrets(),
+            supported_secret_types: query.supp_secret_types().copied(),
+        }
+    }
+
+    /// Returns the number of secrets in the secret store.
+    pub fn stored_secrets(&self) -> u16 {
+        self.stored_secrets
+    }
+
+    /// Returns the maximum number of secrets the secret store can hold.
+    ///
+    /// Older kernels only report the limits for association and retrievable secrets; in that
+    /// case the sum of both is returned.
+    pub fn max_secrets(&self) -> Option<u32> {
+        self.max_secrets.or_else(|| {
+            self.max_association_secrets
+                .zip(self.max_retrievable_secrets)
+                .map(|(a, r)| a.saturating_add(r))
+        })
+    }
+
+    /// Returns the maximum number of association secrets.
+    pub fn max_association_secrets(&self) -> Option<u32> {
+        self.max_association_secrets
+    }
+
+    /// Returns the maximum number of retrievable secrets.
+    pub fn max_retrievable_secrets(&self) -> Option<u32> {
+        self.max_retrievable_secrets
+    }
+
+    /// Returns the number of secrets that can still be added.
+    pub fn free_secrets(&self) -> Option<u32> {
+        self.max_secrets()
+            .map(|max| max.saturating_sub(self.stored_secrets.into()))
+    }
+
+    /// Returns the secret types the Ultravisor supports.
+    pub fn supported_secret_types(&self) -> Option<Vec<ListableSecretType>> {
+        self.supported_secret_types.as_ref().map(types_from_flags)
+    }
+}
+
+fn types_from_flags(flags: &UvFlags) -> Vec<ListableSecretType> {
+    (0u8..64)
+        .filter(|bit| flags.is_set(*bit))
+        .map(|bit| ListableSecretType::from(bit as u16))
+        .collect()
+}
+
+fn ser_types<S: serde::Serializer>(flags: &Option<UvFlags>, ser: S) -> Result<S::Ok, S::Error> {
+    match flags {
+        Some(flags) => ser.collect_seq(types_from_flags(flags).iter().map(ToString::to_string)),
+        None => ser.serialize_none(),
+    }
+}
+
+fn fmt_opt(v: Option<u32>) -> String {
+    v.map_or_else(|| "unknown".to_string(), |v| v.to_string())
+}
+
+impl Display for SecretStoreInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Stored secrets: {}", self.stored_secrets)?;
+        writeln!(
+            f,
+            "Maximum number of secrets: {}",
+            fmt_opt(self.max_secrets())
+        )?;
+        writeln!(
+            f,
+            "  Association secrets: {}",
+            fmt_opt(self.max_association_secrets)
+        )?;
+        writeln!(
+            f,
+            "  Retrievable secrets: {}",
+            fmt_opt(self.max_retrievable_secrets)
+        )?;
+        writeln!(f, "Free secret slots: {}", fmt_opt(self.free_secrets()))?;
+        write!(f, "Supported secret types:")?;
+        match self.supported_secret_types() {
+            None => writeln!(f, " unknown"),
+            Some(types) => {
+                writeln!(f)?;
+                for t in types {
+                    writeln!(f, "  {t}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::uv::{RetrievableSecret, SecretEntry, SecretId};
+
+    fn list(n: u16) -> SecretList {
+        (0..n)
+            .map(|i| {
+                SecretEntry::new(
+                    i,
+                    ListableSecretType::Association,
+                    SecretId::from([i as u8; 32]),
+                    32,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn from_query_dir() {
+        let dir = std::env::temp_dir().join(format!("pv_store_info_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("max_assoc_secrets"), "2\n").unwrap();
+        std::fs::write(dir.join("max_retr_secrets"), "8\n").unwrap();
+        // Association (2) and plaintext (3) in MSB0 ordering
+        std::fs::write(dir.join("supp_secret_types"), "3000000000000000\n").unwrap();
+
+        let info = SecretStoreInfo::with_query(&list(3), &UvQueryInfo::from_dir(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(info.stored_secrets(), 3);
+        assert_eq!(info.max_secrets(), Some(10));
+        assert_eq!(info.free_secrets(), Some(7));
+        assert_eq!(
+            info.supported_secret_types().unwrap(),
+            [
+                ListableSecretType::Association,
+                ListableSecretType::Retrievable(RetrievableSecret::PlainText)
+            ]
+        );
+    }
+
+    #[test]
+    fn no_query_dir() {
+        let info = SecretStoreInfo::with_query(&list(1), &UvQueryInfo::default());
+        assert_eq!(info.stored_secrets(), 1);
+        assert_eq!(info.max_secrets(), None);
+        assert_eq!(info.free_secrets(), None);
+        assert_eq!(info.supported_secret_types(), None);
+        assert_eq!(
+            info.to_string(),
+            "Stored secrets: 1\nMaximum number of secrets: unknown\n  Association secrets: unknown\n  Retrievable secrets: unknown\nFree secret slots: unknown\nSupported secret types: unknown\n"
+        );
+    }
+}
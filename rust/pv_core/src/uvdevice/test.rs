@@ -5,11 +5,12 @@
 #![cfg(test)]
 
 use std::{
+    collections::VecDeque,
     ffi::{c_int, c_ulong},
     sync::{Mutex, MutexGuard},
 };
 
-use super::*;
+use super::{fixture::IoctlExchange, *};
 use lazy_static::lazy_static;
 
 lazy_static! {
@@ -30,6 +31,10 @@ struct IoctlCtx {
     modify: Box<dyn FnMut(&mut ffi::uvio_ioctl_cb) -> i32 + Send + Sync>,
     exp_cmd: c_ulong,
     called: bool,
+    /// Number of further IOCTLs allowed after the first one
+    more_calls: usize,
+    /// Recorded exchanges to replay instead of calling `modify`
+    replay: VecDeque<IoctlExchange>,
 }
 
 impl IoctlCtx {
@@ -46,9 +51,25 @@ impl IoctlCtx {
         self
     }
 
+    pub fn more_calls(&mut self, n: usize) -> &mut Self {
+        self.more_calls = n;
+        self
+    }
+
+    /// Replay the exchanges of the fixture file `name` in `tests/fixtures`.
+    pub fn replay(&mut self, name: &str) -> &mut Self {
+        let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+        let content = std::fs::read_to_string(&path).unwrap();
+        self.replay = IoctlExchange::parse_fixture(&content).unwrap().into();
+        self.more_calls = self.replay.len().saturating_sub(1);
+        self
+    }
+
     pub fn reset(&mut self) -> bool {
         let old = self.called;
+        assert!(self.replay.is_empty(), "Not all exchanges were replayed");
         self.called = false;
+        self.more_calls = 0;
         old
     }
 
@@ -57,6 +78,8 @@ impl IoctlCtx {
             modify: Box::new(|_| -1),
             exp_cmd: 0,
             called: false,
+            more_calls: 0,
+            replay: VecDeque::new(),
         }
     }
 }
@@ -66,18 +89,59 @@ pub mod mock_libc {
 
     pub unsafe fn ioctl(_fd: c_int, cmd: c_ulong, data: *mut ffi::uvio_ioctl_cb) -> c_int {
         let mut ctx = get_lock(&IOCTL_MTX);
-        assert!(!ctx.called, "IOCTL called more than once");
+        if ctx.called {
+            assert!(ctx.more_calls > 0, "IOCTL called more than expected");
+            ctx.more_calls -= 1;
+        }
         ctx.called = true;
 
-        assert_eq!(cmd, ctx.exp_cmd, "IOCTL cmd mismatch");
-
         let data_ref: &mut ffi::uvio_ioctl_cb = &mut *data;
+        if let Some(ex) = ctx.replay.pop_front() {
+            return replay(cmd, data_ref, &ex);
+        }
+
+        assert_eq!(cmd, ctx.exp_cmd, "IOCTL cmd mismatch");
 
         (ctx.modify)(data_ref)
     }
+
+    /// Replays the recorded exchange `ex`.
+    ///
+    /// Only bytes the Ultravisor changed during the recording are written, so that addresses in
+    /// the data, e.g. of the attestation control block, stay valid.
+    unsafe fn replay(cmd: c_ulong, cb: &mut ffi::uvio_ioctl_cb, ex: &IoctlExchange) -> c_int {
+        assert_eq!(cmd, uv_ioctl(ex.nr), "IOCTL cmd mismatch");
+        cb.size_eq(ex.request.len() as u32);
+        if !ex.request.is_empty() {
+            let data =
+                std::slice::from_raw_parts_mut(cb.argument_addr as *mut u8, ex.request.len());
+            for (i, (req, resp)) in ex.request.iter().zip(&ex.response).enumerate() {
+                if req != resp {
+                    data[i] = *resp;
+                }
+            }
+        }
+        cb.set_rc(ex.rc).set_rrc(ex.rrc);
+        match ex.errno {
+            0 => 0,
+            errno => {
+                // SAFETY: errno is thread local
+                *::libc::__errno_location() = errno;
+                -1
+            }
+        }
+    }
 }
 
-impl ffi::uvio_ioctl_cb {
+/// Assertions and setters on the control block of the mocked IOCTL
+trait IoctlCbExt {
+    fn addr_eq(&self, exp: u64) -> &Self;
+    fn size_eq(&self, exp: u32) -> &Self;
+    fn set_rc(&mut self, rc: u16) -> &mut Self;
+    fn set_rrc(&mut self, rrc: u16) -> &mut Self;
+}
+
+impl IoctlCbExt for ffi::uvio_ioctl_cb {
     fn addr_eq(&self, exp: u64) -> &Self {
         assert_eq!(
             self.argument_addr, exp,
@@ -131,11 +195,66 @@ impl UvCmd for TestCmd {
 impl UvDevice {
     /// Use this file as backing file for  `uvdevice`. This is OK, as the ioctl is mocked and never touches the
     /// passed file
-    fn test_dev() -> Self {
-        Self(File::open(".").unwrap())
+    pub(crate) fn test_dev() -> Self {
+        Self(Arc::new(UvDeviceInner::new(
+            Backend::Device(File::open(".").unwrap()),
+            None,
+        )))
+    }
+}
+
+impl UvDeviceInner {
+    fn raw_fd(&self) -> RawFd {
+        match &*self.backend() {
+            Backend::Device(file) => file.as_raw_fd(),
+            #[cfg(feature = "uv-sim")]
+            Backend::Sim(_) => panic!("no device file"),
+        }
     }
 }
 
+#[test]
+fn from_fd() {
+    let dev: OwnedFd = File::open("/dev/null").unwrap().into();
+    assert!(UvDevice::from_fd(dev).is_ok());
+    let dir: OwnedFd = File::open(".").unwrap().into();
+    assert!(matches!(
+        UvDevice::from_fd(dir),
+        Err(Error::Specification(_))
+    ));
+}
+
+#[test]
+fn listen_fds() {
+    let n = UvDevice::FD_NAME;
+    assert_eq!(listen_fd(Some("42"), 42, Some("1"), None, n), Some(3));
+    assert_eq!(listen_fd(Some("42"), 42, Some("2"), None, n), None);
+    assert_eq!(listen_fd(Some("41"), 42, Some("1"), None, n), None);
+    assert_eq!(listen_fd(None, 42, Some("1"), None, n), None);
+    assert_eq!(listen_fd(Some("42"), 42, Some("0"), None, n), None);
+    assert_eq!(
+        listen_fd(Some("42"), 42, Some("3"), Some("sock:uv:log"), n),
+        Some(4)
+    );
+    assert_eq!(listen_fd(Some("42"), 42, Some("1"), Some("sock"), n), None);
+    assert_eq!(
+        listen_fd(Some("42"), 42, Some("1"), Some("sock:uv"), n),
+        None
+    );
+}
+
+#[test]
+fn take_listen_fd_once() {
+    let taken = AtomicBool::new(false);
+    assert_eq!(take_listen_fd(&taken, None).unwrap(), None);
+    assert_eq!(take_listen_fd(&taken, Some(3)).unwrap(), Some(3));
+    assert!(matches!(
+        take_listen_fd(&taken, Some(3)),
+        Err(Error::Specification(_))
+    ));
+    assert_eq!(take_listen_fd(&taken, None).unwrap(), None);
+}
+
 #[test]
 fn ioctl_fail() {
     let _m = get_lock(&TEST_LOCK);
@@ -183,6 +302,139 @@ fn ioctl_simple_err() {
     let res = uv.send_cmd(&mut mock_cmd);
     assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
     assert!(matches!(res, Err(Error::Uv{rc, rrc, ..}) if rc == 17 && rrc == 3 ));
+    assert!(UvMetrics::global()
+        .encode_prometheus()
+        .contains("uv_call_failures_total{cmd=\"Unknown\",rc=\"0x0011\",rrc=\"0x0003\"}"));
+}
+
+#[test]
+fn ioctl_observer() {
+    let _m = get_lock(&TEST_LOCK);
+
+    let calls = Arc::new(Mutex::new(vec![]));
+    let uv = UvDevice::test_dev();
+    let seen = calls.clone();
+    uv.set_observer(Some(Arc::new(move |call: &UvCall| {
+        seen.lock().unwrap().push(*call)
+    })));
+
+    let mut mock_cmd = TestCmd(Some(vec![0u8; 32]));
+    get_lock(&IOCTL_MTX).exp_cmd(TEST_CMD).set_mdfy(|cb| {
+        cb.set_rc(1);
+        0
+    });
+    assert!(uv.send_cmd(&mut mock_cmd).is_ok());
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    get_lock(&IOCTL_MTX).exp_cmd(TEST_CMD).set_mdfy(|cb| {
+        cb.set_rc(17).set_rrc(3);
+        0
+    });
+    assert!(uv.clone().send_cmd(&mut mock_cmd).is_err());
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    uv.set_observer(None);
+    get_lock(&IOCTL_MTX).exp_cmd(TEST_CMD).set_mdfy(|cb| {
+        cb.set_rc(1);
+        0
+    });
+    assert!(uv.send_cmd(&mut mock_cmd).is_ok());
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.len(), 2);
+    assert_eq!((calls[0].cmd, calls[0].nr), ("Unknown", 42));
+    assert_eq!(calls[0].request_size, 32);
+    assert_eq!(calls[0].response_size, None);
+    assert_eq!(calls[0].outcome, CallOutcome::Success);
+    assert_eq!(calls[1].outcome, CallOutcome::Uv { rc: 17, rrc: 3 });
+}
+
+#[test]
+fn ioctl_more_data() {
+    let _m = get_lock(&TEST_LOCK);
+
+    get_lock(&IOCTL_MTX).exp_cmd(TEST_CMD).set_mdfy(|cb| {
+        cb.set_rc(0x100).set_rrc(7).addr_eq(0).size_eq(0);
+        0
+    });
+
+    let uv = UvDevice::test_dev();
+    let res = uv.send_cmd(&mut TestCmd(None)).unwrap();
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert_eq!(res.success(), UvcSuccess::RC_MORE_DATA);
+    assert!(res.more_data());
+    assert_eq!((res.rc(), res.rrc()), (0x100, 7));
+    assert_eq!(res.written(), None);
+}
+
+#[test]
+fn ioctl_raw_data() {
+    let _m = get_lock(&TEST_LOCK);
+
+    let mut data = vec![0u8; 16];
+    let data_addr = data.as_ptr() as u64;
+    get_lock(&IOCTL_MTX)
+        .exp_cmd(uv_ioctl(42))
+        .set_mdfy(move |cb| {
+            cb.set_rc(0x100).set_rrc(2).addr_eq(data_addr).size_eq(16);
+            unsafe {
+                ::libc::memset(cb.argument_addr as *mut ::libc::c_void, 0x42, 16);
+            }
+            0
+        });
+
+    let uv = UvDevice::test_dev();
+    let res = uv.send_raw(42, &mut data).unwrap();
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert_eq!(res, (UvcSuccess::RC_MORE_DATA, 0x100, 2));
+    assert_eq!(data, [0x42; 16]);
+}
+
+#[test]
+fn ioctl_raw_err() {
+    let _m = get_lock(&TEST_LOCK);
+
+    get_lock(&IOCTL_MTX).exp_cmd(uv_ioctl(42)).set_mdfy(|cb| {
+        cb.set_rc(17).set_rrc(3).addr_eq(0).size_eq(0);
+        0
+    });
+
+    let uv = UvDevice::test_dev();
+    let res = uv.send_raw(42, &mut []);
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert!(matches!(res, Err(Error::Uv { rc: 17, rrc: 3, .. })));
+}
+
+fn batch(policy: BatchPolicy) -> Vec<Result<UvcResponse>> {
+    let mut call = 0;
+    get_lock(&IOCTL_MTX)
+        .exp_cmd(TEST_CMD)
+        .more_calls(2)
+        .set_mdfy(move |cb| {
+            call += 1;
+            cb.set_rc(if call == 2 { 0x102 } else { 1 });
+            0
+        });
+
+    let uv = UvDevice::test_dev();
+    let (mut a, mut b, mut c) = (TestCmd(None), TestCmd(None), TestCmd(Some(vec![0; 8])));
+    let res = uv.send_batch(&mut [&mut a, &mut b, &mut c], policy);
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    res
+}
+
+#[test]
+fn ioctl_batch() {
+    let _m = get_lock(&TEST_LOCK);
+
+    let res = batch(BatchPolicy::FailFast);
+    assert_eq!(res.len(), 2);
+    assert!(res[0].is_ok());
+    assert!(matches!(res[1], Err(Error::Uv { rc: 0x102, .. })));
+
+    let res = batch(BatchPolicy::ContinueOnError);
+    assert_eq!(res.len(), 3);
+    assert!(res[1].is_err());
+    assert_eq!(res[2].as_ref().unwrap().success(), UvcSuccess::RC_SUCCESS);
 }
 
 #[test]
@@ -206,7 +458,7 @@ fn ioctl_write_data() {
     let uv = UvDevice::test_dev();
     let res = uv.send_cmd(&mut mock_cmd);
     assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
-    assert_eq!(res.unwrap(), UvcSuccess::RC_SUCCESS);
+    assert_eq!(res.unwrap().success(), UvcSuccess::RC_SUCCESS);
 }
 
 #[test]
@@ -232,5 +484,296 @@ fn ioctl_read_data() {
     let uv = UvDevice::test_dev();
     let res = uv.send_cmd(&mut mock_cmd);
     assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
-    assert_eq!(res.unwrap(), UvcSuccess::RC_SUCCESS);
+    assert_eq!(res.unwrap().success(), UvcSuccess::RC_SUCCESS);
+}
+
+#[test]
+fn ioctl_cloned_dev_other_thread() {
+    let _m = get_lock(&TEST_LOCK);
+
+    get_lock(&IOCTL_MTX).exp_cmd(TEST_CMD).set_mdfy(|cb| {
+        cb.set_rc(1).addr_eq(0).size_eq(0);
+        0
+    });
+
+    let uv = UvDevice::test_dev();
+    let uv_clone = uv.clone();
+    let res = std::thread::spawn(move || uv_clone.send_cmd(&mut TestCmd(None)))
+        .join()
+        .unwrap();
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert_eq!(res.unwrap().success(), UvcSuccess::RC_SUCCESS);
+    assert_eq!(uv.0.raw_fd(), UvDevice::clone(&uv).0.raw_fd());
+}
+
+#[test]
+fn ioctl_unsupported() {
+    let _m = get_lock(&TEST_LOCK);
+
+    get_lock(&IOCTL_MTX).exp_cmd(TEST_CMD).set_mdfy(|_| {
+        // SAFETY: errno is thread local
+        unsafe { *::libc::__errno_location() = ::libc::ENOTTY };
+        -1
+    });
+
+    let uv = UvDevice::test_dev();
+    let res = uv.send_cmd(&mut TestCmd(None));
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert!(matches!(
+        res,
+        Err(Error::Unsupported {
+            nr: 42,
+            name: "Unknown"
+        })
+    ));
+}
+
+fn enodev(cb: &mut ffi::uvio_ioctl_cb) -> c_int {
+    cb.set_rc(0);
+    // SAFETY: errno is thread local
+    unsafe { *::libc::__errno_location() = ::libc::ENODEV };
+    -1
+}
+
+#[test]
+fn ioctl_device_gone() {
+    let _m = get_lock(&TEST_LOCK);
+
+    get_lock(&IOCTL_MTX).exp_cmd(TEST_CMD).set_mdfy(enodev);
+
+    let uv = UvDevice::test_dev();
+    assert_eq!(uv.reconnect_policy(), ReconnectPolicy::Never);
+    let res = uv.send_cmd(&mut TestCmd(None));
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert!(matches!(res, Err(Error::DeviceGone)));
+
+    // A device passed as file descriptor cannot be reopened.
+    uv.set_reconnect_policy(ReconnectPolicy::Reopen);
+    get_lock(&IOCTL_MTX).exp_cmd(TEST_CMD).set_mdfy(enodev);
+    let res = uv.send_cmd(&mut TestCmd(None));
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert!(matches!(res, Err(Error::DeviceGone)));
+}
+
+#[test]
+fn ioctl_reconnect() {
+    let _m = get_lock(&TEST_LOCK);
+
+    let mut call = 0;
+    get_lock(&IOCTL_MTX)
+        .exp_cmd(uv_ioctl(ffi::UVIO_IOCTL_UVDEV_INFO_NR))
+        .more_calls(1)
+        .set_mdfy(move |cb| {
+            call += 1;
+            match call {
+                1 => enodev(cb),
+                _ => {
+                    cb.set_rc(1);
+                    0
+                }
+            }
+        });
+
+    let uv = UvDevice(Arc::new(UvDeviceInner::new(
+        Backend::Device(File::open(".").unwrap()),
+        Some("/dev/null"),
+    )));
+    uv.set_reconnect_policy(ReconnectPolicy::Reopen);
+    let fd = uv.0.raw_fd();
+    // The info command is idempotent and retried with the reopened device.
+    assert!(uv.info().is_ok());
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert_ne!(uv.0.raw_fd(), fd);
+
+    // Other commands are not retried.
+    get_lock(&IOCTL_MTX).exp_cmd(TEST_CMD).set_mdfy(enodev);
+    let res = uv.send_cmd(&mut TestCmd(None));
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert!(matches!(res, Err(Error::DeviceGone)));
+}
+
+#[test]
+fn info_cached() {
+    let _m = get_lock(&TEST_LOCK);
+
+    let mdfy = |cb: &mut ffi::uvio_ioctl_cb| {
+        cb.set_rc(1)
+            .size_eq(size_of::<ffi::uvio_uvdev_info>() as u32);
+        0
+    };
+    get_lock(&IOCTL_MTX)
+        .exp_cmd(uv_ioctl(ffi::UVIO_IOCTL_UVDEV_INFO_NR))
+        .set_mdfy(mdfy);
+
+    let uv = UvDevice::test_dev();
+    let info = uv.info().unwrap();
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    // The mock fails if the IOCTL is called a second time.
+    let cached = uv.clone().info().unwrap();
+    assert!(Arc::ptr_eq(&info, &cached));
+    assert!(!get_lock(&IOCTL_MTX).reset(), "IOCTL was called again");
+
+    uv.refresh();
+    let refreshed = uv.info().unwrap();
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert!(!Arc::ptr_eq(&info, &refreshed));
+}
+
+#[test]
+fn ioctl_retry() {
+    let _m = get_lock(&TEST_LOCK);
+
+    let mut call = 0;
+    get_lock(&IOCTL_MTX)
+        .exp_cmd(TEST_CMD)
+        .more_calls(2)
+        .set_mdfy(move |cb| {
+            call += 1;
+            cb.set_rc(if call < 3 { 0x0102 } else { 1 });
+            0
+        });
+
+    let uv = UvDevice::test_dev();
+    uv.set_retry_policy(RetryPolicy::new(3).retry_rc(0x0102, None));
+    let mut delays = vec![];
+    let res = uv.send_cmd_retry(&mut TestCmd(None), |d| delays.push(d));
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert_eq!(res.unwrap().rc(), UvDevice::RC_SUCCESS);
+    assert_eq!(
+        delays,
+        [
+            RetryPolicy::DEFAULT_BACKOFF,
+            2 * RetryPolicy::DEFAULT_BACKOFF
+        ]
+    );
+
+    // Give up after the last attempt
+    get_lock(&IOCTL_MTX)
+        .exp_cmd(TEST_CMD)
+        .more_calls(1)
+        .set_mdfy(|cb| {
+            cb.set_rc(0x0102);
+            0
+        });
+    uv.set_retry_policy(RetryPolicy::new(2).retry_rc(0x0102, None));
+    let res = uv.send_cmd_retry(&mut TestCmd(None), |_| {});
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert!(matches!(res, Err(Error::Uv { rc: 0x0102, .. })));
+}
+
+#[test]
+fn ioctl_prepared() {
+    let _m = get_lock(&TEST_LOCK);
+
+    let mut prep = PreparedCmd::new(crate::uv::ListCmd::new());
+    let mut addr = None;
+    get_lock(&IOCTL_MTX)
+        .exp_cmd(uv_ioctl(ffi::UVIO_IOCTL_LIST_SECRETS_NR))
+        .more_calls(1)
+        .set_mdfy(move |cb| {
+            // the same buffer is used for every execution
+            assert_eq!(*addr.get_or_insert(cb.argument_addr), cb.argument_addr);
+            cb.size_eq(crate::PAGESIZE as u32);
+            cb.set_rc(1);
+            0
+        });
+
+    let uv = UvDevice::test_dev();
+    prep.send(&uv).unwrap();
+    prep.send(&uv).unwrap();
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert!(crate::uv::SecretList::try_from(prep.cmd())
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn replay_list_more_data() {
+    let _m = get_lock(&TEST_LOCK);
+
+    get_lock(&IOCTL_MTX).replay("list_more_data.fixture");
+    let uv = UvDevice::test_dev();
+    let mut cmd = crate::uv::ListCmd::new();
+    let res = uv.send_cmd(&mut cmd).unwrap();
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert!(res.more_data());
+    assert_eq!(res.written(), Some(0x40));
+
+    let list = crate::uv::SecretList::try_from(cmd).unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list.total_num_secrets(), 2);
+}
+
+#[test]
+fn replay_lock_locked() {
+    let _m = get_lock(&TEST_LOCK);
+
+    get_lock(&IOCTL_MTX).replay("lock_locked.fixture");
+    let uv = UvDevice::test_dev();
+    let res = uv.send_cmd(&mut crate::uv::LockCmd);
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    let err = res.unwrap_err();
+    assert_eq!(err.code(), "store-locked");
+}
+
+#[test]
+fn ioctl_input_only() {
+    let _m = get_lock(&TEST_LOCK);
+
+    let req = b"asrcbM\0\x01";
+    let data_addr = req.as_ptr() as u64;
+    let mut cmd = crate::uv::AddCmd::from_buf(req.as_slice()).unwrap();
+
+    get_lock(&IOCTL_MTX)
+        .exp_cmd(uv_ioctl(ffi::UVIO_IOCTL_ADD_SECRET_NR))
+        .set_mdfy(move |cb| {
+            cb.set_rc(1).addr_eq(data_addr).size_eq(8);
+            0
+        });
+
+    let uv = UvDevice::test_dev();
+    let res = uv.send_cmd(&mut cmd);
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert_eq!(res.unwrap().success(), UvcSuccess::RC_SUCCESS);
+}
+
+#[test]
+fn replay_add_dump_in_progress() {
+    let _m = get_lock(&TEST_LOCK);
+
+    get_lock(&IOCTL_MTX).replay("add_dump_in_progress.fixture");
+    let uv = UvDevice::test_dev();
+    uv.set_retry_policy(RetryPolicy::new(2));
+    let mut cmd = crate::uv::AddCmd::from_vec(b"asrcbM\0\x01".to_vec()).unwrap();
+    let res = uv.send_cmd_retry(&mut cmd, |_| ());
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    assert_eq!(res.unwrap().success(), UvcSuccess::RC_SUCCESS);
+}
+
+#[test]
+#[cfg(feature = "record-ioctl")]
+fn record_replay() {
+    let _m = get_lock(&TEST_LOCK);
+
+    let path = env::temp_dir().join(format!("pv-record-{}.fixture", std::process::id()));
+    IoctlRecorder::start(&path).unwrap();
+    get_lock(&IOCTL_MTX).replay("add_dump_in_progress.fixture");
+    let uv = UvDevice::test_dev();
+    uv.set_retry_policy(RetryPolicy::new(2));
+    let mut cmd = crate::uv::AddCmd::from_vec(b"asrcbM\0\x01".to_vec()).unwrap();
+    uv.send_cmd_retry(&mut cmd, |_| ()).unwrap();
+    assert!(get_lock(&IOCTL_MTX).reset(), "IOCTL was never called");
+    IoctlRecorder::stop();
+
+    let recorded = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let exp = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/add_dump_in_progress.fixture"
+    ))
+    .unwrap();
+    assert_eq!(
+        IoctlExchange::parse_fixture(&recorded).unwrap(),
+        IoctlExchange::parse_fixture(&exp).unwrap()
+    );
 }
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fmt::Write,
+    ops::Range,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        OnceLock,
+    },
+};
+
+use log::{debug, log_enabled, Level};
+
+use super::UvCmd;
+
+/// Environment variable that enables the tracing if set to `1`
+const TRACE_ENV: &str = "PV_IOCTL_TRACE";
+const BYTES_PER_LINE: usize = 16;
+
+const UNSET: u8 = 0;
+const ON: u8 = 1;
+const OFF: u8 = 2;
+static STATE: AtomicU8 = AtomicU8::new(UNSET);
+
+/// Hexdumps of the buffers of Ultravisor calls
+///
+/// If enabled, [`super::UvDevice::send_cmd`] logs the data buffer of every command at
+/// [`Level::Debug`] before and after the ioctl. Bytes the command marks as secret with
+/// [`UvCmd::secret_ranges`] are masked as `**`. Repeated identical lines are collapsed into `*`.
+///
+/// Tracing is disabled unless enabled with [`IoctlTrace::enable`] or with the environment
+/// variable `PV_IOCTL_TRACE=1`.
+#[derive(Debug)]
+pub struct IoctlTrace;
+
+impl IoctlTrace {
+    /// Enable or disable the tracing, overriding the environment.
+    pub fn enable(enable: bool) {
+        STATE.store(if enable { ON } else { OFF }, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the tracing is enabled.
+    pub fn is_enabled() -> bool {
+        static ENV: OnceLock<bool> = OnceLock::new();
+        match STATE.load(Ordering::Relaxed) {
+            ON => true,
+            OFF => false,
+            _ => *ENV.get_or_init(|| std::env::var_os(TRACE_ENV).is_some_and(|v| v == "1")),
+        }
+    }
+}
+
+/// Formats `buf` as hexdump and masks all bytes in `secret`.
+fn hexdump(buf: &[u8], secret: &[Range<usize>]) -> String {
+    let mut out = String::new();
+    let mut prev: Option<String> = None;
+    let mut collapsed = false;
+    for (nr, line) in buf.chunks(BYTES_PER_LINE).enumerate() {
+        let offset = nr * BYTES_PER_LINE;
+        let mut hex = String::with_capacity(3 * BYTES_PER_LINE);
+        for (i, b) in line.iter().enumerate() {
+            match secret.iter().any(|r| r.contains(&(offset + i))) {
+                true => hex.push_str(" **"),
+                false => {
+                    let _ = write!(hex, " {b:02x}");
+                }
+            }
+        }
+        if prev.as_ref() == Some(&hex) && line.len() == BYTES_PER_LINE {
+            if !collapsed {
+                out.push_str("*\n");
+                collapsed = true;
+            }
+            continue;
+        }
+        collapsed = false;
+        let _ = writeln!(out, "{offset:08x}:{hex}");
+        prev = Some(hex);
+    }
+    if collapsed {
+        let _ = writeln!(out, "{:08x}", buf.len());
+    }
+    out
+}
+
+/// Log the data buffer of `cmd` if the tracing is enabled.
+///
+/// The `request` stage logs the buffer the Ultravisor reads, the `response` stage the buffer it
+/// writes.
+pub(super) fn dump<C: UvCmd>(stage: &str, name: &str, cmd: &mut C) {
+    if !IoctlTrace::is_enabled() || !log_enabled!(Level::Debug) {
+        return;
+    }
+    let secret = cmd.secret_ranges();
+    let arg = cmd.arg();
+    let data = match stage {
+        "request" => arg.input(),
+        _ => arg.output(),
+    };
+    match data {
+        Some(data) => debug!(
+            uvc = name, stage = stage, len = data.len();
+            "{name} UVC {stage} buffer ({} bytes):\n{}", data.len(), hexdump(data, &secret)
+        ),
+        None => debug!(uvc = name, stage = stage; "{name} UVC {stage}: no buffer"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hexdump_mask() {
+        let buf: Vec<u8> = (0..20).collect();
+        assert_eq!(
+            hexdump(&buf, &[2..4, 17..100]),
+            "00000000: 00 01 ** ** 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n\
+             00000010: 10 ** ** **\n"
+        );
+    }
+
+    #[test]
+    fn hexdump_collapse() {
+        let mut buf = vec![0u8; 64];
+        buf[63] = 1;
+        assert_eq!(
+            hexdump(&buf, &[]),
+            "00000000: 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00\n\
+             *\n\
+             00000030: 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 01\n"
+        );
+        assert_eq!(
+            hexdump(&[0; 48], &[]),
+            "00000000: 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00\n*\n00000030\n"
+        );
+    }
+
+    #[test]
+    fn enable() {
+        IoctlTrace::enable(true);
+        assert!(IoctlTrace::is_enabled());
+        IoctlTrace::enable(false);
+        assert!(!IoctlTrace::is_enabled());
+    }
+}
@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! D-Bus system service for IBM Secure Execution
+//!
+//! Implements the `com.ibm.s390.Pv1` interface on the object `/com/ibm/s390/Pv1`:
+//!
+//! - `GetAttestationEvidence` runs the Attestation UVC inside a Secure Execution guest,
+//! - `ListSecrets` lists the secrets of the secret store of the guest,
+//! - `RetrieveSecret` retrieves a retrievable secret from the secret store.
+//!
+//! Every call is authorized by polkit before it is processed. The actions are defined in
+//! `data/com.ibm.s390.pv1.policy`.
+
+use std::collections::HashMap;
+
+use log::{debug, info};
+use pv::{
+    attest::UserData,
+    uv::{AttestationCmd, ListCmd, RetrieveCmd, SecretId, SecretList, UvDevice},
+};
+use zbus::{fdo, interface, message::Header, zvariant::Value, Connection};
+
+/// Well-known bus name of the service
+pub const BUS_NAME: &str = "com.ibm.s390.Pv1";
+/// Path of the service object
+pub const OBJECT_PATH: &str = "/com/ibm/s390/Pv1";
+
+/// Methods of the service, each guarded by a polkit action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// `GetAttestationEvidence`
+    GetAttestationEvidence,
+    /// `ListSecrets`
+    ListSecrets,
+    /// `RetrieveSecret`
+    RetrieveSecret,
+}
+
+impl Action {
+    /// The polkit action ID
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::GetAttestationEvidence => "com.ibm.s390.pv1.get-attestation-evidence",
+            Self::ListSecrets => "com.ibm.s390.pv1.list-secrets",
+            Self::RetrieveSecret => "com.ibm.s390.pv1.retrieve-secret",
+        }
+    }
+}
+
+/// polkit authority interface, see `org.freedesktop.PolicyKit1.Authority(5)`
+#[zbus::proxy(
+    interface = "org.freedesktop.PolicyKit1.Authority",
+    default_service = "org.freedesktop.PolicyKit1",
+    default_path = "/org/freedesktop/PolicyKit1/Authority"
+)]
+trait Authority {
+    /// Checks whether `subject` is authorized for `action_id`.
+    ///
+    /// Returns `(is_authorized, is_challenge, details)`.
+    fn check_authorization(
+        &self,
+        subject: &(&str, HashMap<&str, Value<'_>>),
+        action_id: &str,
+        details: HashMap<&str, &str>,
+        flags: u32,
+        cancellation_id: &str,
+    ) -> zbus::Result<(bool, bool, HashMap<String, String>)>;
+}
+
+/// polkit flag to allow an interactive authentication of the caller
+const POLKIT_ALLOW_USER_INTERACTION: u32 = 1;
+
+/// How callers are authorized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authorization {
+    /// Ask polkit for every call
+    Polkit,
+    /// Allow only callers running as root, e.g. on systems without polkit
+    RootOnly,
+}
+
+fn failed<E: std::fmt::Display>(e: E) -> fdo::Error {
+    fdo::Error::Failed(e.to_string())
+}
+
+fn invalid<E: std::fmt::Display>(e: E) -> fdo::Error {
+    fdo::Error::InvalidArgs(e.to_string())
+}
+
+/// Runs a blocking Ultravisor call on the blocking thread pool.
+async fn uv_call<T, F>(f: F) -> fdo::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&UvDevice) -> pv::Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let uv = UvDevice::open().map_err(failed)?;
+        f(&uv).map_err(failed)
+    })
+    .await
+    .map_err(failed)?
+}
+
+fn list_secrets(uv: &UvDevice) -> pv::Result<SecretList> {
+    let mut cmd = ListCmd::default();
+    uv.send_cmd(&mut cmd)?;
+    Ok(cmd.try_into()?)
+}
+
+/// Implementation of the `com.ibm.s390.Pv1` D-Bus interface
+#[derive(Debug)]
+pub struct PvService {
+    auth: Authorization,
+}
+
+impl PvService {
+    /// Creates the service that authorizes callers with `auth`.
+    pub fn new(auth: Authorization) -> Self {
+        Self { auth }
+    }
+
+    async fn authorize(
+        &self,
+        action: Action,
+        hdr: &Header<'_>,
+        conn: &Connection,
+    ) -> fdo::Result<()> {
+        let sender = hdr
+            .sender()
+            .ok_or_else(|| fdo::Error::AccessDenied("The caller is unknown".to_string()))?;
+        let authorized = match self.auth {
+            Authorization::RootOnly => {
+                let dbus = fdo::DBusProxy::new(conn).await?;
+                dbus.get_connection_unix_user(sender.clone().into()).await? == 0
+            }
+            Authorization::Polkit => {
+                let subject = (
+                    "system-bus-name",
+                    HashMap::from([("name", Value::from(sender.as_str()))]),
+                );
+                let (authorized, _, _) = AuthorityProxy::new(conn)
+                    .await?
+                    .check_authorization(
+                        &subject,
+                        action.id(),
+                        HashMap::new(),
+                        POLKIT_ALLOW_USER_INTERACTION,
+                        "",
+                    )
+                    .await?;
+                authorized
+            }
+        };
+        debug!("{sender} authorized for {}: {authorized}", action.id());
+        match authorized {
+            true => Ok(()),
+            false => Err(fdo::Error::AccessDenied(format!(
+                "Not authorized for '{}'",
+                action.id()
+            ))),
+        }
+    }
+}
+
+#[interface(name = "com.ibm.s390.Pv1")]
+impl PvService {
+    /// Runs the attestation request `request` and returns the measurement, the additional
+    /// data, and the configuration unique ID.
+    ///
+    /// An empty `user_data` runs the attestation without user data.
+    async fn get_attestation_evidence(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        request: Vec<u8>,
+        user_data: Vec<u8>,
+        measurement_size: u32,
+        additional_size: u32,
+    ) -> fdo::Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+        self.authorize(Action::GetAttestationEvidence, &hdr, conn)
+            .await?;
+        let user_data = match user_data.is_empty() {
+            true => None,
+            false => Some(UserData::new(user_data).map_err(invalid)?),
+        };
+        let mut cmd = AttestationCmd::new_request(
+            request.into_boxed_slice(),
+            user_data,
+            measurement_size,
+            additional_size,
+        )
+        .map_err(invalid)?;
+        let cmd = uv_call(move |uv| {
+            uv.send_cmd(&mut cmd)?;
+            Ok(cmd)
+        })
+        .await?;
+        let cuid = cmd.cuid().to_vec();
+        let (_, measurement, additional) = cmd.into_parts();
+        Ok((measurement, additional.unwrap_or_default(), cuid))
+    }
+
+    /// Lists the secrets of the secret store as `(index, type, id, size)`.
+    async fn list_secrets(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> fdo::Result<Vec<(u16, String, Vec<u8>, u32)>> {
+        self.authorize(Action::ListSecrets, &hdr, conn).await?;
+        let list = uv_call(list_secrets).await?;
+        Ok(list
+            .iter()
+            .map(|s| {
+                (
+                    s.index(),
+                    s.stype().to_string(),
+                    s.id().to_vec(),
+                    s.secret_size(),
+                )
+            })
+            .collect())
+    }
+
+    /// Retrieves the retrievable secret with the ID `id`.
+    async fn retrieve_secret(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        id: Vec<u8>,
+    ) -> fdo::Result<Vec<u8>> {
+        self.authorize(Action::RetrieveSecret, &hdr, conn).await?;
+        let id: [u8; SecretId::ID_SIZE] = id
+            .try_into()
+            .map_err(|_| invalid(format!("The ID must be {} bytes long", SecretId::ID_SIZE)))?;
+        let id = SecretId::from(id);
+        let secret = uv_call(move |uv| {
+            let entry = list_secrets(uv)?.find(&id).ok_or_else(|| {
+                pv::PvCoreError::Specification(format!("There is no secret with the ID {id}"))
+            })?;
+            info!("Retrieve the secret at index {}", entry.index());
+            let mut cmd = RetrieveCmd::from_entry(entry)?;
+            uv.send_cmd(&mut cmd)?;
+            Ok(cmd.into_key())
+        })
+        .await?;
+        Ok(secret.value().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn action_ids() {
+        let policy = include_str!("../data/com.ibm.s390.pv1.policy");
+        for action in [
+            Action::GetAttestationEvidence,
+            Action::ListSecrets,
+            Action::RetrieveSecret,
+        ] {
+            assert!(policy.contains(&format!("id=\"{}\"", action.id())));
+        }
+    }
+}
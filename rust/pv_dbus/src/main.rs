@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+
+use std::process::ExitCode;
+
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser};
+use log::{info, trace};
+use pv_dbus::{Authorization, PvService, BUS_NAME, OBJECT_PATH};
+use utils::{print_cli_error, print_error, print_version, PvLogger, VerbosityOptions};
+use zbus::connection::Builder;
+
+static LOGGER: PvLogger = PvLogger;
+static EXIT_LOGGER: u8 = 3;
+
+/// Serve IBM Secure Execution functions on the D-Bus system bus.
+///
+/// Inside a Secure Execution guest the service provides attestation evidence and access to the
+/// secret store as 'com.ibm.s390.Pv1'. Every call is authorized by polkit.
+#[derive(Parser, Debug)]
+struct CliOptions {
+    #[clap(flatten)]
+    verbosity: VerbosityOptions,
+
+    /// Serve on the session bus instead of the system bus, e.g. for testing.
+    #[arg(long)]
+    session: bool,
+
+    /// Do not use polkit, allow only callers running as root.
+    #[arg(long)]
+    no_polkit: bool,
+
+    /// Print version information and exit.
+    #[arg(long)]
+    version: bool,
+}
+
+async fn run(cli: &CliOptions) -> Result<()> {
+    let auth = match cli.no_polkit {
+        true => Authorization::RootOnly,
+        false => Authorization::Polkit,
+    };
+    let builder = match cli.session {
+        true => Builder::session()?,
+        false => Builder::system()?,
+    };
+    let _conn = builder
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, PvService::new(auth))?
+        .build()
+        .await
+        .with_context(|| format!("Cannot serve '{BUS_NAME}'"))?;
+    info!("Serving {BUS_NAME} at {OBJECT_PATH}");
+    std::future::pending::<()>().await;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli: CliOptions = match CliOptions::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => return print_cli_error(e, CliOptions::command()),
+    };
+
+    // set up logger/std(out,err)
+    let log_level = cli.verbosity.to_level_filter();
+    if let Err(e) = LOGGER.start_with_format(log_level, cli.verbosity.log_format()) {
+        // should(TM) never happen
+        eprintln!("Logger error: {e:?}");
+        return EXIT_LOGGER.into();
+    }
+
+    if cli.version {
+        print_version!("2025", log_level);
+        return ExitCode::SUCCESS;
+    }
+
+    trace!("Options {cli:?}");
+
+    match run(&cli).await {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => print_error(&e, log_level),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        super::CliOptions::command().debug_assert()
+    }
+}
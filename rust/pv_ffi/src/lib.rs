@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+#![doc = include_str!("../README.md")]
+#![no_std]
+
+mod macros;
+
+/// Bitflags in MSB0 and LSB0 ordering
+pub mod flags;
+
+/// The unencrypted parts of the Secure Execution header
+pub mod se_hdr;
+
+/// Rust representation of the uvdevice header
+#[allow(missing_docs, non_camel_case_types)]
+pub mod uvio;
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, KnownLayout, U32, U64};
+
+use crate::assert_size;
+
+/// Fixed-size, unencrypted head of a Secure Execution header (version 1)
+///
+/// All multi-byte values are stored big-endian; the accessors return them in host byte order.
+#[repr(C)]
+#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout)]
+pub struct SeHdrHead {
+    magic: U64<BigEndian>,
+    version: U32<BigEndian>,
+    size: U32<BigEndian>,
+    iv: [u8; 12],
+    res1: [u8; 4],
+    nks: U64<BigEndian>,
+    sea: U64<BigEndian>,
+    nep: U64<BigEndian>,
+    pcf: U64<BigEndian>,
+    user_pubkey: [u8; 160],
+    pld: [u8; Self::DIGEST_SIZE],
+    ald: [u8; Self::DIGEST_SIZE],
+    tld: [u8; Self::DIGEST_SIZE],
+}
+assert_size!(SeHdrHead, 0x1A0);
+
+impl SeHdrHead {
+    /// Size of the digests in bytes
+    pub const DIGEST_SIZE: usize = 0x40;
+    /// Size of the tag of the header in bytes
+    pub const TAG_SIZE: usize = 0x10;
+    /// Version 1 of the Secure Execution header
+    pub const V1: u32 = 0x100;
+    /// Maximum size of a complete header in bytes; the Ultravisor accepts at most two pages
+    pub const MAX_SIZE: usize = 0x2000;
+
+    /// Returns the version of the header.
+    pub fn version(&self) -> u32 {
+        self.version.get()
+    }
+
+    /// Returns the size of the complete header in bytes.
+    pub fn size(&self) -> u32 {
+        self.size.get()
+    }
+
+    /// Returns the initialization vector used for the encrypted part.
+    pub fn iv(&self) -> &[u8; 12] {
+        &self.iv
+    }
+
+    /// Returns the number of key slots.
+    pub fn nks(&self) -> u64 {
+        self.nks.get()
+    }
+
+    /// Returns the size of the encrypted area in bytes.
+    pub fn sea(&self) -> u64 {
+        self.sea.get()
+    }
+
+    /// Returns the number of encrypted pages of the image.
+    pub fn nep(&self) -> u64 {
+        self.nep.get()
+    }
+
+    /// Returns the plaintext control flags.
+    pub fn pcf(&self) -> u64 {
+        self.pcf.get()
+    }
+
+    /// Returns the public key of the customer (EC P-521 coordinates).
+    pub fn cust_pub_key(&self) -> &[u8; 160] {
+        &self.user_pubkey
+    }
+
+    /// Returns the page list digest.
+    pub fn pld(&self) -> &[u8; Self::DIGEST_SIZE] {
+        &self.pld
+    }
+
+    /// Returns the address list digest.
+    pub fn ald(&self) -> &[u8; Self::DIGEST_SIZE] {
+        &self.ald
+    }
+
+    /// Returns the tweak list digest.
+    pub fn tld(&self) -> &[u8; Self::DIGEST_SIZE] {
+        &self.tld
+    }
+}
+
+/// Key slot of a Secure Execution header (version 1)
+#[repr(C)]
+#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout)]
+pub struct SeHdrKeySlot {
+    phkh: [u8; 32],
+    wrapped_key: [u8; 32],
+    tag: [u8; 16],
+}
+assert_size!(SeHdrKeySlot, 0x50);
+
+impl SeHdrKeySlot {
+    /// Returns the public host key hash of this key slot.
+    pub fn phkh(&self) -> &[u8; 32] {
+        &self.phkh
+    }
+
+    /// Returns the wrapped image key.
+    pub fn wrapped_key(&self) -> &[u8; 32] {
+        &self.wrapped_key
+    }
+
+    /// Returns the authentication tag of the wrapped key.
+    pub fn tag(&self) -> &[u8; 16] {
+        &self.tag
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::mem::size_of;
+
+    use super::*;
+
+    #[test]
+    fn head_big_endian() {
+        let mut raw = [0u8; size_of::<SeHdrHead>()];
+        raw[8..12].copy_from_slice(&SeHdrHead::V1.to_be_bytes());
+        raw[12..16].copy_from_slice(&0x1000u32.to_be_bytes());
+        raw[39] = 2;
+        let head = SeHdrHead::ref_from_bytes(&raw).unwrap();
+        assert_eq!(head.version(), SeHdrHead::V1);
+        assert_eq!(head.size(), 0x1000);
+        assert_eq!(head.nks(), 2);
+    }
+}
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2023, 2025
+
+//! Rust representation of linux/arch/s390/include/uapi/asm/uvdevice.h
+//!
+//! Every submodule contains the definitions that a kernel release added to that header. The
+//! definitions are re-exported here, so that users do not need to know in which release a
+//! definition was introduced. There might be things that are not needed here but nonetheless
+//! defined in that header. The header and this module should be in sync, hence there might be
+//! unused/dead code. The `UVIO_IOCTL_*` and `UVIO_SUPP_*` macros are implemented as functions.
+//!
+//! Multi-byte integers of the structs the Ultravisor reads or writes use the big-endian wrappers
+//! of `zerocopy`, e.g. `U32<BigEndian>`. They have the same layout as the native integers on
+//! s390x, but tools that parse these structs on a little-endian host cannot misread them. The
+//! IOCTL control block [`uvio_ioctl_cb`] is only read by the kernel and stays native.
+//!
+//! # Synchronizing with a new header revision
+//!
+//! 1. Run `pv_ffi/tools/uvdevice-h-sync check <uvdevice.h>` with the header of the new kernel.
+//!    It reports definitions that are missing, changed, or were removed.
+//! 2. Run `pv_ffi/tools/uvdevice-h-sync generate <uvdevice.h> <version>` to create a submodule
+//!    with the missing definitions, e.g. `v6_20.rs`. Review the generated documentation and
+//!    derives and add the module below.
+//! 3. Add the IOCTL numbers of the new submodule to `IOCTL_SINCE` and name them in
+//!    `nr_as_string` of `s390_pv_core`.
+//! 4. Never change existing definitions; the kernel ABI is stable. If the check reports a changed
+//!    definition, the Rust representation is wrong.
+
+use core::mem::size_of;
+
+use crate::static_assert;
+
+mod v5_19;
+mod v6_13;
+mod v6_5;
+
+pub use v5_19::*;
+pub use v6_13::*;
+pub use v6_5::*;
+
+/// The kernel release that introduced an IOCTL, by IOCTL number
+pub const IOCTL_SINCE: [(u8, &str); 6] = [
+    (UVIO_IOCTL_UVDEV_INFO_NR, "6.5"),
+    (UVIO_IOCTL_ATT_NR, "5.19"),
+    (UVIO_IOCTL_ADD_SECRET_NR, "6.5"),
+    (UVIO_IOCTL_LIST_SECRETS_NR, "6.5"),
+    (UVIO_IOCTL_LOCK_SECRETS_NR, "6.5"),
+    (UVIO_IOCTL_RETR_SECRET_NR, "6.13"),
+];
+
+/// Returns the first kernel release that provides the IOCTL `nr`.
+pub fn ioctl_since(nr: u8) -> Option<&'static str> {
+    IOCTL_SINCE.iter().find(|(n, _)| *n == nr).map(|(_, v)| *v)
+}
+
+/// corresponds to the `UV_IOCTL` macro
+pub const fn uv_ioctl(nr: u8) -> u64 {
+    iowr(UVIO_TYPE_UVC, nr, size_of::<uvio_ioctl_cb>())
+}
+static_assert!(uv_ioctl(UVIO_IOCTL_ATT_NR) == 0xc0407501);
+
+/// corresponds to the __IOWR macro
+pub const fn iowr(ty: u8, nr: u8, size: usize) -> u64 {
+    // constants and calculation from linux: asm-generic/ioctl.h
+    const _IOC_WRITE: u32 = 1;
+    const _IOC_READ: u32 = 2;
+    const _IOC_NRSHIFT: u32 = 0;
+    const _IOC_TYPESHIFT: u32 = 8;
+    const _IOC_SIZESHIFT: u32 = 16;
+    const _IOC_DIRSHIFT: u32 = 30;
+    ((_IOC_READ | _IOC_WRITE) as u64) << _IOC_DIRSHIFT
+        | ((ty as u64) << _IOC_TYPESHIFT)
+        | ((nr as u64) << _IOC_NRSHIFT)
+        | ((size as u64) << _IOC_SIZESHIFT)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ioctl_since() {
+        assert_eq!(super::ioctl_since(UVIO_IOCTL_ATT_NR), Some("5.19"));
+        assert_eq!(super::ioctl_since(UVIO_IOCTL_RETR_SECRET_NR), Some("6.13"));
+        assert_eq!(super::ioctl_since(42), None);
+        // Every IOCTL number is listed once.
+        for (nr, _) in IOCTL_SINCE {
+            assert_eq!(IOCTL_SINCE.iter().filter(|(n, _)| *n == nr).count(), 1);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2023, 2025
+
+//! Definitions introduced with Linux 5.19: the uvdevice and the Attestation IOCTL
+
+use crate::assert_size;
+use zerocopy::{BigEndian, FromBytes, IntoBytes, U16, U32, U64};
+
+pub const UVIO_ATT_ARCB_MAX_LEN: usize = 0x100000;
+pub const UVIO_ATT_MEASUREMENT_MAX_LEN: usize = 0x8000;
+pub const UVIO_ATT_ADDITIONAL_MAX_LEN: usize = 0x8000;
+
+// equal to ascii 'u'
+pub const UVIO_TYPE_UVC: u8 = 117u8;
+
+pub const UVIO_IOCTL_ATT_NR: u8 = 1;
+
+/// Uvdevice IOCTL control block
+/// Programs can use this struct to communicate with the uvdevice via IOCTLs
+/// `argument_{addr,len}` specifies in/out data depending on the request
+///
+/// `uv_rc` and `uv_rrc` are the response and reason response codes from the
+/// Ultravisor.
+///
+/// `flags` is currently unused and to be set zero
+#[repr(C)]
+#[derive(Debug)]
+pub struct uvio_ioctl_cb {
+    pub flags: u32,
+    pub uv_rc: u16,
+    pub uv_rrc: u16,
+    pub argument_addr: u64,
+    pub argument_len: u32,
+    pub reserved14: [u8; 44usize],
+}
+assert_size!(uvio_ioctl_cb, 0x40);
+
+pub const UVIO_ATT_USER_DATA_LEN: usize = 0x100;
+pub const UVIO_ATT_UID_LEN: usize = 0x10;
+
+/// Request Attestation Measurement control block
+///
+/// The Attestation Request has two input and two outputs.
+/// ARCB and user-data are inputs for the UV.
+/// Measurement and additional-data are outputs generated by UV.
+///
+/// The Attestation Request Control Block (ARCB) is a cryptographically verified
+/// and secured request to UV and user-data is some plaintext data which is
+/// going to be included in the Attestation Measurement calculation.
+///
+/// Measurement is a cryptographic measurement of the callers properties,
+/// optional data configured by the ARCB and the user data. If specified by the
+/// ARCB, UV will add some additional-data to the measurement calculation.
+/// This additional-data is then returned as well.
+///
+/// If the Retrieve Attestation Measurement UV facility is not present,
+/// UV will return invalid command rc.
+///
+/// All numbers are in big-endian! The byte-order wrappers enforce this on every host, so that a
+/// control block cannot be misread on a little-endian host.
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes)]
+pub struct uvio_attest {
+    pub arcb_addr: U64<BigEndian>,               // in
+    pub meas_addr: U64<BigEndian>,               // out
+    pub add_data_addr: U64<BigEndian>,           // out
+    pub user_data: [u8; UVIO_ATT_USER_DATA_LEN], // in
+    pub config_uid: [u8; UVIO_ATT_UID_LEN],      // out
+    pub arcb_len: U32<BigEndian>,
+    pub meas_len: U32<BigEndian>,
+    pub add_data_len: U32<BigEndian>,
+    pub user_data_len: U16<BigEndian>,
+    pub reserved136: U16<BigEndian>,
+}
+assert_size!(uvio_attest, 0x138);
+
+impl uvio_attest {
+    /// Size of the additional data buffer; after the UVC the size the Ultravisor wrote
+    pub fn add_data_len(&self) -> u32 {
+        self.add_data_len.get()
+    }
+
+    /// Set the size of the additional data buffer.
+    pub fn set_add_data_len(&mut self, len: u32) {
+        self.add_data_len = len.into();
+    }
+}
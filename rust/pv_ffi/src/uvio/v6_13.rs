@@ -0,0 +1,10 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Definitions introduced with Linux 6.13: the Retrieve Secret IOCTL
+
+pub const UVIO_RETR_SECRET_MAX_LEN: usize = 0x2000;
+
+pub const UVIO_IOCTL_RETR_SECRET_NR: u8 = 5;
+pub const UVIO_LIST_SECRETS_MAX_LEN: usize = 0x8000;
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2023, 2025
+
+//! Definitions introduced with Linux 6.5: the Info IOCTL and the secret-store IOCTLs
+
+use crate::assert_size;
+use zerocopy::{BigEndian, FromBytes, IntoBytes, U64};
+
+pub const UVIO_ADD_SECRET_MAX_LEN: usize = 0x100000;
+pub const UVIO_LIST_SECRETS_LEN: usize = 0x1000;
+
+pub const UVIO_IOCTL_UVDEV_INFO_NR: u8 = 0;
+pub const UVIO_IOCTL_ADD_SECRET_NR: u8 = 2;
+pub const UVIO_IOCTL_LIST_SECRETS_NR: u8 = 3;
+pub const UVIO_IOCTL_LOCK_SECRETS_NR: u8 = 4;
+
+/// Information of supported functions by the uvdevice
+///
+/// * `supp_uvio_cmds` - supported IOCTLs by this device
+/// * `supp_uv_cmds` - supported UVCs corresponding to the IOCTL
+///
+/// UVIO request to get information about supported request types by this
+/// uvdevice and the Ultravisor.
+/// Everything is output. Bits are in LSB0 ordering.
+/// If the bit is set in both, `supp_uvio_cmds` and `supp_uv_cmds`,
+/// the uvdevice and the Ultravisor support that call.
+///
+/// Note that bit 0 (`UVIO_IOCTL_UVDEV_INFO_NR`) is always zero for `supp_uv_cmds`
+/// as there is no corresponding UV-call.
+///
+/// All numbers are in big-endian.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, IntoBytes, FromBytes)]
+pub struct uvio_uvdev_info {
+    pub supp_uvio_cmds: U64<BigEndian>,
+    pub supp_uv_cmds: U64<BigEndian>,
+}
+assert_size!(uvio_uvdev_info, 0x10);
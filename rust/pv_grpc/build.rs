@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+fn main() -> std::io::Result<()> {
+    println!("cargo:rerun-if-changed=proto/s390_pv.proto");
+    tonic_build::compile_protos("proto/s390_pv.proto")
+}
@@ -0,0 +1,289 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! gRPC service for IBM Secure Execution attestation
+//!
+//! Implements the `s390.pv.v1.SeAttestation` service of `proto/s390_pv.proto`:
+//!
+//! - `GetEvidence` runs the Attestation UVC inside a Secure Execution guest,
+//! - `VerifyEvidence` verifies the evidence of a guest on the verifier side,
+//! - `ListSecrets` lists the secrets of the secret store of the guest.
+//!
+//! Every call is checked by an [`Authenticator`] before it is processed.
+
+// The gRPC API dictates `tonic::Status` as error type.
+#![allow(clippy::result_large_err)]
+
+use std::{fmt::Display, io::Cursor, sync::Arc};
+
+use pv::{
+    attest::{verify_cuid, AttestationItems, AttestationMeasurement, AttestationRequest, UserData},
+    request::{openssl::pkey::PKey, BootHdrTags, Confidential, SymKey},
+    uv::{AttestationCmd, ConfigUid, ListCmd, SecretList, UvDevice},
+};
+use tonic::{metadata::MetadataMap, transport::CertificateDer, Code, Request, Response, Status};
+
+/// Generated protobuf messages and service definitions
+#[allow(missing_docs, clippy::all)]
+pub mod proto {
+    tonic::include_proto!("s390.pv.v1");
+}
+
+use proto::{
+    se_attestation_server::{SeAttestation, SeAttestationServer},
+    GetEvidenceRequest, GetEvidenceResponse, ListSecretsRequest, ListSecretsResponse, Secret,
+    VerifyEvidenceRequest, VerifyEvidenceResponse,
+};
+
+/// RPCs of the attestation service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// `GetEvidence`
+    GetEvidence,
+    /// `VerifyEvidence`
+    VerifyEvidence,
+    /// `ListSecrets`
+    ListSecrets,
+}
+
+/// Authentication hook of the attestation service
+///
+/// Called for every RPC with the request metadata and, for mutual TLS, the certificate chain
+/// of the client. Returning an error rejects the call with that status.
+pub trait Authenticator: Send + Sync + 'static {
+    /// Decides whether the client may call `method`.
+    fn authenticate(
+        &self,
+        method: Method,
+        metadata: &MetadataMap,
+        peer_certs: Option<&[CertificateDer<'static>]>,
+    ) -> Result<(), Status>;
+}
+
+/// Accepts every client
+///
+/// Use it only together with mutual TLS or on trusted networks.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AllowAll;
+
+impl Authenticator for AllowAll {
+    fn authenticate(
+        &self,
+        _: Method,
+        _: &MetadataMap,
+        _: Option<&[CertificateDer<'static>]>,
+    ) -> Result<(), Status> {
+        Ok(())
+    }
+}
+
+/// Accepts clients that presented a TLS client certificate
+///
+/// The certificate itself is verified by the TLS layer against the client CA.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ClientCertRequired;
+
+impl Authenticator for ClientCertRequired {
+    fn authenticate(
+        &self,
+        _: Method,
+        _: &MetadataMap,
+        peer_certs: Option<&[CertificateDer<'static>]>,
+    ) -> Result<(), Status> {
+        match peer_certs {
+            Some(certs) if !certs.is_empty() => Ok(()),
+            _ => Err(Status::unauthenticated("A client certificate is required")),
+        }
+    }
+}
+
+fn status<E: Display>(code: Code) -> impl Fn(E) -> Status {
+    move |e| Status::new(code, e.to_string())
+}
+
+fn array<const N: usize>(data: &[u8], what: &str) -> Result<[u8; N], Status> {
+    data.try_into()
+        .map_err(|_| Status::invalid_argument(format!("The {what} must be {N} bytes long")))
+}
+
+/// Runs a blocking Ultravisor call on the blocking thread pool.
+async fn uv_call<T, F>(f: F) -> Result<T, Status>
+where
+    T: Send + 'static,
+    F: FnOnce(&UvDevice) -> pv::Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let uv = UvDevice::open().map_err(status(Code::Unavailable))?;
+        f(&uv).map_err(status(Code::FailedPrecondition))
+    })
+    .await
+    .map_err(status(Code::Internal))?
+}
+
+/// Verifies the attestation `evidence` for the attestation request `arcb`.
+fn verify_evidence(req: &VerifyEvidenceRequest) -> Result<bool, Status> {
+    let evidence = req
+        .evidence
+        .as_ref()
+        .ok_or_else(|| Status::invalid_argument("The evidence is missing"))?;
+    let arpk = SymKey::Aes256(Confidential::new(array(
+        &req.arpk,
+        "attestation request protection key",
+    )?));
+    let cuid: ConfigUid = array(&evidence.cuid, "configuration UID")?;
+    let tags = BootHdrTags::from_se_image(&mut Cursor::new(&req.se_header))
+        .map_err(status(Code::InvalidArgument))?;
+    let (auth, conf) = AttestationRequest::decrypt_bin(&req.request, &arpk)
+        .map_err(status(Code::InvalidArgument))?;
+    let meas_key = PKey::hmac(conf.measurement_key()).map_err(status(Code::Internal))?;
+    let items = AttestationItems::new(
+        &tags,
+        &cuid,
+        Some(&evidence.user_data),
+        conf.nonce().as_ref().map(|v| v.value()),
+        Some(&evidence.additional_data)
+            .filter(|a| !a.is_empty())
+            .map(Vec::as_slice),
+    );
+    let measurement = AttestationMeasurement::calculate(items, auth.mai(), &meas_key)
+        .map_err(status(Code::Internal))?;
+    Ok(measurement.eq_secure(&evidence.measurement))
+}
+
+/// Implementation of the `SeAttestation` gRPC service
+#[derive(Debug)]
+pub struct SeAttestationService<A> {
+    auth: Arc<A>,
+}
+
+impl<A: Authenticator> SeAttestationService<A> {
+    /// Creates the service with the authentication hook `auth`.
+    pub fn new(auth: A) -> Self {
+        Self {
+            auth: Arc::new(auth),
+        }
+    }
+
+    /// Wraps the service into a server that can be added to a `tonic` router.
+    pub fn into_server(self) -> SeAttestationServer<Self> {
+        SeAttestationServer::new(self)
+    }
+
+    fn check<T>(&self, method: Method, req: &Request<T>) -> Result<(), Status> {
+        let certs = req.peer_certs();
+        self.auth
+            .authenticate(method, req.metadata(), certs.as_ref().map(|c| c.as_slice()))
+    }
+}
+
+#[tonic::async_trait]
+impl<A: Authenticator> SeAttestation for SeAttestationService<A> {
+    async fn get_evidence(
+        &self,
+        request: Request<GetEvidenceRequest>,
+    ) -> Result<Response<GetEvidenceResponse>, Status> {
+        self.check(Method::GetEvidence, &request)?;
+        let req = request.into_inner();
+        let user_data = req.user_data;
+        let uvc_user_data = match user_data.is_empty() {
+            true => None,
+            false => Some(UserData::new(user_data.clone()).map_err(status(Code::InvalidArgument))?),
+        };
+        let mut cmd = AttestationCmd::new_request(
+            req.request.into_boxed_slice(),
+            uvc_user_data,
+            req.measurement_size,
+            req.additional_size,
+        )
+        .map_err(status(Code::InvalidArgument))?;
+        let cmd = uv_call(move |uv| {
+            uv.send_cmd(&mut cmd)?;
+            Ok(cmd)
+        })
+        .await?;
+        let cuid = cmd.cuid().to_vec();
+        let (_, measurement, additional) = cmd.into_parts();
+        Ok(Response::new(GetEvidenceResponse {
+            measurement,
+            additional_data: additional.unwrap_or_default(),
+            user_data,
+            cuid,
+        }))
+    }
+
+    async fn verify_evidence(
+        &self,
+        request: Request<VerifyEvidenceRequest>,
+    ) -> Result<Response<VerifyEvidenceResponse>, Status> {
+        self.check(Method::VerifyEvidence, &request)?;
+        let req = request.into_inner();
+        let mut valid = verify_evidence(&req)?;
+        if let (true, Some(exp)) = (valid, req.expected_cuid.as_deref()) {
+            let cuid = array(&req.evidence.unwrap_or_default().cuid, "configuration UID")?;
+            valid = verify_cuid(&cuid, &array(exp, "expected configuration UID")?).is_ok();
+        }
+        Ok(Response::new(VerifyEvidenceResponse { valid }))
+    }
+
+    async fn list_secrets(
+        &self,
+        request: Request<ListSecretsRequest>,
+    ) -> Result<Response<ListSecretsResponse>, Status> {
+        self.check(Method::ListSecrets, &request)?;
+        let list: SecretList = uv_call(|uv| {
+            let mut cmd = ListCmd::default();
+            uv.send_cmd(&mut cmd)?;
+            Ok(cmd.try_into()?)
+        })
+        .await?;
+        Ok(Response::new(ListSecretsResponse {
+            total_num_secrets: list.total_num_secrets() as u32,
+            secrets: list
+                .iter()
+                .map(|s| Secret {
+                    index: s.index().into(),
+                    r#type: s.stype().to_string(),
+                    id: s.id().to_vec(),
+                    size: s.secret_size(),
+                })
+                .collect(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn client_cert_required() {
+        let meta = MetadataMap::new();
+        let auth = ClientCertRequired;
+        assert_eq!(
+            auth.authenticate(Method::ListSecrets, &meta, None)
+                .unwrap_err()
+                .code(),
+            Code::Unauthenticated
+        );
+        assert!(auth
+            .authenticate(
+                Method::ListSecrets,
+                &meta,
+                Some(&[CertificateDer::from(vec![0x30])])
+            )
+            .is_ok());
+        assert!(AllowAll
+            .authenticate(Method::GetEvidence, &meta, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn verify_missing_evidence() {
+        let req = VerifyEvidenceRequest::default();
+        assert_eq!(
+            verify_evidence(&req).unwrap_err().code(),
+            Code::InvalidArgument
+        );
+    }
+}
@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+
+use std::{net::SocketAddr, path::PathBuf, process::ExitCode};
+
+use anyhow::{bail, Context, Result};
+use clap::{CommandFactory, Parser, ValueHint};
+use log::{info, trace};
+use pv_grpc::{AllowAll, Authenticator, ClientCertRequired, SeAttestationService};
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use utils::{print_cli_error, print_error, print_version, PvLogger, VerbosityOptions};
+
+static LOGGER: PvLogger = PvLogger;
+static EXIT_LOGGER: u8 = 3;
+
+/// Serve the IBM Secure Execution attestation gRPC service.
+///
+/// Inside a Secure Execution guest the service provides attestation evidence and the list of
+/// secrets. On a verifier it verifies the evidence of guests.
+#[derive(Parser, Debug)]
+struct CliOptions {
+    #[clap(flatten)]
+    verbosity: VerbosityOptions,
+
+    /// Listen on ADDR.
+    ///
+    /// Addresses other than loopback addresses require '--cert', '--key', and '--client-ca', so
+    /// that only authenticated clients can reach the service.
+    #[arg(long, value_name = "ADDR", default_value = "[::1]:50051")]
+    listen: SocketAddr,
+
+    /// Use the PEM certificate (chain) in FILE for TLS.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, requires = "key")]
+    cert: Option<PathBuf>,
+
+    /// Use the PEM private key in FILE for TLS.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, requires = "cert")]
+    key: Option<PathBuf>,
+
+    /// Require client certificates signed by the PEM CA certificate in FILE.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, requires = "cert")]
+    client_ca: Option<PathBuf>,
+
+    /// Print version information and exit.
+    #[arg(long)]
+    version: bool,
+}
+
+fn read(path: &PathBuf, what: &str) -> Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("Cannot read the {what} '{}'", path.display()))
+}
+
+fn tls_config(cli: &CliOptions) -> Result<Option<ServerTlsConfig>> {
+    let (Some(cert), Some(key)) = (&cli.cert, &cli.key) else {
+        return Ok(None);
+    };
+    let mut tls = ServerTlsConfig::new().identity(Identity::from_pem(
+        read(cert, "certificate")?,
+        read(key, "private key")?,
+    ));
+    if let Some(ca) = &cli.client_ca {
+        tls = tls.client_ca_root(Certificate::from_pem(read(ca, "client CA")?));
+    }
+    Ok(Some(tls))
+}
+
+/// Refuses to expose the service beyond the local host without client authentication.
+fn check_exposure(cli: &CliOptions) -> Result<()> {
+    let authenticated = cli.cert.is_some() && cli.key.is_some() && cli.client_ca.is_some();
+    if !cli.listen.ip().is_loopback() && !authenticated {
+        bail!(
+            "Refusing to listen on the non-loopback address {} without client authentication. \
+             Specify '--cert', '--key', and '--client-ca'",
+            cli.listen
+        );
+    }
+    Ok(())
+}
+
+async fn serve<A: Authenticator>(
+    addr: SocketAddr,
+    tls: Option<ServerTlsConfig>,
+    auth: A,
+) -> Result<()> {
+    let mut server = Server::builder();
+    if let Some(tls) = tls {
+        server = server.tls_config(tls)?;
+    }
+    info!("Listening on {addr}");
+    server
+        .add_service(SeAttestationService::new(auth).into_server())
+        .serve(addr)
+        .await
+        .context("The attestation service failed")
+}
+
+async fn run(cli: &CliOptions) -> Result<()> {
+    check_exposure(cli)?;
+    let tls = tls_config(cli)?;
+    match cli.client_ca {
+        Some(_) => serve(cli.listen, tls, ClientCertRequired).await,
+        None => serve(cli.listen, tls, AllowAll).await,
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli: CliOptions = match CliOptions::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => return print_cli_error(e, CliOptions::command()),
+    };
+
+    // set up logger/std(out,err)
+    let log_level = cli.verbosity.to_level_filter();
+    if let Err(e) = LOGGER.start_with_format(log_level, cli.verbosity.log_format()) {
+        // should(TM) never happen
+        eprintln!("Logger error: {e:?}");
+        return EXIT_LOGGER.into();
+    }
+
+    if cli.version {
+        print_version!("2025", log_level);
+        return ExitCode::SUCCESS;
+    }
+
+    trace!("Options {cli:?}");
+
+    match run(&cli).await {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => print_error(&e, log_level),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        super::CliOptions::command().debug_assert()
+    }
+
+    #[test]
+    fn exposure() {
+        let cli = |args: &[&str]| CliOptions::parse_from([&["pvgrpcd"], args].concat());
+        assert!(check_exposure(&cli(&[])).is_ok());
+        assert!(check_exposure(&cli(&["--listen", "127.0.0.1:1234"])).is_ok());
+        assert!(check_exposure(&cli(&["--listen", "[::]:50051"])).is_err());
+        assert!(check_exposure(&cli(&[
+            "--listen",
+            "[::]:50051",
+            "--cert",
+            "c",
+            "--key",
+            "k"
+        ]))
+        .is_err());
+        assert!(check_exposure(&cli(&[
+            "--listen",
+            "[::]:50051",
+            "--cert",
+            "c",
+            "--key",
+            "k",
+            "--client-ca",
+            "ca"
+        ]))
+        .is_ok());
+    }
+}
@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Python bindings of the IBM Secure Execution library
+//!
+//! Exposes the request creation and verification of `s390_pv` as the Python module `pv`, so
+//! that attestation backends written in Python use the same implementation as `pvattest` and
+//! `pvsecret`.
+#![allow(missing_docs)]
+
+use std::{io::Cursor, path::PathBuf};
+
+use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyBytes};
+use s390_pv::{
+    attest::{
+        verify_cuid, AdditionalDataItem, AttestationFlags, AttestationItems, AttestationMeasAlg,
+        AttestationMeasurement, AttestationRequest as Arcb, AttestationVersion,
+    },
+    misc::read_certs,
+    request::{
+        openssl::pkey::{PKey, Public},
+        BootHdrTags, CertVerifier, Confidential, HkdVerifier, NoVerifyHkd, ReqEncrCtx, Request,
+        SymKey, SymKeyType,
+    },
+    secret::{AddSecretFlags, AddSecretRequest, AddSecretVersion, ExtSecret, GuestSecret},
+    uv::ConfigUid,
+};
+
+create_exception!(
+    pv,
+    PvError,
+    PyException,
+    "Error of the IBM Secure Execution library"
+);
+
+fn err<E: std::fmt::Display>(e: E) -> PyErr {
+    PvError::new_err(e.to_string())
+}
+
+fn array<const N: usize>(data: &[u8], what: &str) -> PyResult<[u8; N]> {
+    data.try_into()
+        .map_err(|_| err(format!("The {what} must be {N} bytes long")))
+}
+
+fn boot_tags(se_header: &[u8]) -> PyResult<BootHdrTags> {
+    BootHdrTags::from_se_image(&mut Cursor::new(se_header)).map_err(err)
+}
+
+/// A host key of an IBM Z machine, read from a (verified) host-key document
+#[pyclass(frozen, module = "pv")]
+struct HostKey(PKey<Public>);
+
+/// Reads the host key of a host-key document in PEM or DER format.
+///
+/// The host-key document is verified with the IBM Z signing key certificates `certs`, the
+/// CRLs `crls`, and the root CA `root_ca` (default: the system root CAs). Missing CRLs are
+/// downloaded unless `offline` is set. Set `no_verify` only for testing.
+#[pyfunction]
+#[pyo3(signature = (hkd, certs=vec![], crls=vec![], root_ca=None, offline=false, no_verify=false))]
+fn load_host_key(
+    hkd: &[u8],
+    certs: Vec<PathBuf>,
+    crls: Vec<PathBuf>,
+    root_ca: Option<PathBuf>,
+    offline: bool,
+    no_verify: bool,
+) -> PyResult<HostKey> {
+    let verifier: Box<dyn HkdVerifier> = match no_verify {
+        true => Box::new(NoVerifyHkd),
+        false => Box::new(CertVerifier::new(&certs, &crls, root_ca, offline).map_err(err)?),
+    };
+    let certs = read_certs(hkd).map_err(err)?;
+    let [cert] = certs.as_slice() else {
+        return Err(err(
+            "The host-key document must contain exactly one certificate",
+        ));
+    };
+    verifier.verify(cert).map_err(err)?;
+    Ok(HostKey(cert.public_key().map_err(err)?))
+}
+
+fn additional_item(name: &str) -> PyResult<AdditionalDataItem> {
+    match name {
+        "image_phkh" => Ok(AdditionalDataItem::ImagePhkh),
+        "attestation_phkh" => Ok(AdditionalDataItem::AttestationPhkh),
+        "secret_store_hash" => Ok(AdditionalDataItem::SecretStoreHash),
+        "firmware_state" => Ok(AdditionalDataItem::FirmwareState),
+        _ => Err(err(format!("Unknown additional data '{name}'"))),
+    }
+}
+
+/// An encrypted attestation request and its protection key
+#[pyclass(frozen, module = "pv")]
+struct AttestationRequest {
+    request: Vec<u8>,
+    arpk: Confidential<Vec<u8>>,
+    measurement_size: u32,
+    additional_size: u32,
+}
+
+#[pymethods]
+impl AttestationRequest {
+    /// Creates an attestation request for the given host keys.
+    ///
+    /// `additional` selects additional data: "image_phkh", "attestation_phkh",
+    /// "secret_store_hash", or "firmware_state".
+    #[new]
+    #[pyo3(signature = (host_keys, additional=vec![]))]
+    fn new(host_keys: Vec<PyRef<'_, HostKey>>, additional: Vec<String>) -> PyResult<Self> {
+        let meas_alg = AttestationMeasAlg::HmacSha512;
+        let mut arcb = Arcb::new(
+            AttestationVersion::One,
+            meas_alg,
+            AttestationFlags::default(),
+        )
+        .map_err(err)?;
+        for name in &additional {
+            arcb.request_additional(additional_item(name)?, None)
+                .map_err(err)?;
+        }
+        arcb.add_hostkeys(host_keys.iter().map(|k| k.0.clone()))
+            .map_err(err)?;
+        let ctx = ReqEncrCtx::random(SymKeyType::Aes256Gcm).map_err(err)?;
+        Ok(Self {
+            request: arcb.encrypt(&ctx).map_err(err)?,
+            arpk: Confidential::new(ctx.prot_key().value().to_vec()),
+            measurement_size: meas_alg.exp_size(),
+            additional_size: arcb.flags().expected_additional_size(),
+        })
+    }
+
+    /// The encrypted request for the Ultravisor
+    #[getter]
+    fn request<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.request)
+    }
+
+    /// The attestation request protection key, required for the verification
+    #[getter]
+    fn arpk<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.arpk.value())
+    }
+
+    /// Size of the measurement the guest must provide
+    #[getter]
+    fn measurement_size(&self) -> u32 {
+        self.measurement_size
+    }
+
+    /// Size of the additional data the guest must provide
+    #[getter]
+    fn additional_size(&self) -> u32 {
+        self.additional_size
+    }
+}
+
+/// Verifies an attestation measurement of a guest.
+///
+/// `request` and `arpk` are the request and key of an `AttestationRequest`, `se_header` the
+/// Secure Execution image (or its header) the guest is expected to run. The other arguments
+/// are the response of the guest. Returns `True` if the measurement is valid.
+#[pyfunction]
+#[pyo3(signature = (request, arpk, se_header, measurement, cuid, user_data=None, additional=None, expected_cuid=None))]
+#[allow(clippy::too_many_arguments)]
+fn verify_attestation(
+    request: &[u8],
+    arpk: &[u8],
+    se_header: &[u8],
+    measurement: &[u8],
+    cuid: &[u8],
+    user_data: Option<&[u8]>,
+    additional: Option<&[u8]>,
+    expected_cuid: Option<&[u8]>,
+) -> PyResult<bool> {
+    let arpk = SymKey::Aes256(Confidential::new(array(
+        arpk,
+        "attestation request protection key",
+    )?));
+    let cuid: ConfigUid = array(cuid, "configuration UID")?;
+    let tags = boot_tags(se_header)?;
+    let (auth, conf) = Arcb::decrypt_bin(request, &arpk).map_err(err)?;
+    let meas_key = PKey::hmac(conf.measurement_key()).map_err(err)?;
+    let items = AttestationItems::new(
+        &tags,
+        &cuid,
+        user_data,
+        conf.nonce().as_ref().map(|v| v.value()),
+        additional,
+    );
+    let calculated =
+        AttestationMeasurement::calculate(items, auth.mai(), &meas_key).map_err(err)?;
+    if !calculated.eq_secure(measurement) {
+        return Ok(false);
+    }
+    match expected_cuid {
+        Some(exp) => Ok(verify_cuid(&cuid, &array(exp, "expected configuration UID")?).is_ok()),
+        None => Ok(true),
+    }
+}
+
+/// Creates an add-secret request for an association secret.
+///
+/// The secret `name` is added to the guest running the Secure Execution image (or header)
+/// `se_header`. A random secret is generated if `secret` is omitted. With `cck` the extension
+/// secret is derived from the customer communication key; with `cuid` the request is bound to
+/// one guest instance. Returns the request and the secret.
+#[pyfunction]
+#[pyo3(signature = (host_keys, se_header, name, secret=None, cck=None, cuid=None, disable_dump=false))]
+#[allow(clippy::too_many_arguments)]
+fn create_association_request<'py>(
+    py: Python<'py>,
+    host_keys: Vec<PyRef<'py, HostKey>>,
+    se_header: &[u8],
+    name: &str,
+    secret: Option<&[u8]>,
+    cck: Option<&[u8]>,
+    cuid: Option<&[u8]>,
+    disable_dump: bool,
+) -> PyResult<(Bound<'py, PyBytes>, Bound<'py, PyBytes>)> {
+    let secret = secret.map(|s| array(s, "association secret")).transpose()?;
+    let secret = GuestSecret::association(name, secret).map_err(err)?;
+    let mut flags = AddSecretFlags::default();
+    if disable_dump {
+        flags.set_disable_dump();
+    }
+    let mut asrcb =
+        AddSecretRequest::new(AddSecretVersion::One, secret, boot_tags(se_header)?, flags);
+    if let Some(cck) = cck {
+        asrcb
+            .set_ext_secret(ExtSecret::Derived(Confidential::new(array(cck, "CCK")?)))
+            .map_err(err)?;
+    }
+    if let Some(cuid) = cuid {
+        asrcb.set_cuid(array(cuid, "configuration UID")?);
+    }
+    asrcb
+        .add_hostkeys(host_keys.iter().map(|k| k.0.clone()))
+        .map_err(err)?;
+    let ctx = ReqEncrCtx::random(SymKeyType::Aes256Gcm).map_err(err)?;
+    let request = asrcb.encrypt(&ctx).map_err(err)?;
+    Ok((
+        PyBytes::new(py, &request),
+        PyBytes::new(py, asrcb.guest_secret().confidential()),
+    ))
+}
+
+/// IBM Secure Execution request creation and verification
+#[pymodule]
+fn pv(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("PvError", m.py().get_type::<PvError>())?;
+    m.add_class::<HostKey>()?;
+    m.add_class::<AttestationRequest>()?;
+    m.add_function(wrap_pyfunction!(load_host_key, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_attestation, m)?)?;
+    m.add_function(wrap_pyfunction!(create_association_request, m)?)?;
+    Ok(())
+}
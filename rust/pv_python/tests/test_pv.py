@@ -0,0 +1,82 @@
+# SPDX-License-Identifier: MIT
+#
+# Copyright IBM Corp. 2025
+
+"""Smoke tests of the Python module `pv`
+
+Run with `make -C rust standalone-test`, or set PV_PYTHON_LIB to the built
+`libpv.so` and run `python3 -m unittest discover -s rust/pv_python/tests`.
+"""
+
+import importlib.machinery
+import importlib.util
+import os
+import unittest
+
+ASSETS = os.path.join(os.path.dirname(__file__), "..", "..", "pv", "tests", "assets")
+
+
+def load_pv():
+    """Import the built extension module, its file name does not matter."""
+    path = os.environ["PV_PYTHON_LIB"]
+    loader = importlib.machinery.ExtensionFileLoader("pv", path)
+    spec = importlib.util.spec_from_file_location("pv", path, loader=loader)
+    module = importlib.util.module_from_spec(spec)
+    loader.exec_module(module)
+    return module
+
+
+def asset(name):
+    with open(os.path.join(ASSETS, name), "rb") as f:
+        return f.read()
+
+
+pv = load_pv()
+
+
+class TestPv(unittest.TestCase):
+    def setUp(self):
+        self.hkd = pv.load_host_key(asset("keys/host.pem.crt"), no_verify=True)
+        self.se_header = asset("exp/secure_guest.hdr")
+
+    def test_attestation_request(self):
+        req = pv.AttestationRequest([self.hkd], additional=["image_phkh"])
+        self.assertEqual(len(req.arpk), 32)
+        self.assertEqual(req.measurement_size, 64)
+        self.assertEqual(req.additional_size, 32)
+        self.assertGreater(len(req.request), 0)
+
+        measurement = bytes(req.measurement_size)
+        self.assertFalse(
+            pv.verify_attestation(
+                req.request, req.arpk, self.se_header, measurement, bytes(16)
+            )
+        )
+        with self.assertRaises(pv.PvError):
+            pv.verify_attestation(
+                req.request, bytes(32), self.se_header, measurement, bytes(16)
+            )
+
+    def test_association_request(self):
+        request, secret = pv.create_association_request(
+            [self.hkd], self.se_header, "my secret", secret=bytes(range(32))
+        )
+        self.assertGreater(len(request), 0)
+        self.assertEqual(secret, bytes(range(32)))
+
+        _, secret = pv.create_association_request([self.hkd], self.se_header, "random")
+        self.assertEqual(len(secret), 32)
+
+    def test_errors(self):
+        with self.assertRaises(pv.PvError):
+            pv.load_host_key(b"no certificate", no_verify=True)
+        with self.assertRaises(pv.PvError):
+            pv.AttestationRequest([self.hkd], additional=["unknown"])
+        with self.assertRaises(pv.PvError):
+            pv.create_association_request(
+                [self.hkd], self.se_header, "short", secret=bytes(16)
+            )
+
+
+if __name__ == "__main__":
+    unittest.main()
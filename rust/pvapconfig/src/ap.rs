@@ -8,6 +8,7 @@
 use crate::helper::*;
 use pv_core::ap::*;
 use pv_core::misc::read_file_string;
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::slice::Iter;
 use std::thread;
@@ -22,6 +23,9 @@ const PATH_SYS_DEVICES_AP: &str = "/sys/devices/ap";
 
 const SYS_BUS_AP_BINDINGS_POLL_MS: u64 = 500;
 
+/// sysfs attributes of AP cards and queues that change on hotplug and checkstop
+const AP_DEVICE_STATE_ATTRS: [&str; 3] = ["online", "config", "chkstop"];
+
 /// Check if AP bus support is available.
 /// Returns Result with Ok(()) or Err(failurestring).
 pub fn check_ap_bus_support() -> Result<(), String> {
@@ -81,6 +85,67 @@ pub fn wait_for_ap_bus_bindings_complete() -> bool {
     }
 }
 
+/// Snapshot of the AP cards and queues and their state
+///
+/// Maps the sysfs directory name of each card and queue to the
+/// values of its online, config and checkstop attributes.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ApBusState(BTreeMap<String, String>);
+
+impl ApBusState {
+    /// Read the current state of all AP devices from sysfs.
+    /// Devices or attributes which vanish during the scan are skipped.
+    pub fn read() -> Self {
+        Self::read_from(PATH_SYS_DEVICES_AP)
+    }
+
+    fn read_from(base: &str) -> Self {
+        let mut state = BTreeMap::new();
+        let card_dirs =
+            sysfs_get_list_of_subdirs_matching_regex(base, RE_CARD_DIR).unwrap_or_default();
+        for card_dir in card_dirs {
+            let path = format!("{base}/{card_dir}");
+            let queue_dirs =
+                sysfs_get_list_of_subdirs_matching_regex(&path, RE_QUEUE_DIR).unwrap_or_default();
+            for queue_dir in queue_dirs {
+                state.insert(
+                    queue_dir.clone(),
+                    Self::read_attrs(&format!("{path}/{queue_dir}")),
+                );
+            }
+            state.insert(card_dir, Self::read_attrs(&path));
+        }
+        Self(state)
+    }
+
+    fn read_attrs(path: &str) -> String {
+        AP_DEVICE_STATE_ATTRS
+            .iter()
+            .map(|attr| {
+                std::fs::read_to_string(format!("{path}/{attr}"))
+                    .map(|v| v.trim().to_string())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Wait for a change of the AP devices.
+///
+/// Polls the AP devices in sysfs every `interval` until cards or
+/// queues appear or vanish, or change their online, config or
+/// checkstop state. Returns the new state.
+pub fn wait_for_ap_bus_change(state: &ApBusState, interval: time::Duration) -> ApBusState {
+    loop {
+        thread::sleep(interval);
+        let new_state = ApBusState::read();
+        if new_state != *state {
+            return new_state;
+        }
+    }
+}
+
 /// Wrapper object around Vector of Apqns
 #[derive(Debug)]
 pub struct ApqnList(Vec<Apqn>);
@@ -173,7 +238,7 @@ impl ApqnList {
     /// Sort this Apqnlist by card generation:
     /// newest generation first, older generations last.
     pub fn sort_by_gen(&mut self) {
-        self.0.sort_unstable_by(|a, b| b.gen.cmp(&a.gen));
+        self.0.sort_unstable_by_key(|a| std::cmp::Reverse(a.gen));
     }
 
     /// Check MK restriction
@@ -188,15 +253,13 @@ impl ApqnList {
                 if a1.card == a2.card
                     && a1.domain < a2.domain
                     && a1.mode == apqn_mode::Ep11
-                    && a1.info.is_some()
-                    && a2.info.is_some()
                 {
-                    let i1 = match a1.info.as_ref().unwrap() {
-                        apqn_info::Ep11(i) => i,
+                    let i1 = match &a1.info {
+                        Some(apqn_info::Ep11(i)) => i,
                         _ => continue,
                     };
-                    let i2 = match a2.info.as_ref().unwrap() {
-                        apqn_info::Ep11(i) => i,
+                    let i2 = match &a2.info {
+                        Some(apqn_info::Ep11(i)) => i,
                         _ => continue,
                     };
                     if i1.mkvp.is_empty() || i2.mkvp.is_empty() {
@@ -213,6 +276,130 @@ impl ApqnList {
     }
 }
 
+/// Bind and association state of an APQN as set by pvapconfig
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApqnState {
+    Unbound,
+    Bound,
+    Associated(u16),
+}
+
+/// A single change of the state of an APQN
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApqnStep {
+    Unbind,
+    Bind,
+    Associate(u16),
+}
+
+impl ApqnState {
+    /// Read the current state of the APQN.
+    ///
+    /// Returns `Ok(None)` if the APQN does not support binding.
+    fn read(apqn: &Apqn) -> Result<Option<Self>, String> {
+        let bound = match apqn.bind_state() {
+            Err(err) => return Err(format!("Failure reading APQN {apqn} bind state: {err}")),
+            Ok(bind_state::NotSupported) => return Ok(None),
+            Ok(bind_state::Unbound) => return Ok(Some(Self::Unbound)),
+            Ok(bind_state::Bound) => Self::Bound,
+        };
+        if apqn.mode != apqn_mode::Ep11 {
+            return Ok(Some(bound));
+        }
+        match apqn.associate_state() {
+            Err(err) => Err(format!(
+                "Failure reading APQN {apqn} associate state: {err}"
+            )),
+            Ok(assoc_state::Associated(idx)) => Ok(Some(Self::Associated(idx))),
+            Ok(_) => Ok(Some(bound)),
+        }
+    }
+
+    /// Steps to change the state `self` into `target`.
+    ///
+    /// An association can only be changed by unbinding the APQN first.
+    fn steps_to(self, target: Self) -> Vec<ApqnStep> {
+        let mut steps = vec![];
+        if self == target {
+            return steps;
+        }
+        if self != Self::Unbound {
+            steps.push(ApqnStep::Unbind);
+        }
+        match target {
+            Self::Unbound => (),
+            Self::Bound => steps.push(ApqnStep::Bind),
+            Self::Associated(idx) => steps.extend([ApqnStep::Bind, ApqnStep::Associate(idx)]),
+        }
+        steps
+    }
+}
+
+/// Snapshot of the bind and association state of APQNs
+///
+/// Taken before the AP configuration is applied, so that a failing
+/// configuration can be rolled back instead of leaving the APQNs
+/// half-configured.
+#[derive(Debug)]
+pub struct ApSnapshot(Vec<(Apqn, ApqnState)>);
+
+impl ApSnapshot {
+    /// Record the state of all APQNs in the list.
+    ///
+    /// APQNs whose state cannot be read are not recorded and a
+    /// warning is printed.
+    pub fn take(apqns: &ApqnList) -> Self {
+        let states = apqns
+            .iter()
+            .filter_map(|apqn| match ApqnState::read(apqn) {
+                Ok(state) => state.map(|state| (apqn.clone(), state)),
+                Err(err) => {
+                    eprintln!("Warning: {err}");
+                    None
+                }
+            })
+            .collect();
+        Self(states)
+    }
+
+    /// Restore the recorded state of all APQNs.
+    ///
+    /// Tries to restore every APQN, even if restoring another one failed.
+    /// Returns the number of restored APQNs or a failure string listing
+    /// all APQNs that could not be restored.
+    pub fn rollback(&self) -> Result<usize, String> {
+        let mut restored = 0;
+        let mut failures = vec![];
+        for (apqn, target) in &self.0 {
+            match Self::restore(apqn, *target) {
+                Ok(true) => restored += 1,
+                Ok(false) => (),
+                Err(err) => failures.push(err),
+            }
+        }
+        match failures.is_empty() {
+            true => Ok(restored),
+            false => Err(failures.join("\n")),
+        }
+    }
+
+    fn restore(apqn: &Apqn, target: ApqnState) -> Result<bool, String> {
+        let Some(current) = ApqnState::read(apqn)? else {
+            return Ok(false);
+        };
+        let steps = current.steps_to(target);
+        for step in &steps {
+            match step {
+                ApqnStep::Unbind => apqn.set_bind_state(bind_state::Unbound),
+                ApqnStep::Bind => apqn.set_bind_state(bind_state::Bound),
+                ApqnStep::Associate(idx) => apqn.set_associate_state(assoc_state::Associated(*idx)),
+            }
+            .map_err(|err| format!("Failure restoring APQN {apqn} ({step:?}): {err}"))?;
+        }
+        Ok(!steps.is_empty())
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -254,6 +441,35 @@ mod tests {
             assert!(!r);
         }
     }
+    #[test]
+    fn test_ap_bus_state() {
+        let base = std::env::temp_dir().join(format!("ap-test-{}", rand::random::<u32>()));
+        let queue = base.join("card01/01.0005");
+        std::fs::create_dir_all(&queue).unwrap();
+        std::fs::create_dir_all(base.join("nocard")).unwrap();
+        std::fs::write(base.join("card01/online"), "1\n").unwrap();
+        std::fs::write(queue.join("online"), "1\n").unwrap();
+        std::fs::write(queue.join("chkstop"), "0\n").unwrap();
+        let base_str = base.to_str().unwrap();
+
+        let state = ApBusState::read_from(base_str);
+        assert_eq!(state.0.len(), 2);
+        assert_eq!(state.0["card01"], "1,,");
+        assert_eq!(state.0["01.0005"], "1,,0");
+        assert_eq!(state, ApBusState::read_from(base_str));
+
+        // checkstop
+        std::fs::write(queue.join("chkstop"), "1\n").unwrap();
+        assert_ne!(state, ApBusState::read_from(base_str));
+        // hotplug of another card
+        std::fs::write(queue.join("chkstop"), "0\n").unwrap();
+        std::fs::create_dir_all(base.join("card02")).unwrap();
+        assert_ne!(state, ApBusState::read_from(base_str));
+
+        std::fs::remove_dir_all(&base).unwrap();
+        assert_eq!(ApBusState::read_from(base_str), ApBusState::default());
+    }
+
     #[test]
     fn test_gather_apqns() {
         let r = ApqnList::gather_apqns();
@@ -302,4 +518,22 @@ mod tests {
             assert!(r.is_none());
         }
     }
+
+    #[test]
+    fn test_apqn_state_steps() {
+        use ApqnState::*;
+        use ApqnStep::*;
+        assert!(Bound.steps_to(Bound).is_empty());
+        assert!(Associated(3).steps_to(Associated(3)).is_empty());
+        assert_eq!(Unbound.steps_to(Bound), [Bind]);
+        assert_eq!(Bound.steps_to(Unbound), [Unbind]);
+        assert_eq!(Associated(1).steps_to(Unbound), [Unbind]);
+        assert_eq!(Unbound.steps_to(Associated(2)), [Bind, Associate(2)]);
+        assert_eq!(Bound.steps_to(Associated(2)), [Unbind, Bind, Associate(2)]);
+        assert_eq!(
+            Associated(1).steps_to(Associated(2)),
+            [Unbind, Bind, Associate(2)]
+        );
+        assert_eq!(Associated(1).steps_to(Bound), [Unbind, Bind]);
+    }
 }
@@ -4,8 +4,9 @@
 //
 //
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use lazy_static::lazy_static;
+use utils::GenerateOptions;
 
 /// The default pvapconfig config file
 pub const PATH_DEFAULT_CONFIG_FILE: &str = "/etc/pvapconfig.yaml";
@@ -14,6 +15,8 @@ pub const PATH_DEFAULT_CONFIG_FILE: &str = "/etc/pvapconfig.yaml";
 #[derive(Parser, Clone)]
 pub struct Cli {
     /// Provide a custom config file (overwrites default /etc/pvapconfig.yaml).
+    ///
+    /// Files ending with .json are read as JSON, all others as YAML.
     #[arg(short, long, value_name = "FILE")]
     pub config: Option<String>,
 
@@ -33,6 +36,19 @@ pub struct Cli {
     #[arg(long, conflicts_with_all = ["config", "strict"])]
     pub unbind: bool,
 
+    /// Keep running and re-apply the configuration whenever AP devices change.
+    ///
+    /// After applying the configuration, watch the AP cards and queues for
+    /// changes, like a hotplugged crypto card or the recovery from a
+    /// checkstop, and apply the configuration again.
+    #[arg(long, conflicts_with = "unbind")]
+    pub watch: bool,
+
+    /// Poll the AP devices every SECONDS in watch mode.
+    #[arg(long, value_name = "SECONDS", default_value_t = 5, requires = "watch",
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub watch_interval: u64,
+
     /// Provide more detailed output.
     #[arg(short, long)]
     pub verbose: bool,
@@ -40,6 +56,17 @@ pub struct Cli {
     /// Print version information and exit.
     #[arg(short = 'V', long)]
     pub version: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands not needed for regular operation
+#[derive(Subcommand, Clone)]
+pub enum Command {
+    /// Generate shell completions and man pages.
+    #[command(hide(true))]
+    Generate(GenerateOptions),
 }
 
 lazy_static! {
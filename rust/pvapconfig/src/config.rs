@@ -3,7 +3,9 @@
 // Copyright IBM Corp. 2023
 //
 //! Functions around handling the pvapconfig configuration file
-//
+//!
+//! The configuration file is either in YAML or, if the file name ends with `.json`, in JSON
+//! format. Both formats share the same schema: a list of AP config entries.
 
 use openssl::sha::sha256;
 use pv_core::misc::encode_hex;
@@ -11,6 +13,7 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_yaml::{self};
 use std::fs::File;
+use std::path::Path;
 use std::slice::Iter;
 
 pub const STR_MODE_EP11: &str = "ep11";
@@ -149,6 +152,23 @@ impl ApConfigEntry {
     }
 }
 
+/// Format of the configuration file
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum ConfigFormat {
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// JSON for files ending with `.json`, YAML otherwise.
+    fn from_fname(fname: &str) -> Self {
+        match Path::new(fname).extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Yaml,
+        }
+    }
+}
+
 /// Wrapper object around Vector of ApConfigEntry
 #[derive(Default)]
 pub struct ApConfigList(Vec<ApConfigEntry>);
@@ -171,14 +191,17 @@ impl ApConfigList {
         self.0.is_empty()
     }
 
-    fn read_yaml_file(fname: &str) -> Result<Vec<ApConfigEntry>, String> {
+    fn read_file(fname: &str) -> Result<Vec<ApConfigEntry>, String> {
         let file = match File::open(fname) {
             Ok(f) => f,
             Err(err) => return Err(format!("Failure to open AP config file {fname}: {err:?}")),
         };
-        match serde_yaml::from_reader(file) {
-            Ok(cfg) => Ok(cfg),
-            Err(err) => Err(format!("Failure parsing AP config file {fname}: {err:?}")),
+        match ConfigFormat::from_fname(fname) {
+            ConfigFormat::Yaml => serde_yaml::from_reader(file)
+                .map_err(|err| format!("Failure parsing AP config file {fname}: {err:?}")),
+            // serde_json reports the line and column of the failure
+            ConfigFormat::Json => serde_json::from_reader(file)
+                .map_err(|err| format!("Failure parsing JSON AP config file {fname}: {err}")),
         }
     }
 
@@ -196,11 +219,11 @@ impl ApConfigList {
         Ok(())
     }
 
-    /// Read in and validate the yaml configuration from a file.
+    /// Read in and validate the yaml or json configuration from a file.
     /// Returns a Result with Ok(ApConfigList) on success
     /// or an Err(errorstring) on failure.
-    pub fn read_and_validate_yaml_file(fname: &str) -> Result<Self, String> {
-        let mut apconfig = Self::read_yaml_file(fname)?;
+    pub fn read_and_validate_file(fname: &str) -> Result<Self, String> {
+        let mut apconfig = Self::read_file(fname)?;
         Self::validate(&mut apconfig)?;
         Ok(Self(apconfig))
     }
@@ -325,9 +348,13 @@ mod tests {
     ];
 
     fn write_yaml_config_to_temp_file(content: &str) -> Result<String, String> {
+        write_config_to_temp_file(content, "yaml")
+    }
+
+    fn write_config_to_temp_file(content: &str, ext: &str) -> Result<String, String> {
         let dir = env::temp_dir();
         let rnd = rand::random::<u32>();
-        let fname = format!("{}/config-test-{}.yaml", dir.to_str().unwrap(), rnd);
+        let fname = format!("{}/config-test-{}.{ext}", dir.to_str().unwrap(), rnd);
         let mut f = match File::create(&fname) {
             Ok(f) => f,
             Err(_) => return Err(format!("Failure creating temp file '{fname}'.")),
@@ -345,7 +372,7 @@ mod tests {
     fn test_good_yaml() {
         for yaml in GOOD_CONFIGS {
             let f = write_yaml_config_to_temp_file(yaml).unwrap();
-            let config = ApConfigList::read_and_validate_yaml_file(&f).unwrap();
+            let config = ApConfigList::read_and_validate_file(&f).unwrap();
             assert!(!config.is_empty());
             fs::remove_file(&f).ok();
         }
@@ -354,7 +381,7 @@ mod tests {
     fn test_bad_yaml() {
         for yaml in BAD_CONFIGS {
             let f = write_yaml_config_to_temp_file(yaml).unwrap();
-            let r = ApConfigList::read_and_validate_yaml_file(&f);
+            let r = ApConfigList::read_and_validate_file(&f);
             assert!(r.is_err());
             fs::remove_file(&f).ok();
         }
@@ -363,11 +390,56 @@ mod tests {
     fn test_invalid_deserizalize() {
         for yaml in BAD_DESERIALIZE {
             let f = write_yaml_config_to_temp_file(yaml).unwrap();
-            let r = ApConfigList::read_and_validate_yaml_file(&f);
+            let r = ApConfigList::read_and_validate_file(&f);
             assert!(r.is_err());
             fs::remove_file(&f).ok();
         }
     }
+    #[test]
+    fn test_json() {
+        let json = r#"[
+            {"name": "my Accelerator", "mode": "Accel", "mingen": "cex7"},
+            {"name": "My-EP11-AP-config", "mode": "EP11",
+             "mkvp": "0xdb3c3b3c3f097dd55ec7eb0e7fdbcb93"}
+        ]"#;
+        let f = write_config_to_temp_file(json, "json").unwrap();
+        let config = ApConfigList::read_and_validate_file(&f).unwrap();
+        fs::remove_file(&f).ok();
+        assert_eq!(config.len(), 2);
+        let ep11 = config.iter().nth(1).unwrap();
+        assert_eq!(ep11.mode, STR_MODE_EP11);
+        assert_eq!(ep11.mkvp, "db3c3b3c3f097dd55ec7eb0e7fdbcb93");
+    }
+
+    #[test]
+    fn test_bad_json() {
+        let bad = [
+            // unknown field
+            ("[{\"mode\": \"Accel\",\n \"type\": \"EP11\"}]", "line 2"),
+            // wrong type
+            ("[{\"mode\": \"Accel\", \"mingen\": 7}]", "line 1"),
+            // no list
+            ("{\"mode\": \"Accel\"}", "line 1"),
+            // invalid mode
+            ("[{\"mode\": \"CCA\"}]", "AP config entry 0"),
+        ];
+        for (json, location) in bad {
+            let f = write_config_to_temp_file(json, "json").unwrap();
+            let r = ApConfigList::read_and_validate_file(&f);
+            fs::remove_file(&f).ok();
+            let err = r.err().unwrap();
+            assert!(err.contains(location), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(ConfigFormat::from_fname("a.json"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_fname("/etc/a.JSON"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_fname("a.yaml"), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_fname("json"), ConfigFormat::Yaml);
+    }
+
     #[test]
     fn test_sha256() {
         assert!(
@@ -12,7 +12,8 @@ mod config;
 mod helper;
 mod uv;
 
-use ap::ApqnList;
+use ap::{ApSnapshot, ApqnList};
+use clap::CommandFactory;
 use cli::ARGS;
 use config::{ApConfigEntry, ApConfigList};
 use helper::{LockFile, PATH_PVAPCONFIG_LOCK};
@@ -20,6 +21,7 @@ use pv_core::ap::{self as pvap, Apqn};
 use pv_core::misc::encode_hex;
 use pv_core::uv::{ListableSecretType, SecretList};
 use std::process::ExitCode;
+use std::time::Duration;
 use utils::print_version;
 
 /// Simple macro for
@@ -50,8 +52,8 @@ macro_rules! println_and_exit_failure {
 /// returns with exit failure.
 macro_rules! on_error_print_and_exit {
     ($r:expr) => {
-        if $r.is_err() {
-            eprintln!("{}", $r.unwrap_err());
+        if let Err(e) = &$r {
+            eprintln!("{e}");
             return ExitCode::FAILURE;
         }
     };
@@ -64,6 +66,12 @@ fn main() -> ExitCode {
         return ExitCode::SUCCESS;
     }
 
+    if let Some(cli::Command::Generate(opt)) = &cli::ARGS.command {
+        let r = opt.run(cli::Cli::command(), "Pvapconfig Manual");
+        on_error_print_and_exit!(r);
+        return ExitCode::SUCCESS;
+    }
+
     // make sure only one pvapconfig instance is running
     let r = LockFile::try_lock(PATH_PVAPCONFIG_LOCK);
     on_error_print_and_exit!(r);
@@ -97,7 +105,7 @@ fn main() -> ExitCode {
             "Reading AP configuration entries from file '{}'...\n",
             configfile
         );
-        apconfig = match ApConfigList::read_and_validate_yaml_file(configfile) {
+        apconfig = match ApConfigList::read_and_validate_file(configfile) {
             Ok(apcfg) => apcfg,
             Err(err) => println_and_exit_failure!("{}", err),
         };
@@ -130,6 +138,15 @@ fn main() -> ExitCode {
         );
     }
 
+    let rc = apply_ap_config(&apconfig, &secrets);
+    if !ARGS.watch {
+        return rc;
+    }
+    watch_ap_config(&apconfig, secrets)
+}
+
+/// Apply the AP configuration to the currently available APQNs.
+fn apply_ap_config(apconfig: &ApConfigList, secrets: &SecretList) -> ExitCode {
     info!("Waiting for AP bus bindings complete...\n");
     if !ap::wait_for_ap_bus_bindings_complete() {
         return ExitCode::FAILURE;
@@ -149,10 +166,29 @@ fn main() -> ExitCode {
         return ExitCode::FAILURE;
     }
 
+    // remember the current state to roll back a failing configuration
+    let snapshot = match ARGS.dryrun() {
+        true => None,
+        false => Some(ApSnapshot::take(&apqns)),
+    };
+    let rollback = || {
+        if let Some(snapshot) = &snapshot {
+            println!("Rolling back the AP configuration...");
+            match snapshot.rollback() {
+                Ok(n) => info!("Restored the state of {} APQNs.\n", n),
+                Err(e) => eprintln!("Failure rolling back the AP configuration:\n{e}"),
+            }
+        }
+    };
+
     // now the real work
     info!("Applying AP configuration...\n");
-    let n = match do_ap_config(&mut apqns, &secrets, &apconfig, false) {
-        Err(e) => println_and_exit_failure!("{}", e),
+    let n = match do_ap_config(&mut apqns, secrets, apconfig, false) {
+        Err(e) => {
+            eprintln!("{e}");
+            rollback();
+            return ExitCode::FAILURE;
+        }
         Ok(n) => n,
     };
 
@@ -162,11 +198,13 @@ fn main() -> ExitCode {
             apconfig.len()
         );
     } else if ARGS.strict() && n != apconfig.len() {
-        println_and_exit_failure!(
+        eprintln!(
             "Strict flag given and only {} out of {} AP config entries have been applied.",
             n,
             apconfig.len()
         );
+        rollback();
+        return ExitCode::FAILURE;
     }
 
     if !cli::ARGS.unbind {
@@ -177,9 +215,40 @@ fn main() -> ExitCode {
         );
     }
 
+    if ARGS.verbose() {
+        match pvap::ap_queue_status() {
+            Ok(status) => println!("{}", pvap::ApHealthSummary::new(&status)),
+            Err(e) => eprintln!("Warning: {e}"),
+        }
+    }
+
     ExitCode::SUCCESS
 }
 
+/// Watch mode: re-apply the AP configuration on each change of the AP devices.
+///
+/// Crypto cards may be hotplugged or recover from a checkstop while the
+/// guest is running. Re-applying the configuration binds and associates
+/// the new APQNs without manual intervention. A failure to apply the
+/// configuration is reported but does not terminate the watch mode.
+fn watch_ap_config(apconfig: &ApConfigList, mut secrets: SecretList) -> ExitCode {
+    let interval = Duration::from_secs(ARGS.watch_interval);
+    let mut state = ap::ApBusState::read();
+    info!("Watching AP devices for changes...\n");
+    loop {
+        ap::wait_for_ap_bus_change(&state, interval);
+        println!("AP devices changed: Applying AP configuration again.");
+        // secrets may have been added in the meantime
+        match uv::gather_secrets() {
+            Ok(los) => secrets = los,
+            Err(e) => eprintln!("Warning: {e}"),
+        }
+        apply_ap_config(apconfig, &secrets);
+        // do not react on the changes caused by applying the configuration
+        state = ap::ApBusState::read();
+    }
+}
+
 /// The real worker function
 ///
 /// This is the real algorithm which is trying to apply the
@@ -56,7 +56,11 @@ pub fn gather_secrets() -> Result<SecretList, String> {
         Ok(u) => u,
     };
     let mut cmd = ListCmd::default();
-    match uv.send_cmd(&mut cmd).map_err(|e| format!("{e:?}"))? {
+    match uv
+        .send_cmd(&mut cmd)
+        .map_err(|e| format!("{e:?}"))?
+        .success()
+    {
         UvcSuccess::RC_SUCCESS => (),
         UvcSuccess::RC_MORE_DATA => println!("Warning: There is more data available than expected"),
     };
@@ -2,15 +2,16 @@
 //
 // Copyright IBM Corp. 2024
 
-use crate::exchange::ExchangeFormatResponse;
 use anyhow::Result;
-use pv::attest::{AdditionalData, AttestationFlags};
+use pv::attest::{AdditionalData, AttestationFlags, ExchangeFormatResponse};
 use serde::Serialize;
 use std::fmt::Display;
 use utils::HexSlice;
 
 #[derive(Serialize)]
 pub struct AttestationResult<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<&'a str>,
     pub cuid: HexSlice<'a>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub add: Option<HexSlice<'a>>,
@@ -30,6 +31,7 @@ impl<'a> AttestationResult<'a> {
             .map(|a| AdditionalData::from_slice_sized(a, flags))
             .transpose()?;
         Ok(Self {
+            image: None,
             cuid: resp.config_uid().into(),
             add: resp.additional().map(|a| a.into()),
             add_fields,
@@ -40,6 +42,9 @@ impl<'a> AttestationResult<'a> {
 
 impl Display for AttestationResult<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(image) = self.image {
+            writeln!(f, "Image: {image}")?;
+        }
         writeln!(f, "Config UID:")?;
         writeln!(f, "{:#}", self.cuid)?;
         if let Some(data) = &self.add {
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use pv::{
+    attest::KeyBinding,
+    misc::read_file,
+    request::openssl::{
+        pkey::{PKey, Public},
+        x509::X509,
+    },
+};
+
+/// Parse a PEM or DER encoded public key or X.509 certificate.
+fn parse_public_key(buf: &[u8]) -> Option<PKey<Public>> {
+    PKey::public_key_from_pem(buf)
+        .or_else(|_| PKey::public_key_from_der(buf))
+        .ok()
+        .or_else(|| {
+            X509::from_pem(buf)
+                .or_else(|_| X509::from_der(buf))
+                .and_then(|cert| cert.public_key())
+                .ok()
+        })
+}
+
+/// Bind the public key in `path` for `context`.
+///
+/// `path` contains a public key or an X.509 certificate, PEM or DER encoded.
+pub fn key_binding<P: AsRef<Path>>(path: P, context: &str) -> Result<KeyBinding> {
+    let path = path.as_ref();
+    let buf = read_file(path, "public key")?;
+    let key = parse_public_key(&buf).with_context(|| {
+        format!(
+            "'{}' contains no public key or X.509 certificate",
+            path.display()
+        )
+    })?;
+    Ok(KeyBinding::new(&key, context)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let cert = include_bytes!("../tests/assets/host.pem.crt");
+        let key = parse_public_key(cert).unwrap();
+        let der = parse_public_key(&key.public_key_to_der().unwrap()).unwrap();
+        let pem = parse_public_key(&key.public_key_to_pem().unwrap()).unwrap();
+        assert!(der.public_eq(&key));
+        assert!(pem.public_eq(&key));
+        assert!(parse_public_key(b"no key").is_none());
+    }
+}
@@ -2,10 +2,14 @@
 //
 // Copyright IBM Corp. 2024
 
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::path::PathBuf;
 
 use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
-use utils::{CertificateOptions, DeprecatedVerbosityOptions};
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+use utils::{AuditOptions, MetricsOptions, PrivilegeOptions};
+use utils::{CertificateOptions, DeprecatedVerbosityOptions, GenerateOptions, SandboxOptions};
 
 /// create, perform, and verify attestation measurements
 ///
@@ -21,6 +25,12 @@ pub struct CliOptions {
     #[arg(long)]
     pub version: bool,
 
+    /// Require OpenSSL to be in FIPS mode and use FIPS-approved algorithms only.
+    ///
+    /// Fails if OpenSSL is not in FIPS mode.
+    #[arg(long, global = true)]
+    pub fips: bool,
+
     #[command(subcommand)]
     pub cmd: Command,
 }
@@ -33,6 +43,8 @@ pub enum Command {
     /// attestation requests in a trusted environment such as your Workstation. To avoid
     /// compromising the attestation do not publish the attestation request protection key and
     /// shred it after verification. Every 'create' will generate a new, random protection key.
+    /// User data is stored in the request and used by 'pvattest perform' unless it is called with
+    /// own user data.
     Create(Box<CreateAttOpt>),
 
     /// Send the attestation request to the Ultravisor.
@@ -40,9 +52,20 @@ pub enum Command {
     /// Run a measurement of this system through ’/dev/uv’. This device must be accessible and the
     /// attestation Ultravisor facility must be present. The input must be an attestation request
     /// created with ’pvattest create’. Output will contain the original request and the response
-    /// from the Ultravisor.
+    /// from the Ultravisor. The output uses the exchange format version of the input, unless the
+    /// user-data encoding requires version 2. Without user data, the user data of the request is
+    /// used.
     Perform(PerformAttOpt),
 
+    /// Serve attestation requests on a vsock port.
+    ///
+    /// Listen on a vsock port inside the IBM Secure Execution guest, run every attestation request
+    /// received through '/dev/uv', and send the response back on the same connection. Enables the
+    /// attestation of guests without external network, for example during early provisioning.
+    /// Requests and responses use the same format as 'pvattest perform'. Without user data, the
+    /// user data of each request is used.
+    Serve(ServeOpt),
+
     /// Verify an attestation response.
     ///
     /// Verify that a previously generated attestation measurement of an IBM Secure Execution guest
@@ -56,14 +79,87 @@ pub enum Command {
     /// you verified the Attestation measurement.
     Verify(VerifyOpt),
 
+    /// Attest an IBM Secure Execution guest over SSH.
+    ///
+    /// Send the attestation request created with 'pvattest create' to the guest over SSH, run it
+    /// there through '/dev/uv', retrieve the response, and verify it on this system like
+    /// 'pvattest verify'. The guest must have pvattest installed and the SSH user must have access
+    /// to '/dev/uv'. The user data of the request is used.
+    Remote(Box<RemoteOpt>),
+
+    /// Run the attestation request from stdin and write the response to stdout.
+    ///
+    /// Companion of 'pvattest remote' that runs inside the IBM Secure Execution guest.
+    #[command(hide(true))]
+    RemotePerform(RemotePerformOpt),
+
+    /// Calculate the expected attestation measurement.
+    ///
+    /// Calculate the attestation measurement an IBM Secure Execution guest running the given image
+    /// returns for a request created with 'pvattest create'. No IBM Secure Execution hardware is
+    /// required, so that reference values can be produced in a build pipeline. The Configuration
+    /// Unique ID of the guest must be known. Without user data, the user data of the request is
+    /// used.
+    Measure(MeasureOpt),
+
     /// Check if the attestation result matches defined policies.
     ///
     /// After the attestation verification, check whether the attestation result complies with user-defined policies.
     Check(CheckOpt),
 
+    /// Calculate the public host-key hashes of host-key documents.
+    ///
+    /// Print the public host-key hash (PHKH) of each host-key document. These hashes show up in
+    /// the additional data of attestation responses if the image or attestation PHKH was
+    /// requested. Use them to pre-compute the expected reference values.
+    HkdHash(HkdHashOpt),
+
+    /// Check the prerequisites of the attestation.
+    ///
+    /// Check the whole stack that 'pvattest perform' depends on: whether this system is an IBM
+    /// Secure Execution guest, the Ultravisor-call facility, the attestation support of the
+    /// Ultravisor, and whether the uvdevice kernel module is loaded and '/dev/uv' is accessible.
+    /// Optionally check that host-key documents are readable and not expired. Print a fix for
+    /// every failing check and exit with a non-zero code if any check failed.
+    Doctor(DoctorOpt),
+
     /// Print version information and exit.
     #[command(aliases(["--version"]), hide(true))]
     Version,
+
+    /// Generate shell completions and man pages.
+    #[command(hide(true))]
+    Generate(GenerateOptions),
+}
+
+#[derive(Args, Debug)]
+pub struct HkdHashOpt {
+    /// Use FILE as a host-key document.
+    ///
+    /// Can be specified multiple times. The hash of the first certificate in each file is
+    /// printed.
+    #[arg(value_name = "FILE", required = true, value_hint = ValueHint::FilePath,)]
+    pub host_key_documents: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DoctorOpt {
+    /// Check that the host-key document in FILE is readable and not expired.
+    ///
+    /// Warns if the host-key document expires within 30 days. Can be specified multiple times.
+    #[arg(
+        short = 'k',
+        long = "host-key-document",
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub host_key_documents: Vec<PathBuf>,
+
+    /// Skip the checks of the Secure Execution guest.
+    ///
+    /// Use this on a trusted system that creates requests and verifies responses.
+    #[arg(long)]
+    pub no_guest: bool,
 }
 
 #[derive(Args, Debug)]
@@ -93,6 +189,57 @@ pub struct CreateAttOpt {
         value_delimiter = ','
     )]
     pub add_data: Vec<AttAddFlags>,
+
+    /// Use ALG as measurement algorithm.
+    ///
+    /// The Ultravisor of the attested guest must support the algorithm.
+    #[arg(long, value_name = "ALG", value_enum, default_value_t)]
+    pub measurement_alg: AttMeasAlg,
+
+    // The user data is stored in the request. 'pvattest perform' uses it unless it is called
+    // with own user data.
+    #[command(flatten)]
+    pub user_data_args: UserDataOptions,
+}
+
+#[derive(Args, Debug)]
+pub struct UserDataOptions {
+    /// Read up to 256 bytes of user data from FILE.
+    ///
+    /// User-data is arbitrary user-defined data appended to the Attestation measurement.
+    /// It is verified during the Attestation measurement verification. Use '-' to read from
+    /// stdin.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub user_data: Option<String>,
+
+    /// Use DATA as user data.
+    #[arg(long, value_name = "DATA", conflicts_with("user_data"))]
+    pub user_data_value: Option<String>,
+
+    /// Decode the user data with ENCODING.
+    ///
+    /// The 256-byte limit applies to the decoded user data. The encoding is stored in the
+    /// exchange file, so that 'pvattest verify' writes the user data in the same encoding.
+    #[arg(long, value_name = "ENCODING", value_enum, default_value_t)]
+    pub user_data_encoding: UserDataEncoding,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UserDataEncoding {
+    /// Use the data as is.
+    #[default]
+    Raw,
+    /// The data is a hex string.
+    Hex,
+    /// The data is a (standard, padded) base64 string.
+    Base64,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, Default)]
+pub enum AttMeasAlg {
+    /// HMAC with SHA512.
+    #[default]
+    HmacSha512,
 }
 
 #[derive(Debug, ValueEnum, Clone, Copy)]
@@ -114,43 +261,120 @@ pub enum AttAddFlags {
 #[derive(Args, Debug)]
 pub struct PerformAttOpt {
     /// Specify the request to be sent.
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     #[arg(hide=true, short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub input: Option<String>,
 
     /// Specify the request to be sent.
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     #[arg(value_name = "IN", value_hint = ValueHint::FilePath, required_unless_present("input"), conflicts_with("input"))]
     pub input_pos: Option<String>,
 
     /// Write the result to FILE.
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     #[arg(hide=true, short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub output: Option<String>,
 
     /// Write the result to FILE.
     #[arg(value_name = "OUT", value_hint = ValueHint::FilePath, required_unless_present("output"), conflicts_with("output"))]
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     pub output_pos: Option<String>,
 
-    /// Provide up to 256 bytes of user input
+    // Without user data, the user data of the request is used, if it contains any.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub user_data_args: UserDataOptions,
+
+    /// Bind the public key in FILE to the attestation measurement.
     ///
-    /// User-data is arbitrary user-defined data appended to the Attestation measurement.
-    /// It is verified during the Attestation measurement verification.
-    /// May be any arbitrary data, as long as it is less or equal to 256 bytes
-    #[arg(short, long, value_name = "File", value_hint = ValueHint::FilePath,)]
-    pub user_data: Option<String>,
+    /// Uses a canonical encoding of the public key and the context as user-data. FILE contains a
+    /// public key or an X.509 certificate, PEM or DER encoded. Verify the binding with 'pvattest
+    /// check --bind-key'.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, conflicts_with_all(["user_data", "user_data_value"]))]
+    pub bind_key: Option<String>,
+
+    /// Bind the public key for CONTEXT, e.g. 'tls'.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_name = "CONTEXT", default_value = "", requires("bind_key"))]
+    pub bind_context: String,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub audit: AuditOptions,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub privileges: PrivilegeOptions,
+}
+
+// all members s390x only
+#[derive(Args, Debug)]
+pub struct ServeOpt {
+    /// Listen on the vsock port PORT.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(short, long, value_name = "PORT")]
+    pub port: u32,
+
+    // The user data is used for every request. Without user data, the user data of the
+    // request is used, if it contains any.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub user_data_args: UserDataOptions,
+
+    /// Exit after the first request.
+    ///
+    /// The exit code reflects whether the attestation succeeded.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long)]
+    pub once: bool,
+
+    /// Accept at most N connections at a time.
+    ///
+    /// Further connections are closed without a response until a connection is finished.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_name = "N", default_value_t = NonZeroUsize::new(16).unwrap())]
+    pub queue_depth: NonZeroUsize,
+
+    /// Run at most N attestation requests through the uvdevice at a time.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_name = "N", default_value_t = NonZeroUsize::new(1).unwrap())]
+    pub max_concurrent_uvc: NonZeroUsize,
+
+    /// Accept at most N requests per minute from each client.
+    ///
+    /// Clients are identified by their vsock context ID. Unused requests accumulate up to N, so
+    /// that short bursts are possible. Requests beyond the limit are closed without a response.
+    /// By default, the request rate is not limited.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_name = "N")]
+    pub rate_limit: Option<NonZeroU32>,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub audit: AuditOptions,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub privileges: PrivilegeOptions,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub metrics: MetricsOptions,
 }
 
-#[cfg(target_arch = "s390x")]
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 #[derive(Debug)]
 pub struct PerformAttOptComb<'a> {
     pub input: &'a str,
     pub output: &'a str,
-    pub user_data: Option<&'a str>,
+    pub user_data: &'a UserDataOptions,
+    pub bind_key: Option<(&'a str, &'a str)>,
+    pub audit: &'a AuditOptions,
+    pub privileges: &'a PrivilegeOptions,
 }
 
-#[cfg(target_arch = "s390x")]
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 impl<'a> From<&'a PerformAttOpt> for PerformAttOptComb<'a> {
     fn from(value: &'a PerformAttOpt) -> Self {
         let input = match (&value.input, &value.input_pos) {
@@ -165,24 +389,33 @@ impl<'a> From<&'a PerformAttOpt> for PerformAttOptComb<'a> {
             (Some(_), Some(_)) => unreachable!(),
             (None, None) => unreachable!(),
         };
-        let user_data = value.user_data.as_deref();
+        let user_data = &value.user_data_args;
+        let bind_key = value
+            .bind_key
+            .as_deref()
+            .map(|key| (key, value.bind_context.as_str()));
         Self {
             input,
             output,
             user_data,
+            bind_key,
+            audit: &value.audit,
+            privileges: &value.privileges,
         }
     }
 }
 
 #[derive(Args, Debug)]
-pub struct VerifyOpt {
-    /// Specify the attestation response to be verified.
+pub struct MeasureOpt {
+    /// Specify the attestation request created by 'pvattest create'.
     #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub input: String,
 
-    /// Specify the output for the verification result
-    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
-    pub output: Option<String>,
+    /// Specify the output for the measurement.
+    ///
+    /// Use '-' to write to stdout.
+    #[arg(short, long, value_name = "FILE", default_value = "-", value_hint = ValueHint::FilePath,)]
+    pub output: PathBuf,
 
     /// Specifies the header of the guest image.
     ///
@@ -194,10 +427,66 @@ pub struct VerifyOpt {
     /// Use FILE as the protection key to decrypt the request
     ///
     /// Do not publish this key, otherwise your attestation is compromised.
-    /// Delete this key after verification.
     #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub arpk: String,
 
+    /// Use HEXSTRING as the Configuration Unique ID of the guest.
+    ///
+    /// Must be a hex 128-bit unsigned big endian number string. Leading zeros must be provided.
+    #[arg(long, value_name = "HEXSTRING")]
+    pub cuid: String,
+
+    #[command(flatten)]
+    pub user_data_args: UserDataOptions,
+
+    /// Read the additional data the Ultravisor returns from FILE.
+    ///
+    /// Required if the request asks for additional data, as it is part of the measurement.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub additional_data: Option<String>,
+
+    /// Write a reference value named NAME instead of the hex-encoded measurement.
+    ///
+    /// The output is a YAML list with one entry that can be used with 'pvattest verify
+    /// --reference-values'.
+    #[arg(long, value_name = "NAME")]
+    pub name: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyOpt {
+    /// Specify the attestation response to be verified.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,
+        required_unless_present = "input_dir")]
+    pub input: Option<String>,
+
+    /// Verify all attestation responses in DIR against the same policy.
+    ///
+    /// Every regular, non-hidden file in DIR must be an attestation response of a request
+    /// protected with the key given by '--arpk', e.g. the responses of a fleet of guests to one
+    /// request. A failing response does not stop the verification of the others. The consolidated
+    /// report with the verdict per file and summary counts is written to '--output' or, if not
+    /// specified, to stdout. The command fails if not all responses are verified.
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath,
+        conflicts_with_all = ["input", "expect_cuid", "user_data"])]
+    pub input_dir: Option<PathBuf>,
+
+    /// Specify the output for the verification result
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub policy: VerifyPolicyOptions,
+
+    /// Append the verified measurement and Config-UID to FILE.
+    ///
+    /// The entry is written in the IMA ASCII measurement-list format with the 'ima-buf'
+    /// template, so that host integrity tooling can incorporate the attestation result. The
+    /// event data is the Config-UID followed by the measurement. Nothing is written if the
+    /// verification fails.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub evidence_log: Option<String>,
+
     /// Define the output format.
     #[arg(long, value_enum, default_value_t)]
     pub format: OutputType,
@@ -207,11 +496,118 @@ pub struct VerifyOpt {
     /// Writes the user data, if the response contains any, to FILE
     /// The user-data is part of the attestation measurement. If the user-data is written to FILE
     /// the user-data was part of the measurement and verified.
-    /// Emits a warning if the response contains no user-data.
+    /// Emits a warning if the response contains no user-data. The user-data is written in the
+    /// encoding it was provided in.
+    #[arg(long, short ,value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub user_data: Option<String>,
+
+    #[command(flatten)]
+    pub sandbox: SandboxOptions,
+}
+
+#[derive(Args, Debug)]
+pub struct RemoteOpt {
+    /// Connect to the guest with 'ssh DESTINATION', e.g. 'root@guest'.
+    #[arg(long, value_name = "DESTINATION")]
+    pub ssh: String,
+
+    /// Pass OPTION to ssh with '-o', e.g. 'Port=2222'.
+    ///
+    /// Can be specified multiple times.
+    #[arg(long, value_name = "OPTION")]
+    pub ssh_option: Vec<String>,
+
+    /// Run PATH as pvattest in the guest.
+    #[arg(long, value_name = "PATH", default_value = "pvattest")]
+    pub remote_pvattest: String,
+
+    /// Specify the attestation request created by 'pvattest create'.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub input: String,
+
+    /// Write the attestation response of the guest to FILE.
+    ///
+    /// The response is written before it is verified, so that it can be inspected or checked
+    /// with 'pvattest check' afterwards.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub response: String,
+
+    #[command(flatten)]
+    pub policy: VerifyPolicyOptions,
+
+    /// Specify the output for the verification result
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub output: Option<String>,
+
+    /// Append the verified measurement and Config-UID to FILE.
+    ///
+    /// The entry is written in the IMA ASCII measurement-list format with the 'ima-buf'
+    /// template. Nothing is written if the verification fails.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub evidence_log: Option<String>,
+
+    /// Define the output format.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputType,
+
+    /// Write the user data to the FILE if any.
     #[arg(long, short ,value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub user_data: Option<String>,
 }
 
+// all members s390x only
+#[derive(Args, Debug)]
+pub struct RemotePerformOpt {
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub audit: AuditOptions,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub privileges: PrivilegeOptions,
+}
+
+/// What an attestation response is verified against
+#[derive(Args, Debug, Clone)]
+pub struct VerifyPolicyOptions {
+    /// Specifies the header of the guest image.
+    ///
+    /// Can be an IBM Secure Execution image created by genprotimg or an extracted IBM Secure
+    /// Execution header. The header must start at a page boundary.
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+        required_unless_present = "reference_values"
+    )]
+    pub hdr: Option<String>,
+
+    /// Use FILE as a list of known-good images the guest is compared against.
+    ///
+    /// FILE is a YAML list of reference values. Each entry has a 'name' and either a 'hdr' with
+    /// the path to an IBM Secure Execution image or header, 'tags' with the hex-encoded 'pld',
+    /// 'ald', 'tld', and 'tag' of the SE header, or a precomputed hex-encoded 'measurement'. The
+    /// verification succeeds if the measurement matches one of the entries, or the header given
+    /// by '--hdr'. The name of the first matching entry is reported.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub reference_values: Option<String>,
+
+    /// Use FILE as the protection key to decrypt the request
+    ///
+    /// Do not publish this key, otherwise your attestation is compromised.
+    /// Delete this key after verification.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub arpk: String,
+
+    /// Expect HEXSTRING as the Configuration Unique ID of the attested guest.
+    ///
+    /// Must be a hex 128-bit unsigned big endian number string. Leading zeros must be provided.
+    /// The verification fails if the Config-UID of the attestation response differs, i.e., if a
+    /// different guest instance answered the request.
+    #[arg(long, value_name = "HEXSTRING")]
+    pub expect_cuid: Option<String>,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
 pub enum OutputType {
     /// Use yaml format.
@@ -265,6 +661,18 @@ pub struct CheckOpt {
     #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub user_data: Option<PathBuf>,
 
+    /// Check if the attestation response binds the public key in FILE.
+    ///
+    /// Checks that the user-data of the attestation response is the binding of the public key
+    /// and the context created by 'pvattest perform --bind-key'. FILE contains a public key or an
+    /// X.509 certificate, PEM or DER encoded.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, conflicts_with("user_data"))]
+    pub bind_key: Option<PathBuf>,
+
+    /// Check that the public key is bound for CONTEXT.
+    #[arg(long, value_name = "CONTEXT", default_value = "", requires("bind_key"))]
+    pub bind_context: String,
+
     /// Use FILE to include as successful Add-secret request.
     ///
     /// Checks if the Attestation response contains the hash of all specified add secret
@@ -4,24 +4,42 @@
 //
 pub mod check;
 pub mod create;
-#[cfg(target_arch = "s390x")]
+pub mod doctor;
+pub mod hkd_hash;
+pub mod measure;
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 pub mod perform;
+pub mod remote;
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+pub mod serve;
 pub mod verify;
 
 pub use check::check;
 pub use create::create;
+pub use doctor::doctor;
+pub use hkd_hash::hkd_hash;
+pub use measure::measure;
+pub use remote::remote;
 pub use verify::verify;
 
-pub const CMD_FN: &[&str] = &["+create", "+verify"];
+pub const CMD_FN: &[&str] = &[
+    "+create",
+    "+verify",
+    "+measure",
+    "+hkd-hash",
+    "+doctor",
+    "+remote",
+];
 // s390 branch
-#[cfg(target_arch = "s390x")]
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 mod uv_cmd {
-    pub use super::perform::perform;
-    pub const UV_CMD_FN: &[&str] = &["+perform"];
+    pub use super::perform::{perform, remote_perform};
+    pub use super::serve::serve;
+    pub const UV_CMD_FN: &[&str] = &["+perform", "+serve"];
 }
 
 // non s390-branch
-#[cfg(not(target_arch = "s390x"))]
+#[cfg(not(any(target_arch = "s390x", feature = "uv-sim")))]
 mod uv_cmd {
     use std::process::ExitCode;
 
@@ -30,6 +48,12 @@ mod uv_cmd {
     pub fn perform(_: &crate::cli::PerformAttOpt) -> Result<ExitCode> {
         bail!("Command only available on s390x")
     }
+    pub fn serve(_: &crate::cli::ServeOpt) -> Result<ExitCode> {
+        bail!("Command only available on s390x")
+    }
+    pub fn remote_perform(_: &crate::cli::RemotePerformOpt) -> Result<ExitCode> {
+        bail!("Command only available on s390x")
+    }
     pub const UV_CMD_FN: &[&str] = &[];
 }
 pub use uv_cmd::*;
@@ -12,12 +12,12 @@ use self::{
     secret_store::secret_store_check,
     secret_store::SecretStoreCheck,
 };
-use crate::{additional::AttestationResult, cli::CheckOpt, exchange::ExchangeFormatResponse};
+use crate::{additional::AttestationResult, binding::key_binding, cli::CheckOpt};
 use anyhow::Result;
 use log::{debug, info, warn};
 use pv::{
-    attest::AttestationRequest,
-    misc::{create_file, open_file, read_file},
+    attest::{AttestationRequest, ExchangeFormatResponse},
+    misc::{create_file, eq_ct, open_file, read_file},
 };
 use serde::Serialize;
 use std::process::ExitCode;
@@ -76,7 +76,11 @@ fn user_data_check<'a>(
         None => return Ok(CheckState::None),
     };
 
-    if Some(HexSlice::from(&user_data)) != att_res.user_data {
+    if !att_res
+        .user_data
+        .as_ref()
+        .is_some_and(|exp| eq_ct(exp.as_ref(), &user_data))
+    {
         bail_check!(
             "The Provided user data does not match the user data from the attestation response."
         );
@@ -85,6 +89,27 @@ fn user_data_check<'a>(
     Ok(att_res.user_data.clone().into())
 }
 
+/// Check if the user-data in the attestation response binds the public key
+fn key_binding_check(opt: &CheckOpt, att_res: &AttestationResult) -> Result<CheckState<bool>> {
+    let binding = match &opt.bind_key {
+        Some(file) => key_binding(file, &opt.bind_context)?,
+        None => return Ok(CheckState::None),
+    };
+
+    if !att_res
+        .user_data
+        .as_ref()
+        .is_some_and(|data| binding.verify(data.as_ref()))
+    {
+        bail_check!(
+            "The attestation response does not bind the provided public key for the context '{}'.",
+            opt.bind_context
+        );
+    }
+    info!("✓ Checked public-key binding");
+    Ok(CheckState::Data(true))
+}
+
 #[derive(Debug, Serialize, Default)]
 pub struct CheckResult<'a> {
     successful: bool,
@@ -97,6 +122,8 @@ pub struct CheckResult<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     user_data: Option<HexSlice<'a>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    key_binding: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     secret_store: Option<SecretStoreCheck<'a>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     valid_firmware: Option<bool>,
@@ -118,6 +145,7 @@ pub fn check(opt: &CheckOpt) -> Result<ExitCode> {
         .unwrap();
 
     let user_data = user_data_check(opt, &att_res)?.check(&mut issues);
+    let key_binding = key_binding_check(opt, &att_res)?.check(&mut issues);
     let secret_store = secret_store_check(opt, &att_res)?.check(&mut issues);
 
     let firmware_check = firmware_check(opt, &att_res)?;
@@ -134,6 +162,7 @@ pub fn check(opt: &CheckOpt) -> Result<ExitCode> {
         image_host_key,
         attest_host_key,
         user_data,
+        key_binding,
         secret_store,
         valid_firmware,
     };
@@ -4,10 +4,7 @@
 
 use anyhow::Result;
 use log::{debug, info};
-use pv::{
-    misc::{read_certs, read_file},
-    request::{openssl::DigestBytes, EcPubKeyCoord},
-};
+use pv::{misc::eq_ct, request::Keyslot};
 use serde::Serialize;
 use std::{fmt::Display, path::Path};
 use utils::HexSlice;
@@ -16,6 +13,7 @@ use super::CheckState;
 use crate::{
     additional::AttestationResult,
     cli::{CheckOpt, HostKeyCheckPolicy},
+    cmd::hkd_hash::read_phkh,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -37,30 +35,23 @@ impl Display for HkCheck {
     }
 }
 
-fn load_host_keys<A: AsRef<Path>>(hkds: &[A]) -> Result<Vec<(&Path, DigestBytes)>> {
-    let mut hkd_hash = Vec::with_capacity(hkds.len());
-    for hkd in hkds {
-        let hkd = hkd.as_ref();
-        let hk = read_file(hkd, "host-key document")?;
-        let certs = read_certs(&hk).map_err(|source| pv::Error::HkdNotPemOrDer {
-            hkd: hkd.display().to_string(),
-            source,
-        })?;
-        let ec_coord: EcPubKeyCoord = certs.first().unwrap().public_key()?.as_ref().try_into()?;
-        hkd_hash.push((hkd, ec_coord.sha256()?));
-    }
-    Ok(hkd_hash)
+type Phkh = [u8; Keyslot::PHKH_SIZE as usize];
+
+fn load_host_keys<A: AsRef<Path>>(hkds: &[A]) -> Result<Vec<(&Path, Phkh)>> {
+    hkds.iter()
+        .map(|hkd| Ok((hkd.as_ref(), read_phkh(hkd.as_ref())?)))
+        .collect()
 }
 
 fn contains_phkh<'a>(
-    hkd_hashes: &[(&'a Path, DigestBytes)],
+    hkd_hashes: &[(&'a Path, Phkh)],
     phkh: &HexSlice<'_>,
     mode: HkCheck,
     check_enforced: bool,
 ) -> CheckState<HostKeyCheck<'a>> {
     let hk: Vec<_> = hkd_hashes
         .iter()
-        .filter_map(|(path, hash)| match hash.as_ref() == phkh.as_ref() {
+        .filter_map(|(path, hash)| match eq_ct(hash.as_ref(), phkh.as_ref()) {
             true => Some(*path),
             false => None,
         })
@@ -134,7 +125,6 @@ pub fn host_key_check<'a, 'b>(
 
 #[cfg(test)]
 mod test {
-    use std::path::PathBuf;
 
     use super::*;
 
@@ -169,7 +159,7 @@ mod test {
         let res = contains_phkh(&hash, &HexSlice::from(&hash[0].1), HkCheck::Image, true);
         assert!(matches!(
             res,
-            CheckState::Data(s) if s.hash.unwrap() == PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/host.pem.crt"))
+            CheckState::Data(s) if s.hash.unwrap() == concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/host.pem.crt")
         ))
     }
 }
@@ -8,7 +8,10 @@ use anyhow::Result;
 use log::info;
 use openssl::hash::DigestBytes;
 use openssl::hash::{hash, MessageDigest};
-use pv::{misc::read_file, secret::AddSecretRequest};
+use pv::{
+    misc::{eq_ct, read_file},
+    secret::AddSecretRequest,
+};
 use serde::Serialize;
 
 use super::{bail_check, CheckState};
@@ -54,7 +57,10 @@ pub fn secret_store_check<'a>(
         ),
     };
 
-    if secret_store_hash(&opt.secret, locked)?.as_ref() != att_store_hash.as_ref() {
+    if !eq_ct(
+        &secret_store_hash(&opt.secret, locked)?,
+        att_store_hash.as_ref(),
+    ) {
         bail_check!("The calculated secret-store-hash does not match with the provided hash");
     }
     info!("✓ Secret Store hash");
@@ -2,44 +2,51 @@
 //
 // Copyright IBM Corp. 2024
 
-use crate::{
-    cli::{AttAddFlags, CreateAttOpt},
-    exchange::{ExchangeFormatRequest, ExchangeFormatVersion},
-};
+use crate::cli::{AttAddFlags, AttMeasAlg, CreateAttOpt};
 use anyhow::{bail, Context, Result};
 use log::{debug, warn};
 use pv::{
-    attest::{AttestationFlags, AttestationMeasAlg, AttestationRequest, AttestationVersion},
+    attest::{
+        AdditionalDataItem, AttestationFlags, AttestationMeasAlg, AttestationRequest,
+        AttestationVersion, ExchangeExtension, ExchangeFormatRequest, ExchangeFormatVersion,
+    },
     misc::{create_file, write_file},
     request::{ReqEncrCtx, Request, SymKey, SymKeyType},
 };
 use std::process::ExitCode;
 
-fn flags(cli_flags: &[AttAddFlags]) -> AttestationFlags {
-    let mut att_flags = AttestationFlags::default();
-    for flag in cli_flags {
-        match flag {
-            AttAddFlags::PhkhImg => att_flags.set_image_phkh(),
-            AttAddFlags::PhkhAtt => att_flags.set_attest_phkh(),
-            AttAddFlags::SecretStoreHash => att_flags.set_secret_store_hash(),
-            AttAddFlags::FirmwareState => att_flags.set_firmware_state(),
-        }
+fn additional_item(flag: &AttAddFlags) -> AdditionalDataItem {
+    match flag {
+        AttAddFlags::PhkhImg => AdditionalDataItem::ImagePhkh,
+        AttAddFlags::PhkhAtt => AdditionalDataItem::AttestationPhkh,
+        AttAddFlags::SecretStoreHash => AdditionalDataItem::SecretStoreHash,
+        AttAddFlags::FirmwareState => AdditionalDataItem::FirmwareState,
+    }
+}
+
+fn meas_alg(alg: AttMeasAlg) -> AttestationMeasAlg {
+    match alg {
+        AttMeasAlg::HmacSha512 => AttestationMeasAlg::HmacSha512,
     }
-    att_flags
 }
 
 pub fn create(opt: &CreateAttOpt) -> Result<ExitCode> {
     let att_version = AttestationVersion::One;
-    let meas_alg = AttestationMeasAlg::HmacSha512;
+    let meas_alg = meas_alg(opt.measurement_alg);
 
-    let mut arcb = AttestationRequest::new(att_version, meas_alg, flags(&opt.add_data))?;
+    let mut arcb = AttestationRequest::new(att_version, meas_alg, AttestationFlags::default())?;
+    for flag in &opt.add_data {
+        // The request is usually created on a trusted system, not on the guest. Therefore, the
+        // features of the Ultravisor are unknown.
+        arcb.request_additional(additional_item(flag), None)?;
+    }
     debug!("Generated Attestation request");
 
     // Add host-key documents
-    opt.certificate_args
-        .get_verified_hkds("attestation request")?
-        .into_iter()
-        .for_each(|k| arcb.add_hostkey(k));
+    arcb.add_hostkeys(
+        opt.certificate_args
+            .get_verified_hkds("attestation request")?,
+    )?;
     debug!("Added all host-keys");
 
     let encr_ctx =
@@ -47,13 +54,29 @@ pub fn create(opt: &CreateAttOpt) -> Result<ExitCode> {
     let ser_arcb = arcb.encrypt(&encr_ctx)?;
     warn!("Successfully generated the request");
 
+    let user_data = opt.user_data_args.read()?;
+
     let mut output = create_file(&opt.output)?;
-    let exch_ctx = ExchangeFormatRequest::new(
+    let mut exch_ctx = ExchangeFormatRequest::new(
         ser_arcb,
         meas_alg.exp_size(),
         arcb.flags().expected_additional_size(),
     )?;
-    exch_ctx.write(&mut output, ExchangeFormatVersion::One)?;
+    if let Some(user_data) = user_data {
+        exch_ctx.extensions.push(ExchangeExtension::new(
+            ExchangeExtension::USER_DATA,
+            user_data.into(),
+        ));
+        opt.user_data_args
+            .user_data_encoding
+            .store(&mut exch_ctx.extensions);
+    }
+    // Stay compatible with older pvattest versions unless extensions are required
+    let version = match exch_ctx.extensions.is_empty() {
+        true => ExchangeFormatVersion::One,
+        false => ExchangeFormatVersion::Two,
+    };
+    exch_ctx.write(&mut output, version)?;
 
     let arpk = match encr_ctx.prot_key() {
         SymKey::Aes256(k) => k,
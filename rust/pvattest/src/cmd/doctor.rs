@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{cmp::Ordering, fmt::Display, io::ErrorKind, path::Path, process::ExitCode};
+
+use anyhow::Result;
+use log::debug;
+use openssl::{asn1::Asn1Time, x509::X509Ref};
+use pv::{
+    env::{has_uv_call_facility, is_se_guest},
+    misc::{read_hkd, Flags},
+    uv::{AttestationCmd, UvDevice, UvFlags, UvQueryInfo},
+    PvCoreError,
+};
+
+use crate::cli::DoctorOpt;
+
+/// Ultravisor facility bit of the Retrieve Attestation Measurement UVC
+const UV_FAC_ATTESTATION: u8 = 28;
+/// Warn if a host-key document expires within this number of days
+const HKD_EXPIRY_WARN_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ok => write!(f, " OK "),
+            Self::Warn => write!(f, "WARN"),
+            Self::Fail => write!(f, "FAIL"),
+            Self::Skip => write!(f, "SKIP"),
+        }
+    }
+}
+
+/// Result of one check, with a fix if the check did not pass
+#[derive(Debug)]
+struct Finding {
+    status: Status,
+    msg: String,
+    fix: Option<&'static str>,
+}
+
+impl Finding {
+    fn ok<S: Into<String>>(msg: S) -> Self {
+        Self {
+            status: Status::Ok,
+            msg: msg.into(),
+            fix: None,
+        }
+    }
+
+    fn skip<S: Into<String>>(msg: S) -> Self {
+        Self {
+            status: Status::Skip,
+            msg: msg.into(),
+            fix: None,
+        }
+    }
+
+    fn warn<S: Into<String>>(msg: S, fix: &'static str) -> Self {
+        Self {
+            status: Status::Warn,
+            msg: msg.into(),
+            fix: Some(fix),
+        }
+    }
+
+    fn fail<S: Into<String>>(msg: S, fix: &'static str) -> Self {
+        Self {
+            status: Status::Fail,
+            msg: msg.into(),
+            fix: Some(fix),
+        }
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.status, self.msg)?;
+        if let Some(fix) = self.fix {
+            write!(f, "\n       Fix: {fix}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the prerequisites of 'pvattest perform' and 'pvattest serve'.
+///
+/// Stops at the first failing check that the remaining checks depend on.
+fn check_guest(findings: &mut Vec<Finding>) {
+    if !cfg!(target_arch = "s390x") {
+        findings.push(Finding::skip(
+            "Guest checks: 'pvattest perform' is only available on s390x",
+        ));
+        return;
+    }
+    if !is_se_guest() {
+        findings.push(Finding::fail(
+            "This system is not an IBM Secure Execution guest",
+            "Run 'pvattest perform' inside an IBM Secure Execution guest",
+        ));
+        return;
+    }
+    findings.push(Finding::ok("This system is an IBM Secure Execution guest"));
+
+    if !has_uv_call_facility() {
+        findings.push(Finding::fail(
+            "The Ultravisor-call facility (158) is not available",
+            "Run the guest on an IBM z15 or newer machine with IBM Secure Execution enabled",
+        ));
+        return;
+    }
+    findings.push(Finding::ok("The Ultravisor-call facility is available"));
+
+    findings.push(check_firmware(UvQueryInfo::read().facilities()));
+
+    if !Path::new(UvDevice::PATH).exists() {
+        findings.push(Finding::fail(
+            format!("'{}' does not exist", UvDevice::PATH),
+            "Load the uvdevice kernel module with 'modprobe uvdevice'",
+        ));
+        return;
+    }
+    let uv = match UvDevice::open() {
+        Ok(uv) => uv,
+        Err(e) => {
+            debug!("Cannot open the uvdevice: {e:?}");
+            findings.push(open_failure(&e));
+            return;
+        }
+    };
+    findings.push(Finding::ok(format!("'{}' is accessible", UvDevice::PATH)));
+
+    findings.push(match uv.info() {
+        Ok(info) if info.supports::<AttestationCmd>() => {
+            Finding::ok("The uvdevice supports the attestation")
+        }
+        Ok(_) => Finding::fail(
+            "The uvdevice does not support the attestation",
+            "Update the kernel to Linux 5.19 or newer",
+        ),
+        Err(e) => Finding::fail(
+            format!("Cannot query the uvdevice: {e}"),
+            "Update the kernel to Linux 5.19 or newer",
+        ),
+    });
+}
+
+/// Checks that the Ultravisor reports the attestation facility.
+fn check_firmware(facilities: Option<&UvFlags>) -> Finding {
+    match facilities {
+        Some(fac) if fac.is_set(UV_FAC_ATTESTATION) => {
+            Finding::ok("The Ultravisor provides the attestation")
+        }
+        Some(_) => Finding::fail(
+            "The Ultravisor does not provide the attestation",
+            "Update the firmware of the machine; the attestation requires an IBM z16 or newer",
+        ),
+        None => Finding::warn(
+            format!(
+                "The Ultravisor facilities are unknown ('{}' is not available)",
+                UvQueryInfo::SYSFS_QUERY_DIR
+            ),
+            "Update the kernel to report the Ultravisor information",
+        ),
+    }
+}
+
+fn open_failure(err: &PvCoreError) -> Finding {
+    let kind = match err {
+        PvCoreError::FileAccess { source, .. } => Some(source.kind()),
+        _ => None,
+    };
+    match kind {
+        Some(ErrorKind::PermissionDenied) => Finding::fail(
+            format!("No permission to open '{}'", UvDevice::PATH),
+            "Run the command as root or grant read and write access to '/dev/uv', e.g. with a udev rule",
+        ),
+        Some(ErrorKind::NotFound) => Finding::fail(
+            format!("'{}' does not exist", UvDevice::PATH),
+            "Load the uvdevice kernel module with 'modprobe uvdevice'",
+        ),
+        _ => Finding::fail(
+            format!("Cannot open '{}': {err}", UvDevice::PATH),
+            "Check that '/dev/uv' is the uvdevice character device",
+        ),
+    }
+}
+
+/// Checks that the host-key document `hkd` is valid now and in the next days.
+fn check_hkd_validity(name: &str, hkd: &X509Ref) -> Result<Finding> {
+    const FIX: &str =
+        "Download the current host-key document of the machine from IBM Resource Link";
+    let now = Asn1Time::days_from_now(0)?;
+    let soon = Asn1Time::days_from_now(HKD_EXPIRY_WARN_DAYS)?;
+    Ok(if now.compare(hkd.not_before())? == Ordering::Less {
+        Finding::fail(format!("Host-key document '{name}' is not yet valid"), FIX)
+    } else if now.compare(hkd.not_after())? != Ordering::Less {
+        Finding::fail(
+            format!("Host-key document '{name}' expired on {}", hkd.not_after()),
+            FIX,
+        )
+    } else if soon.compare(hkd.not_after())? != Ordering::Less {
+        Finding::warn(
+            format!("Host-key document '{name}' expires on {}", hkd.not_after()),
+            FIX,
+        )
+    } else {
+        Finding::ok(format!(
+            "Host-key document '{name}' is valid until {}",
+            hkd.not_after()
+        ))
+    })
+}
+
+fn check_hkd(path: &Path) -> Result<Finding> {
+    let name = path.display().to_string();
+    match read_hkd(path) {
+        Ok(hkd) => check_hkd_validity(&name, &hkd),
+        Err(e) => Ok(Finding::fail(
+            format!("Cannot read host-key document '{name}': {e}"),
+            "Specify a readable host-key document in PEM or DER format",
+        )),
+    }
+}
+
+pub fn doctor(opt: &DoctorOpt) -> Result<ExitCode> {
+    let mut findings = vec![];
+    if !opt.no_guest {
+        check_guest(&mut findings);
+    }
+    for hkd in &opt.host_key_documents {
+        findings.push(check_hkd(hkd)?);
+    }
+
+    for finding in &findings {
+        println!("{finding}");
+    }
+    let failed = findings.iter().filter(|f| f.status == Status::Fail).count();
+    Ok(match failed {
+        0 => ExitCode::SUCCESS,
+        _ => {
+            println!("{failed} check(s) failed");
+            ExitCode::FAILURE
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use pv::test_utils::get_cert_asset_path;
+
+    use super::*;
+
+    #[test]
+    fn hkd() {
+        let valid = check_hkd(&get_cert_asset_path("host.crt")).unwrap();
+        assert_eq!(valid.status, Status::Ok);
+        assert!(valid.fix.is_none());
+
+        let expired = check_hkd(&get_cert_asset_path("host_crt_expired.crt")).unwrap();
+        assert_eq!(expired.status, Status::Fail);
+        assert!(expired.to_string().contains("expired on"));
+
+        let missing = check_hkd(Path::new("/does/not/exist")).unwrap();
+        assert_eq!(missing.status, Status::Fail);
+        assert!(missing.to_string().contains("Fix: "));
+    }
+
+    #[test]
+    fn firmware() {
+        let att = UvFlags::from(1 << (63 - UV_FAC_ATTESTATION));
+        assert_eq!(check_firmware(Some(&att)).status, Status::Ok);
+        assert_eq!(check_firmware(Some(&UvFlags::from(0))).status, Status::Fail);
+        assert_eq!(check_firmware(None).status, Status::Warn);
+    }
+}
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{path::Path, process::ExitCode};
+
+use anyhow::Result;
+use log::debug;
+use pv::{
+    misc::read_hkd,
+    request::{public_host_key_hash, Keyslot},
+};
+use utils::HexSlice;
+
+use crate::cli::HkdHashOpt;
+
+/// Read the host-key document at `hkd` and calculate the public host-key hash.
+pub fn read_phkh(hkd: &Path) -> Result<[u8; Keyslot::PHKH_SIZE as usize]> {
+    let cert = read_hkd(hkd)?;
+    Ok(public_host_key_hash(&cert)?)
+}
+
+pub fn hkd_hash(opt: &HkdHashOpt) -> Result<ExitCode> {
+    for hkd in &opt.host_key_documents {
+        let phkh = read_phkh(hkd)?;
+        debug!("PHKH of '{}': {}", hkd.display(), HexSlice::from(&phkh));
+        println!("{}  {}", HexSlice::from(&phkh), hkd.display());
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn phkh() {
+        let hkd = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/assets/host.pem.crt"
+        ));
+        let phkh = read_phkh(hkd).unwrap();
+        assert_ne!(phkh, [0; 32]);
+
+        let not_hkd = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
+        assert!(read_phkh(not_hkd).is_err());
+    }
+}
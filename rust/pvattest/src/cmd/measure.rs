@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{io::Write, process::ExitCode};
+
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use pv::{
+    attest::{
+        AttestationItems, AttestationMeasurement, AttestationRequest, ExchangeExtension,
+        ExchangeFormatRequest,
+    },
+    misc::{encode_hex, open_file, read_exact_file, read_file, try_parse_u128},
+    request::{openssl::pkey::PKey, BootHdrTags, Confidential, SymKey},
+};
+use serde::Serialize;
+use utils::get_writer_from_cli_file_arg;
+
+use crate::cli::MeasureOpt;
+
+/// Reference value as read by `pvattest verify --reference-values`
+#[derive(Serialize)]
+struct MeasurementRef<'a> {
+    name: &'a str,
+    measurement: String,
+}
+
+/// Format the measurement as hex string or, if `name` is given, as reference value list.
+fn format_measurement(meas: &AttestationMeasurement, name: Option<&str>) -> Result<String> {
+    let measurement = encode_hex(meas);
+    match name {
+        None => Ok(format!("{measurement}\n")),
+        Some(name) => Ok(serde_yaml::to_string(&[MeasurementRef {
+            name,
+            measurement,
+        }])?),
+    }
+}
+
+pub fn measure(opt: &MeasureOpt) -> Result<ExitCode> {
+    let tags = BootHdrTags::from_se_image(&mut open_file(&opt.hdr)?)?;
+    let cuid = try_parse_u128(&opt.cuid, "CUID")?;
+    let arpk = SymKey::Aes256(
+        read_exact_file(&opt.arpk, "Attestation request protection key").map(Confidential::new)?,
+    );
+    let ex_in = ExchangeFormatRequest::read(&mut open_file(&opt.input)?)?;
+
+    let user_data = match opt.user_data_args.read()? {
+        Some(user_data) => Some(user_data.as_bytes().to_vec()),
+        None => ExchangeExtension::find(&ex_in.extensions, ExchangeExtension::USER_DATA)
+            .map(|ext| ext.data().to_vec()),
+    };
+    let additional = opt
+        .additional_data
+        .as_ref()
+        .map(|path| read_file(path, "additional data"))
+        .transpose()?;
+    match (ex_in.exp_additional, &additional) {
+        (0, Some(_)) => warn!("The request asks for no additional data; ignoring the additional data"),
+        (0, _) | (_, Some(_)) => (),
+        (_, None) => bail!(
+            "The request asks for additional data, which is part of the measurement. Specify it with '--additional-data'"
+        ),
+    }
+    let additional = additional.filter(|_| ex_in.exp_additional != 0);
+
+    let (auth, conf) = AttestationRequest::decrypt_bin(&ex_in.arcb, &arpk)
+        .context("Cannot decrypt the attestation request. Is the protection key correct?")?;
+    let meas_key = PKey::hmac(conf.measurement_key())?;
+    let items = AttestationItems::new(
+        &tags,
+        &cuid,
+        user_data.as_deref(),
+        conf.nonce().as_ref().map(|v| v.value()),
+        additional.as_deref(),
+    );
+    let measurement = AttestationMeasurement::calculate(items, auth.mai(), &meas_key)?;
+
+    let mut output = get_writer_from_cli_file_arg(&opt.output)?;
+    output.write_all(format_measurement(&measurement, opt.name.as_deref())?.as_bytes())?;
+    output.flush().context("Cannot write the measurement")?;
+    info!("Measurement written to '{}'", opt.output.display());
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format() {
+        let meas = AttestationMeasurement::from(vec![0xab; 4]);
+        assert_eq!(format_measurement(&meas, None).unwrap(), "abababab\n");
+        assert_eq!(
+            format_measurement(&meas, Some("image v1.2")).unwrap(),
+            "- name: image v1.2\n  measurement: abababab\n"
+        );
+    }
+}
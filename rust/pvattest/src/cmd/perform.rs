@@ -3,52 +3,176 @@
 // Copyright IBM Corp. 2024
 
 use crate::{
-    cli::PerformAttOptComb,
-    exchange::{ExchangeFormatRequest, ExchangeFormatResponse, ExchangeFormatVersion},
+    binding::key_binding,
+    cli::{PerformAttOptComb, RemotePerformOpt, UserDataEncoding, UserDataOptions},
 };
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use log::{debug, info};
 use pv::{
-    misc::{create_file, open_file, read_file},
+    attest::{
+        AttestationFlags, AttestationMeasAlg, AttestationRequest, ExchangeExtension,
+        ExchangeFormatRequest, ExchangeFormatResponse, ExchangeFormatVersion, UserData,
+    },
+    audit::{AuditLog, AuditOp, AuditRecord},
+    misc::{create_file, open_file},
     uv::{AttestationCmd, UvDevice},
 };
-use std::process::ExitCode;
+use std::{
+    io::{stdin, stdout, Write},
+    process::ExitCode,
+};
 
-pub fn perform<'a, P>(opt: P) -> Result<ExitCode>
-where
-    P: Into<PerformAttOptComb<'a>>,
-{
-    let opt = opt.into();
-    let mut input = open_file(opt.input)?;
-    let mut output = create_file(opt.output)?;
-    let uvdevice = UvDevice::open()?;
+/// Fail early if the request asks for additional data the Ultravisor cannot provide.
+fn check_additional_data(uv: &UvDevice, arcb: &[u8]) -> Result<()> {
+    let Some(supported) = AttestationFlags::uv_supported(&uv.query_info()) else {
+        debug!("The supported attestation flags are unknown");
+        return Ok(());
+    };
+    let unsupported = AttestationRequest::auth_bin(arcb)?
+        .flags()
+        .unsupported_additional(&supported);
+    if !unsupported.is_empty() {
+        let items: Vec<_> = unsupported.iter().map(ToString::to_string).collect();
+        bail!(
+            "The Ultravisor does not support the requested additional data: {}",
+            items.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Fail early if the request uses a measurement algorithm the Ultravisor does not support.
+fn check_measurement_alg(uv: &UvDevice, arcb: &[u8]) -> Result<()> {
+    let Some(supported) = AttestationMeasAlg::uv_supported(&uv.query_info()) else {
+        debug!("The supported measurement algorithms are unknown");
+        return Ok(());
+    };
+    let mai = AttestationRequest::auth_bin(arcb)?.mai();
+    if !supported.contains(&mai) {
+        bail!("The Ultravisor does not support the measurement algorithm {mai}");
+    }
+    Ok(())
+}
+
+/// Select the user data for the attestation request `ex_in`.
+///
+/// User data from the command line takes precedence over the user data of the request. The
+/// encoding of the user data from the command line replaces the encoding of the request.
+pub fn select_user_data(
+    ex_in: &mut ExchangeFormatRequest,
+    user_data: Option<UserData>,
+    opt: &UserDataOptions,
+) -> Result<Option<UserData>> {
+    if user_data.is_some() {
+        opt.user_data_encoding.store(&mut ex_in.extensions);
+        return Ok(user_data);
+    }
+    request_user_data(ex_in)
+}
+
+/// The user data stored in the attestation request `ex_in`, if any.
+fn request_user_data(ex_in: &ExchangeFormatRequest) -> Result<Option<UserData>> {
+    ExchangeExtension::find(&ex_in.extensions, ExchangeExtension::USER_DATA)
+        .map(|ext| UserData::new(ext.data().to_vec()))
+        .transpose()
+        .context("Invalid user-data in the attestation request")
+}
+
+/// Execute the attestation request `ex_in` with the Ultravisor.
+pub fn attest(
+    uv: &UvDevice,
+    audit: &AuditLog,
+    ex_in: ExchangeFormatRequest,
+    user_data: Option<UserData>,
+) -> Result<ExchangeFormatResponse> {
+    check_additional_data(uv, &ex_in.arcb)?;
+    check_measurement_alg(uv, &ex_in.arcb)?;
 
-    let ex_in = ExchangeFormatRequest::read(&mut input)?;
-    let user_data = opt
-        .user_data
-        .map(|u| read_file(u, "user-data"))
-        .transpose()?;
+    let mut record = AuditRecord::new(AuditOp::Attestation).digest("arcb", &ex_in.arcb);
+    if let Some(user_data) = &user_data {
+        record = record.digest("user_data", user_data.as_bytes());
+    }
 
+    // Hand the request buffer over to the command to avoid copying megabyte-sized requests.
     let mut cmd = AttestationCmd::new_request(
-        ex_in.arcb.clone().into(),
+        ex_in.arcb.into_boxed_slice(),
         user_data.clone(),
         ex_in.exp_measurement,
         ex_in.exp_additional,
     )?;
 
-    uvdevice.send_cmd(&mut cmd)?;
+    audit.send_cmd(uv, &mut cmd, record)?;
 
-    let measurement = cmd.measurement();
-    let additional = cmd.additional_owned();
-    let cuid = cmd.cuid();
+    let cuid = cmd.cuid().to_owned();
+    let (arcb, measurement, additional) = cmd.into_parts();
 
-    let ex_out = ExchangeFormatResponse::new(
-        ex_in.arcb,
-        measurement.to_owned(),
+    let mut ex_out = ExchangeFormatResponse::new(
+        arcb.into_vec(),
+        measurement,
         additional,
-        user_data,
-        cuid.to_owned(),
+        user_data.map(Vec::from),
+        cuid,
     )?;
-    ex_out.write(&mut output, ExchangeFormatVersion::One)?;
+    // pass extensions of newer pvattest versions back to the requester
+    ex_out.extensions = ex_in.extensions;
+    Ok(ex_out)
+}
+
+pub fn perform<'a, P>(opt: P) -> Result<ExitCode>
+where
+    P: Into<PerformAttOptComb<'a>>,
+{
+    let opt = opt.into();
+    let mut input = open_file(opt.input)?;
+    let mut output = create_file(opt.output)?;
+    let audit = opt.audit.open()?;
+    let uvdevice = UvDevice::inherited_or_open()?;
+    opt.privileges.drop_privileges()?;
+
+    let (mut ex_in, mut version) = ExchangeFormatRequest::read_versioned(&mut input)?;
+    let user_data = match opt.bind_key {
+        Some((key, context)) => {
+            let binding = Some(key_binding(key, context)?.user_data());
+            UserDataEncoding::Raw.store(&mut ex_in.extensions);
+            binding
+        }
+        None => {
+            let user_data = opt.user_data.read()?;
+            select_user_data(&mut ex_in, user_data, opt.user_data)?
+        }
+    };
+    // answer in the exchange format version of the request, unless the encoding of the
+    // user-data must be stored
+    if version == ExchangeFormatVersion::One
+        && ExchangeExtension::find(&ex_in.extensions, ExchangeExtension::USER_DATA_ENCODING)
+            .is_some()
+    {
+        info!("Using exchange format version 2 to store the user-data encoding");
+        version = ExchangeFormatVersion::Two;
+    }
+
+    let ex_out = attest(&uvdevice, &audit, ex_in, user_data)?;
+    ex_out.write(&mut output, version)?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Run the attestation request from stdin and write the response to stdout.
+///
+/// Companion of `pvattest remote`, which connects stdin and stdout to the SSH channel.
+pub fn remote_perform(opt: &RemotePerformOpt) -> Result<ExitCode> {
+    let audit = opt.audit.open()?;
+    let uvdevice = UvDevice::inherited_or_open()?;
+    opt.privileges.drop_privileges()?;
+
+    // answer in the exchange format version of the request
+    let (ex_in, version) = ExchangeFormatRequest::read_stream(&mut stdin().lock())
+        .context("Cannot read the attestation request from stdin")?;
+    let user_data = request_user_data(&ex_in)?;
+    let ex_out = attest(&uvdevice, &audit, ex_in, user_data)?;
 
+    let mut stdout = stdout().lock();
+    ex_out.write(&mut stdout, version)?;
+    stdout.flush()?;
     Ok(ExitCode::SUCCESS)
 }
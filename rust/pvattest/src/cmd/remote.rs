@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    ffi::OsString,
+    io::{Cursor, Write},
+    process::{Command, ExitCode, Stdio},
+    thread,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::{debug, info};
+use pv::{
+    attest::{ExchangeFormatRequest, ExchangeFormatResponse},
+    misc::{read_file, write_file},
+};
+use utils::SandboxOptions;
+
+use super::verify::verify;
+use crate::cli::{RemoteOpt, VerifyOpt};
+
+/// Quote `arg` for the POSIX shell that runs the remote command.
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Arguments for `ssh` to run the companion command on the guest.
+fn ssh_args(opt: &RemoteOpt) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec!["-T".into()];
+    for o in &opt.ssh_option {
+        args.extend(["-o".into(), o.into()]);
+    }
+    args.extend([
+        "--".into(),
+        (&opt.ssh).into(),
+        format!("{} remote-perform", shell_quote(&opt.remote_pvattest)).into(),
+    ]);
+    args
+}
+
+/// Send `request` to the guest and return its attestation response.
+fn run_remote(opt: &RemoteOpt, request: &[u8]) -> Result<Vec<u8>> {
+    let args = ssh_args(opt);
+    debug!("Running ssh {args:?}");
+    let mut child = Command::new("ssh")
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .context("Cannot run 'ssh'")?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // Write from a separate thread, so that a chatty remote side cannot deadlock the pipes.
+    let (written, output) = thread::scope(|s| {
+        let writer = s.spawn(move || stdin.write_all(request));
+        let output = child.wait_with_output();
+        let written = writer
+            .join()
+            .map_err(|_| anyhow!("Cannot send the request"))?;
+        Ok::<_, anyhow::Error>((written, output?))
+    })?;
+    if !output.status.success() {
+        bail!("Attestation on '{}' failed ({})", opt.ssh, output.status);
+    }
+    written.context("Cannot send the attestation request to the guest")?;
+    Ok(output.stdout)
+}
+
+pub fn remote(opt: &RemoteOpt) -> Result<ExitCode> {
+    let request = read_file(&opt.input, "attestation request")?;
+    // reject invalid requests before connecting to the guest
+    ExchangeFormatRequest::read(&mut Cursor::new(&request))
+        .with_context(|| format!("Invalid attestation request '{}'", opt.input))?;
+
+    info!("Performing the attestation on '{}'", opt.ssh);
+    let response = run_remote(opt, &request)?;
+    ExchangeFormatResponse::read(&mut Cursor::new(&response))
+        .with_context(|| format!("Invalid attestation response from '{}'", opt.ssh))?;
+    write_file(&opt.response, &response, "attestation response")?;
+
+    verify(&VerifyOpt {
+        input: Some(opt.response.clone()),
+        input_dir: None,
+        output: opt.output.clone(),
+        policy: opt.policy.clone(),
+        evidence_log: opt.evidence_log.clone(),
+        format: opt.format,
+        user_data: opt.user_data.clone(),
+        sandbox: SandboxOptions::default(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cli::{OutputType, VerifyPolicyOptions};
+
+    #[test]
+    fn shell_quote() {
+        assert_eq!(super::shell_quote("pvattest"), "'pvattest'");
+        assert_eq!(
+            super::shell_quote("/opt/my dir/it's"),
+            r"'/opt/my dir/it'\''s'"
+        );
+    }
+
+    #[test]
+    fn ssh_args() {
+        let opt = RemoteOpt {
+            ssh: "root@guest".to_string(),
+            ssh_option: vec!["Port=2222".to_string(), "BatchMode=yes".to_string()],
+            remote_pvattest: "/usr/local/bin/pvattest".to_string(),
+            input: "arcb".to_string(),
+            response: "resp".to_string(),
+            policy: VerifyPolicyOptions {
+                hdr: Some("hdr".to_string()),
+                reference_values: None,
+                arpk: "arpk".to_string(),
+                expect_cuid: None,
+            },
+            output: None,
+            evidence_log: None,
+            format: OutputType::default(),
+            user_data: None,
+        };
+        assert_eq!(
+            super::ssh_args(&opt),
+            [
+                "-T",
+                "-o",
+                "Port=2222",
+                "-o",
+                "BatchMode=yes",
+                "--",
+                "root@guest",
+                "'/usr/local/bin/pvattest' remote-perform"
+            ]
+        );
+    }
+}
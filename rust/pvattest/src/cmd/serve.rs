@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use crate::{
+    cli::{ServeOpt, UserDataOptions},
+    cmd::perform::{attest, select_user_data},
+    limit::RateLimiter,
+    vsock::{VsockListener, VsockStream},
+};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use pv::{
+    attest::{ExchangeFormatRequest, UserData},
+    audit::AuditLog,
+    uv::{ReconnectPolicy, UvDevice},
+};
+use std::{
+    io::Write,
+    process::ExitCode,
+    thread,
+    time::{Duration, Instant},
+};
+use utils::Semaphore;
+
+/// Time a client may take to send the request or to receive the response
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn handle(
+    uv: &UvDevice,
+    audit: &AuditLog,
+    uvc_slots: &Semaphore,
+    stream: &mut VsockStream,
+    user_data: Option<UserData>,
+    opt: &UserDataOptions,
+) -> Result<()> {
+    stream.set_timeout(IO_TIMEOUT)?;
+    // answer in the exchange format version of the request
+    let (mut ex_in, version) = ExchangeFormatRequest::read_stream(stream)?;
+    let user_data = select_user_data(&mut ex_in, user_data, opt)?;
+    let ex_out = {
+        let _slot = uvc_slots.acquire();
+        attest(uv, audit, ex_in, user_data)?
+    };
+    ex_out.write(stream, version)?;
+    stream.flush()?;
+    Ok(())
+}
+
+pub fn serve(opt: &ServeOpt) -> Result<ExitCode> {
+    let audit = opt.audit.open()?;
+    let uvdevice = UvDevice::inherited_or_open()?;
+    uvdevice.set_reconnect_policy(ReconnectPolicy::Reopen);
+    let listener = VsockListener::bind(opt.port)
+        .with_context(|| format!("Cannot listen on vsock port {}", opt.port))?;
+    let metrics = opt.metrics.bind()?;
+    opt.privileges.drop_privileges()?;
+    if let Some(metrics) = metrics {
+        metrics.serve();
+    }
+
+    let user_data = opt.user_data_args.read()?;
+
+    let uvc_slots = Semaphore::new(opt.max_concurrent_uvc.get());
+    let queue = Semaphore::new(opt.queue_depth.get());
+    let rate_limit = opt.rate_limit.map(RateLimiter::new);
+
+    info!("Serving attestation requests on vsock port {}", opt.port);
+    if opt.once {
+        let mut stream = listener.accept()?;
+        let res = handle(
+            &uvdevice,
+            &audit,
+            &uvc_slots,
+            &mut stream,
+            user_data,
+            &opt.user_data_args,
+        );
+        log_result(stream.peer_cid(), &res);
+        return res.map(|_| ExitCode::SUCCESS);
+    }
+
+    thread::scope(|s| loop {
+        let mut stream = match listener.accept() {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("{e}");
+                continue;
+            }
+        };
+        let cid = stream.peer_cid();
+        // Dropping the stream closes the connection without a response.
+        if let Some(limit) = &rate_limit {
+            if !limit.check(cid, Instant::now()) {
+                warn!("Request of CID {cid} rejected: rate limit exceeded");
+                continue;
+            }
+        }
+        let Some(slot) = queue.try_acquire() else {
+            warn!("Request of CID {cid} rejected: too many pending requests");
+            continue;
+        };
+        let (uvdevice, audit, uvc_slots, user_data) =
+            (&uvdevice, &audit, &uvc_slots, user_data.clone());
+        s.spawn(move || {
+            let _slot = slot;
+            // A failing request must not terminate the proxy.
+            let res = handle(
+                uvdevice,
+                audit,
+                uvc_slots,
+                &mut stream,
+                user_data,
+                &opt.user_data_args,
+            );
+            log_result(cid, &res);
+        });
+    })
+}
+
+fn log_result(cid: u32, res: &Result<()>) {
+    match res {
+        Ok(()) => info!("Attestation for CID {cid} performed"),
+        Err(e) => warn!("Attestation for CID {cid} failed: {e:#}"),
+    }
+}
@@ -5,53 +5,180 @@
 use anyhow::Result;
 use log::{debug, warn};
 use pv::{
-    attest::{AttestationItems, AttestationMeasurement, AttestationRequest},
-    misc::{create_file, open_file, read_exact_file, write_file},
+    attest::{
+        verify_cuid, AttestationAuthenticated, AttestationItems, AttestationMeasurement,
+        AttestationRequest, BatchReport, ExchangeFormatResponse, Outcome,
+    },
+    evidence_log::{EvidenceLog, EvidenceRecord},
+    misc::{create_file, open_file, read_exact_file, try_parse_u128, write_file},
     request::{openssl::pkey::PKey, BootHdrTags, Confidential, SymKey},
+    sandbox::{FsAccess, Sandbox},
+    uv::ConfigUid,
 };
-use std::process::ExitCode;
-use utils::HexSlice;
+use std::{io::Write, path::Path, process::ExitCode};
+use utils::{get_writer_from_cli_file_arg, HexSlice, STDOUT};
 
 use crate::{
     additional::AttestationResult,
-    cli::{OutputType, VerifyOpt},
-    exchange::ExchangeFormatResponse,
+    cli::{OutputType, UserDataEncoding, VerifyOpt},
+    reference::{read_reference_values, ReferenceValue},
     EXIT_CODE_ATTESTATION_FAIL,
 };
 
+/// What an attestation response is verified against
+struct Policy {
+    tags: Option<BootHdrTags>,
+    refs: Vec<ReferenceValue>,
+    exp_cuid: Option<ConfigUid>,
+    arpk: SymKey,
+}
+
+impl Policy {
+    /// Verify the measurement and Config-UID of `exchange`.
+    fn check(
+        &self,
+        exchange: &ExchangeFormatResponse,
+    ) -> Result<(Outcome, AttestationAuthenticated)> {
+        let (auth, conf) = AttestationRequest::decrypt_bin(exchange.arcb(), &self.arpk)?;
+        let meas_key = PKey::hmac(conf.measurement_key())?;
+        let measure = |tags: &BootHdrTags| -> Result<AttestationMeasurement> {
+            let items = AttestationItems::new(
+                tags,
+                exchange.config_uid(),
+                exchange.user(),
+                conf.nonce().as_ref().map(|v| v.value()),
+                exchange.additional(),
+            );
+            Ok(AttestationMeasurement::calculate(
+                items,
+                auth.mai(),
+                &meas_key,
+            )?)
+        };
+
+        let uv_meas = exchange.measurement();
+        let mut image = None;
+        if let Some(tags) = &self.tags {
+            let measurement = measure(tags)?;
+            if !measurement.eq_secure(uv_meas) {
+                debug!("Measurement values:");
+                debug!("Recieved: {}", HexSlice::from(uv_meas));
+                debug!("Calculated: {}", HexSlice::from(measurement.as_ref()));
+                let reason = "Attestation measurement verification failed. Calculated and received attestation measurement are not equal.";
+                return Ok((Outcome::Failed(reason.to_string()), auth));
+            }
+        }
+        for refv in &self.refs {
+            if refv.matches(uv_meas, measure)? {
+                image = Some(refv.name.clone());
+                break;
+            }
+            debug!("Reference value '{}' does not match", refv.name);
+        }
+        if self.tags.is_none() && image.is_none() {
+            let reason = "Attestation measurement verification failed. The attestation measurement does not match any of the reference values.";
+            return Ok((Outcome::Failed(reason.to_string()), auth));
+        }
+        if let Some(exp_cuid) = &self.exp_cuid {
+            if let Err(e) = verify_cuid(exchange.config_uid(), exp_cuid) {
+                let reason = format!("Attestation verification failed. {e}");
+                return Ok((Outcome::Failed(reason), auth));
+            }
+        }
+        Ok((Outcome::Verified { image }, auth))
+    }
+}
+
+/// Append the verified measurement of `exchange` to the evidence log, if any.
+fn log_evidence(
+    log: Option<&EvidenceLog>,
+    exchange: &ExchangeFormatResponse,
+    image: Option<&str>,
+) -> Result<()> {
+    if let Some(log) = log {
+        let record = EvidenceRecord::new(exchange.config_uid(), exchange.measurement());
+        log.append(&match image {
+            Some(name) => record.image(name),
+            None => record,
+        })?;
+    }
+    Ok(())
+}
+
 pub fn verify(opt: &VerifyOpt) -> Result<ExitCode> {
-    let mut input = open_file(&opt.input)?;
-    let mut img = open_file(&opt.hdr)?;
-    let output = opt.output.as_ref().map(create_file).transpose()?;
+    let tags = opt
+        .policy
+        .hdr
+        .as_ref()
+        .map(|hdr| BootHdrTags::from_se_image(&mut open_file(hdr)?))
+        .transpose()?;
+    let refs = opt
+        .policy
+        .reference_values
+        .as_ref()
+        .map(read_reference_values)
+        .transpose()?
+        .unwrap_or_default();
+    let exp_cuid = opt
+        .policy
+        .expect_cuid
+        .as_deref()
+        .map(|c| try_parse_u128(c, "Expected CUID"))
+        .transpose()?;
+    let evidence_log = opt
+        .evidence_log
+        .as_ref()
+        .map(EvidenceLog::open)
+        .transpose()?;
     let arpk = SymKey::Aes256(
-        read_exact_file(&opt.arpk, "Attestation request protection key").map(Confidential::new)?,
+        read_exact_file(&opt.policy.arpk, "Attestation request protection key")
+            .map(Confidential::new)?,
     );
-    let tags = BootHdrTags::from_se_image(&mut img)?;
-    let exchange = ExchangeFormatResponse::read(&mut input)?;
+    let policy = Policy {
+        tags,
+        refs,
+        exp_cuid,
+        arpk,
+    };
 
-    let (auth, conf) = AttestationRequest::decrypt_bin(exchange.arcb(), &arpk)?;
-    let meas_key = PKey::hmac(conf.measurement_key())?;
-    let items = AttestationItems::new(
-        &tags,
-        exchange.config_uid(),
-        exchange.user(),
-        conf.nonce().as_ref().map(|v| v.value()),
-        exchange.additional(),
-    );
+    match (&opt.input, &opt.input_dir) {
+        (_, Some(dir)) => verify_dir(opt, dir, &policy, evidence_log.as_ref()),
+        (Some(input), None) => verify_file(opt, input, &policy, evidence_log.as_ref()),
+        // clap ensures that one of both is present
+        (None, None) => unreachable!(),
+    }
+}
 
-    let measurement = AttestationMeasurement::calculate(items, auth.mai(), &meas_key)?;
+fn verify_file(
+    opt: &VerifyOpt,
+    input: &str,
+    policy: &Policy,
+    evidence_log: Option<&EvidenceLog>,
+) -> Result<ExitCode> {
+    let mut input = open_file(input)?;
+    let output = opt.output.as_ref().map(create_file).transpose()?;
+    let sandbox = match &opt.user_data {
+        Some(user_data) => Sandbox::new().allow_create(user_data),
+        None => Sandbox::new(),
+    };
+    opt.sandbox.apply(sandbox)?;
+    let exchange = ExchangeFormatResponse::read(&mut input)?;
 
-    let uv_meas = exchange.measurement();
-    if !measurement.eq_secure(uv_meas) {
-        debug!("Measurement values:");
-        debug!("Recieved: {}", HexSlice::from(uv_meas));
-        debug!("Calculated: {}", HexSlice::from(measurement.as_ref()));
-        warn!("Attestation measurement verification failed. Calculated and received attestation measurement are not equal.");
-        return Ok(ExitCode::from(EXIT_CODE_ATTESTATION_FAIL));
-    }
+    let (image, auth) = match policy.check(&exchange)? {
+        (Outcome::Verified { image }, auth) => (image, auth),
+        (Outcome::Failed(reason), _) => {
+            warn!("{reason}");
+            return Ok(ExitCode::from(EXIT_CODE_ATTESTATION_FAIL));
+        }
+    };
     warn!("Attestation measurement verified");
+    if !policy.refs.is_empty() && image.is_none() {
+        warn!("The verified image does not match any of the reference values");
+    }
+    log_evidence(evidence_log, &exchange, image.as_deref())?;
     // Error impossible CUID is present Attestation verified
-    let pr_data = AttestationResult::from_exchange(&exchange, auth.flags())?;
+    let mut pr_data = AttestationResult::from_exchange(&exchange, auth.flags())?;
+    pr_data.image = image.as_deref();
 
     warn!("{pr_data}");
     if let Some(mut output) = output {
@@ -62,7 +189,10 @@ pub fn verify(opt: &VerifyOpt) -> Result<ExitCode> {
 
     if let Some(user_data) = &opt.user_data {
         match exchange.user() {
-            Some(data) => write_file(user_data, data, "user-data")?,
+            Some(data) => {
+                let encoding = UserDataEncoding::from_extensions(&exchange.extensions)?;
+                write_file(user_data, encoding.encode(data), "user-data")?
+            }
             None => {
                 warn!("Location for `user-data` specified, but respose does not contain any user-data")
             }
@@ -71,3 +201,45 @@ pub fn verify(opt: &VerifyOpt) -> Result<ExitCode> {
 
     Ok(ExitCode::SUCCESS)
 }
+
+fn verify_dir(
+    opt: &VerifyOpt,
+    dir: &Path,
+    policy: &Policy,
+    evidence_log: Option<&EvidenceLog>,
+) -> Result<ExitCode> {
+    let mut output = get_writer_from_cli_file_arg(opt.output.as_deref().unwrap_or(STDOUT))?;
+    opt.sandbox
+        .apply(Sandbox::new().allow_path(dir, FsAccess::Read))?;
+
+    let report = BatchReport::verify_dir(dir, |file, exchange| -> Result<Outcome> {
+        let (outcome, _) = policy.check(exchange)?;
+        match &outcome {
+            Outcome::Verified { image } => {
+                log_evidence(evidence_log, exchange, image.as_deref())?;
+                debug!("'{}': verified", file.display());
+            }
+            Outcome::Failed(reason) => warn!("'{}': {reason}", file.display()),
+        }
+        Ok(outcome)
+    })?;
+    for entry in &report.results {
+        if let (pv::attest::Verdict::Error, Some(reason)) = (entry.verdict, &entry.reason) {
+            warn!("'{}': {reason}", entry.file.display());
+        }
+    }
+
+    match opt.format {
+        OutputType::Yaml => serde_yaml::to_writer(&mut output, &report)?,
+    };
+    output.flush()?;
+    let summary = &report.summary;
+    warn!(
+        "{} of {} attestation responses verified, {} failed, {} not verifiable",
+        summary.verified, summary.total, summary.failed, summary.errors
+    );
+    match report.all_verified() {
+        true => Ok(ExitCode::SUCCESS),
+        false => Ok(ExitCode::from(EXIT_CODE_ATTESTATION_FAIL)),
+    }
+}
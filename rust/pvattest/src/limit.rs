@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+/// Token bucket rate limiter per client
+///
+/// Every client may send `rate` requests per minute. Unused requests accumulate up to `rate`,
+/// allowing short bursts.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    clients: Mutex<HashMap<u32, Bucket>>,
+}
+
+impl RateLimiter {
+    const PERIOD: Duration = Duration::from_secs(60);
+
+    pub fn new(rate: NonZeroU32) -> Self {
+        Self {
+            rate: rate.get().into(),
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Account a request of `client` at `now`; returns false if the client exceeded its rate.
+    pub fn check(&self, client: u32, now: Instant) -> bool {
+        let mut clients = self.clients.lock().unwrap();
+        // Clients idle for a full period have a full bucket again.
+        clients.retain(|_, b| now.saturating_duration_since(b.last) < Self::PERIOD);
+
+        let bucket = clients.entry(client).or_insert(Bucket {
+            tokens: self.rate,
+            last: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.last).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * self.rate / Self::PERIOD.as_secs_f64()).min(self.rate);
+        bucket.last = now;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rate_limit() {
+        let limit = RateLimiter::new(NonZeroU32::new(3).unwrap());
+        let start = Instant::now();
+        let at = |s| start + Duration::from_secs(s);
+
+        assert!((0..3).all(|_| limit.check(2, at(0))));
+        assert!(!limit.check(2, at(0)));
+        // other clients are not affected
+        assert!(limit.check(3, at(0)));
+        // one request every 20 seconds
+        assert!(!limit.check(2, at(19)));
+        assert!(limit.check(2, at(21)));
+        assert!(!limit.check(2, at(22)));
+        // full burst after being idle
+        assert!((0..3).all(|_| limit.check(2, at(200))));
+        assert!(!limit.check(2, at(200)));
+    }
+}
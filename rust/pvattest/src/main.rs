@@ -4,9 +4,15 @@
 
 #![allow(missing_docs)]
 mod additional;
+mod binding;
 mod cli;
 mod cmd;
-mod exchange;
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+mod limit;
+mod reference;
+mod user_data;
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+mod vsock;
 
 use clap::{CommandFactory, Parser};
 use cli::{CliOptions, Command};
@@ -14,7 +20,10 @@ use log::trace;
 use std::process::ExitCode;
 use utils::{print_cli_error, print_error, print_version, PvLogger};
 
-use crate::cmd::{check, create, perform, verify, CMD_FN, UV_CMD_FN};
+use crate::cmd::{
+    check, create, doctor, hkd_hash, measure, perform, remote, remote_perform, serve, verify,
+    CMD_FN, UV_CMD_FN,
+};
 
 static LOGGER: PvLogger = PvLogger;
 const FEATURES: &[&[&str]] = &[CMD_FN, UV_CMD_FN];
@@ -29,7 +38,7 @@ fn main() -> ExitCode {
 
     // set up logger/stderr
     let log_level = cli.verbosity.to_level_filter();
-    if let Err(e) = LOGGER.start(log_level) {
+    if let Err(e) = LOGGER.start_with_format(log_level, cli.verbosity.log_format()) {
         // should(TM) never happen
         eprintln!("Logger error: {e:?}");
         return EXIT_CODE_LOGGER_FAIL.into();
@@ -38,15 +47,31 @@ fn main() -> ExitCode {
     trace!("Trace verbosity, may leak secrets to command-line");
     trace!("Options {cli:?}");
 
+    if cli.fips {
+        if let Err(e) = pv::fips::enforce_fips() {
+            return print_error(&anyhow::Error::from(e), log_level);
+        }
+    }
+
     let res = match &cli.cmd {
         Command::Create(opt) => create(opt),
         Command::Perform(opt) => perform(opt),
+        Command::Serve(opt) => serve(opt),
         Command::Verify(opt) => verify(opt),
+        Command::Remote(opt) => remote(opt),
+        Command::RemotePerform(opt) => remote_perform(opt),
+        Command::Measure(opt) => measure(opt),
         Command::Version => {
             print_version!("2024", log_level; FEATURES.concat());
             Ok(ExitCode::SUCCESS)
         }
         Command::Check(opt) => check(opt),
+        Command::HkdHash(opt) => hkd_hash(opt),
+        Command::Doctor(opt) => doctor(opt),
+        Command::Generate(opt) => opt
+            .run(CliOptions::command(), "Attestation Manual")
+            .map(|_| ExitCode::SUCCESS)
+            .map_err(Into::into),
     };
     match res {
         Ok(c) => c,
@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use pv::{
+    attest::AttestationMeasurement,
+    misc::{decode_hex, eq_ct, open_file},
+    request::BootHdrTags,
+};
+use serde::Deserialize;
+use zerocopy::FromBytes;
+
+/// Measurement inputs of an SE header.
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RefTags {
+    pld: String,
+    ald: String,
+    tld: String,
+    tag: String,
+}
+
+/// What the attestation measurement is compared against.
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Reference {
+    /// IBM Secure Execution image or header.
+    Hdr(PathBuf),
+    /// Measurement inputs of the SE header.
+    Tags(RefTags),
+    /// Precomputed attestation measurement (hex).
+    Measurement(String),
+}
+
+/// A known-good image an attested guest can correspond to.
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ReferenceValue {
+    /// Name of the image, e.g. its build version.
+    pub name: String,
+    #[serde(flatten)]
+    pub reference: Reference,
+}
+
+impl RefTags {
+    fn to_tags(&self) -> Result<BootHdrTags> {
+        let bytes = [&self.pld, &self.ald, &self.tld, &self.tag]
+            .iter()
+            .map(decode_hex)
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+        BootHdrTags::read_from_bytes(&bytes)
+            .map_err(|_| anyhow!("Invalid size of the SE-header tags"))
+    }
+}
+
+impl ReferenceValue {
+    /// Tests whether this reference value matches the measurement calculated by the UV.
+    ///
+    /// `measure` calculates the attestation measurement for the given SE-header tags.
+    pub fn matches<F>(&self, uv_meas: &[u8], measure: F) -> Result<bool>
+    where
+        F: Fn(&BootHdrTags) -> Result<AttestationMeasurement>,
+    {
+        let tags = match &self.reference {
+            Reference::Hdr(hdr) => BootHdrTags::from_se_image(&mut open_file(hdr)?)?,
+            Reference::Tags(tags) => tags.to_tags()?,
+            Reference::Measurement(meas) => {
+                let meas = decode_hex(meas)?;
+                return Ok(eq_ct(&meas, uv_meas));
+            }
+        };
+        Ok(measure(&tags)?.eq_secure(uv_meas))
+    }
+}
+
+/// Reads a YAML list of reference values.
+pub fn read_reference_values<P: AsRef<Path>>(path: P) -> Result<Vec<ReferenceValue>> {
+    let path = path.as_ref();
+    let refs: Vec<ReferenceValue> = serde_yaml::from_reader(open_file(path)?)
+        .with_context(|| format!("Invalid reference-values file '{}'", path.display()))?;
+    if refs.is_empty() {
+        bail!("No reference values in '{}'", path.display());
+    }
+    Ok(refs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deserialize() {
+        let yaml = r#"
+- name: guest-1.0
+  hdr: /var/lib/guest-1.0.img
+- name: guest-1.1
+  tags:
+    pld: "00"
+    ald: "11"
+    tld: "22"
+    tag: "33"
+- name: guest-1.2
+  measurement: "abcd"
+"#;
+        let refs: Vec<ReferenceValue> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            refs,
+            [
+                ReferenceValue {
+                    name: "guest-1.0".to_string(),
+                    reference: Reference::Hdr("/var/lib/guest-1.0.img".into()),
+                },
+                ReferenceValue {
+                    name: "guest-1.1".to_string(),
+                    reference: Reference::Tags(RefTags {
+                        pld: "00".to_string(),
+                        ald: "11".to_string(),
+                        tld: "22".to_string(),
+                        tag: "33".to_string(),
+                    }),
+                },
+                ReferenceValue {
+                    name: "guest-1.2".to_string(),
+                    reference: Reference::Measurement("abcd".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tags() {
+        let tags = RefTags {
+            pld: "01".repeat(64),
+            ald: "02".repeat(64),
+            tld: "03".repeat(64),
+            tag: "04".repeat(16),
+        };
+        assert_eq!(
+            tags.to_tags().unwrap(),
+            BootHdrTags::new([1; 64], [2; 64], [3; 64], [4; 16])
+        );
+
+        let short = RefTags {
+            tag: "04".repeat(15),
+            ..tags
+        };
+        assert!(short.to_tags().is_err());
+    }
+
+    #[test]
+    fn measurement() {
+        let refv = ReferenceValue {
+            name: "guest".to_string(),
+            reference: Reference::Measurement("abcd".to_string()),
+        };
+        let no_calc = |_: &BootHdrTags| -> Result<AttestationMeasurement> { unreachable!() };
+        assert!(refv.matches(&[0xab, 0xcd], no_calc).unwrap());
+        assert!(!refv.matches(&[0xab, 0xce], no_calc).unwrap());
+        assert!(!refv.matches(&[0xab], no_calc).unwrap());
+    }
+}
@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::io::Read;
+
+use anyhow::{bail, Context, Result};
+use pv::{
+    attest::{ExchangeExtension, UserData},
+    misc::{decode_base64, decode_hex, encode_base64, encode_hex, read_file},
+};
+
+use crate::cli::{UserDataEncoding, UserDataOptions};
+
+impl UserDataEncoding {
+    /// Value of the [`ExchangeExtension::USER_DATA_ENCODING`] extension
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Raw => 0,
+            Self::Hex => 1,
+            Self::Base64 => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Raw),
+            1 => Some(Self::Hex),
+            2 => Some(Self::Base64),
+            _ => None,
+        }
+    }
+
+    /// Decode `buf` and check that the result fits into the attestation request.
+    ///
+    /// Surrounding whitespace of hex and base64 encoded data is ignored.
+    pub fn decode(self, buf: &[u8]) -> Result<UserData> {
+        let text = || {
+            std::str::from_utf8(buf)
+                .map(str::trim)
+                .context("The user-data is not valid UTF-8")
+        };
+        let data = match self {
+            Self::Raw => buf.to_vec(),
+            Self::Hex => decode_hex(text()?)?,
+            Self::Base64 => decode_base64(text()?)?,
+        };
+        Ok(UserData::new(data)?)
+    }
+
+    /// Encode `data` in this encoding.
+    pub fn encode(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Raw => data.to_vec(),
+            Self::Hex => encode_hex(data).into_bytes(),
+            Self::Base64 => encode_base64(data).into_bytes(),
+        }
+    }
+
+    /// The extension that stores this encoding.
+    pub fn extension(self) -> ExchangeExtension {
+        ExchangeExtension::new(ExchangeExtension::USER_DATA_ENCODING, vec![self.to_byte()])
+    }
+
+    /// Find the encoding in `extensions`.
+    ///
+    /// Defaults to [`UserDataEncoding::Raw`] if `extensions` do not specify an encoding.
+    pub fn from_extensions(extensions: &[ExchangeExtension]) -> Result<Self> {
+        let Some(ext) = ExchangeExtension::find(extensions, ExchangeExtension::USER_DATA_ENCODING)
+        else {
+            return Ok(Self::Raw);
+        };
+        match ext.data() {
+            [byte] => match Self::from_byte(*byte) {
+                Some(enc) => Ok(enc),
+                None => bail!("Unknown user-data encoding {byte}"),
+            },
+            _ => bail!("Invalid user-data encoding extension"),
+        }
+    }
+
+    /// Replace the encoding in `extensions` with this encoding.
+    ///
+    /// Raw data needs no extension.
+    pub fn store(self, extensions: &mut Vec<ExchangeExtension>) {
+        extensions.retain(|ext| ext.tag() != ExchangeExtension::USER_DATA_ENCODING);
+        if self != Self::Raw {
+            extensions.push(self.extension());
+        }
+    }
+}
+
+impl UserDataOptions {
+    /// Read and decode the user data, if specified.
+    ///
+    /// The user data is read from the value, a file, or stdin if the file is '-'.
+    pub fn read(&self) -> Result<Option<UserData>> {
+        let buf = match (&self.user_data_value, &self.user_data) {
+            (Some(value), _) => value.as_bytes().to_vec(),
+            (None, Some(path)) if path == "-" => {
+                let mut buf = vec![];
+                std::io::stdin()
+                    .take(UserData::MAX_SIZE as u64 * 4 + 1)
+                    .read_to_end(&mut buf)
+                    .context("Cannot read the user-data from stdin")?;
+                buf
+            }
+            (None, Some(path)) => read_file(path, "user-data")?,
+            (None, None) => return Ok(None),
+        };
+        let src = self.user_data.as_deref().unwrap_or("the command line");
+        self.user_data_encoding
+            .decode(&buf)
+            .with_context(|| format!("Invalid user-data in '{src}'"))
+            .map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let exp = UserData::try_from("user").unwrap();
+        assert_eq!(UserDataEncoding::Raw.decode(b"user").unwrap(), exp);
+        assert_eq!(UserDataEncoding::Hex.decode(b"75736572\n").unwrap(), exp);
+        assert_eq!(UserDataEncoding::Base64.decode(b" dXNlcg==").unwrap(), exp);
+        assert!(UserDataEncoding::Hex.decode(b"7573657").is_err());
+        assert!(UserDataEncoding::Base64.decode(b"\xff").is_err());
+
+        // the limit applies to the decoded data
+        let hex = "00".repeat(UserData::MAX_SIZE);
+        assert!(UserDataEncoding::Raw.decode(hex.as_bytes()).is_err());
+        assert!(UserDataEncoding::Hex.decode(hex.as_bytes()).is_ok());
+        assert!(UserDataEncoding::Hex
+            .decode(format!("{hex}00").as_bytes())
+            .is_err());
+
+        for enc in [
+            UserDataEncoding::Raw,
+            UserDataEncoding::Hex,
+            UserDataEncoding::Base64,
+        ] {
+            assert_eq!(enc.decode(&enc.encode(b"user")).unwrap(), exp);
+        }
+    }
+
+    #[test]
+    fn extensions() {
+        let mut exts = vec![];
+        assert_eq!(
+            UserDataEncoding::from_extensions(&exts).unwrap(),
+            UserDataEncoding::Raw
+        );
+        UserDataEncoding::Base64.store(&mut exts);
+        UserDataEncoding::Hex.store(&mut exts);
+        assert_eq!(exts.len(), 1);
+        assert_eq!(
+            UserDataEncoding::from_extensions(&exts).unwrap(),
+            UserDataEncoding::Hex
+        );
+        UserDataEncoding::Raw.store(&mut exts);
+        assert!(exts.is_empty());
+
+        let inv = [ExchangeExtension::new(
+            ExchangeExtension::USER_DATA_ENCODING,
+            vec![7],
+        )];
+        assert!(UserDataEncoding::from_extensions(&inv).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fs::File,
+    io::{Error, Read, Result, Write},
+    mem::size_of,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    time::Duration,
+};
+
+/// Listen backlog of the vsock socket. Connections beyond the queue depth are rejected anyway.
+const BACKLOG: libc::c_int = 8;
+
+fn sockaddr(cid: u32, port: u32) -> libc::sockaddr_vm {
+    libc::sockaddr_vm {
+        svm_family: libc::AF_VSOCK as libc::sa_family_t,
+        svm_reserved1: 0,
+        svm_port: port,
+        svm_cid: cid,
+        svm_zero: [0; 4],
+    }
+}
+
+fn check(rc: libc::c_int) -> Result<libc::c_int> {
+    match rc {
+        rc if rc < 0 => Err(Error::last_os_error()),
+        rc => Ok(rc),
+    }
+}
+
+/// A vsock stream socket accepting connections
+#[derive(Debug)]
+pub struct VsockListener(OwnedFd);
+
+impl VsockListener {
+    /// Listen on `port` for connections from any context ID, e.g. the hypervisor.
+    pub fn bind(port: u32) -> Result<Self> {
+        // SAFETY: no pointers involved
+        let fd = check(unsafe {
+            libc::socket(libc::AF_VSOCK, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0)
+        })?;
+        // SAFETY: fd is a freshly created, owned file descriptor
+        let sock = Self(unsafe { OwnedFd::from_raw_fd(fd) });
+
+        let addr = sockaddr(libc::VMADDR_CID_ANY, port);
+        // SAFETY: addr is a valid sockaddr_vm of the advertised size
+        check(unsafe {
+            libc::bind(
+                sock.0.as_raw_fd(),
+                &addr as *const libc::sockaddr_vm as *const libc::sockaddr,
+                size_of::<libc::sockaddr_vm>() as libc::socklen_t,
+            )
+        })?;
+        // SAFETY: no pointers involved
+        check(unsafe { libc::listen(sock.0.as_raw_fd(), BACKLOG) })?;
+        Ok(sock)
+    }
+
+    /// Wait for the next connection.
+    pub fn accept(&self) -> Result<VsockStream> {
+        let mut addr = sockaddr(0, 0);
+        let mut len = size_of::<libc::sockaddr_vm>() as libc::socklen_t;
+        // SAFETY: addr and len point to valid memory of the advertised size
+        let fd = check(unsafe {
+            libc::accept4(
+                self.0.as_raw_fd(),
+                &mut addr as *mut libc::sockaddr_vm as *mut libc::sockaddr,
+                &mut len,
+                libc::SOCK_CLOEXEC,
+            )
+        })?;
+        Ok(VsockStream {
+            // SAFETY: fd is a freshly accepted, owned file descriptor
+            sock: File::from(unsafe { OwnedFd::from_raw_fd(fd) }),
+            peer_cid: addr.svm_cid,
+        })
+    }
+}
+
+/// A connected vsock stream socket
+#[derive(Debug)]
+pub struct VsockStream {
+    sock: File,
+    peer_cid: u32,
+}
+
+impl VsockStream {
+    /// Context ID of the connected peer; 2 is the hypervisor.
+    pub fn peer_cid(&self) -> u32 {
+        self.peer_cid
+    }
+
+    /// Fail reads and writes that block longer than `timeout`.
+    pub fn set_timeout(&self, timeout: Duration) -> Result<()> {
+        let tv = libc::timeval {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+        };
+        for opt in [libc::SO_RCVTIMEO, libc::SO_SNDTIMEO] {
+            // SAFETY: tv is a valid timeval of the advertised size
+            check(unsafe {
+                libc::setsockopt(
+                    self.sock.as_raw_fd(),
+                    libc::SOL_SOCKET,
+                    opt,
+                    &tv as *const libc::timeval as *const libc::c_void,
+                    size_of::<libc::timeval>() as libc::socklen_t,
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl Read for VsockStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.sock.read(buf)
+    }
+}
+
+impl Write for VsockStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.sock.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.sock.flush()
+    }
+}
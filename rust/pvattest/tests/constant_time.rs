@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+//! Guards the verification paths against comparisons that return at the first difference.
+
+#[path = "../../pv/tests/ct_lint/mod.rs"]
+mod ct_lint;
+
+/// Source files and directories of the verification
+const VERIFICATION_SOURCES: &[&str] = &[
+    "src/reference.rs",
+    "src/cmd/verify.rs",
+    "src/cmd/check.rs",
+    "src/cmd/check",
+];
+
+#[test]
+fn no_early_exit_comparisons() {
+    let found = ct_lint::early_exit_cmps(env!("CARGO_MANIFEST_DIR"), VERIFICATION_SOURCES);
+    assert!(
+        found.is_empty(),
+        "Use eq_ct for these comparisons:\n{}",
+        found.join("\n")
+    );
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::path::PathBuf;
+
+use clap::{Parser, ValueHint};
+use utils::{SandboxOptions, VerbosityOptions};
+
+/// Decrypt an IBM Secure Execution guest dump.
+///
+/// Converts the encrypted dump of a Secure Execution guest, as created by 'virsh dump
+/// --memory-only', into a plain ELF core file that can be analyzed with crash or gdb. The dump is
+/// decrypted with the customer communication key (CCK) of the Secure Execution image of the
+/// guest.
+#[derive(Parser, Debug)]
+pub struct CliOptions {
+    #[clap(flatten)]
+    pub verbosity: VerbosityOptions,
+
+    /// Use the content of FILE as the customer communication key (CCK).
+    ///
+    /// The file must contain exactly 32 bytes. This is the key that was used to create the
+    /// Secure Execution image of the guest.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,
+          required_unless_present("version"))]
+    pub key: Option<PathBuf>,
+
+    /// The encrypted Secure Execution guest dump.
+    #[arg(value_name = "DUMP", value_hint = ValueHint::FilePath,
+          required_unless_present("version"))]
+    pub input: Option<PathBuf>,
+
+    /// Write the decrypted ELF core file to OUTPUT.
+    #[arg(value_name = "OUTPUT", value_hint = ValueHint::FilePath,
+          required_unless_present("version"))]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite an existing OUTPUT file.
+    #[arg(long)]
+    pub overwrite: bool,
+
+    #[command(flatten)]
+    pub sandbox: SandboxOptions,
+
+    /// Print version information and exit.
+    #[arg(long)]
+    pub version: bool,
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        super::CliOptions::command().debug_assert()
+    }
+}
@@ -0,0 +1,382 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use anyhow::{bail, Context, Result};
+use log::{debug, warn};
+use pv::{
+    dump::{
+        CpuState, DumpCompletion, COMPL_SECTION, MEM_META_SECTION, NT_S390_PV_CPU_DATA,
+        TWEAK_COMPONENT_SIZE,
+    },
+    request::Confidential,
+};
+use zerocopy::IntoBytes;
+
+use crate::elf::{push_note, write_core_headers, ElfCore, Load, PAGE_SIZE, PT_LOAD, PT_NOTE};
+
+const NT_PRSTATUS: u32 = 1;
+const NT_FPREGSET: u32 = 2;
+const NT_PRPSINFO: u32 = 3;
+const NT_S390_TIMER: u32 = 0x301;
+const NT_S390_TODCMP: u32 = 0x302;
+const NT_S390_TODPREG: u32 = 0x303;
+const NT_S390_CTRS: u32 = 0x304;
+const NT_S390_PREFIX: u32 = 0x305;
+const NT_S390_VXRS_LOW: u32 = 0x309;
+const NT_S390_VXRS_HIGH: u32 = 0x30a;
+const NT_S390_GS_CB: u32 = 0x30b;
+
+const NOTE_NAME_CORE: &str = "CORE";
+const NOTE_NAME_LINUX: &str = "LINUX";
+
+fn be_bytes<const N: usize, T: Copy>(v: &[T; N], f: impl Fn(T) -> Vec<u8>) -> Vec<u8> {
+    v.iter().flat_map(|v| f(*v)).collect()
+}
+
+/// Appends the Linux core notes of one CPU to `notes`.
+fn push_cpu_notes(notes: &mut Vec<u8>, nr: u32, cpu: &CpuState) {
+    let mut prstatus = vec![0; 32];
+    prstatus.extend_from_slice(&nr.to_be_bytes());
+    prstatus.resize(112, 0);
+    prstatus.extend(be_bytes(&cpu.psw, |v| v.to_be_bytes().to_vec()));
+    prstatus.extend(be_bytes(&cpu.gprs, |v| v.to_be_bytes().to_vec()));
+    prstatus.extend(be_bytes(&cpu.acrs, |v| v.to_be_bytes().to_vec()));
+    // orig_gpr2, pr_fpvalid, padding
+    prstatus.resize(336, 0);
+    push_note(notes, NT_PRSTATUS, NOTE_NAME_CORE, &prstatus);
+
+    let mut fpregset = cpu.fpc.to_be_bytes().to_vec();
+    fpregset.resize(8, 0);
+    fpregset.extend(be_bytes(&cpu.fprs, |v| v.to_be_bytes().to_vec()));
+    push_note(notes, NT_FPREGSET, NOTE_NAME_CORE, &fpregset);
+
+    push_note(
+        notes,
+        NT_S390_TIMER,
+        NOTE_NAME_LINUX,
+        &cpu.timer.to_be_bytes(),
+    );
+    push_note(
+        notes,
+        NT_S390_TODCMP,
+        NOTE_NAME_LINUX,
+        &cpu.todcmp.to_be_bytes(),
+    );
+    push_note(
+        notes,
+        NT_S390_TODPREG,
+        NOTE_NAME_LINUX,
+        &cpu.todpreg.to_be_bytes(),
+    );
+    push_note(
+        notes,
+        NT_S390_CTRS,
+        NOTE_NAME_LINUX,
+        &be_bytes(&cpu.ctrs, |v| v.to_be_bytes().to_vec()),
+    );
+    push_note(
+        notes,
+        NT_S390_PREFIX,
+        NOTE_NAME_LINUX,
+        &cpu.prefix.to_be_bytes(),
+    );
+    push_note(
+        notes,
+        NT_S390_VXRS_LOW,
+        NOTE_NAME_LINUX,
+        &be_bytes(&cpu.vxrs_low, |v| v.to_be_bytes().to_vec()),
+    );
+    push_note(
+        notes,
+        NT_S390_VXRS_HIGH,
+        NOTE_NAME_LINUX,
+        cpu.vxrs_high.as_bytes(),
+    );
+    let gs_cb = be_bytes(&[0, cpu.gsd, cpu.gssm, cpu.gs_epl_a], |v: u64| {
+        v.to_be_bytes().to_vec()
+    });
+    push_note(notes, NT_S390_GS_CB, NOTE_NAME_LINUX, &gs_cb);
+}
+
+/// Returns the process information note of the kernel.
+fn prpsinfo() -> Vec<u8> {
+    let mut prpsinfo = vec![0; 136];
+    // pr_sname
+    prpsinfo[1] = b'R';
+    // pr_fname
+    prpsinfo[40..47].copy_from_slice(b"vmlinux");
+    prpsinfo
+}
+
+/// Summary of a decrypted dump
+#[derive(Debug, PartialEq, Eq)]
+pub struct DumpSummary {
+    pub cpus: usize,
+    pub pages: u64,
+}
+
+/// Decrypts the Secure Execution guest dump `input` with the customer communication key `cck`
+/// and writes an ELF core file to `output`.
+pub fn decrypt_dump<R, W>(
+    input: &mut R,
+    cck: &Confidential<[u8; 32]>,
+    output: &mut W,
+) -> Result<DumpSummary>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+{
+    let elf = ElfCore::read(input)?;
+    let compl = elf.read_section(input, COMPL_SECTION)?;
+    let compl = DumpCompletion::decrypt(&compl, cck)
+        .context("Cannot decrypt the dump. Is the customer communication key correct?")?;
+    let (tweaks_off, tweaks_size) = elf
+        .section(MEM_META_SECTION)
+        .with_context(|| format!("The dump has no '{MEM_META_SECTION}' section"))?;
+
+    let mut notes = vec![];
+    push_note(&mut notes, NT_PRPSINFO, NOTE_NAME_CORE, &prpsinfo());
+    let mut cpus = 0;
+    // Guests with memory holes have one segment per contiguous memory block.
+    let mut loads = vec![];
+    for phdr in elf.phdrs() {
+        match phdr.ty.get() {
+            PT_NOTE => {
+                for (ty, desc) in ElfCore::read_notes(input, phdr)? {
+                    if ty != NT_S390_PV_CPU_DATA {
+                        debug!("Ignoring ELF note {ty:#x}");
+                        continue;
+                    }
+                    let cpu = compl
+                        .decrypt_cpu(&desc)
+                        .context("Cannot decrypt the state of a CPU")?;
+                    if cpu.has_osii() {
+                        warn!("CPU state may contain partial instruction results");
+                    }
+                    cpus += 1;
+                    push_cpu_notes(&mut notes, cpus as u32, &cpu);
+                }
+            }
+            PT_LOAD => loads.push(*phdr),
+            ty => warn!("Unknown ELF program header type {ty:#x}"),
+        }
+    }
+    if loads.is_empty() {
+        bail!("The dump contains no memory");
+    }
+    if loads.iter().any(|l| l.filesz.get() % PAGE_SIZE != 0) {
+        bail!("The memory of the dump is corrupted");
+    }
+    // The tweak components of all segments are stored consecutively, in segment order.
+    let pages = loads
+        .iter()
+        .map(|l| l.filesz.get() / PAGE_SIZE)
+        .sum::<u64>();
+    if pages * TWEAK_COMPONENT_SIZE as u64 > tweaks_size {
+        bail!("The memory of the dump is corrupted");
+    }
+    debug!(
+        "Decrypting {cpus} CPU states and {pages} memory pages in {} segments",
+        loads.len()
+    );
+
+    let out_loads: Vec<_> = loads
+        .iter()
+        .map(|l| Load {
+            addr: l.paddr.get(),
+            size: l.filesz.get(),
+        })
+        .collect();
+    let offsets = write_core_headers(output, &notes, &out_loads)?;
+
+    let mut component = [0; TWEAK_COMPONENT_SIZE];
+    let mut page = vec![0; PAGE_SIZE as usize];
+    let mut plain = Confidential::new(vec![0; PAGE_SIZE as usize]);
+    let mut idx = 0;
+    for (load, out_off) in loads.iter().zip(offsets) {
+        output.seek(SeekFrom::Start(out_off))?;
+        for nr in 0..load.filesz.get() / PAGE_SIZE {
+            input.seek(SeekFrom::Start(
+                tweaks_off + idx * TWEAK_COMPONENT_SIZE as u64,
+            ))?;
+            input.read_exact(&mut component)?;
+            input.seek(SeekFrom::Start(load.offset.get() + nr * PAGE_SIZE))?;
+            input
+                .read_exact(&mut page)
+                .context("Cannot read the memory of the dump")?;
+            compl
+                .decrypt_page(&component, &page, plain.value_mut())
+                .with_context(|| {
+                    format!(
+                        "Cannot decrypt the memory page at {:#x}",
+                        load.paddr.get() + nr * PAGE_SIZE
+                    )
+                })?;
+            output.write_all(plain.value())?;
+            idx += 1;
+        }
+    }
+    Ok(DumpSummary { cpus, pages })
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use pv::dump::test_data;
+
+    use super::*;
+
+    const NONCE: [u8; 16] = [0x0f; 16];
+
+    fn xts_key() -> [u8; 64] {
+        core::array::from_fn(|i| i as u8)
+    }
+
+    fn cck() -> Confidential<[u8; 32]> {
+        Confidential::new([0x42; 32])
+    }
+
+    /// Creates an encrypted dump with `cpus` CPUs and three pages in two segments: encrypted and
+    /// zero at 0, and shared at 1 MiB.
+    fn encrypted_dump(cpus: u64) -> Vec<u8> {
+        let mut notes = vec![];
+        for cpu in 0..cpus {
+            push_note(
+                &mut notes,
+                NT_S390_PV_CPU_DATA,
+                NOTE_NAME_CORE,
+                &test_data::cpu(&cck(), [cpu; 16]),
+            );
+        }
+        // Ignored by pvdump
+        push_note(&mut notes, NT_PRSTATUS, NOTE_NAME_CORE, &[0; 336]);
+
+        let mut zero = [0; 16];
+        zero[..4].copy_from_slice(&[0xff; 4]);
+        let mut shared = zero;
+        zero[15] = 0x05;
+        shared[15] = 0x06;
+        let encr = [0x10; 16];
+        let tweaks = [encr, zero, shared].concat();
+        let mem = [
+            test_data::encrypt_page(&xts_key(), &NONCE, &encr, &[0xaa; 0x1000]),
+            vec![0xff; 0x1000],
+            vec![0x55; 0x1000],
+        ]
+        .concat();
+
+        let compl = test_data::completion(&cck(), NONCE, xts_key());
+        let shstrtab = format!("\0{COMPL_SECTION}\0{MEM_META_SECTION}\0.shstrtab\0");
+        let mut out = Cursor::new(vec![]);
+        let offsets = write_core_headers(
+            &mut out,
+            &notes,
+            &[
+                Load {
+                    addr: 0,
+                    size: 0x2000,
+                },
+                Load {
+                    addr: 0x100000,
+                    size: 0x1000,
+                },
+            ],
+        )
+        .unwrap();
+        let mut dump = out.into_inner();
+        dump.resize(offsets[0] as usize, 0);
+        dump.extend(mem);
+
+        let mut section = |name: u32, data: &[u8]| {
+            let off = dump.len() as u64;
+            dump.extend_from_slice(data);
+            let mut shdr = [0u8; 64];
+            shdr[..4].copy_from_slice(&name.to_be_bytes());
+            shdr[24..32].copy_from_slice(&off.to_be_bytes());
+            shdr[32..40].copy_from_slice(&(data.len() as u64).to_be_bytes());
+            shdr
+        };
+        let shdrs = [
+            [0; 64],
+            section(1, &compl),
+            section(10, &tweaks),
+            section(22, shstrtab.as_bytes()),
+        ]
+        .concat();
+        let shoff = dump.len() as u64;
+        dump.extend(shdrs);
+        // e_shoff, e_shentsize, e_shnum, e_shstrndx
+        dump[40..48].copy_from_slice(&shoff.to_be_bytes());
+        dump[58..60].copy_from_slice(&64u16.to_be_bytes());
+        dump[60..62].copy_from_slice(&4u16.to_be_bytes());
+        dump[62..64].copy_from_slice(&3u16.to_be_bytes());
+        dump
+    }
+
+    #[test]
+    fn decrypt() {
+        let mut out = Cursor::new(vec![]);
+        let summary = decrypt_dump(&mut Cursor::new(encrypted_dump(2)), &cck(), &mut out).unwrap();
+        assert_eq!(summary, DumpSummary { cpus: 2, pages: 3 });
+
+        let mut out = Cursor::new(out.into_inner());
+        let core = ElfCore::read(&mut out).unwrap();
+        assert_eq!(core.phdrs().len(), 3);
+        let notes = ElfCore::read_notes(&mut out, &core.phdrs()[0]).unwrap();
+        let types: Vec<_> = notes.iter().map(|(ty, _)| *ty).collect();
+        let cpu = [1, 2, 0x301, 0x302, 0x303, 0x304, 0x305, 0x309, 0x30a, 0x30b];
+        assert_eq!(types, [&[3][..], &cpu, &cpu].concat());
+        // gpr 0 of the second CPU
+        assert_eq!(notes[11].1[32..36], 2u32.to_be_bytes());
+        assert_eq!(notes[11].1[128..136], 1u64.to_be_bytes());
+        // fpr 1 of the first CPU
+        assert_eq!(notes[2].1[16..24], 0x1111u64.to_be_bytes());
+
+        let load = &core.phdrs()[1];
+        assert_eq!((load.paddr.get(), load.filesz.get()), (0, 0x2000));
+        let mem = &out.get_ref()[load.offset.get() as usize..];
+        assert_eq!(mem[..0x1000], [0xaa; 0x1000]);
+        assert_eq!(mem[0x1000..0x2000], [0; 0x1000]);
+        let load = &core.phdrs()[2];
+        assert_eq!((load.paddr.get(), load.filesz.get()), (0x100000, 0x1000));
+        let mem = &out.get_ref()[load.offset.get() as usize..];
+        assert_eq!(mem[..0x1000], [0x55; 0x1000]);
+    }
+
+    #[test]
+    fn many_cpus() {
+        let mut out = Cursor::new(vec![]);
+        let summary =
+            decrypt_dump(&mut Cursor::new(encrypted_dump(248)), &cck(), &mut out).unwrap();
+        assert_eq!(
+            summary,
+            DumpSummary {
+                cpus: 248,
+                pages: 3
+            }
+        );
+    }
+
+    #[test]
+    fn wrong_key() {
+        let err = decrypt_dump(
+            &mut Cursor::new(encrypted_dump(2)),
+            &Confidential::new([0; 32]),
+            &mut Cursor::new(vec![]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("customer communication key"));
+    }
+
+    #[test]
+    fn no_se_dump() {
+        let mut out = Cursor::new(vec![]);
+        write_core_headers(&mut out, &[], &[]).unwrap();
+        let err = decrypt_dump(&mut out, &cck(), &mut Cursor::new(vec![])).unwrap_err();
+        assert!(err.to_string().contains("no Secure Execution guest dump"));
+    }
+}
@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Minimal support for s390x ELF core files.
+
+use std::{
+    io::{Read, Seek, SeekFrom, Write},
+    mem::size_of,
+};
+
+use anyhow::{bail, Context, Result};
+use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, KnownLayout, U16, U32, U64};
+
+const ELFMAG: [u8; 4] = *b"\x7fELF";
+const ELFCLASS64: u8 = 2;
+const ELFDATA2MSB: u8 = 2;
+const EV_CURRENT: u8 = 1;
+const ET_CORE: u16 = 4;
+const EM_S390: u16 = 22;
+
+pub const PT_LOAD: u32 = 1;
+pub const PT_NOTE: u32 = 4;
+const PF_RWX: u32 = 0x7;
+
+pub const PAGE_SIZE: u64 = 0x1000;
+
+type Be16 = U16<BigEndian>;
+type Be32 = U32<BigEndian>;
+type Be64 = U64<BigEndian>;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+pub struct Ehdr {
+    ident: [u8; 16],
+    ty: Be16,
+    machine: Be16,
+    version: Be32,
+    entry: Be64,
+    phoff: Be64,
+    shoff: Be64,
+    flags: Be32,
+    ehsize: Be16,
+    phentsize: Be16,
+    phnum: Be16,
+    shentsize: Be16,
+    shnum: Be16,
+    shstrndx: Be16,
+}
+pv::assert_size!(Ehdr, 64);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+pub struct Phdr {
+    pub ty: Be32,
+    pub flags: Be32,
+    pub offset: Be64,
+    pub vaddr: Be64,
+    pub paddr: Be64,
+    pub filesz: Be64,
+    pub memsz: Be64,
+    pub align: Be64,
+}
+pv::assert_size!(Phdr, 56);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+pub struct Shdr {
+    name: Be32,
+    ty: Be32,
+    flags: Be64,
+    addr: Be64,
+    offset: Be64,
+    size: Be64,
+    link: Be32,
+    info: Be32,
+    addralign: Be64,
+    entsize: Be64,
+}
+pv::assert_size!(Shdr, 64);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+struct Nhdr {
+    namesz: Be32,
+    descsz: Be32,
+    ty: Be32,
+}
+
+const fn note_align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// Reads `size` bytes at `offset`.
+///
+/// The read is bounded by the size of the input, so that corrupted headers cannot cause huge
+/// allocations.
+fn read_at<R: Read + Seek>(input: &mut R, offset: u64, size: u64, what: &str) -> Result<Vec<u8>> {
+    let len = input.seek(SeekFrom::End(0))?;
+    if offset.checked_add(size).map_or(true, |end| end > len) {
+        bail!("The {what} of the dump exceeds the file ({size} bytes at offset {offset:#x})");
+    }
+    let mut buf = vec![0; size as usize];
+    input.seek(SeekFrom::Start(offset))?;
+    input
+        .read_exact(&mut buf)
+        .with_context(|| format!("Cannot read the {what} of the dump"))?;
+    Ok(buf)
+}
+
+fn read_table<T: FromBytes + Copy, R: Read + Seek>(
+    input: &mut R,
+    offset: u64,
+    num: u16,
+    entsize: u16,
+    what: &str,
+) -> Result<Vec<T>> {
+    if num > 0 && entsize as usize != size_of::<T>() {
+        bail!("The dump has an invalid {what} entry size ({entsize})");
+    }
+    let buf = read_at(input, offset, num as u64 * entsize as u64, what)?;
+    Ok(buf
+        .chunks_exact(size_of::<T>())
+        .map(|e| T::read_from_bytes(e).expect("chunk has the size of T"))
+        .collect())
+}
+
+/// An s390x ELF core file opened for reading
+pub struct ElfCore {
+    phdrs: Vec<Phdr>,
+    shdrs: Vec<Shdr>,
+    shstrtab: Vec<u8>,
+}
+
+impl ElfCore {
+    /// Reads the ELF, program, and section headers of `input`.
+    pub fn read<R: Read + Seek>(input: &mut R) -> Result<Self> {
+        let mut buf = [0; size_of::<Ehdr>()];
+        input.seek(SeekFrom::Start(0))?;
+        input
+            .read_exact(&mut buf)
+            .context("The input is no ELF core file")?;
+        let ehdr = Ehdr::read_from_bytes(&buf).expect("buffer has the size of an ELF header");
+        if ehdr.ident[..4] != ELFMAG || ehdr.ty.get() != ET_CORE {
+            bail!("The input is no ELF core file");
+        }
+        if ehdr.ident[4] != ELFCLASS64
+            || ehdr.ident[5] != ELFDATA2MSB
+            || ehdr.machine.get() != EM_S390
+        {
+            bail!("The input is no s390x (64 bit) ELF core file");
+        }
+
+        let phdrs = read_table(
+            input,
+            ehdr.phoff.get(),
+            ehdr.phnum.get(),
+            ehdr.phentsize.get(),
+            "program header table",
+        )?;
+        let shdrs: Vec<Shdr> = read_table(
+            input,
+            ehdr.shoff.get(),
+            ehdr.shnum.get(),
+            ehdr.shentsize.get(),
+            "section header table",
+        )?;
+        let shstrtab = match shdrs.get(ehdr.shstrndx.get() as usize) {
+            Some(shdr) => read_at(
+                input,
+                shdr.offset.get(),
+                shdr.size.get(),
+                "section name table",
+            )?,
+            None => vec![],
+        };
+        Ok(Self {
+            phdrs,
+            shdrs,
+            shstrtab,
+        })
+    }
+
+    pub fn phdrs(&self) -> &[Phdr] {
+        &self.phdrs
+    }
+
+    /// Returns the file offset and size of the section `name`.
+    pub fn section(&self, name: &str) -> Option<(u64, u64)> {
+        self.shdrs
+            .iter()
+            .find(|shdr| {
+                self.shstrtab
+                    .get(shdr.name.get() as usize..)
+                    .and_then(|s| s.split(|c| *c == 0).next())
+                    .is_some_and(|s| s == name.as_bytes())
+            })
+            .map(|shdr| (shdr.offset.get(), shdr.size.get()))
+    }
+
+    /// Reads the content of the section `name`.
+    pub fn read_section<R: Read + Seek>(&self, input: &mut R, name: &str) -> Result<Vec<u8>> {
+        let (offset, size) = self.section(name).with_context(|| {
+            format!(
+                "The dump has no '{name}' section. It is probably no Secure Execution guest dump"
+            )
+        })?;
+        read_at(input, offset, size, name)
+    }
+
+    /// Reads all notes of the note segment `phdr` as (type, description).
+    pub fn read_notes<R: Read + Seek>(input: &mut R, phdr: &Phdr) -> Result<Vec<(u32, Vec<u8>)>> {
+        let segment = read_at(input, phdr.offset.get(), phdr.filesz.get(), "note segment")?;
+        let mut notes = vec![];
+        let mut rest = segment.as_slice();
+        while !rest.is_empty() {
+            let (nhdr, data) = Nhdr::read_from_prefix(rest)
+                .map_err(|_| anyhow::anyhow!("The dump has a corrupted note"))?;
+            let desc_off = note_align(nhdr.namesz.get() as usize);
+            let desc_end = desc_off + nhdr.descsz.get() as usize;
+            let Some(desc) = data.get(desc_off..desc_end) else {
+                bail!("The dump has a corrupted note");
+            };
+            notes.push((nhdr.ty.get(), desc.to_vec()));
+            rest = data.get(note_align(desc_end)..).unwrap_or_default();
+        }
+        Ok(notes)
+    }
+}
+
+/// Appends an ELF note to `buf`.
+pub fn push_note(buf: &mut Vec<u8>, ty: u32, name: &str, desc: &[u8]) {
+    let nhdr = Nhdr {
+        namesz: (name.len() as u32 + 1).into(),
+        descsz: (desc.len() as u32).into(),
+        ty: ty.into(),
+    };
+    buf.extend_from_slice(nhdr.as_bytes());
+    buf.extend_from_slice(name.as_bytes());
+    buf.resize(note_align(buf.len() + 1), 0);
+    buf.extend_from_slice(desc);
+    buf.resize(note_align(buf.len()), 0);
+}
+
+/// A memory segment of the output core file
+pub struct Load {
+    pub addr: u64,
+    pub size: u64,
+}
+
+/// Writes the ELF header, the program headers and the notes of a core file with `loads` to
+/// `output`.
+///
+/// Returns the file offsets of the load segments.
+pub fn write_core_headers<W: Write>(
+    output: &mut W,
+    notes: &[u8],
+    loads: &[Load],
+) -> Result<Vec<u64>> {
+    let phnum = 1 + loads.len();
+    let mut ident = [0; 16];
+    ident[..4].copy_from_slice(&ELFMAG);
+    ident[4] = ELFCLASS64;
+    ident[5] = ELFDATA2MSB;
+    ident[6] = EV_CURRENT;
+    let ehdr = Ehdr {
+        ident,
+        ty: ET_CORE.into(),
+        machine: EM_S390.into(),
+        version: (EV_CURRENT as u32).into(),
+        entry: 0.into(),
+        phoff: (size_of::<Ehdr>() as u64).into(),
+        shoff: 0.into(),
+        flags: 0.into(),
+        ehsize: (size_of::<Ehdr>() as u16).into(),
+        phentsize: (size_of::<Phdr>() as u16).into(),
+        phnum: u16::try_from(phnum)
+            .context("The dump has too many memory segments")?
+            .into(),
+        shentsize: 0.into(),
+        shnum: 0.into(),
+        shstrndx: 0.into(),
+    };
+
+    let notes_off = (size_of::<Ehdr>() + phnum * size_of::<Phdr>()) as u64;
+    let mut phdrs = vec![Phdr {
+        ty: PT_NOTE.into(),
+        flags: 0.into(),
+        offset: notes_off.into(),
+        vaddr: 0.into(),
+        paddr: 0.into(),
+        filesz: (notes.len() as u64).into(),
+        memsz: (notes.len() as u64).into(),
+        align: 0.into(),
+    }];
+    let mut offset = (notes_off + notes.len() as u64).next_multiple_of(PAGE_SIZE);
+    let mut offsets = vec![];
+    for load in loads {
+        phdrs.push(Phdr {
+            ty: PT_LOAD.into(),
+            flags: PF_RWX.into(),
+            offset: offset.into(),
+            vaddr: load.addr.into(),
+            paddr: load.addr.into(),
+            filesz: load.size.into(),
+            memsz: load.size.into(),
+            align: PAGE_SIZE.into(),
+        });
+        offsets.push(offset);
+        offset += load.size;
+    }
+
+    output.write_all(ehdr.as_bytes())?;
+    phdrs
+        .iter()
+        .try_for_each(|phdr| output.write_all(phdr.as_bytes()))?;
+    output.write_all(notes)?;
+    Ok(offsets)
+}
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+mod cli;
+mod dump;
+mod elf;
+
+use std::{fs::OpenOptions, process::ExitCode};
+
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser};
+use cli::CliOptions;
+use dump::decrypt_dump;
+use log::{info, trace};
+use pv::{
+    misc::{open_file, read_exact_file},
+    sandbox::Sandbox,
+};
+use utils::{
+    print_cli_error, print_error, print_version, AtomicFile, AtomicFileOperation, PvLogger,
+};
+
+static LOGGER: PvLogger = PvLogger;
+static EXIT_LOGGER: u8 = 3;
+
+fn run(cli: &CliOptions) -> Result<()> {
+    // clap ensures that the arguments are present unless `--version` is given
+    let (Some(key), Some(input), Some(output)) = (&cli.key, &cli.input, &cli.output) else {
+        unreachable!("required arguments missing")
+    };
+    let cck = read_exact_file(key, "customer communication key")
+        .context("The customer communication key must be 32 bytes long")?
+        .into();
+    let mut input = open_file(input)?;
+    let mut writer = AtomicFile::new(output, &mut OpenOptions::new())?;
+    cli.sandbox.apply(Sandbox::new().allow_create(output))?;
+    let summary = decrypt_dump(&mut input, &cck, &mut writer)?;
+
+    let op = match cli.overwrite {
+        true => AtomicFileOperation::Replace,
+        false => AtomicFileOperation::NoReplace,
+    };
+    writer.finish(op)?;
+    info!(
+        "Decrypted {} CPU states and {} memory pages into '{}'",
+        summary.cpus,
+        summary.pages,
+        output.display()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli: CliOptions = match CliOptions::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => return print_cli_error(e, CliOptions::command()),
+    };
+
+    // set up logger/std(out,err)
+    let log_level = cli.verbosity.to_level_filter();
+    if let Err(e) = LOGGER.start_with_format(log_level, cli.verbosity.log_format()) {
+        // should(TM) never happen
+        eprintln!("Logger error: {e:?}");
+        return EXIT_LOGGER.into();
+    }
+
+    if cli.version {
+        print_version!("2025", log_level);
+        return ExitCode::SUCCESS;
+    }
+
+    trace!("Options {cli:?}");
+
+    match run(&cli) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => print_error(&e, log_level),
+    }
+}
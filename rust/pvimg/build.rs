@@ -4,7 +4,7 @@
 // it under the terms of the MIT license. See LICENSE for details.
 #![allow(missing_docs)]
 
-use std::io::Error;
+use std::{env, io::Error};
 
 use clap_complete::{generate_to, Shell};
 
@@ -2,11 +2,11 @@
 //
 // Copyright IBM Corp. 2024
 
-use std::{env, fmt::Display, path::PathBuf};
+use std::{ffi::OsString, fmt::Display, path::PathBuf};
 
 use clap::{ArgGroup, Args, Command, CommandFactory, Parser, ValueEnum, ValueHint};
 use log::warn;
-use utils::{CertificateOptions, DeprecatedVerbosityOptions};
+use utils::{CertificateOptions, DeprecatedVerbosityOptions, GenerateOptions};
 
 /// Create and inspect IBM Secure Execution images.
 ///
@@ -25,6 +25,12 @@ pub struct CliOptions {
     #[arg(long)]
     pub version: bool,
 
+    /// Require OpenSSL to be in FIPS mode and use FIPS-approved algorithms only.
+    ///
+    /// Fails if OpenSSL is not in FIPS mode.
+    #[arg(long, global = true)]
+    pub fips: bool,
+
     #[command(subcommand)]
     pub cmd: SubCommands,
 }
@@ -34,6 +40,7 @@ impl From<GenprotimgCliOptions> for CliOptions {
         Self {
             verbose: value.verbose,
             version: false,
+            fips: false,
             cmd: SubCommands::Create(value.args),
         }
     }
@@ -44,6 +51,7 @@ impl CliOptions {
         Self {
             verbose: DeprecatedVerbosityOptions::default(),
             version: true,
+            fips: false,
             cmd: SubCommands::Version,
         }
     }
@@ -85,6 +93,16 @@ pub struct ComponentPaths {
     #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
     pub ramdisk: Option<PathBuf>,
 
+    /// Append the initramfs archive in FILE to the ramdisk.
+    ///
+    /// Can be specified multiple times. The archives are appended in the given
+    /// order, each at a 4-byte boundary, so that the kernel unpacks them one
+    /// after the other and later archives overwrite files of earlier ones.
+    /// Each archive must be an uncompressed newc cpio archive or a compressed
+    /// archive, see the documentation for details.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, requires = "ramdisk")]
+    pub ramdisk_overlay: Vec<PathBuf>,
+
     /// Use the content of FILE as the Linux kernel command line.
     ///
     /// The Linux kernel command line must be shorter than the maximum kernel
@@ -109,6 +127,20 @@ pub struct UserKeys {
     )]
     pub cck: Option<PathBuf>,
 
+    /// Generate a random customer-communication key (CCK) and write it to FILE.
+    ///
+    /// FILE must not exist; it is created with permissions 0600. Keep the key
+    /// safe, it is required to decrypt guest dumps and to derive the extension
+    /// secret.
+    #[arg(
+        long,
+        value_name = "FILE",
+        group = "cck-available",
+        conflicts_with = "cck",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub generate_cck: Option<PathBuf>,
+
     /// Use the content of FILE as the Secure Execution header protection key.
     ///
     /// The file must contain exactly 32 bytes of data. If the option is not
@@ -136,7 +168,8 @@ pub struct UserKeys {
     group(ArgGroup::new("cck-available").multiple(true)))]
 pub struct CreateBootImageLegacyFlags {
     /// Enable Secure Execution guest dump support. This option requires the
-    /// '--cck' or '--enable-cck-update' option.
+    /// '--cck', '--generate-cck', or '--enable-cck-update' option. Requires
+    /// z16 or up.
     #[arg(long, action = clap::ArgAction::SetTrue, requires = "cck-available", group="header-flags")]
     pub enable_dump: Option<bool>,
 
@@ -145,9 +178,9 @@ pub struct CreateBootImageLegacyFlags {
     pub disable_dump: Option<bool>,
 
     /// Add-secret requests must provide an extension secret that matches the
-    /// CCK-derived extension secret. This option requires the '--cck'
-    /// option.
-    #[arg(long, action = clap::ArgAction::SetTrue, requires="cck", group="header-flags")]
+    /// CCK-derived extension secret. This option requires the '--cck' or
+    /// '--generate-cck' option.
+    #[arg(long, action = clap::ArgAction::SetTrue, requires="cck-available", group="header-flags")]
     pub enable_cck_extension_secret: Option<bool>,
 
     /// Add-secret requests don't have to provide the CCK-derived extension
@@ -182,6 +215,22 @@ pub struct CreateBootImageLegacyFlags {
     #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with="enable_pckmo_hmac", group="header-flags")]
     pub disable_pckmo_hmac: Option<bool>,
 
+    /// Disable the support for the specified PCKMO key encryption functions.
+    ///
+    /// All other PCKMO functions keep their default. Can be specified multiple
+    /// times or as a comma-separated list. This option cannot be used in
+    /// conjunction with the other PCKMO options.
+    #[arg(
+        long,
+        value_name = "FUNCTION",
+        value_enum,
+        use_value_delimiter = true,
+        value_delimiter = ',',
+        conflicts_with_all = ["enable_pckmo", "disable_pckmo", "enable_pckmo_hmac", "disable_pckmo_hmac"],
+        group = "header-flags",
+    )]
+    pub disable_pckmo_function: Vec<PckmoFunction>,
+
     /// Enable the support for backup target keys.
     #[arg(long, action = clap::ArgAction::SetTrue, group="header-flags")]
     pub enable_backup_keys: Option<bool>,
@@ -205,6 +254,44 @@ pub struct CreateBootImageLegacyFlags {
     pub disable_image_encryption: Option<bool>,
 }
 
+/// PCKMO key encryption functions
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Debug)]
+pub enum PckmoFunction {
+    /// DEA and TDEA key encryption functions.
+    DeaTdea,
+    /// AES key encryption functions.
+    Aes,
+    /// ECC key encryption functions.
+    Ecc,
+    /// HMAC key encryption functions.
+    Hmac,
+}
+
+/// IBM Z machine generations running Secure Execution guests
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum TargetMachine {
+    /// IBM z15 and LinuxONE III.
+    Z15,
+    /// IBM z16 and LinuxONE 4.
+    Z16,
+    /// IBM z17 and LinuxONE 5.
+    Z17,
+}
+
+impl Display for TargetMachine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Z15 => "z15",
+                Self::Z16 => "z16",
+                Self::Z17 => "z17",
+            }
+        )
+    }
+}
+
 #[non_exhaustive]
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
 pub enum OutputFormat {
@@ -248,6 +335,49 @@ pub struct InfoArgs {
     pub hdr_key: Option<PathBuf>,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Debug, Default)]
+pub enum ExtractHdrFormat {
+    /// The Secure Execution header as it is stored in the image.
+    #[default]
+    Raw,
+    /// The decoded Secure Execution header in JSON format.
+    Json,
+}
+
+fn parse_offset(s: &str) -> Result<u64, std::num::ParseIntError> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ExtractHdrArgs {
+    /// Use INPUT as the Secure Execution image or as the disk device that
+    /// contains it.
+    #[arg(value_name = "INPUT", value_hint = ValueHint::FilePath)]
+    pub input: PathBuf,
+
+    /// Write the Secure Execution header to FILE.
+    ///
+    /// If FILE is '-', the header is written to stdout.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub output: PathBuf,
+
+    /// The output format
+    #[arg(long, value_enum, default_value_t)]
+    pub format: ExtractHdrFormat,
+
+    /// Start the search for the Secure Execution header at OFFSET.
+    ///
+    /// Specify OFFSET in bytes, in decimal or, prefixed with '0x', in
+    /// hexadecimal. The header is searched at the page boundaries following
+    /// OFFSET. Use it, for example, if the image starts at a partition offset
+    /// on a disk device.
+    #[arg(long, value_name = "OFFSET", value_parser = parse_offset)]
+    pub offset: Option<u64>,
+}
+
 #[derive(Args, Debug)]
 #[command(group(ArgGroup::new("test-args").multiple(true).required(true)))]
 pub struct TestArgs {
@@ -332,10 +462,9 @@ impl GenprotimgCliOptions {
         cmd.bin_name("genprotimg")
     }
 
-    pub fn own_parse() -> CliOptions {
-        let args = env::args_os();
+    pub fn own_parse(args: Vec<OsString>) -> CliOptions {
         let args_len = args.len();
-        let version_count = args.filter(|value| value == "--version").count();
+        let version_count = args.iter().filter(|value| *value == "--version").count();
         if version_count > 1 || version_count == 1 && (args_len != version_count + 1) {
             Self::command()
                 .error(
@@ -348,7 +477,7 @@ impl GenprotimgCliOptions {
         if version_count == 1 {
             CliOptions::new_version_cmd_opts()
         } else {
-            let genprotimg_opts = Self::parse();
+            let genprotimg_opts = Self::parse_from(args);
             genprotimg_opts.into()
         }
     }
@@ -357,6 +486,16 @@ impl GenprotimgCliOptions {
 #[derive(Parser, Debug)]
 #[cfg_attr(test, derive(Default))]
 pub struct CreateBootImageArgs {
+    /// Read the image components, host-key documents, flags, and output paths from the YAML
+    /// build configuration FILE.
+    ///
+    /// '${NAME}' and '${NAME:-DEFAULT}' in the values are replaced by the environment variable
+    /// NAME. Relative paths are relative to the directory of FILE. Options in FILE must not be
+    /// repeated on the command line, except options that can be specified multiple times, such
+    /// as '--host-key-document'.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub config: Option<PathBuf>,
+
     #[clap(flatten)]
     pub component_paths: ComponentPaths,
 
@@ -370,7 +509,9 @@ pub struct CreateBootImageArgs {
     /// Disable all input component checks.
     ///
     /// For example, for the Linux kernel, it tests if the given kernel looks
-    /// like a raw binary s390x kernel.
+    /// like a raw binary s390x kernel. The checks also verify that the ramdisk
+    /// is not empty, that the kernel command line fits the kernel and contains
+    /// no NUL characters, and that all components fit into the image layout.
     #[arg(long)]
     pub no_component_check: bool,
 
@@ -378,6 +519,16 @@ pub struct CreateBootImageArgs {
     #[arg(long)]
     pub overwrite: bool,
 
+    /// Verify that the oldest machine generation the image must run on
+    /// supports the selected header flags.
+    ///
+    /// Optional. By default, the selected flags are not checked.
+    #[arg(long, value_name = "MACHINE", value_enum)]
+    pub target_machine: Option<TargetMachine>,
+
+    #[clap(flatten)]
+    pub zipl: ZiplArgs,
+
     #[clap(flatten)]
     pub keys: UserKeys,
 
@@ -388,6 +539,43 @@ pub struct CreateBootImageArgs {
     pub experimental_args: CreateBootImageExperimentalArgs,
 }
 
+/// Options for booting the image from a disk
+#[derive(Args, Debug)]
+#[cfg_attr(test, derive(Default))]
+pub struct ZiplArgs {
+    /// Write a zipl configuration for the generated image to FILE.
+    ///
+    /// The configuration contains a boot section that loads the generated
+    /// Secure Execution image. Use it with 'zipl --config FILE' to write the
+    /// boot record, or specify '--run-zipl'.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub zipl_config: Option<PathBuf>,
+
+    /// Use NAME as the name of the boot section in the zipl configuration.
+    #[arg(
+        long,
+        value_name = "NAME",
+        default_value = "secure",
+        requires = "zipl_config"
+    )]
+    pub zipl_section: String,
+
+    /// Use DIR as the zipl target directory for the boot map.
+    #[arg(
+        long,
+        value_name = "DIR",
+        value_hint = ValueHint::DirPath,
+        default_value = "/boot",
+        requires = "zipl_config"
+    )]
+    pub zipl_target: PathBuf,
+
+    /// Run zipl with the generated zipl configuration to make the image
+    /// bootable from disk.
+    #[arg(long, requires = "zipl_config")]
+    pub run_zipl: bool,
+}
+
 /// Experimental options
 #[derive(Args, Debug)]
 #[cfg_attr(test, derive(Default))]
@@ -414,6 +602,56 @@ pub struct CreateBootImageExperimentalArgs {
     pub x_scf: Option<String>,
 }
 
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    #[clap(flatten)]
+    pub input: SeImgInputArgs,
+
+    /// Use FILE as a host key document.
+    ///
+    /// The image must contain the host key hash of each specified host key
+    /// and no other host key hashes. This parameter can be specified multiple
+    /// times.
+    #[arg(
+        short = 'k',
+        long = "host-key-document",
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+        use_value_delimiter = true,
+        value_delimiter = ',',
+        required = true,
+        )]
+    pub host_key_documents: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportBundleArgs {
+    /// Use DIR as the CRL and certificate cache.
+    ///
+    /// Defaults to $XDG_CACHE_HOME/s390-tools/pv or $HOME/.cache/s390-tools/pv.
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Write the bundle to FILE.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportBundleArgs {
+    /// Use DIR as the CRL and certificate cache.
+    ///
+    /// Defaults to $XDG_CACHE_HOME/s390-tools/pv or $HOME/.cache/s390-tools/pv.
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Use BUNDLE as the bundle to import.
+    ///
+    /// Certificates and CRLs in PEM, DER, or PKCS#7 format are accepted.
+    #[arg(value_name = "BUNDLE", value_hint = ValueHint::FilePath)]
+    pub input: PathBuf,
+}
+
 #[derive(Debug, clap::Subcommand)]
 pub enum SubCommands {
     /// Create an IBM Secure Execution image.
@@ -434,9 +672,42 @@ pub enum SubCommands {
     /// Test different aspects of an existing IBM Secure Execution image.
     Test(Box<TestArgs>),
 
+    /// Verify an existing IBM Secure Execution image before deployment.
+    ///
+    /// Verify that the Secure Execution header is well-formed, that the image
+    /// targets exactly the given host keys, and that the sizes and locations of
+    /// the image components are consistent with the header.
+    Verify(Box<VerifyArgs>),
+
+    /// Extract the Secure Execution header from an image.
+    ///
+    /// Locates the Secure Execution header in a Secure Execution image or a
+    /// disk device and writes it, either as raw bytes or decoded as JSON. Use
+    /// the header, for example, as input for 'pvattest verify --hdr' or to
+    /// compute attestation reference values.
+    ExtractHdr(ExtractHdrArgs),
+
+    /// Export the cached CRLs and certificates as bundle.
+    ///
+    /// Writes all valid CRLs and certificates of the cache, which the host-key
+    /// document verification filled, to a PEM file. Use 'pvimg import-bundle'
+    /// to import the bundle on an air-gapped system.
+    ExportBundle(ExportBundleArgs),
+
+    /// Import CRLs and certificates into the cache.
+    ///
+    /// Imports a bundle created by 'pvimg export-bundle' on a system with
+    /// network access. The host-key document verification then uses the
+    /// imported CRLs, also with '--offline'.
+    ImportBundle(ImportBundleArgs),
+
     /// Print version information and exit.
     #[command(aliases(["--version"]), hide(true))]
     Version,
+
+    /// Generate shell completions and man pages.
+    #[command(hide(true))]
+    Generate(GenerateOptions),
 }
 
 #[allow(clippy::shadow_unrelated)]
@@ -519,6 +790,9 @@ mod test {
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("verbose", ["-VVV"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("offline", ["--offline"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("ramdisk", ["--ramdisk", "/dev/null"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("ramdisk", ["--ramdisk", "/dev/null"]),
+                                                   CliOption::new("overlay", ["--ramdisk-overlay", "/dev/null"]),
+                                                   CliOption::new("overlay2", ["--ramdisk-overlay", "/dev/null"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("parmfile", ["--parmfile", "/dev/null"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("enable-dump", ["--enable-dump"]),
                                                    CliOption::new("comm-key", ["--comm-key", "/dev/null"])])),
@@ -542,6 +816,19 @@ mod test {
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("disable-cck-update", ["--disable-cck-update"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("multiple-cck", ["--disable-cck-update", "--cck", "/dev/null"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("x-comp-key", ["--x-comp-key", "/dev/null"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("enable-dump", ["--enable-dump"]),
+                                                   CliOption::new("generate-cck", ["--generate-cck", "/dev/null"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("extension", ["--enable-cck-extension-secret"]),
+                                                   CliOption::new("generate-cck", ["--generate-cck", "/dev/null"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("disable-pckmo-function", ["--disable-pckmo-function", "aes,ecc"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("disable-pckmo-function", ["--disable-pckmo-function", "dea-tdea"]),
+                                                   CliOption::new("disable-pckmo-function2", ["--disable-pckmo-function", "hmac"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("target-machine", ["--target-machine", "z16"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("zipl-config", ["--zipl-config", "/dev/null"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("zipl-config", ["--zipl-config", "/dev/null"]),
+                                                   CliOption::new("zipl-section", ["--zipl-section", "sel"]),
+                                                   CliOption::new("zipl-target", ["--zipl-target", "/boot"]),
+                                                   CliOption::new("run-zipl", ["--run-zipl"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("image-key", ["--image-key", "/dev/null"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("enable-image-encryption", ["--enable-image-encryption"]),
                                                        CliOption::new("image-key", ["--image-key", "/dev/null"])])),
@@ -564,6 +851,8 @@ mod test {
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("output2", ["--output", "/dev/null"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("ramdisk", ["--ramdisk", "/dev/null"]),
                                                    CliOption::new("ramdisk2", ["--ramdisk", "/dev/null"]) ])),
+            // --ramdisk-overlay requires --ramdisk
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("overlay", ["--ramdisk-overlay", "/dev/null"])])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("parmfile", ["--parmfile", "/dev/null"]),
                                                    CliOption::new("parmfile2", ["--parmfile", "/dev/null"]) ])),
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("x-pcf", ["--x-pcf", "0x0"]),
@@ -582,6 +871,19 @@ mod test {
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("x-comp-key", ["--x-comp-key", "/dev/null"]),
                                                        CliOption::new("image-key", ["--image-key", "/dev/null"])])),
 
+            // Only one CCK source can be used.
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("cck", ["--cck", "/dev/null"]),
+                                                       CliOption::new("generate-cck", ["--generate-cck", "/dev/null"])])),
+
+            // The selective PCKMO option conflicts with the other PCKMO options.
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("enable-pckmo", ["--enable-pckmo"]),
+                                                       CliOption::new("disable-pckmo-function", ["--disable-pckmo-function", "aes"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("disable-pckmo-function", ["--disable-pckmo-function", "sha"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("target-machine", ["--target-machine", "z14"])])),
+            // The zipl options require a zipl configuration.
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("run-zipl", ["--run-zipl"])])),
+            flat_map_collect(insert(mvca.clone(), vec![CliOption::new("zipl-section", ["--zipl-section", "sel"])])),
+
             // Disable image encryption and providing an image-key is mutually
             // exclusive.
             flat_map_collect(insert(mvca.clone(), vec![CliOption::new("disable-image-encryption", ["--disable-image-encryption"]),
@@ -871,6 +1173,42 @@ mod test {
         }
     }
 
+    #[test]
+    fn pvimg_extract_hdr_cli() {
+        let parse = |args: &[&str]| {
+            CliOptions::try_parse_from([["pvimg", "extract-hdr"].as_slice(), args].concat()).map(
+                |opts| match opts.cmd {
+                    SubCommands::ExtractHdr(args) => (args.format, args.offset),
+                    _ => unreachable!(),
+                },
+            )
+        };
+        assert_eq!(
+            parse(&["-o", "-", "/dev/null"]).unwrap(),
+            (ExtractHdrFormat::Raw, None)
+        );
+        assert_eq!(
+            parse(&[
+                "-o",
+                "-",
+                "--format",
+                "json",
+                "--offset",
+                "0x14000",
+                "/dev/null"
+            ])
+            .unwrap(),
+            (ExtractHdrFormat::Json, Some(0x14000))
+        );
+        assert_eq!(
+            parse(&["-o", "-", "--offset", "4096", "/dev/null"]).unwrap(),
+            (ExtractHdrFormat::Raw, Some(4096))
+        );
+        assert!(parse(&["/dev/null"]).is_err());
+        assert!(parse(&["-o", "-", "--offset", "0xg", "/dev/null"]).is_err());
+        assert!(parse(&["-o", "-", "--format", "yaml", "/dev/null"]).is_err());
+    }
+
     #[test]
     fn verify_cli() {
         use clap::CommandFactory;
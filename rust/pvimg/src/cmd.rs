@@ -2,15 +2,30 @@
 //
 // Copyright IBM Corp. 2024
 
+mod bundle;
 mod common;
 mod create;
+mod extract_hdr;
 mod info;
 mod test;
+mod verify;
 mod version;
+mod zipl;
 
-pub const CMD_FN: &[&str] = &["+create", "+test", "+info"];
+pub const CMD_FN: &[&str] = &[
+    "+create",
+    "+test",
+    "+info",
+    "+verify",
+    "+extract-hdr",
+    "+export-bundle",
+    "+import-bundle",
+];
 
+pub use bundle::{export_bundle, import_bundle};
 pub use create::create;
+pub use extract_hdr::extract_hdr;
 pub use info::info;
 pub use test::test;
+pub use verify::verify;
 pub use version::version;
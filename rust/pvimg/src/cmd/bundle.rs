@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use pv::{
+    misc::{read_file, write_file},
+    request::CrlCache,
+};
+use pvimg::error::OwnExitCode;
+
+use crate::cli::{ExportBundleArgs, ImportBundleArgs};
+
+fn open_cache(dir: Option<&PathBuf>) -> Result<CrlCache> {
+    let dir = dir
+        .cloned()
+        .or_else(CrlCache::default_dir)
+        .context("Cannot determine the default cache directory. Specify '--cache-dir'")?;
+    Ok(CrlCache::new(dir)?)
+}
+
+pub fn export_bundle(opt: &ExportBundleArgs) -> Result<OwnExitCode> {
+    let cache = open_cache(opt.cache_dir.as_ref())?;
+    let bundle = cache.export_bundle()?;
+    if bundle.is_empty() {
+        warn!(
+            "The cache at '{}' contains no valid certificates or CRLs",
+            cache.dir().display()
+        );
+    }
+    write_file(&opt.output, bundle, "bundle")?;
+    info!("Exported the cache to '{}'", opt.output.display());
+    Ok(OwnExitCode::Success)
+}
+
+pub fn import_bundle(opt: &ImportBundleArgs) -> Result<OwnExitCode> {
+    let cache = open_cache(opt.cache_dir.as_ref())?;
+    let bundle = read_file(&opt.input, "bundle")?;
+    let (certs, crls) = cache.import_bundle(&bundle)?;
+    if certs == 0 && crls == 0 {
+        warn!(
+            "The bundle '{}' contains no valid certificates or CRLs",
+            opt.input.display()
+        );
+    }
+    info!(
+        "Imported {certs} certificates and {crls} CRLs into '{}'",
+        cache.dir().display()
+    );
+    Ok(OwnExitCode::Success)
+}
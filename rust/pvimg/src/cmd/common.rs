@@ -2,11 +2,19 @@
 //
 // Copyright IBM Corp. 2024
 
-use std::path::PathBuf;
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use log::info;
-use pv::{misc::read_file, request::Confidential};
+use pv::{
+    misc::read_file,
+    request::{random_array, Confidential, SymKeyType},
+};
 
 use crate::cli::UserKeys;
 
@@ -22,6 +30,19 @@ pub struct UserProvidedKeys {
     pub(crate) aead_key: Option<(PathBuf, Confidential<Vec<u8>>)>,
 }
 
+/// Generate a random CCK and write it to the new file `path`.
+fn generate_cck(path: &Path) -> Result<Confidential<Vec<u8>>> {
+    let cck = Confidential::new(random_array::<{ SymKeyType::AES_256_GCM_KEY_LEN }>()?.to_vec());
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Cannot create the CCK file '{}'", path.display()))?;
+    file.write_all(cck.value())?;
+    Ok(cck)
+}
+
 /// Reads all user provided keys.
 pub fn read_user_provided_keys(keys: &UserKeys) -> Result<UserProvidedKeys> {
     let components_key = {
@@ -59,8 +80,15 @@ pub fn read_user_provided_keys(keys: &UserKeys) -> Result<UserProvidedKeys> {
     };
 
     let cck = {
-        match &keys.cck {
-            Some(key_path) => {
+        match (&keys.cck, &keys.generate_cck) {
+            (None, Some(key_path)) => {
+                info!(
+                    "Generate the customer communication key (CCK) and write it to '{}'",
+                    key_path.display()
+                );
+                Some((key_path.to_owned(), generate_cck(key_path)?))
+            }
+            (Some(key_path), _) => {
                 info!(
                     "Use file '{}' as the customer communication key (CCK)",
                     key_path.display()
@@ -70,7 +98,7 @@ pub fn read_user_provided_keys(keys: &UserKeys) -> Result<UserProvidedKeys> {
                     (Confidential::new(read_file(key_path, "customer communication key (CCK)")?)),
                 ))
             }
-            None => None,
+            (None, None) => None,
         }
     };
 
@@ -4,7 +4,7 @@
 
 use std::{fs::OpenOptions, io::BufReader};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use log::{debug, warn};
 use pv::misc::{open_file, try_parse_u64};
 use pvimg::{
@@ -18,11 +18,12 @@ use pvimg::{
 use utils::{AtomicFile, AtomicFileOperation};
 
 use crate::{
-    cli::{ComponentPaths, CreateBootImageArgs},
-    cmd::common::read_user_provided_keys,
+    cli::{ComponentPaths, CreateBootImageArgs, PckmoFunction, TargetMachine},
+    cmd::{common::read_user_provided_keys, zipl::zipl},
     se_img::{SeHdrArgs, SeImgBuilder},
     se_img_comps::{
         check_components, cmdline::Cmdline, kernel::S390Kernel, ramdisk::Ramdisk, Component,
+        ReadSeekDebug,
     },
 };
 
@@ -37,7 +38,20 @@ fn components(component_args: &ComponentPaths) -> Result<Vec<Component>> {
     let mut components: Vec<Component> =
         vec![S390Kernel::new(Box::new(BufReader::new(open_file(&component_args.kernel)?))).into()];
     if let Some(path) = &component_args.ramdisk {
-        components.push(Ramdisk::new(Box::new(BufReader::new(open_file(path)?))).into());
+        let ramdisk = match component_args.ramdisk_overlay.is_empty() {
+            true => Ramdisk::new(Box::new(BufReader::new(open_file(path)?))),
+            false => Ramdisk::compose(
+                std::iter::once(path)
+                    .chain(&component_args.ramdisk_overlay)
+                    .map(|path| {
+                        let reader: Box<dyn ReadSeekDebug> =
+                            Box::new(BufReader::new(open_file(path)?));
+                        Ok((path.display().to_string(), reader))
+                    })
+                    .collect::<Result<_>>()?,
+            )?,
+        };
+        components.push(ramdisk.into());
     }
     if let Some(path) = &component_args.parmfile {
         components.push(Cmdline::new(Box::new(BufReader::new(open_file(path)?))).into());
@@ -71,6 +85,19 @@ fn parse_flags(
         flag_enabled!(lf.enable_pckmo, PlaintextControlFlagsV1::PCKMO),
         flag_disabled!(lf.disable_pckmo_hmac, [PcfV1::PckmoHmac]),
         flag_enabled!(lf.enable_pckmo_hmac, [PcfV1::PckmoHmac]),
+        (!lf.disable_pckmo_function.is_empty()).then(|| {
+            let functions: Vec<_> = lf
+                .disable_pckmo_function
+                .iter()
+                .map(|f| match f {
+                    PckmoFunction::DeaTdea => PcfV1::PckmoDeaTdea,
+                    PckmoFunction::Aes => PcfV1::PckmoAes,
+                    PckmoFunction::Ecc => PcfV1::PckmoEcc,
+                    PckmoFunction::Hmac => PcfV1::PckmoHmac,
+                })
+                .collect();
+            ControlFlagTrait::all_disabled(functions)
+        }),
         flag_disabled!(lf.disable_backup_keys, [PcfV1::BackupTargetKeys]),
         flag_enabled!(lf.enable_backup_keys, [PcfV1::BackupTargetKeys]),
         flag_enabled!(lf.disable_image_encryption, [PcfV1::NoComponentEncryption]),
@@ -116,9 +143,39 @@ fn parse_flags(
     scf.parse_flags(&secret_flags);
     debug!("Using secret flags:    {scf}");
 
+    if let Some(machine) = args.target_machine {
+        check_target_machine(machine, &pcf, &scf)?;
+    }
+
     Ok((pcf, scf))
 }
 
+/// Verify that `machine` supports all selected header flags.
+fn check_target_machine(
+    machine: TargetMachine,
+    pcf: &PlaintextControlFlagsV1,
+    scf: &SecretControlFlagsV1,
+) -> Result<()> {
+    let required = [
+        (
+            pcf.is_set(PcfV1::AllowDumping),
+            TargetMachine::Z16,
+            "Guest dump support",
+        ),
+        (
+            scf.is_set(ScfV1::CckUpdateAllowed),
+            TargetMachine::Z17,
+            "CCK update support",
+        ),
+    ];
+    for (_, min, what) in required.into_iter().filter(|(set, ..)| *set) {
+        if machine < min {
+            bail!("{what} requires {min} or up, but the target machine is {machine}");
+        }
+    }
+    Ok(())
+}
+
 /// Create a Secure Execution boot image
 pub fn create(opt: &CreateBootImageArgs) -> Result<OwnExitCode> {
     // Verify host key documents first, because if they are not valid there is
@@ -151,6 +208,10 @@ pub fn create(opt: &CreateBootImageArgs) -> Result<OwnExitCode> {
         opt.experimental_args.x_bootloader_directory.as_ref(),
     )?;
 
+    if !opt.no_component_check {
+        seimg_ctx.check_layout(&mut components)?;
+    }
+
     // Enable expert mode
     seimg_ctx.i_know_what_i_am_doing();
     if let Some((path, key)) = user_provided_keys.components_key {
@@ -202,6 +263,7 @@ pub fn create(opt: &CreateBootImageArgs) -> Result<OwnExitCode> {
     writer.finish(op)?;
 
     warn!("Successfully generated the Secure Execution image.");
+    zipl(&opt.zipl, &opt.output, opt.overwrite)?;
     Ok(OwnExitCode::Success)
 }
 
@@ -229,4 +291,36 @@ mod test {
         let scf = SecretControlFlagsV1::from_flags(ScfV1::all_enabled(exp_scf));
         assert_eq!(parsed_flags.1, scf);
     }
+
+    #[test]
+    fn parse_flags_pckmo_function() {
+        let args = CreateBootImageArgs {
+            legacy_flags: CreateBootImageLegacyFlags {
+                disable_pckmo_function: vec![PckmoFunction::DeaTdea, PckmoFunction::Ecc],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (pcf, _) = super::parse_flags(&args).expect("Failed to parse flags {args:?}");
+        assert!(pcf.is_set(PcfV1::PckmoAes));
+        assert!(pcf.is_unset(PcfV1::PckmoDeaTdea));
+        assert!(pcf.is_unset(PcfV1::PckmoEcc));
+        assert!(pcf.is_unset(PcfV1::PckmoHmac));
+    }
+
+    #[test]
+    fn parse_flags_target_machine() {
+        let args = |target_machine| CreateBootImageArgs {
+            target_machine: Some(target_machine),
+            legacy_flags: CreateBootImageLegacyFlags {
+                enable_dump: Some(true),
+                enable_cck_update: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(super::parse_flags(&args(TargetMachine::Z15)).is_err());
+        assert!(super::parse_flags(&args(TargetMachine::Z16)).is_err());
+        assert!(super::parse_flags(&args(TargetMachine::Z17)).is_ok());
+    }
 }
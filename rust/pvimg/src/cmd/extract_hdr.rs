@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use anyhow::{Context, Result};
+use log::info;
+use pv::misc::open_file;
+use pvimg::{error::OwnExitCode, uvdata::SeHdr};
+use utils::get_writer_from_cli_file_arg;
+
+use crate::cli::{ExtractHdrArgs, ExtractHdrFormat};
+
+/// Locates the Secure Execution header in `input`, starting at `offset`.
+///
+/// Returns the offset of the header, the header as stored in `input`, and the
+/// decoded header.
+fn read_hdr<R: Read + Seek>(input: &mut R, offset: Option<u64>) -> Result<(u64, Vec<u8>, SeHdr)> {
+    SeHdr::seek_sehdr(input, offset)?;
+    let start = input.stream_position()?;
+    let hdr = SeHdr::try_from_io(&mut *input)?;
+    let size = input.stream_position()? - start;
+
+    let mut raw = vec![0; size.try_into()?];
+    input.seek(SeekFrom::Start(start))?;
+    input.read_exact(&mut raw)?;
+    Ok((start, raw, hdr))
+}
+
+pub fn extract_hdr(opt: &ExtractHdrArgs) -> Result<OwnExitCode> {
+    info!(
+        "Searching the Secure Execution header in {}",
+        opt.input.display()
+    );
+    let mut input = open_file(&opt.input)?;
+    let (start, raw, hdr) = read_hdr(&mut input, opt.offset)?;
+    info!(
+        "Secure Execution header found at offset {start:#x} ({} bytes)",
+        raw.len()
+    );
+
+    let mut output = get_writer_from_cli_file_arg(&opt.output)?;
+    match opt.format {
+        ExtractHdrFormat::Raw => output.write_all(&raw)?,
+        ExtractHdrFormat::Json => {
+            serde_json::to_writer_pretty(&mut output, &hdr)?;
+            writeln!(output)?;
+        }
+    }
+    output
+        .flush()
+        .context("Cannot write the Secure Execution header")?;
+    info!(
+        "Secure Execution header written to '{}'",
+        opt.output.display()
+    );
+    Ok(OwnExitCode::Success)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use pvimg::misc::PAGESIZE;
+
+    use super::*;
+
+    const HDR: &[u8] = include_bytes!("../../../pv/tests/assets/exp/secure_guest.hdr");
+
+    #[test]
+    fn read() {
+        let mut img = vec![0; 0x14 * PAGESIZE];
+        img.extend_from_slice(HDR);
+        img.resize(img.len() + 3 * PAGESIZE, 0);
+
+        let (start, raw, _) = read_hdr(&mut Cursor::new(&img), None).unwrap();
+        assert_eq!(start, 0x14 * PAGESIZE as u64);
+        assert_eq!(raw, HDR);
+
+        let (start, raw, _) =
+            read_hdr(&mut Cursor::new(&img), Some(0x13 * PAGESIZE as u64)).unwrap();
+        assert_eq!(start, 0x14 * PAGESIZE as u64);
+        assert_eq!(raw, HDR);
+
+        // no header after the offset
+        assert!(read_hdr(&mut Cursor::new(&img), Some(0x15 * PAGESIZE as u64)).is_err());
+        assert!(read_hdr(&mut Cursor::new(vec![0; 0x20 * PAGESIZE]), None).is_err());
+    }
+}
@@ -2,16 +2,16 @@
 //
 // Copyright IBM Corp. 2024
 
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 use anyhow::Result;
 use log::{info, warn};
 use pv::{
-    misc::{open_file, read_certs, read_file},
+    misc::{open_file, read_hkd},
     FileAccessErrorType, PvCoreError,
 };
 use pvimg::{
-    error::{Error, OwnExitCode, PvError},
+    error::{Error, OwnExitCode},
     uvdata::{KeyExchangeTrait, SeHdr, UvKeyHashesV1},
 };
 use utils::HexSlice;
@@ -27,7 +27,7 @@ fn hdr_test_target_hashes(hdr: &SeHdr, key_hashes: &Path) -> Result<bool> {
             ref source,
         } if matches!(ty, FileAccessErrorType::Open)
             && source.kind() == std::io::ErrorKind::NotFound
-            && *path == PathBuf::from(UvKeyHashesV1::SYS_UV_KEYS_ALL) =>
+            && *path == Path::new(UvKeyHashesV1::SYS_UV_KEYS_ALL) =>
         {
             Error::UnavailableQueryUvKeyHashesSupport { source: err }
         }
@@ -76,19 +76,7 @@ where
     let mut result = false;
     for path in host_key_documents {
         let hkd_path = path.as_ref();
-        let hkd_data = read_file(hkd_path, "host key document")?;
-        let certs = read_certs(&hkd_data)?;
-        if certs.is_empty() {
-            return Err(PvError::NoHkdInFile(hkd_path.display().to_string()).into());
-        }
-
-        if certs.len() != 1 {
-            warn!("The host key document in '{}' contains more than one certificate! Only the first certificate will be used.",
-                  hkd_path.display());
-        }
-
-        // Panic: len is == 1 -> unwrap will succeed/not panic
-        let cert = certs.first().unwrap();
+        let cert = read_hkd(hkd_path)?;
         if hdr.contains(cert.public_key()?)? {
             result = true;
             log_println!(" ✓ Host key document '{}' is included", hkd_path.display());
@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    collections::BTreeSet,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use pv::{
+    misc::{open_file, read_hkd},
+    request::{public_host_key_hash, SeImgMetaData},
+};
+use pvimg::{
+    error::OwnExitCode,
+    misc::PAGESIZE,
+    uvdata::{SeHdr, SeHdrVersioned},
+};
+use utils::HexSlice;
+
+use crate::{cli::VerifyArgs, log_println, se_img_comps::read_ipib};
+
+type Phkh = [u8; 32];
+
+/// Location of a secured component in the image as described by the IPIB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CompLocation {
+    addr: u64,
+    len: u64,
+}
+
+/// Values of the image layout that must be consistent with each other.
+#[derive(Debug)]
+struct ImgLayout {
+    img_size: u64,
+    hdr_off: u64,
+    hdr_size: u64,
+    ipib_hdr_addr: u64,
+    ipib_hdr_size: u64,
+    nep: u64,
+    comps: Vec<CompLocation>,
+}
+
+impl ImgLayout {
+    /// Returns a description of every inconsistency found.
+    fn check(&self) -> Vec<String> {
+        let page_size = PAGESIZE as u64;
+        let mut errors = vec![];
+
+        if self.ipib_hdr_addr != self.hdr_off || self.ipib_hdr_size != self.hdr_size {
+            errors.push(format!(
+                "The IPIB locates the Secure Execution header at {:#x} ({} bytes), but it is at {:#x} ({} bytes)",
+                self.ipib_hdr_addr, self.ipib_hdr_size, self.hdr_off, self.hdr_size
+            ));
+        }
+        if self.comps.is_empty() {
+            errors.push("The IPIB contains no secured components".to_string());
+        }
+
+        let mut pages: u64 = 0;
+        let mut next_addr = 0;
+        for (idx, comp) in self.comps.iter().enumerate() {
+            let Some(stop) = comp.addr.checked_add(comp.len) else {
+                errors.push(format!("Component {idx} exceeds the address space"));
+                continue;
+            };
+            if comp.addr % page_size != 0 {
+                errors.push(format!(
+                    "Component {idx} at {:#x} is not page aligned",
+                    comp.addr
+                ));
+            }
+            if comp.addr < next_addr {
+                errors.push(format!(
+                    "Component {idx} at {:#x} overlaps with or is placed before the previous component",
+                    comp.addr
+                ));
+            }
+            if stop > self.img_size {
+                errors.push(format!(
+                    "Component {idx} ({:#x}-{stop:#x}) exceeds the image size of {:#x} bytes",
+                    comp.addr, self.img_size
+                ));
+            }
+            next_addr = stop;
+            pages = pages.saturating_add(comp.len.div_ceil(page_size));
+        }
+
+        if pages != self.nep {
+            errors.push(format!(
+                "The components consist of {pages} pages, but the Secure Execution header expects {} encrypted pages",
+                self.nep
+            ));
+        }
+        errors
+    }
+}
+
+fn read_hkd_hashes<P: AsRef<Path>>(host_key_documents: &[P]) -> Result<Vec<(&Path, Phkh)>> {
+    let mut hashes = Vec::with_capacity(host_key_documents.len());
+    for path in host_key_documents {
+        let hkd_path = path.as_ref();
+        let cert = read_hkd(hkd_path)?;
+        hashes.push((hkd_path, public_host_key_hash(&cert)?));
+    }
+    Ok(hashes)
+}
+
+/// Returns `true` if the header targets exactly the given host keys.
+fn verify_host_keys(slots: &[Phkh], hkds: &[(&Path, Phkh)]) -> bool {
+    let mut success = true;
+    for (path, hash) in hkds {
+        if slots.contains(hash) {
+            log_println!(" ✓ Host key document '{}' is included", path.display());
+        } else {
+            warn!(" ✘ Host key document '{}' is not included", path.display());
+            success = false;
+        }
+    }
+
+    let given: BTreeSet<_> = hkds.iter().map(|(_, hash)| hash).collect();
+    for hash in slots.iter().filter(|hash| !given.contains(hash)) {
+        warn!(
+            " ✘ Host key hash {:#} does not belong to any given host key document",
+            HexSlice::from(hash)
+        );
+        success = false;
+    }
+    success
+}
+
+/// Verify the layout of the image, if it contains image metadata.
+fn verify_layout<R: Read + Seek>(img: &mut R, hdr: &SeHdr, nep: u64) -> Result<bool> {
+    let img_size = img.seek(SeekFrom::End(0))?;
+    let Some(metadata) = SeImgMetaData::try_from_io(img)? else {
+        warn!(" ✘ The image contains no metadata, the component layout cannot be verified");
+        return Ok(false);
+    };
+
+    img.seek(SeekFrom::Start(metadata.ipib_off()))?;
+    // The IPIB is always smaller than a page.
+    let mut ipib_data = Vec::with_capacity(PAGESIZE);
+    img.take(PAGESIZE as u64).read_to_end(&mut ipib_data)?;
+    let ipib = read_ipib(&ipib_data).context("Cannot read the IPL parameter block")?;
+
+    let layout = ImgLayout {
+        img_size,
+        hdr_off: metadata.hdr_off(),
+        hdr_size: hdr.as_bytes()?.len().try_into()?,
+        ipib_hdr_addr: ipib.pv.pv_hdr_addr,
+        ipib_hdr_size: ipib.pv.pv_hdr_size,
+        nep,
+        comps: ipib
+            .pv
+            .components
+            .iter()
+            .map(|c| CompLocation {
+                addr: c.addr,
+                len: c.len,
+            })
+            .collect(),
+    };
+    let errors = layout.check();
+    if errors.is_empty() {
+        log_println!(
+            " ✓ Layout of the {} secured components is consistent",
+            layout.comps.len()
+        );
+    }
+    for error in &errors {
+        warn!(" ✘ {error}");
+    }
+    Ok(errors.is_empty())
+}
+
+pub fn verify(opt: &VerifyArgs) -> Result<OwnExitCode> {
+    info!("Verifying a Secure Execution image");
+
+    let hkds = read_hkd_hashes(&opt.host_key_documents)?;
+
+    let mut img = open_file(&opt.input.path)?;
+    SeHdr::seek_sehdr(&mut img, None)?;
+    let hdr = SeHdr::try_from_io(&mut img).with_context(|| {
+        format!(
+            "'{}' contains no valid Secure Execution header",
+            opt.input.path.display()
+        )
+    })?;
+    log_println!(" ✓ Secure Execution header is well-formed");
+
+    let (slots, nep) = match &hdr.data {
+        SeHdrVersioned::SeHdrBinV1(bin) => (
+            bin.aad
+                .keyslots
+                .iter()
+                .map(|slot| slot.phkh)
+                .collect::<Vec<_>>(),
+            bin.aad.nep,
+        ),
+    };
+
+    let mut success = verify_host_keys(&slots, &hkds);
+    success = verify_layout(&mut img, &hdr, nep)? && success;
+
+    Ok(if success {
+        log_println!("Successfully verified the Secure Execution image.");
+        OwnExitCode::Success
+    } else {
+        OwnExitCode::GenericError
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn layout() -> ImgLayout {
+        ImgLayout {
+            img_size: 0x20000,
+            hdr_off: 0x14000,
+            hdr_size: 0x280,
+            ipib_hdr_addr: 0x14000,
+            ipib_hdr_size: 0x280,
+            nep: 4,
+            comps: vec![
+                CompLocation {
+                    addr: 0x0,
+                    len: 0x2000,
+                },
+                CompLocation {
+                    addr: 0x2000,
+                    len: 0x1001,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn layout_ok() {
+        assert!(layout().check().is_empty());
+    }
+
+    #[test]
+    fn layout_errors() {
+        let mut l = layout();
+        l.ipib_hdr_size = 0x300;
+        assert_eq!(l.check().len(), 1);
+
+        let mut l = layout();
+        l.nep = 3;
+        assert_eq!(l.check().len(), 1);
+
+        let mut l = layout();
+        l.comps[1].addr = 0x1000;
+        assert_eq!(l.check().len(), 1);
+
+        let mut l = layout();
+        l.comps[1].addr = 0x2800;
+        assert_eq!(l.check().len(), 1);
+
+        let mut l = layout();
+        l.img_size = 0x2800;
+        assert_eq!(l.check().len(), 1);
+
+        let mut l = layout();
+        l.comps.clear();
+        assert_eq!(l.check().len(), 2);
+    }
+
+    #[test]
+    fn host_keys() {
+        let a = Path::new("a");
+        let b = Path::new("b");
+        assert!(verify_host_keys(&[[1; 32]], &[(a, [1; 32])]));
+        assert!(verify_host_keys(
+            &[[1; 32], [2; 32]],
+            &[(a, [1; 32]), (b, [2; 32])]
+        ));
+        // additional host key in the image
+        assert!(!verify_host_keys(&[[1; 32], [2; 32]], &[(a, [1; 32])]));
+        // host key missing in the image
+        assert!(!verify_host_keys(&[[1; 32]], &[(a, [1; 32]), (b, [2; 32])]));
+    }
+}
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use utils::{AtomicFile, AtomicFileOperation};
+
+use crate::cli::ZiplArgs;
+
+const ZIPL: &str = "zipl";
+
+fn check_section_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    {
+        bail!("Invalid zipl section name '{name}'");
+    }
+    Ok(())
+}
+
+/// Generate a zipl configuration that boots `image` by default.
+///
+/// A Secure Execution image already contains the kernel, the initial RAM disk,
+/// and the kernel command line, therefore no 'ramdisk' or 'parameters' are
+/// needed.
+fn zipl_config(section: &str, target: &Path, image: &Path) -> Result<String> {
+    check_section_name(section)?;
+    for (name, path) in [("target", target), ("image", image)] {
+        if !path.to_str().is_some_and(|p| !p.contains('\n')) {
+            bail!(
+                "The zipl {name} '{}' cannot be used in a zipl configuration",
+                path.display()
+            );
+        }
+    }
+    Ok(format!(
+        "[defaultboot]\ndefault = {section}\n\n[{section}]\ntarget = {}\nimage = {}\n",
+        target.display(),
+        image.display()
+    ))
+}
+
+/// Write the zipl configuration for `image` and run zipl if requested.
+pub fn zipl(opt: &ZiplArgs, image: &Path, overwrite: bool) -> Result<()> {
+    let Some(config_path) = &opt.zipl_config else {
+        return Ok(());
+    };
+
+    // zipl resolves relative paths based on its working directory.
+    let image: PathBuf = image
+        .canonicalize()
+        .with_context(|| format!("Cannot resolve '{}'", image.display()))?;
+    let config = zipl_config(&opt.zipl_section, &opt.zipl_target, &image)?;
+
+    let mut writer = AtomicFile::with_extension(config_path, "part", &mut OpenOptions::new())?;
+    writer.write_all(config.as_bytes())?;
+    writer.finish(match overwrite {
+        true => AtomicFileOperation::Replace,
+        false => AtomicFileOperation::NoReplace,
+    })?;
+    info!(
+        "Wrote the zipl configuration to '{}'",
+        config_path.display()
+    );
+
+    if !opt.run_zipl {
+        return Ok(());
+    }
+    info!("Running '{ZIPL} --config {}'", config_path.display());
+    let status = Command::new(ZIPL)
+        .arg("--config")
+        .arg(config_path)
+        .status()
+        .with_context(|| format!("Cannot run '{ZIPL}'"))?;
+    if !status.success() {
+        bail!("'{ZIPL}' failed ({status})");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn config() {
+        let config = zipl_config(
+            "secure",
+            Path::new("/boot"),
+            Path::new("/boot/secure-linux"),
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            "[defaultboot]\ndefault = secure\n\n[secure]\ntarget = /boot\nimage = /boot/secure-linux\n"
+        );
+    }
+
+    #[test]
+    fn config_invalid() {
+        let target = Path::new("/boot");
+        let image = Path::new("/boot/secure-linux");
+        assert!(zipl_config("", target, image).is_err());
+        assert!(zipl_config("a]\n[b", target, image).is_err());
+        assert!(zipl_config("a b", target, image).is_err());
+        assert!(zipl_config("secure", target, Path::new("/boot/a\nb")).is_err());
+    }
+}
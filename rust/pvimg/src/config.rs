@@ -0,0 +1,430 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ValueEnum;
+use pv::misc::read_file;
+use serde::Deserialize;
+use serde_norway::Value;
+
+use crate::cli::{PckmoFunction, TargetMachine};
+
+const CONFIG_ARG: &str = "--config";
+
+/// Header flag in a build configuration
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum Flag {
+    EnableDump,
+    DisableDump,
+    EnableCckExtensionSecret,
+    DisableCckExtensionSecret,
+    EnableCckUpdate,
+    DisableCckUpdate,
+    EnablePckmo,
+    DisablePckmo,
+    EnablePckmoHmac,
+    DisablePckmoHmac,
+    EnableBackupKeys,
+    DisableBackupKeys,
+    EnableImageEncryption,
+    DisableImageEncryption,
+}
+
+impl Flag {
+    /// The equivalent command-line option.
+    const fn arg(self) -> &'static str {
+        match self {
+            Self::EnableDump => "--enable-dump",
+            Self::DisableDump => "--disable-dump",
+            Self::EnableCckExtensionSecret => "--enable-cck-extension-secret",
+            Self::DisableCckExtensionSecret => "--disable-cck-extension-secret",
+            Self::EnableCckUpdate => "--enable-cck-update",
+            Self::DisableCckUpdate => "--disable-cck-update",
+            Self::EnablePckmo => "--enable-pckmo",
+            Self::DisablePckmo => "--disable-pckmo",
+            Self::EnablePckmoHmac => "--enable-pckmo-hmac",
+            Self::DisablePckmoHmac => "--disable-pckmo-hmac",
+            Self::EnableBackupKeys => "--enable-backup-keys",
+            Self::DisableBackupKeys => "--disable-backup-keys",
+            Self::EnableImageEncryption => "--enable-image-encryption",
+            Self::DisableImageEncryption => "--disable-image-encryption",
+        }
+    }
+}
+
+/// Boot-from-disk settings of a build configuration
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct ZiplConfig {
+    config: PathBuf,
+    section: Option<String>,
+    target: Option<PathBuf>,
+    #[serde(default)]
+    run: bool,
+}
+
+/// Build configuration of a Secure Execution image
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct BuildConfig {
+    kernel: PathBuf,
+    #[serde(alias = "initrd")]
+    ramdisk: Option<PathBuf>,
+    #[serde(default, alias = "initrd-overlays")]
+    ramdisk_overlays: Vec<PathBuf>,
+    parmfile: Option<PathBuf>,
+    output: PathBuf,
+    #[serde(default)]
+    overwrite: bool,
+    #[serde(default)]
+    host_key_documents: Vec<String>,
+    #[serde(default)]
+    certs: Vec<String>,
+    #[serde(default)]
+    crls: Vec<String>,
+    root_ca: Option<String>,
+    #[serde(default)]
+    offline: bool,
+    #[serde(default)]
+    no_verify: bool,
+    cck: Option<PathBuf>,
+    generate_cck: Option<PathBuf>,
+    hdr_key: Option<PathBuf>,
+    image_key: Option<PathBuf>,
+    #[serde(default)]
+    flags: Vec<Flag>,
+    #[serde(default)]
+    disable_pckmo_functions: Vec<String>,
+    target_machine: Option<String>,
+    #[serde(default)]
+    no_component_check: bool,
+    zipl: Option<ZiplConfig>,
+}
+
+/// Replace `${NAME}` and `${NAME:-DEFAULT}` in `value` by the environment variable NAME.
+///
+/// `$$` is replaced by a single `$`; any other `$` is kept as is.
+fn substitute(value: &str) -> Result<String> {
+    let mut res = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find('$') {
+        res.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        if let Some(r) = rest.strip_prefix('$') {
+            res.push('$');
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix('{') {
+            let end = r
+                .find('}')
+                .ok_or_else(|| anyhow!("Unterminated variable reference in '{value}'"))?;
+            let (name, default) = match r[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&r[..end], None),
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                bail!("Invalid variable name '{name}' in '{value}'");
+            }
+            match (env::var(name), default) {
+                (Ok(v), _) => res.push_str(&v),
+                (Err(_), Some(default)) => res.push_str(default),
+                (Err(e), None) => {
+                    return Err(e).with_context(|| format!("Cannot substitute '${{{name}}}'"))
+                }
+            }
+            rest = &r[end + 1..];
+        } else {
+            res.push('$');
+        }
+    }
+    res.push_str(rest);
+    Ok(res)
+}
+
+/// Substitute environment variables in all strings of `value`.
+fn substitute_all(value: &mut Value) -> Result<()> {
+    match value {
+        Value::String(s) => *s = substitute(s)?,
+        Value::Sequence(seq) => seq.iter_mut().try_for_each(substitute_all)?,
+        Value::Mapping(map) => map.iter_mut().try_for_each(|(_, v)| substitute_all(v))?,
+        Value::Tagged(tagged) => substitute_all(&mut tagged.value)?,
+        Value::Null | Value::Bool(_) | Value::Number(_) => (),
+    }
+    Ok(())
+}
+
+/// Resolve `path` relative to the directory of the configuration.
+fn resolve<P: AsRef<Path>>(dir: &Path, path: P) -> OsString {
+    dir.join(path).into_os_string()
+}
+
+/// Resolve `input`, which can also be an URL, relative to the directory of the configuration.
+fn resolve_input(dir: &Path, input: &str) -> OsString {
+    match input.starts_with("https://") || input.starts_with("http://") {
+        true => input.into(),
+        false => resolve(dir, input),
+    }
+}
+
+impl BuildConfig {
+    fn parse(buf: &[u8]) -> Result<Self> {
+        let mut value: Value = serde_norway::from_slice(buf)?;
+        substitute_all(&mut value)?;
+        let config: Self = serde_norway::from_value(value)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for f in &self.disable_pckmo_functions {
+            PckmoFunction::from_str(f, false).map_err(|_| {
+                anyhow!("Unknown PCKMO function '{f}' in 'disable-pckmo-functions'")
+            })?;
+        }
+        if let Some(m) = &self.target_machine {
+            TargetMachine::from_str(m, false)
+                .map_err(|_| anyhow!("Unknown machine '{m}' in 'target-machine'"))?;
+        }
+        let mut flags = self.flags.clone();
+        flags.sort_by_key(|f| f.arg());
+        if let Some(w) = flags.windows(2).find(|w| w[0] == w[1]) {
+            bail!("The flag '{}' is listed more than once", &w[0].arg()[2..]);
+        }
+        Ok(())
+    }
+
+    /// Command-line arguments equivalent to the configuration; paths are resolved relative to
+    /// `dir`.
+    fn to_args(&self, dir: &Path) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["--kernel".into(), resolve(dir, &self.kernel)];
+        let mut push = |opt: &str, val: OsString| args.extend([opt.into(), val]);
+        let paths = [
+            ("--ramdisk", &self.ramdisk),
+            ("--parmfile", &self.parmfile),
+            ("--cck", &self.cck),
+            ("--generate-cck", &self.generate_cck),
+            ("--hdr-key", &self.hdr_key),
+            ("--image-key", &self.image_key),
+        ];
+        for (opt, path) in paths {
+            if let Some(path) = path {
+                push(opt, resolve(dir, path));
+            }
+        }
+        for overlay in &self.ramdisk_overlays {
+            push("--ramdisk-overlay", resolve(dir, overlay));
+        }
+        push("--output", resolve(dir, &self.output));
+        for hkd in &self.host_key_documents {
+            push("--host-key-document", resolve_input(dir, hkd));
+        }
+        for crt in &self.certs {
+            push("--cert", resolve_input(dir, crt));
+        }
+        for crl in &self.crls {
+            push("--crl", resolve_input(dir, crl));
+        }
+        if let Some(ca) = &self.root_ca {
+            push("--root-ca", resolve_input(dir, ca));
+        }
+        for f in &self.disable_pckmo_functions {
+            push("--disable-pckmo-function", f.into());
+        }
+        if let Some(m) = &self.target_machine {
+            push("--target-machine", m.into());
+        }
+        if let Some(zipl) = &self.zipl {
+            push("--zipl-config", resolve(dir, &zipl.config));
+            if let Some(section) = &zipl.section {
+                push("--zipl-section", section.into());
+            }
+            if let Some(target) = &zipl.target {
+                push("--zipl-target", resolve(dir, target));
+            }
+        }
+        let switches = [
+            ("--overwrite", self.overwrite),
+            ("--offline", self.offline),
+            ("--no-verify", self.no_verify),
+            ("--no-component-check", self.no_component_check),
+            ("--run-zipl", self.zipl.as_ref().is_some_and(|z| z.run)),
+        ];
+        args.extend(
+            switches
+                .into_iter()
+                .filter(|(_, set)| *set)
+                .map(|(opt, _)| opt.into()),
+        );
+        args.extend(self.flags.iter().map(|f| f.arg().into()));
+        args
+    }
+}
+
+/// Expand the `--config FILE` option of the create command in `args`.
+///
+/// Inserts the command-line arguments equivalent to the build configuration in FILE after the
+/// option, so that the configuration is checked like options given on the command line.
+/// Options in the configuration must not be repeated on the command line, except options that
+/// can be specified multiple times.
+pub fn expand_config_arg(mut args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let Some(pos) = args
+        .iter()
+        .take_while(|a| *a != "--")
+        .position(|a| *a == CONFIG_ARG || a.to_string_lossy().starts_with("--config="))
+    else {
+        return Ok(args);
+    };
+    let path: PathBuf = match args[pos].to_str().and_then(|a| a.strip_prefix("--config=")) {
+        Some(path) => path.into(),
+        None => args
+            .get(pos + 1)
+            .with_context(|| format!("The option '{CONFIG_ARG}' requires a FILE"))?
+            .into(),
+    };
+    let buf = read_file(&path, "build configuration")?;
+    let config = BuildConfig::parse(&buf)
+        .with_context(|| format!("Invalid build configuration '{}'", path.display()))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let at = match args[pos] == CONFIG_ARG {
+        true => pos + 2,
+        false => pos + 1,
+    };
+    args.splice(at..at, config.to_args(dir));
+    Ok(args)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIG: &str = r#"
+kernel: vmlinuz
+initrd: /boot/initrd.img
+ramdisk-overlays: [overlay.cpio]
+parmfile: parmfile
+output: out/se.img
+host-key-documents:
+  - hkd.crt
+  - https://example.com/hkd.crt
+certs: [ibm-z-host-key-signing.crt, DigiCertCA.crt]
+generate-cck: cck.bin
+flags:
+  - enable-dump
+  - disable-pckmo
+disable-pckmo-functions: [hmac]
+target-machine: z16
+zipl:
+  config: zipl.conf
+  run: true
+"#;
+
+    fn args(config: &BuildConfig) -> Vec<String> {
+        config
+            .to_args(Path::new("/cfg"))
+            .into_iter()
+            .map(|a| a.into_string().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parse() {
+        let config = BuildConfig::parse(CONFIG.as_bytes()).unwrap();
+        assert_eq!(
+            args(&config),
+            [
+                "--kernel",
+                "/cfg/vmlinuz",
+                "--ramdisk",
+                "/boot/initrd.img",
+                "--parmfile",
+                "/cfg/parmfile",
+                "--generate-cck",
+                "/cfg/cck.bin",
+                "--ramdisk-overlay",
+                "/cfg/overlay.cpio",
+                "--output",
+                "/cfg/out/se.img",
+                "--host-key-document",
+                "/cfg/hkd.crt",
+                "--host-key-document",
+                "https://example.com/hkd.crt",
+                "--cert",
+                "/cfg/ibm-z-host-key-signing.crt",
+                "--cert",
+                "/cfg/DigiCertCA.crt",
+                "--disable-pckmo-function",
+                "hmac",
+                "--target-machine",
+                "z16",
+                "--zipl-config",
+                "/cfg/zipl.conf",
+                "--run-zipl",
+                "--enable-dump",
+                "--disable-pckmo",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for (yaml, msg) in [
+            ("output: se.img", "kernel"),
+            ("kernel: k\noutput: o\nramdisc: r", "ramdisc"),
+            (
+                "kernel: k\noutput: o\nflags: [enable-everything]",
+                "enable-everything",
+            ),
+            (
+                "kernel: k\noutput: o\nflags: [enable-dump, enable-dump]",
+                "more than once",
+            ),
+            ("kernel: k\noutput: o\ntarget-machine: z14", "z14"),
+            (
+                "kernel: k\noutput: o\ndisable-pckmo-functions: [des]",
+                "des",
+            ),
+            ("kernel: k\noutput: o\nzipl: {run: true}", "config"),
+        ] {
+            let err = BuildConfig::parse(yaml.as_bytes()).unwrap_err();
+            assert!(format!("{err:#}").contains(msg), "{yaml}: {err:#}");
+        }
+    }
+
+    #[test]
+    fn substitute() {
+        env::set_var("PVIMG_TEST_KERNEL", "6.12.0");
+        env::remove_var("PVIMG_TEST_UNSET");
+        assert_eq!(
+            super::substitute("vmlinuz-${PVIMG_TEST_KERNEL}").unwrap(),
+            "vmlinuz-6.12.0"
+        );
+        assert_eq!(
+            super::substitute("${PVIMG_TEST_UNSET:-/boot}/$$x$y").unwrap(),
+            "/boot/$x$y"
+        );
+        assert!(super::substitute("${PVIMG_TEST_UNSET}").is_err());
+        assert!(super::substitute("${PVIMG_TEST_KERNEL").is_err());
+        assert!(super::substitute("${}").is_err());
+
+        let config =
+            BuildConfig::parse(b"kernel: /boot/vmlinuz-${PVIMG_TEST_KERNEL}\noutput: o").unwrap();
+        assert_eq!(config.kernel, Path::new("/boot/vmlinuz-6.12.0"));
+    }
+
+    #[test]
+    fn expand_config_arg() {
+        let args: Vec<OsString> = ["pvimg", "create", "-v"].map(Into::into).into();
+        assert_eq!(super::expand_config_arg(args.clone()).unwrap(), args);
+        let args: Vec<OsString> = ["pvimg", "create", "--", "--config"].map(Into::into).into();
+        assert_eq!(super::expand_config_arg(args.clone()).unwrap(), args);
+        let args: Vec<OsString> = ["pvimg", "create", "--config"].map(Into::into).into();
+        assert!(super::expand_config_arg(args).is_err());
+    }
+}
@@ -13,14 +13,15 @@
 
 mod cli;
 mod cmd;
+mod config;
 mod se_img;
 mod se_img_comps;
 
-use std::{env, process::ExitCode};
+use std::{env, ffi::OsString, process::ExitCode};
 
 use clap::{Command, CommandFactory, Parser};
 use cli::{validate_cli, CliOptions, SubCommands};
-use log::trace;
+use log::{trace, LevelFilter};
 use pvimg::error::OwnExitCode;
 use utils::{print_cli_error, print_error, PvLogger};
 
@@ -29,19 +30,36 @@ use crate::cli::GenprotimgCliOptions;
 static LOGGER: PvLogger = PvLogger;
 
 fn main() -> ExitCode {
-    let exe = env::args_os().next().unwrap();
-    let (opts, cmd): (CliOptions, Command) = match exe.to_str() {
-        // Test if the symlink executable 'genprotimg' was used. If so use the
-        // `pvimg create` command directly.
-        Some(val) if val.ends_with("genprotimg") => (
-            GenprotimgCliOptions::own_parse(),
+    let args: Vec<OsString> = env::args_os().collect();
+    // Test if the symlink executable 'genprotimg' was used. If so use the
+    // `pvimg create` command directly.
+    let genprotimg = args[0]
+        .to_str()
+        .is_some_and(|val| val.ends_with("genprotimg"));
+    // The first argument that is not an option is the subcommand.
+    let create = genprotimg
+        || args
+            .iter()
+            .skip(1)
+            .find(|a| !a.to_string_lossy().starts_with('-'))
+            == Some(&"create".into());
+    let args = match create {
+        true => match config::expand_config_arg(args) {
+            Ok(args) => args,
+            Err(e) => return print_error(&e, LevelFilter::Warn),
+        },
+        false => args,
+    };
+    let (opts, cmd): (CliOptions, Command) = match genprotimg {
+        true => (
+            GenprotimgCliOptions::own_parse(args),
             GenprotimgCliOptions::command(),
         ),
-        _ => (CliOptions::parse(), CliOptions::command()),
+        false => (CliOptions::parse_from(args), CliOptions::command()),
     };
 
     let verbosity = opts.verbose.to_level_filter();
-    if let Err(e) = LOGGER.start(verbosity) {
+    if let Err(e) = LOGGER.start_with_format(verbosity, opts.verbose.log_format()) {
         unreachable!("Logger error: {e:?}");
     }
 
@@ -57,11 +75,25 @@ fn main() -> ExitCode {
     trace!("Trace verbosity, may leak secrets to command-line");
     trace!("Options {opts:?}");
 
+    if opts.fips {
+        if let Err(e) = pv::fips::enforce_fips() {
+            return print_error(&anyhow::Error::from(e), verbosity);
+        }
+    }
+
     let res = match &opts.cmd {
         SubCommands::Create(opt) => cmd::create(opt),
         SubCommands::Info(opt) => cmd::info(opt),
         SubCommands::Test(opt) => cmd::test(opt),
+        SubCommands::Verify(opt) => cmd::verify(opt),
+        SubCommands::ExtractHdr(opt) => cmd::extract_hdr(opt),
+        SubCommands::ExportBundle(opt) => cmd::export_bundle(opt),
+        SubCommands::ImportBundle(opt) => cmd::import_bundle(opt),
         SubCommands::Version => cmd::version(verbosity),
+        SubCommands::Generate(opt) => opt
+            .run(CliOptions::command(), "Pvimg Manual")
+            .map(|_| OwnExitCode::Success)
+            .map_err(Into::into),
     };
 
     match res {
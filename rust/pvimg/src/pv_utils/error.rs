@@ -42,6 +42,12 @@ pub enum Error {
     #[error("Invalid interval: Start {start} is larger than {stop}")]
     InvalidInterval { start: u64, stop: u64 },
 
+    #[error("Invalid IPL parameter block (IPIB)")]
+    InvalidIpib,
+
+    #[error("Cannot add '{name}' to the ramdisk: {reason}")]
+    InvalidInitramfs { name: String, reason: String },
+
     #[error(
         "The given tweak size {given} is smaller than the expected tweak size, which is {expected}"
     )]
@@ -62,9 +68,23 @@ pub enum Error {
     #[error("Image already finalized")]
     ImageAlreadyFinalized,
 
-    #[error("Provided kernel cmdline is too large: {size} > {max_size}")]
+    #[error("Provided kernel cmdline is too large: {size} > {max_size}. Shorten the parmfile to at most {max} bytes", max = .max_size.saturating_sub(1))]
     KernelCmdlineTooLarge { size: usize, max_size: usize },
 
+    #[error("Provided kernel cmdline contains a NUL character at offset {offset}. Remove it from the parmfile, as the kernel ignores everything after it")]
+    KernelCmdlineNul { offset: usize },
+
+    #[error("The {kind} is empty. Provide a non-empty file or omit the {kind}")]
+    EmptyComponent { kind: String },
+
+    #[error("The {kind} ({size} bytes) does not fit into the Secure Execution image at {addr:#x}: {source}")]
+    ComponentLayout {
+        kind: String,
+        size: u64,
+        addr: u64,
+        source: Box<Error>,
+    },
+
     #[error("Cannot convert to short PSW")]
     TryToShortPSWError,
 
@@ -74,7 +94,7 @@ pub enum Error {
     #[error("Support for query UV host key hashes is not available")]
     UnavailableQueryUvKeyHashesSupport { source: PvCoreError },
 
-    #[error("ELF file found, but only raw binary kernels are supported.")]
+    #[error("ELF file found, but only raw binary kernels are supported. Use the kernel image (e.g. /boot/image), not vmlinux")]
     UnexpectedElfFile,
 
     #[error("Unexpected arithmetic overflow")]
@@ -97,7 +117,7 @@ pub enum Error {
     #[error("No host key document provided")]
     NoHostkey,
 
-    #[error("No s390x Linux kernel provided")]
+    #[error("No s390x Linux kernel provided. Use the kernel image (e.g. /boot/image) of an s390x Linux kernel")]
     NoS390Kernel,
 
     #[error("Expert mode is not enabled")]
@@ -58,7 +58,7 @@ impl Interval {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Layout {
     pub next_addr: u64,
     pub alignment: u64,
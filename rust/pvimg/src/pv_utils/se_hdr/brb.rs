@@ -29,7 +29,6 @@ use crate::{
             UvDataTrait,
         },
         uvdata_builder::{AeadCipherBuilderTrait, KeyExchangeBuilderTrait},
-        PSW,
     },
 };
 
@@ -142,9 +141,7 @@ pub trait SeHdrPubBuilderTrait {
 
 #[enum_dispatch(SeHdrData)]
 pub trait SeHdrConfBuilderTrait {
-    fn generate_cck(&self) -> Result<SymKey>;
     fn set_cck(&mut self, cck: Confidential<Vec<u8>>) -> Result<()>;
-    fn set_psw(&mut self, psw: &PSW);
     fn set_scf(&mut self, scf: &SecretControlFlagsV1) -> Result<()>;
 }
 
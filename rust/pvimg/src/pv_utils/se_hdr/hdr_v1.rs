@@ -4,15 +4,11 @@
 use std::mem::{size_of, size_of_val};
 
 use deku::{ctx::Endian, prelude::*};
-use openssl::{
-    nid::Nid,
-    pkey::{PKeyRef, Public},
-};
+use openssl::pkey::{PKeyRef, Public};
 use pv::request::{
-    gen_ec_key,
     openssl::pkey::{PKey, Private},
-    random_array, Aes256XtsKey, Confidential, EcPubKeyCoord, Encrypt, Keyslot, SymKey, SymKeyType,
-    Zeroize, SHA_512_HASH_LEN,
+    random_array, Aes256XtsKey, Confidential, EcPubKeyCoord, Encrypt, HostKeyType, Keyslot, SymKey,
+    SymKeyType, Zeroize, SHA_512_HASH_LEN,
 };
 use serde::{Serialize, Serializer};
 
@@ -335,7 +331,7 @@ impl KeyExchangeBuilderTrait for SeHdrDataV1 {
         aead_key: &SymKey,
         priv_key: &PKeyRef<Private>,
     ) -> Result<()> {
-        let keyslot = Keyslot::new(hostkey.to_owned());
+        let keyslot = Keyslot::try_new(hostkey.to_owned())?;
         let keyslot_bin = keyslot.encrypt(aead_key.value(), priv_key)?.try_into()?;
         let keyslot_bin_size = u32::try_from(size_of_val(&keyslot_bin)).unwrap();
         self.aad.keyslots.push(keyslot_bin);
@@ -353,7 +349,7 @@ impl KeyExchangeBuilderTrait for SeHdrDataV1 {
     }
 
     fn generate_private_key(&self) -> Result<PKey<Private>> {
-        Ok(gen_ec_key(Nid::SECP521R1)?)
+        Ok(HostKeyType::default().generate_key()?)
     }
 
     fn set_cust_public_key(&mut self, key: &PKeyRef<Private>) -> Result<()> {
@@ -395,10 +391,6 @@ impl KeyExchangeTrait for SeHdrDataV1 {
 }
 
 impl SeHdrConfBuilderTrait for SeHdrDataV1 {
-    fn set_psw(&mut self, psw: &PSW) {
-        self.data.value_mut().psw = psw.clone();
-    }
-
     fn set_scf(&mut self, scf: &SecretControlFlagsV1) -> Result<()> {
         self.data.value_mut().scf = scf.into();
         Ok(())
@@ -408,10 +400,6 @@ impl SeHdrConfBuilderTrait for SeHdrDataV1 {
         self.data.value_mut().cck = cck.try_into()?;
         Ok(())
     }
-
-    fn generate_cck(&self) -> Result<SymKey> {
-        Ok(SymKey::random(SymKeyType::Aes256Gcm)?)
-    }
 }
 
 impl SeHdrPubBuilderTrait for SeHdrDataV1 {
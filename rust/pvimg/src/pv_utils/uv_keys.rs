@@ -18,21 +18,6 @@ trait UvKeyHashTrait: AsRef<[u8]> {}
 #[derive(Debug, PartialEq, Eq)]
 pub struct UvKeyHashV1([u8; 32]);
 
-#[non_exhaustive]
-#[enum_dispatch]
-#[derive(PartialEq, Eq, Debug)]
-pub enum UvKeyHash {
-    UvKeyHashV1(UvKeyHashV1),
-}
-
-impl AsRef<[u8]> for UvKeyHash {
-    fn as_ref(&self) -> &[u8] {
-        match self {
-            Self::UvKeyHashV1(hash) => hash.as_ref(),
-        }
-    }
-}
-
 impl UvKeyHashV1 {
     pub fn new<T: AsRef<[u8]>>(data: T) -> Result<Self> {
         let array = try_copy_slice_to_array(data.as_ref())?;
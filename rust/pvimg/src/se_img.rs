@@ -168,6 +168,44 @@ impl<W: Write + Seek> SeImgBuilder<W> {
         self.expert_mode = true;
     }
 
+    /// Check that the given components fit into the image layout.
+    ///
+    /// Computes the layout of the prepared components before anything is written, so that layout
+    /// problems are reported for the offending component instead of failing late. The stream
+    /// positions of the components remain unchanged.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a component does not fit into the layout, or there
+    /// was an IO error.
+    pub(crate) fn check_layout(&self, components: &mut [Component]) -> Result<()> {
+        let mut layout = self.layout.clone();
+        let chunk_size: u64 = self.builder.chunk_size().try_into()?;
+        for comp in components {
+            let old_pos = comp.stream_position()?;
+            let input_size = std::io::copy(comp, &mut std::io::sink())?;
+            comp.seek(SeekFrom::Start(old_pos))?;
+
+            // Prepared components consist of at least one chunk
+            let size = round_up(input_size, chunk_size)?.max(chunk_size);
+            let addr = layout.next_addr;
+            let fits = match layout.max_size_of_chunk_at_addr(addr)? {
+                Some(max) if size > max.try_into()? => Err(Error::PreparedComponentTooLarge {
+                    output_size: size.try_into()?,
+                    max_output_size: max,
+                }),
+                _ => layout.insert_interval(addr, size).map(|_| ()),
+            };
+            fits.map_err(|e| Error::ComponentLayout {
+                kind: comp.kind().to_string(),
+                size: input_size,
+                addr,
+                source: Box::new(e),
+            })?;
+        }
+        Ok(())
+    }
+
     /// Prepare the given component as secured component, append it to the layout
     /// and write it to the output.
     ///
@@ -479,10 +517,13 @@ impl<W: Write + Seek> SeImgBuilder<W> {
 
 #[cfg(test)]
 mod tests {
-    use std::io::Cursor;
+    use std::io::{Cursor, Seek};
 
     use super::SeImgBuilder;
-    use crate::{se_img::stage3a_path, se_img_comps::stage3b_path};
+    use crate::{
+        se_img::stage3a_path,
+        se_img_comps::{cmdline::Cmdline, ramdisk::Ramdisk, stage3b_path, Component},
+    };
 
     #[test]
     fn test_comp_ctx_new() {
@@ -501,4 +542,24 @@ mod tests {
         assert!(ctx.builder.encryption_enabled());
         assert_eq!(ctx.comps, vec![]);
     }
+
+    #[test]
+    fn check_layout() {
+        // If the bootloader does not exist, we cannot test.
+        if !stage3a_path(None).exists() || !stage3b_path(None).exists() {
+            return;
+        }
+
+        let mut writer = Cursor::new(Vec::new());
+        let ctx = SeImgBuilder::new_v1(&mut writer, true, None, None).unwrap();
+        let mut comps: Vec<Component> = vec![
+            Ramdisk::new(Box::new(Cursor::new(vec![0; 0x1001]))).into(),
+            Cmdline::new(Box::new(Cursor::new(b"root=/dev/vda".to_vec()))).into(),
+        ];
+        ctx.check_layout(&mut comps).unwrap();
+        // The layout check must not consume the components
+        assert_eq!(comps[0].stream_position().unwrap(), 0);
+        // The layout of the builder is unchanged
+        assert_eq!(ctx.layout.next_addr, 0x13000);
+    }
 }
@@ -18,8 +18,8 @@ use self::{
     sehdr::SeHdrComp, shortpsw::ShortPSWComp, stage3a::Stage3a, stage3b::Stage3b,
 };
 pub use crate::se_img_comps::bootloader::{
-    create_ipib, render_stage3a, render_stage3b, stage3a_path, stage3b_path, STAGE3A_ENTRY,
-    STAGE3A_INIT_ENTRY, STAGE3A_LOAD_ADDRESS,
+    create_ipib, read_ipib, render_stage3a, render_stage3b, stage3a_path, stage3b_path,
+    STAGE3A_ENTRY, STAGE3A_INIT_ENTRY, STAGE3A_LOAD_ADDRESS,
 };
 use crate::se_img_comps::ipib::Ipib;
 
@@ -311,8 +311,15 @@ mod tests {
         prop_assert_eq, prop_oneof, proptest,
     };
 
+    use std::io::Cursor;
+
+    use pvimg::error::Error;
+
     use super::{ComponentCheckCtx, ComponentKind};
-    use crate::se_img_comps::{check_components, kernel::S390Kernel, CompTweakPrefV1, CompTweakV1};
+    use crate::se_img_comps::{
+        check_components, cmdline::Cmdline, kernel::S390Kernel, ramdisk::Ramdisk, CompTweakPrefV1,
+        CompTweakV1, Component,
+    };
 
     fn component_kind_strategy() -> impl Strategy<Value = ComponentKind> {
         prop_oneof![
@@ -350,6 +357,36 @@ mod tests {
         check_components(&mut []).unwrap();
     }
 
+    #[test]
+    fn check_components_invalid() {
+        let comp = |kind: ComponentKind, data: &[u8]| -> Component {
+            let reader = Box::new(Cursor::new(data.to_vec()));
+            match kind {
+                ComponentKind::Kernel => S390Kernel::new(reader).into(),
+                ComponentKind::Ramdisk => Ramdisk::new(reader).into(),
+                _ => Cmdline::new(reader).into(),
+            }
+        };
+        let err = |comps: &mut [Component]| {
+            check_components(comps)
+                .unwrap_err()
+                .root_cause()
+                .downcast_ref::<Error>()
+                .map(|e| format!("{e:?}"))
+                .unwrap()
+        };
+
+        assert!(err(&mut [comp(ComponentKind::Kernel, b"\x7fELF")]).starts_with("NoS390Kernel"));
+        assert!(err(&mut [comp(ComponentKind::Ramdisk, b"")]).starts_with("EmptyComponent"));
+        assert!(err(&mut [comp(ComponentKind::Cmdline, b"a\0b")]).starts_with("KernelCmdlineNul"));
+        check_components(&mut [comp(ComponentKind::Cmdline, b"root=/dev/vda\0")]).unwrap();
+        assert!(err(&mut [comp(
+            ComponentKind::Cmdline,
+            &[b'a'; S390Kernel::LEGACY_MAX_COMMAND_LINE_SIZE]
+        )])
+        .starts_with("KernelCmdlineTooLarge"));
+    }
+
     #[test]
     fn comptweak_v1() {
         let tweak = CompTweakV1 {
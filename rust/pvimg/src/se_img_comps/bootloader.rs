@@ -7,6 +7,7 @@ use std::{io::Cursor, path::PathBuf, rc::Rc};
 pub mod ipl;
 mod stage3a_defs;
 mod stage3b_defs;
+use deku::DekuContainerRead;
 use ipl::IPL_PARM_BLOCK_PV_VERSION;
 use log::trace;
 use pvimg::{
@@ -70,6 +71,20 @@ pub fn stage3b_path(dir: Option<&PathBuf>) -> PathBuf {
     bootloader_dir(dir).join("stage3b_reloc.bin")
 }
 
+/// Parse the IPIB of a Secure Execution image.
+///
+/// Only IPIBs of type PV are accepted.
+pub fn read_ipib(data: &[u8]) -> Result<ipl_parameter_block> {
+    let (_, ipib) = ipl_parameter_block::from_bytes((data, 0)).map_err(|_| Error::InvalidIpib)?;
+    if ipib.pv.pbt != ipl_pbt_IPL_PBT_PV
+        || ipib.hdr.version != IPL_PARM_BLOCK_VERSION
+        || ipib.pv.version != IPL_PARM_BLOCK_PV_VERSION
+    {
+        return Err(Error::InvalidIpib);
+    }
+    Ok(ipib)
+}
+
 /// Render stage3b "template"
 pub fn render_stage3a(
     mut stage3a: Vec<u8>,
@@ -71,6 +71,14 @@ impl ComponentCheckTrait for Cmdline {
                 max_size: ctx.max_kernel_cmdline_size,
             });
         }
+        // `read` ensures that there is a trailing NUL-terminator
+        if let Some(offset) = buf
+            .iter()
+            .position(|b| *b == b'\0')
+            .filter(|o| *o + 1 < size)
+        {
+            return Err(Error::KernelCmdlineNul { offset });
+        }
 
         Ok(())
     }
@@ -19,7 +19,6 @@ impl S390Kernel {
     const ELF_MAGIC_OFF: u64 = 0x0;
     const ELF_MAGIC_SIZE: usize = 4;
     const KERNEL_COMMAND_LINE_SIZE_ADDR: u64 = 0x10430;
-    const KERNEL_COMMAND_LINE_SIZE_LEN: usize = 8;
     pub const KERNEL_ENTRY: u64 = 0x10000;
     pub const LEGACY_MAX_COMMAND_LINE_SIZE: usize = 896;
     const S390EP: [u8; Self::S390EP_SIZE] = [0x53, 0x33, 0x39, 0x30, 0x45, 0x50];
@@ -31,27 +30,27 @@ impl S390Kernel {
         Self(CompReader { reader })
     }
 
+    /// Reads the bytes at `offset`. A file that is too small is no s390x Linux kernel.
+    fn read_at<const N: usize>(&mut self, offset: u64) -> Result<[u8; N]> {
+        self.seek(SeekFrom::Start(offset))?;
+        let mut buf = [0x0_u8; N];
+        self.read_exact(&mut buf).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => Error::NoS390Kernel,
+            _ => e.into(),
+        })?;
+        Ok(buf)
+    }
+
     fn is_elf_file(&mut self) -> Result<bool> {
-        self.seek(SeekFrom::Start(Self::ELF_MAGIC_OFF))?;
-        let mut buf = [0x0_u8; Self::ELF_MAGIC_SIZE];
-        self.read_exact(&mut buf)?;
-        Ok(buf == Self::ELF_MAGIC)
+        Ok(self.read_at::<{ Self::ELF_MAGIC_SIZE }>(Self::ELF_MAGIC_OFF)? == Self::ELF_MAGIC)
     }
 
     fn is_s390x_kernel(&mut self) -> Result<bool> {
-        self.seek(SeekFrom::Start(Self::S390EP_OFFS))?;
-        let mut buf = [0_u8; Self::S390EP_SIZE];
-        self.read_exact(&mut buf)?;
-        Ok(buf == Self::S390EP)
+        Ok(self.read_at::<{ Self::S390EP_SIZE }>(Self::S390EP_OFFS)? == Self::S390EP)
     }
 
     fn read_max_kernel_cmdline_size(&mut self) -> Result<usize> {
-        self.seek(SeekFrom::Start(Self::KERNEL_COMMAND_LINE_SIZE_ADDR))?;
-        let mut buf = [0x0_u8; Self::KERNEL_COMMAND_LINE_SIZE_LEN];
-        self.read_exact(&mut buf).map_err(|e| match e.kind() {
-            std::io::ErrorKind::UnexpectedEof => Error::NoS390Kernel,
-            _ => e.into(),
-        })?;
+        let buf = self.read_at(Self::KERNEL_COMMAND_LINE_SIZE_ADDR)?;
         let mut max_size = u64::from_be_bytes(buf).try_into()?;
         if max_size == 0 {
             max_size = Self::LEGACY_MAX_COMMAND_LINE_SIZE;
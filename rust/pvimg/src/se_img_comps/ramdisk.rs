@@ -2,9 +2,16 @@
 //
 // Copyright IBM Corp. 2024
 
-use std::io::{Read, Seek};
+use std::{
+    fmt::Display,
+    io::{Read, Seek, SeekFrom},
+};
 
-use pvimg::error::Result;
+use log::info;
+use pvimg::{
+    error::{Error, Result},
+    misc::round_up,
+};
 
 use super::ComponentKind;
 use super::{CompReader, ComponentCheckCtx, ComponentCheckTrait, ComponentTrait, ReadSeekDebug};
@@ -18,6 +25,204 @@ impl Ramdisk {
     }
 }
 
+/// Alignment of the archives in a composed ramdisk
+///
+/// The kernel expects an uncompressed cpio archive to start at a 4-byte boundary and skips the
+/// zero padding between the archives.
+const ARCHIVE_ALIGNMENT: u64 = 4;
+/// Number of bytes at the end of an archive that are checked for its end marker
+const TAIL_SIZE: u64 = 512;
+
+/// Format of an initramfs archive as detected by the kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Cpio,
+    Gzip,
+    Bzip2,
+    Lzma,
+    Xz,
+    Lzo,
+    Lz4,
+    Zstd,
+}
+
+impl ArchiveFormat {
+    /// Detect the format by the magic at the start of the archive.
+    fn detect(magic: &[u8]) -> Option<Self> {
+        const MAGICS: [(&[u8], ArchiveFormat); 9] = [
+            (b"070701", ArchiveFormat::Cpio),
+            (b"070702", ArchiveFormat::Cpio),
+            (&[0x1f, 0x8b], ArchiveFormat::Gzip),
+            (b"BZh", ArchiveFormat::Bzip2),
+            (&[0x5d, 0x00, 0x00], ArchiveFormat::Lzma),
+            (&[0xfd, b'7', b'z', b'X', b'Z', 0x00], ArchiveFormat::Xz),
+            (&[0x89, b'L', b'Z', b'O'], ArchiveFormat::Lzo),
+            (&[0x02, 0x21, 0x4c, 0x18], ArchiveFormat::Lz4),
+            (&[0x28, 0xb5, 0x2f, 0xfd], ArchiveFormat::Zstd),
+        ];
+        MAGICS
+            .iter()
+            .find(|(m, _)| magic.starts_with(m))
+            .map(|(_, f)| *f)
+    }
+
+    /// Check the end of the archive, without trailing zeros, if the format has an end marker.
+    fn check_tail(self, tail: &[u8]) -> std::result::Result<(), &'static str> {
+        match self {
+            // The kernel continues with the next archive after the trailer entry. Without the
+            // trailer, the headers of the next archive would be taken as file data.
+            Self::Cpio if !tail.ends_with(b"TRAILER!!!") => {
+                Err("The cpio archive does not end with a trailer entry")
+            }
+            Self::Xz if !tail.ends_with(b"YZ") => Err("The xz stream is truncated"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Display for ArchiveFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Cpio => "uncompressed cpio",
+            Self::Gzip => "gzip",
+            Self::Bzip2 => "bzip2",
+            Self::Lzma => "lzma",
+            Self::Xz => "xz",
+            Self::Lzo => "lzo",
+            Self::Lz4 => "lz4",
+            Self::Zstd => "zstd",
+        })
+    }
+}
+
+/// Determine the format of the archive `reader` and check that it ends at an archive boundary.
+///
+/// Returns the format and the size of the archive.
+fn check_archive(name: &str, reader: &mut dyn ReadSeekDebug) -> Result<(ArchiveFormat, u64)> {
+    let invalid = |reason: &str| Error::InvalidInitramfs {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    };
+    let size = reader.seek(SeekFrom::End(0))?;
+    if size == 0 {
+        return Err(invalid("The archive is empty"));
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    let mut magic = Vec::with_capacity(6);
+    Read::take(&mut *reader, 6).read_to_end(&mut magic)?;
+    let format = ArchiveFormat::detect(&magic).ok_or_else(|| {
+        invalid("Unknown archive format. Use an uncompressed newc cpio archive or a compressed one")
+    })?;
+
+    let tail_start = size.saturating_sub(TAIL_SIZE);
+    reader.seek(SeekFrom::Start(tail_start))?;
+    let mut tail = Vec::with_capacity(TAIL_SIZE as usize);
+    reader.read_to_end(&mut tail)?;
+    let end = tail.iter().rposition(|b| *b != 0).map_or(0, |p| p + 1);
+    format.check_tail(&tail[..end]).map_err(invalid)?;
+    reader.seek(SeekFrom::Start(0))?;
+    Ok((format, size))
+}
+
+/// An archive of a [`ConcatReader`]
+#[derive(Debug)]
+struct Archive {
+    reader: Box<dyn ReadSeekDebug>,
+    /// Offset of the archive in the ramdisk
+    start: u64,
+    size: u64,
+}
+
+/// Concatenation of archives, each padded with zeros to [`ARCHIVE_ALIGNMENT`]
+#[derive(Debug)]
+struct ConcatReader {
+    archives: Vec<Archive>,
+    size: u64,
+    pos: u64,
+}
+
+impl ConcatReader {
+    fn new(archives: Vec<(Box<dyn ReadSeekDebug>, u64)>) -> Result<Self> {
+        let mut start = 0;
+        let mut parts = Vec::with_capacity(archives.len());
+        for (reader, size) in archives {
+            start = round_up(start, ARCHIVE_ALIGNMENT)?;
+            parts.push(Archive {
+                reader,
+                start,
+                size,
+            });
+            start = start.checked_add(size).ok_or(Error::UnexpectedOverflow)?;
+        }
+        Ok(Self {
+            archives: parts,
+            size: start,
+            pos: 0,
+        })
+    }
+}
+
+impl Read for ConcatReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let pos = self.pos;
+        // the last archive that starts at or before the position
+        let Some(archive) = self.archives.iter_mut().rev().find(|a| a.start <= pos) else {
+            return Ok(0);
+        };
+        let offset = pos - archive.start;
+        let read = if offset < archive.size {
+            let len = buf.len().min((archive.size - offset) as usize);
+            archive.reader.seek(SeekFrom::Start(offset))?;
+            archive.reader.read(&mut buf[..len])?
+        } else {
+            // zero padding up to the next archive; nothing after the last one
+            let len = buf.len().min((self.size.saturating_sub(pos)) as usize);
+            let len = len.min((round_up(pos, ARCHIVE_ALIGNMENT).unwrap_or(pos) - pos) as usize);
+            buf[..len].fill(0);
+            len
+        };
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for ConcatReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(off) => self.size.checked_add_signed(off),
+            SeekFrom::Current(off) => self.pos.checked_add_signed(off),
+        };
+        self.pos = pos.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid seek position")
+        })?;
+        Ok(self.pos)
+    }
+}
+
+impl Ramdisk {
+    /// Compose a ramdisk from the initramfs archives `archives`, e.g. a base initramfs and
+    /// overlay archives.
+    ///
+    /// The archives are concatenated in the given order, each starting at a 4-byte boundary,
+    /// which the kernel unpacks one after the other. Each archive must be an uncompressed newc
+    /// cpio archive or a compressed archive; later archives overwrite files of earlier ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidInitramfs`] if an archive is empty, has an unknown format, or does
+    /// not end at an archive boundary.
+    pub fn compose(archives: Vec<(String, Box<dyn ReadSeekDebug>)>) -> Result<Self> {
+        let mut parts = Vec::with_capacity(archives.len());
+        for (name, mut reader) in archives {
+            let (format, size) = check_archive(&name, &mut reader)?;
+            info!("Adding '{name}' ({format}, {size} bytes) to the ramdisk");
+            parts.push((reader, size));
+        }
+        Ok(Self::new(Box::new(ConcatReader::new(parts)?)))
+    }
+}
+
 impl Read for Ramdisk {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         self.0.read(buf)
@@ -25,13 +230,18 @@ impl Read for Ramdisk {
 }
 
 impl Seek for Ramdisk {
-    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
         self.0.seek(pos)
     }
 }
 
 impl ComponentCheckTrait for Ramdisk {
     fn check(&mut self, _ctx: &ComponentCheckCtx) -> Result<()> {
+        if self.seek(SeekFrom::End(0))? == 0 {
+            return Err(Error::EmptyComponent {
+                kind: self.kind().to_string(),
+            });
+        }
         Ok(())
     }
 
@@ -49,3 +259,83 @@ impl ComponentTrait<ComponentKind> for Ramdisk {
         true
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// A minimal newc cpio archive with only the trailer entry.
+    fn cpio(pad: usize) -> Vec<u8> {
+        let mut data = b"070701".to_vec();
+        data.extend([b'0'; 88]);
+        data.extend(b"0000000B00000000TRAILER!!!\0");
+        data.extend(vec![0; pad]);
+        data
+    }
+
+    fn archive(data: Vec<u8>) -> Box<dyn ReadSeekDebug> {
+        Box::new(Cursor::new(data))
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(ArchiveFormat::detect(&cpio(0)), Some(ArchiveFormat::Cpio));
+        assert_eq!(
+            ArchiveFormat::detect(&[0x1f, 0x8b, 8, 0]),
+            Some(ArchiveFormat::Gzip)
+        );
+        assert_eq!(
+            ArchiveFormat::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0]),
+            Some(ArchiveFormat::Zstd)
+        );
+        assert_eq!(ArchiveFormat::detect(b"\x7fELF"), None);
+    }
+
+    #[test]
+    fn compose() {
+        let base = cpio(1);
+        let gzip = vec![0x1f, 0x8b, 8, 0, 1];
+        let overlay = cpio(0);
+        let mut ramdisk = Ramdisk::compose(vec![
+            ("base".to_string(), archive(base.clone())),
+            ("gzip".to_string(), archive(gzip.clone())),
+            ("overlay".to_string(), archive(overlay.clone())),
+        ])
+        .unwrap();
+
+        let mut exp = base.clone();
+        exp.extend([0; 2]);
+        exp.extend(&gzip);
+        exp.extend([0; 3]);
+        exp.extend(&overlay);
+        let mut data = vec![];
+        ramdisk.read_to_end(&mut data).unwrap();
+        assert_eq!(data, exp);
+        assert_eq!(ramdisk.seek(SeekFrom::End(0)).unwrap(), exp.len() as u64);
+
+        ramdisk.seek(SeekFrom::Start(base.len() as u64)).unwrap();
+        let mut buf = [0xff; 4];
+        ramdisk.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 0, 0x1f, 0x8b]);
+    }
+
+    #[test]
+    fn compose_invalid() {
+        let err = |data: Vec<u8>| {
+            Ramdisk::compose(vec![
+                ("base".to_string(), archive(cpio(0))),
+                ("overlay".to_string(), archive(data)),
+            ])
+            .unwrap_err()
+            .to_string()
+        };
+        assert!(err(vec![]).contains("empty"), "{}", err(vec![]));
+        assert!(err(b"\x7fELF".to_vec()).contains("Unknown archive format"));
+        let mut truncated = cpio(0);
+        truncated.truncate(100);
+        assert!(err(truncated).contains("trailer entry"));
+        assert!(err(vec![0xfd, b'7', b'z', b'X', b'Z', 0, 1]).contains("xz stream"));
+    }
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use clap::{Parser, ValueEnum};
+use utils::VerbosityOptions;
+
+/// Report the IBM Secure Execution capabilities of this system.
+///
+/// Merges the information of the uvdevice and the Ultravisor query information from
+/// '/sys/firmware/uv' into one report. Include this report when reporting problems with IBM
+/// Secure Execution features.
+#[derive(Parser, Debug)]
+pub struct CliOptions {
+    #[clap(flatten)]
+    pub verbosity: VerbosityOptions,
+
+    /// Define the output format.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+
+    /// Print version information and exit.
+    #[arg(long)]
+    pub version: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
+pub enum OutputFormat {
+    /// Human-focused, non-parsable output format.
+    #[default]
+    Human,
+    /// Use JSON format.
+    Json,
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        super::CliOptions::command().debug_assert()
+    }
+}
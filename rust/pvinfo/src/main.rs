@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+mod cli;
+mod report;
+
+use std::{io::Write, process::ExitCode};
+
+use clap::{CommandFactory, Parser};
+use cli::{CliOptions, OutputFormat};
+use log::trace;
+use report::Report;
+use utils::{print_cli_error, print_error, print_version, PvLogger};
+
+static LOGGER: PvLogger = PvLogger;
+static EXIT_LOGGER: u8 = 3;
+
+fn run(cli: &CliOptions) -> anyhow::Result<()> {
+    let report = Report::gather();
+    let mut out = std::io::stdout().lock();
+    match cli.format {
+        OutputFormat::Human => write!(out, "{report}")?,
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &report)?;
+            writeln!(out)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli: CliOptions = match CliOptions::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => return print_cli_error(e, CliOptions::command()),
+    };
+
+    // set up logger/std(out,err)
+    let log_level = cli.verbosity.to_level_filter();
+    if let Err(e) = LOGGER.start_with_format(log_level, cli.verbosity.log_format()) {
+        // should(TM) never happen
+        eprintln!("Logger error: {e:?}");
+        return EXIT_LOGGER.into();
+    }
+
+    if cli.version {
+        print_version!("2025", log_level);
+        return ExitCode::SUCCESS;
+    }
+
+    trace!("Options {cli:?}");
+
+    match run(&cli) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => print_error(&e, log_level),
+    }
+}
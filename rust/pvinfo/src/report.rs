@@ -0,0 +1,235 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::fmt::{self, Display};
+
+use pv::{
+    attest::AttestationFlags,
+    env::se_host_capable,
+    misc::{pv_guest_bit_set, Flags},
+    uv::{ListableSecretType, UvDeviceInfo, UvQueryInfo},
+};
+use serde::Serialize;
+
+/// Commands of the uvdevice, or why they are not available
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum UvdevReport {
+    SupportedCommands(Vec<&'static str>),
+    /// The uvdevice is not available; commands of the Ultravisor according to sysfs
+    ReadOnly(Vec<&'static str>),
+    Error(String),
+}
+
+impl From<pv::PvCoreError> for UvdevReport {
+    fn from(e: pv::PvCoreError) -> Self {
+        Self::Error(e.to_string())
+    }
+}
+
+impl From<UvDeviceInfo> for UvdevReport {
+    fn from(info: UvDeviceInfo) -> Self {
+        match info.is_read_only() {
+            true => Self::ReadOnly(info.supported_cmds()),
+            false => Self::SupportedCommands(info.supported_cmds()),
+        }
+    }
+}
+
+/// Secure Execution capabilities of this system
+#[derive(Debug, Serialize)]
+pub struct Report {
+    se_guest: bool,
+    se_host: bool,
+    uvdevice: UvdevReport,
+    installed_uv_calls: Option<Vec<String>>,
+    uv_features: Option<Vec<String>>,
+    attestation_additional_data: Option<Vec<String>>,
+    secret_types: Option<Vec<String>>,
+    max_secrets: Option<u32>,
+    max_association_secrets: Option<u32>,
+    max_retrievable_secrets: Option<u32>,
+}
+
+impl Report {
+    /// Gather the report from the uvdevice and sysfs.
+    ///
+    /// Information that is not available, e.g. because this is no Secure Execution guest, is
+    /// reported as such.
+    pub fn gather() -> Self {
+        let uvdevice = UvDeviceInfo::query().map_or_else(UvdevReport::from, UvdevReport::from);
+        Self::new(
+            pv_guest_bit_set(),
+            se_host_capable(),
+            uvdevice,
+            &UvQueryInfo::read(),
+        )
+    }
+
+    fn new(se_guest: bool, se_host: bool, uvdevice: UvdevReport, query: &UvQueryInfo) -> Self {
+        let attestation_additional_data = AttestationFlags::uv_supported(query).map(|f| {
+            f.additional_data_selection()
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        });
+        let secret_types = query.supp_secret_types().map(|types| {
+            (0u8..64)
+                .filter(|bit| types.is_set(*bit))
+                .map(|bit| ListableSecretType::from(bit as u16).to_string())
+                .collect()
+        });
+        Self {
+            se_guest,
+            se_host,
+            uvdevice,
+            installed_uv_calls: query.installed_calls(),
+            uv_features: query.features(),
+            attestation_additional_data,
+            secret_types,
+            max_secrets: query.max_secrets(),
+            max_association_secrets: query.max_assoc_secrets(),
+            max_retrievable_secrets: query.max_retr_secrets(),
+        }
+    }
+}
+
+fn yes_no(v: bool) -> &'static str {
+    match v {
+        true => "yes",
+        false => "no",
+    }
+}
+
+fn write_list<T: Display>(f: &mut fmt::Formatter<'_>, name: &str, v: Option<&[T]>) -> fmt::Result {
+    match v {
+        None => writeln!(f, "{name}: unknown"),
+        Some([]) => writeln!(f, "{name}: none"),
+        Some(v) => {
+            writeln!(f, "{name}:")?;
+            v.iter().try_for_each(|i| writeln!(f, "  {i}"))
+        }
+    }
+}
+
+fn fmt_opt(v: Option<u32>) -> String {
+    v.map_or_else(|| "unknown".to_string(), |v| v.to_string())
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Secure Execution guest: {}", yes_no(self.se_guest))?;
+        writeln!(f, "Secure Execution host: {}", yes_no(self.se_host))?;
+        match &self.uvdevice {
+            UvdevReport::SupportedCommands(cmds) => {
+                write_list(f, "uvdevice commands", Some(cmds.as_slice()))?
+            }
+            UvdevReport::ReadOnly(cmds) => {
+                writeln!(f, "uvdevice: not available")?;
+                write_list(
+                    f,
+                    "uvdevice commands (read-only, from sysfs)",
+                    Some(cmds.as_slice()),
+                )?
+            }
+            UvdevReport::Error(e) => writeln!(f, "uvdevice: not available ({e})")?,
+        }
+        write_list(
+            f,
+            "Installed Ultravisor calls",
+            self.installed_uv_calls.as_deref(),
+        )?;
+        write_list(f, "Ultravisor features", self.uv_features.as_deref())?;
+        write_list(
+            f,
+            "Attestation additional data",
+            self.attestation_additional_data.as_deref(),
+        )?;
+        write_list(f, "Secret types", self.secret_types.as_deref())?;
+        writeln!(
+            f,
+            "Maximum number of secrets: {}",
+            fmt_opt(self.max_secrets)
+        )?;
+        writeln!(
+            f,
+            "  Association secrets: {}",
+            fmt_opt(self.max_association_secrets)
+        )?;
+        writeln!(
+            f,
+            "  Retrievable secrets: {}",
+            fmt_opt(self.max_retrievable_secrets)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn no_se() {
+        let report = Report::new(
+            false,
+            false,
+            UvdevReport::Error("No such file or directory".to_string()),
+            &UvQueryInfo::default(),
+        );
+        assert_eq!(
+            report.to_string(),
+            "Secure Execution guest: no
+Secure Execution host: no
+uvdevice: not available (No such file or directory)
+Installed Ultravisor calls: unknown
+Ultravisor features: unknown
+Attestation additional data: unknown
+Secret types: unknown
+Maximum number of secrets: unknown
+  Association secrets: unknown
+  Retrievable secrets: unknown
+"
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["uvdevice"]["error"], "No such file or directory");
+        assert!(json["installed_uv_calls"].is_null());
+    }
+
+    #[test]
+    fn uvdevice() {
+        let report = Report::new(
+            true,
+            false,
+            UvdevReport::SupportedCommands(vec!["Info", "Attestation"]),
+            &UvQueryInfo::default(),
+        );
+        assert!(report
+            .to_string()
+            .contains("uvdevice commands:\n  Info\n  Attestation\n"));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["se_guest"], true);
+        assert_eq!(
+            json["uvdevice"]["supported_commands"],
+            serde_json::json!(["Info", "Attestation"])
+        );
+    }
+
+    #[test]
+    fn read_only() {
+        let report = Report::new(
+            true,
+            false,
+            UvdevReport::ReadOnly(vec!["Attestation"]),
+            &UvQueryInfo::default(),
+        );
+        assert!(report.to_string().contains(
+            "uvdevice: not available\nuvdevice commands (read-only, from sysfs):\n  Attestation\n"
+        ));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json["uvdevice"]["read_only"],
+            serde_json::json!(["Attestation"])
+        );
+    }
+}
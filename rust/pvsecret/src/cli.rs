@@ -2,11 +2,15 @@
 //
 // Copyright IBM Corp. 2023, 2024
 
-use std::fmt::Display;
+use std::{fmt::Display, os::fd::RawFd};
 
 use clap::error::ErrorKind::ValueValidation;
 use clap::{ArgGroup, Args, CommandFactory, Parser, Subcommand, ValueEnum, ValueHint};
-use utils::{CertificateOptions, DeprecatedVerbosityOptions, STDOUT};
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+use utils::{AuditOptions, PrivilegeOptions};
+use utils::{
+    CertificateOptions, DeprecatedVerbosityOptions, GenerateOptions, SandboxOptions, STDOUT,
+};
 
 /// Manage secrets for IBM Secure Execution guests.
 ///
@@ -22,6 +26,12 @@ pub struct CliOptions {
     #[arg(long)]
     pub version: bool,
 
+    /// Require OpenSSL to be in FIPS mode and use FIPS-approved algorithms only.
+    ///
+    /// Fails if OpenSSL is not in FIPS mode.
+    #[arg(long, global = true)]
+    pub fips: bool,
+
     #[command(subcommand)]
     pub cmd: Command,
 }
@@ -137,9 +147,11 @@ pub struct CreateSecretOpt {
     /// 3072-bit and EC(secp521r1) keys. The firmware ignores the content, but the request tag
     /// protects the signature. The user-signing key signs the request. The location of the
     /// signature is filled with zeros during the signature calculation. The request tag also
-    /// secures the signature. See man pvsecret verify for more details. Optional. No signature
-    /// by default.
-    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    /// secures the signature. See man pvsecret verify for more details. Instead of a FILE, a
+    /// PKCS#11 URI (RFC 7512, starting with 'pkcs11:') can be specified to use a key held in a
+    /// token or HSM. This requires an OpenSSL configuration that loads the pkcs11-provider.
+    /// Optional. No signature by default.
+    #[arg(long, value_name = "FILE|URI", value_hint = ValueHint::FilePath,)]
     pub user_sign_key: Option<String>,
 
     /// Do not hash the name, use it directly as secret ID.
@@ -175,7 +187,7 @@ pub enum AddSecretType {
         stdout: bool,
 
         /// Path from which to read the plaintext secret. Uses a random secret if not specified.
-        #[arg(long, value_name = "SECRET-FILE", value_hint = ValueHint::FilePath, conflicts_with("output_secret"))]
+        #[arg(long, value_name = "SECRET-FILE", value_hint = ValueHint::FilePath, conflicts_with("secret_out"))]
         input_secret: Option<String>,
 
         /// Save the generated secret as plaintext in SECRET-FILE.
@@ -183,8 +195,25 @@ pub enum AddSecretType {
         /// The generated secret can be used to generate add-secret requests for a different guest
         /// with the same secret using '--input-secret'. Destroy the secret when it is not used
         /// anymore.
-        #[arg(long, value_name = "SECRET-FILE", value_hint = ValueHint::FilePath,)]
+        #[arg(long, value_name = "SECRET-FILE", value_hint = ValueHint::FilePath, group = "secret_out")]
         output_secret: Option<String>,
+
+        /// Write the generated secret to the open file descriptor FD.
+        ///
+        /// Like '--output-secret', but without a file on disk, e.g., '--output-secret-fd 3' with
+        /// the file descriptor 3 connected to a pipe.
+        #[arg(long, value_name = "FD", group = "secret_out")]
+        output_secret_fd: Option<RawFd>,
+
+        /// Write the generated secret in FORMAT.
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            value_enum,
+            default_value_t,
+            requires = "secret_out"
+        )]
+        output_secret_format: SecretOutputFormat,
     },
 
     /// Create a retrievable secret.
@@ -227,6 +256,68 @@ pub enum AddSecretType {
         #[arg(long, value_name = "CCK-FILE", value_hint = ValueHint::FilePath)]
         secret: String,
     },
+
+    /// Create add-secret requests for all secrets of a manifest.
+    ///
+    /// MANIFEST is a YAML file that lists the secrets with their name, type, source, flags, and
+    /// the file name of the request. The requests are written into the directory given by
+    /// '--output'. All other options, such as the host-key documents, the SE-header, and the
+    /// Config-UID, apply to all requests. Prints the status of every secret. See the MANIFEST
+    /// section of the man page for the format.
+    Manifest {
+        /// The manifest of the secrets.
+        #[arg(value_name = "MANIFEST", value_hint = ValueHint::FilePath)]
+        manifest: String,
+
+        #[command(flatten)]
+        manifest_args: ManifestOptions,
+    },
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("manifest-verify").args(["manifest_key", "no_manifest_verify"])))]
+pub struct ManifestOptions {
+    /// Verify the signature of the manifest with the public key in FILE.
+    ///
+    /// FILE contains an X.509 certificate or a public key in PEM or DER format. Supported are EC
+    /// and RSA keys. The detached signature must be calculated over the manifest with SHA-512; RSA
+    /// signatures must use PSS padding. Either this option or '--no-manifest-verify' is required.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub manifest_key: Option<String>,
+
+    /// Read the detached signature of the manifest from FILE.
+    ///
+    /// Defaults to the manifest path with the suffix '.sig'.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, requires = "manifest_key")]
+    pub manifest_sig: Option<String>,
+
+    /// Do not verify the signature of the manifest.
+    ///
+    /// Only use this option for manifests from a trusted source.
+    #[arg(long)]
+    pub no_manifest_verify: bool,
+
+    /// Process the secrets of the manifest only if all of them can be processed.
+    ///
+    /// Without this option, a failing secret does not stop the processing of the others.
+    #[arg(long)]
+    pub all_or_nothing: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
+pub enum SecretOutputFormat {
+    /// The plain secret bytes.
+    #[default]
+    Raw,
+    /// The secret as hex string.
+    Hex,
+    /// The secret as (standard, padded) base64 string.
+    Base64,
+    /// A JSON object with the name, the ID, and the hex-encoded secret.
+    Json,
+    /// Shell variable assignments of the name, the ID, and the hex-encoded secret, e.g., for
+    /// '.env' files.
+    Dotenv,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
@@ -269,9 +360,24 @@ impl Display for RetrieveableSecretInpKind {
 #[derive(Args, Debug)]
 pub struct AddSecretOpt {
     /// Specify the request to be sent.
-    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath,)]
-    #[cfg(target_arch = "s390x")]
-    pub input: String,
+    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath, required_unless_present = "manifest")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    pub input: Option<String>,
+
+    /// Add the requests of all secrets of the manifest in FILE.
+    ///
+    /// The requests must be in the directory of the manifest. Checks that every request contains
+    /// the secret type, the ID of the name, and the flags that the manifest specifies. Prints the
+    /// status of every secret. With '--all-or-nothing', all requests are checked like with
+    /// '--dry-run' before the first one is added, and the first failing request stops the
+    /// processing. Secrets that were added cannot be removed.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, conflicts_with = "input")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    pub manifest: Option<String>,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub manifest_args: ManifestOptions,
 
     /// Force the addition of add-secret requests.
     ///
@@ -279,10 +385,34 @@ pub struct AddSecretOpt {
     /// store.
     #[arg(short, long)]
     pub force: bool,
+
+    /// Only validate the request, do not add the secret.
+    ///
+    /// Parse the request and check the size limits, the Ultravisor support, the free capacity of
+    /// the secret store, and duplicated IDs. Report what would happen without sending the Add
+    /// Secret UVC.
+    #[arg(long)]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    pub dry_run: bool,
+
+    /// Retry the Add Secret UVC up to N times if it fails temporarily.
+    ///
+    /// The UVC is retried with an increasing delay if, for example, a dump is in progress.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    pub retries: u32,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub audit: AuditOptions,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub privileges: PrivilegeOptions,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
-#[cfg(target_arch = "s390x")]
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 pub enum ListSecretOutputType {
     /// Human-focused, non-parsable output format
     #[default]
@@ -291,6 +421,8 @@ pub enum ListSecretOutputType {
     Yaml,
     /// Use the format the ultravisor uses to pass the list.
     Bin,
+    /// Use the binary format of the C tools, the ultravisor format zero-padded to 32 KiB.
+    CBin,
 }
 
 // all members s390x only
@@ -298,13 +430,54 @@ pub enum ListSecretOutputType {
 pub struct ListSecretOpt {
     /// Store the result in FILE
     #[arg(value_name = "FILE", default_value = STDOUT, value_hint = ValueHint::FilePath,)]
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     pub output: String,
 
     /// Define the output format of the list.
     #[arg(long, value_enum, default_value_t)]
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     pub format: ListSecretOutputType,
+
+    /// Annotate the secrets with the names from FILE.
+    ///
+    /// FILE is a yaml list of entries. An entry is either a secret name or a mapping with a
+    /// `name` and an `id`, like the secret information written by `pvsecret create`. A listed
+    /// secret is annotated with a name if its ID matches the given ID or the ID derived from the
+    /// name. Can be specified multiple times.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    pub names: Vec<String>,
+
+    /// Read the list from FILE instead of the Ultravisor.
+    ///
+    /// FILE must contain a list in the 'bin' or 'c-bin' format, e.g. written by 'pvsecret list'
+    /// or by the C tools. Use this option to convert between the formats.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    pub input: Option<String>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InfoOutputType {
+    /// Human-focused, non-parsable output format
+    #[default]
+    Human,
+    /// Use yaml format.
+    Yaml,
+}
+
+// all members s390x only
+#[derive(Args, Debug)]
+pub struct InfoOpt {
+    /// Store the result in FILE
+    #[arg(value_name = "FILE", default_value = STDOUT, value_hint = ValueHint::FilePath,)]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    pub output: String,
+
+    /// Define the output format of the information.
+    #[arg(long, value_enum, default_value_t)]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    pub format: InfoOutputType,
 }
 
 #[derive(Args, Debug)]
@@ -329,6 +502,36 @@ pub struct VerifyOpt {
     /// zeros if available.
     #[arg(short, long, value_name = "FILE", default_value = STDOUT, value_hint = ValueHint::FilePath,)]
     pub output: String,
+
+    #[command(flatten)]
+    pub sandbox: SandboxOptions,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InspectOutputType {
+    /// Human-focused, non-parsable output format
+    #[default]
+    Human,
+    /// Use yaml format.
+    Yaml,
+}
+
+#[derive(Args, Debug)]
+pub struct InspectOpt {
+    /// Specify the request to be inspected.
+    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub input: String,
+
+    /// Store the result in FILE
+    #[arg(short, long, value_name = "FILE", default_value = STDOUT, value_hint = ValueHint::FilePath,)]
+    pub output: String,
+
+    /// Define the output format of the information.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: InspectOutputType,
+
+    #[command(flatten)]
+    pub sandbox: SandboxOptions,
 }
 
 // all members s390x only
@@ -341,24 +544,99 @@ pub struct RetrSecretOptions {
     /// handle encodes in hexadecimal. Leading zeros are required. If there are multiple secrets in
     /// the store with the same Id there are no guarantees on which specific secret is retrieved.
     /// Use --inform=idx to make sure a specific secret is retrieved.
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     #[arg(value_name = "ID", value_hint = ValueHint::FilePath)]
     pub input: String,
 
     /// Specify the output path to place the secret value
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     #[arg(short, long, value_name = "FILE", default_value = STDOUT, value_hint = ValueHint::FilePath)]
     pub output: String,
 
     /// Define input type for the Secret ID
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     #[arg(long, value_enum, default_value_t)]
     pub inform: RetrInpFmt,
 
     /// Define the output format for the retrieved secret
-    #[cfg(target_arch = "s390x")]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
     #[arg(long, value_enum, default_value_t)]
     pub outform: RetrOutFmt,
+
+    /// Add the secret to a kernel keyring instead of writing it to the output.
+    ///
+    /// The secret is added in binary format as `user` key with the description specified by
+    /// '--key-description'. If the keyring already holds a key with that description, the key is
+    /// updated.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_enum, requires = "key_description", conflicts_with_all = ["output", "outform"])]
+    pub keyring: Option<RetrKeyring>,
+
+    /// Description of the key added to the kernel keyring.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_name = "DESC", requires = "keyring")]
+    pub key_description: Option<String>,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub audit: AuditOptions,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum RetrKeyring {
+    /// The session keyring of the caller.
+    Session,
+    /// The UID-specific keyring of the caller.
+    User,
+    /// The process-specific keyring of the caller.
+    Process,
+}
+
+// all members s390x only
+#[derive(Args, Debug)]
+pub struct LockSecretOpt {
+    /// Lock the secret store without asking for confirmation.
+    ///
+    /// Locking cannot be undone until the guest is rebooted. Without this flag, pvsecret asks for
+    /// confirmation if the standard input is a terminal, and refuses to lock otherwise.
+    #[arg(short, long)]
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    pub yes: bool,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub audit: AuditOptions,
+}
+
+// all members s390x only
+#[derive(Args, Debug)]
+pub struct CredOpt {
+    /// Name of the secret and of the credential.
+    ///
+    /// The secret is looked up by its name, as specified with 'pvsecret create --name'.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_name = "NAME", required_unless_present = "socket")]
+    pub name: Option<String>,
+
+    /// Write the credential into DIR.
+    ///
+    /// Defaults to the directory specified by the 'CREDENTIALS_DIRECTORY' environment variable,
+    /// which systemd sets for services that use credentials.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath, conflicts_with = "socket")]
+    pub dir: Option<String>,
+
+    /// Serve credentials on the unix socket PATH.
+    ///
+    /// Point 'LoadCredential=' of a service to PATH. systemd connects to the socket whenever the
+    /// service is started and receives the secret with the name of the requested credential.
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath, conflicts_with = "name")]
+    pub socket: Option<String>,
+
+    #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+    #[command(flatten)]
+    pub audit: AuditOptions,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
@@ -407,8 +685,10 @@ pub enum Command {
     /// Lock the secret-store (s390x only).
     ///
     /// Lock the secret store (s390x only). After this command executed successfully, all
-    /// subsequent add-secret requests will fail. Only available on s390x.
-    Lock,
+    /// subsequent add-secret requests will fail. Prints the state of the secret store before and
+    /// after locking. Asks for confirmation unless '--yes' is specified. Exits with 4 if the
+    /// secret store was already locked. Only available on s390x.
+    Lock(LockSecretOpt),
 
     /// List all ultravisor secrets (s390x only).
     ///
@@ -416,6 +696,12 @@ pub enum Command {
     /// running IBM Secure Execution guest. Only available on s390x.
     List(ListSecretOpt),
 
+    /// Show information about the ultravisor secret store (s390x only).
+    ///
+    /// Shows the number of stored secrets, the maximum number of secrets, the number of free
+    /// secret slots, and the supported secret types. Only available on s390x.
+    Info(InfoOpt),
+
     /// Verify that an add-secret request is sane.
     ///
     /// Verifies that the given request is an add-secret request by testing for some values to be
@@ -423,13 +709,31 @@ pub enum Command {
     /// provided key. Outputs the arbitrary user-data.
     Verify(VerifyOpt),
 
+    /// Show the non-confidential content of an add-secret request.
+    ///
+    /// Decodes the unencrypted part of a binary add-secret request: the secret type, ID, and size,
+    /// the flags, the targeted SE-header tag and Config-UID, the kind of user data, and the hashes
+    /// of the host-keys the request is encrypted for. Use it to review requests before adding them
+    /// to the Ultravisor. The authenticity of the request is not checked.
+    Inspect(InspectOpt),
+
     /// Retrieve a secret from the UV secret store (s390x only).
     #[command(visible_alias = "retr")]
     Retrieve(RetrSecretOptions),
 
+    /// Provide retrievable secrets as systemd service credentials (s390x only).
+    ///
+    /// Writes a retrievable secret in binary format into the credentials directory of a service or
+    /// serves secrets on a unix socket that can be used with 'LoadCredential='.
+    Cred(CredOpt),
+
     /// Print version information and exit.
     #[command(aliases(["--version"]), hide(true))]
     Version,
+
+    /// Generate shell completions and man pages.
+    #[command(hide(true))]
+    Generate(GenerateOptions),
 }
 
 /// Additional checks to assure, option integrity
@@ -438,8 +742,8 @@ pub fn validate_cli(cli: &CliOptions) -> Result<(), clap::Error> {
         if let AddSecretType::Association {
             name,
             stdout,
-            input_secret: _,
             output_secret: secret_out,
+            ..
         } = &opt.secret
         {
             if *stdout {
@@ -476,11 +780,15 @@ mod test {
         //configuration
         let valid_args = [
             vec!["pvsecret", "lock"],
+            #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+            vec!["pvsecret", "lock", "--yes"],
             vec!["pvsecret", "version"],
             vec!["pvsecret", "list"],
-            #[cfg(target_arch = "s390x")]
+            #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
             vec!["pvsecret", "add", "abc"],
-            #[cfg(not(target_arch = "s390x"))]
+            #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+            vec!["pvsecret", "add", "--manifest", "abc", "--no-manifest-verify", "--all-or-nothing"],
+            #[cfg(not(any(target_arch = "s390x", feature = "uv-sim")))]
             vec!["pvsecret", "add"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "meta"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name" ],
@@ -492,17 +800,25 @@ mod test {
                 "--root-ca", "tttt", "--cck", "cck", "--cuid-hex", "0x11223344556677889900aabbccddeeff", "--pcf", "0x123", "association", "name", "--stdout",
                 "--output-secret", "secret"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name", "--output-secret", "secret"],
-            #[cfg(target_arch = "s390x")]
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name", "--output-secret", "secret", "--output-secret-format", "json"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name", "--output-secret-fd", "3", "--output-secret-format", "dotenv"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "manifest", "m", "--manifest-key", "k"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "manifest", "m", "--manifest-key", "k", "--manifest-sig", "s"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "manifest", "m", "--no-manifest-verify", "--all-or-nothing"],
+            #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
             vec!["pvsecret", "list", "--format", "human"],
-            #[cfg(target_arch = "s390x")]
+            #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
             vec!["pvsecret", "list", "--format", "yaml"],
-            #[cfg(target_arch = "s390x")]
+            #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
             vec!["pvsecret", "list", "--format", "bin"],
+            vec!["pvsecret", "inspect", "abc"],
+            vec!["pvsecret", "inspect", "abc", "--format", "yaml", "-o", "out"],
         ];
         // Test for the minimal amount of flags to yield an invalid combination
         let invalid_args = [
             vec!["pvsecret"],
             vec!["pvsecret", "list", "--yaml", "--bin"],
+            vec!["pvsecret", "inspect"],
             vec!["pvsecret", "create", "--hdr", "abc", "-o", "abc", "--no-verify" ,"null"],
             vec!["pvsecret", "create", "-k", "abc", "-o", "abc", "--no-verify", "null"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "--no-verify", "null"],
@@ -512,7 +828,15 @@ mod test {
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "--cuid", "abc", "--cuid_hex", "9",  "null"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name", "--output-secret", "secret", "--input-secret", "secret"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name", "--output-secret-fd", "3", "--input-secret", "secret"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name", "--output-secret-fd", "3", "--output-secret", "secret"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name", "--output-secret-format", "hex"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "update-cck"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "manifest"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "manifest", "m", "--manifest-key", "k", "--no-manifest-verify"],
+            vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "manifest", "m", "--manifest-sig", "s"],
+            #[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+            vec!["pvsecret", "add", "abc", "--manifest", "m"],
             ];
         for arg in valid_args {
             let res = CliOptions::try_parse_from(&arg);
@@ -8,35 +8,58 @@ pub use create::create;
 mod verify;
 pub use verify::verify;
 
-pub const CMD_FN: &[&str] = &["+create", "+verify"];
+mod inspect;
+pub use inspect::inspect;
 
-#[cfg(target_arch = "s390x")]
+mod peer;
+
+pub const CMD_FN: &[&str] = &["+create", "+verify", "+inspect"];
+
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 mod add;
-#[cfg(target_arch = "s390x")]
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+mod cred;
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
+mod info;
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 mod list;
-#[cfg(target_arch = "s390x")]
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 mod lock;
-#[cfg(target_arch = "s390x")]
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 mod retr;
 
+/// Result of a Lock Secret Store UVC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(any(target_arch = "s390x", feature = "uv-sim")), allow(dead_code))]
+pub enum LockState {
+    /// The secret store was locked by this command
+    Locked,
+    /// The secret store was already locked before
+    AlreadyLocked,
+}
+
 // Commands (directly) related to UVCs are only available on s389x
-#[cfg(target_arch = "s390x")]
+#[cfg(any(target_arch = "s390x", feature = "uv-sim"))]
 mod uv_cmd {
     pub use super::*;
     pub use add::add;
+    pub use cred::cred;
+    pub use info::info;
     pub use list::list;
     pub use lock::lock;
     pub use retr::retr;
-    pub const UV_CMD_FN: &[&str] = &["+add", "+lock", "+list"];
+    pub const UV_CMD_FN: &[&str] = &["+add", "+lock", "+list", "+cred", "+info"];
 }
 
-#[cfg(not(target_arch = "s390x"))]
+#[cfg(not(any(target_arch = "s390x", feature = "uv-sim")))]
 mod uv_cmd {
-    use crate::cli::{AddSecretOpt, ListSecretOpt, RetrSecretOptions};
+    use crate::cli::{
+        AddSecretOpt, CredOpt, InfoOpt, ListSecretOpt, LockSecretOpt, RetrSecretOptions,
+    };
     use anyhow::{bail, Result};
     macro_rules! not_supp {
-        ($name: ident $( ,$opt: ty )?) => {
-            pub fn $name($(_: &$opt)?) -> Result<()> {
+        ($name: ident, $opt: ty) => {
+            pub fn $name(_: &$opt) -> Result<()> {
                 bail!("Command only available on s390x")
             }
         };
@@ -44,7 +67,11 @@ mod uv_cmd {
     not_supp!(add, AddSecretOpt);
     not_supp!(list, ListSecretOpt);
     not_supp!(retr, RetrSecretOptions);
-    not_supp!(lock);
+    not_supp!(cred, CredOpt);
+    pub fn lock(_: &LockSecretOpt) -> Result<super::LockState> {
+        bail!("Command only available on s390x")
+    }
+    not_supp!(info, InfoOpt);
     pub const UV_CMD_FN: &[&str] = &[];
 }
 pub use uv_cmd::*;
@@ -1,24 +1,95 @@
 // SPDX-License-Identifier: MIT
 //
-// Copyright IBM Corp. 2023
+// Copyright IBM Corp. 2023, 2025
 
-use crate::{cli::AddSecretOpt, cmd::list::list_uvc};
+use crate::{
+    cli::AddSecretOpt,
+    cmd::list::list_uvc,
+    manifest::{Flag, Item, Manifest, SecretType, Status, StatusReport},
+};
 use anyhow::{bail, Context, Result};
 use log::warn;
 use pv::{
-    secret::AddSecretRequest,
-    uv::{AddCmd, UvCmd, UvDevice},
+    audit::{AuditLog, AuditOp, AuditRecord},
+    misc::open_file,
+    secret::{AddSecretRequest, AddSecretRequestInfo, GuestSecret},
+    uv::{
+        AddCmd, ListableSecretType, RetryPolicy, SecretId, SecretList, SecretStoreInfo, UvDevice,
+    },
 };
 use utils::get_reader_from_cli_file_arg;
 
+/// Check the secret store for a request without adding it.
+fn check_store(
+    list: &SecretList,
+    store: &SecretStoreInfo,
+    info: &AddSecretRequestInfo,
+    force: bool,
+) -> Result<()> {
+    let secret_type = ListableSecretType::from(info.secret_type());
+    if let Some(types) = store.supported_secret_types() {
+        // Meta and CCK-update secrets are not listable
+        if !matches!(secret_type, ListableSecretType::Invalid(_)) && !types.contains(&secret_type) {
+            bail!("The Ultravisor does not support the secret type of the request");
+        }
+    }
+    if store.free_secrets() == Some(0) {
+        bail!("The secret store is full");
+    }
+    if let Some(id) = info.secret_id() {
+        if list.iter().any(|e| e.id() == id.as_ref()) {
+            warn!("There is already a secret in the secret store with that id.");
+            if !force {
+                bail!("Unable to add the secret due to duplicated IDs");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check everything the Add Secret UVC depends on without issuing it.
+fn dry_run(uv: &UvDevice, cmd: &mut AddCmd, force: bool) -> Result<()> {
+    if !uv.info()?.supports::<AddCmd>() {
+        bail!("The Ultravisor or the uvdevice does not support adding secrets");
+    }
+    let info = AddSecretRequest::bin_info(cmd.request())?;
+    println!("Add-secret request:\n{info}");
+
+    let list = list_uvc(uv)?;
+    let store = SecretStoreInfo::with_query(&list, &uv.query_info());
+    println!("Secret store:\n{store}");
+    check_store(&list, &store, &info, force)?;
+    warn!("Dry run: The secret would be added");
+    Ok(())
+}
+
 /// Do an Add Secret UVC
 pub fn add(opt: &AddSecretOpt) -> Result<()> {
-    let uv = UvDevice::open()?;
-    let mut rd_in = get_reader_from_cli_file_arg(&opt.input)?;
-    let mut cmd =
-        AddCmd::new(&mut rd_in).context(format!("Processing input file {}", opt.input))?;
+    let audit = match opt.dry_run {
+        true => None,
+        false => Some(opt.audit.open()?),
+    };
+    let uv = UvDevice::inherited_or_open()?;
+    uv.set_retry_policy(RetryPolicy::new(opt.retries.saturating_add(1)));
+    let input = match (&opt.input, &opt.manifest) {
+        (_, Some(manifest)) => {
+            let manifest = Manifest::read(manifest, &opt.manifest_args)?;
+            opt.privileges.drop_privileges()?;
+            return add_manifest(opt, &uv, audit.as_ref(), &manifest);
+        }
+        (Some(input), None) => input,
+        // clap ensures that one of both is present
+        (None, None) => unreachable!(),
+    };
+    let mut rd_in = get_reader_from_cli_file_arg(input)?;
+    opt.privileges.drop_privileges()?;
+    let mut cmd = AddCmd::new(&mut rd_in).context(format!("Processing input file {input}"))?;
 
-    if let Some(id) = AddSecretRequest::bin_id(cmd.data().unwrap())? {
+    let Some(audit) = audit else {
+        return dry_run(&uv, &mut cmd, opt.force);
+    };
+
+    if let Some(id) = AddSecretRequest::bin_id(cmd.request())? {
         if list_uvc(&uv)?.iter().any(|e| e.id() == id.as_ref()) {
             warn!("There is already a secret in the secret store with that id.");
             match opt.force {
@@ -28,7 +99,126 @@ pub fn add(opt: &AddSecretOpt) -> Result<()> {
         }
     }
 
-    uv.send_cmd(&mut cmd)?;
+    let record = AuditRecord::new(AuditOp::AddSecret).digest("asrcb", cmd.request());
+    audit.send_cmd(&uv, &mut cmd, record)?;
     warn!("Successfully added the secret");
     Ok(())
 }
+
+/// Check that the request contains the secret the manifest specifies.
+fn check_item(item: &Item, info: &AddSecretRequestInfo) -> Result<()> {
+    let secret_type = info.secret_type();
+    let type_matches = match item.kind {
+        SecretType::Meta => secret_type == ListableSecretType::NULL,
+        SecretType::UpdateCck => secret_type == ListableSecretType::UPDATE_CCK,
+        SecretType::Association => secret_type == ListableSecretType::ASSOCIATION,
+        SecretType::Retrievable => matches!(
+            ListableSecretType::from(secret_type),
+            ListableSecretType::Retrievable(_)
+        ),
+    };
+    if !type_matches {
+        bail!(
+            "The request does not contain a {:?} secret as specified in the manifest",
+            item.kind
+        );
+    }
+    if let (Some(name), Some(id)) = (&item.name, info.secret_id()) {
+        // The ID is either the hashed name, or the name itself ('pvsecret create --use-name')
+        if id != &GuestSecret::name_to_id(name)? && id != &SecretId::from_string(name) {
+            bail!("The secret ID of the request does not belong to the name '{name}'");
+        }
+    }
+    for flag in &item.flags {
+        match flag {
+            Flag::DisableDump if !info.flags().disable_dump() => {
+                bail!(
+                    "The request does not set the flag 'disable-dump' as specified in the manifest"
+                )
+            }
+            Flag::DisableDump => (),
+        }
+    }
+    Ok(())
+}
+
+/// Read the request of a manifest item and check it against the manifest.
+fn read_item(manifest: &Manifest, item: &Item) -> Result<(AddCmd, AddSecretRequestInfo)> {
+    let path = manifest.path(&item.request);
+    let cmd = AddCmd::new(&mut open_file(&path)?)
+        .with_context(|| format!("Processing input file {}", path.display()))?;
+    let info = AddSecretRequest::bin_info(cmd.request())?;
+    check_item(item, &info)?;
+    Ok((cmd, info))
+}
+
+/// Add the requests of all secrets of a manifest
+fn add_manifest(
+    opt: &AddSecretOpt,
+    uv: &UvDevice,
+    audit: Option<&AuditLog>,
+    manifest: &Manifest,
+) -> Result<()> {
+    if !uv.info()?.supports::<AddCmd>() {
+        bail!("The Ultravisor or the uvdevice does not support adding secrets");
+    }
+    let all_or_nothing = opt.manifest_args.all_or_nothing;
+    let list = list_uvc(uv)?;
+    let store = SecretStoreInfo::with_query(&list, &uv.query_info());
+
+    let reqs: Vec<Result<AddCmd>> = manifest
+        .secrets
+        .iter()
+        .map(|item| {
+            let (cmd, info) = read_item(manifest, item)?;
+            check_store(&list, &store, &info, opt.force)?;
+            Ok(cmd)
+        })
+        .collect();
+
+    let listable = manifest
+        .secrets
+        .iter()
+        .filter(|i| matches!(i.kind, SecretType::Association | SecretType::Retrievable))
+        .count();
+    let capacity_err = match store.free_secrets() {
+        Some(free) if (free as usize) < listable => Some(format!(
+            "The secret store has space for {free} of the {listable} secrets"
+        )),
+        _ => None,
+    };
+
+    if let Some(e) = &capacity_err {
+        warn!("{e}");
+    }
+
+    let mut report = StatusReport::default();
+    let prechecked = reqs.iter().all(Result::is_ok) && capacity_err.is_none();
+    if all_or_nothing && !prechecked {
+        for (item, req) in manifest.secrets.iter().zip(reqs) {
+            report.push(item, req.err().map_or(Status::Skipped, Status::Failed));
+        }
+        return report.finish("added");
+    }
+
+    let mut stop = false;
+    for (item, req) in manifest.secrets.iter().zip(reqs) {
+        let status = match (req, audit) {
+            _ if stop => Status::Skipped,
+            (Err(e), _) => Status::Failed(e),
+            (Ok(_), None) => Status::Done,
+            (Ok(mut cmd), Some(audit)) => {
+                let record = AuditRecord::new(AuditOp::AddSecret).digest("asrcb", cmd.request());
+                let res = audit.send_cmd(uv, &mut cmd, record);
+                res.map(|_| ()).map_err(Into::into).into()
+            }
+        };
+        // Secrets cannot be removed; stop at the first failure
+        stop |= all_or_nothing && matches!(status, Status::Failed(_));
+        report.push(item, status);
+    }
+    match audit {
+        Some(_) => report.finish("added"),
+        None => report.finish("valid"),
+    }
+}
@@ -8,20 +8,32 @@ use anyhow::{anyhow, bail, Context, Error, Result};
 use log::{debug, info, trace, warn};
 use pv::{
     misc::{
-        decode_hex, open_file, pv_guest_bit_set, read_exact_file, read_file, try_parse_u128,
-        try_parse_u64, write,
+        decode_hex, encode_base64, encode_hex, open_file, pv_guest_bit_set, read_exact_file,
+        read_file, read_secret_file, try_parse_u128, try_parse_u64, write,
     },
     request::{
-        openssl::pkey::{PKey, Private},
-        BootHdrTags, ReqEncrCtx, Request, SymKeyType,
+        openssl::pkey::{PKey, Private, Public},
+        private_key_from_uri, BootHdrTags, Confidential, ReqEncrCtx, Request, SymKeyType,
+    },
+    secret::{
+        AddSecretFlag, AddSecretFlags, AddSecretRequest, AddSecretVersion, ExtSecret, GuestSecret,
     },
-    secret::{AddSecretFlags, AddSecretRequest, AddSecretVersion, ExtSecret, GuestSecret},
     uv::ConfigUid,
 };
+use serde::Serialize;
 use serde_yaml::Value;
-use utils::get_writer_from_cli_file_arg;
+use utils::{get_writer_from_cli_fd_arg, get_writer_from_cli_file_arg};
+
+use crate::{
+    cli::{
+        AddSecretType, CreateSecretFlags, CreateSecretOpt, RetrieveableSecretInpKind,
+        SecretOutputFormat,
+    },
+    manifest::{Item, Manifest, SecretType, Status, StatusReport},
+};
 
-use crate::cli::{AddSecretType, CreateSecretFlags, CreateSecretOpt, RetrieveableSecretInpKind};
+/// Prefix of PKCS#11 URIs (RFC 7512)
+const PKCS11_URI_PREFIX: &str = "pkcs11:";
 
 fn write_out<P, D>(path: &P, data: D, ctx: &str) -> pv::Result<()>
 where
@@ -34,7 +46,7 @@ where
 }
 
 fn retrievable(name: &str, secret: &str, kind: &RetrieveableSecretInpKind) -> Result<GuestSecret> {
-    let secret_data = read_file(secret, &format!("retrievable {kind}"))?.into();
+    let secret_data = read_secret_file(secret, &format!("retrievable {kind}"))?.into();
 
     match kind {
         RetrieveableSecretInpKind::Plain => GuestSecret::plaintext(name, secret_data),
@@ -61,14 +73,20 @@ pub fn create(opt: &CreateSecretOpt) -> Result<()> {
         }
     }
 
-    let mut asrcb = build_asrcb(opt)?;
+    if let AddSecretType::Manifest {
+        manifest,
+        manifest_args,
+    } = &opt.secret
+    {
+        let manifest = Manifest::read(manifest, manifest_args)?;
+        return create_manifest(opt, &manifest, manifest_args.all_or_nothing);
+    }
+
+    let mut asrcb = build_asrcb(opt, cli_secret(&opt.secret)?, cli_flags(opt)?)?;
     debug!("Generated Add-secret request");
 
     // Add host-key documents
-    opt.certificate_args
-        .get_verified_hkds("secret")?
-        .into_iter()
-        .for_each(|k| asrcb.add_hostkey(k));
+    asrcb.add_hostkeys(opt.certificate_args.get_verified_hkds("secret")?)?;
 
     debug!("Added all host-keys");
 
@@ -83,6 +101,79 @@ pub fn create(opt: &CreateSecretOpt) -> Result<()> {
     write_secret(&opt.secret, asrcb.guest_secret(), &opt.output)
 }
 
+/// Generate and encrypt the request of a manifest item
+fn create_item(
+    opt: &CreateSecretOpt,
+    manifest: &Manifest,
+    item: &Item,
+    hkds: &[PKey<Public>],
+) -> Result<(AddSecretRequest, Vec<u8>)> {
+    let mut flags = cli_flags(opt)?;
+    item.flags
+        .iter()
+        .for_each(|f| set_flag(&mut flags, (*f).into()));
+    let mut asrcb = build_asrcb(opt, manifest_secret(manifest, item)?, flags)?;
+    asrcb.add_hostkeys(hkds.iter().cloned())?;
+    let rq =
+        ReqEncrCtx::random(SymKeyType::Aes256Gcm).context("Failed to generate random input")?;
+    let bin = asrcb.encrypt(&rq)?;
+    Ok((asrcb, bin))
+}
+
+/// Write the request of a manifest item and its secret information into `dir`.
+fn write_item(dir: &Path, item: &Item, asrcb: &AddSecretRequest, bin: &[u8]) -> Result<()> {
+    let path = dir.join(&item.request);
+    write_out(&path, bin, "add-secret request")?;
+    info!("Successfully wrote the request to '{}'", path.display());
+    if let Some(name) = &item.name {
+        write_yaml(name, asrcb.guest_secret(), &false, &path)?;
+    }
+    Ok(())
+}
+
+/// Prepare the add-secret requests of all secrets of a manifest
+fn create_manifest(opt: &CreateSecretOpt, manifest: &Manifest, all_or_nothing: bool) -> Result<()> {
+    let dir = Path::new(&opt.output);
+    if !dir.is_dir() {
+        bail!(
+            "The output '{}' must be a directory for the requests of the manifest",
+            dir.display()
+        );
+    }
+    let hkds = opt.certificate_args.get_verified_hkds("secret")?;
+
+    let mut report = StatusReport::default();
+    if all_or_nothing {
+        // Write no request unless all of them can be generated
+        let reqs: Vec<_> = manifest
+            .secrets
+            .iter()
+            .map(|item| create_item(opt, manifest, item, &hkds))
+            .collect();
+        match reqs.iter().all(|r| r.is_ok()) {
+            true => {
+                for (item, req) in manifest.secrets.iter().zip(reqs) {
+                    // Panic: all requests are Ok
+                    let (asrcb, bin) = req.unwrap();
+                    report.push(item, write_item(dir, item, &asrcb, &bin).into());
+                }
+            }
+            false => {
+                for (item, req) in manifest.secrets.iter().zip(reqs) {
+                    report.push(item, req.err().map_or(Status::Skipped, Status::Failed));
+                }
+            }
+        }
+    } else {
+        for item in &manifest.secrets {
+            let res = create_item(opt, manifest, item, &hkds)
+                .and_then(|(asrcb, bin)| write_item(dir, item, &asrcb, &bin));
+            report.push(item, res.into());
+        }
+    }
+    report.finish("created")
+}
+
 /// Read+parse the first key from the buffer.
 fn read_private_key(buf: &[u8]) -> Result<PKey<Private>> {
     PKey::private_key_from_der(buf)
@@ -90,11 +181,19 @@ fn read_private_key(buf: &[u8]) -> Result<PKey<Private>> {
         .map_err(Error::new)
 }
 
-/// Set-up the `add-secret request` from command-line arguments
-fn build_asrcb(opt: &CreateSecretOpt) -> Result<AddSecretRequest> {
-    debug!("Build add-secret request");
+/// Read the user-signing key from a file or, for PKCS#11 URIs, from a token.
+fn read_user_sign_key(arg: &str) -> Result<PKey<Private>> {
+    if arg.starts_with(PKCS11_URI_PREFIX) {
+        return private_key_from_uri(arg)
+            .with_context(|| format!("Cannot load the user-signing key from '{arg}'"));
+    }
+    let buf = read_secret_file(arg, "User-signing key")?;
+    read_private_key(buf.as_bytes()).context("Cannot read {secret} as private key from PEM or DER")
+}
 
-    let mut secret = match &opt.secret {
+/// Read the secret specified on the command-line
+fn cli_secret(secret: &AddSecretType) -> Result<GuestSecret> {
+    let secret = match secret {
         AddSecretType::Meta => GuestSecret::Null,
         AddSecretType::Association {
             name,
@@ -112,18 +211,61 @@ fn build_asrcb(opt: &CreateSecretOpt) -> Result<AddSecretRequest> {
         AddSecretType::UpdateCck { secret } => {
             GuestSecret::update_cck(read_exact_file(secret, "CCK file")?)
         }
+        AddSecretType::Manifest { .. } => unreachable!("manifests are created by item"),
     };
-    trace!("AddSecret: {secret:x?}");
+    Ok(secret)
+}
 
-    opt.use_name.then(|| secret.no_hash_name());
+/// Read the secret of a manifest item
+fn manifest_secret(manifest: &Manifest, item: &Item) -> Result<GuestSecret> {
+    let source = item.secret.as_ref().map(|p| manifest.path(p));
+    let name = item.name.as_deref().unwrap_or_default();
+    let secret = match (item.kind, &source) {
+        (SecretType::Meta, _) => GuestSecret::Null,
+        (SecretType::Association, Some(p)) => {
+            GuestSecret::association(name, read_exact_file(p, "Association secret")?)?
+        }
+        (SecretType::Association, None) => GuestSecret::association(name, None)?,
+        (SecretType::Retrievable, Some(p)) => {
+            // Panic: the manifest is validated, retrievable secrets have a key type
+            let kind = item.key_type.unwrap().into();
+            retrievable(name, &p.to_string_lossy(), &kind)?
+        }
+        (SecretType::UpdateCck, Some(p)) => {
+            GuestSecret::update_cck(read_exact_file(p, "CCK file")?)
+        }
+        // the manifest is validated, these secrets have a source
+        (SecretType::Retrievable | SecretType::UpdateCck, None) => unreachable!(),
+    };
+    Ok(secret)
+}
 
+/// Flags specified on the command-line
+fn cli_flags(opt: &CreateSecretOpt) -> Result<AddSecretFlags> {
     let mut flags = match &opt.pcf {
-        Some(v) => (&try_parse_u64(v, "pcf")?).into(),
+        Some(v) => AddSecretFlags::from_raw(try_parse_u64(v, "pcf")?),
         None => AddSecretFlags::default(),
     };
-    opt.flags.iter().for_each(|v| match v {
-        CreateSecretFlags::DisableDump => flags.set_disable_dump(),
-    });
+    opt.flags.iter().for_each(|v| set_flag(&mut flags, *v));
+    Ok(flags)
+}
+
+fn set_flag(flags: &mut AddSecretFlags, flag: CreateSecretFlags) {
+    match flag {
+        CreateSecretFlags::DisableDump => flags.set(AddSecretFlag::DisableDump),
+    }
+}
+
+/// Set-up the `add-secret request` from command-line arguments
+fn build_asrcb(
+    opt: &CreateSecretOpt,
+    mut secret: GuestSecret,
+    flags: AddSecretFlags,
+) -> Result<AddSecretRequest> {
+    debug!("Build add-secret request");
+    trace!("AddSecret: {secret:x?}");
+
+    opt.use_name.then(|| secret.no_hash_name());
     debug!("FLAGS: {flags:x?}");
 
     let mut se_hdr = open_file(&opt.hdr)?;
@@ -159,12 +301,8 @@ fn build_asrcb(opt: &CreateSecretOpt) -> Result<AddSecretRequest> {
 
     let user_key = opt
         .user_sign_key
-        .as_ref()
-        .map(|p| read_file(p, "User-signing key"))
-        .transpose()?
-        .map(|buf| {
-            read_private_key(&buf).context("Cannot read {secret} as private key from PEM or DER")
-        })
+        .as_deref()
+        .map(read_user_sign_key)
         .transpose()?;
 
     if user_data.is_some() || user_key.is_some() {
@@ -268,6 +406,51 @@ fn write_yaml<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Quote `val` for '.env' files and POSIX shells.
+fn dotenv_quote(val: &str) -> String {
+    format!("'{}'", val.replace('\'', "'\\''"))
+}
+
+/// Encode the association secret in `format`.
+fn format_secret(
+    guest_secret: &GuestSecret,
+    format: SecretOutputFormat,
+) -> Result<Confidential<Vec<u8>>> {
+    #[derive(Serialize)]
+    struct AssocOut<'a> {
+        name: &'a str,
+        id: String,
+        secret: &'a str,
+    }
+
+    let GuestSecret::Association { name, id, .. } = guest_secret else {
+        bail!("Only association secrets can be written");
+    };
+    let secret = guest_secret.confidential();
+    let hex = Confidential::new(encode_hex(secret));
+    let out = match format {
+        SecretOutputFormat::Raw => secret.to_vec(),
+        SecretOutputFormat::Hex => format!("{}\n", hex.value()).into_bytes(),
+        SecretOutputFormat::Base64 => format!("{}\n", encode_base64(secret)).into_bytes(),
+        SecretOutputFormat::Json => {
+            let mut out = serde_json::to_vec_pretty(&AssocOut {
+                name,
+                id: format!("{id:#x}"),
+                secret: hex.value(),
+            })?;
+            out.push(b'\n');
+            out
+        }
+        SecretOutputFormat::Dotenv => format!(
+            "ASSOCIATION_SECRET_NAME={}\nASSOCIATION_SECRET_ID={id:#x}\nASSOCIATION_SECRET={}\n",
+            dotenv_quote(name),
+            hex.value()
+        )
+        .into_bytes(),
+    };
+    Ok(Confidential::new(out))
+}
+
 /// Write the generated secret (if any) to the specified output stream
 fn write_secret<P: AsRef<Path>>(
     secret: &AddSecretType,
@@ -279,11 +462,21 @@ fn write_secret<P: AsRef<Path>>(
             name,
             stdout,
             output_secret,
+            output_secret_fd,
+            output_secret_format,
             ..
         } => {
             write_yaml(name, guest_secret, stdout, outp_path)?;
+            const CTX: &str = "Association secret";
             if let Some(path) = output_secret {
-                write_out(path, guest_secret.confidential(), "Association secret")?
+                let secret = format_secret(guest_secret, *output_secret_format)?;
+                write_out(path, secret.value(), CTX)?
+            }
+            if let Some(fd) = output_secret_fd {
+                let secret = format_secret(guest_secret, *output_secret_format)?;
+                let mut wr = get_writer_from_cli_fd_arg(*fd)
+                    .with_context(|| format!("Cannot write to file descriptor {fd}"))?;
+                write(&mut wr, secret.value(), format!("fd {fd}"), CTX)?;
             }
         }
         AddSecretType::Retrievable { name, stdout, .. } => {
@@ -304,6 +497,36 @@ mod test {
         assert_eq!(key.rsa().unwrap().size(), 384);
     }
 
+    #[test]
+    fn format_secret() {
+        use super::{GuestSecret, SecretOutputFormat};
+
+        let secret = GuestSecret::association("it's", [0x11; 32]).unwrap();
+        let fmt = |f| super::format_secret(&secret, f).unwrap().value().to_owned();
+        let hex = "11".repeat(32);
+        let id = "0x24ceef1cb6b0cbc0b3321021318245760500d1b1e9411a091929268ad1491c9e";
+
+        assert_eq!(fmt(SecretOutputFormat::Raw), [0x11; 32]);
+        assert_eq!(fmt(SecretOutputFormat::Hex), format!("{hex}\n").as_bytes());
+        assert_eq!(
+            fmt(SecretOutputFormat::Base64),
+            b"ERERERERERERERERERERERERERERERERERERERERERE=\n"
+        );
+        let json: serde_json::Value =
+            serde_json::from_slice(&fmt(SecretOutputFormat::Json)).unwrap();
+        assert_eq!(json["name"], "it's");
+        assert_eq!(json["id"], id);
+        assert_eq!(json["secret"], hex);
+        assert_eq!(
+            String::from_utf8(fmt(SecretOutputFormat::Dotenv)).unwrap(),
+            format!(
+                "ASSOCIATION_SECRET_NAME='it'\\''s'\nASSOCIATION_SECRET_ID={id}\nASSOCIATION_SECRET={hex}\n"
+            )
+        );
+
+        assert!(super::format_secret(&GuestSecret::Null, SecretOutputFormat::Hex).is_err());
+    }
+
     #[test]
     fn read_private_key_fail() {
         let key = include_bytes!("create.rs");
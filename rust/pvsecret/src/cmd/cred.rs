@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    env,
+    fs::OpenOptions,
+    io::Write,
+    os::{
+        linux::net::SocketAddrExt,
+        unix::{fs::OpenOptionsExt, net::UnixStream},
+    },
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::{info, warn};
+use pv::{audit::AuditLog, request::Confidential, uv::SecretId};
+use utils::bind_unix_socket;
+
+use super::{
+    peer::parse_peer_name,
+    retr::{retrieve, Value},
+};
+use crate::cli::CredOpt;
+
+/// Environment variable systemd uses to pass the credentials directory to services
+const CREDENTIALS_DIRECTORY: &str = "CREDENTIALS_DIRECTORY";
+
+/// Retrieve the secret with the given name in binary format.
+fn retrieve_cred(name: &str, audit: &AuditLog) -> Result<Confidential<Vec<u8>>> {
+    let secret = retrieve(Value::Id(SecretId::from_string(name)), audit)
+        .with_context(|| format!("Could not retrieve the secret '{name}'"))?;
+    Ok(secret.into_bytes())
+}
+
+/// Extract the credential name from the peer address systemd uses to connect to the socket.
+///
+/// systemd binds the connecting socket to the abstract address
+/// `\0<random>/unit/<unit-name>/<credential-name>`.
+fn cred_name_from_peer(stream: &UnixStream) -> Option<(String, String)> {
+    let addr = stream.peer_addr().ok()?;
+    let name = std::str::from_utf8(addr.as_abstract_name()?).ok()?;
+    parse_peer_name(name)
+}
+
+fn serve_one(mut stream: UnixStream, audit: &AuditLog) -> Result<()> {
+    let (unit, name) = cred_name_from_peer(&stream)
+        .ok_or(anyhow!("Connection is not a systemd credential request"))?;
+    info!("Serving credential '{name}' for unit '{unit}'");
+    let data = retrieve_cred(&name, audit)?;
+    stream.write_all(data.value())?;
+    Ok(())
+}
+
+fn serve(path: &Path, audit: &AuditLog) -> Result<()> {
+    // Only the service manager (root) shall connect.
+    let listener = bind_unix_socket(path, 0o600)
+        .with_context(|| format!("Cannot bind socket '{}'", path.display()))?;
+    info!("Serving credentials on '{}'", path.display());
+
+    for stream in listener.incoming() {
+        // A failing request must not terminate the provider.
+        if let Err(e) = stream
+            .map_err(anyhow::Error::from)
+            .and_then(|s| serve_one(s, audit))
+        {
+            warn!("{e:#}");
+        }
+    }
+    Ok(())
+}
+
+fn write_cred(dir: &Path, name: &str, audit: &AuditLog) -> Result<()> {
+    if name.contains('/') || name == "." || name == ".." {
+        bail!("Invalid credential name '{name}'");
+    }
+    let data = retrieve_cred(name, audit)?;
+    let path = dir.join(name);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o400)
+        .open(&path)
+        .with_context(|| format!("Cannot create credential file '{}'", path.display()))?;
+    file.write_all(data.value())?;
+    info!("Wrote credential '{name}' to '{}'", path.display());
+    Ok(())
+}
+
+/// Provide retrievable secrets as systemd credentials
+pub fn cred(opt: &CredOpt) -> Result<()> {
+    let audit = opt.audit.open()?;
+    if let Some(socket) = &opt.socket {
+        return serve(Path::new(socket), &audit);
+    }
+
+    // Ensured by clap
+    let name = opt.name.as_ref().unwrap();
+    let dir = match &opt.dir {
+        Some(dir) => PathBuf::from(dir),
+        None => env::var_os(CREDENTIALS_DIRECTORY)
+            .map(PathBuf::from)
+            .ok_or(anyhow!(
+                "No credentials directory specified and '{CREDENTIALS_DIRECTORY}' is not set"
+            ))?,
+    };
+    write_cred(&dir, name, &audit)
+}
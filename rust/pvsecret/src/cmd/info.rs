@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use anyhow::{Context, Result};
+use pv::uv::{SecretStoreInfo, UvDevice};
+use utils::get_writer_from_cli_file_arg;
+
+use super::list::list_uvc;
+use crate::cli::{InfoOpt, InfoOutputType};
+
+/// Output information about the secret store in the requested format
+pub fn info(opt: &InfoOpt) -> Result<()> {
+    let uv = UvDevice::inherited_or_open()?;
+    let info = SecretStoreInfo::with_query(&list_uvc(&uv)?, &uv.query_info());
+    let mut wr_out = get_writer_from_cli_file_arg(&opt.output)?;
+
+    match &opt.format {
+        InfoOutputType::Human => write!(wr_out, "{info}").context("Cannot generate output")?,
+        InfoOutputType::Yaml => write!(wr_out, "{}", serde_yaml::to_string(&info)?)
+            .context("Cannot generate yaml output")?,
+    }
+    wr_out.flush()?;
+    Ok(())
+}
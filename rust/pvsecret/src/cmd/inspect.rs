@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use crate::cli::{InspectOpt, InspectOutputType};
+use anyhow::{Context, Result};
+use pv::{sandbox::Sandbox, secret::AddSecretRequest};
+use utils::{get_reader_from_cli_file_arg, get_writer_from_cli_file_arg};
+
+pub fn inspect(opt: &InspectOpt) -> Result<()> {
+    let mut rd_in = get_reader_from_cli_file_arg(&opt.input)?;
+    let mut wr_out = get_writer_from_cli_file_arg(&opt.output)?;
+    opt.sandbox.apply(Sandbox::new())?;
+    let mut data_in = Vec::with_capacity(0x1000);
+    rd_in
+        .read_to_end(&mut data_in)
+        .with_context(|| format!("Cannot read input file {}", opt.input))?;
+
+    let info = AddSecretRequest::bin_info(&data_in)
+        .with_context(|| format!("Cannot decode the add-secret request {}", opt.input))?;
+
+    match opt.format {
+        InspectOutputType::Human => write!(wr_out, "{info}").context("Cannot generate output")?,
+        InspectOutputType::Yaml => write!(wr_out, "{}", serde_yaml::to_string(&info)?)
+            .context("Cannot generate yaml output")?,
+    }
+    wr_out.flush()?;
+    Ok(())
+}
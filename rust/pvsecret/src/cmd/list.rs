@@ -2,12 +2,20 @@
 //
 // Copyright IBM Corp. 2023
 
-use std::io::ErrorKind;
+use std::{
+    fmt::{self, Display},
+    io::{Cursor, ErrorKind},
+};
 
 use crate::cli::{ListSecretOpt, ListSecretOutputType};
 use anyhow::{Context, Error, Result};
 use log::{info, warn};
-use pv::uv::{ListCmd, SecretList, UvDevice};
+use pv::{
+    misc::read_file,
+    secret::GuestSecret,
+    uv::{ListCmd, SecretEntry, SecretId, SecretList, UvDevice},
+};
+use serde::{Deserialize, Serialize};
 use utils::{get_writer_from_cli_file_arg, STDOUT};
 
 const SECRET_LIST_BUF_SIZE: usize = 4;
@@ -32,21 +40,140 @@ pub fn list_uvc(uv: &UvDevice) -> Result<SecretList> {
     cmd.try_into().map_err(Error::new)
 }
 
+/// Entry of a name-mapping file
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum NameEntry {
+    /// Name and the expected ID, e.g. from the secret information of `pvsecret create`
+    Id { name: String, id: SecretId },
+    /// Just the name; the ID is derived from it
+    Name(String),
+}
+
+/// Human-readable names of secret IDs
+#[derive(Debug, Default)]
+struct SecretNames(Vec<(SecretId, String)>);
+
+impl SecretNames {
+    fn from_entries(entries: Vec<NameEntry>) -> Result<Self> {
+        let mut names = Self::default();
+        for entry in entries {
+            match entry {
+                NameEntry::Id { name, id } => names.0.push((id, name)),
+                NameEntry::Name(name) => {
+                    // `pvsecret create` hashes the name unless `--use-name` was given; accept
+                    // both derivations.
+                    names
+                        .0
+                        .push((GuestSecret::name_to_id(&name)?, name.clone()));
+                    names.0.push((SecretId::from_string(&name), name));
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Read the name-mapping files.
+    ///
+    /// Each file is a yaml list of names or of `name`/`id` pairs.
+    fn read(paths: &[String]) -> Result<Self> {
+        let mut entries = vec![];
+        for path in paths {
+            let buf = read_file(path, "name-mapping file")?;
+            let file: Vec<NameEntry> = serde_yaml::from_slice(&buf)
+                .with_context(|| format!("Invalid name-mapping file '{path}'"))?;
+            entries.extend(file);
+        }
+        Self::from_entries(entries)
+    }
+
+    fn resolve(&self, id: &[u8]) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(i, _)| i.as_ref() == id)
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct NamedEntry<'a> {
+    #[serde(flatten)]
+    entry: &'a SecretEntry,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+}
+
+/// A [`SecretList`] with the secrets annotated with their names
+#[derive(Debug, Serialize)]
+struct NamedSecretList<'a> {
+    total_num_secrets: usize,
+    secrets: Vec<NamedEntry<'a>>,
+}
+
+impl<'a> NamedSecretList<'a> {
+    fn new(list: &'a SecretList, names: &'a SecretNames) -> Self {
+        Self {
+            total_num_secrets: list.total_num_secrets(),
+            secrets: list
+                .iter()
+                .map(|entry| NamedEntry {
+                    entry,
+                    name: names.resolve(entry.id()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Display for NamedSecretList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total number of secrets: {}", self.total_num_secrets)?;
+        if !self.secrets.is_empty() {
+            writeln!(f)?;
+        }
+        for s in &self.secrets {
+            match s.name {
+                Some(name) => writeln!(f, "{} ({name})", s.entry)?,
+                None => writeln!(f, "{}", s.entry)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read a list in the binary format of the UV or of the C tools.
+fn read_list(path: &str) -> Result<SecretList> {
+    let buf = read_file(path, "secret list")?;
+    SecretList::decode(&mut Cursor::new(&buf))
+        .with_context(|| format!("'{path}' does not contain a binary secret list"))
+}
+
 /// Do a List Secrets UVC and output the list in the requested format
 pub fn list(opt: &ListSecretOpt) -> Result<()> {
-    let uv = UvDevice::open()?;
-    let secret_list = list_uvc(&uv)?;
+    let names = SecretNames::read(&opt.names)?;
+    let secret_list = match &opt.input {
+        Some(path) => read_list(path)?,
+        None => list_uvc(&UvDevice::inherited_or_open()?)?,
+    };
     let mut wr_out = get_writer_from_cli_file_arg(&opt.output)?;
+    let named_list = NamedSecretList::new(&secret_list, &names);
 
     match &opt.format {
         ListSecretOutputType::Human => {
-            write!(wr_out, "{secret_list}").context("Cannot generate output")?
+            write!(wr_out, "{named_list}").context("Cannot generate output")?
         }
-        ListSecretOutputType::Yaml => write!(wr_out, "{}", serde_yaml::to_string(&secret_list)?)
+        ListSecretOutputType::Yaml => write!(wr_out, "{}", serde_yaml::to_string(&named_list)?)
             .context("Cannot generate yaml output")?,
-        ListSecretOutputType::Bin => secret_list
-            .encode(&mut wr_out)
-            .context("Cannot encode secret list")?,
+        ListSecretOutputType::Bin | ListSecretOutputType::CBin => {
+            if !opt.names.is_empty() {
+                warn!("The binary format cannot contain secret names. Ignoring the name-mapping.");
+            }
+            match opt.format {
+                ListSecretOutputType::CBin => secret_list.encode_c(&mut wr_out),
+                _ => secret_list.encode(&mut wr_out),
+            }
+            .context("Cannot encode secret list")?
+        }
     }
     wr_out.flush()?;
 
@@ -58,3 +185,53 @@ pub fn list(opt: &ListSecretOpt) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pv::uv::ListableSecretType;
+
+    const NAMES: &str = r#"
+- my-association
+- name: my-key
+  id: 0x0101010101010101010101010101010101010101010101010101010101010101
+"#;
+
+    #[test]
+    fn names() {
+        let names = SecretNames::from_entries(serde_yaml::from_str(NAMES).unwrap()).unwrap();
+        let hashed = GuestSecret::name_to_id("my-association").unwrap();
+        assert_eq!(names.resolve(hashed.as_ref()), Some("my-association"));
+        assert_eq!(
+            names.resolve(SecretId::from_string("my-association").as_ref()),
+            Some("my-association")
+        );
+        assert_eq!(names.resolve(&[1; 32]), Some("my-key"));
+        assert_eq!(names.resolve(&[2; 32]), None);
+        assert!(serde_yaml::from_str::<Vec<NameEntry>>("- name: a\n  id: 0x01").is_err());
+    }
+
+    #[test]
+    fn named_list() {
+        let names = SecretNames::from_entries(serde_yaml::from_str(NAMES).unwrap()).unwrap();
+        let list = SecretList::new(
+            2,
+            vec![
+                SecretEntry::new(0, ListableSecretType::Association, [1; 32].into(), 32),
+                SecretEntry::new(1, ListableSecretType::Association, [2; 32].into(), 32),
+            ],
+        );
+        let named = NamedSecretList::new(&list, &names);
+        assert_eq!(
+            named.to_string(),
+            format!(
+                "Total number of secrets: 2\n\n0 Association:\n 0x{} (my-key)\n1 Association:\n 0x{}\n",
+                "01".repeat(32),
+                "02".repeat(32)
+            )
+        );
+        let yaml = serde_yaml::to_string(&named).unwrap();
+        assert!(yaml.contains("name: my-key"));
+        assert_eq!(yaml.matches("name:").count(), 1);
+    }
+}
@@ -1,14 +1,64 @@
 // SPDX-License-Identifier: MIT
 //
-// Copyright IBM Corp. 2023
+// Copyright IBM Corp. 2023, 2025
 
-use anyhow::Result;
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::cli::LockSecretOpt;
+use anyhow::{bail, Context, Result};
 use log::warn;
-use pv::uv::{LockCmd, UvDevice};
+use pv::{
+    audit::{AuditOp, AuditRecord},
+    uv::{LockCmd, SecretStoreInfo, UvDevice},
+    PvCoreError,
+};
+
+use super::{list::list_uvc, LockState};
+
+/// Ultravisor return code of the Lock Secret Store UVC for an already locked store
+const RC_ALREADY_LOCKED: u16 = 0x0102;
+
+/// Ask the user whether to lock the secret store.
+fn confirm() -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        bail!("Locking the secret store cannot be undone. Specify '--yes' to lock it non-interactively");
+    }
+    eprint!(
+        "Locking the secret store cannot be undone until the guest is rebooted. Lock it? [y/N] "
+    );
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .context("Cannot read the confirmation")?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes"))
+}
 
 /// Do a Lock Secret Store UVC
-pub fn lock() -> Result<()> {
-    UvDevice::open()?.send_cmd(&mut LockCmd)?;
-    warn!("Successfully locked secret store");
-    Ok(())
+pub fn lock(opt: &LockSecretOpt) -> Result<LockState> {
+    let audit = opt.audit.open()?;
+    let uv = UvDevice::inherited_or_open()?;
+
+    let info = SecretStoreInfo::with_query(&list_uvc(&uv)?, &uv.query_info());
+    println!("Secret store before locking:\n{info}");
+
+    if !opt.yes && !confirm()? {
+        bail!("Locking the secret store aborted");
+    }
+
+    let state = match audit.send_cmd(&uv, &mut LockCmd, AuditRecord::new(AuditOp::Lock)) {
+        Ok(_) => LockState::Locked,
+        Err(pv::Error::PvCore(PvCoreError::Uv {
+            rc: RC_ALREADY_LOCKED,
+            ..
+        })) => LockState::AlreadyLocked,
+        Err(e) => return Err(e.into()),
+    };
+    match state {
+        LockState::Locked => warn!("Successfully locked secret store"),
+        LockState::AlreadyLocked => warn!("The secret store was already locked"),
+    }
+    println!("Secret store state: locked");
+    Ok(state)
 }
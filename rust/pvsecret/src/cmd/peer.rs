@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+/// Split the peer address of a systemd credential request into unit and credential name.
+///
+/// The address has the form `<random>/unit/<unit-name>/<credential-name>`.
+#[cfg_attr(not(any(target_arch = "s390x", feature = "uv-sim")), allow(dead_code))]
+pub fn parse_peer_name(name: &str) -> Option<(String, String)> {
+    let mut parts = name.splitn(4, '/');
+    let _random = parts.next()?;
+    if parts.next()? != "unit" {
+        return None;
+    }
+    let unit = parts.next()?;
+    let cred = parts.next()?;
+    if unit.is_empty() || cred.is_empty() || cred.contains('/') {
+        return None;
+    }
+    Some((unit.to_string(), cred.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn peer_name() {
+        assert_eq!(
+            parse_peer_name("0123456789abcdef/unit/foo.service/my_secret"),
+            Some(("foo.service".to_string(), "my_secret".to_string()))
+        );
+        assert_eq!(parse_peer_name("0123456789abcdef/unit/foo.service/"), None);
+        assert_eq!(parse_peer_name("0123456789abcdef/unit/foo.service"), None);
+        assert_eq!(parse_peer_name("0123456789abcdef/slice/foo/bar"), None);
+        assert_eq!(parse_peer_name("0123/unit/foo.service/a/b"), None);
+    }
+}
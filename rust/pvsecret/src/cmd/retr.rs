@@ -7,17 +7,18 @@ use std::{collections::VecDeque, fmt::Display};
 use anyhow::{anyhow, bail, Context, Result};
 use log::{debug, info, warn};
 use pv::{
-    misc::open_file,
-    misc::write,
+    audit::{AuditLog, AuditOp, AuditRecord},
+    keyring::{add_key, KeyDescription, Keyring},
+    misc::{encode_hex, open_file, write},
     secret::{GuestSecret, RetrievedSecret},
     uv::{RetrieveCmd, SecretEntry, SecretId, SecretList, UvDevice},
 };
 use utils::get_writer_from_cli_file_arg;
 
 use super::list::list_uvc;
-use crate::cli::{RetrInpFmt, RetrOutFmt, RetrSecretOptions};
+use crate::cli::{RetrInpFmt, RetrKeyring, RetrOutFmt, RetrSecretOptions};
 
-enum Value {
+pub(super) enum Value {
     Id(SecretId),
     Idx(u16),
 }
@@ -73,8 +74,8 @@ fn find_secret_by_id(secrets: &SecretList, id: &SecretId) -> Option<SecretEntry>
     secret.cloned()
 }
 
-fn retrieve(value: Value) -> Result<RetrievedSecret> {
-    let uv = UvDevice::open()?;
+pub(super) fn retrieve(value: Value, audit: &AuditLog) -> Result<RetrievedSecret> {
+    let uv = UvDevice::inherited_or_open()?;
     let secrets = list_uvc(&uv)?;
 
     let entry = match &value {
@@ -97,15 +98,45 @@ fn retrieve(value: Value) -> Result<RetrievedSecret> {
     info!("Try to retrieve secret at index: {}", entry.index());
     debug!("Try to retrieve: {entry:?}");
 
+    let record = AuditRecord::new(AuditOp::Retrieve)
+        .field("index", entry.index())
+        .field("id", encode_hex(entry.id()));
     let mut uv_cmd = RetrieveCmd::from_entry(entry)?;
-    uv.send_cmd(&mut uv_cmd)?;
+    audit.send_cmd(&uv, &mut uv_cmd, record)?;
 
     Ok(RetrievedSecret::from_cmd(uv_cmd))
 }
 
+impl From<RetrKeyring> for Keyring {
+    fn from(value: RetrKeyring) -> Self {
+        match value {
+            RetrKeyring::Session => Self::Session,
+            RetrKeyring::User => Self::User,
+            RetrKeyring::Process => Self::Process,
+        }
+    }
+}
+
+fn retr_to_keyring(opt: &RetrSecretOptions, keyring: RetrKeyring) -> Result<()> {
+    // Ensured by clap
+    let desc = KeyDescription::user(opt.key_description.clone().unwrap())?;
+    let retr_secret = retrieve(opt.try_into()?, &opt.audit.open()?)
+        .context("Could not retrieve the secret from the UV secret store.")?;
+    let keyring = keyring.into();
+    let serial = add_key(&desc, retr_secret.into_bytes().value(), keyring)
+        .with_context(|| format!("Could not add the secret to the keyring {keyring}"))?;
+    info!("Added the secret as {desc} to the keyring {keyring}");
+    println!("{serial}");
+    Ok(())
+}
+
 pub fn retr(opt: &RetrSecretOptions) -> Result<()> {
+    if let Some(keyring) = opt.keyring {
+        return retr_to_keyring(opt, keyring);
+    }
+
     let mut output = get_writer_from_cli_file_arg(&opt.output)?;
-    let retr_secret = retrieve(opt.try_into()?)
+    let retr_secret = retrieve(opt.try_into()?, &opt.audit.open()?)
         .context("Could not retrieve the secret from the UV secret store.")?;
 
     let out_data = match opt.outform {
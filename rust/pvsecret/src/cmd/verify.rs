@@ -8,9 +8,10 @@ use log::warn;
 use pv::misc::{read_certs, read_file};
 use pv::{
     request::openssl::pkey::{PKey, Public},
+    sandbox::Sandbox,
     secret::verify_asrcb_and_get_user_data,
 };
-use utils::{get_reader_from_cli_file_arg, get_writer_from_cli_file_arg};
+use utils::{get_reader_from_cli_file_arg, get_writer_from_cli_file_arg, STDOUT};
 
 /// read the content of a DER or PEM x509 and return the public key
 fn read_sgn_key(path: &str) -> Result<PKey<Public>> {
@@ -35,6 +36,13 @@ pub fn verify(opt: &VerifyOpt) -> Result<()> {
         .transpose()
         .context("Cannot read user-verification certificate.")?;
 
+    // The output is only created if the request contains user data
+    let sandbox = match opt.output.as_str() {
+        STDOUT => Sandbox::new(),
+        output => Sandbox::new().allow_create(output),
+    };
+    opt.sandbox.apply(sandbox)?;
+
     let user_data = verify_asrcb_and_get_user_data(data_in, verify_cert)
         .context("Could not verify the the Add-secret request")?;
 
@@ -5,6 +5,7 @@
 #![allow(missing_docs)]
 mod cli;
 mod cmd;
+mod manifest;
 
 use clap::{CommandFactory, Parser};
 use cli::{validate_cli, CliOptions, Command};
@@ -14,6 +15,7 @@ use utils::{print_cli_error, print_error, print_version, PvLogger};
 
 static LOGGER: PvLogger = PvLogger;
 static EXIT_LOGGER: u8 = 3;
+static EXIT_ALREADY_LOCKED: u8 = 4;
 const FEATURES: &[&[&str]] = &[cmd::CMD_FN, cmd::UV_CMD_FN];
 
 fn main() -> ExitCode {
@@ -27,7 +29,7 @@ fn main() -> ExitCode {
 
     // set up logger/std(out,err)
     let log_level = cli.verbosity.to_level_filter();
-    if let Err(e) = LOGGER.start(log_level) {
+    if let Err(e) = LOGGER.start_with_format(log_level, cli.verbosity.log_format()) {
         // should(TM) never happen
         eprintln!("Logger error: {e:?}");
         return EXIT_LOGGER.into();
@@ -37,15 +39,33 @@ fn main() -> ExitCode {
     trace!("Trace verbosity, may leak secrets to command-line");
     trace!("Options {cli:?}");
 
+    if cli.fips {
+        if let Err(e) = pv::fips::enforce_fips() {
+            return print_error(&anyhow::Error::from(e), log_level);
+        }
+    }
+
     // perform the command selected by the user
     let res = match &cli.cmd {
         Command::Add(opt) => cmd::add(opt),
         Command::List(opt) => cmd::list(opt),
-        Command::Lock => cmd::lock(),
+        Command::Info(opt) => cmd::info(opt),
+        Command::Lock(opt) => match cmd::lock(opt) {
+            Ok(cmd::LockState::AlreadyLocked) => return EXIT_ALREADY_LOCKED.into(),
+            res => res.map(|_| ()),
+        },
         Command::Create(opt) => cmd::create(opt),
-        Command::Version => Ok(print_version!("2024", log_level; FEATURES.concat())),
+        Command::Version => {
+            print_version!("2024", log_level; FEATURES.concat());
+            Ok(())
+        }
         Command::Verify(opt) => cmd::verify(opt),
+        Command::Inspect(opt) => cmd::inspect(opt),
         Command::Retrieve(opt) => cmd::retr(opt),
+        Command::Cred(opt) => cmd::cred(opt),
+        Command::Generate(opt) => opt
+            .run(CliOptions::command(), "UV-Secret Manual")
+            .map_err(Into::into),
     };
 
     match res {
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::{info, warn};
+use pv::{
+    misc::read_file,
+    request::{
+        openssl::{
+            pkey::{PKey, Public},
+            x509::X509,
+            MessageDigest,
+        },
+        verify_signature,
+    },
+};
+use serde::Deserialize;
+
+use crate::cli::{CreateSecretFlags, ManifestOptions, RetrieveableSecretInpKind};
+
+/// Type of a secret in a manifest
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretType {
+    Meta,
+    Association,
+    Retrievable,
+    UpdateCck,
+}
+
+/// Key type of a retrievable secret in a manifest
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyType {
+    Plain,
+    Aes,
+    AesXts,
+    HmacSha,
+    Ec,
+}
+
+impl From<KeyType> for RetrieveableSecretInpKind {
+    fn from(value: KeyType) -> Self {
+        match value {
+            KeyType::Plain => Self::Plain,
+            KeyType::Aes => Self::Aes,
+            KeyType::AesXts => Self::AesXts,
+            KeyType::HmacSha => Self::HmacSha,
+            KeyType::Ec => Self::Ec,
+        }
+    }
+}
+
+/// Add-secret request flag in a manifest
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Flag {
+    DisableDump,
+}
+
+impl From<Flag> for CreateSecretFlags {
+    fn from(value: Flag) -> Self {
+        match value {
+            Flag::DisableDump => Self::DisableDump,
+        }
+    }
+}
+
+/// One secret of a manifest
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Item {
+    /// Name of the secret; required for association and retrievable secrets
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: SecretType,
+    /// Key type of a retrievable secret
+    pub key_type: Option<KeyType>,
+    /// File with the secret value; random association secret if not set
+    pub secret: Option<PathBuf>,
+    #[serde(default)]
+    pub flags: Vec<Flag>,
+    /// File name of the add-secret request
+    pub request: PathBuf,
+}
+
+impl Item {
+    /// Name of the item in status reports
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self.request.display().to_string(),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let named = matches!(self.kind, SecretType::Association | SecretType::Retrievable);
+        match (named, &self.name) {
+            (true, None) => bail!("{:?} secrets require a 'name'", self.kind),
+            (false, Some(_)) => bail!("{:?} secrets have no 'name'", self.kind),
+            _ => (),
+        }
+        match (self.kind, &self.key_type) {
+            (SecretType::Retrievable, None) => bail!("Retrievable secrets require a 'key-type'"),
+            (SecretType::Retrievable, Some(_)) | (_, None) => (),
+            (kind, Some(_)) => bail!("{kind:?} secrets have no 'key-type'"),
+        }
+        match (self.kind, &self.secret) {
+            (SecretType::Retrievable | SecretType::UpdateCck, None) => {
+                bail!("{:?} secrets require a 'secret'", self.kind)
+            }
+            (SecretType::Meta, Some(_)) => bail!("Meta secrets have no 'secret'"),
+            _ => (),
+        }
+        if self.request.is_absolute()
+            || self.request.components().count() != 1
+            || self.request.file_name().is_none()
+        {
+            bail!(
+                "The request '{}' must be a plain file name",
+                self.request.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    secrets: Vec<Item>,
+}
+
+/// List of secrets to be created or added in one invocation
+#[derive(Debug)]
+pub struct Manifest {
+    dir: PathBuf,
+    pub secrets: Vec<Item>,
+}
+
+/// Read a public key from a certificate or a public key in PEM or DER format.
+fn read_public_key(path: &str) -> Result<PKey<Public>> {
+    let buf = read_file(path, "manifest key")?;
+    X509::from_pem(&buf)
+        .or_else(|_| X509::from_der(&buf))
+        .and_then(|crt| crt.public_key())
+        .or_else(|_| PKey::public_key_from_pem(&buf))
+        .or_else(|_| PKey::public_key_from_der(&buf))
+        .with_context(|| format!("Cannot read '{path}' as certificate or public key"))
+}
+
+impl Manifest {
+    /// Read and validate the manifest in `path`; verify its signature as specified in `opt`.
+    pub fn read<P: AsRef<Path>>(path: P, opt: &ManifestOptions) -> Result<Self> {
+        let path = path.as_ref();
+        let buf = read_file(path, "manifest")?;
+        match (&opt.manifest_key, opt.no_manifest_verify) {
+            (Some(key), _) => {
+                let sig_path = match &opt.manifest_sig {
+                    Some(sig) => PathBuf::from(sig),
+                    None => PathBuf::from(format!("{}.sig", path.display())),
+                };
+                let sig = read_file(&sig_path, "manifest signature")?;
+                let key = read_public_key(key)?;
+                if !verify_signature(&key, MessageDigest::sha512(), &buf, &sig).unwrap_or(false) {
+                    bail!(
+                        "The signature '{}' of the manifest could not be verified",
+                        sig_path.display()
+                    );
+                }
+                info!("Verified the signature of the manifest");
+            }
+            (None, true) => warn!("The signature of the manifest is not verified"),
+            (None, false) => bail!(
+                "Specify the key to verify the manifest signature with '--manifest-key' or skip the verification with '--no-manifest-verify'"
+            ),
+        }
+        let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        Self::parse(&buf, dir).with_context(|| format!("Invalid manifest '{}'", path.display()))
+    }
+
+    fn parse(buf: &[u8], dir: PathBuf) -> Result<Self> {
+        let ManifestFile { secrets } = serde_yaml::from_slice(buf)?;
+        if secrets.is_empty() {
+            bail!("The manifest lists no secrets");
+        }
+        let mut requests = HashSet::new();
+        let mut names = HashSet::new();
+        for (nr, item) in secrets.iter().enumerate() {
+            item.validate()
+                .with_context(|| format!("Secret {} ('{}')", nr + 1, item.label()))?;
+            if !requests.insert(&item.request) {
+                bail!(
+                    "The request '{}' is listed more than once",
+                    item.request.display()
+                );
+            }
+            if let Some(name) = &item.name {
+                if !names.insert(name) {
+                    bail!("The secret '{name}' is listed more than once");
+                }
+            }
+        }
+        Ok(Self { dir, secrets })
+    }
+
+    /// Resolve `path` of the manifest relative to the directory of the manifest.
+    pub fn path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.dir.join(path)
+    }
+}
+
+/// Status of one secret of a manifest
+#[derive(Debug)]
+pub enum Status {
+    Done,
+    Failed(anyhow::Error),
+    Skipped,
+}
+
+impl From<Result<()>> for Status {
+    fn from(value: Result<()>) -> Self {
+        match value {
+            Ok(()) => Self::Done,
+            Err(e) => Self::Failed(e),
+        }
+    }
+}
+
+/// Per-secret status of a manifest run
+#[derive(Debug, Default)]
+pub struct StatusReport(Vec<(String, Status)>);
+
+impl StatusReport {
+    pub fn push(&mut self, item: &Item, status: Status) {
+        self.0.push((item.label(), status));
+    }
+
+    /// Print the report to stdout and fail if not all secrets were processed.
+    ///
+    /// `done` describes a processed secret, e.g. "added".
+    pub fn finish(self, done: &str) -> Result<()> {
+        let width = self.0.iter().map(|(l, _)| l.len()).max().unwrap_or(0);
+        for (label, status) in &self.0 {
+            match status {
+                Status::Done => println!("{label:width$}  {done}"),
+                Status::Failed(e) => println!("{label:width$}  failed: {e:#}"),
+                Status::Skipped => println!("{label:width$}  skipped"),
+            }
+        }
+        match self
+            .0
+            .iter()
+            .filter(|(_, s)| !matches!(s, Status::Done))
+            .count()
+        {
+            0 => Ok(()),
+            n => Err(anyhow!("{n} of {} secrets not {done}", self.0.len())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(yaml: &str) -> Result<Manifest> {
+        Manifest::parse(yaml.as_bytes(), PathBuf::from("/m"))
+    }
+
+    #[test]
+    fn manifest() {
+        let m = parse(
+            r#"
+secrets:
+  - name: db key
+    type: retrievable
+    key-type: aes-xts
+    secret: keys/db.key
+    request: db.bin
+  - name: adapter
+    type: association
+    flags: [disable-dump]
+    request: adapter.bin
+  - type: meta
+    request: meta.bin
+"#,
+        )
+        .unwrap();
+        assert_eq!(m.secrets.len(), 3);
+        assert_eq!(m.secrets[0].key_type, Some(KeyType::AesXts));
+        assert_eq!(m.secrets[1].flags, [Flag::DisableDump]);
+        assert_eq!(m.secrets[2].label(), "meta.bin");
+        assert_eq!(m.path(&m.secrets[0].request), Path::new("/m/db.bin"));
+    }
+
+    #[test]
+    fn manifest_invalid() {
+        for yaml in [
+            "secrets: []",
+            "secrets: [{type: association, request: a}]",
+            "secrets: [{type: meta, name: a, request: a}]",
+            "secrets: [{type: retrievable, name: a, secret: s, request: a}]",
+            "secrets: [{type: association, name: a, key-type: aes, request: a}]",
+            "secrets: [{type: update-cck, request: a}]",
+            "secrets: [{type: meta, request: ../a}]",
+            "secrets: [{type: meta, request: a}, {type: meta, request: a}]",
+            "secrets: [{type: association, name: a, request: a}, {type: association, name: a, request: b}]",
+            "secrets: [{type: meta, request: a, unknown: 1}]",
+        ] {
+            assert!(parse(yaml).is_err(), "{yaml}");
+        }
+    }
+}
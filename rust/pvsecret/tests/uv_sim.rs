@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! End-to-end tests of pvsecret against the software Ultravisor simulator
+//!
+//! Run with `cargo test -p pvsecret --features uv-sim`.
+#![cfg(feature = "uv-sim")]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use pv::request::{
+    openssl::{pkey::PKey, MessageDigest},
+    sign_msg,
+};
+
+const ASSETS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../pv/tests/assets");
+
+fn workdir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pvsecret-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn pvsecret(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pvsecret"))
+        .current_dir(dir)
+        .env("PV_UV_SIM", dir.join("uv.state"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn create(dir: &Path, out: &str, args: &[&str]) {
+    let hkd = format!("{ASSETS}/keys/host.pem.crt");
+    let hdr = format!("{ASSETS}/exp/secure_guest.hdr");
+    let mut all = vec![
+        "create",
+        "-k",
+        &hkd,
+        "--no-verify",
+        "--hdr",
+        &hdr,
+        "-o",
+        out,
+    ];
+    all.extend_from_slice(args);
+    let res = pvsecret(dir, &all);
+    assert!(res.status.success(), "{res:?}");
+}
+
+#[test]
+fn add_list_lock() {
+    let dir = workdir("add");
+    create(&dir, "assoc.bin", &["association", "assoc"]);
+    fs::write(dir.join("key"), [0x42; 32]).unwrap();
+    create(
+        &dir,
+        "retr.bin",
+        &["retrievable", "key", "--secret", "key", "--type", "aes"],
+    );
+
+    assert!(pvsecret(&dir, &["add", "assoc.bin"]).status.success());
+    assert!(pvsecret(&dir, &["add", "retr.bin"]).status.success());
+    // The Ultravisor rejects duplicate IDs.
+    assert!(!pvsecret(&dir, &["add", "assoc.bin"]).status.success());
+
+    let res = pvsecret(&dir, &["list"]);
+    assert!(res.status.success(), "{res:?}");
+    let list = String::from_utf8(res.stdout).unwrap();
+    assert!(list.starts_with("Total number of secrets: 2"), "{list}");
+    assert!(list.contains("Association"), "{list}");
+    assert!(list.contains("AES-256-KEY"), "{list}");
+
+    let res = pvsecret(
+        &dir,
+        &["retrieve", "key.yaml", "-o", "out", "--outform", "bin"],
+    );
+    assert!(res.status.success(), "{res:?}");
+    assert_eq!(fs::read(dir.join("out")).unwrap().len(), 32);
+
+    assert!(pvsecret(&dir, &["lock", "--yes"]).status.success());
+    let res = pvsecret(&dir, &["add", "retr.bin"]);
+    assert!(!res.status.success());
+    let err = String::from_utf8(res.stderr).unwrap();
+    assert!(err.contains("secret store locked"), "{err}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn lock() {
+    let dir = workdir("lock");
+    create(&dir, "assoc.bin", &["association", "assoc"]);
+
+    // The standard input of the tests is not a terminal
+    let res = pvsecret(&dir, &["lock"]);
+    assert!(!res.status.success());
+    let err = String::from_utf8(res.stderr).unwrap();
+    assert!(err.contains("Specify '--yes'"), "{err}");
+    // The store is still unlocked
+    assert!(pvsecret(&dir, &["add", "assoc.bin"]).status.success());
+
+    let res = pvsecret(&dir, &["lock", "--yes"]);
+    assert!(res.status.success(), "{res:?}");
+    let out = String::from_utf8(res.stdout).unwrap();
+    assert!(out.starts_with("Secret store before locking:\n"), "{out}");
+    assert!(out.ends_with("Secret store state: locked\n"), "{out}");
+
+    // Locking again reports the already locked store with a distinct exit code
+    let res = pvsecret(&dir, &["lock", "--yes"]);
+    assert_eq!(res.status.code(), Some(4), "{res:?}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn add_dry_run() {
+    let dir = workdir("dry-run");
+    create(&dir, "assoc.bin", &["association", "assoc"]);
+    let count = |dir: &Path| {
+        let res = pvsecret(dir, &["list"]);
+        assert!(res.status.success(), "{res:?}");
+        String::from_utf8(res.stdout)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .to_owned()
+    };
+
+    let res = pvsecret(&dir, &["add", "--dry-run", "assoc.bin"]);
+    assert!(res.status.success(), "{res:?}");
+    let out = String::from_utf8(res.stdout).unwrap();
+    assert!(out.starts_with("Add-secret request:\n"), "{out}");
+    assert!(out.contains("Secret type: Association\n"), "{out}");
+    assert!(out.contains("Secret store:\nStored secrets: 0\n"), "{out}");
+    // Nothing was added
+    assert_eq!(count(&dir), "Total number of secrets: 0");
+
+    assert!(pvsecret(&dir, &["add", "assoc.bin"]).status.success());
+    // The dry run detects the duplicate and leaves the store alone
+    let res = pvsecret(&dir, &["add", "--dry-run", "assoc.bin"]);
+    assert!(!res.status.success());
+    let err = String::from_utf8(res.stderr).unwrap();
+    assert!(err.contains("duplicated IDs"), "{err}");
+    let res = pvsecret(&dir, &["add", "--dry-run", "--force", "assoc.bin"]);
+    assert!(res.status.success(), "{res:?}");
+    assert_eq!(count(&dir), "Total number of secrets: 1");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn manifest() {
+    let dir = workdir("manifest");
+    fs::create_dir(dir.join("out")).unwrap();
+    // The secret sources are relative to the manifest.
+    fs::write(dir.join("out/key"), [0x42; 32]).unwrap();
+    let manifest = b"secrets:
+  - name: key
+    type: retrievable
+    key-type: aes
+    secret: key
+    request: key.bin
+  - name: assoc
+    type: association
+    flags: [disable-dump]
+    request: assoc.bin
+";
+    fs::write(dir.join("out/manifest.yaml"), manifest).unwrap();
+    let key = fs::read(format!("{ASSETS}/keys/rsa2048key.pem")).unwrap();
+    let key = PKey::private_key_from_pem(&key).unwrap();
+    let sig = sign_msg(&key, MessageDigest::sha512(), manifest).unwrap();
+    fs::write(dir.join("out/manifest.yaml.sig"), sig).unwrap();
+    let pubkey = format!("{ASSETS}/keys/rsa2048.crt");
+
+    create(
+        &dir,
+        "out",
+        &["manifest", "out/manifest.yaml", "--manifest-key", &pubkey],
+    );
+    assert!(dir.join("out/key.bin").exists());
+    assert!(dir.join("out/assoc.yaml").exists());
+
+    let add = |args: &[&str]| {
+        let mut all = vec!["add", "--manifest", "out/manifest.yaml", "--manifest-key"];
+        all.push(&pubkey);
+        all.extend_from_slice(args);
+        pvsecret(&dir, &all)
+    };
+    let res = add(&[]);
+    assert!(res.status.success(), "{res:?}");
+    let out = String::from_utf8(res.stdout).unwrap();
+    assert_eq!(out, "key    added\nassoc  added\n");
+
+    // Both IDs are taken now, nothing is added
+    let res = add(&["--all-or-nothing"]);
+    assert!(!res.status.success());
+    let out = String::from_utf8(res.stdout).unwrap();
+    assert!(
+        out.contains("key    failed: Unable to add the secret due to duplicated IDs"),
+        "{out}"
+    );
+
+    // A modified manifest is rejected
+    let mut modified = manifest.to_vec();
+    modified.extend_from_slice(b"# comment\n");
+    fs::write(dir.join("out/manifest.yaml"), modified).unwrap();
+    let res = add(&[]);
+    assert!(!res.status.success());
+    let err = String::from_utf8(res.stderr).unwrap();
+    assert!(err.contains("could not be verified"), "{err}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
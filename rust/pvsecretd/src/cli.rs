@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{num::NonZeroUsize, path::PathBuf};
+
+use clap::{Parser, ValueHint};
+use utils::{AuditOptions, MetricsOptions, PrivilegeOptions, VerbosityOptions};
+
+/// The default socket pvsecretd listens on
+pub const DEFAULT_SOCKET: &str = "/run/pvsecretd.sock";
+
+/// Serve secrets of the IBM Secure Execution secret store to local clients.
+///
+/// Opens the uvdevice once, caches the list of secrets, and serves list and retrieve requests on a
+/// unix socket. Clients are authorized by their peer credentials. Processes running as root are
+/// always allowed.
+#[derive(Parser, Debug)]
+pub struct CliOptions {
+    #[clap(flatten)]
+    pub verbosity: VerbosityOptions,
+
+    /// Listen for clients on the unix socket PATH.
+    #[arg(short, long, value_name = "PATH", default_value = DEFAULT_SOCKET, value_hint = ValueHint::FilePath)]
+    pub socket: PathBuf,
+
+    /// Allow members of GROUP to connect to the socket.
+    ///
+    /// GROUP is a group name or a numeric group ID. Without this option only root can connect.
+    /// Clients must additionally be allowed by '--allow-uid' or '--allow-gid'.
+    #[arg(long, value_name = "GROUP")]
+    pub socket_group: Option<String>,
+
+    /// Allow clients running with the user ID UID.
+    ///
+    /// Can be specified multiple times.
+    #[arg(long = "allow-uid", value_name = "UID")]
+    pub allow_uids: Vec<u32>,
+
+    /// Allow clients running with the group ID GID.
+    ///
+    /// Both the primary and the supplementary groups of the client are considered. Can be
+    /// specified multiple times.
+    #[arg(long = "allow-gid", value_name = "GID")]
+    pub allow_gids: Vec<u32>,
+
+    /// Serve at most N clients at a time.
+    ///
+    /// Further clients receive an error response until a connection is finished. Idle clients are
+    /// disconnected after 30 seconds.
+    #[arg(long, value_name = "N", default_value_t = NonZeroUsize::new(32).unwrap())]
+    pub max_clients: NonZeroUsize,
+
+    #[command(flatten)]
+    pub audit: AuditOptions,
+
+    #[command(flatten)]
+    pub metrics: MetricsOptions,
+
+    #[command(flatten)]
+    pub privileges: PrivilegeOptions,
+
+    /// Print version information and exit.
+    #[arg(long)]
+    pub version: bool,
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        super::CliOptions::command().debug_assert()
+    }
+}
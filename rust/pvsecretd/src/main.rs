@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+mod cli;
+mod protocol;
+mod server;
+mod store;
+
+use clap::{CommandFactory, Parser};
+use cli::CliOptions;
+use log::trace;
+use server::AccessPolicy;
+use std::process::ExitCode;
+use store::SecretStore;
+use utils::{print_cli_error, print_error, print_version, PvLogger};
+
+static LOGGER: PvLogger = PvLogger;
+static EXIT_LOGGER: u8 = 3;
+
+fn run(cli: &CliOptions) -> anyhow::Result<()> {
+    let policy = AccessPolicy::new(cli.allow_uids.clone(), cli.allow_gids.clone());
+    let store = SecretStore::open(cli.audit.open()?)?;
+    let socket_group = cli
+        .socket_group
+        .as_deref()
+        .map(pv::privilege::lookup_group)
+        .transpose()?;
+    let listener = server::bind(&cli.socket, socket_group)?;
+    let metrics = cli.metrics.bind()?;
+    // Everything that needs root is set up, clients are served unprivileged. Privileges must be
+    // dropped before any thread is started, as they are dropped for the calling thread only.
+    cli.privileges.drop_privileges()?;
+    if let Some(metrics) = metrics {
+        metrics.serve();
+    }
+    server::serve(listener, store, policy, cli.max_clients.get())
+}
+
+fn main() -> ExitCode {
+    let cli: CliOptions = match CliOptions::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => return print_cli_error(e, CliOptions::command()),
+    };
+
+    // set up logger/std(out,err)
+    let log_level = cli.verbosity.to_level_filter();
+    if let Err(e) = LOGGER.start_with_format(log_level, cli.verbosity.log_format()) {
+        // should(TM) never happen
+        eprintln!("Logger error: {e:?}");
+        return EXIT_LOGGER.into();
+    }
+
+    if cli.version {
+        print_version!("2025", log_level);
+        return ExitCode::SUCCESS;
+    }
+
+    trace!("Options {cli:?}");
+
+    let res = run(&cli);
+    match res {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => print_error(&e, log_level),
+    }
+}
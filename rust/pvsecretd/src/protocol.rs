@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Wire protocol between pvsecretd and its clients.
+//!
+//! Each request and each response is a single line of JSON.
+
+use pv::{
+    request::Confidential,
+    uv::{SecretId, SecretList},
+};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// A request sent by a client
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case")]
+pub enum Request {
+    /// List all secrets in the secret store
+    List,
+    /// Retrieve a retrievable secret, either by ID or by name
+    Retrieve {
+        #[serde(default)]
+        id: Option<SecretId>,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Re-read the secret list from the Ultravisor
+    Refresh,
+}
+
+/// The response pvsecretd sends for each request
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum Response<'a> {
+    /// The cached secret list
+    List { secrets: &'a SecretList },
+    /// A retrieved secret; the value is hex encoded
+    Secret {
+        id: SecretId,
+        #[serde(serialize_with = "ser_confidential")]
+        value: Confidential<String>,
+    },
+    /// The request was successful, no further data
+    Ok,
+    /// The request failed
+    Error { message: String },
+}
+
+fn ser_confidential<S: Serializer>(v: &Confidential<String>, ser: S) -> Result<S::Ok, S::Error> {
+    ser.serialize_str(v.value())
+}
+
+impl Response<'_> {
+    pub fn error<E: std::fmt::Display>(e: E) -> Self {
+        Self::Error {
+            message: format!("{e:#}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_requests() {
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"cmd":"list"}"#).unwrap(),
+            Request::List
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"cmd":"refresh"}"#).unwrap(),
+            Request::Refresh
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"cmd":"retrieve","name":"my_key"}"#).unwrap(),
+            Request::Retrieve {
+                id: None,
+                name: Some("my_key".to_string())
+            }
+        );
+        let id = format!("0x{}", "ab".repeat(32));
+        assert_eq!(
+            serde_json::from_str::<Request>(&format!(r#"{{"cmd":"retrieve","id":"{id}"}}"#))
+                .unwrap(),
+            Request::Retrieve {
+                id: Some(SecretId::from([0xab; 32])),
+                name: None
+            }
+        );
+        assert!(serde_json::from_str::<Request>(r#"{"cmd":"add"}"#).is_err());
+    }
+
+    #[test]
+    fn write_responses() {
+        assert_eq!(
+            serde_json::to_string(&Response::Ok).unwrap(),
+            r#"{"status":"ok"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Response::error("nope")).unwrap(),
+            r#"{"status":"error","message":"nope"}"#
+        );
+        let list = SecretList::default();
+        assert!(serde_json::to_string(&Response::List { secrets: &list })
+            .unwrap()
+            .starts_with(r#"{"status":"list","secrets":{"#));
+        let secret = Response::Secret {
+            id: SecretId::from([0; 32]),
+            value: "c0ffee".to_string().into(),
+        };
+        assert!(serde_json::to_string(&secret)
+            .unwrap()
+            .ends_with(r#""value":"c0ffee"}"#));
+    }
+}
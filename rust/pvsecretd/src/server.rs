@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
+    os::unix::{
+        fs::chown,
+        io::AsRawFd,
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info, warn};
+use pv::{
+    audit::{AuditOp, AuditRecord},
+    misc::encode_hex,
+};
+
+use utils::{bind_unix_socket, Semaphore};
+
+use crate::{
+    protocol::{Request, Response},
+    store::{Lookup, SecretStore},
+};
+
+/// Upper bound for the size of a single request line
+const MAX_REQUEST: u64 = 4096;
+
+/// Time a client may stay idle or take to receive a response
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Credentials of the process on the other end of a connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerCred {
+    pub pid: i32,
+    pub uid: u32,
+    pub gid: u32,
+    /// Supplementary groups
+    pub groups: Vec<u32>,
+}
+
+impl PeerCred {
+    /// Query the credentials of the connected peer via `SO_PEERCRED` and `SO_PEERGROUPS`.
+    fn of(stream: &UnixStream) -> Result<Self> {
+        let mut cred = libc::ucred {
+            pid: 0,
+            uid: 0,
+            gid: 0,
+        };
+        let mut len = size_of::<libc::ucred>() as libc::socklen_t;
+        // SAFETY: cred and len point to valid memory of the advertised size.
+        let rc = unsafe {
+            libc::getsockopt(
+                stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                &mut cred as *mut libc::ucred as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        if rc != 0 {
+            return Err(std::io::Error::last_os_error()).context("Cannot get peer credentials");
+        }
+        Ok(Self {
+            pid: cred.pid,
+            uid: cred.uid,
+            gid: cred.gid,
+            groups: Self::groups_of(stream)?,
+        })
+    }
+
+    /// Query the supplementary groups of the connected peer via `SO_PEERGROUPS`.
+    fn groups_of(stream: &UnixStream) -> Result<Vec<u32>> {
+        const GID_SIZE: usize = size_of::<libc::gid_t>();
+        let mut groups: Vec<libc::gid_t> = vec![0; 32];
+        loop {
+            let mut len = (groups.len() * GID_SIZE) as libc::socklen_t;
+            // SAFETY: groups and len point to valid memory of the advertised size.
+            let rc = unsafe {
+                libc::getsockopt(
+                    stream.as_raw_fd(),
+                    libc::SOL_SOCKET,
+                    libc::SO_PEERGROUPS,
+                    groups.as_mut_ptr() as *mut libc::c_void,
+                    &mut len,
+                )
+            };
+            // The kernel reports the required size, if the buffer is too small.
+            let count = len as usize / GID_SIZE;
+            if rc == 0 {
+                groups.truncate(count);
+                return Ok(groups);
+            }
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::ERANGE) || count <= groups.len() {
+                return Err(err).context("Cannot get peer groups");
+            }
+            groups.resize(count, 0);
+        }
+    }
+}
+
+/// Decides which peers may talk to the daemon
+///
+/// Root is always allowed. Other peers are allowed if their user ID, their primary group ID, or
+/// one of their supplementary group IDs is in the allow lists.
+#[derive(Debug, Default)]
+pub struct AccessPolicy {
+    uids: Vec<u32>,
+    gids: Vec<u32>,
+}
+
+impl AccessPolicy {
+    pub fn new(uids: Vec<u32>, gids: Vec<u32>) -> Self {
+        Self { uids, gids }
+    }
+
+    pub fn allows(&self, cred: &PeerCred) -> bool {
+        cred.uid == 0
+            || self.uids.contains(&cred.uid)
+            || self.gids.contains(&cred.gid)
+            || cred.groups.iter().any(|gid| self.gids.contains(gid))
+    }
+}
+
+fn handle_request(
+    store: &SecretStore,
+    cred: &PeerCred,
+    req: Request,
+    writer: &mut impl Write,
+) -> Result<()> {
+    let rsp = match req {
+        Request::List => {
+            let list = store.list();
+            return write_response(writer, &Response::List { secrets: &list });
+        }
+        Request::Refresh => match store.refresh() {
+            Ok(()) => Response::Ok,
+            Err(e) => Response::error(e),
+        },
+        Request::Retrieve { id, name } => {
+            let lookup = match (id, name) {
+                (Some(id), None) => Ok(Lookup::Id(id)),
+                (None, Some(name)) => Ok(Lookup::Name(name)),
+                _ => Err(anyhow!("Specify exactly one of 'id' or 'name'")),
+            };
+            let record = AuditRecord::new(AuditOp::Retrieve).caller(cred.pid, cred.uid);
+            match lookup.and_then(|l| store.retrieve(&l, record)) {
+                Ok((id, secret)) => Response::Secret {
+                    id,
+                    value: encode_hex(secret.data()).into(),
+                },
+                Err(e) => Response::error(e),
+            }
+        }
+    };
+    write_response(writer, &rsp)
+}
+
+fn write_response(writer: &mut impl Write, rsp: &Response) -> Result<()> {
+    serde_json::to_writer(&mut *writer, rsp)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read the next request line into `line`.
+///
+/// Returns `false` at the end of the stream. Requests longer than [`MAX_REQUEST`] are rejected with
+/// [`ErrorKind::InvalidData`].
+fn read_request(reader: &mut impl BufRead, line: &mut String) -> std::io::Result<bool> {
+    line.clear();
+    let len = reader.take(MAX_REQUEST).read_line(line)?;
+    if len as u64 == MAX_REQUEST && !line.ends_with('\n') {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "Request too long",
+        ));
+    }
+    Ok(len > 0)
+}
+
+fn serve_client(store: &SecretStore, policy: &AccessPolicy, stream: UnixStream) -> Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let cred = PeerCred::of(&stream)?;
+    if !policy.allows(&cred) {
+        warn!(
+            "Rejected client pid {} (uid {}, gid {})",
+            cred.pid, cred.uid, cred.gid
+        );
+        return write_response(&mut &stream, &Response::error("Permission denied"));
+    }
+    debug!(
+        "Accepted client pid {} (uid {}, gid {})",
+        cred.pid, cred.uid, cred.gid
+    );
+
+    let mut writer = &stream;
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    loop {
+        match read_request(&mut reader, &mut line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                debug!("Closing idle connection of pid {}", cred.pid);
+                break;
+            }
+            Err(e) if e.kind() == ErrorKind::InvalidData => {
+                return write_response(&mut writer, &Response::error(e));
+            }
+            Err(e) => return Err(e.into()),
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(req) => {
+                debug!("Request from pid {}: {req:?}", cred.pid);
+                handle_request(store, &cred, req, &mut writer)?
+            }
+            Err(e) => write_response(&mut writer, &Response::error(e))?,
+        }
+    }
+    Ok(())
+}
+
+/// Bind the unix socket at `path`.
+///
+/// A stale socket at `path` is replaced. The socket is accessible by root and, if `group` is
+/// given, by the members of `group`.
+pub fn bind(path: &Path, group: Option<u32>) -> Result<UnixListener> {
+    let listener = bind_unix_socket(path, 0o660)
+        .with_context(|| format!("Cannot bind socket '{}'", path.display()))?;
+    if let Some(gid) = group {
+        chown(path, None, Some(gid))
+            .with_context(|| format!("Cannot change the group of socket '{}'", path.display()))?;
+    }
+    info!("Serving secrets on '{}'", path.display());
+    Ok(listener)
+}
+
+/// Serve clients on `listener` until the process is terminated.
+///
+/// At most `max_clients` clients are served at the same time. Further clients receive an error
+/// response and are disconnected.
+pub fn serve(
+    listener: UnixListener,
+    store: SecretStore,
+    policy: AccessPolicy,
+    max_clients: usize,
+) -> Result<()> {
+    let clients = Semaphore::new(max_clients);
+    thread::scope(|s| {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("{e}");
+                    continue;
+                }
+            };
+            let Some(permit) = clients.try_acquire() else {
+                warn!("Too many clients, rejecting connection");
+                // Best effort, the client might not read the response.
+                let _ = write_response(&mut &stream, &Response::error("Too many clients"));
+                continue;
+            };
+            let (store, policy) = (&store, &policy);
+            s.spawn(move || {
+                let _permit = permit;
+                // A failing client must not terminate the daemon.
+                if let Err(e) = serve_client(store, policy, stream) {
+                    warn!("{e:#}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cred(uid: u32, gid: u32) -> PeerCred {
+        PeerCred {
+            pid: 1,
+            uid,
+            gid,
+            groups: vec![],
+        }
+    }
+
+    #[test]
+    fn policy() {
+        let policy = AccessPolicy::new(vec![1000], vec![42]);
+        assert!(policy.allows(&cred(0, 0)));
+        assert!(policy.allows(&cred(1000, 1000)));
+        assert!(policy.allows(&cred(1001, 42)));
+        assert!(!policy.allows(&cred(1001, 1001)));
+        assert!(!AccessPolicy::default().allows(&cred(1000, 1000)));
+
+        let supplementary = PeerCred {
+            groups: vec![7, 42],
+            ..cred(1001, 1001)
+        };
+        assert!(policy.allows(&supplementary));
+    }
+
+    #[test]
+    fn request_size() {
+        let mut line = String::new();
+        let mut reader = std::io::Cursor::new("{\"cmd\":\"list\"}\n\n");
+        assert!(read_request(&mut reader, &mut line).unwrap());
+        assert_eq!(line, "{\"cmd\":\"list\"}\n");
+        assert!(read_request(&mut reader, &mut line).unwrap());
+        assert!(!read_request(&mut reader, &mut line).unwrap());
+
+        let long = " ".repeat(MAX_REQUEST as usize + 1);
+        let err = read_request(&mut long.as_bytes(), &mut line).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        // The newline fits exactly
+        let max = format!("{}\n", " ".repeat(MAX_REQUEST as usize - 1));
+        assert!(read_request(&mut max.as_bytes(), &mut line).unwrap());
+    }
+
+    #[test]
+    fn peer_cred() {
+        let (a, _b) = UnixStream::pair().unwrap();
+        let cred = PeerCred::of(&a).unwrap();
+        // SAFETY: getuid cannot fail
+        assert_eq!(cred.uid, unsafe { libc::getuid() });
+        assert_eq!(cred.pid, std::process::id() as i32);
+
+        // SAFETY: a zero sized query returns the number of groups
+        let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+        let mut groups = vec![0; count as usize];
+        // SAFETY: groups has room for count groups
+        unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+        groups.sort_unstable();
+        let mut peer_groups = cred.groups;
+        peer_groups.sort_unstable();
+        assert_eq!(peer_groups, groups);
+    }
+}
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    io::ErrorKind,
+    sync::{Mutex, MutexGuard},
+};
+
+use anyhow::{anyhow, Error, Result};
+use log::{debug, info, warn};
+use pv::{
+    audit::{AuditLog, AuditRecord},
+    misc::encode_hex,
+    secret::{GuestSecret, RetrievedSecret},
+    uv::{
+        ListCmd, PreparedCmd, ReconnectPolicy, RetrieveCmd, SecretEntry, SecretId, SecretList,
+        UvDevice,
+    },
+};
+
+const SECRET_LIST_BUF_SIZE: usize = 4;
+
+/// Do a List Secrets UVC
+///
+/// `cmd` is reused for every refresh. If the uvdevice does not support longer lists, it is
+/// replaced by a one page list.
+fn list_uvc(uv: &UvDevice, cmd: &mut PreparedCmd<ListCmd>) -> Result<SecretList> {
+    let more_data = match cmd.send(uv) {
+        Ok(v) => Ok(v),
+        Err(pv::PvCoreError::Io(e)) if e.kind() == ErrorKind::InvalidInput => {
+            info!("Uvdevice does not suport longer list. Fallback to one page list.");
+            *cmd = PreparedCmd::new(ListCmd::default());
+            cmd.send(uv)
+        }
+        Err(e) => Err(e),
+    }?
+    .more_data();
+    if more_data {
+        warn!("The secret list contains more data but the uvdevice cannot show all.");
+    }
+
+    SecretList::try_from(cmd.cmd()).map_err(Error::new)
+}
+
+fn lock_ignore_poison<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    match m.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Identifies a secret a client asks for
+#[derive(Debug)]
+pub enum Lookup {
+    Id(SecretId),
+    Name(String),
+}
+
+impl Lookup {
+    fn find(&self, secrets: &SecretList) -> Result<Option<SecretEntry>> {
+        Ok(match self {
+            Self::Id(id) => secrets.find(id),
+            // Names are either used directly as ID or hashed, see `pvsecret create`.
+            Self::Name(name) => match secrets.find(&SecretId::from_string(name)) {
+                Some(e) => Some(e),
+                None => secrets.find(&GuestSecret::name_to_id(name)?),
+            },
+        })
+    }
+}
+
+impl std::fmt::Display for Lookup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "ID {id:#x}"),
+            Self::Name(name) => write!(f, "name '{name}'"),
+        }
+    }
+}
+
+/// The uvdevice together with a cached secret list
+#[derive(Debug)]
+pub struct SecretStore {
+    uv: UvDevice,
+    cache: Mutex<SecretList>,
+    list_cmd: Mutex<PreparedCmd<ListCmd>>,
+    audit: AuditLog,
+}
+
+impl SecretStore {
+    /// Open the uvdevice and read the initial secret list.
+    ///
+    /// Retrieve operations are recorded to `audit`.
+    pub fn open(audit: AuditLog) -> Result<Self> {
+        let uv = UvDevice::inherited_or_open()?;
+        uv.set_reconnect_policy(ReconnectPolicy::Reopen);
+        let mut list_cmd = PreparedCmd::new(ListCmd::with_pages(SECRET_LIST_BUF_SIZE));
+        let cache = Mutex::new(list_uvc(&uv, &mut list_cmd)?);
+        Ok(Self {
+            uv,
+            cache,
+            list_cmd: Mutex::new(list_cmd),
+            audit,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SecretList> {
+        // The list is replaced as a whole; a panic cannot leave it inconsistent.
+        lock_ignore_poison(&self.cache)
+    }
+
+    /// Access the cached secret list.
+    pub fn list(&self) -> MutexGuard<'_, SecretList> {
+        self.lock()
+    }
+
+    /// Re-read the secret list from the Ultravisor.
+    pub fn refresh(&self) -> Result<()> {
+        let list = list_uvc(&self.uv, &mut lock_ignore_poison(&self.list_cmd))?;
+        info!("Refreshed secret list, {} secrets", list.len());
+        *self.lock() = list;
+        Ok(())
+    }
+
+    /// Retrieve a secret.
+    ///
+    /// If the secret is not in the cached list, the list is refreshed once, as secrets might have
+    /// been added since the last refresh. The operation is recorded to the audit log with
+    /// `record`, which identifies the client.
+    pub fn retrieve(
+        &self,
+        lookup: &Lookup,
+        record: AuditRecord,
+    ) -> Result<(SecretId, RetrievedSecret)> {
+        let entry = match lookup.find(&self.lock())? {
+            Some(entry) => entry,
+            None => {
+                debug!("Secret with {lookup} not cached, refreshing the list");
+                self.refresh()?;
+                lookup.find(&self.lock())?.ok_or(anyhow!(
+                    "The UV secret-store has no secret with the {lookup}"
+                ))?
+            }
+        };
+        let id = SecretId::from(
+            // Cannot fail, IDs are always 32 bytes long
+            <[u8; SecretId::ID_SIZE]>::try_from(entry.id()).unwrap(),
+        );
+
+        info!("Retrieve secret at index: {}", entry.index());
+        let record = record
+            .field("index", entry.index())
+            .field("id", encode_hex(entry.id()));
+        let mut uv_cmd = RetrieveCmd::from_entry(entry)?;
+        self.audit.send_cmd(&self.uv, &mut uv_cmd, record)?;
+        Ok((id, RetrievedSecret::from_cmd(uv_cmd)))
+    }
+}
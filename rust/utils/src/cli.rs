@@ -4,18 +4,27 @@
 
 use clap::{ArgAction, ArgGroup, Args, Command, ValueHint};
 use log::{info, warn, LevelFilter};
-use pv::misc::read_file;
+
+use crate::{log::LogFormat, TemporaryDirectory};
 use pv::{
-    misc::{create_file, open_file, read_certs},
+    audit::{AuditLog, AuditTarget},
+    misc::{
+        create_file, decode_hex, encode_hex, fetch_https, is_url, open_file, read_hkd, MachineId,
+    },
+    privilege::{lookup_group, lookup_user, Capability, PrivDrop},
     request::{
         openssl::pkey::{PKey, Public},
-        HkdVerifier,
+        CertInfo, CheckOutcome, CrlCache, HkdReport, HkdVerifier, PinInfo, VerificationReport,
     },
-    Error, Result,
+    sandbox::Sandbox,
+    PvCoreError, Result,
 };
+use std::fs::File;
 use std::io::{Read, Write};
+use std::os::fd::{FromRawFd, OwnedFd, RawFd};
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::str::FromStr;
 
 /// CLI Argument collection for handling host-keys, IBM signing keys, and certificates.
 #[derive(Args, Debug, Clone, PartialEq, Eq, Default)]
@@ -25,7 +34,9 @@ use std::process::ExitCode;
 pub struct CertificateOptions {
     /// Use FILE as a host-key document.
     ///
-    /// Can be specified multiple times and must be specified at least once.
+    /// Can be specified multiple times and must be specified at least once. Specify several
+    /// host-key documents to target several machines, or the current and a backup host key of a
+    /// machine. FILE can also be an HTTPS URL.
     #[arg(
         short = 'k',
         long = "host-key-document",
@@ -48,7 +59,7 @@ pub struct CertificateOptions {
     ///
     /// The certificates are used to establish a chain of trust for the verification
     /// of the host-key documents. Specify this option twice to specify the IBM Z signing key and
-    /// the intermediate CA certificate (signed by the root CA).
+    /// the intermediate CA certificate (signed by the root CA). FILE can also be an HTTPS URL.
     #[arg(
         short= 'C',
         long = "cert",
@@ -63,7 +74,8 @@ pub struct CertificateOptions {
     /// Use FILE as a certificate revocation list (CRL).
     ///
     /// The list is used to check whether a certificate of the chain of
-    /// trust is revoked. Specify this option multiple times to use multiple CRLs.
+    /// trust is revoked. Specify this option multiple times to use multiple CRLs. FILE can also be
+    /// an HTTPS URL.
     #[arg(
         long = "crl",
         requires("certs"),
@@ -74,16 +86,152 @@ pub struct CertificateOptions {
     )]
     pub crls: Vec<PathBuf>,
 
-    /// Make no attempt to download CRLs.
-    #[arg(long, requires("certs"))]
+    /// Make no attempt to access the network.
+    ///
+    /// Neither CRLs are downloaded nor inputs specified as HTTPS URLs.
+    #[arg(long)]
     pub offline: bool,
 
     /// Use FILE as the root-CA certificate for the verification.
     ///
     /// If omitted, the system wide-root CAs installed on the system are used.
-    /// Use this only if you trust the specified certificate.
+    /// Use this only if you trust the specified certificate. FILE can also be an HTTPS URL.
     #[arg(long, requires("certs"))]
     pub root_ca: Option<PathBuf>,
+
+    /// Accept the input downloaded from URL only if its SHA-256 digest is SHA256.
+    ///
+    /// SHA256 is the hex-encoded digest. Pins the content of an input specified as HTTPS URL
+    /// independent of the server. Can be specified multiple times.
+    #[arg(long = "pin", value_name = "URL=SHA256")]
+    pub pins: Vec<UrlPin>,
+
+    /// Use DIR to cache downloaded CRLs and the certificates of the chain of trust.
+    ///
+    /// Valid cached CRLs are used for the verification, also together with '--offline'. Use
+    /// 'pvimg import-bundle' to fill the cache of an air-gapped system. Defaults to
+    /// $XDG_CACHE_HOME/s390-tools/pv or $HOME/.cache/s390-tools/pv.
+    #[arg(long, value_name = "DIR", requires("certs"), value_hint = ValueHint::DirPath)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Do not use the CRL and certificate cache.
+    #[arg(long, requires("certs"), conflicts_with("cache_dir"))]
+    pub no_cache: bool,
+
+    /// Warn about host-key documents that are not issued for the machine [TYPE-]SERIAL.
+    ///
+    /// For example, 3931-02A1B2C. If omitted and running on IBM Z, the current machine is used.
+    #[arg(long, value_name = "[TYPE-]SERIAL")]
+    pub hkd_machine: Option<MachineId>,
+
+    /// Write a JSON report of the host-key document verification to FILE.
+    ///
+    /// The report lists the chain of trust, the CRLs consulted with their thisUpdate and
+    /// nextUpdate times, the pinning checks of downloaded inputs, and the outcome of every check
+    /// per host-key document. The report is also written if the verification fails.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub verification_report: Option<PathBuf>,
+}
+
+/// SHA-256 digest an input downloaded from an URL must have
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlPin {
+    url: String,
+    sha256: [u8; 32],
+}
+
+impl FromStr for UrlPin {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        // URLs may contain '=', hex digests do not
+        let (url, digest) = s
+            .rsplit_once('=')
+            .ok_or_else(|| "Expected URL=SHA256".to_string())?;
+        let sha256 = decode_hex(digest)
+            .ok()
+            .and_then(|d| d.try_into().ok())
+            .ok_or_else(|| format!("'{digest}' is no hex-encoded SHA-256 digest"))?;
+        Ok(Self {
+            url: url.to_string(),
+            sha256,
+        })
+    }
+}
+
+/// Local copies of the inputs specified as HTTPS URLs
+///
+/// The copies are removed when this is dropped.
+struct Downloads<'a> {
+    opt: &'a CertificateOptions,
+    dir: Option<TemporaryDirectory>,
+    num: usize,
+    pins: Vec<PinInfo>,
+}
+
+impl<'a> Downloads<'a> {
+    fn new(opt: &'a CertificateOptions) -> Self {
+        Self {
+            opt,
+            dir: None,
+            num: 0,
+            pins: vec![],
+        }
+    }
+
+    /// Returns a local path for `input`, downloading it first if it is an URL.
+    fn local<P: AsRef<Path>>(&mut self, input: P, what: &str) -> Result<PathBuf> {
+        let input = input.as_ref();
+        if !is_url(input) {
+            return Ok(input.to_path_buf());
+        }
+        let url = input.to_string_lossy();
+        if self.opt.offline {
+            return Err(pv::Error::DownloadOffline(url.into_owned()));
+        }
+        let pin = self
+            .opt
+            .pins
+            .iter()
+            .find(|p| p.url == url)
+            .map(|p| &p.sha256);
+        if pin.is_none() {
+            warn!("The {what} at '{url}' is not pinned to a SHA-256 digest");
+        }
+        let res = fetch_https(&url, pin);
+        let outcome = match (&res, pin) {
+            (_, None) => Some(CheckOutcome::Skipped),
+            (Ok(_), Some(_)) => Some(CheckOutcome::Passed),
+            (Err(pv::Error::DownloadChecksum(_)), Some(_)) => Some(CheckOutcome::Failed),
+            // the pin was not checked
+            (Err(_), Some(_)) => None,
+        };
+        if let Some(outcome) = outcome {
+            self.pins.push(PinInfo {
+                url: url.to_string(),
+                input: what.to_string(),
+                sha256: pin.map(encode_hex),
+                outcome,
+            });
+        }
+        let buf = res?;
+        info!("Downloaded the {what} from '{url}'");
+
+        let dir = match &mut self.dir {
+            Some(dir) => dir,
+            dir => dir.insert(TemporaryDirectory::with_prefix(
+                std::env::temp_dir().join("s390-tools-inputs."),
+            )?),
+        };
+        let path = dir.path().join(self.num.to_string());
+        self.num += 1;
+        std::fs::write(&path, buf)?;
+        Ok(path)
+    }
+
+    fn local_all<P: AsRef<Path>>(&mut self, inputs: &[P], what: &str) -> Result<Vec<PathBuf>> {
+        inputs.iter().map(|i| self.local(i, what)).collect()
+    }
 }
 
 impl CertificateOptions {
@@ -94,7 +242,11 @@ impl CertificateOptions {
     /// # Errors
     ///
     /// This function will return an error if [`crate::request::HkdVerifier`] cannot be created.
-    fn verifier(&self, protectee: &'static str) -> Result<Box<dyn HkdVerifier>> {
+    fn verifier(
+        &self,
+        protectee: &'static str,
+        downloads: &mut Downloads,
+    ) -> Result<Box<dyn HkdVerifier>> {
         use pv::request::{CertVerifier, NoVerifyHkd};
         match self.no_verify {
             true => {
@@ -103,54 +255,255 @@ impl CertificateOptions {
                 );
                 Ok(Box::new(NoVerifyHkd))
             }
-            false => Ok(Box::new(CertVerifier::new(
-                &self.certs,
-                &self.crls,
-                self.root_ca.as_ref(),
+            false => Ok(Box::new(CertVerifier::with_cache(
+                &downloads.local_all(&self.certs, "certificate")?,
+                &downloads.local_all(&self.crls, "CRL")?,
+                self.root_ca
+                    .as_ref()
+                    .map(|ca| downloads.local(ca, "root-CA certificate"))
+                    .transpose()?,
                 self.offline,
+                self.crl_cache()?,
             )?)),
         }
     }
 
+    /// Returns the CRL cache of this [`CertificateOptions`], if enabled.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the explicitly specified cache directory cannot be
+    /// created. Problems with the default cache directory are logged only.
+    fn crl_cache(&self) -> Result<Option<CrlCache>> {
+        if self.no_cache {
+            return Ok(None);
+        }
+        if let Some(dir) = &self.cache_dir {
+            return CrlCache::new(dir).map(Some);
+        }
+        let Some(dir) = CrlCache::default_dir() else {
+            return Ok(None);
+        };
+        match CrlCache::new(&dir) {
+            Ok(cache) => Ok(Some(cache)),
+            Err(e) => {
+                warn!("Cannot use the CRL cache at '{}': {e}", dir.display());
+                Ok(None)
+            }
+        }
+    }
+
     /// Read the host-keys specified and verifies them if required
     ///
     /// - `protectee`: what you want to create. e.g. add-secret request or SE-image
     ///
+    /// Writes the verification report if requested, also if the verification fails.
+    ///
     /// # Error
     /// Returns an error if something went wrong during parsing the HKDs, the verification chain
     /// could not built, or when the verification
     /// failed.
     pub fn get_verified_hkds(&self, protectee: &'static str) -> Result<Vec<PKey<Public>>> {
+        let mut downloads = Downloads::new(self);
+        let mut report = VerificationReport::default();
+        let res = self.verify_hkds(protectee, &mut downloads, &mut report);
+
+        if let Some(path) = &self.verification_report {
+            report.pins = std::mem::take(&mut downloads.pins);
+            report.verified =
+                res.is_ok() && !report.hkds.is_empty() && report.hkds.iter().all(|h| h.verified);
+            let mut file = create_file(path)?;
+            serde_json::to_writer_pretty(&mut file, &report).map_err(std::io::Error::from)?;
+            writeln!(file)?;
+            info!("Wrote the verification report to '{}'", path.display());
+        }
+        res
+    }
+
+    fn verify_hkds(
+        &self,
+        protectee: &'static str,
+        downloads: &mut Downloads,
+        report: &mut VerificationReport,
+    ) -> Result<Vec<PKey<Public>>> {
         let hkds = &self.host_key_documents;
-        let verifier = self.verifier(protectee)?;
+        let verifier = self.verifier(protectee, downloads)?;
+        report.chain = verifier
+            .chain()
+            .iter()
+            .map(|c| CertInfo::new(c))
+            .collect::<Result<_>>()?;
+
+        let machine = self.hkd_machine.clone().or_else(MachineId::current);
+        let mut matched = false;
 
         let mut res = Vec::with_capacity(hkds.len());
         for hkd in hkds {
-            let hk = read_file(hkd, "host-key document")?;
-            let certs = read_certs(&hk).map_err(|source| Error::HkdNotPemOrDer {
-                hkd: hkd.display().to_string(),
-                source,
-            })?;
-            if certs.is_empty() {
-                return Err(Error::NoHkdInFile(hkd.display().to_string()));
-            }
-            if certs.len() != 1 {
-                warn!(
-                    "The host-key document in '{}' contains more than one certificate!",
-                    hkd.display()
-                )
-            }
-
-            // Panic: len is == 1 -> unwrap will succeed/not panic
-            let c = certs.first().unwrap();
-            verifier.verify(c)?;
+            let c = read_hkd(downloads.local(hkd, "host-key document")?)?;
+            let mut hkd_report = HkdReport::new(&c)?;
+            hkd_report.source = Some(hkd.display().to_string());
+            let verified = verifier.verify_with_report(&c, &mut hkd_report);
+            report.hkds.push(hkd_report);
+            verified?;
             res.push(c.public_key()?);
             info!("Use host-key document at '{}'", hkd.display());
+            matched |= check_hkd_machine(machine.as_ref(), &c, hkd);
+        }
+        if let Some(machine) = machine.as_ref().filter(|_| !matched) {
+            warn!("None of the host-key documents is issued for the machine {machine}. The {protectee} cannot be used on this machine.");
         }
         Ok(res)
     }
 }
 
+/// Warns if the host-key document `hkd` at `path` is issued for a machine other than `machine`.
+///
+/// Returns `true` if `hkd` is issued for `machine` or does not identify a machine.
+fn check_hkd_machine(
+    machine: Option<&MachineId>,
+    hkd: &pv::request::openssl::x509::X509Ref,
+    path: &Path,
+) -> bool {
+    let (Some(machine), Some(target)) = (machine, MachineId::of_hkd(hkd, Some(path))) else {
+        return true;
+    };
+    if target.matches(machine) {
+        info!(
+            "The host-key document at '{}' is issued for the machine {machine}",
+            path.display()
+        );
+        true
+    } else {
+        warn!(
+            "The host-key document at '{}' is issued for the machine {target}, not for {machine}",
+            path.display()
+        );
+        false
+    }
+}
+
+/// CLI Argument collection for audit logging of Ultravisor operations.
+#[derive(Args, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AuditOptions {
+    /// Append a record of every Ultravisor operation that touches the secret store to FILE.
+    ///
+    /// Records contain the time, the caller, the operation, the Ultravisor return codes, and
+    /// digests of the request.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub audit_log: Option<PathBuf>,
+
+    /// Send a record of every Ultravisor operation to the Linux audit framework.
+    ///
+    /// Requires the CAP_AUDIT_WRITE capability.
+    #[arg(long, conflicts_with("audit_log"))]
+    pub audit_kernel: bool,
+}
+
+impl AuditOptions {
+    /// Opens the audit log specified by these options.
+    ///
+    /// If no audit logging was requested, the log discards all records.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the audit log cannot be opened.
+    pub fn open(&self) -> Result<AuditLog> {
+        match (&self.audit_log, self.audit_kernel) {
+            (Some(path), _) => AuditLog::open(&AuditTarget::File(path.clone())),
+            (None, true) => AuditLog::open(&AuditTarget::Kernel),
+            (None, false) => Ok(AuditLog::none()),
+        }
+    }
+}
+
+/// CLI Argument collection for dropping privileges after opening privileged resources.
+#[derive(Args, Debug, Clone, PartialEq, Eq, Default)]
+pub struct PrivilegeOptions {
+    /// Drop privileges to USER after opening the uvdevice.
+    ///
+    /// USER is a user name or a numeric user ID. All untrusted input is processed without root
+    /// privileges.
+    #[arg(long, value_name = "USER")]
+    pub user: Option<String>,
+
+    /// Drop privileges to GROUP after opening the uvdevice.
+    ///
+    /// GROUP is a group name or a numeric group ID. Defaults to the primary group of USER.
+    #[arg(long, value_name = "GROUP", requires("user"))]
+    pub group: Option<String>,
+
+    /// Keep the capability CAP after dropping privileges.
+    ///
+    /// For example, specify 'cap_audit_write' together with '--audit-kernel'. Can be specified
+    /// multiple times.
+    #[arg(long = "keep-cap", value_name = "CAP", requires("user"))]
+    pub keep_caps: Vec<Capability>,
+}
+
+impl PrivilegeOptions {
+    /// Irreversibly drops the privileges of the process as specified by these options.
+    ///
+    /// Does nothing if no user was specified.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the user or group is unknown or the privileges
+    /// cannot be dropped.
+    pub fn drop_privileges(&self) -> Result<()> {
+        let Some(user) = &self.user else {
+            return Ok(());
+        };
+        let (uid, primary_gid) = lookup_user(user)?;
+        let gid = match (&self.group, primary_gid) {
+            (Some(group), _) => lookup_group(group)?,
+            (None, Some(gid)) => gid,
+            (None, None) => {
+                return Err(PvCoreError::UnknownId {
+                    kind: "primary group of user",
+                    name: user.clone(),
+                }
+                .into())
+            }
+        };
+        self.keep_caps
+            .iter()
+            .fold(PrivDrop::new(uid, gid), |d, c| d.keep(*c))
+            .apply()?;
+        info!("Dropped privileges to uid {uid} gid {gid}");
+        Ok(())
+    }
+}
+
+/// CLI Argument collection for sandboxing the processing of untrusted input.
+#[derive(Args, Debug, Clone, PartialEq, Eq, Default)]
+pub struct SandboxOptions {
+    /// Sandbox the process before processing the input.
+    ///
+    /// After all input and output files are opened, restrict the file system access with Landlock
+    /// and the system calls with seccomp. Limits the impact of bugs in the parsers of untrusted
+    /// input. Without Landlock support of the kernel, only the system calls are restricted.
+    #[arg(long)]
+    pub sandbox: bool,
+}
+
+impl SandboxOptions {
+    /// Irreversibly applies `sandbox` if sandboxing was requested.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the sandbox cannot be applied.
+    pub fn apply(&self, sandbox: Sandbox) -> Result<()> {
+        if !self.sandbox {
+            return Ok(());
+        }
+        // Initialize OpenSSL before opening its configuration becomes impossible
+        pv::request::openssl::init();
+        sandbox.apply()?;
+        info!("Sandboxed the process");
+        Ok(())
+    }
+}
+
 /// stdout
 pub const STDOUT: &str = "-";
 /// stdin
@@ -165,6 +518,19 @@ pub fn get_writer_from_cli_file_arg<P: AsRef<Path>>(path: P) -> Result<Box<dyn W
     }
 }
 
+/// Converts a file descriptor argument into a Writer.
+///
+/// The file descriptor is duplicated; the caller stays responsible for closing `fd`.
+pub fn get_writer_from_cli_fd_arg(fd: RawFd) -> Result<File> {
+    // SAFETY: no pointers involved, fails for file descriptors that are not open
+    let dup = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+    if dup < 0 {
+        return Err(PvCoreError::from(std::io::Error::last_os_error()).into());
+    }
+    // SAFETY: dup is a freshly duplicated, owned file descriptor
+    Ok(File::from(unsafe { OwnedFd::from_raw_fd(dup) }))
+}
+
 /// Converts an argument value into a Reader.
 pub fn get_reader_from_cli_file_arg<P: AsRef<Path>>(path: P) -> Result<Box<dyn Read>> {
     if path.as_ref() == Path::new(STDIN) {
@@ -192,7 +558,9 @@ where
     // Error trait is not required, but here to limit the usage to errors
     E: AsRef<dyn std::error::Error> + std::fmt::Debug + std::fmt::Display,
 {
-    if verbosity > LevelFilter::Warn {
+    if crate::log::json_enabled() {
+        eprintln!("{}", error_json_line(e.as_ref(), verbosity));
+    } else if verbosity > LevelFilter::Warn {
         // Debug formatter also prints the whole error stack
         // So only print it when on verbose
         eprintln!("error: {e:?}")
@@ -202,6 +570,57 @@ where
     ExitCode::FAILURE
 }
 
+/// Formats an error as JSON log line.
+///
+/// Adds the stable error code, the remediation hint, the Ultravisor return codes, and the file path
+/// of the first error in the chain of sources that has them. Errors without a code of their own are
+/// reported as `error`.
+fn error_json_line(e: &(dyn std::error::Error + 'static), verbosity: LevelFilter) -> String {
+    use serde_json::{Map, Value};
+
+    let mut fields = Map::new();
+    let mut chain = vec![];
+    let mut source = Some(e);
+    while let Some(err) = source {
+        chain.push(Value::from(err.to_string()));
+        let core_err = err.downcast_ref::<PvCoreError>().or_else(|| {
+            // pv errors wrap pv_core errors transparently
+            match err.downcast_ref::<pv::Error>() {
+                Some(pv::Error::PvCore(e)) => Some(e),
+                _ => None,
+            }
+        });
+        let code = err
+            .downcast_ref::<pv::Error>()
+            .map(|e| (e.code(), e.hint()))
+            .or_else(|| core_err.map(|e| (e.code(), e.hint())));
+        if let Some((code, hint)) = code.filter(|_| !fields.contains_key("code")) {
+            fields.insert("code".into(), code.into());
+            if let Some(hint) = hint {
+                fields.insert("hint".into(), hint.into());
+            }
+        }
+        match core_err {
+            Some(PvCoreError::Uv { rc, rrc, .. }) if !fields.contains_key("rc") => {
+                fields.insert("rc".into(), (*rc).into());
+                fields.insert("rrc".into(), (*rrc).into());
+            }
+            Some(PvCoreError::FileAccess { path, .. } | PvCoreError::FileIo { path, .. })
+                if !fields.contains_key("path") =>
+            {
+                fields.insert("path".into(), path.display().to_string().into());
+            }
+            _ => (),
+        }
+        source = err.source();
+    }
+    fields.entry("code").or_insert_with(|| "error".into());
+    if verbosity > LevelFilter::Warn {
+        fields.insert("causes".into(), Value::Array(chain.split_off(1)));
+    }
+    crate::log::json_line(::log::Level::Error, "error", &e.to_string(), fields)
+}
+
 #[derive(Args, Debug, Clone, Default)]
 pub struct VerbosityOptions {
     #[arg(
@@ -224,6 +643,10 @@ pub struct VerbosityOptions {
     )]
     /// Provide less output.
     quiet: u8,
+
+    /// Define the format of the log output.
+    #[arg(long, value_enum, default_value_t, global = true, display_order = 999)]
+    log_format: LogFormat,
 }
 
 const fn to_level_filter(v: u8) -> LevelFilter {
@@ -238,6 +661,11 @@ const fn to_level_filter(v: u8) -> LevelFilter {
 }
 
 impl VerbosityOptions {
+    /// Returns the format of the log output.
+    pub fn log_format(&self) -> LogFormat {
+        self.log_format
+    }
+
     fn verbosity(&self) -> u8 {
         (LevelFilter::Warn as i16 + self.verbose as i16 - self.quiet as i16)
             .clamp(u8::MIN.into(), u8::MAX.into()) as u8
@@ -264,6 +692,11 @@ pub struct DeprecatedVerbosityOptions {
 }
 
 impl DeprecatedVerbosityOptions {
+    /// Returns the format of the log output.
+    pub fn log_format(&self) -> LogFormat {
+        self.verbosity.log_format()
+    }
+
     pub fn to_level_filter(&self) -> LevelFilter {
         if self.deprecated_verbose > 0 {
             // Use eprintln as the logger is most likely not yet initialized.
@@ -288,7 +721,11 @@ mod test {
     fn cli_args() {
         //Verify only that some arguments are optional, we do not want to test clap, only the
         //configuration
-        let valid_args = [vec!["pgr", "-k", "hkd.crt", "--no-verify"], vec!["pgr", "-k", "hkd.crt", "--crt", "abc.crt"]];
+        let valid_args = [
+            vec!["pgr", "-k", "hkd.crt", "--no-verify"],
+            vec!["pgr", "-k", "hkd.crt", "--crt", "abc.crt"],
+            vec!["pgr", "-k", "https://example.com/hkd.crt", "--no-verify", "--offline"],
+        ];
         // Test for the minimal amount of flags to yield an invalid combination
         let invalid_args = [
             vec!["pgr", "-k", "hkd.crt"],
@@ -299,6 +736,8 @@ mod test {
             vec!["pgr", "--offline"],
             vec!["pgr", "--crl", "abc.crl"],
             vec!["pgr", "--root-ca", "root.crt"],
+            vec!["pgr", "-k", "hkd.crt", "--no-verify", "--pin", "https://example.com/hkd.crt"],
+            vec!["pgr", "-k", "hkd.crt", "--no-verify", "--pin", "https://example.com/hkd.crt=00"],
         ];
         #[derive(Parser, Debug)]
         struct TestParser {
@@ -316,4 +755,107 @@ mod test {
             assert!(res.is_err());
         }
     }
+
+    #[test]
+    fn url_pin() {
+        let digest = "ab".repeat(32);
+        let pin: UrlPin = format!("https://example.com/crl?a=b={digest}")
+            .parse()
+            .unwrap();
+        assert_eq!(pin.url, "https://example.com/crl?a=b");
+        assert_eq!(pin.sha256, [0xab; 32]);
+        assert!("https://example.com/crl".parse::<UrlPin>().is_err());
+        assert!("https://example.com/crl=abab".parse::<UrlPin>().is_err());
+    }
+
+    #[test]
+    fn error_json() {
+        let err = pv::Error::PvCore(PvCoreError::Uv {
+            rc: 0x0102,
+            rrc: 0,
+            msg: "secret store locked",
+        });
+        let json: serde_json::Value =
+            serde_json::from_str(&error_json_line(&err, LevelFilter::Warn)).unwrap();
+        assert_eq!(json["code"], "store-locked");
+        assert_eq!(json["rc"], 0x0102);
+        assert!(json["hint"].is_string());
+
+        let err = std::io::Error::other("Something failed");
+        let json: serde_json::Value =
+            serde_json::from_str(&error_json_line(&err, LevelFilter::Warn)).unwrap();
+        assert_eq!(json["message"], "Something failed");
+        assert_eq!(json["code"], "error");
+        assert!(json.get("hint").is_none());
+    }
+
+    #[test]
+    fn verification_report() {
+        use pv::test_utils::get_cert_asset_path;
+        let dir = TemporaryDirectory::new().unwrap();
+        let path = dir.path().join("report.json");
+        let mut opt = CertificateOptions {
+            host_key_documents: vec![get_cert_asset_path("host.crt")],
+            certs: vec![
+                get_cert_asset_path("ibm.crt"),
+                get_cert_asset_path("inter_ca.crt"),
+            ],
+            crls: vec![
+                get_cert_asset_path("ibm.crl"),
+                get_cert_asset_path("inter_ca.crl"),
+            ],
+            root_ca: Some(get_cert_asset_path("root_ca.chained.crt")),
+            offline: true,
+            no_cache: true,
+            verification_report: Some(path.clone()),
+            ..Default::default()
+        };
+        opt.get_verified_hkds("test").unwrap();
+        let report: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(report["verified"], true);
+        assert_eq!(report["chain"].as_array().unwrap().len(), 3);
+        assert_eq!(report["hkds"][0]["checks"][6]["name"], "revocation");
+        assert!(report["hkds"][0]["crls"][0]["this_update"].is_string());
+
+        // the report is written if the verification fails
+        opt.host_key_documents
+            .push(get_cert_asset_path("host_rev.crt"));
+        assert!(opt.get_verified_hkds("test").is_err());
+        let report: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(report["verified"], false);
+        assert_eq!(report["hkds"][1]["checks"][6]["outcome"], "failed");
+    }
+
+    #[test]
+    fn offline_url() {
+        let opt = CertificateOptions {
+            host_key_documents: vec!["https://example.com/hkd.crt".into()],
+            no_verify: true,
+            offline: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            opt.get_verified_hkds("test"),
+            Err(pv::Error::DownloadOffline(_))
+        ));
+    }
+
+    #[test]
+    fn writer_from_fd() {
+        use std::os::fd::AsRawFd;
+
+        let dir = TemporaryDirectory::new().unwrap();
+        let path = dir.path().join("out");
+        let file = File::create(&path).unwrap();
+        get_writer_from_cli_fd_arg(file.as_raw_fd())
+            .unwrap()
+            .write_all(b"secret")
+            .unwrap();
+        drop(file);
+        assert_eq!(std::fs::read(&path).unwrap(), b"secret");
+
+        assert!(get_writer_from_cli_fd_arg(-1).is_err());
+    }
 }
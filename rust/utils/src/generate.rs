@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::{Args, Command, Subcommand};
+use clap_complete::Shell;
+use clap_mangen::Man;
+use pv::{misc::create_file, Result};
+
+/// Generate shell completions and man pages from the command line definition.
+///
+/// Intended to be used as a hidden subcommand at package build time.
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+pub struct GenerateOptions {
+    #[command(subcommand)]
+    pub kind: GenerateKind,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum GenerateKind {
+    /// Print the completion script for SHELL to stdout.
+    Completion {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Write a man page for the tool and each of its commands into DIR.
+    Man {
+        #[arg(value_name = "DIR", default_value = ".")]
+        dir: PathBuf,
+
+        /// Date for the man page header (YYYY-MM-DD).
+        ///
+        /// Defaults to the date of SOURCE_DATE_EPOCH if set, otherwise today.
+        #[arg(long)]
+        date: Option<String>,
+    },
+}
+
+impl GenerateOptions {
+    /// Generate the requested output for `cmd`.
+    ///
+    /// `manual` is the title of the manual the man pages belong to, e.g. "UV-Secret Manual".
+    pub fn run(&self, mut cmd: Command, manual: &str) -> Result<()> {
+        match &self.kind {
+            GenerateKind::Completion { shell } => {
+                let name = cmd.get_name().to_string();
+                // clap_complete panics on write errors, e.g. a closed pipe.
+                let mut buf = vec![];
+                clap_complete::generate(*shell, &mut cmd, name, &mut buf);
+                Ok(std::io::stdout().write_all(&buf)?)
+            }
+            GenerateKind::Man { dir, date } => {
+                let date = date.clone().unwrap_or_else(build_date);
+                let mut cmd = cmd.disable_help_subcommand(true);
+                cmd.build();
+                write_man_pages(&cmd, manual, &date, dir)
+            }
+        }
+    }
+}
+
+fn write_man_pages(cmd: &Command, manual: &str, date: &str, dir: &Path) -> Result<()> {
+    // Subcommands are named after their parent after the command was built, e.g. "tool-list".
+    let name = cmd.get_display_name().unwrap_or_else(|| cmd.get_name());
+    let year = date.split('-').next().unwrap_or_default();
+    let mut page = format!(
+        ".\\\" Copyright {year} IBM Corp.\n\
+         .\\\" s390-tools is free software; you can redistribute it and/or modify\n\
+         .\\\" it under the terms of the MIT license. See LICENSE for details.\n\
+         .\\\"\n"
+    )
+    .into_bytes();
+    Man::new(cmd.clone())
+        .title(name.to_uppercase())
+        .section("1")
+        .date(date)
+        .source("s390-tools")
+        .manual(manual)
+        .render(&mut page)?;
+    create_file(dir.join(format!("{name}.1")))?.write_all(&page)?;
+
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        write_man_pages(sub, manual, date, dir)?;
+    }
+    Ok(())
+}
+
+/// Date of the build in YYYY-MM-DD format.
+///
+/// Respects SOURCE_DATE_EPOCH for reproducible builds.
+fn build_date() -> String {
+    let secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default()
+        });
+    date_from_epoch(secs)
+}
+
+/// Convert seconds since the epoch into a proleptic Gregorian date (YYYY-MM-DD).
+fn date_from_epoch(secs: u64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = secs / 86400 + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod test {
+    use clap::{CommandFactory, Parser, ValueEnum};
+
+    use super::*;
+
+    #[derive(ValueEnum, Clone, Debug)]
+    enum Format {
+        /// Human-focused output.
+        Human,
+        /// Use yaml format.
+        Yaml,
+    }
+
+    /// Does things
+    #[derive(Parser, Debug)]
+    #[command(name = "tool")]
+    struct Cli {
+        #[command(subcommand)]
+        cmd: Cmd,
+    }
+
+    #[derive(Subcommand, Debug)]
+    enum Cmd {
+        /// List all the things.
+        ///
+        /// Long description of list.
+        List {
+            /// Store the result in FILE.
+            ///
+            /// .tool is appended if FILE has no extension.
+            #[arg(value_name = "FILE", default_value = "-")]
+            output: String,
+            /// Define the output format.
+            #[arg(long, value_enum, default_value_t = Format::Human)]
+            format: Format,
+        },
+        #[command(hide = true)]
+        Generate(GenerateOptions),
+    }
+
+    #[test]
+    fn date() {
+        assert_eq!(date_from_epoch(0), "1970-01-01");
+        assert_eq!(date_from_epoch(951_782_400), "2000-02-29");
+        assert_eq!(date_from_epoch(1_760_486_400), "2025-10-15");
+    }
+
+    #[test]
+    fn man_pages_written() {
+        let dir = crate::TemporaryDirectory::new().unwrap();
+        let opt = GenerateOptions {
+            kind: GenerateKind::Man {
+                dir: dir.path().to_owned(),
+                date: Some("2025-01-02".to_string()),
+            },
+        };
+        opt.run(Cli::command(), "Tool Manual").unwrap();
+        assert!(dir.path().join("tool.1").exists());
+        assert!(dir.path().join("tool-list.1").exists());
+        assert!(!dir.path().join("tool-generate.1").exists());
+        assert!(!dir.path().join("tool-help.1").exists());
+
+        let page = std::fs::read_to_string(dir.path().join("tool-list.1")).unwrap();
+        assert!(page.starts_with(".\\\" Copyright 2025 IBM Corp.\n"));
+        assert!(page.contains(".TH TOOL-LIST 1 2025-01-02 s390-tools \"Tool Manual\""));
+        assert!(page.contains("\n\\&.tool is appended"), "{page}");
+        assert_eq!(page.matches(".RS").count(), page.matches(".RE").count());
+    }
+}
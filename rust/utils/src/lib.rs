@@ -6,21 +6,30 @@
 mod cli;
 mod exit_code;
 mod file;
+mod generate;
 mod hexslice;
 mod log;
+mod metrics;
+mod semaphore;
+mod socket;
 mod tmpfile;
 
 pub use ::log::LevelFilter;
 
 pub use crate::{
     cli::{
-        get_reader_from_cli_file_arg, get_writer_from_cli_file_arg, print_cli_error, print_error,
-        CertificateOptions, DeprecatedVerbosityOptions, VerbosityOptions, STDIN, STDOUT,
+        get_reader_from_cli_file_arg, get_writer_from_cli_fd_arg, get_writer_from_cli_file_arg,
+        print_cli_error, print_error, AuditOptions, CertificateOptions, DeprecatedVerbosityOptions,
+        PrivilegeOptions, SandboxOptions, UrlPin, VerbosityOptions, STDIN, STDOUT,
     },
     exit_code::{docstring, ExitCodeDoc, ExitCodeTrait, ExitCodeVariantDoc},
     file::{AtomicFile, AtomicFileOperation},
+    generate::{GenerateKind, GenerateOptions},
     hexslice::HexSlice,
-    log::PvLogger,
+    log::{LogFormat, PvLogger},
+    metrics::{MetricsOptions, MetricsServer},
+    semaphore::{Permit, Semaphore},
+    socket::bind_unix_socket,
     tmpfile::TemporaryDirectory,
 };
 
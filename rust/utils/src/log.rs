@@ -2,7 +2,35 @@
 //
 // Copyright IBM Corp. 2023
 
-use log::{self, Level, LevelFilter, Log, Metadata, Record};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+use log::{
+    self,
+    kv::{self, Key, Value, VisitSource},
+    Level, LevelFilter, Log, Metadata, Record,
+};
+use serde_json::{Map, Value as Json};
+
+/// Format of the log output
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Human-readable text.
+    #[default]
+    Human,
+    /// One JSON object per line, e.g. for journald or ELK.
+    Json,
+}
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Returns `true` if the log output is in JSON format.
+pub(crate) fn json_enabled() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
 
 /// A simple Logger that prints to stderr if the verbosity level is high enough.
 /// Prints log-level for Debug+Trace
@@ -16,24 +44,151 @@ impl PvLogger {
     ///
     /// An error is returned if a logger has already been set.
     pub fn start(&'static self, filter: LevelFilter) -> Result<(), log::SetLoggerError> {
-        log::set_logger(self).map(|()| log::set_max_level(filter))
+        self.start_with_format(filter, LogFormat::Human)
+    }
+
+    /// Set self as the logger for this application and print in `format`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a logger has already been set.
+    pub fn start_with_format(
+        &'static self,
+        filter: LevelFilter,
+        format: LogFormat,
+    ) -> Result<(), log::SetLoggerError> {
+        log::set_logger(self).map(|()| {
+            log::set_max_level(filter);
+            JSON.store(format == LogFormat::Json, Ordering::Relaxed);
+        })
     }
 }
 
+/// Collects the key-values of a record as JSON values
+struct JsonFields<'a>(&'a mut Map<String, Json>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(v) = value.to_u64() {
+            v.into()
+        } else if let Some(v) = value.to_i64() {
+            v.into()
+        } else if let Some(v) = value.to_bool() {
+            v.into()
+        } else {
+            value.to_string().into()
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+/// Formats `time` as RFC 3339 timestamp in UTC with microseconds.
+fn rfc3339(time: SystemTime) -> String {
+    let dur = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = dur.as_secs();
+    let (days, rem) = (secs / 86400, secs % 86400);
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:06}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        dur.subsec_micros()
+    )
+}
+
+/// Name of the running program
+fn command() -> String {
+    std::env::args_os()
+        .next()
+        .as_deref()
+        .map(std::path::Path::new)
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Formats a log entry as JSON line.
+///
+/// Contains the `timestamp`, `level`, `command`, `target`, and `message` followed by `fields`.
+pub(crate) fn json_line(
+    level: Level,
+    target: &str,
+    message: &str,
+    fields: Map<String, Json>,
+) -> String {
+    let mut entry = Map::new();
+    entry.insert("timestamp".into(), rfc3339(SystemTime::now()).into());
+    entry.insert("level".into(), level.as_str().into());
+    entry.insert("command".into(), command().into());
+    entry.insert("target".into(), target.into());
+    entry.insert("message".into(), message.into());
+    entry.extend(fields);
+    Json::Object(entry).to_string()
+}
+
 impl Log for PvLogger {
     fn enabled(&self, _metadata: &Metadata) -> bool {
         true
     }
 
     fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
-            if record.level() > Level::Info {
-                eprintln!("{}: {}", record.level(), record.args());
-            } else {
-                eprintln!("{}", record.args());
-            }
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if json_enabled() {
+            let mut fields = Map::new();
+            let _ = record.key_values().visit(&mut JsonFields(&mut fields));
+            eprintln!(
+                "{}",
+                json_line(
+                    record.level(),
+                    record.target(),
+                    &record.args().to_string(),
+                    fields
+                )
+            );
+        } else if record.level() > Level::Info {
+            eprintln!("{}: {}", record.level(), record.args());
+        } else {
+            eprintln!("{}", record.args());
         }
     }
 
     fn flush(&self) {}
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rfc3339() {
+        assert_eq!(super::rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000000Z");
+        let time = UNIX_EPOCH + std::time::Duration::from_micros(1_709_219_045_123_456);
+        assert_eq!(super::rfc3339(time), "2024-02-29T15:04:05.123456Z");
+    }
+
+    #[test]
+    fn json_line() {
+        let mut fields = Map::new();
+        fields.insert("rc".into(), 0x101.into());
+        let line = super::json_line(Level::Warn, "pvsecret", "a \"message\"", fields);
+        let json: Json = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["level"], "WARN");
+        assert_eq!(json["target"], "pvsecret");
+        assert_eq!(json["message"], "a \"message\"");
+        assert_eq!(json["rc"], 0x101);
+        assert!(json["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+}
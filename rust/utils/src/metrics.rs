@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use clap::Args;
+use log::{info, warn};
+use pv::{uv::UvMetrics, Result};
+
+/// Upper bound for the HTTP request line of a scrape. Longer requests are cut off.
+const MAX_REQUEST_LINE: u64 = 1024;
+
+/// Read and write timeout of a scrape. Scrapes are served one at a time, so that a stalled client
+/// must not block the others for longer than this.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// CLI Argument collection for exposing metrics of long-running services.
+#[derive(Args, Debug, Clone, PartialEq, Eq, Default)]
+pub struct MetricsOptions {
+    /// Serve Prometheus metrics about the Ultravisor calls on ADDR.
+    ///
+    /// The metrics are available via HTTP at '/metrics', e.g. '127.0.0.1:9464'.
+    #[arg(long, value_name = "ADDR")]
+    pub metrics: Option<SocketAddr>,
+}
+
+impl MetricsOptions {
+    /// Binds the metrics address, if requested.
+    ///
+    /// Serving is split from binding, so that privileges can be dropped in between while the
+    /// process is still single-threaded: bind, drop the privileges, then
+    /// [`MetricsServer::serve`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the metrics address cannot be bound.
+    pub fn bind(&self) -> Result<Option<MetricsServer>> {
+        let Some(addr) = self.metrics else {
+            return Ok(None);
+        };
+        let listener = TcpListener::bind(addr)?;
+        Ok(Some(MetricsServer { listener, addr }))
+    }
+}
+
+/// A bound metrics listener that is not yet served.
+#[derive(Debug)]
+pub struct MetricsServer {
+    listener: TcpListener,
+    addr: SocketAddr,
+}
+
+impl MetricsServer {
+    /// Starts serving the metrics in a background thread.
+    pub fn serve(self) {
+        let Self { listener, addr } = self;
+        info!("Serving metrics on 'http://{addr}/metrics'");
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if let Err(e) = stream.and_then(handle_scrape) {
+                    warn!("Metrics: {e}");
+                }
+            }
+        });
+    }
+}
+
+fn handle_scrape(stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new((&stream).take(MAX_REQUEST_LINE)).read_line(&mut line)?;
+    let (status, body) = match line.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+        ["GET", "/metrics"] => ("200 OK", UvMetrics::global().encode_prometheus()),
+        ["GET", _] => ("404 Not Found", String::new()),
+        _ => ("405 Method Not Allowed", String::new()),
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scrape(addr: SocketAddr, req: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(req.as_bytes()).unwrap();
+        let mut rsp = String::new();
+        stream.read_to_string(&mut rsp).unwrap();
+        rsp
+    }
+
+    #[test]
+    fn serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(4) {
+                handle_scrape(stream.unwrap()).unwrap();
+            }
+        });
+
+        let rsp = scrape(addr, "GET /metrics HTTP/1.1\r\n\r\n");
+        assert!(rsp.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(rsp.contains("# TYPE uv_calls_total counter\n"));
+        assert!(scrape(addr, "GET / HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 404"));
+        assert!(scrape(addr, "POST /metrics HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 405"));
+        // The request line is cut off instead of waiting for a newline
+        let long = format!("GET /{}", "a".repeat(MAX_REQUEST_LINE as usize - 5));
+        assert!(scrape(addr, &long).starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn no_metrics() {
+        assert!(MetricsOptions::default().bind().unwrap().is_none());
+    }
+}
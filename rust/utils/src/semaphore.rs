@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::sync::{Condvar, Mutex};
+
+/// Counting semaphore limiting the number of concurrent operations
+///
+/// Long-running services use it to bound the number of clients they serve at the same time.
+#[derive(Debug)]
+pub struct Semaphore {
+    free: Mutex<usize>,
+    cond: Condvar,
+}
+
+/// A slot of a [`Semaphore`]; released on drop.
+#[derive(Debug)]
+pub struct Permit<'a>(&'a Semaphore);
+
+impl Semaphore {
+    /// Create a semaphore with `slots` free slots.
+    pub fn new(slots: usize) -> Self {
+        Self {
+            free: Mutex::new(slots),
+            cond: Condvar::new(),
+        }
+    }
+
+    /// Take a slot, if one is free.
+    pub fn try_acquire(&self) -> Option<Permit<'_>> {
+        let mut free = self.free.lock().unwrap();
+        match *free {
+            0 => None,
+            _ => {
+                *free -= 1;
+                Some(Permit(self))
+            }
+        }
+    }
+
+    /// Wait for a free slot and take it.
+    pub fn acquire(&self) -> Permit<'_> {
+        let mut free = self
+            .cond
+            .wait_while(self.free.lock().unwrap(), |free| *free == 0)
+            .unwrap();
+        *free -= 1;
+        Permit(self)
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        *self.0.free.lock().unwrap() += 1;
+        self.0.cond.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn semaphore() {
+        let sem = Semaphore::new(2);
+        let a = sem.try_acquire().unwrap();
+        let _b = sem.acquire();
+        assert!(sem.try_acquire().is_none());
+        drop(a);
+        let _c = sem.try_acquire().unwrap();
+        assert!(sem.try_acquire().is_none());
+
+        let sem = Semaphore::new(1);
+        let permit = sem.acquire();
+        std::thread::scope(|s| {
+            let waiter = s.spawn(|| drop(sem.acquire()));
+            drop(permit);
+            waiter.join().unwrap();
+        });
+    }
+}
@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{
+    fs,
+    io::{Error, ErrorKind, Result},
+    os::unix::{
+        fs::{FileTypeExt, PermissionsExt},
+        net::UnixListener,
+    },
+    path::Path,
+};
+
+/// Bind a unix socket at `path` that is accessible with `mode`.
+///
+/// A stale socket at `path` is replaced; any other file is left alone and an error is returned.
+/// The socket is created with `mode`, so that it is never more accessible than requested.
+///
+/// Temporarily changes the umask of the process; call it before other threads are started.
+///
+/// # Errors
+///
+/// This function will return an error if `path` exists and is not a socket, or if the socket
+/// cannot be removed, bound, or its mode cannot be set.
+pub fn bind_unix_socket<P: AsRef<Path>>(path: P, mode: u32) -> Result<UnixListener> {
+    let path = path.as_ref();
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("'{}' exists and is not a socket", path.display()),
+            ))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => (),
+        Err(e) => return Err(e),
+    }
+
+    // SAFETY: umask cannot fail and has no memory-safety requirements.
+    let old = unsafe { libc::umask(!mode & 0o777) };
+    let listener = UnixListener::bind(path);
+    // SAFETY: see above.
+    unsafe { libc::umask(old) };
+    let listener = listener?;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod test {
+    use std::os::unix::fs::MetadataExt;
+
+    use super::*;
+    use crate::TemporaryDirectory;
+
+    #[test]
+    fn bind() {
+        let dir = TemporaryDirectory::new().unwrap();
+        let path = dir.path().join("test.sock");
+
+        let listener = bind_unix_socket(&path, 0o600).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().mode() & 0o777, 0o600);
+        drop(listener);
+
+        // a stale socket is replaced
+        let _listener = bind_unix_socket(&path, 0o660).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().mode() & 0o777, 0o660);
+
+        // any other file is kept
+        let file = dir.path().join("file");
+        fs::write(&file, "data").unwrap();
+        let err = bind_unix_socket(&file, 0o600).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&file).unwrap(), b"data");
+    }
+}